@@ -1,19 +1,26 @@
 use std::{
     fmt,
     io::Read,
-    sync::{Arc, Mutex, PoisonError},
+    path::PathBuf,
+    rc::Rc,
+    cell::RefCell,
+    any::Any,
+    sync::{Arc, Mutex, PoisonError, mpsc::Receiver},
+    time::{Duration, Instant},
 };
 use glium::{SwapBuffersError, glutin::Event};
-use webrender::api::{RenderApi, HitTestFlags, DevicePixel};
+use webrender::api::{RenderApi, HitTestFlags, HitTestResult, DevicePixel, WorldPoint, PipelineId, BuiltDisplayList, LayoutSize};
 use image::ImageError;
 use euclid::{TypedScale, TypedSize2D};
 use {
-    images::ImageType,
+    FastHashMap, FastHashSet,
+    images::{ImageType, evict_lazy_images},
     errors::{FontError, ClipboardError},
-    window::{Window, WindowCreateOptions, WindowCreateError, WindowId},
+    font::FontLoadState,
+    window::{Window, WindowCreateOptions, WindowCreateError, WindowCreateRequest, WindowId, WindowEvent, SimulatedWindowOps, DragAndDrop, EmbeddedLayout},
     css_parser::{Font as FontId, PixelValue, FontSize},
     text_cache::TextId,
-    dom::UpdateScreen,
+    dom::{UpdateScreen, Callback, NodeData},
     window::FakeWindow,
     css::{Css, FakeCss},
     resources::AppResources,
@@ -21,6 +28,9 @@ use {
     traits::Layout,
     ui_state::UiState,
     ui_description::UiDescription,
+    inspector::DomInspectorResult,
+    id_tree::{Arena, NodeId},
+    widgets::get_drawer_bounds,
 };
 
 /// Graphical application that maintains some kind of application state
@@ -29,6 +39,52 @@ pub struct App<'a, T: Layout> {
     windows: Vec<Window<T>>,
     /// The global application state
     pub app_state: AppState<'a, T>,
+    /// Channels registered via `register_background_channel()`,
+    /// each function drains the messages of one channel
+    background_channels: Vec<Box<Fn() -> Vec<Rc<Any + Send>>>>,
+    /// Messages from the background channels, along with the windows whose
+    /// `Layout::layout()` function hasn't been called with the message yet
+    background_messages: Vec<(Rc<Any + Send>, Vec<WindowId>)>,
+    /// Whether the OS was in high contrast mode during the last check
+    high_contrast_mode: bool,
+    /// When the high contrast mode was last queried, see `HIGH_CONTRAST_CHECK_INTERVAL`
+    last_high_contrast_check: Instant,
+    /// Functions that are called on application-wide events, see `add_app_event_handler()`
+    app_event_handlers: Vec<fn(&mut AppState<T>, AppEvent) -> UpdateScreen>,
+    /// The DOMs of the last frame, kept between the calls to `run_one_frame()`
+    frame_caches: FrameCaches<T>,
+    /// Stacking order of the windows (bottom to top), see `set_window_order()`. The `windows`
+    /// stay in the order they were created in, so that the window indices don't change.
+    window_order: Vec<WindowId>,
+    /// Order from `set_window_order()`, applied at the start of the next frame
+    pending_window_order: Option<Vec<WindowId>>,
+    /// Position of the cursor on the screen during a drag-and-drop
+    drag_cursor_position: Option<(f64, f64)>,
+}
+
+/// Result of `App::run_one_frame()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AppRunResult {
+    /// At least one window is still open, `run_one_frame()` should be called again
+    Continue,
+    /// All windows have been closed
+    Exit,
+}
+
+/// Per-window state of the main loop, in the same order as `App::windows`
+struct FrameCaches<T: Layout> {
+    ui_state_cache: Vec<UiState<T>>,
+    ui_description_cache: Vec<UiDescription<T>>,
+    /// Number of frames that a window still has to be redrawn for (see the winit resize bug in `run_one_frame()`)
+    force_redraw_cache: Vec<usize>,
+}
+
+/// Application-wide events that don't belong to a single window
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AppEvent {
+    /// The high contrast mode of the OS was turned on (`true`) or off (`false`).
+    /// The `@media (forced-colors: active)` rules of all windows are toggled automatically.
+    HighContrastChanged(bool),
 }
 
 /// Error returned by the `.run()` function
@@ -56,7 +112,33 @@ impl<T: Layout> From<SwapBuffersError> for RuntimeError<T> {
 
 impl<T: Layout> fmt::Debug for RuntimeError<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+        use self::RuntimeError::*;
+        match self {
+            GlSwapError(e) => write!(f, "GlSwapError({:?})", e),
+            ArcUnlockError => write!(f, "ArcUnlockError"),
+            // T doesn't necessarily implement Debug
+            MutexPoisonError(_) => write!(f, "MutexPoisonError"),
+        }
+    }
+}
+
+impl<T: Layout> fmt::Display for RuntimeError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::RuntimeError::*;
+        match self {
+            GlSwapError(e) => write!(f, "Could not swap the display: {}", e),
+            ArcUnlockError => write!(f, "The app data is still referenced after all windows were closed"),
+            MutexPoisonError(_) => write!(f, "The app data mutex was poisoned"),
+        }
+    }
+}
+
+impl<T: Layout> ::std::error::Error for RuntimeError<T> {
+    fn source(&self) -> Option<&(::std::error::Error + 'static)> {
+        match self {
+            RuntimeError::GlSwapError(e) => Some(e),
+            _ => None,
+        }
     }
 }
 
@@ -68,6 +150,11 @@ pub(crate) struct FrameEventInfo {
     pub(crate) new_window_size: Option<(u32, u32)>,
     pub(crate) new_dpi_factor: Option<f32>,
     pub(crate) is_resize_event: bool,
+    pub(crate) is_move_event: bool,
+    pub(crate) is_scroll_event: bool,
+    /// Messages received from the background channels that haven't
+    /// been delivered to the layout function of this window yet
+    pub(crate) background_messages: Vec<Rc<Any + Send>>,
 }
 
 impl Default for FrameEventInfo {
@@ -80,6 +167,9 @@ impl Default for FrameEventInfo {
             new_window_size: None,
             new_dpi_factor: None,
             is_resize_event: false,
+            is_move_event: false,
+            is_scroll_event: false,
+            background_messages: Vec::new(),
         }
     }
 }
@@ -91,18 +181,221 @@ impl<'a, T: Layout> App<'a, T> {
         Self {
             windows: Vec::new(),
             app_state: AppState::new(initial_data),
+            background_channels: Vec::new(),
+            background_messages: Vec::new(),
+            high_contrast_mode: is_high_contrast_mode_active(),
+            last_high_contrast_check: Instant::now(),
+            app_event_handlers: Vec::new(),
+            frame_caches: FrameCaches {
+                ui_state_cache: Vec::new(),
+                ui_description_cache: Vec::new(),
+                force_redraw_cache: Vec::new(),
+            },
+            window_order: Vec::new(),
+            pending_window_order: None,
+            drag_cursor_position: None,
         }
     }
 
+    /// Returns whether the OS is currently in high contrast mode. Checked once per second.
+    ///
+    /// **NOTE**: On Linux, this only detects the GTK `HighContrast` themes (via the
+    /// `GTK_THEME` environment variable or the GNOME settings), on macOS it always
+    /// returns `false`. The system colors (`Canvas`, `ButtonText`, ...) always have
+    /// the values of the Windows "High Contrast Black" theme, they don't follow the
+    /// colors of the high contrast theme that the user has selected.
+    pub fn is_high_contrast_mode(&self) -> bool {
+        self.high_contrast_mode
+    }
+
+    /// Registers a function that is called on application-wide events,
+    /// i.e. when the high contrast mode of the OS changes
+    pub fn add_app_event_handler(&mut self, handler: fn(&mut AppState<T>, AppEvent) -> UpdateScreen) {
+        self.app_event_handlers.push(handler);
+    }
+
+    /// Registers the receiving end of a channel that background threads send their results to.
+    ///
+    /// Once per frame, all pending messages are received and handed to `Layout::layout()`,
+    /// via `WindowInfo::get_background_messages()`. All windows are redrawn when a
+    /// message arrives, so the DOM can be updated with the results. A message is kept
+    /// until the layout function of every window that was open when it arrived got it.
+    pub fn register_background_channel<M: Any + Send>(&mut self, receiver: Receiver<M>) {
+        self.background_channels.push(Box::new(move || {
+            receiver.try_iter().map(|message| Rc::new(message) as Rc<Any + Send>).collect()
+        }));
+    }
+
+    /// Returns a snapshot of the DOM of the window, for debugging layout problems:
+    /// every node along with its CSS classes, the CSS properties that apply to it
+    /// (after the cascade) and its position from the most recently solved layout.
+    ///
+    /// Note that this calls `Layout::layout()` and re-styles the DOM, without changing
+    /// the state of the window. Returns `None` if the window doesn't exist (anymore).
+    pub fn inspect_dom(&self, window_id: WindowId) -> Option<DomInspectorResult> {
+        use window::{ReadOnlyWindow, WindowInfo};
+
+        let idx = self.windows.iter().position(|w| w.id == window_id)?;
+        let window = &self.windows[idx];
+
+        let ui_state = UiState::from_app_state_isolated(&self.app_state, WindowInfo {
+            window_id: window_id,
+            window: ReadOnlyWindow {
+                inner: window.display.clone(),
+            },
+            open_drawers: self.app_state.windows[idx].open_drawers.clone(),
+            background_messages: Vec::new(),
+            monitor_id: window.current_monitor.clone(),
+            monitor_dpi: window.current_monitor_dpi,
+            is_printing: false,
+        });
+        let ui_description = UiDescription::from_ui_state(&ui_state, &window.css);
+
+        Some(DomInspectorResult::new(window_id, &ui_description, &window.solver))
+    }
+
+    /// Returns the constraints of the layout solver of the window, with the
+    /// variables named after their node (i.e. `"Node(42).width >= 100"`) and whether
+    /// the last solved layout fulfills them. Only available in debug builds.
+    /// Returns `None` if the window doesn't exist (anymore).
+    #[cfg(debug_assertions)]
+    pub fn debug_constraints(&self, window_id: WindowId) -> Option<Vec<::inspector::ConstraintDebugInfo>> {
+        let window = self.windows.iter().find(|w| w.id == window_id)?;
+        Some(window.solver.debug_constraints())
+    }
+
+    /// Lays out the DOM of the window for printing and renders it onto pages that are
+    /// `page_size` (width, height in pixels) large. Returns one PNG file per page.
+    ///
+    /// The DOM is styled with the `css`, with the `@media print { }` rules active (so that
+    /// i.e. toolbars can be hidden via `display: none`) and the margin of `@page { margin: 1in; }`
+    /// around each page. Drawers are printed open, other collapsed sections can be expanded
+    /// via `WindowInfo::is_printing()`. The pages end between the texts and images of the DOM,
+    /// instead of cutting through them.
+    ///
+    /// The pages are drawn with the renderer of the window (without being shown), so pages
+    /// that are larger than the window are scaled down to fit into the window. Returns `None`
+    /// if the window doesn't exist (anymore).
+    pub fn render_for_print(&mut self, window_id: WindowId, page_size: (f32, f32), css: &Css) -> Option<Vec<Vec<u8>>> {
+        use webrender::api::Transaction;
+        use window::{ReadOnlyWindow, WindowInfo, UiSolver};
+        use window_state::WindowSize;
+        use display_list::{DisplayList, get_unbreakable_boxes, get_page_breaks};
+        use css::CssMediaType;
+
+        let idx = self.windows.iter().position(|w| w.id == window_id)?;
+        let window = &mut self.windows[idx];
+
+        let ui_state = UiState::from_app_state_isolated(&self.app_state, WindowInfo {
+            window_id: window_id,
+            window: ReadOnlyWindow {
+                inner: window.display.clone(),
+            },
+            open_drawers: FastHashSet::default(),
+            background_messages: Vec::new(),
+            monitor_id: window.current_monitor.clone(),
+            monitor_dpi: window.current_monitor_dpi,
+            is_printing: true,
+        });
+
+        // The `:active` / `:focus` nodes of the window are not printed
+        let mut print_css = css.clone();
+        print_css.set_media_type(CssMediaType::Print);
+        print_css.set_active_node(None);
+        print_css.set_focused_node(None);
+        print_css.needs_relayout = true;
+
+        let margin = print_css.get_page_margin().unwrap_or(0.0);
+        let content_size = LayoutSize::new((page_size.0 - 2.0 * margin).max(1.0), (page_size.1 - 2.0 * margin).max(1.0));
+        let content_window_size = WindowSize {
+            width: content_size.width as u32,
+            height: content_size.height as u32,
+            hidpi_factor: 1.0,
+            min_dimensions: None,
+            max_dimensions: None,
+        };
+
+        let ui_description = UiDescription::from_ui_state(&ui_state, &print_css);
+        let display_list = DisplayList::new_from_ui_description(&ui_description);
+
+        // The content is laid out once and drawn into an extra pipeline,
+        // which is embedded into every page with a different offset
+        let content_pipeline_id = PipelineId(0, window.internal.next_embedded_pipeline_id);
+        window.internal.next_embedded_pipeline_id += 1;
+
+        let mut ui_solver = UiSolver::empty();
+        let builder = display_list.into_display_list_builder(
+            content_pipeline_id,
+            &mut ui_solver,
+            &mut print_css,
+            &mut self.app_state.resources,
+            &window.internal.api,
+            true,
+            &content_window_size,
+            window.get_text_aa_mode())?;
+        let content_display_list = builder.finalize().2;
+
+        let (unbreakable_boxes, content_height) = {
+            let arena = ui_description.ui_descr_arena.borrow();
+            get_unbreakable_boxes(&display_list.rectangles, &*arena, &ui_solver)
+        };
+        let content_height = content_height.max(content_size.height);
+        let page_starts = get_page_breaks(&unbreakable_boxes, content_height, content_size.height);
+
+        let mut pages = Vec::with_capacity(page_starts.len());
+        for (page_idx, page_start) in page_starts.iter().enumerate() {
+            let page_end = page_starts.get(page_idx + 1).cloned().unwrap_or(content_height);
+            let page = render_print_page(
+                window,
+                content_pipeline_id,
+                &content_display_list,
+                LayoutSize::new(content_size.width, content_height),
+                page_size,
+                margin,
+                (*page_start, page_end));
+            pages.push(page?);
+        }
+
+        // Draw the content of the window again, the pages were never shown
+        let mut txn = Transaction::new();
+        txn.remove_pipeline(content_pipeline_id);
+        window.internal.api.send_transaction(window.internal.document_id, txn);
+        send_display_list(window);
+
+        Some(pages)
+    }
+
     /// Spawn a new window on the screen. If an application has no windows,
     /// the [`run`](#method.run) function will exit immediately.
     pub fn create_window(&mut self, options: WindowCreateOptions, css: Css) -> Result<(), WindowCreateError> {
-        let window = Window::new(options, css)?;
+        self.create_window_with_id(WindowId::next(), options, css)
+    }
+
+    fn create_window_with_id(&mut self, id: WindowId, options: WindowCreateOptions, css: Css) -> Result<(), WindowCreateError> {
+        let enable_wr_capture = options.enable_wr_capture;
+        let mut window = Window::with_id(id, options, css)?;
+        window.css.set_forced_colors(self.high_contrast_mode);
         self.app_state.windows.push(FakeWindow {
             state: window.state.clone(),
             css: FakeCss::default(),
-            read_only_window: window.display.clone(),
+            read_only_window: Some(window.display.clone()),
+            open_drawers: FastHashSet::default(),
+            wr_capture_enabled: enable_wr_capture,
+            pending_wr_capture: None,
+            window_id: window.id,
+            pending_scrolls: Vec::new(),
+            pending_scrolls_into_view: Vec::new(),
+            pending_scroll_animations: Vec::new(),
+            pending_css: None,
+            pending_new_windows: Vec::new(),
+            focused_node: None,
+            char_rects: Rc::new(FastHashMap::default()),
+            char_rect_queries: RefCell::new(FastHashSet::default()),
+            user_select: Rc::new(FastHashMap::default()),
+            drawer_bounds: Vec::new(),
+            css_ids: Rc::new(FastHashMap::default()),
         });
+        self.app_state.window_registry.register(window.id, &window.display);
         self.windows.push(window);
         Ok(())
     }
@@ -137,122 +430,479 @@ impl<'a, T: Layout> App<'a, T> {
     }
 
     fn run_inner(&mut self) -> Result<(), RuntimeError<T>> {
-        use std::{thread, time::{Duration, Instant}};
+        use std::thread;
+
+        loop {
+            let time_start = Instant::now();
+
+            if self.run_one_frame()? == AppRunResult::Exit {
+                break;
+            }
+
+            // Wait until 16ms have passed
+            let diff = time_start.elapsed();
+            const FRAME_TIME: Duration = Duration::from_millis(16);
+            if diff < FRAME_TIME {
+                thread::sleep(FRAME_TIME - diff);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs exactly one iteration of the main loop: processes the pending events of all
+    /// windows, calls the callbacks, re-layouts and renders the windows that need it, then
+    /// runs the deamons and cleans up finished tasks. Unlike `run()`, this doesn't block,
+    /// so azul can be embedded in an existing event loop (i.e. a game loop):
+    ///
+    /// ```no_run,ignore
+    /// while app.run_one_frame()? == AppRunResult::Continue {
+    ///     update_game_world();
+    /// }
+    /// ```
+    ///
+    /// Windows that were created since the last call are drawn for the first time.
+    /// The caller is responsible for the frame timing - `run()` calls this function
+    /// at most every 16ms. Returns `AppRunResult::Exit` once all windows are closed.
+    pub fn run_one_frame(&mut self) -> Result<AppRunResult, RuntimeError<T>> {
         use window::{ReadOnlyWindow, WindowInfo};
 
-        let mut ui_state_cache = Self::initialize_ui_state(&self.windows, &self.app_state);
-        let mut ui_description_cache = Self::do_first_redraw(&mut self.windows, &mut self.app_state, &ui_state_cache);
+        // First repaint of new windows, otherwise they would be black on startup
+        self.initialize_new_windows();
+
+        if self.pending_window_order.is_some() {
+            self.apply_window_order();
+        }
 
-        let mut force_redraw_cache = vec![0_usize; self.windows.len()];
+        if self.windows.is_empty() {
+            return Ok(AppRunResult::Exit);
+        }
 
-        while !self.windows.is_empty() {
+        let mut closed_windows = Vec::<usize>::new();
+        let mut is_drag_released = false;
+        let mut is_any_window_relayouted = false;
 
-            let time_start = Instant::now();
-            let mut closed_windows = Vec::<usize>::new();
+        // New messages are delivered to all windows that are open right now
+        let open_window_ids = self.windows.iter().map(|window| window.id).collect::<Vec<_>>();
+        for drain_channel in &self.background_channels {
+            self.background_messages.extend(drain_channel().into_iter().map(|message| (message, open_window_ids.clone())));
+        }
+
+        let high_contrast_changed = self.update_high_contrast_mode();
+
+        // Fonts that were loaded on a background thread replace their fallback fonts
+        let fonts_changed = self.app_state.resources.add_loaded_fonts();
 
-            'window_loop: for (idx, ref mut window) in self.windows.iter_mut().enumerate() {
+        let FrameCaches {
+            ref mut ui_state_cache,
+            ref mut ui_description_cache,
+            ref mut force_redraw_cache,
+        } = self.frame_caches;
+
+        'window_loop: for (idx, ref mut window) in self.windows.iter_mut().enumerate() {
 /*
-                unsafe {
-                    use glium::glutin::GlContext;
-                    window.display.gl_window().make_current().unwrap();
-                }
+            unsafe {
+                use glium::glutin::GlContext;
+                window.display.gl_window().make_current().unwrap();
+            }
 */
-                let window_id = WindowId { id: idx };
-                let mut frame_event_info = FrameEventInfo::default();
+            let window_id = window.id;
+            let mut frame_event_info = FrameEventInfo::default();
+            frame_event_info.background_messages = self.background_messages.iter()
+                .filter(|&&(_, ref window_ids)| window_ids.contains(&window_id))
+                .map(|&(ref message, _)| message.clone())
+                .collect();
+
+            let mut events = Vec::new();
+            window.events_loop.poll_events(|e| events.push(e));
+
+            // Events that other windows sent to this window via `send_event_to_window()`
+            let (sent_events, other_events): (Vec<_>, Vec<_>) = self.app_state.pending_window_events
+                .drain(..).partition(|&(target_id, _)| target_id == window_id);
+            self.app_state.pending_window_events = other_events;
+            let glutin_window_id = window.display.gl_window().id();
+            events.extend(sent_events.into_iter().map(|(_, event)| Event::WindowEvent {
+                window_id: glutin_window_id,
+                event: event,
+            }));
+
+            for event in &events {
+                if preprocess_event(event, &mut frame_event_info) == WindowCloseEvent::AboutToClose {
+                    closed_windows.push(idx);
+                    continue 'window_loop;
+                }
+                window.state.update_mouse_cursor_position(event);
+                if self.app_state.drag_and_drop.is_some() {
+                    // The window where the drag started usually gets all mouse events until the
+                    // button is released, so the target window is found via the screen position
+                    if let Some(position) = window.get_screen_cursor_position() {
+                        self.drag_cursor_position = Some(position);
+                    }
+                    is_drag_released |= is_left_mouse_release(event);
+                }
+            }
 
-                let mut events = Vec::new();
-                window.events_loop.poll_events(|e| events.push(e));
+            // Dragging the handle of a `resize` container changes its size
+            for event in &events {
+                if window.update_resize_drag(event, &ui_description_cache[idx]) {
+                    frame_event_info.should_redraw_window = true;
+                }
+            }
 
+            if frame_event_info.should_hittest {
                 for event in &events {
-                    if preprocess_event(event, &mut frame_event_info) == WindowCloseEvent::AboutToClose {
-                        closed_windows.push(idx);
-                        continue 'window_loop;
-                    }
-                    window.state.update_mouse_cursor_position(event);
+                    do_hit_test_and_call_callbacks(
+                        event,
+                        window,
+                        idx,
+                        &mut frame_event_info,
+                        &ui_state_cache,
+                        &ui_description_cache[idx],
+                        &mut self.app_state);
                 }
+            }
 
-                if frame_event_info.should_hittest {
-                    for event in &events {
-                        do_hit_test_and_call_callbacks(
-                            event,
-                            window,
-                            window_id,
-                            &mut frame_event_info,
-                            &ui_state_cache,
-                            &mut self.app_state);
+            if frame_event_info.should_swap_window || frame_event_info.is_resize_event {
+                window.display.swap_buffers()?;
+                if let Some(i) = force_redraw_cache.get_mut(idx) {
+                    if *i > 0 { *i -= 1 };
+                    if *i == 0 {
+                        use compositor::{TO_DELETE_TEXTURES, ACTIVE_GL_TEXTURES};
+                        let mut to_delete_lock = TO_DELETE_TEXTURES.lock().unwrap();
+                        let mut active_textures_lock = ACTIVE_GL_TEXTURES.lock().unwrap();
+                        to_delete_lock.drain().for_each(|tex| { active_textures_lock.remove(&tex); });
                     }
                 }
+            }
 
-                if frame_event_info.should_swap_window || frame_event_info.is_resize_event {
-                    window.display.swap_buffers()?;
-                    if let Some(i) = force_redraw_cache.get_mut(idx) {
-                        if *i > 0 { *i -= 1 };
-                        if *i == 0 {
-                            use compositor::{TO_DELETE_TEXTURES, ACTIVE_GL_TEXTURES};
-                            let mut to_delete_lock = TO_DELETE_TEXTURES.lock().unwrap();
-                            let mut active_textures_lock = ACTIVE_GL_TEXTURES.lock().unwrap();
-                            to_delete_lock.drain().for_each(|tex| { active_textures_lock.remove(&tex); });
-                        }
-                    }
-                }
+            if frame_event_info.is_resize_event || frame_event_info.should_redraw_window {
+                // This is a hack because during a resize event, winit eats the "awakened"
+                // event. So what we do is that we call the layout-and-render again, to
+                // trigger a second "awakened" event. So when the window is resized, the
+                // layout function is called twice (the first event will be eaten by winit)
+                //
+                // This is a reported bug and should be fixed somewhere in July
+                force_redraw_cache[idx] = 2;
+            }
+
+            // Update the window state that we got from the frame event (updates window dimensions and DPI)
+            window.update_from_external_window_state(&mut frame_event_info);
+            let background_color_changed =
+                window.state.background_color != self.app_state.windows[idx].state.background_color;
+            // The text has to be drawn again with the new anti-aliasing
+            if window.state.text_aa_mode != self.app_state.windows[idx].state.text_aa_mode {
+                frame_event_info.should_redraw_window = true;
+            }
+            // Update the window state every frame that was set by the user
+            window.update_from_user_window_state(self.app_state.windows[idx].state.clone());
+            // The mouse wheel scrolls the container under the cursor
+            let mut scroll_offsets_changed = window.scroll_hovered_container(&ui_description_cache[idx]);
+            // Snap the `scroll-snap-type` containers once the user has stopped scrolling
+            window.snap_scroll_containers(frame_event_info.is_scroll_event, &ui_description_cache[idx]);
+            // Reset the scroll amount to 0 (for the next frame)
+            window.clear_scroll_state();
+
+            // Programmatic scrolling, instant or animated depending on the `scroll-behavior`
+            // of the scroll container - scrolling with the mouse wheel is always instant
+            for (node_id, scroll_x, scroll_y) in self.app_state.windows[idx].pending_scrolls.drain(..) {
+                let behavior = ui_description_cache[idx].get_scroll_behavior(node_id);
+                window.start_scroll(node_id, scroll_x, scroll_y, behavior);
+                scroll_offsets_changed = true;
+            }
+            for node_id in self.app_state.windows[idx].pending_scrolls_into_view.drain(..) {
+                scroll_offsets_changed |= window.scroll_node_into_view(node_id, &ui_description_cache[idx]);
+            }
+            for (node_id, target, duration) in self.app_state.windows[idx].pending_scroll_animations.drain(..) {
+                window.animate_scroll(node_id, target, duration);
+            }
+            scroll_offsets_changed |= window.advance_scroll_animations();
+
+            // Theme switching, see `FakeWindow::switch_css()`
+            if let Some(new_css) = self.app_state.windows[idx].pending_css.take() {
+                window.switch_css(new_css);
+                frame_event_info.should_redraw_window = true;
+            }
 
-                if frame_event_info.is_resize_event || frame_event_info.should_redraw_window {
-                    // This is a hack because during a resize event, winit eats the "awakened"
-                    // event. So what we do is that we call the layout-and-render again, to
-                    // trigger a second "awakened" event. So when the window is resized, the
-                    // layout function is called twice (the first event will be eaten by winit)
-                    //
-                    // This is a reported bug and should be fixed somewhere in July
-                    force_redraw_cache[idx] = 2;
+            // Animated images need a redraw whenever they advance to the next frame,
+            // lazy images need a re-layout once they have finished loading
+            if ui_state_cache[idx].dom.has_new_animation_frame() {
+                frame_event_info.should_redraw_window = true;
+            }
+
+            // The results of background threads have to be shown by the layout() function
+            if !frame_event_info.background_messages.is_empty() || high_contrast_changed || fonts_changed {
+                frame_event_info.should_redraw_window = true;
+            }
+
+            // The character bounds of the nodes that were queried for the first time
+            // are only laid out on the next layout, see `FakeWindow::get_char_bounds()`
+            for node_id in self.app_state.windows[idx].char_rect_queries.borrow_mut().drain() {
+                if window.solver.solved_layout.char_rect_queries.insert(node_id) {
+                    frame_event_info.should_redraw_window = true;
                 }
+            }
 
-                // Update the window state that we got from the frame event (updates window dimensions and DPI)
-                window.update_from_external_window_state(&mut frame_event_info);
-                // Update the window state every frame that was set by the user
-                window.update_from_user_window_state(self.app_state.windows[idx].state.clone());
-                // Reset the scroll amount to 0 (for the next frame)
-                window.clear_scroll_state();
-
-                if frame_event_info.should_redraw_window || force_redraw_cache[idx] > 0 {
-                    // Call the Layout::layout() fn, get the DOM
-                    ui_state_cache[idx] = UiState::from_app_state(&self.app_state, WindowInfo {
-                        window_id: WindowId { id: idx },
-                        window: ReadOnlyWindow {
-                            inner: window.display.clone(),
-                        }
-                    });
-                    // Style the DOM
-                    ui_description_cache[idx] = UiDescription::from_ui_state(&ui_state_cache[idx], &mut window.css);
-                    // send webrender the size and buffer of the display
-                    Self::update_display(&window);
-                    // render the window (webrender will send an Awakened event when the frame is done)
-                    render(window, &WindowId { id: idx }, &ui_description_cache[idx], &mut self.app_state.resources, true);
+            if frame_event_info.should_redraw_window || force_redraw_cache[idx] > 0 {
+                // Call the Layout::layout() fn, get the DOM
+                ui_state_cache[idx] = UiState::from_app_state(&self.app_state, WindowInfo {
+                    window_id: window_id,
+                    window: ReadOnlyWindow {
+                        inner: window.display.clone(),
+                    },
+                    open_drawers: self.app_state.windows[idx].open_drawers.clone(),
+                    background_messages: frame_event_info.background_messages.clone(),
+                    monitor_id: window.current_monitor.clone(),
+                    monitor_dpi: window.current_monitor_dpi,
+                    is_printing: false,
+                });
+                // Style the DOM
+                ui_description_cache[idx] = UiDescription::from_ui_state(&ui_state_cache[idx], &mut window.css);
+                // send webrender the size and buffer of the display
+                Self::update_display(&window);
+                update_embedded_pipelines(window, idx, &mut self.app_state, frame_event_info.background_messages.clone());
+                // render the window (webrender will send an Awakened event when the frame is done)
+                render(window, &window_id, &ui_description_cache[idx], &mut self.app_state.resources, true);
+                self.app_state.windows[idx].char_rects = window.solver.solved_layout.char_rects.clone();
+                self.app_state.windows[idx].user_select = window.solver.solved_layout.user_select.clone();
+                self.app_state.windows[idx].drawer_bounds = get_drawer_bounds(&ui_state_cache[idx], &window.solver);
+                self.app_state.windows[idx].css_ids = Rc::new(ui_state_cache[idx].dom.get_css_ids());
+                is_any_window_relayouted = true;
+                // The layout function got the messages, so they aren't delivered again
+                for &mut (_, ref mut window_ids) in self.background_messages.iter_mut() {
+                    window_ids.retain(|id| *id != window_id);
                 }
+            } else if background_color_changed || scroll_offsets_changed {
+                // Only the background or the scroll offsets changed - no need to re-layout,
+                // just re-send the last display list (with the new scroll offsets)
+                send_display_list(window);
             }
 
-            // Close windows if necessary
-            closed_windows.into_iter().for_each(|closed_window_id| {
-                ui_state_cache.remove(closed_window_id);
-                ui_description_cache.remove(closed_window_id);
-                force_redraw_cache.remove(closed_window_id);
-                self.windows.remove(closed_window_id);
-            });
+            // Lazy images start loading once they are scrolled near the visible area
+            window.start_loading_visible_lazy_images(&ui_state_cache[idx].dom);
 
-            // Run deamons and remove them from the even queue if they are finished
-            self.app_state.run_all_deamons();
+            if let Some(capture_dir) = self.app_state.windows[idx].pending_wr_capture.take() {
+                use webrender::api::CaptureBits;
+                window.internal.api.save_capture(capture_dir, CaptureBits::all());
+            }
+        }
 
-            // Clean up finished tasks, remove them if possible
-            self.app_state.clean_up_finished_tasks();
+        if self.app_state.drag_and_drop.is_some() {
+            self.update_drag_and_drop(is_drag_released);
+        }
 
-            // Wait until 16ms have passed
-            let diff = time_start.elapsed();
-            const FRAME_TIME: Duration = Duration::from_millis(16);
-            if diff < FRAME_TIME {
-                thread::sleep(FRAME_TIME - diff);
+        // Lazy images that were removed from all DOMs (e.g. scrolled out of a virtual list)
+        // can be dropped from the image cache
+        if is_any_window_relayouted {
+            let lazy_image_urls = ui_state_cache.iter()
+                .flat_map(|ui_state| ui_state.dom.get_lazy_image_urls())
+                .collect::<FastHashSet<String>>();
+            evict_lazy_images(&lazy_image_urls);
+        }
+
+        // Windows that were opened by the callbacks (also by the windows that are closed now)
+        let new_windows = self.app_state.windows.iter_mut()
+            .flat_map(|fake_window| fake_window.pending_new_windows.drain(..))
+            .collect::<Vec<_>>();
+
+        // Close windows if necessary
+        // (in reverse order, so that the indices of the other closed windows stay valid)
+        for closed_window_idx in closed_windows.into_iter().rev() {
+            ui_state_cache.remove(closed_window_idx);
+            ui_description_cache.remove(closed_window_idx);
+            force_redraw_cache.remove(closed_window_idx);
+            let closed_window = self.windows.remove(closed_window_idx);
+            self.app_state.windows.remove(closed_window_idx);
+            self.app_state.window_registry.remove(closed_window.id);
+            for &mut (_, ref mut window_ids) in self.background_messages.iter_mut() {
+                window_ids.retain(|id| *id != closed_window.id);
             }
         }
+        self.background_messages.retain(|&(_, ref window_ids)| !window_ids.is_empty());
+        self.app_state.window_registry.remove_closed_windows();
 
-        Ok(())
+        // The new windows are drawn for the first time on the next frame
+        for WindowCreateRequest { id, options, css } in new_windows {
+            if let Err(e) = self.create_window_with_id(id, options, css) {
+                error!("could not open the window {:?}: {:?}", id, e);
+            }
+        }
+
+        // Run deamons and remove them from the even queue if they are finished
+        self.app_state.run_all_deamons();
+
+        // Clean up finished tasks, remove them if possible
+        self.app_state.clean_up_finished_tasks();
+
+        if self.windows.is_empty() {
+            Ok(AppRunResult::Exit)
+        } else {
+            Ok(AppRunResult::Continue)
+        }
+    }
+
+    /// Highlights the drop targets of the window under the cursor (see `:-azul-drop-target`)
+    /// and calls the `On::Drop` callback of the frontmost drop target once the drag is released
+    fn update_drag_and_drop(&mut self, is_drag_released: bool) {
+
+        let window_order = self.get_window_order();
+        let hovered_window = self.drag_cursor_position.and_then(|position| get_window_at_screen_position(&self.windows, &window_order, position));
+
+        for (idx, window) in self.windows.iter_mut().enumerate() {
+            let was_drag_over = window.css.is_drag_over;
+            window.css.set_drag_over(hovered_window == Some(idx) && !is_drag_released);
+            if window.css.is_drag_over != was_drag_over {
+                self.frame_caches.force_redraw_cache[idx] = self.frame_caches.force_redraw_cache[idx].max(1);
+            }
+        }
+
+        if !is_drag_released {
+            return;
+        }
+
+        let drag = self.app_state.drag_and_drop.take();
+        let screen_position = self.drag_cursor_position.take();
+
+        if let (Some(drag), Some(idx), Some(screen_position)) = (drag, hovered_window, screen_position) {
+            let FrameCaches { ref ui_state_cache, ref mut force_redraw_cache, .. } = self.frame_caches;
+            let window = &mut self.windows[idx];
+            if call_drop_callback(window, idx, drag, screen_position, ui_state_cache, &mut self.app_state) == UpdateScreen::Redraw {
+                force_redraw_cache[idx] = force_redraw_cache[idx].max(1);
+            }
+        }
+    }
+
+    /// Registers a new webrender pipeline in the window, which can be embedded into the DOM
+    /// of the window via `Dom::iframe(pipeline_id, width, height)`. The content of the
+    /// pipeline is the DOM returned by `layout`, which is called whenever the window is
+    /// laid out. Returns `None` if the window doesn't exist.
+    ///
+    /// **NOTE**: Only the window itself is hit-tested, the callbacks in the DOM of
+    /// the embedded pipeline are never called.
+    pub fn add_embedded_pipeline(&mut self, window_id: WindowId, layout: EmbeddedLayout<T>) -> Option<PipelineId> {
+        self.windows.iter_mut().find(|w| w.id == window_id).map(|w| w.add_embedded_pipeline(layout))
+    }
+
+    /// Removes an embedded pipeline, `Dom::iframe()` nodes that still refer
+    /// to the pipeline will show up empty
+    pub fn remove_embedded_pipeline(&mut self, window_id: WindowId, pipeline_id: PipelineId) {
+        if let Some(window) = self.windows.iter_mut().find(|w| w.id == window_id) {
+            window.remove_embedded_pipeline(&pipeline_id);
+        }
+    }
+
+    /// Re-orders the windows, so that the windows later in the `order` appear on top of
+    /// the windows earlier in the `order` (at the start of the next frame). Windows that
+    /// aren't in the `order` keep their relative order, below the ordered windows.
+    /// Unknown window IDs are ignored. The indices of the windows (i.e. `WindowEvent::window`)
+    /// don't change, only their stacking order.
+    ///
+    /// **NOTE**: winit has no API for the stacking order, so the ordered windows are shown
+    /// again from the bottom to the top window. This raises the windows on X11 and macOS,
+    /// but not necessarily on Windows and Wayland. Invisible windows aren't raised, since
+    /// showing them would make them visible again.
+    pub fn set_window_order(&mut self, order: Vec<WindowId>) {
+        self.pending_window_order = Some(order);
+    }
+
+    /// Moves the window on top of all other windows, see `set_window_order()`
+    pub fn raise_window(&mut self, id: WindowId) {
+        let mut order = self.get_window_order();
+        order.retain(|w| *w != id);
+        order.push(id);
+        self.set_window_order(order);
+    }
+
+    /// Moves the window below all other windows, see `set_window_order()`
+    pub fn lower_window(&mut self, id: WindowId) {
+        let mut order = self.get_window_order();
+        order.retain(|w| *w != id);
+        order.insert(0, id);
+        self.set_window_order(order);
+    }
+
+    /// The current order of the windows (including a pending `set_window_order()`), bottom to top.
+    /// Windows that were opened after the last re-ordering are on top of the ordered windows.
+    fn get_window_order(&self) -> Vec<WindowId> {
+        let mut window_ids = self.window_order.iter()
+            .filter(|id| self.windows.iter().any(|w| w.id == **id))
+            .cloned()
+            .collect::<Vec<WindowId>>();
+        for window in &self.windows {
+            if !window_ids.contains(&window.id) {
+                window_ids.push(window.id);
+            }
+        }
+
+        match self.pending_window_order {
+            Some(ref pending) => get_window_permutation(&window_ids, pending).into_iter().map(|idx| window_ids[idx]).collect(),
+            None => window_ids,
+        }
+    }
+
+    /// Applies the pending `set_window_order()` and raises the ordered windows (bottom to top)
+    fn apply_window_order(&mut self) {
+        self.window_order = self.get_window_order();
+        let raised_windows = self.pending_window_order.take().unwrap_or_default();
+
+        for id in self.window_order.iter().filter(|id| raised_windows.contains(id)) {
+            if let Some(window) = self.windows.iter().find(|w| w.id == *id && w.state.is_visible) {
+                window.display.gl_window().show();
+            }
+        }
+    }
+
+    /// Lays out and draws the windows that were created since the last frame
+    /// (on the first frame: all windows) and adds them to the `frame_caches`
+    fn initialize_new_windows(&mut self) {
+        let first_new_window = self.frame_caches.ui_state_cache.len();
+        if first_new_window >= self.windows.len() {
+            return;
+        }
+
+        let new_ui_states = Self::initialize_ui_state(&self.windows, first_new_window, &self.app_state);
+
+        // Focus the `initial_focus` (if it exists in the DOM) or the first focusable node
+        let new_windows = self.windows[first_new_window..].iter_mut().zip(self.app_state.windows[first_new_window..].iter_mut());
+        for ((window, fake_window), ui_state) in new_windows.zip(new_ui_states.iter()) {
+            fake_window.focused_node = window.initial_focus.as_ref()
+                .and_then(|id| ui_state.dom.get_css_ids().get(id).cloned())
+                .or_else(|| ui_state.dom.get_first_focusable_node());
+            window.css.set_focused_node(fake_window.focused_node);
+        }
+
+        let new_ui_descriptions = Self::do_first_redraw(&mut self.windows, first_new_window, &mut self.app_state, &new_ui_states);
+
+        let new_window_count = new_ui_states.len();
+        self.frame_caches.ui_state_cache.extend(new_ui_states);
+        self.frame_caches.ui_description_cache.extend(new_ui_descriptions);
+        self.frame_caches.force_redraw_cache.extend(vec![0_usize; new_window_count]);
+    }
+
+    /// Checks if the OS has entered or left the high contrast mode, toggles the
+    /// forced colors in the CSS of all windows and notifies the app event handlers.
+    /// Returns whether the mode has changed.
+    fn update_high_contrast_mode(&mut self) -> bool {
+        // Querying the settings may spawn a process (on Linux), so it isn't done every frame
+        if self.last_high_contrast_check.elapsed() < HIGH_CONTRAST_CHECK_INTERVAL {
+            return false;
+        }
+        self.last_high_contrast_check = Instant::now();
+
+        let high_contrast_mode = is_high_contrast_mode_active();
+        if high_contrast_mode == self.high_contrast_mode {
+            return false;
+        }
+
+        self.high_contrast_mode = high_contrast_mode;
+        for window in self.windows.iter_mut() {
+            window.css.set_forced_colors(high_contrast_mode);
+        }
+        for handler in &self.app_event_handlers {
+            // all windows are redrawn anyway, since the colors have changed
+            let _ = (handler)(&mut self.app_state, AppEvent::HighContrastChanged(high_contrast_mode));
+        }
+
+        true
     }
 
     fn update_display(window: &Window<T>)
@@ -268,33 +918,47 @@ impl<'a, T: Layout> App<'a, T> {
         window.internal.api.send_transaction(window.internal.document_id, txn);
     }
 
-    fn initialize_ui_state(windows: &[Window<T>], app_state: &AppState<'a, T>)
+    /// Calls the layout function of the windows, starting at the window with the index `first_window_idx`
+    fn initialize_ui_state(windows: &[Window<T>], first_window_idx: usize, app_state: &AppState<'a, T>)
     -> Vec<UiState<T>>
     {
         use window::{ReadOnlyWindow, WindowInfo};
 
-        windows.iter().enumerate().map(|(idx, w)|
+        windows.iter().enumerate().skip(first_window_idx).map(|(idx, w)|
             UiState::from_app_state(app_state, WindowInfo {
-                window_id: WindowId { id: idx },
+                window_id: w.id,
                 window: ReadOnlyWindow {
                     inner: w.display.clone(),
-                }
+                },
+                open_drawers: app_state.windows[idx].open_drawers.clone(),
+                background_messages: Vec::new(),
+                monitor_id: w.current_monitor.clone(),
+                monitor_dpi: w.current_monitor_dpi,
+                is_printing: false,
             })
         ).collect()
     }
 
-    /// First repaint, otherwise the window would be black on startup
+    /// First repaint, otherwise the window would be black on startup.
+    /// The `ui_state_cache` contains the UI states of the windows, starting at `first_window_idx`
     fn do_first_redraw(
         windows: &mut [Window<T>],
+        first_window_idx: usize,
         app_state: &mut AppState<'a, T>,
         ui_state_cache: &[UiState<T>])
     -> Vec<UiDescription<T>>
     {
-        let mut ui_description_cache = vec![UiDescription::default(); windows.len()];
+        let mut ui_description_cache = vec![UiDescription::default(); ui_state_cache.len()];
 
-        for (idx, window) in windows.iter_mut().enumerate() {
+        for (idx, window) in windows[first_window_idx..].iter_mut().enumerate() {
             ui_description_cache[idx] = UiDescription::from_ui_state(&ui_state_cache[idx], &mut window.css);
-            render(window, &WindowId { id: idx, }, &ui_description_cache[idx], &mut app_state.resources, true);
+            let window_id = window.id;
+            update_embedded_pipelines(window, first_window_idx + idx, app_state, Rc::new(Vec::new()));
+            render(window, &window_id, &ui_description_cache[idx], &mut app_state.resources, true);
+            app_state.windows[first_window_idx + idx].char_rects = window.solver.solved_layout.char_rects.clone();
+            app_state.windows[first_window_idx + idx].user_select = window.solver.solved_layout.user_select.clone();
+            app_state.windows[first_window_idx + idx].drawer_bounds = get_drawer_bounds(&ui_state_cache[idx], &window.solver);
+            app_state.windows[first_window_idx + idx].css_ids = Rc::new(ui_state_cache[idx].dom.get_css_ids());
         }
 
         ui_description_cache
@@ -342,6 +1006,17 @@ impl<'a, T: Layout> App<'a, T> {
         self.app_state.add_font(id, data)
     }
 
+    /// Loads a font from a file on a background thread, see `AppState::add_font_from_file()`
+    pub fn add_font_from_file<S: Into<String>, P: Into<PathBuf>>(&mut self, id: S, path: P) {
+        self.app_state.add_font_from_file(id, path)
+    }
+
+    /// Returns the load state of a font added via `add_font_from_file()`,
+    /// see `AppState::get_font_load_state()`
+    pub fn get_font_load_state(&self, id: &str) -> Option<FontLoadState> {
+        self.app_state.get_font_load_state(id)
+    }
+
     /// Checks if a font is currently registered and ready-to-use
     pub fn has_font<S: Into<String>>(&mut self, id: S)
         -> bool
@@ -449,6 +1124,38 @@ impl<'a, T: Layout> App<'a, T> {
         self.app_state.set_clipboard_string(contents)
     }
 
+    /// Invokes the callback directly, without a real window - for unit-testing the
+    /// logic of callbacks. No OS or GPU resources are needed.
+    ///
+    /// While the callback runs, `app_state.windows` only contains simulated windows
+    /// (as many as necessary so that `app_state.windows[event.window]` doesn't panic).
+    /// Calling `FakeWindow::get_window()` on a simulated window panics. Afterwards,
+    /// the changes that the callback made to `app_state.windows[event.window]` are
+    /// returned as a `SimulatedWindowOps`, the real windows are left untouched.
+    ///
+    /// ```no_run,ignore
+    /// let mut app = App::new(MyState { clicked: false });
+    /// let (update_screen, ops) = app.simulate_callback(WindowEvent::mock(), Callback(on_button_click));
+    /// assert!(ops.redraw_requested);
+    /// assert!(app.app_state.data.lock().unwrap().clicked);
+    /// ```
+    pub fn simulate_callback(&mut self, event: WindowEvent, callback: Callback<T>)
+    -> (UpdateScreen, SimulatedWindowOps)
+    {
+        use std::mem;
+
+        let simulated_windows = (0..event.window + 1).map(|_| FakeWindow::new_simulated()).collect();
+        let real_windows = mem::replace(&mut self.app_state.windows, simulated_windows);
+
+        let Callback(callback_func) = callback;
+        let update_screen = (callback_func)(&mut self.app_state, event);
+
+        let simulated_windows = mem::replace(&mut self.app_state.windows, real_windows);
+        let ops = SimulatedWindowOps::new(&simulated_windows[event.window], update_screen);
+
+        (update_screen, ops)
+    }
+
     /// Mock rendering function, for creating a hidden window and rendering one frame
     /// Used in unit tests. You **have** to enable software rendering, otherwise,
     /// this function won't work in a headless environment.
@@ -471,8 +1178,8 @@ impl<'a, T: Layout> App<'a, T> {
             .. Default::default()
         };
         self.create_window(hidden_create_options, Css::native()).unwrap();
-        let ui_state_cache = Self::initialize_ui_state(&self.windows, &self.app_state);
-        Self::do_first_redraw(&mut self.windows, &mut self.app_state, &ui_state_cache);
+        let ui_state_cache = Self::initialize_ui_state(&self.windows, 0, &self.app_state);
+        Self::do_first_redraw(&mut self.windows, 0, &mut self.app_state, &ui_state_cache);
     }
 }
 
@@ -490,6 +1197,48 @@ enum WindowCloseEvent {
     NoCloseEvent,
 }
 
+/// How often `App::run_one_frame()` queries the OS whether the high contrast mode has changed
+const HIGH_CONTRAST_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Queries the OS whether the high contrast mode is currently turned on
+#[cfg(target_os = "windows")]
+fn is_high_contrast_mode_active() -> bool {
+    use std::mem;
+    use winapi::um::winuser::{SystemParametersInfoW, HIGHCONTRASTW, SPI_GETHIGHCONTRAST, HCF_HIGHCONTRASTON};
+
+    let mut high_contrast: HIGHCONTRASTW = unsafe { mem::zeroed() };
+    high_contrast.cbSize = mem::size_of::<HIGHCONTRASTW>() as u32;
+    let success = unsafe {
+        SystemParametersInfoW(SPI_GETHIGHCONTRAST, high_contrast.cbSize, &mut high_contrast as *mut _ as *mut _, 0)
+    };
+    success != 0 && (high_contrast.dwFlags & HCF_HIGHCONTRASTON) != 0
+}
+
+/// Queries the OS whether the high contrast mode is currently turned on
+///
+/// The `GTK_THEME` environment variable overrides the theme of the desktop. Otherwise the
+/// GTK theme is read from the GNOME settings, since the user can change it at runtime.
+#[cfg(target_os = "linux")]
+fn is_high_contrast_mode_active() -> bool {
+    use std::process::Command;
+
+    let theme = ::std::env::var("GTK_THEME").ok().or_else(|| {
+        let output = Command::new("gsettings").args(&["get", "org.gnome.desktop.interface", "gtk-theme"]).output().ok()?;
+        // the theme is printed in single quotes, i.e. `'HighContrast'`
+        Some(String::from_utf8_lossy(&output.stdout).trim().trim_matches('\'').to_string())
+    });
+
+    // "HighContrast" and "HighContrastInverse" are the high contrast themes of GTK
+    theme.map(|theme| theme.starts_with("HighContrast")).unwrap_or(false)
+}
+
+/// Queries the OS whether the high contrast mode is currently turned on
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn is_high_contrast_mode_active() -> bool {
+    // TODO: NSWorkspace.accessibilityDisplayShouldIncreaseContrast on macOS
+    false
+}
+
 fn preprocess_event(event: &Event, frame_event_info: &mut FrameEventInfo) -> WindowCloseEvent {
     use glium::glutin::WindowEvent;
 
@@ -508,6 +1257,9 @@ fn preprocess_event(event: &Event, frame_event_info: &mut FrameEventInfo) -> Win
                     frame_event_info.is_resize_event = true;
                     frame_event_info.should_redraw_window = true;
                 },
+                WindowEvent::Moved(..) => {
+                    frame_event_info.is_move_event = true;
+                },
                 WindowEvent::Refresh => {
                     frame_event_info.should_redraw_window = true;
                 },
@@ -517,6 +1269,15 @@ fn preprocess_event(event: &Event, frame_event_info: &mut FrameEventInfo) -> Win
                 },
                 WindowEvent::MouseWheel { .. } => {
                     frame_event_info.should_hittest = true;
+                    frame_event_info.is_scroll_event = true;
+                },
+                WindowEvent::Touch(..) => {
+                    frame_event_info.should_hittest = true;
+                },
+                // Keyboard events go to the focused node, the space bar also
+                // activates the hovered node (`:active`)
+                WindowEvent::KeyboardInput { .. } | WindowEvent::ReceivedCharacter(..) => {
+                    frame_event_info.should_hittest = true;
                 },
                 WindowEvent::Closed => {
                     return WindowCloseEvent::AboutToClose;
@@ -536,69 +1297,391 @@ fn preprocess_event(event: &Event, frame_event_info: &mut FrameEventInfo) -> Win
 fn do_hit_test_and_call_callbacks<T: Layout>(
     event: &Event,
     window: &mut Window<T>,
-    window_id: WindowId,
+    window_idx: usize,
     info: &mut FrameEventInfo,
     ui_state_cache: &[UiState<T>],
+    ui_description: &UiDescription<T>,
     app_state: &mut AppState<T>)
 {
     use dom::UpdateScreen;
-    use webrender::api::WorldPoint;
     use window::WindowEvent;
-    use dom::Callback;
-    use window_state::{KeyboardState, MouseState};
+    use dom::{Callback, On};
+    use window_state::{KeyboardState, MouseState, MultiTouchState};
+    use glium::glutin::VirtualKeyCode;
 
-    let (cursor_x, cursor_y) = window.state.mouse_state.cursor_pos.and_then(|(x, y)| Some((x as f32, y as f32))).unwrap_or((0.0, 0.0));
+    let callbacks_filter_list = window.state.determine_callbacks(event);
+    let pinch_gesture = window.state.touch_state.pinch_gesture;
+    let rotate_gesture = window.state.touch_state.rotate_gesture;
+    let touch = window.state.touch_state.current_touch;
+
+    // Touch events are hit-tested at the position of the finger instead of the mouse cursor
+    let (cursor_x, cursor_y) = match touch {
+        Some((_, touch_point)) => touch_point.position,
+        None => window.state.mouse_state.cursor_pos.and_then(|(x, y)| Some((x as f32, y as f32))).unwrap_or((0.0, 0.0)),
+    };
     let point = WorldPoint::new(cursor_x, cursor_y);
+    let hit_test_results = hit_test(window, point);
+    let tag_map = get_tag_map(&ui_state_cache[window_idx]);
 
-    let hit_test_results =  window.internal.api.hit_test(
-        window.internal.document_id,
-        Some(window.internal.pipeline_id),
-        point,
-        HitTestFlags::FIND_ALL);
+    // ... and gestures at the center of the fingers, so that the pinch / rotation goes to
+    // the node between the fingers, while the touches go to the nodes under the fingers
+    let (gesture_callbacks, callbacks_filter_list): (Vec<On>, Vec<On>) = callbacks_filter_list.into_iter()
+        .partition(|on| *on == On::Pinch || *on == On::Rotate);
+    let gesture_center = pinch_gesture.map(|pinch| pinch.center).or(rotate_gesture.map(|rotate| rotate.center));
+
+    // Keyboard events aren't hit-tested, they go to the focused node
+    let (keyboard_callbacks, callbacks_filter_list): (Vec<On>, Vec<On>) = callbacks_filter_list.into_iter()
+        .partition(|on| *on == On::VirtualKeyDown || *on == On::VirtualKeyUp || *on == On::TextInput);
 
     let mut should_update_screen = UpdateScreen::DontRedraw;
 
-    let callbacks_filter_list = window.state.determine_callbacks(event);
+    // Pressing or releasing a mouse button / the space bar changes the `:active` node
+    let focused_node = app_state.windows[window_idx].focused_node;
+    if let Some(active_node) = get_active_node(event, &hit_test_results, &tag_map, &ui_state_cache[window_idx], focused_node) {
+        if window.state.mouse_state.active_node != active_node {
+            window.state.mouse_state.active_node = active_node;
+            window.css.set_active_node(active_node);
+            info.should_redraw_window = true;
+        }
+    }
+
+    // Clicking on a focusable node focuses it
+    if callbacks_filter_list.contains(&On::LeftMouseDown) {
+        if let Some(clicked_node) = get_clicked_focusable_node(&hit_test_results, &tag_map, &ui_state_cache[window_idx]) {
+            app_state.windows[window_idx].focused_node = Some(clicked_node);
+        }
+    }
+
     // TODO: this should be refactored - currently very stateful and error-prone!
-    app_state.windows[window_id.id].set_keyboard_state(&window.state.keyboard_state);
-    app_state.windows[window_id.id].set_mouse_state(&window.state.mouse_state);
-
-    // NOTE: for some reason hit_test_results is empty...
-    // ... but only when the mouse is relased - possible timing issue?
-    for (item, callback_list) in hit_test_results.items.iter().filter_map(|item|
-        ui_state_cache[window_id.id].node_ids_to_callbacks_list
-        .get(&item.tag.0)
-        .and_then(|callback_list| Some((item, callback_list)))
-    ) {
-        // TODO: currently we don't have information about what DOM node was hit
-        let window_event = WindowEvent {
-            window: window_id.id,
-            number_of_previous_siblings: None,
-            cursor_relative_to_item: (item.point_in_viewport.x, item.point_in_viewport.y),
-            cursor_in_viewport: (item.point_in_viewport.x, item.point_in_viewport.y),
+    app_state.windows[window_idx].set_keyboard_state(&window.state.keyboard_state);
+    app_state.windows[window_idx].set_mouse_state(&window.state.mouse_state);
+    app_state.windows[window_idx].set_touches(&window.state.touch_state.touches);
+
+    let mut hit_tests = vec![(point, hit_test_results, callbacks_filter_list)];
+    if let Some((center_x, center_y)) = gesture_center {
+        let center = WorldPoint::new(center_x, center_y);
+        hit_tests.push((center, hit_test(window, center), gesture_callbacks));
+    }
+
+    for (point, hit_test_results, callbacks_filter_list) in hit_tests {
+        // NOTE: for some reason hit_test_results is empty...
+        // ... but only when the mouse is relased - possible timing issue?
+        for (item, callback_list) in hit_test_results.items.iter().filter_map(|item|
+            ui_state_cache[window_idx].node_ids_to_callbacks_list
+            .get(&item.tag.0)
+            .and_then(|callback_list| Some((item, callback_list)))
+        ) {
+            // `pointer-events: none` / `painted`: the events go to the nodes behind this node
+            if let Some(&node_id) = tag_map.get(&item.tag.0) {
+                if !receives_pointer_events(node_id, point, window, &ui_state_cache[window_idx], ui_description, &app_state.resources) {
+                    continue;
+                }
+            }
+
+            // The frontmost node with an `On::DragStart` callback starts the drag-and-drop
+            if app_state.drag_and_drop.is_none() && callbacks_filter_list.contains(&On::DragStart) && callback_list.contains_key(&On::DragStart) {
+                app_state.drag_and_drop = tag_map.get(&item.tag.0).map(|&node_id| DragAndDrop {
+                    source_window: window.id,
+                    source_node: node_id,
+                    target: None,
+                });
+            }
+
+            let hit_node = tag_map.get(&item.tag.0).cloned();
+            let number_of_previous_siblings = hit_node.and_then(|node_id|
+                get_number_of_previous_siblings(&*ui_state_cache[window_idx].dom.arena.borrow(), node_id));
+
+            let window_event = WindowEvent {
+                window: window_idx,
+                window_id: window.id,
+                number_of_previous_siblings: number_of_previous_siblings,
+                cursor_relative_to_item: (item.point_in_viewport.x, item.point_in_viewport.y),
+                cursor_in_viewport: (item.point_in_viewport.x, item.point_in_viewport.y),
+                drag_and_drop: app_state.drag_and_drop,
+                pinch_gesture: pinch_gesture,
+                rotate_gesture: rotate_gesture,
+                touch: touch,
+                hit_node: hit_node,
+            };
+
+            // Invoke callback if necessary
+            for callback_id in callbacks_filter_list.iter().filter_map(|on| callback_list.get(on)) {
+                let Callback(callback_func) = ui_state_cache[window_idx].callback_list[callback_id];
+                if (callback_func)(app_state, window_event) == UpdateScreen::Redraw {
+                    should_update_screen = UpdateScreen::Redraw;
+                }
+            }
+        }
+    }
+
+    if let Some(focused_node) = app_state.windows[window_idx].focused_node {
+        let ui_state = &ui_state_cache[window_idx];
+        let arena = ui_state.dom.arena.borrow();
+        let callback_list = if focused_node.index.get() < arena.nodes_len() {
+            arena[focused_node].data.tag.and_then(|tag| ui_state.node_ids_to_callbacks_list.get(&tag))
+        } else {
+            None
         };
 
-        // Invoke callback if necessary
-        for callback_id in callbacks_filter_list.iter().filter_map(|on| callback_list.get(on)) {
-            let Callback(callback_func) = ui_state_cache[window_id.id].callback_list[callback_id];
-            if (callback_func)(app_state, window_event) == UpdateScreen::Redraw {
-                should_update_screen = UpdateScreen::Redraw;
+        if let Some(callback_list) = callback_list {
+            let window_event = WindowEvent {
+                window: window_idx,
+                window_id: window.id,
+                number_of_previous_siblings: get_number_of_previous_siblings(&*arena, focused_node),
+                cursor_relative_to_item: (cursor_x, cursor_y),
+                cursor_in_viewport: (cursor_x, cursor_y),
+                drag_and_drop: app_state.drag_and_drop,
+                pinch_gesture: pinch_gesture,
+                rotate_gesture: rotate_gesture,
+                touch: touch,
+                hit_node: Some(focused_node),
+            };
+
+            for callback_id in keyboard_callbacks.iter().filter_map(|on| callback_list.get(on)) {
+                let Callback(callback_func) = ui_state.callback_list[callback_id];
+                if (callback_func)(app_state, window_event) == UpdateScreen::Redraw {
+                    should_update_screen = UpdateScreen::Redraw;
+                }
             }
         }
     }
 
-    app_state.windows[window_id.id].set_keyboard_state(&KeyboardState::default());
-    app_state.windows[window_id.id].set_mouse_state(&MouseState::default());
+    // Tab / Shift + Tab moves the focus to the next / previous focusable node
+    if keyboard_callbacks.contains(&On::VirtualKeyDown) &&
+       window.state.keyboard_state.current_virtual_keycode == Some(VirtualKeyCode::Tab) {
+        let forward = !window.state.keyboard_state.is_shift_down();
+        let focused_node = app_state.windows[window_idx].focused_node;
+        app_state.windows[window_idx].focused_node = ui_state_cache[window_idx].dom.get_next_focusable_node(focused_node, forward);
+    }
+
+    // The focused node may have been changed by the callbacks, Tab or a click (`:focus`)
+    if window.css.focused_node != app_state.windows[window_idx].focused_node {
+        window.css.set_focused_node(app_state.windows[window_idx].focused_node);
+        info.should_redraw_window = true;
+    }
+
+    app_state.windows[window_idx].set_keyboard_state(&KeyboardState::default());
+    app_state.windows[window_idx].set_mouse_state(&MouseState::default());
+    app_state.windows[window_idx].set_touches(&MultiTouchState::default());
 
     if should_update_screen == UpdateScreen::Redraw {
         info.should_redraw_window = true;
         // TODO: THIS IS PROBABLY THE WRONG PLACE TO DO THIS!!!
         // Copy the current fake CSS changes to the real CSS, then clear the fake CSS again
         // TODO: .clone() and .clear() can be one operation
-        window.css.dynamic_css_overrides = app_state.windows[window_id.id].css.dynamic_css_overrides.clone();
+        window.css.dynamic_css_overrides = app_state.windows[window_idx].css.dynamic_css_overrides.clone();
         // clear the dynamic CSS overrides
-        app_state.windows[window_id.id].css.clear();
+        app_state.windows[window_idx].css.clear();
+    }
+}
+
+/// What pressed the `:active` node, see `get_pressed_node()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ActivationInput {
+    Mouse,
+    Keyboard,
+}
+
+/// Returns the new `:active` node if the event presses or releases a mouse button or the
+/// space bar (see `get_pressed_node()`), or `None` if the event doesn't change the active node
+fn get_active_node<T: Layout>(
+    event: &Event,
+    hit_test_results: &HitTestResult,
+    tag_map: &FastHashMap<u64, NodeId>,
+    ui_state: &UiState<T>,
+    focused_node: Option<NodeId>)
+-> Option<Option<NodeId>>
+{
+    use glium::glutin::{WindowEvent, ElementState, KeyboardInput, VirtualKeyCode};
+
+    let (input, state) = match event {
+        Event::WindowEvent { event: WindowEvent::MouseInput { state, .. }, .. } => (ActivationInput::Mouse, state),
+        Event::WindowEvent { event: WindowEvent::KeyboardInput {
+            input: KeyboardInput { state, virtual_keycode: Some(VirtualKeyCode::Space), .. }, ..
+        }, .. } => (ActivationInput::Keyboard, state),
+        _ => return None,
+    };
+
+    if *state == ElementState::Released {
+        return Some(None);
+    }
+
+    let hit_nodes = hit_test_results.items.iter()
+        .filter_map(|item| tag_map.get(&item.tag.0).cloned())
+        .collect::<Vec<NodeId>>();
+
+    Some(get_pressed_node(input, &hit_nodes, focused_node, &*ui_state.dom.arena.borrow()))
+}
+
+/// A mouse button presses the frontmost of the `hit_nodes` (the nodes under the cursor,
+/// frontmost first), the space bar presses the `focused_node`. Disabled nodes can't be pressed.
+fn get_pressed_node<T: Layout>(input: ActivationInput, hit_nodes: &[NodeId], focused_node: Option<NodeId>, arena: &Arena<NodeData<T>>)
+-> Option<NodeId>
+{
+    let is_enabled = |node_id: &NodeId| !arena[*node_id].data.disabled;
+    match input {
+        ActivationInput::Mouse => hit_nodes.iter().cloned().filter(is_enabled).next(),
+        ActivationInput::Keyboard => focused_node.filter(is_enabled),
+    }
+}
+
+/// Returns the frontmost hit node that can be focused (see `Dom::set_tab_index()`)
+fn get_clicked_focusable_node<T: Layout>(hit_test_results: &HitTestResult, tag_map: &FastHashMap<u64, NodeId>, ui_state: &UiState<T>) -> Option<NodeId> {
+    let arena = ui_state.dom.arena.borrow();
+    hit_test_results.items.iter()
+        .filter_map(|item| tag_map.get(&item.tag.0).cloned())
+        .find(|node_id| arena[*node_id].data.tab_index.is_some() && !arena[*node_id].data.disabled)
+}
+
+fn hit_test<T: Layout>(window: &Window<T>, point: WorldPoint) -> HitTestResult {
+    window.internal.api.hit_test(
+        window.internal.document_id,
+        Some(window.internal.pipeline_id),
+        point,
+        HitTestFlags::FIND_ALL)
+}
+
+/// Maps the hit-testing tags to the nodes (only nodes with callbacks have a tag),
+/// built once per hit test
+fn get_tag_map<T: Layout>(ui_state: &UiState<T>) -> FastHashMap<u64, NodeId> {
+    let arena = ui_state.dom.arena.borrow();
+    arena.linear_iter().filter_map(|node_id| Some((arena[node_id].data.tag?, node_id))).collect()
+}
+
+/// Returns the index of the node among the children of its parent (see
+/// `WindowEvent::number_of_previous_siblings`), `None` for the root node
+fn get_number_of_previous_siblings<T>(arena: &Arena<T>, node_id: NodeId) -> Option<usize> {
+    arena[node_id].parent()?;
+    // NOTE: the iterator includes the node itself
+    Some(node_id.preceding_siblings(arena).count() - 1)
+}
+
+/// Returns whether the node receives the events at the `point` (in window coordinates),
+/// depending on its `pointer-events`
+fn receives_pointer_events<T: Layout>(
+    node_id: NodeId,
+    point: WorldPoint,
+    window: &Window<T>,
+    ui_state: &UiState<T>,
+    ui_description: &UiDescription<T>,
+    app_resources: &AppResources)
+-> bool
+{
+    use css_parser::{StylePointerEvents, ParsedCssProperty};
+    use dom::NodeType;
+    use webrender::api::LayoutPoint;
+
+    match ui_description.get_pointer_events(node_id) {
+        StylePointerEvents::Auto => return true,
+        StylePointerEvents::None => return false,
+        StylePointerEvents::Painted => { },
+    }
+
+    let bounds = match window.solver.query_bounds_of_rect(node_id) {
+        Some(bounds) => bounds,
+        None => return true,
+    };
+
+    let arena = ui_state.dom.arena.borrow();
+    match arena[node_id].data.node_type {
+        NodeType::Image(ref image_id) => {
+            let object_fit = ui_description.get_css_property(node_id, |property| match *property {
+                ParsedCssProperty::ObjectFit(object_fit) => Some(object_fit),
+                _ => None,
+            }).unwrap_or_default();
+            let object_position = ui_description.get_css_property(node_id, |property| match *property {
+                ParsedCssProperty::ObjectPosition(object_position) => Some(object_position),
+                _ => None,
+            }).unwrap_or_default();
+            ::display_list::is_image_painted_at(app_resources, image_id, &bounds, object_fit, object_position, LayoutPoint::new(point.x, point.y))
+        },
+        // i.e. `Svg` widgets: only the fill and the stroke of the shapes receive events
+        NodeType::GlTexture(ref texture) => {
+            ::display_list::is_texture_painted_at(texture, &bounds, LayoutPoint::new(point.x, point.y))
+        },
+        _ => true,
+    }
+}
+
+fn is_left_mouse_release(event: &Event) -> bool {
+    use glium::glutin::{WindowEvent, ElementState, MouseButton};
+    match event {
+        Event::WindowEvent { event: WindowEvent::MouseInput { state: ElementState::Released, button: MouseButton::Left, .. }, .. } => true,
+        _ => false,
+    }
+}
+
+/// Returns the index of the frontmost window at the position on the screen
+/// (the `window_order` is ordered from the bottom to the top window)
+fn get_window_at_screen_position<T: Layout>(windows: &[Window<T>], window_order: &[WindowId], position: (f64, f64)) -> Option<usize> {
+    window_order.iter().rev().filter_map(|id| windows.iter().position(|w| w.id == *id)).find(|idx| {
+        let gl_window = windows[*idx].display.gl_window();
+        match (gl_window.get_inner_position(), gl_window.get_inner_size_pixels()) {
+            (Some((x, y)), Some((width, height))) =>
+                position.0 >= x as f64 && position.0 < x as f64 + width as f64 &&
+                position.1 >= y as f64 && position.1 < y as f64 + height as f64,
+            _ => false,
+        }
+    })
+}
+
+/// Hit-tests the window at the position where the drag was released and calls the
+/// `On::Drop` callback of the frontmost node that has one
+fn call_drop_callback<T: Layout>(
+    window: &mut Window<T>,
+    window_idx: usize,
+    drag: DragAndDrop,
+    screen_position: (f64, f64),
+    ui_state_cache: &[UiState<T>],
+    app_state: &mut AppState<T>)
+-> UpdateScreen
+{
+    use dom::On;
+
+    let (window_x, window_y) = window.display.gl_window().get_inner_position().unwrap_or((0, 0));
+    let cursor_in_viewport = ((screen_position.0 - window_x as f64) as f32, (screen_position.1 - window_y as f64) as f32);
+
+    let hit_test_results = window.internal.api.hit_test(
+        window.internal.document_id,
+        Some(window.internal.pipeline_id),
+        WorldPoint::new(cursor_in_viewport.0, cursor_in_viewport.1),
+        HitTestFlags::FIND_ALL);
+
+    let ui_state = &ui_state_cache[window_idx];
+    let tag_map = get_tag_map(ui_state);
+    let drop_target = hit_test_results.items.iter().filter_map(|item| {
+        let callback_id = ui_state.node_ids_to_callbacks_list.get(&item.tag.0)?.get(&On::Drop)?;
+        Some((item, *callback_id, *tag_map.get(&item.tag.0)?))
+    }).next();
+
+    let (item, callback_id, node_id) = match drop_target {
+        Some(target) => target,
+        None => return UpdateScreen::DontRedraw,
+    };
+
+    let window_event = WindowEvent {
+        window: window_idx,
+        window_id: window.id,
+        number_of_previous_siblings: get_number_of_previous_siblings(&*ui_state.dom.arena.borrow(), node_id),
+        cursor_relative_to_item: (item.point_in_viewport.x, item.point_in_viewport.y),
+        cursor_in_viewport: cursor_in_viewport,
+        drag_and_drop: Some(DragAndDrop { target: Some((window.id, node_id)), .. drag }),
+        pinch_gesture: None,
+        rotate_gesture: None,
+        touch: None,
+        hit_node: Some(node_id),
+    };
+
+    let Callback(callback_func) = ui_state.callback_list[&callback_id];
+    let update_screen = (callback_func)(app_state, window_event);
+
+    if update_screen == UpdateScreen::Redraw {
+        // see `do_hit_test_and_call_callbacks()`
+        window.css.dynamic_css_overrides = app_state.windows[window_idx].css.dynamic_css_overrides.clone();
+        app_state.windows[window_idx].css.clear();
     }
+
+    update_screen
 }
 
 fn render<T: Layout>(
@@ -620,13 +1703,82 @@ fn render<T: Layout>(
         app_resources,
         &window.internal.api,
         has_window_size_changed,
-        &window.state.size);
+        &window.state.size,
+        window.get_text_aa_mode());
 
     if let Some(new_builder) = builder {
         // only finalize the list if we actually need to. Otherwise just redraw the last display list
         window.internal.last_display_list_builder = new_builder.finalize().2;
     }
 
+    send_display_list(window);
+}
+
+/// Calls the layout functions of the embedded pipelines of the window (see
+/// `App::add_embedded_pipeline()`) and builds their display lists, which are
+/// submitted to webrender together with the display list of the window.
+/// The embedded pipelines are always laid out from scratch.
+fn update_embedded_pipelines<T: Layout>(
+    window: &mut Window<T>,
+    window_idx: usize,
+    app_state: &mut AppState<T>,
+    background_messages: Vec<Rc<Any + Send>>)
+{
+    use display_list::DisplayList;
+    use window::{ReadOnlyWindow, WindowInfo, UiSolver};
+
+    let embedded_layouts = window.embedded_layouts.iter()
+        .map(|(pipeline_id, layout)| (*pipeline_id, *layout))
+        .collect::<Vec<(PipelineId, EmbeddedLayout<T>)>>();
+
+    for (pipeline_id, layout) in embedded_layouts {
+        let window_info = WindowInfo {
+            window_id: window.id,
+            window: ReadOnlyWindow {
+                inner: window.display.clone(),
+            },
+            open_drawers: app_state.windows[window_idx].open_drawers.clone(),
+            background_messages: background_messages.clone(),
+            monitor_id: window.current_monitor.clone(),
+            monitor_dpi: window.current_monitor_dpi,
+            is_printing: false,
+        };
+
+        let dom = layout(&*app_state.data.lock().unwrap(), window_info);
+        let ui_state = UiState::from_dom(dom);
+        let ui_description = UiDescription::from_ui_state(&ui_state, &window.css);
+        let display_list = DisplayList::new_from_ui_description(&ui_description);
+
+        // The `:active` / `:focus` nodes of the window are not part of the embedded DOM
+        let mut css = window.css.clone();
+        css.set_active_node(None);
+        css.set_focused_node(None);
+        css.needs_relayout = true;
+
+        let mut ui_solver = UiSolver::empty();
+        let builder = display_list.into_display_list_builder(
+            pipeline_id,
+            &mut ui_solver,
+            &mut css,
+            &mut app_state.resources,
+            &window.internal.api,
+            true,
+            &window.state.size,
+            window.get_text_aa_mode());
+
+        if let Some(builder) = builder {
+            window.set_embedded_display_list(pipeline_id, builder.finalize().2);
+        }
+    }
+}
+
+/// Sends the last display list (and the display lists of the embedded pipelines)
+/// together with the current background color to webrender and renders the frame
+fn send_display_list<T: Layout>(window: &mut Window<T>) {
+    use webrender::api::*;
+    use euclid::TypedSize2D;
+    use display_list::get_external_scroll_id;
+
     let mut txn = Transaction::new();
 
     let framebuffer_size = TypedSize2D::new(window.state.size.width, window.state.size.height);
@@ -634,12 +1786,32 @@ fn render<T: Layout>(
 
     txn.set_display_list(
         window.internal.epoch,
-        None,
+        Some(window.state.background_color),
         layout_size,
         (window.internal.pipeline_id, layout_size, window.internal.last_display_list_builder.clone()),
         true,
     );
 
+    for (pipeline_id, embedded_display_list) in window.internal.embedded_pipelines.iter() {
+        if let Some(ref embedded_display_list) = *embedded_display_list {
+            txn.set_display_list(
+                window.internal.epoch,
+                None,
+                layout_size,
+                (*pipeline_id, layout_size, embedded_display_list.clone()),
+                true,
+            );
+        }
+    }
+
+    // The scroll frames of a new display list start unscrolled
+    for (node_id, &(offset_x, offset_y)) in window.scroll_states.offsets.iter() {
+        txn.scroll_node_with_id(
+            LayoutPoint::new(offset_x, offset_y),
+            get_external_scroll_id(*node_id, window.internal.pipeline_id),
+            ScrollClamping::NoClamping);
+    }
+
     txn.set_root_pipeline(window.internal.pipeline_id);
     txn.generate_frame();
 
@@ -656,12 +1828,115 @@ fn render_inner<T: Layout>(window: &mut Window<T>, framebuffer_size: TypedSize2D
 
     use glium::backend::Facade;
     use gleam::gl;
-    use window::get_gl_context;
+    use window::{get_gl_context, ReadOnlyWindow};
+
+    let read_only_window = ReadOnlyWindow { inner: window.display.clone() };
+
+    if let Some(ref pre_render) = window.pre_render {
+        (pre_render.0)(&read_only_window);
+    }
 
     let mut current_program = [0_i32];
     unsafe { get_gl_context(&window.display).unwrap().get_integer_v(gl::CURRENT_PROGRAM, &mut current_program) };
     window.renderer.as_mut().unwrap().render(framebuffer_size).unwrap();
     get_gl_context(&window.display).unwrap().use_program(current_program[0] as u32);
+
+    if let Some(ref post_render) = window.post_render {
+        (post_render.0)(&read_only_window);
+    }
+}
+
+/// Renders the part of the content between the `content_offsets` (top and bottom edge) as
+/// one page for `App::render_for_print()` and returns the page as a PNG file. The page is
+/// drawn into the back buffer of the window and read back before the buffers are swapped,
+/// scaled down if the page is larger than the window.
+fn render_print_page<T: Layout>(
+    window: &mut Window<T>,
+    content_pipeline_id: PipelineId,
+    content_display_list: &BuiltDisplayList,
+    content_size: LayoutSize,
+    page_size: (f32, f32),
+    margin: f32,
+    content_offsets: (f32, f32))
+-> Option<Vec<u8>>
+{
+    use webrender::api::*;
+    use euclid::TypedRect;
+    use gleam::gl;
+    use image::{ColorType, png::PNGEncoder};
+    use window::{get_gl_context, flip_rows, ReadOnlyWindow};
+
+    let (page_top, page_bottom) = content_offsets;
+    let framebuffer_size = TypedSize2D::new(window.state.size.width, window.state.size.height);
+    let hidpi_factor = window.state.size.hidpi_factor;
+    let layout_size = framebuffer_size.to_f32() / TypedScale::new(hidpi_factor);
+
+    let scale = (layout_size.width / page_size.0).min(layout_size.height / page_size.1).min(1.0);
+    let page_rect = LayoutRect::new(LayoutPoint::zero(), LayoutSize::new(page_size.0 * scale, page_size.1 * scale));
+
+    let mut builder = DisplayListBuilder::new(window.internal.pipeline_id, layout_size);
+    builder.push_rect(&LayoutPrimitiveInfo::new(page_rect), ColorF::new(1.0, 1.0, 1.0, 1.0));
+
+    // moves the part of the content that is printed onto this page to the top of the page
+    let transform = LayoutTransform::create_translation(margin, margin - page_top, 0.0).post_scale(scale, scale, 1.0);
+    builder.push_stacking_context(
+        &LayoutPrimitiveInfo::new(LayoutRect::new(LayoutPoint::zero(), content_size)),
+        ScrollPolicy::Scrollable,
+        Some(PropertyBinding::Value(transform)),
+        TransformStyle::Flat,
+        None,
+        MixBlendMode::Normal,
+        Vec::new());
+    let content_rect = LayoutRect::new(LayoutPoint::zero(), content_size);
+    builder.push_iframe(&LayoutPrimitiveInfo {
+        rect: content_rect,
+        clip_rect: TypedRect::new(LayoutPoint::new(0.0, page_top), LayoutSize::new(content_size.width, page_bottom - page_top)),
+        is_backface_visible: false,
+        tag: None,
+    }, content_pipeline_id);
+    builder.pop_stacking_context();
+
+    let mut txn = Transaction::new();
+    txn.set_display_list(window.internal.epoch, Some(window.state.background_color), layout_size,
+                         (window.internal.pipeline_id, layout_size, builder.finalize().2), true);
+    txn.set_display_list(window.internal.epoch, None, content_size,
+                         (content_pipeline_id, content_size, content_display_list.clone()), true);
+    txn.set_root_pipeline(window.internal.pipeline_id);
+    txn.generate_frame();
+    window.internal.api.send_transaction(window.internal.document_id, txn);
+
+    ReadOnlyWindow { inner: window.display.clone() }.make_current();
+    let gl = get_gl_context(&window.display).ok()?;
+
+    window.renderer.as_mut().unwrap().update();
+    let mut current_program = [0_i32];
+    unsafe { gl.get_integer_v(gl::CURRENT_PROGRAM, &mut current_program) };
+    window.renderer.as_mut().unwrap().render(framebuffer_size).ok()?;
+    gl.use_program(current_program[0] as u32);
+
+    // OpenGL starts at the bottom left corner of the window
+    let width = ((page_rect.size.width * hidpi_factor) as u32).min(framebuffer_size.width).max(1);
+    let height = ((page_rect.size.height * hidpi_factor) as u32).min(framebuffer_size.height).max(1);
+    gl.pixel_store_i(gl::PACK_ALIGNMENT, 1);
+    let pixels = gl.read_pixels(0, (framebuffer_size.height - height) as i32, width as i32, height as i32, gl::RGBA, gl::UNSIGNED_BYTE);
+    let pixels = flip_rows(pixels, width as usize * 4);
+
+    let mut png = Vec::new();
+    PNGEncoder::new(&mut png).encode(&pixels, width, height, ColorType::RGBA(8)).ok()?;
+    Some(png)
+}
+
+/// Returns the indices of the `window_ids`, sorted in the `order` (see `App::set_window_order()`)
+fn get_window_permutation(window_ids: &[WindowId], order: &[WindowId]) -> Vec<usize> {
+    let mut permutation = (0..window_ids.len()).filter(|idx| !order.contains(&window_ids[*idx])).collect::<Vec<usize>>();
+    for id in order {
+        if let Some(idx) = window_ids.iter().position(|w| w == id) {
+            if !permutation.contains(&idx) {
+                permutation.push(idx);
+            }
+        }
+    }
+    permutation
 }
 
 // Empty test, for some reason codecov doesn't detect any files (and therefore
@@ -670,4 +1945,118 @@ fn render_inner<T: Layout>(window: &mut Window<T>, framebuffer_size: TypedSize2D
 #[test]
 fn __codecov_test_app_file() {
 
+}
+
+#[test]
+fn test_simulate_callback() {
+    use dom::{Dom, NodeType};
+    use traits::ModifyAppState;
+    use widgets::DrawerId;
+
+    struct TestLayout {
+        clicked: bool,
+    }
+
+    impl Layout for TestLayout {
+        fn layout(&self) -> Dom<Self> {
+            Dom::new(NodeType::Div)
+        }
+    }
+
+    fn on_click(app_state: &mut AppState<TestLayout>, event: WindowEvent) -> UpdateScreen {
+        app_state.data.modify(|state| state.clicked = true);
+        app_state.windows[event.window].state.title = String::from("clicked");
+        app_state.windows[event.window].open_drawer(DrawerId::new());
+        UpdateScreen::Redraw
+    }
+
+    let mut app = App::new(TestLayout { clicked: false });
+    let (update_screen, ops) = app.simulate_callback(WindowEvent::mock(), Callback(on_click));
+
+    assert_eq!(update_screen, UpdateScreen::Redraw);
+    assert!(ops.redraw_requested);
+    assert_eq!(ops.new_title, Some(String::from("clicked")));
+    assert_eq!(ops.open_drawers.len(), 1);
+    assert!(ops.scrolls.is_empty());
+    assert!(ops.scrolls_into_view.is_empty());
+    assert!(app.app_state.data.lock().unwrap().clicked);
+    assert!(app.app_state.windows.is_empty());
+}
+
+#[test]
+fn test_get_window_permutation() {
+    let ids = (0..4).map(WindowId::new).collect::<Vec<WindowId>>();
+
+    // the ordered windows are on top, the other windows keep their order
+    assert_eq!(get_window_permutation(&ids, &[ids[0]]), vec![1, 2, 3, 0]);
+    assert_eq!(get_window_permutation(&ids, &[ids[2], ids[1]]), vec![0, 3, 2, 1]);
+    assert_eq!(get_window_permutation(&ids, &[WindowId::new(10), ids[3], ids[3]]), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn test_click_radio_group_option() {
+    use dom::{Dom, NodeType};
+    use traits::ModifyAppState;
+    use widgets::RadioGroup;
+
+    struct TestLayout {
+        selected: usize,
+    }
+
+    impl Layout for TestLayout {
+        fn layout(&self) -> Dom<Self> {
+            Dom::new(NodeType::Div)
+        }
+    }
+
+    fn on_change(app_state: &mut AppState<TestLayout>, event: WindowEvent) -> UpdateScreen {
+        match RadioGroup::get_clicked_option(&event) {
+            Some(option) => {
+                app_state.data.modify(|state| state.selected = option);
+                UpdateScreen::Redraw
+            },
+            None => UpdateScreen::DontRedraw,
+        }
+    }
+
+    let options = vec![String::from("a"), String::from("b"), String::from("c")];
+    let dom = RadioGroup::new(options, 0).dom(Callback(on_change));
+    let arena = dom.arena.borrow();
+
+    // The options are the children of the radio group, the root has no siblings
+    let third_option = dom.root.children(&*arena).nth(2).unwrap();
+    assert_eq!(get_number_of_previous_siblings(&*arena, dom.root), None);
+
+    let event = WindowEvent {
+        number_of_previous_siblings: get_number_of_previous_siblings(&*arena, third_option),
+        .. WindowEvent::mock()
+    };
+
+    let mut app = App::new(TestLayout { selected: 0 });
+    let (update_screen, _) = app.simulate_callback(event, Callback(on_change));
+
+    assert_eq!(update_screen, UpdateScreen::Redraw);
+    assert_eq!(app.app_state.data.lock().unwrap().selected, 2);
+}
+
+#[test]
+fn test_get_pressed_node() {
+    use dom::{Dom, NodeType, TestLayout};
+
+    let dom = Dom::<TestLayout>::new(NodeType::Div)
+        .with_child(Dom::new(NodeType::Div))
+        .with_child(Dom::new(NodeType::Div))
+        .with_child(Dom::new(NodeType::Div).with_disabled(true));
+    let arena = dom.arena.borrow();
+    let children = dom.root.children(&*arena).collect::<Vec<NodeId>>();
+    let (hovered, focused, disabled) = (children[0], children[1], children[2]);
+
+    // the mouse presses the hovered node, the space bar the focused node
+    assert_eq!(get_pressed_node(ActivationInput::Mouse, &[hovered, dom.root], Some(focused), &*arena), Some(hovered));
+    assert_eq!(get_pressed_node(ActivationInput::Keyboard, &[hovered, dom.root], Some(focused), &*arena), Some(focused));
+    assert_eq!(get_pressed_node(ActivationInput::Keyboard, &[hovered], None, &*arena), None);
+
+    // disabled nodes can't be pressed
+    assert_eq!(get_pressed_node(ActivationInput::Mouse, &[disabled, dom.root], None, &*arena), Some(dom.root));
+    assert_eq!(get_pressed_node(ActivationInput::Keyboard, &[], Some(disabled), &*arena), None);
 }
\ No newline at end of file
@@ -1,20 +1,23 @@
 use std::{
     io::Read,
+    path::PathBuf,
     collections::hash_map::Entry::*,
     sync::{Arc, Mutex},
 };
 use image::ImageError;
+use glium::glutin::WindowEvent as GlutinWindowEvent;
 use {
     FastHashMap,
     text_cache::TextId,
-    window::FakeWindow,
+    window::{FakeWindow, WindowId, WindowRegistry, DragAndDrop},
     window_state::WindowState,
     task::Task,
     dom::UpdateScreen,
     traits::Layout,
     resources::AppResources,
     images::ImageType,
-    font::FontError,
+    font::{FontError, FontLoadState},
+    text_layout::FontVerticalMetrics,
     svg::{SvgLayerId, SvgLayer, SvgParseError},
     css_parser::{Font as FontId, FontSize, PixelValue},
     errors::ClipboardError,
@@ -41,6 +44,12 @@ pub struct AppState<'a, T: Layout> {
     pub(crate) deamons: FastHashMap<String, fn(&mut T) -> UpdateScreen>,
     /// Currently running tasks (asynchronous functions running on a different thread)
     pub(crate) tasks: Vec<Task>,
+    /// All open windows, by their `WindowId`
+    pub(crate) window_registry: WindowRegistry,
+    /// Events sent via `send_event_to_window()`, handled on the next frame
+    pub(crate) pending_window_events: Vec<(WindowId, GlutinWindowEvent)>,
+    /// The drag-and-drop that is currently in progress, see `On::DragStart`
+    pub(crate) drag_and_drop: Option<DragAndDrop>,
 }
 
 impl<'a, T: Layout> AppState<'a, T> {
@@ -53,9 +62,41 @@ impl<'a, T: Layout> AppState<'a, T> {
             resources: AppResources::default(),
             deamons: FastHashMap::default(),
             tasks: Vec::new(),
+            window_registry: WindowRegistry::default(),
+            pending_window_events: Vec::new(),
+            drag_and_drop: None,
         }
     }
 
+    /// Returns the drag-and-drop that is currently in progress. A drag is started by
+    /// pressing the left mouse button on a node with an `On::DragStart` callback and
+    /// ends when the button is released - over any window of the app.
+    pub fn get_drag_and_drop(&self) -> Option<DragAndDrop> {
+        self.drag_and_drop
+    }
+
+    /// Cancels the current drag-and-drop, no `On::Drop` callback will be called
+    pub fn cancel_drag_and_drop(&mut self) {
+        self.drag_and_drop = None;
+    }
+
+    /// Returns the registry of all open windows, to look up a window by its `WindowId`
+    pub fn window_registry(&self) -> &WindowRegistry {
+        &self.window_registry
+    }
+
+    /// Sends an event to another window, i.e. to simulate a click or a key press in it.
+    /// The event is handled like a regular event of that window on the next frame.
+    ///
+    /// Returns `false` if there is no open window with the given ID.
+    pub fn send_event_to_window(&mut self, window_id: WindowId, event: GlutinWindowEvent) -> bool {
+        if !self.window_registry.contains(window_id) {
+            return false;
+        }
+        self.pending_window_events.push((window_id, event));
+        true
+    }
+
     /// Add an image to the internal resources.
     ///
     /// ## Arguments
@@ -145,6 +186,28 @@ impl<'a, T: Layout> AppState<'a, T> {
         self.resources.add_font(id, data)
     }
 
+    /// Loads a font (TTF or OTF) from a file on a background thread, so that large fonts
+    /// don't block the UI. What is drawn while the font is loading depends on the
+    /// `font-display` property of the nodes that use the font. The windows are re-layouted
+    /// once the font has loaded, see `get_font_load_state()` for the progress.
+    ///
+    /// Does nothing if a font with the same ID is already loading or loaded.
+    pub fn add_font_from_file<S: Into<String>, P: Into<PathBuf>>(&mut self, id: S, path: P) {
+        let id = id.into();
+        if self.resources.has_font(id.as_str()) {
+            return;
+        }
+        // a font that failed to load can be loaded again
+        self.resources.font_loads.remove(&id);
+        ::font::load_font_async(id, path.into());
+    }
+
+    /// Returns the load state of a font added via `add_font_from_file()`.
+    /// Returns `None` if the font wasn't added via `add_font_from_file()`.
+    pub fn get_font_load_state(&self, id: &str) -> Option<FontLoadState> {
+        self.resources.get_font_load_state(id)
+    }
+
     /// Checks if a font is currently registered and ready-to-use
     pub fn has_font<S: Into<String>>(&mut self, id: S)
         -> bool
@@ -152,6 +215,18 @@ impl<'a, T: Layout> AppState<'a, T> {
         self.resources.has_font(id)
     }
 
+    /// Returns the metrics (ascent, cap height, etc.) of the first loaded font of the
+    /// `font_family` (a CSS font stack, i.e. `"Webly Sleeky UI, sans-serif"`) at the
+    /// `font_size` in pixels, i.e. for custom text layout or aligning a baseline.
+    ///
+    /// Returns `None` if the font stack can't be parsed or none of the fonts are loaded.
+    /// The fonts are shared between all windows, so the metrics don't depend on the window.
+    pub fn get_font_metrics(&self, font_family: &str, font_size: f32)
+        -> Option<FontVerticalMetrics>
+    {
+        self.resources.get_font_metrics(font_family, font_size)
+    }
+
     /// Deletes a font from the internal app resources.
     ///
     /// ## Arguments
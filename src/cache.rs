@@ -34,37 +34,89 @@
 //! Lastly, we go through the `HashMap<(DomHash, bool)>` and remove the edit variables if the `bool` is false,
 //! meaning that the variable was not present in the current DOM tree, so leaving the variables in the solver
 //! would be garbage.
+//!
+//! NOTE: Instead of the `bool`, the `EditVariableCache` counts how many nodes use the variables of a hash
+//! (identical nodes have the same hash). `EditVariableCache::apply_diff()` only touches the variables of the
+//! added and removed nodes of the `DomChangeSet` - the variables are removed once no node uses them anymore.
 
 use std::{
+    fmt,
     ops::Deref,
+    any::{Any, TypeId},
+    cell::RefCell,
     collections::BTreeMap,
 };
-use cassowary::Solver;
+use cassowary::{Solver, Variable, strength::STRONG};
 
 use {
+    FastHashMap,
+    css_parser::LayoutResize,
     constraints::DisplayRect,
     id_tree::{NodeId, Arena},
     traits::Layout,
-    dom::NodeData,
+    dom::{Dom, NodeData},
 };
 
+/// Strength of the size that the user has dragged a `resize` container to: stronger than
+/// the (`STRONG`) size constraints of the node, but weaker than the `REQUIRED` constraints
+const RESIZE_STRENGTH: f64 = STRONG * 10.0;
+
+/// Magic bytes at the start of a serialized `DomTreeCache`
+const DOM_CACHE_MAGIC: &[u8; 8] = b"AZULDOMC";
+
 /// We keep the tree from the previous re-layout. Then, when a re-layout is required,
 /// we re-hash all the nodes, insert the
+///
+/// The cache can be stored across app restarts, see `WindowCreateOptions::dom_cache_path`.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
-pub(crate) struct DomTreeCache {
+pub struct DomTreeCache {
     pub(crate) previous_layout: HashedDomTree,
+    /// Set if the cache was restored via `deserialize()`: the hash of the restored tree,
+    /// which is compared with the hash of the first DOM after the restore
+    pub(crate) restored_tree_hash: Option<DomHash>,
+}
+
+/// Error that can happen when restoring a `DomTreeCache` via `DomTreeCache::deserialize()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CacheError {
+    /// The data doesn't start with the magic bytes of a serialized `DomTreeCache`
+    InvalidHeader,
+    /// The data ended before all nodes were read
+    UnexpectedEnd,
+    /// A node refers to a node that doesn't exist in the tree
+    InvalidNodeId(u64),
+    /// The hash of the tree doesn't match the stored hash (the data was modified)
+    HashMismatch,
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CacheError::*;
+        match self {
+            InvalidHeader => write!(f, "the data is not a serialized DOM cache"),
+            UnexpectedEnd => write!(f, "the DOM cache ended before all nodes were read"),
+            InvalidNodeId(id) => write!(f, "the DOM cache refers to the non-existent node {}", id),
+            HashMismatch => write!(f, "the hash of the DOM cache doesn't match its contents"),
+        }
+    }
 }
 
+impl ::std::error::Error for CacheError { }
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct DomChangeSet {
     // TODO: calculate the constraints that have to be updated
     pub(crate) added_nodes: BTreeMap<NodeId, DomHash>,
+    /// Nodes of the previous DOM that were changed or don't exist anymore,
+    /// with their (old) hash. The `NodeId`s refer to the previous DOM.
+    pub(crate) removed_nodes: BTreeMap<NodeId, DomHash>,
 }
 
 impl DomChangeSet {
     pub(crate) fn empty() -> Self {
         Self {
             added_nodes: BTreeMap::new(),
+            removed_nodes: BTreeMap::new(),
         }
     }
 }
@@ -85,6 +137,88 @@ impl DomTreeCache {
                 arena: Arena::<DomHash>::new(),
                 root: None,
             },
+            restored_tree_hash: None,
+        }
+    }
+
+    /// Serializes the hashed DOM tree of the last frame, see `deserialize()`
+    ///
+    /// Layout: `DOM_CACHE_MAGIC`, the hash of the tree, the root (`0` for no root, otherwise
+    /// the node index + 1), the number of nodes, then the parent, previous sibling,
+    /// next sibling, first child, last child (encoded like the root) and the hash of each node.
+    /// All numbers are u64, little endian.
+    pub fn serialize(&self) -> Vec<u8> {
+        let arena = &self.previous_layout.arena;
+        let mut data = Vec::with_capacity(DOM_CACHE_MAGIC.len() + (3 + arena.nodes_len() * 6) * 8);
+
+        data.extend_from_slice(DOM_CACHE_MAGIC);
+        write_u64(&mut data, get_tree_hash(arena).0);
+        write_u64(&mut data, encode_node_id(self.previous_layout.root));
+        write_u64(&mut data, arena.nodes_len() as u64);
+
+        for node in &arena.nodes {
+            for node_id in &[node.parent, node.previous_sibling, node.next_sibling, node.first_child, node.last_child] {
+                write_u64(&mut data, encode_node_id(*node_id));
+            }
+            write_u64(&mut data, node.data.0);
+        }
+
+        data
+    }
+
+    /// Restores a cache that was stored via `serialize()`. On the first frame after the restore,
+    /// the restored tree is diffed with the new DOM like the tree of the previous frame - if the
+    /// DOM has changed (i.e. because the app was updated), only the changed nodes are rebuilt.
+    pub fn deserialize(data: &[u8]) -> Result<Self, CacheError> {
+
+        use id_tree::Node;
+
+        if data.len() < DOM_CACHE_MAGIC.len() || &data[..DOM_CACHE_MAGIC.len()] != DOM_CACHE_MAGIC {
+            return Err(CacheError::InvalidHeader);
+        }
+
+        let mut numbers = data[DOM_CACHE_MAGIC.len()..].chunks(8).map(read_u64);
+        let mut next_number = || numbers.next().and_then(|number| number).ok_or(CacheError::UnexpectedEnd);
+
+        let tree_hash = DomHash(next_number()?);
+        let encoded_root = next_number()?;
+        let nodes_len = next_number()?;
+        let decode = |number: u64| decode_node_id(number, nodes_len);
+        let root = decode(encoded_root)?;
+
+        let mut nodes = Vec::new();
+        for _ in 0..nodes_len {
+            nodes.push(Node {
+                parent: decode(next_number()?)?,
+                previous_sibling: decode(next_number()?)?,
+                next_sibling: decode(next_number()?)?,
+                first_child: decode(next_number()?)?,
+                last_child: decode(next_number()?)?,
+                data: DomHash(next_number()?),
+            });
+        }
+
+        let arena = Arena::from_nodes(nodes);
+        if get_tree_hash(&arena) != tree_hash {
+            return Err(CacheError::HashMismatch);
+        }
+
+        Ok(Self {
+            previous_layout: HashedDomTree {
+                arena: arena,
+                root: root,
+            },
+            restored_tree_hash: Some(tree_hash),
+        })
+    }
+
+    /// Returns all nodes of the tree as added nodes. The edit variables of the solver can't be
+    /// stored, so the nodes of a restored tree have to be added to the (new) solver once, before
+    /// the first `update()` - which then only reports the nodes that changed since the restore.
+    pub(crate) fn get_restored_nodes(&self) -> DomChangeSet {
+        DomChangeSet {
+            added_nodes: self.previous_layout.arena.get_all_node_ids(),
+            removed_nodes: BTreeMap::new(),
         }
     }
 
@@ -92,9 +226,16 @@ impl DomTreeCache {
 
         use std::hash::Hash;
 
+        let new_tree = new_nodes_arena.transform(|data, _| data.calculate_node_data_hash());
+
+        if let Some(restored_tree_hash) = self.restored_tree_hash.take() {
+            if get_tree_hash(&new_tree) != restored_tree_hash {
+                info!("the DOM has changed since the DOM cache was stored, only the changed nodes are rebuilt");
+            }
+        }
+
         if let Some(previous_root) = self.previous_layout.root {
             // let mut changeset = DomChangeSet::empty();
-            let new_tree = new_nodes_arena.transform(|data, _| data.calculate_node_data_hash());
             // Self::update_tree_inner(previous_root, &self.previous_layout.arena, new_root, &new_nodes_arena, &mut changeset);
             let changeset = Self::update_tree_inner_2(&self.previous_layout.arena, &new_tree);
             self.previous_layout.arena = new_tree;
@@ -102,10 +243,11 @@ impl DomTreeCache {
         } else {
             // initialize arena
             use std::iter::FromIterator;
-            self.previous_layout.arena = new_nodes_arena.transform(|data, _| data.calculate_node_data_hash());
+            self.previous_layout.arena = new_tree;
             self.previous_layout.root = Some(new_root);
             DomChangeSet {
                 added_nodes: self.previous_layout.arena.get_all_node_ids(),
+                removed_nodes: BTreeMap::new(),
             }
         }
     }
@@ -122,12 +264,19 @@ impl DomTreeCache {
             if let Some(old_hash) = previous_iter.next() {
                 if old_hash.data != next_hash.data {
                     changeset.added_nodes.insert(NodeId { index: NonZeroUsizeHack::new(next_idx) }, next_hash.data);
+                    changeset.removed_nodes.insert(NodeId { index: NonZeroUsizeHack::new(next_idx) }, old_hash.data);
                 }
             } else {
                 // println!("chrildren: no old hash, but subtree has to be added: {:?}!", new_next_id);
                 changeset.added_nodes.insert(NodeId { index: NonZeroUsizeHack::new(next_idx) }, next_hash.data);
             }
         }
+
+        // the previous DOM had more nodes than the current DOM
+        let next_len = next_arena.nodes.len();
+        for (old_idx, old_hash) in previous_iter.enumerate() {
+            changeset.removed_nodes.insert(NodeId { index: NonZeroUsizeHack::new(next_len + old_idx) }, old_hash.data);
+        }
 /*
         loop {
             match (previous_iter.next(), next_iter.next().enumerate()) {
@@ -246,6 +395,99 @@ impl DomTreeCache {
     }
 }
 
+thread_local! {
+    /// Sub-DOMs created via `Dom::memoize()`. `Layout::layout()` doesn't have access
+    /// to the `DomTreeCache` of the window, so the memoized DOMs are stored per thread
+    /// (the layout is always called from the main thread).
+    static DOM_MEMO_CACHE: RefCell<DomMemoCache> = RefCell::new(DomMemoCache::default());
+}
+
+/// Stores the last DOM subtree that was built for each `Dom::memoize()` key
+#[derive(Default)]
+pub(crate) struct DomMemoCache {
+    /// Keyed by the type of the app data (`Dom<T>` is generic) and the memoization key.
+    /// The `bool` is set to `true` if the DOM was used in the current frame.
+    doms: FastHashMap<(TypeId, u64), (bool, Box<Any>)>,
+}
+
+impl DomMemoCache {
+
+    /// Returns a copy of the memoized DOM for the `key`, if there is one
+    pub(crate) fn get<T: Layout + 'static>(key: u64) -> Option<Dom<T>> {
+        DOM_MEMO_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let &mut (ref mut is_used, ref dom) = cache.doms.get_mut(&(TypeId::of::<T>(), key))?;
+            *is_used = true;
+            dom.downcast_ref::<Dom<T>>().map(|dom| dom.deep_clone())
+        })
+    }
+
+    /// Stores a copy of the `dom`, so that the next `get()` with the same `key` can return it
+    pub(crate) fn insert<T: Layout + 'static>(key: u64, dom: &Dom<T>) {
+        DOM_MEMO_CACHE.with(|cache| {
+            cache.borrow_mut().doms.insert((TypeId::of::<T>(), key), (true, Box::new(dom.deep_clone())));
+        })
+    }
+
+    /// Runs `f` with an empty cache and restores the previous cache afterwards,
+    /// so that the `Dom::memoize()` calls in `f` neither use nor change the cache
+    pub(crate) fn isolated<R, F: FnOnce() -> R>(f: F) -> R {
+        let previous_cache = DOM_MEMO_CACHE.with(|cache| ::std::mem::replace(&mut *cache.borrow_mut(), DomMemoCache::default()));
+        let result = f();
+        DOM_MEMO_CACHE.with(|cache| *cache.borrow_mut() = previous_cache);
+        result
+    }
+
+    /// Removes all DOMs that weren't used since the last call, called after each `layout()`
+    ///
+    /// TODO: This is called once per window, so a DOM that is only memoized in
+    /// one of several windows gets rebuilt on each frame
+    pub(crate) fn remove_unused() {
+        DOM_MEMO_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            cache.doms.retain(|_, &mut (is_used, _)| is_used);
+            for (is_used, _) in cache.doms.values_mut() {
+                *is_used = false;
+            }
+        })
+    }
+}
+
+/// Hash of the node hashes and the structure (the parents) of the tree
+fn get_tree_hash(arena: &Arena<DomHash>) -> DomHash {
+    use std::hash::{Hash, Hasher};
+    use twox_hash::XxHash;
+    let mut hasher = XxHash::default();
+    for node in &arena.nodes {
+        node.data.hash(&mut hasher);
+        encode_node_id(node.parent).hash(&mut hasher);
+    }
+    DomHash(hasher.finish())
+}
+
+fn encode_node_id(node_id: Option<NodeId>) -> u64 {
+    node_id.map(|node_id| node_id.index.get() as u64 + 1).unwrap_or(0)
+}
+
+fn decode_node_id(number: u64, nodes_len: u64) -> Result<Option<NodeId>, CacheError> {
+    match number {
+        0 => Ok(None),
+        n if n <= nodes_len => Ok(Some(NodeId::new((n - 1) as usize))),
+        n => Err(CacheError::InvalidNodeId(n - 1)),
+    }
+}
+
+fn write_u64(data: &mut Vec<u8>, number: u64) {
+    data.extend((0..8).map(|byte| (number >> (byte * 8)) as u8));
+}
+
+fn read_u64(bytes: &[u8]) -> Option<u64> {
+    if bytes.len() != 8 {
+        return None;
+    }
+    Some(bytes.iter().rev().fold(0_u64, |number, byte| (number << 8) | *byte as u64))
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub(crate) struct HashedDomTree {
     pub(crate) arena: Arena<DomHash>,
@@ -265,7 +507,8 @@ pub(crate) struct DomNodeHash {
 
 #[derive(Debug)]
 pub(crate) struct EditVariableCache {
-    pub(crate) map: BTreeMap<DomHash, (bool, DisplayRect)>
+    /// The edit variables of each hash, along with the number of nodes in the DOM that have this hash
+    pub(crate) map: BTreeMap<DomHash, (usize, DisplayRect)>
 }
 
 impl EditVariableCache {
@@ -275,40 +518,92 @@ impl EditVariableCache {
         }
     }
 
-    pub(crate) fn initialize_new_rectangles(&mut self, solver: &mut Solver, rects: &DomChangeSet) {
+    /// Adds the edit variables of the added nodes to the solver and removes the edit variables
+    /// that aren't used by any node anymore. The variables of unchanged nodes aren't touched.
+    pub(crate) fn apply_diff(&mut self, solver: &mut Solver, diff: &DomChangeSet) {
         use std::collections::btree_map::Entry::*;
 
-        for dom_hash in rects.added_nodes.values() {
-
-            let map_entry = self.map.entry(*dom_hash);
-            match map_entry {
+        // add first, so that the variables of a node that only moved to another index are kept
+        for dom_hash in diff.added_nodes.values() {
+            match self.map.entry(*dom_hash) {
                 Occupied(e) => {
-                    e.into_mut().0 = true;
+                    e.into_mut().0 += 1;
                 },
                 Vacant(e) => {
                     let rect = DisplayRect::default();
                     rect.add_to_solver(solver);
-                    e.insert((true, rect));
+                    e.insert((1, rect));
                 }
             }
         }
-    }
 
-    /// Last step of the caching algorithm:
-    /// Remove all edit variables where the `bool` is set to false
-    pub(crate) fn remove_unused_variables(&mut self, solver: &mut Solver) {
+        for dom_hash in diff.removed_nodes.values() {
+            let is_unused = match self.map.get_mut(dom_hash) {
+                Some(&mut (ref mut node_count, _)) => {
+                    *node_count = node_count.saturating_sub(1);
+                    *node_count == 0
+                },
+                None => false,
+            };
+            if is_unused {
+                if let Some((_, rect)) = self.map.remove(dom_hash) {
+                    rect.remove_from_solver(solver);
+                }
+            }
+        }
+    }
 
-        let mut to_be_removed = Vec::<DomHash>::new();
+    /// Suggests the new width / height of a node (after the user has dragged
+    /// the resize handle) to the solver. Does nothing if the node has no edit variables.
+    ///
+    /// The edit variables of the nodes are `WEAK`, so the dragged size would lose against the
+    /// size from the CSS: the edit variable is replaced by one with the `RESIZE_STRENGTH`,
+    /// which keeps the dragged size until the node is removed from the DOM.
+    pub(crate) fn apply_resize_interaction(&self, solver: &mut Solver, dom_hash: &DomHash, interaction: &ResizeInteraction) {
+        let display_rect = match self.map.get(dom_hash) {
+            Some(&(_, rect)) => rect,
+            None => return,
+        };
+
+        if let Some(new_width) = interaction.new_width {
+            suggest_resized_value(solver, display_rect.width, new_width);
+        }
 
-        for (key, &(active, variable_rect)) in &self.map {
-            if !active {
-                variable_rect.remove_from_solver(solver);
-                to_be_removed.push(*key);
-            }
+        if let Some(new_height) = interaction.new_height {
+            suggest_resized_value(solver, display_rect.height, new_height);
         }
+    }
+}
+
+fn suggest_resized_value(solver: &mut Solver, variable: Variable, value: f32) {
+    solver.remove_edit_variable(variable).unwrap_or_else(|_e| { });
+    solver.add_edit_variable(variable, RESIZE_STRENGTH).unwrap_or_else(|_e| { });
+    solver.suggest_value(variable, value as f64).unwrap_or_else(|_e| { });
+}
 
-        for hash in &to_be_removed {
-            self.map.remove(hash);
+/// Fired when the user drags the resize handle of a node that has the `resize` property set
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct ResizeInteraction {
+    /// New width of the node, `None` if the width should stay the same
+    pub(crate) new_width: Option<f32>,
+    /// New height of the node, `None` if the height should stay the same
+    pub(crate) new_height: Option<f32>,
+}
+
+impl ResizeInteraction {
+    /// Calculates the new size of the node from the size when the drag started and how
+    /// far the mouse has been dragged since then. Respects the allowed resize direction.
+    pub(crate) fn from_drag(resize: LayoutResize, initial_size: (f32, f32), drag_delta: (f32, f32)) -> Self {
+        use css_parser::LayoutResize::*;
+
+        let new_width = (initial_size.0 + drag_delta.0).max(0.0);
+        let new_height = (initial_size.1 + drag_delta.1).max(0.0);
+
+        match resize {
+            None => Self { new_width: Option::None, new_height: Option::None },
+            Both => Self { new_width: Some(new_width), new_height: Some(new_height) },
+            Horizontal => Self { new_width: Some(new_width), new_height: Option::None },
+            Vertical => Self { new_width: Option::None, new_height: Some(new_height) },
         }
     }
 }
@@ -319,4 +614,147 @@ impl EditVariableCache {
 #[test]
 fn __codecov_test_cache_file() {
 
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_resize_interaction_overrides_the_css_size() {
+    use cassowary::WeightedRelation::EQ;
+
+    let mut solver = Solver::new();
+    let mut edit_variable_cache = EditVariableCache::empty();
+    let dom_hash = DomHash(1);
+    let rect = DisplayRect::default();
+    rect.add_to_solver(&mut solver);
+    edit_variable_cache.map.insert(dom_hash, (1, rect));
+
+    // the size from the CSS, see `create_layout_constraints()`
+    solver.add_constraint(rect.width | EQ(STRONG) | 200.0).unwrap();
+    solver.add_constraint(rect.height | EQ(STRONG) | 200.0).unwrap();
+
+    let interaction = ResizeInteraction::from_drag(LayoutResize::Horizontal, (200.0, 200.0), (50.0, 30.0));
+    edit_variable_cache.apply_resize_interaction(&mut solver, &dom_hash, &interaction);
+
+    assert_eq!(solver.get_value(rect.width), 250.0);
+    assert_eq!(solver.get_value(rect.height), 200.0);
+}
+
+#[test]
+fn test_resize_interaction_from_drag() {
+    let interaction = ResizeInteraction::from_drag(LayoutResize::Horizontal, (100.0, 50.0), (20.0, 30.0));
+    assert_eq!(interaction, ResizeInteraction { new_width: Some(120.0), new_height: None });
+}
+
+#[test]
+fn test_dom_memo_cache() {
+
+    use dom::{NodeType, TestLayout};
+
+    let mut builder_calls = 0;
+    for _ in 0..2 {
+        let dom = Dom::<TestLayout>::memoize(5, || {
+            builder_calls += 1;
+            Dom::new(NodeType::Div).with_id("memoized")
+        });
+        assert_eq!(dom.arena.borrow()[dom.root].data.id, Some(String::from("memoized")));
+        DomMemoCache::remove_unused();
+    }
+    assert_eq!(builder_calls, 1);
+
+    // the key wasn't used in the last frame, so the DOM is removed
+    DomMemoCache::remove_unused();
+    assert!(DomMemoCache::get::<TestLayout>(5).is_none());
+}
+
+#[test]
+fn test_dom_memo_cache_isolated() {
+
+    use dom::{NodeType, TestLayout};
+
+    DomMemoCache::insert(1, &Dom::<TestLayout>::new(NodeType::Div));
+
+    DomMemoCache::isolated(|| {
+        assert!(DomMemoCache::get::<TestLayout>(1).is_none());
+        DomMemoCache::insert(2, &Dom::<TestLayout>::new(NodeType::Div));
+    });
+
+    assert!(DomMemoCache::get::<TestLayout>(1).is_some());
+    assert!(DomMemoCache::get::<TestLayout>(2).is_none());
+}
+
+#[test]
+fn test_dom_tree_cache_serialization() {
+
+    use dom::{NodeType, TestLayout};
+
+    let dom = Dom::<TestLayout>::new(NodeType::Div)
+        .with_child(Dom::new(NodeType::Div).with_id("a"))
+        .with_child(Dom::new(NodeType::Div).with_id("b"));
+
+    let mut cache = DomTreeCache::empty();
+    cache.update(dom.root, &*dom.arena.borrow());
+
+    let data = cache.serialize();
+    let restored = DomTreeCache::deserialize(&data).unwrap();
+    assert_eq!(restored.previous_layout, cache.previous_layout);
+
+    assert_eq!(DomTreeCache::deserialize(b"not a cache"), Err(CacheError::InvalidHeader));
+    assert_eq!(DomTreeCache::deserialize(&data[..data.len() - 4]), Err(CacheError::UnexpectedEnd));
+
+    let mut modified = data.clone();
+    let last = modified.len() - 1;
+    modified[last] ^= 1;
+    assert_eq!(DomTreeCache::deserialize(&modified), Err(CacheError::HashMismatch));
+}
+
+#[test]
+fn test_dom_tree_cache_update_unchanged_dom() {
+
+    use dom::{NodeType, TestLayout};
+
+    let dom = Dom::<TestLayout>::new(NodeType::Div)
+        .with_child(Dom::new(NodeType::Div).with_id("a"))
+        .with_child(Dom::new(NodeType::Div).with_id("b"));
+
+    let mut cache = DomTreeCache::empty();
+    assert_eq!(cache.update(dom.root, &*dom.arena.borrow()).added_nodes.len(), 3);
+    assert_eq!(cache.update(dom.root, &*dom.arena.borrow()), DomChangeSet::empty());
+
+    // the restored nodes are added to the solver once, the first update doesn't add them again
+    let mut restored = DomTreeCache::deserialize(&cache.serialize()).unwrap();
+    assert_eq!(restored.get_restored_nodes().added_nodes.len(), 3);
+    assert_eq!(restored.update(dom.root, &*dom.arena.borrow()), DomChangeSet::empty());
+}
+
+#[test]
+fn test_edit_variable_cache_apply_diff() {
+
+    use dom::{NodeType, TestLayout};
+
+    let dom_1 = Dom::<TestLayout>::new(NodeType::Div)
+        .with_child(Dom::new(NodeType::Div).with_id("a"))
+        .with_child(Dom::new(NodeType::Div).with_id("b"));
+    let dom_2 = Dom::<TestLayout>::new(NodeType::Div)
+        .with_child(Dom::new(NodeType::Div).with_id("a"));
+
+    let mut solver = Solver::new();
+    let mut dom_tree_cache = DomTreeCache::empty();
+    let mut edit_variable_cache = EditVariableCache::empty();
+
+    let diff = dom_tree_cache.update(dom_1.root, &*dom_1.arena.borrow());
+    edit_variable_cache.apply_diff(&mut solver, &diff);
+    assert_eq!(edit_variable_cache.map.len(), 3);
+
+    let hash_of = |cache: &DomTreeCache, idx: usize| cache.previous_layout.arena[NodeId::new(idx)].data;
+    let root_hash = hash_of(&dom_tree_cache, 0);
+    let b_hash = hash_of(&dom_tree_cache, 2);
+    let root_variables = edit_variable_cache.map[&root_hash].1.width;
+
+    // removing "b" only removes the variables of "b"
+    let diff = dom_tree_cache.update(dom_2.root, &*dom_2.arena.borrow());
+    assert!(diff.added_nodes.is_empty());
+    assert_eq!(diff.removed_nodes.values().cloned().collect::<Vec<DomHash>>(), vec![b_hash]);
+    edit_variable_cache.apply_diff(&mut solver, &diff);
+    assert_eq!(edit_variable_cache.map.len(), 2);
+    assert!(!edit_variable_cache.map.contains_key(&b_hash));
+    assert_eq!(edit_variable_cache.map[&root_hash].1.width, root_variables);
+}
@@ -0,0 +1,363 @@
+//! CPU-rasterized 2D canvas (similar to the HTML5 `<canvas>` 2D context),
+//! for procedural graphics that are too complex to express via the DOM or SVG.
+//!
+//! The canvas is rasterized on the CPU (using `raqote`) and then uploaded
+//! as a GPU texture via `Canvas2d::to_texture`.
+
+use std::f32::consts::PI;
+use raqote::{
+    DrawTarget, PathBuilder, Path, Source, SolidSource, DrawOptions,
+    StrokeStyle, LineCap, LineJoin, Image, Transform,
+};
+use rusttype::{Font, Scale, point};
+use webrender::api::ColorU;
+use {
+    dom::Texture,
+    window::ReadOnlyWindow,
+    svg::{SvgLineCap, SvgLineJoin},
+    font::{self, FontError},
+};
+
+/// 2D affine transform, in the same layout as the HTML5 canvas `setTransform(a, b, c, d, e, f)`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform2d {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Default for Transform2d {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform2d {
+    pub fn identity() -> Self {
+        Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 0.0, f: 0.0 }
+    }
+
+    pub fn translation(x: f32, y: f32) -> Self {
+        Self { e: x, f: y, .. Self::identity() }
+    }
+
+    pub fn scale(x: f32, y: f32) -> Self {
+        Self { a: x, d: y, .. Self::identity() }
+    }
+
+    /// Rotation, in degrees (clockwise, since the y axis points down)
+    pub fn rotation(degrees: f32) -> Self {
+        let (sin, cos) = (degrees * PI / 180.0).sin_cos();
+        Self { a: cos, b: sin, c: -sin, d: cos, e: 0.0, f: 0.0 }
+    }
+
+    /// Returns the transform that first applies `self`, then `other`
+    pub fn then(&self, other: &Transform2d) -> Self {
+        Self {
+            a: self.a * other.a + self.b * other.c,
+            b: self.a * other.b + self.b * other.d,
+            c: self.c * other.a + self.d * other.c,
+            d: self.c * other.b + self.d * other.d,
+            e: self.e * other.a + self.f * other.c + other.e,
+            f: self.e * other.b + self.f * other.d + other.f,
+        }
+    }
+
+    fn into_raqote(&self) -> Transform {
+        Transform::row_major(self.a, self.b, self.c, self.d, self.e, self.f)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum PathOp {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    QuadTo(f32, f32, f32, f32),
+    CubicTo(f32, f32, f32, f32, f32, f32),
+    Close,
+}
+
+/// Path that can be filled or stroked on a `Canvas2d`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Path2d {
+    ops: Vec<PathOp>,
+}
+
+impl Path2d {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn move_to(mut self, x: f32, y: f32) -> Self {
+        self.ops.push(PathOp::MoveTo(x, y));
+        self
+    }
+
+    pub fn line_to(mut self, x: f32, y: f32) -> Self {
+        self.ops.push(PathOp::LineTo(x, y));
+        self
+    }
+
+    pub fn quad_to(mut self, cx: f32, cy: f32, x: f32, y: f32) -> Self {
+        self.ops.push(PathOp::QuadTo(cx, cy, x, y));
+        self
+    }
+
+    pub fn cubic_to(mut self, c1x: f32, c1y: f32, c2x: f32, c2y: f32, x: f32, y: f32) -> Self {
+        self.ops.push(PathOp::CubicTo(c1x, c1y, c2x, c2y, x, y));
+        self
+    }
+
+    pub fn close(mut self) -> Self {
+        self.ops.push(PathOp::Close);
+        self
+    }
+
+    pub fn rect(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Path2d::new()
+            .move_to(x, y)
+            .line_to(x + width, y)
+            .line_to(x + width, y + height)
+            .line_to(x, y + height)
+            .close()
+    }
+
+    fn build(&self) -> Path {
+        use self::PathOp::*;
+        let mut builder = PathBuilder::new();
+        for op in &self.ops {
+            match *op {
+                MoveTo(x, y) => builder.move_to(x, y),
+                LineTo(x, y) => builder.line_to(x, y),
+                QuadTo(cx, cy, x, y) => builder.quad_to(cx, cy, x, y),
+                CubicTo(c1x, c1y, c2x, c2y, x, y) => builder.cubic_to(c1x, c1y, c2x, c2y, x, y),
+                Close => builder.close(),
+            }
+        }
+        builder.finish()
+    }
+}
+
+/// Font + font size for `Canvas2d::fill_text`
+pub struct Canvas2dFont {
+    font: Font<'static>,
+    /// Font size in pixels
+    pub size: f32,
+}
+
+impl Canvas2dFont {
+    /// Parses the font from the raw bytes of a TTF / OTF file
+    pub fn new(data: Vec<u8>, size: f32) -> Result<Self, FontError> {
+        Ok(Self {
+            font: font::rusttype_load_font(data)?,
+            size: size,
+        })
+    }
+}
+
+/// CPU-backed 2D drawing surface. Draw calls are immediate, i.e. they are
+/// rasterized into the pixel buffer right away. Call `to_texture` to upload
+/// the final image to the GPU.
+pub struct Canvas2d {
+    target: DrawTarget,
+    transform: Transform2d,
+    /// Color for the `fill_*` functions, default: black
+    pub fill_color: ColorU,
+    /// Color for the `stroke_*` functions, default: black
+    pub stroke_color: ColorU,
+}
+
+impl Canvas2d {
+    /// Creates a new (fully transparent) canvas
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            target: DrawTarget::new(width as i32, height as i32),
+            transform: Transform2d::identity(),
+            fill_color: ColorU { r: 0, g: 0, b: 0, a: 255 },
+            stroke_color: ColorU { r: 0, g: 0, b: 0, a: 255 },
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.target.width() as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        self.target.height() as u32
+    }
+
+    /// Returns whether the canvas has a width or height of zero (and so no pixels)
+    pub fn is_empty(&self) -> bool {
+        self.width() == 0 || self.height() == 0
+    }
+
+    /// Sets the transform for all following draw calls
+    pub fn set_transform(&mut self, transform: Transform2d) {
+        self.transform = transform;
+        self.target.set_transform(&transform.into_raqote());
+    }
+
+    pub fn get_transform(&self) -> Transform2d {
+        self.transform
+    }
+
+    /// Resets every pixel of the canvas to the given color
+    pub fn clear(&mut self, color: ColorU) {
+        let color = solid_source(color);
+        self.target.clear(color);
+    }
+
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.fill_path(&Path2d::rect(x, y, width, height));
+    }
+
+    pub fn stroke_rect(&mut self, x: f32, y: f32, width: f32, height: f32, stroke_width: f32) {
+        self.stroke_path(&Path2d::rect(x, y, width, height), stroke_width, SvgLineCap::Butt, SvgLineJoin::Miter);
+    }
+
+    pub fn fill_circle(&mut self, center_x: f32, center_y: f32, radius: f32) {
+        let path = circle_path(center_x, center_y, radius);
+        self.target.fill(&path, &Source::Solid(solid_source(self.fill_color)), &DrawOptions::new());
+    }
+
+    pub fn stroke_circle(&mut self, center_x: f32, center_y: f32, radius: f32, stroke_width: f32) {
+        let path = circle_path(center_x, center_y, radius);
+        let style = stroke_style(stroke_width, SvgLineCap::Butt, SvgLineJoin::Miter);
+        self.target.stroke(&path, &Source::Solid(solid_source(self.stroke_color)), &style, &DrawOptions::new());
+    }
+
+    pub fn fill_path(&mut self, path: &Path2d) {
+        self.target.fill(&path.build(), &Source::Solid(solid_source(self.fill_color)), &DrawOptions::new());
+    }
+
+    pub fn stroke_path(&mut self, path: &Path2d, stroke_width: f32, cap: SvgLineCap, join: SvgLineJoin) {
+        let style = stroke_style(stroke_width, cap, join);
+        self.target.stroke(&path.build(), &Source::Solid(solid_source(self.stroke_color)), &style, &DrawOptions::new());
+    }
+
+    /// Downloads the texture from the GPU and draws it (scaled) at the given position
+    pub fn draw_image(&mut self, texture: &Texture, x: f32, y: f32, width: f32, height: f32) {
+        // OpenGL textures are stored bottom-to-top, so the rows have to be reversed
+        let rows: Vec<Vec<(u8, u8, u8, u8)>> = texture.inner.read();
+        let image_height = rows.len();
+        let image_width = rows.first().map(|r| r.len()).unwrap_or(0);
+        if image_width == 0 || image_height == 0 {
+            return;
+        }
+
+        let data = rows.iter().rev().flat_map(|row| row.iter().map(|&(r, g, b, a)| {
+            premultiplied_argb(ColorU { r, g, b, a })
+        })).collect::<Vec<u32>>();
+
+        let image = Image { width: image_width as i32, height: image_height as i32, data: &data };
+        self.target.draw_image_with_size_at(width, height, x, y, &image, &DrawOptions::new());
+    }
+
+    /// Draws the text with the current `fill_color`, `y` is the position of the baseline
+    pub fn fill_text(&mut self, text: &str, x: f32, y: f32, font: &Canvas2dFont) {
+        let color = self.fill_color;
+        for glyph in font.font.layout(text, Scale::uniform(font.size), point(x, y)) {
+            let bounds = match glyph.pixel_bounding_box() {
+                Some(b) => b,
+                None => continue, // whitespace
+            };
+
+            let glyph_width = (bounds.max.x - bounds.min.x) as usize;
+            let glyph_height = (bounds.max.y - bounds.min.y) as usize;
+            let mut data = vec![0_u32; glyph_width * glyph_height];
+
+            glyph.draw(|gx, gy, coverage| {
+                let alpha = (color.a as f32 * coverage).round() as u8;
+                data[gy as usize * glyph_width + gx as usize] = premultiplied_argb(ColorU { a: alpha, .. color });
+            });
+
+            let image = Image { width: glyph_width as i32, height: glyph_height as i32, data: &data };
+            self.target.draw_image_at(bounds.min.x as f32, bounds.min.y as f32, &image, &DrawOptions::new());
+        }
+    }
+
+    /// Returns the pixels of the canvas as (non-premultiplied) RGBA bytes, starting at the top left
+    pub fn get_rgba_pixels(&self) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(self.target.get_data().len() * 4);
+        for pixel in self.target.get_data() {
+            let a = (pixel >> 24) as u8;
+            let unpremultiply = |c: u32| -> u8 {
+                if a == 0 { 0 } else { ((c & 0xff) * 255 / a as u32) as u8 }
+            };
+            pixels.push(unpremultiply(pixel >> 16));
+            pixels.push(unpremultiply(pixel >> 8));
+            pixels.push(unpremultiply(*pixel));
+            pixels.push(a);
+        }
+        pixels
+    }
+
+    /// Uploads the current contents of the canvas to the GPU. Returns `None` if the
+    /// canvas is empty or larger than the maximum texture size of the GPU.
+    pub fn to_texture(&self, window: &ReadOnlyWindow) -> Option<Texture> {
+        use glium::texture::{RawImage2d, texture2d::Texture2d};
+
+        if self.is_empty() {
+            return None;
+        }
+
+        let pixels = self.get_rgba_pixels();
+        let raw_image = RawImage2d::from_raw_rgba_reversed(&pixels, (self.width(), self.height()));
+        Texture2d::new(&*window.inner, raw_image).ok().map(Texture::new)
+    }
+}
+
+fn circle_path(center_x: f32, center_y: f32, radius: f32) -> Path {
+    let mut builder = PathBuilder::new();
+    builder.arc(center_x, center_y, radius, 0.0, 2.0 * PI);
+    builder.close();
+    builder.finish()
+}
+
+fn stroke_style(width: f32, cap: SvgLineCap, join: SvgLineJoin) -> StrokeStyle {
+    StrokeStyle {
+        width: width,
+        cap: match cap {
+            SvgLineCap::Butt => LineCap::Butt,
+            SvgLineCap::Square => LineCap::Square,
+            SvgLineCap::Round => LineCap::Round,
+        },
+        join: match join {
+            SvgLineJoin::Miter | SvgLineJoin::MiterClip => LineJoin::Miter,
+            SvgLineJoin::Round => LineJoin::Round,
+            SvgLineJoin::Bevel => LineJoin::Bevel,
+        },
+        .. StrokeStyle::default()
+    }
+}
+
+fn solid_source(color: ColorU) -> SolidSource {
+    let premultiply = |c: u8| ((c as u32 * color.a as u32) / 255) as u8;
+    SolidSource { r: premultiply(color.r), g: premultiply(color.g), b: premultiply(color.b), a: color.a }
+}
+
+fn premultiplied_argb(color: ColorU) -> u32 {
+    let c = solid_source(color);
+    ((c.a as u32) << 24) | ((c.r as u32) << 16) | ((c.g as u32) << 8) | c.b as u32
+}
+
+#[test]
+fn test_canvas_fill_rect() {
+    let mut canvas = Canvas2d::new(4, 4);
+    canvas.fill_color = ColorU { r: 255, g: 0, b: 0, a: 255 };
+    canvas.fill_rect(0.0, 0.0, 2.0, 4.0);
+    let pixels = canvas.get_rgba_pixels();
+    assert_eq!(&pixels[0..4], &[255, 0, 0, 255]);
+    assert_eq!(&pixels[12..16], &[0, 0, 0, 0]);
+}
+
+#[test]
+fn test_zero_sized_canvas() {
+    let canvas = Canvas2d::new(0, 4);
+    assert!(canvas.is_empty());
+    assert!(canvas.get_rgba_pixels().is_empty());
+    assert!(!Canvas2d::new(1, 1).is_empty());
+}
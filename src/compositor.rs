@@ -14,6 +14,8 @@ use glium::{
     backend::Facade,
 };
 use euclid::TypedPoint2D;
+#[cfg(unix)]
+use std::os::unix::io::RawFd;
 use {
     FastHashMap, FastHashSet,
     dom::Texture,
@@ -29,6 +31,66 @@ lazy_static! {
     /// texture) does not run until we remove the textures
     pub(crate) static ref ACTIVE_GL_TEXTURES: Mutex<FastHashMap<ExternalImageId, ActiveTexture>> = Mutex::new(FastHashMap::default());
     pub(crate) static ref TO_DELETE_TEXTURES: Mutex<FastHashSet<ExternalImageId>> = Mutex::new(FastHashSet::default());
+    /// Pixel data that was sent via a `TextureUpdateSender`, written into the texture the next
+    /// time that webrender draws it. Only the newest update of each texture is kept.
+    static ref PENDING_TEXTURE_UPDATES: Arc<Mutex<FastHashMap<ExternalImageId, TextureUpdate>>> = Arc::new(Mutex::new(FastHashMap::default()));
+}
+
+#[cfg(unix)]
+lazy_static! {
+    /// Images that are shared with another process via shared memory. Re-read
+    /// on every frame when webrender locks the image.
+    pub(crate) static ref SHARED_MEMORY_IMAGES: Mutex<FastHashMap<ExternalImageId, SharedMemoryExternalImage>> = Mutex::new(FastHashMap::default());
+}
+
+/// Image (BGRA8) that lives in a shared memory region, usually written to by a
+/// seperate rendering process. On Linux, the file descriptor can be created via `memfd_create`.
+///
+/// The image is displayed via `NodeType::SharedMemoryImage`, the compositor re-reads the
+/// shared memory every time webrender draws a frame - so after the other process has
+/// written a new frame, a daemon or a callback only has to return `UpdateScreen::Redraw`.
+/// The `key` has to be unique among all external images (the `Texture`s use their
+/// OpenGL ID as the key, so better use large numbers).
+#[cfg(unix)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SharedMemoryExternalImage {
+    pub key: ExternalImageId,
+    /// File descriptor of the shared memory. Azul does not take ownership of the descriptor.
+    pub shm_fd: RawFd,
+    pub width: u32,
+    pub height: u32,
+    /// Bytes per row (can be larger than `width * 4` because of padding)
+    pub stride: u32,
+}
+
+/// Where the compositor should get the contents of an external image from
+#[derive(Debug)]
+pub(crate) enum CompositorImageSource {
+    /// OpenGL texture, drawn by the application itself
+    GlTexture(Texture),
+    /// Shared memory region from another process
+    #[cfg(unix)]
+    SharedMemory(SharedMemoryExternalImage),
+}
+
+/// Registers an external image, so that the compositor can find it when webrender
+/// draws the frame. The image stays registered until `unregister_external_image` is called.
+pub(crate) fn register_external_image(key: ExternalImageId, source: CompositorImageSource) {
+    match source {
+        CompositorImageSource::GlTexture(texture) => {
+            ACTIVE_GL_TEXTURES.lock().unwrap().insert(key, ActiveTexture { texture: texture });
+        },
+        #[cfg(unix)]
+        CompositorImageSource::SharedMemory(image) => {
+            SHARED_MEMORY_IMAGES.lock().unwrap().insert(key, image);
+        },
+    }
+}
+
+pub(crate) fn unregister_external_image(key: &ExternalImageId) {
+    ACTIVE_GL_TEXTURES.lock().unwrap().remove(key);
+    #[cfg(unix)]
+    SHARED_MEMORY_IMAGES.lock().unwrap().remove(key);
 }
 
 /// The Texture struct is public to the user
@@ -45,12 +107,114 @@ pub(crate) struct ActiveTexture {
 unsafe impl Send for ActiveTexture { }
 unsafe impl Sync for ActiveTexture { }
 
+/// New contents of a texture, see `TextureUpdateSender::update`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TextureUpdate {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+/// Sends new pixel data for `Texture`s from any thread (i.e. decoded video frames),
+/// without blocking on the renderer. Created by `Compositor::create_update_channel()`.
+#[derive(Debug, Clone)]
+pub struct TextureUpdateSender {
+    updates: Arc<Mutex<FastHashMap<ExternalImageId, TextureUpdate>>>,
+}
+
+impl TextureUpdateSender {
+    /// Replaces the contents of the texture with the `key` (see `Texture::external_image_id()`)
+    /// the next time a frame is drawn. `rgba` are the RGBA8 pixels, from the top to the bottom
+    /// row, `width` and `height` have to match the size of the texture.
+    ///
+    /// If the texture is updated multiple times before the next frame, only the last update
+    /// is drawn. The update doesn't redraw the window by itself, a daemon or a callback has
+    /// to return `UpdateScreen::Redraw`.
+    pub fn update(&self, key: ExternalImageId, rgba: Vec<u8>, width: u32, height: u32) {
+        self.updates.lock().unwrap().insert(key, TextureUpdate { rgba: rgba, width: width, height: height });
+    }
+}
+
+/// Writes the update into the texture, if the size of the update matches the size of the texture
+fn apply_texture_update(key: ExternalImageId, texture: &Texture, update: TextureUpdate) {
+    use glium::{Rect, texture::RawImage2d};
+
+    let (width, height) = (texture.inner.width(), texture.inner.height());
+    if update.width != width || update.height != height || update.rgba.len() != (width * height * 4) as usize {
+        warn!("ignoring the update of texture {:?}: the update is {}x{} ({} bytes), the texture is {}x{}",
+              key, update.width, update.height, update.rgba.len(), width, height);
+        return;
+    }
+
+    // OpenGL expects the bottom row first
+    let image = RawImage2d::from_raw_rgba_reversed(&update.rgba, (width, height));
+    texture.inner.write(Rect { left: 0, bottom: 0, width: width, height: height }, image);
+}
+
+/// The `ExternalImageHandler` of webrender: hands the textures and shared memory images
+/// to webrender when a frame is drawn
 #[derive(Debug)]
-pub(crate) struct Compositor { }
+pub struct Compositor {
+    /// CPU-side copies of the shared memory images, webrender uploads them
+    /// to the GPU after `lock()` has returned
+    #[cfg(unix)]
+    shared_memory_buffers: FastHashMap<ExternalImageId, Vec<u8>>,
+}
 
 impl Default for Compositor {
     fn default() -> Self {
-        Self { }
+        Self {
+            #[cfg(unix)]
+            shared_memory_buffers: FastHashMap::default(),
+        }
+    }
+}
+
+impl Compositor {
+    /// Creates a sender for updating textures from background threads. The updates are
+    /// written into the textures when webrender locks them for drawing the next frame.
+    ///
+    /// The second element would be the receiving end of the channel - the compositor receives
+    /// the updates itself, so there is nothing to receive for the application.
+    pub fn create_update_channel() -> (TextureUpdateSender, ()) {
+        (TextureUpdateSender { updates: PENDING_TEXTURE_UPDATES.clone() }, ())
+    }
+}
+
+/// Reads the image from the shared memory into the `buffer`, removing the row padding.
+/// On failure, the buffer is filled with transparent pixels.
+#[cfg(unix)]
+fn read_shared_memory_image(image: &SharedMemoryExternalImage, buffer: &mut Vec<u8>) {
+    use std::fs::File;
+    use std::mem::ManuallyDrop;
+    use std::os::unix::{fs::FileExt, io::FromRawFd};
+
+    let row_len = image.width as usize * 4;
+    buffer.clear();
+    buffer.resize(row_len * image.height as usize, 0);
+
+    if buffer.is_empty() {
+        return;
+    }
+
+    // ManuallyDrop: the file descriptor belongs to the caller, so don't close it
+    let file = ManuallyDrop::new(unsafe { File::from_raw_fd(image.shm_fd) });
+
+    let mut read_failed = false;
+
+    for (row_idx, row) in buffer.chunks_mut(row_len).enumerate() {
+        let offset = row_idx as u64 * image.stride as u64;
+        if let Err(e) = file.read_exact_at(row, offset) {
+            warn!("could not read shared memory image {:?}: {}", image.key, e);
+            read_failed = true;
+            break;
+        }
+    }
+
+    if read_failed {
+        for byte in buffer.iter_mut() {
+            *byte = 0;
+        }
     }
 }
 
@@ -58,9 +222,29 @@ impl ExternalImageHandler for Compositor {
     fn lock(&mut self, key: ExternalImageId, _channel_index: u8) -> ExternalImage {
         use glium::GlObject;
 
+        #[cfg(unix)] {
+            let shared_memory_image = SHARED_MEMORY_IMAGES.lock().unwrap().get(&key).cloned();
+            if let Some(image) = shared_memory_image {
+                let buffer = self.shared_memory_buffers.entry(key).or_insert_with(Vec::new);
+                read_shared_memory_image(&image, buffer);
+                return ExternalImage {
+                    uv: TexelRect {
+                        uv0: TypedPoint2D::zero(),
+                        uv1: TypedPoint2D::<f32, DevicePixel>::new(image.width as f32, image.height as f32),
+                    },
+                    source: ExternalImageSource::RawData(&buffer[..]),
+                };
+            }
+        }
+
         let gl_tex_lock = ACTIVE_GL_TEXTURES.lock().unwrap();
         let tex = &gl_tex_lock[&key];
 
+        let pending_update = PENDING_TEXTURE_UPDATES.lock().unwrap().remove(&key);
+        if let Some(update) = pending_update {
+            apply_texture_update(key, &tex.texture, update);
+        }
+
         ExternalImage {
             uv: TexelRect {
                 uv0: TypedPoint2D::zero(),
@@ -82,4 +266,48 @@ impl ExternalImageHandler for Compositor {
 #[test]
 fn __codecov_test_compositor_file() {
 
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_texture_update_sender_keeps_newest_update() {
+    let (sender, ()) = Compositor::create_update_channel();
+    let key = ExternalImageId(::std::u64::MAX - 1);
+
+    sender.update(key, vec![0; 4], 1, 1);
+    sender.clone().update(key, vec![255; 4], 1, 1);
+
+    let update = PENDING_TEXTURE_UPDATES.lock().unwrap().remove(&key);
+    assert_eq!(update, Some(TextureUpdate { rgba: vec![255; 4], width: 1, height: 1 }));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shared_memory_image_is_reread_on_lock() {
+    use std::{fs::OpenOptions, io::{Seek, SeekFrom, Write}, os::unix::io::AsRawFd};
+
+    let path = ::std::env::temp_dir().join("azul_test_shared_memory_image");
+    let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&path).unwrap();
+
+    // 1x2 pixels, each row is padded to 8 bytes
+    file.write_all(&[1, 2, 3, 4, 0, 0, 0, 0, 5, 6, 7, 8, 0, 0, 0, 0]).unwrap();
+
+    let key = ExternalImageId(::std::u64::MAX - 2);
+    let image = SharedMemoryExternalImage { key, shm_fd: file.as_raw_fd(), width: 1, height: 2, stride: 8 };
+    register_external_image(key, CompositorImageSource::SharedMemory(image));
+
+    let mut compositor = Compositor::default();
+    let read_pixels = |compositor: &mut Compositor| match compositor.lock(key, 0).source {
+        ExternalImageSource::RawData(data) => data.to_vec(),
+        _ => panic!("shared memory image is not raw data"),
+    };
+
+    assert_eq!(read_pixels(&mut compositor), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+
+    // the other process writes a new frame
+    file.seek(SeekFrom::Start(8)).unwrap();
+    file.write_all(&[9, 10, 11, 12]).unwrap();
+    assert_eq!(read_pixels(&mut compositor), vec![1, 2, 3, 4, 9, 10, 11, 12]);
+
+    unregister_external_image(&key);
+    ::std::fs::remove_file(&path).unwrap();
+}
@@ -109,6 +109,12 @@ pub(crate) enum PaddingConstraint {
     MatchLayout(DisplayRect),
     MatchWidth(Variable),
     MatchHeight(Variable),
+    /// `rect.height == factor * width + padding`, for percentages that refer to a width
+    HeightFromWidth(Variable, f32),
+    /// `rect.height >= factor * width + padding`, for percentages that refer to a width
+    MinHeightFromWidth(Variable, f32),
+    /// `rect.width >= factor * width + padding`, for percentages that refer to a width
+    MinWidthFromWidth(Variable, f32),
 }
 
 impl SizeConstraint {
@@ -251,6 +257,15 @@ impl PaddingConstraint {
             MatchHeight(height) => {
                 vec![ height - rect.height | EQ(strength) | padding ]
             },
+            HeightFromWidth(width, factor) => {
+                vec![ rect.height - factor * width | EQ(strength) | padding ]
+            },
+            MinHeightFromWidth(width, factor) => {
+                vec![ rect.height - factor * width | GE(strength) | padding ]
+            },
+            MinWidthFromWidth(width, factor) => {
+                vec![ rect.width - factor * width | GE(strength) | padding ]
+            },
         }
     }
 }
@@ -1,10 +1,17 @@
 //! CSS parsing and styling
-use std::ops::Add;
+use std::{
+    ops::Add,
+    fmt,
+    fs,
+    path::Path,
+    rc::Rc,
+};
 use {
     FastHashMap,
     traits::IntoParsedCssProperty,
     css_parser::{ParsedCssProperty, CssParsingError},
     errors::CssSyntaxError,
+    id_tree::NodeId,
 };
 
 #[cfg(target_os="windows")]
@@ -36,6 +43,44 @@ pub struct Css {
     /// Ex. if only a background color has changed, we need to redraw, but we
     /// don't need to re-layout the frame
     pub(crate) needs_relayout: bool,
+    /// Which `@media` rules are currently active (`Screen` by default)
+    pub(crate) active_media: CssMediaType,
+    /// Whether the rules in `@media (forced-colors: active)` blocks are active,
+    /// set when the OS is in high contrast mode
+    pub(crate) forced_colors: bool,
+    /// Page margin in pixels, set via `@page { margin: 1in; }`. Only used for printing.
+    pub(crate) page_margin: Option<f32>,
+    /// The node that is currently pressed (with the mouse or the space bar),
+    /// this node is matched by the `:active` pseudo-class
+    pub(crate) active_node: Option<NodeId>,
+    /// The node that has the keyboard focus, matched by the `:focus` pseudo-class
+    pub(crate) focused_node: Option<NodeId>,
+    /// Whether a drag-and-drop is over the window, the nodes with an `On::Drop`
+    /// callback are matched by the `:-azul-drop-target` pseudo-class
+    pub(crate) is_drag_over: bool,
+}
+
+/// Media type of a CSS rule, set via `@media print { ... }`.
+/// Rules outside of a `@media` block apply to all media types.
+///
+/// `App::render_for_print()` renders the pages with the `Print` rules active, a window can
+/// also switch to them via `Css::set_media_type()`, i.e. to show a print preview.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CssMediaType {
+    All,
+    Screen,
+    Print,
+    /// `@media (forced-colors: active)`: Rules that only apply if the OS is in high contrast
+    /// mode. These rules override all other rules, regardless of their specificity.
+    ForcedColors,
+}
+
+impl CssMediaType {
+    /// Returns whether a rule with this media type should be applied
+    /// when rendering for the `active` media type
+    pub fn matches(&self, active: CssMediaType) -> bool {
+        *self == CssMediaType::All || active == CssMediaType::All || *self == active
+    }
 }
 
 /// Fake CSS that can be changed by the user
@@ -80,6 +125,20 @@ pub enum CssParseError<'a> {
     /// (Css is parsed eagerly, directly converted to strongly typed values
     /// as soon as possible)
     UnexpectedValue(CssParsingError<'a>),
+    /// Unknown `@`-rule or media type, such as `@keyframes` or `@media tv`
+    UnsupportedAtRule(&'a str),
+    /// Invalid declaration inside of a `@page` block, such as `@page { margin: abc; }`
+    InvalidPageRule(&'a str),
+    /// Unknown pseudo-element, such as `div::after`
+    UnsupportedPseudoElement(&'a str),
+    /// Pseudo-elements may only set one property, such as the `background-color` in
+    /// `.a::-azul-scrollbar-thumb { background-color: red; }` or the `content` of `::before`
+    InvalidPseudoElementRule(&'a str),
+    /// Unknown or malformed pseudo-class, such as `:hover` or `:nth-child(abc)`
+    UnsupportedPseudoClass(&'a str),
+    /// Rules with pseudo-classes or pseudo-elements may only have a simple selector
+    /// (`div#id.class:first-child`), combinators such as `div > p:first-child` are not supported
+    UnsupportedSelector(&'a str),
 }
 
 impl<'a> From<CssParsingError<'a>> for CssParseError<'a> {
@@ -94,6 +153,27 @@ impl<'a> From<DynamicCssParseError<'a>> for CssParseError<'a> {
     }
 }
 
+impl<'a> fmt::Display for CssParseError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CssParseError::*;
+        match self {
+            ParseError(e) => write!(f, "CSS syntax error: {:?}", e),
+            UnclosedBlock => write!(f, "Braces are not balanced properly"),
+            MalformedCss => write!(f, "Malformed CSS"),
+            DynamicCssParseError(e) => write!(f, "Invalid dynamic CSS property: {}", e),
+            UnexpectedValue(e) => write!(f, "Invalid CSS value: {:?}", e),
+            UnsupportedAtRule(rule) => write!(f, "Unsupported @-rule or media type: \"{}\"", rule),
+            InvalidPageRule(rule) => write!(f, "Invalid declaration in a @page block: \"{}\"", rule),
+            UnsupportedPseudoElement(element) => write!(f, "Unsupported pseudo-element: \"{}\"", element),
+            InvalidPseudoElementRule(rule) => write!(f, "Invalid pseudo-element rule: \"{}\"", rule),
+            UnsupportedPseudoClass(class) => write!(f, "Unsupported pseudo-class: \"{}\"", class),
+            UnsupportedSelector(selector) => write!(f, "Unsupported selector: \"{}\"", selector),
+        }
+    }
+}
+
+impl<'a> ::std::error::Error for CssParseError<'a> { }
+
 /// Rule that applies to some "path" in the CSS, i.e.
 /// `div#myid.myclass -> ("justify-content", "center")`
 ///
@@ -107,8 +187,110 @@ pub(crate) struct CssRule {
     pub id: Option<String>,
     /// `.myclass .myotherclass` (vec![] by default)
     pub classes: Vec<String>,
+    /// `:first-child :nth-child(2n+1)` (vec![] by default)
+    pub pseudo_classes: Vec<CssPseudoClass>,
     /// `("justify-content", "center")`
-    pub declaration: (String, CssDeclaration),
+    pub declaration: (Rc<str>, CssDeclaration),
+    /// `@media print { }` (`CssMediaType::All` by default)
+    pub media: CssMediaType,
+}
+
+/// Pseudo-class that restricts a rule to nodes at a certain position among their siblings
+/// or to nodes in a certain state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CssPseudoClass {
+    /// `:first-child`
+    FirstChild,
+    /// `:last-child`
+    LastChild,
+    /// `:only-child`
+    OnlyChild,
+    /// `:first-of-type`
+    FirstOfType,
+    /// `:last-of-type`
+    LastOfType,
+    /// `:nth-child(2n+1)`
+    NthChild(CssNthChildPattern),
+    /// `:nth-of-type(2n+1)`
+    NthOfType(CssNthChildPattern),
+    /// `:not(.disabled)` or `:not(.a, .b)`, matches if none of the selectors match
+    Not(Vec<CssNegatedSelector>),
+    /// `:active`, matches the node while a mouse button (or the space bar) is pressed on it
+    Active,
+    /// `:focus`, matches the node that has the keyboard focus, see `FakeWindow::set_focus()`
+    Focus,
+    /// `:disabled`, matches nodes that were disabled via `Dom::with_disabled(true)`
+    Disabled,
+    /// `:placeholder-shown`, matches empty text inputs that show their placeholder text
+    PlaceholderShown,
+    /// `:-azul-drop-target`, matches the nodes with an `On::Drop` callback while
+    /// a drag-and-drop is over the window
+    DropTarget,
+}
+
+/// Selector inside of a `:not()`, such as `button.disabled` or `:first-child`.
+/// May not contain another `:not()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CssNegatedSelector {
+    /// `button` (`*` by default)
+    pub html_type: String,
+    /// `#myid` (`None` by default)
+    pub id: Option<String>,
+    /// `.disabled` (vec![] by default)
+    pub classes: Vec<String>,
+    /// `:first-child` (vec![] by default)
+    pub pseudo_classes: Vec<CssPseudoClass>,
+}
+
+/// The `an+b` argument of `:nth-child()` / `:nth-of-type()`, i.e. `odd` = `2n+1`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct CssNthChildPattern {
+    /// `a`, the node matches every `a`-th sibling
+    pub repeat: i32,
+    /// `b`, the index of the first matching sibling (starts at 1)
+    pub offset: i32,
+}
+
+impl CssNthChildPattern {
+    /// Parses `odd`, `even`, `3`, `3n`, `2n+1`, `-n+3`, etc.
+    pub fn from_str(input: &str) -> Option<Self> {
+        let input = input.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+
+        match input.as_str() {
+            "odd" => return Some(CssNthChildPattern { repeat: 2, offset: 1 }),
+            "even" => return Some(CssNthChildPattern { repeat: 2, offset: 0 }),
+            _ => { },
+        }
+
+        let n_pos = match input.find('n') {
+            Some(n) => n,
+            None => return input.parse::<i32>().ok().map(|offset| CssNthChildPattern { repeat: 0, offset: offset }),
+        };
+
+        let repeat = match &input[..n_pos] {
+            "" | "+" => 1,
+            "-" => -1,
+            repeat => repeat.parse::<i32>().ok()?,
+        };
+
+        let offset = match &input[(n_pos + 1)..] {
+            "" => 0,
+            offset if offset.starts_with('+') || offset.starts_with('-') => offset.parse::<i32>().ok()?,
+            _ => return None,
+        };
+
+        Some(CssNthChildPattern { repeat: repeat, offset: offset })
+    }
+
+    /// Returns whether the sibling at `index` (1-based) is selected by this pattern
+    pub fn matches(&self, index: usize) -> bool {
+        let index = index as i32;
+        if self.repeat == 0 {
+            return index == self.offset;
+        }
+        let distance = index - self.offset;
+        distance % self.repeat == 0 && distance / self.repeat >= 0
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -155,107 +337,135 @@ impl Css {
             rules: Vec::new(),
             needs_relayout: false,
             dynamic_css_overrides: FastHashMap::default(),
+            active_media: CssMediaType::Screen,
+            forced_colors: false,
+            page_margin: None,
+            active_node: None,
+            focused_node: None,
+            is_drag_over: false,
         }
     }
 
     /// Parses a CSS string (single-threaded) and returns the parsed rules
     pub fn new_from_string<'a>(css_string: &'a str) -> Result<Self, CssParseError<'a>> {
-        use simplecss::{Tokenizer, Token};
-        use std::collections::HashSet;
 
-        let mut tokenizer = Tokenizer::new(css_string);
-
-        let mut block_nesting = 0_usize;
         let mut css_rules = Vec::<CssRule>::new();
+        let mut page_margin = None;
 
-        // TODO: For now, rules may not be nested, otherwise, this won't work
-        // TODO: This could be more efficient. We don't even need to clone the
-        // strings, but this is just a quick-n-dirty CSS parser
-        // This will also use up a lot of memory, since the strings get duplicated
-
-        let mut parser_in_block = false;
-        let mut current_type = "*";
-        let mut current_id = None;
-        let mut current_classes = HashSet::<&str>::new();
-
-        'css_parse_loop: loop {
-            let tokenize_result = tokenizer.parse_next();
-            match tokenize_result {
-                Ok(token) => {
-                    match token {
-                        Token::EndOfStream => {
-                            break 'css_parse_loop;
-                        },
-                        Token::BlockStart => {
-                            parser_in_block = true;
-                            block_nesting += 1;
-                        },
-                        Token::BlockEnd => {
-                            block_nesting -= 1;
-                            parser_in_block = false;
-                            current_type = "*";
-                            current_id = None;
-                            current_classes = HashSet::<&str>::new();
-                        },
-                        Token::TypeSelector(div_type) => {
-                            if parser_in_block {
-                                return Err(CssParseError::MalformedCss);
-                            }
-                            current_type = div_type;
-                        },
-                        Token::IdSelector(id) => {
-                            if parser_in_block {
-                                return Err(CssParseError::MalformedCss);
-                            }
-                            current_id = Some(id.to_string());
-                        }
-                        Token::ClassSelector(class) => {
-                            if parser_in_block {
-                                return Err(CssParseError::MalformedCss);
-                            }
-                            current_classes.insert(class);
-                        }
-                        Token::Declaration(key, val) => {
-                            if !parser_in_block {
-                                return Err(CssParseError::MalformedCss);
-                            }
-
-                            // see if the Declaration is static or dynamic
-                            //
-                            // css_val = "center" | "{{ my_dynamic_id | center }}"
-                            let css_decl = determine_static_or_dynamic_css_property(key, val)?;
-                            let mut css_rule = CssRule {
-                                html_type: current_type.to_string(),
-                                id: current_id.clone(),
-                                classes: current_classes.iter().map(|e| e.to_string()).collect::<Vec<String>>(),
-                                declaration: (key.to_string(), css_decl),
-                            };
-                            // IMPORTANT!
-                            css_rule.classes.sort();
-                            css_rules.push(css_rule);
-                        },
-                        _ => { }
-                    }
-                },
-                Err(e) => {
-                    return Err(CssParseError::ParseError(e));
-                }
+        for section in split_at_rules(css_string)? {
+            match section {
+                CssSection::Rules(media, rules) => parse_css_rules_with_pseudo_selectors(rules, media, &mut css_rules)?,
+                CssSection::Page(declarations) => page_margin = parse_page_rule(declarations)?.or(page_margin),
             }
         }
 
-        // non-even number of blocks
-        if block_nesting != 0 {
-            return Err(CssParseError::UnclosedBlock);
-        }
-
         Ok(Self {
             rules: css_rules,
             // force re-layout for the first frame
             needs_relayout: true,
             dynamic_css_overrides: FastHashMap::default(),
+            active_media: CssMediaType::Screen,
+            forced_colors: false,
+            page_margin: page_margin,
+            active_node: None,
+            focused_node: None,
+            is_drag_over: false,
         })
     }
 
+    /// Validates a stylesheet at compile time - call this from the `build.rs` of an
+    /// application that embeds its CSS via `include_str!()`, so that invalid CSS fails
+    /// the build instead of panicking at startup:
+    ///
+    /// ```no_run
+    /// // build.rs (with azul as a build-dependency)
+    /// extern crate azul;
+    /// fn main() {
+    ///     azul::prelude::Css::check_in_build_script("src/style.css");
+    /// }
+    /// ```
+    ///
+    /// The build script is re-run when the file changes. Note that the CSS is still
+    /// parsed at runtime: a `Css` owns heap-allocated rules, so it can't be created
+    /// in a `const` expression.
+    pub fn check_in_build_script<P: AsRef<Path>>(path: P) {
+        let path = path.as_ref();
+        println!("cargo:rerun-if-changed={}", path.display());
+        let css_string = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("could not read the CSS file {:?}: {}", path, e));
+        if let Some(error) = get_build_script_error(path, &css_string) {
+            panic!("{}", error);
+        }
+    }
+
+    /// Switches the active media type, i.e. `CssMediaType::Print` activates all
+    /// rules in `@media print { }` blocks. Forces a re-layout on the next frame.
+    pub fn set_media_type(&mut self, media: CssMediaType) {
+        if self.active_media != media {
+            self.active_media = media;
+            self.needs_relayout = true;
+        }
+    }
+
+    /// Activates or deactivates the `@media (forced-colors: active)` rules,
+    /// done automatically when the high contrast mode of the OS changes
+    pub fn set_forced_colors(&mut self, forced_colors: bool) {
+        if self.forced_colors != forced_colors {
+            self.forced_colors = forced_colors;
+            self.needs_relayout = true;
+        }
+    }
+
+    /// Sets the node that is matched by `:active`, done automatically when a mouse
+    /// button or the space bar is pressed or released. Forces a re-layout on the next frame.
+    pub(crate) fn set_active_node(&mut self, active_node: Option<NodeId>) {
+        if self.active_node != active_node {
+            self.active_node = active_node;
+            self.needs_relayout = true;
+        }
+    }
+
+    /// Sets the node that is matched by `:focus`, see `FakeWindow::set_focus()`.
+    /// Forces a re-layout on the next frame if the focused node has changed.
+    pub(crate) fn set_focused_node(&mut self, focused_node: Option<NodeId>) {
+        if self.focused_node != focused_node {
+            self.focused_node = focused_node;
+            self.needs_relayout = true;
+        }
+    }
+
+    /// Sets whether a drag-and-drop is over the window (for `:-azul-drop-target`),
+    /// forces a re-layout on the next frame if it has changed
+    pub(crate) fn set_drag_over(&mut self, is_drag_over: bool) {
+        if self.is_drag_over != is_drag_over {
+            self.is_drag_over = is_drag_over;
+            self.needs_relayout = true;
+        }
+    }
+
+    /// Replaces the rules (and the `@page` margin) with the ones of `new_css`, see
+    /// `FakeWindow::switch_css()`. The state of the window (active media type, `:active`
+    /// node, etc.) is kept. Forces a re-layout on the next frame.
+    pub(crate) fn replace_rules(&mut self, new_css: Css) {
+        self.rules = new_css.rules;
+        self.page_margin = new_css.page_margin;
+        self.needs_relayout = true;
+    }
+
+    /// Returns whether the rules of a `@media` block of the given type currently apply
+    pub(crate) fn is_media_active(&self, media: CssMediaType) -> bool {
+        match media {
+            CssMediaType::ForcedColors => self.forced_colors,
+            other => other.matches(self.active_media),
+        }
+    }
+
+    /// Returns the page margin (in pixels) that was set via `@page { margin: ...; }`,
+    /// which `App::render_for_print()` leaves empty around the content of each page.
+    pub fn get_page_margin(&self) -> Option<f32> {
+        self.page_margin
+    }
+
     /// Returns the native style for the OS
     #[cfg(target_os="windows")]
     pub fn native() -> Self {
@@ -275,6 +485,507 @@ impl Css {
     }
 }
 
+/// Error message of `Css::check_in_build_script()`, `None` if the CSS is valid
+fn get_build_script_error(path: &Path, css_string: &str) -> Option<String> {
+    Css::new_from_string(css_string).err().map(|e| format!("invalid CSS in {:?}: {}", path, e))
+}
+
+/// A part of the CSS source, split up at the `@media` and `@page` rules
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum CssSection<'a> {
+    /// Regular CSS rules that apply to the given media type
+    Rules(CssMediaType, &'a str),
+    /// The block of a `@page { }` rule, including the braces
+    Page(&'a str),
+}
+
+/// The `simplecss` tokenizer doesn't know about `@`-rules, so the source is split at the
+/// `@media` and `@page` blocks first and each section is tokenized separately.
+///
+/// NOTE: `@media` blocks may not be nested
+fn split_at_rules<'a>(css_string: &'a str)
+-> Result<Vec<CssSection<'a>>, CssParseError<'a>>
+{
+    let mut sections = Vec::new();
+    let mut remaining = css_string;
+
+    while let Some(at_pos) = find_unquoted(remaining, |ch| ch == '@') {
+        sections.push(CssSection::Rules(CssMediaType::All, &remaining[..at_pos]));
+
+        let at_rule = &remaining[at_pos..];
+        let block_start = find_unquoted(at_rule, |ch| ch == '{').ok_or(CssParseError::UnclosedBlock)?;
+        let block_end = block_start + find_closing_brace(&at_rule[block_start..]).ok_or(CssParseError::UnclosedBlock)?;
+        let block_contents = &at_rule[(block_start + 1)..block_end];
+        let block = &at_rule[block_start..(block_end + 1)];
+
+        // "media print", "media (forced-colors: active)" or "page"
+        let rule_name = at_rule[1..block_start].trim();
+
+        if rule_name == "page" {
+            sections.push(CssSection::Page(block));
+        } else if rule_name.starts_with("media ") {
+            let media_query = rule_name["media".len()..].split_whitespace().collect::<String>();
+            let media = match media_query.as_str() {
+                "all" => CssMediaType::All,
+                "screen" => CssMediaType::Screen,
+                "print" => CssMediaType::Print,
+                "(forced-colors:active)" => CssMediaType::ForcedColors,
+                _ => return Err(CssParseError::UnsupportedAtRule(rule_name)),
+            };
+            sections.push(CssSection::Rules(media, block_contents));
+        } else {
+            return Err(CssParseError::UnsupportedAtRule(rule_name));
+        }
+
+        remaining = &at_rule[(block_end + 1)..];
+    }
+
+    sections.push(CssSection::Rules(CssMediaType::All, remaining));
+    Ok(sections)
+}
+
+/// Input has to start with a `{` - returns the index of the matching `}`,
+/// ignoring the braces in quoted strings (`content: "}"`)
+fn find_closing_brace(input: &str) -> Option<usize> {
+    let mut nesting = 0_usize;
+    let mut pos = 0;
+    while let Some(idx) = find_unquoted(&input[pos..], |ch| ch == '{' || ch == '}').map(|idx| pos + idx) {
+        if input[idx..].starts_with('{') {
+            nesting += 1;
+        } else {
+            nesting = nesting.checked_sub(1)?;
+            if nesting == 0 {
+                return Some(idx);
+            }
+        }
+        pos = idx + 1;
+    }
+    None
+}
+
+/// Returns the index of the first character for which `predicate` is true and which is not
+/// inside of a `"` or `'` quoted string, i.e. `content: "@"` doesn't start an `@`-rule
+fn find_unquoted<F: Fn(char) -> bool>(input: &str, predicate: F) -> Option<usize> {
+    let mut quote = None;
+    let mut escaped = false;
+    for (idx, ch) in input.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if ch == '\\' => escaped = true,
+            Some(q) if ch == q => quote = None,
+            Some(_) => { },
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if predicate(ch) => return Some(idx),
+            None => { },
+        }
+    }
+    None
+}
+
+/// Tokenizes a block such as `{ content: "a;b"; }` (including the braces) with the same
+/// tokenizer as the regular rules, so that a `;` in a string or in `url()` doesn't
+/// end the declaration. Returns the key, the value and the whole declaration (for errors).
+fn tokenize_declarations<'a>(block: &'a str)
+-> Result<Vec<(&'a str, &'a str, &'a str)>, CssParseError<'a>>
+{
+    use simplecss::{Tokenizer, Token};
+
+    // "color: red" out of the `block`, the key and the value are sub-slices of the block
+    let declaration = |key: &'a str, value: &'a str| {
+        let start = key.as_ptr() as usize - block.as_ptr() as usize;
+        let end = value.as_ptr() as usize - block.as_ptr() as usize + value.len();
+        &block[start..end]
+    };
+
+    let mut tokenizer = Tokenizer::new(block);
+    let mut declarations = Vec::new();
+
+    loop {
+        match tokenizer.parse_next().map_err(CssParseError::ParseError)? {
+            Token::EndOfStream => return Ok(declarations),
+            Token::BlockStart | Token::BlockEnd => { },
+            Token::Declaration(key, value) => declarations.push((key, value, declaration(key, value))),
+            _ => return Err(CssParseError::MalformedCss),
+        }
+    }
+}
+
+/// Parses a `@page { }` block (including the braces), returns the page margin in pixels.
+///
+/// Currently only `margin` is supported, in `px`, `pt`, `in`, `cm` or `mm`.
+fn parse_page_rule<'a>(block: &'a str)
+-> Result<Option<f32>, CssParseError<'a>>
+{
+    let mut margin = None;
+
+    for (key, value, declaration) in tokenize_declarations(block)? {
+        if key != "margin" {
+            return Err(CssParseError::InvalidPageRule(declaration));
+        }
+
+        const UNITS: [(&str, f32); 5] = [("px", 1.0), ("pt", 96.0 / 72.0), ("in", 96.0), ("cm", 96.0 / 2.54), ("mm", 96.0 / 25.4)];
+
+        let (number, factor) = UNITS.iter()
+            .filter(|(unit, _)| value.ends_with(unit))
+            .map(|(unit, factor)| (&value[..(value.len() - unit.len())], *factor))
+            .next()
+            .ok_or(CssParseError::InvalidPageRule(declaration))?;
+
+        let number = number.trim().parse::<f32>().map_err(|_| CssParseError::InvalidPageRule(declaration))?;
+        margin = Some(number * factor);
+    }
+
+    Ok(margin)
+}
+
+/// Supported pseudo-elements, the properties they may set and the property that these are mapped to
+const PSEUDO_ELEMENT_PROPERTIES: [(&str, &[&str], &str); 3] = [
+    ("::-azul-scrollbar-thumb", &["background-color", "background"], "-azul-scrollbar-thumb-color"),
+    ("::-azul-scrollbar-track", &["background-color", "background"], "-azul-scrollbar-track-color"),
+    ("::before", &["content"], "-azul-before-content"),
+];
+
+/// The `simplecss` tokenizer doesn't know about pseudo-classes or pseudo-elements, so rules
+/// with a `:` in their selector are cut out of the section and parsed by `parse_pseudo_rule()`,
+/// the rest of the section is tokenized normally.
+fn parse_css_rules_with_pseudo_selectors<'a>(css_string: &'a str, media: CssMediaType, css_rules: &mut Vec<CssRule>)
+-> Result<(), CssParseError<'a>>
+{
+    // Start of the next rule
+    let mut rule_start = 0;
+    // Start of the rules that have to be tokenized by `parse_css_rules()`
+    let mut regular_rules_start = 0;
+
+    while let Some(block_start) = find_unquoted(&css_string[rule_start..], |ch| ch == '{').map(|pos| rule_start + pos) {
+        let block_end = block_start + find_closing_brace(&css_string[block_start..]).ok_or(CssParseError::UnclosedBlock)?;
+        let selector = skip_leading_comments(&css_string[rule_start..block_start]).trim();
+
+        if selector.contains(':') {
+            parse_css_rules(&css_string[regular_rules_start..rule_start], media, css_rules)?;
+            parse_pseudo_rule(selector, &css_string[block_start..(block_end + 1)], media, css_rules)?;
+            regular_rules_start = block_end + 1;
+        }
+
+        rule_start = block_end + 1;
+    }
+
+    parse_css_rules(&css_string[regular_rules_start..], media, css_rules)
+}
+
+/// Pushes the rule of a declaration in the block starting at `block_start`. The `all`
+/// shorthand resets the node, so it is moved in front of the other declarations of
+/// the block - `div { color: red; all: unset; }` still has a red text.
+fn push_css_rule(css_rules: &mut Vec<CssRule>, block_start: usize, css_rule: CssRule) {
+    if &*css_rule.declaration.0 == "all" {
+        // after the previous `all` declarations of the block, so that the last one wins
+        let insert_pos = block_start + css_rules[block_start..].iter().take_while(|rule| &*rule.declaration.0 == "all").count();
+        css_rules.insert(insert_pos, css_rule);
+    } else {
+        css_rules.push(css_rule);
+    }
+}
+
+/// `/* comment */ div` -> `div`
+fn skip_leading_comments(mut input: &str) -> &str {
+    loop {
+        input = input.trim_left();
+        if !input.starts_with("/*") {
+            return input;
+        }
+        match input.find("*/") {
+            Some(comment_end) => input = &input[(comment_end + 2)..],
+            None => return input,
+        }
+    }
+}
+
+/// Parses a rule with pseudo-classes and / or a pseudo-element, such as
+/// `li.item:nth-child(odd) { background-color: grey; }`. The `block` includes the braces.
+///
+/// The declarations of a pseudo-element are applied to the element itself, under the key of
+/// the pseudo-element, i.e. `.a::-azul-scrollbar-thumb { background-color: red; }` is
+/// equivalent to `.a { -azul-scrollbar-thumb-color: red; }`
+fn parse_pseudo_rule<'a>(selector: &'a str, block: &'a str, media: CssMediaType, css_rules: &mut Vec<CssRule>)
+-> Result<(), CssParseError<'a>>
+{
+    let (element_selector, pseudo_element_key) = match selector.find("::") {
+        Some(pseudo_element_pos) => {
+            let (element_selector, pseudo_element) = selector.split_at(pseudo_element_pos);
+            let property = PSEUDO_ELEMENT_PROPERTIES.iter()
+                .find(|(name, _, _)| *name == pseudo_element)
+                .map(|(_, allowed_keys, key)| (*allowed_keys, *key))
+                .ok_or(CssParseError::UnsupportedPseudoElement(pseudo_element))?;
+            (element_selector, Some(property))
+        },
+        None => (selector, None),
+    };
+
+    // `li.item:first-child` -> (`li.item`, `:first-child`)
+    let pseudo_class_start = element_selector.find(':').unwrap_or(element_selector.len());
+    let (simple_selector, pseudo_classes) = element_selector.split_at(pseudo_class_start);
+
+    let (html_type, id, mut classes) = parse_simple_selector(simple_selector)
+        .ok_or(CssParseError::UnsupportedSelector(selector))?;
+    // IMPORTANT!
+    classes.sort();
+
+    let pseudo_classes = split_pseudo_classes(pseudo_classes)
+        .into_iter()
+        .map(parse_pseudo_class)
+        .collect::<Result<Vec<CssPseudoClass>, CssParseError<'a>>>()?;
+
+    let block_start = css_rules.len();
+
+    for (key, value, declaration) in tokenize_declarations(block)? {
+        let key = match pseudo_element_key {
+            Some((allowed_keys, property_key)) => {
+                if !allowed_keys.contains(&key) {
+                    return Err(CssParseError::InvalidPseudoElementRule(declaration));
+                }
+                property_key
+            },
+            None => key,
+        };
+
+        let css_rule = CssRule {
+            html_type: html_type.to_string(),
+            id: id.map(|id| id.to_string()),
+            classes: classes.iter().map(|class| class.to_string()).collect(),
+            pseudo_classes: pseudo_classes.clone(),
+            declaration: (Rc::from(key), determine_static_or_dynamic_css_property(key, value)?),
+            media: media,
+        };
+        push_css_rule(css_rules, block_start, css_rule);
+    }
+
+    Ok(())
+}
+
+/// Splits a simple selector such as `div#id.a.b` into its type, id and classes.
+/// Returns `None` for combinators (`div > p`, `div p`, `div, p`).
+fn parse_simple_selector<'a>(selector: &'a str) -> Option<(&'a str, Option<&'a str>, Vec<&'a str>)> {
+    if selector.contains(|c: char| c.is_whitespace() || c == ',' || c == '>' || c == '+' || c == '~') {
+        return None;
+    }
+
+    let mut html_type = "*";
+    let mut id = None;
+    let mut classes = Vec::new();
+
+    // Indices of the '#' and '.' that start a new part of the selector
+    let mut part_starts = selector.match_indices(|c| c == '#' || c == '.').map(|(idx, _)| idx).collect::<Vec<_>>();
+    part_starts.push(selector.len());
+
+    if part_starts[0] != 0 {
+        html_type = &selector[..part_starts[0]];
+    }
+
+    for part in part_starts.windows(2) {
+        let (prefix, name) = selector[part[0]..part[1]].split_at(1);
+        if name.is_empty() {
+            return None;
+        }
+        match prefix {
+            "#" => id = Some(name),
+            _ => classes.push(name),
+        }
+    }
+
+    Some((html_type, id, classes))
+}
+
+/// `:nth-child(2n+1):last-child` -> `["nth-child(2n+1)", "last-child"]`
+///
+/// Colons inside of parentheses don't start a new pseudo-class
+fn split_pseudo_classes<'a>(input: &'a str) -> Vec<&'a str> {
+    let mut pseudo_classes = Vec::new();
+    let mut nesting = 0_usize;
+    let mut current_start = None;
+
+    for (idx, ch) in input.char_indices() {
+        match ch {
+            '(' => nesting += 1,
+            ')' => nesting = nesting.saturating_sub(1),
+            ':' if nesting == 0 => {
+                if let Some(start) = current_start {
+                    pseudo_classes.push(&input[start..idx]);
+                }
+                current_start = Some(idx + 1);
+            },
+            _ => { },
+        }
+    }
+
+    if let Some(start) = current_start {
+        pseudo_classes.push(&input[start..]);
+    }
+
+    pseudo_classes
+}
+
+/// Parses a single pseudo-class (without the leading `:`), such as `nth-child(2n+1)`
+fn parse_pseudo_class<'a>(input: &'a str) -> Result<CssPseudoClass, CssParseError<'a>> {
+    use self::CssPseudoClass::*;
+
+    let error = CssParseError::UnsupportedPseudoClass(input);
+
+    let (name, argument) = match input.find('(') {
+        Some(paren_pos) if input.ends_with(')') => (&input[..paren_pos], Some(&input[(paren_pos + 1)..(input.len() - 1)])),
+        Some(_) => return Err(error),
+        None => (input, None),
+    };
+
+    match (name, argument) {
+        ("first-child", None) => Ok(FirstChild),
+        ("last-child", None) => Ok(LastChild),
+        ("only-child", None) => Ok(OnlyChild),
+        ("first-of-type", None) => Ok(FirstOfType),
+        ("last-of-type", None) => Ok(LastOfType),
+        ("active", None) => Ok(Active),
+        ("focus", None) => Ok(Focus),
+        ("disabled", None) => Ok(Disabled),
+        ("placeholder-shown", None) => Ok(PlaceholderShown),
+        ("-azul-drop-target", None) => Ok(DropTarget),
+        ("nth-child", Some(pattern)) => CssNthChildPattern::from_str(pattern).map(NthChild).ok_or(error),
+        ("nth-of-type", Some(pattern)) => CssNthChildPattern::from_str(pattern).map(NthOfType).ok_or(error),
+        ("not", Some(selectors)) => {
+            selectors.split(',')
+                .map(|selector| parse_negated_selector(selector.trim()))
+                .collect::<Option<Vec<CssNegatedSelector>>>()
+                .map(Not)
+                .ok_or(error)
+        },
+        _ => Err(error),
+    }
+}
+
+/// Parses the selector inside of a `:not()`, returns `None` on a nested `:not()`
+fn parse_negated_selector(selector: &str) -> Option<CssNegatedSelector> {
+    if selector.is_empty() {
+        return None;
+    }
+
+    let pseudo_class_start = selector.find(':').unwrap_or(selector.len());
+    let (simple_selector, pseudo_classes) = selector.split_at(pseudo_class_start);
+    let (html_type, id, mut classes) = parse_simple_selector(simple_selector)?;
+    classes.sort();
+
+    let pseudo_classes = split_pseudo_classes(pseudo_classes)
+        .into_iter()
+        .map(|pseudo_class| parse_pseudo_class(pseudo_class).ok())
+        .collect::<Option<Vec<CssPseudoClass>>>()?;
+
+    if pseudo_classes.iter().any(|pseudo_class| match pseudo_class { CssPseudoClass::Not(_) => true, _ => false }) {
+        return None;
+    }
+
+    Some(CssNegatedSelector {
+        html_type: html_type.to_string(),
+        id: id.map(|id| id.to_string()),
+        classes: classes.iter().map(|class| class.to_string()).collect(),
+        pseudo_classes: pseudo_classes,
+    })
+}
+
+/// Tokenizes one section of the CSS source and appends the rules to `css_rules`
+fn parse_css_rules<'a>(css_string: &'a str, media: CssMediaType, css_rules: &mut Vec<CssRule>)
+-> Result<(), CssParseError<'a>>
+{
+    use simplecss::{Tokenizer, Token};
+    use std::collections::HashSet;
+
+    let mut tokenizer = Tokenizer::new(css_string);
+
+    let mut block_nesting = 0_usize;
+
+    // TODO: For now, rules may not be nested, otherwise, this won't work
+    // TODO: This could be more efficient. We don't even need to clone the
+    // strings, but this is just a quick-n-dirty CSS parser
+    // This will also use up a lot of memory, since the strings get duplicated
+
+    let mut parser_in_block = false;
+    let mut current_type = "*";
+    let mut current_id = None;
+    let mut current_classes = HashSet::<&str>::new();
+    // Index of the first rule of the current block
+    let mut block_start = css_rules.len();
+
+    'css_parse_loop: loop {
+        let tokenize_result = tokenizer.parse_next();
+        match tokenize_result {
+            Ok(token) => {
+                match token {
+                    Token::EndOfStream => {
+                        break 'css_parse_loop;
+                    },
+                    Token::BlockStart => {
+                        parser_in_block = true;
+                        block_nesting += 1;
+                        block_start = css_rules.len();
+                    },
+                    Token::BlockEnd => {
+                        block_nesting -= 1;
+                        parser_in_block = false;
+                        current_type = "*";
+                        current_id = None;
+                        current_classes = HashSet::<&str>::new();
+                    },
+                    Token::TypeSelector(div_type) => {
+                        if parser_in_block {
+                            return Err(CssParseError::MalformedCss);
+                        }
+                        current_type = div_type;
+                    },
+                    Token::IdSelector(id) => {
+                        if parser_in_block {
+                            return Err(CssParseError::MalformedCss);
+                        }
+                        current_id = Some(id.to_string());
+                    }
+                    Token::ClassSelector(class) => {
+                        if parser_in_block {
+                            return Err(CssParseError::MalformedCss);
+                        }
+                        current_classes.insert(class);
+                    }
+                    Token::Declaration(key, val) => {
+                        if !parser_in_block {
+                            return Err(CssParseError::MalformedCss);
+                        }
+
+                        // see if the Declaration is static or dynamic
+                        //
+                        // css_val = "center" | "{{ my_dynamic_id | center }}"
+                        let css_decl = determine_static_or_dynamic_css_property(key, val)?;
+                        let mut css_rule = CssRule {
+                            html_type: current_type.to_string(),
+                            id: current_id.clone(),
+                            classes: current_classes.iter().map(|e| e.to_string()).collect::<Vec<String>>(),
+                            pseudo_classes: Vec::new(),
+                            declaration: (Rc::from(key), css_decl),
+                            media: media,
+                        };
+                        // IMPORTANT!
+                        css_rule.classes.sort();
+                        push_css_rule(css_rules, block_start, css_rule);
+                    },
+                    _ => { }
+                }
+            },
+            Err(e) => {
+                return Err(CssParseError::ParseError(e));
+            }
+        }
+    }
+
+    // non-even number of blocks
+    if block_nesting != 0 {
+        return Err(CssParseError::UnclosedBlock);
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum DynamicCssParseError<'a> {
     UnclosedBraces,
@@ -296,6 +1007,22 @@ impl<'a> From<CssParsingError<'a>> for DynamicCssParseError<'a> {
     }
 }
 
+impl<'a> fmt::Display for DynamicCssParseError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::DynamicCssParseError::*;
+        match self {
+            UnclosedBraces => write!(f, "The braces of the dynamic CSS property are not closed"),
+            NoDefaultCase => write!(f, "The dynamic CSS property has no default case"),
+            NoId => write!(f, "The dynamic CSS property has no ID"),
+            InvalidId => write!(f, "The ID of the dynamic CSS property starts with a number or is a CSS property"),
+            EmptyBraces => write!(f, "The braces of the dynamic CSS property are empty"),
+            UnexpectedValue(e) => write!(f, "Invalid CSS value: {:?}", e),
+        }
+    }
+}
+
+impl<'a> ::std::error::Error for DynamicCssParseError<'a> { }
+
 /// Determine if a Css property is static (immutable) or if it can change
 /// during the runtime of the program
 fn determine_static_or_dynamic_css_property<'a>(key: &'a str, value: &'a str)
@@ -433,4 +1160,206 @@ fn test_detect_static_or_dynamic_property() {
         determine_static_or_dynamic_css_property("text-align", "[[ |  ]]"),
         Err(DynamicCssParseError::EmptyBraces)
     );
+}
+
+#[test]
+fn test_split_at_rules() {
+    assert_eq!(
+        split_at_rules("div { color: red; } @media print { div { color: black; } } @page { margin: 1in; }"),
+        Ok(vec![
+            CssSection::Rules(CssMediaType::All, "div { color: red; } "),
+            CssSection::Rules(CssMediaType::Print, " div { color: black; } "),
+            CssSection::Rules(CssMediaType::All, " "),
+            CssSection::Page("{ margin: 1in; }"),
+            CssSection::Rules(CssMediaType::All, ""),
+        ])
+    );
+
+    assert_eq!(split_at_rules("@media tv { }"), Err(CssParseError::UnsupportedAtRule("media tv")));
+    assert_eq!(
+        split_at_rules("@media (forced-colors: active) { div { color: CanvasText; } }"),
+        Ok(vec![
+            CssSection::Rules(CssMediaType::All, ""),
+            CssSection::Rules(CssMediaType::ForcedColors, " div { color: CanvasText; } "),
+            CssSection::Rules(CssMediaType::All, " "),
+        ])
+    );
+    assert_eq!(parse_page_rule("{ margin: 1in; }"), Ok(Some(96.0)));
+    assert_eq!(parse_page_rule("{ margin: 1in; size: a4 }"), Err(CssParseError::InvalidPageRule("size: a4")));
+
+    // `@` and braces in strings don't start an at-rule or end the block
+    assert_eq!(
+        split_at_rules("div::before { content: \"@\"; }"),
+        Ok(vec![CssSection::Rules(CssMediaType::All, "div::before { content: \"@\"; }")])
+    );
+    assert_eq!(
+        split_at_rules("@media print { div::before { content: '}'; } }"),
+        Ok(vec![
+            CssSection::Rules(CssMediaType::All, ""),
+            CssSection::Rules(CssMediaType::Print, " div::before { content: '}'; } "),
+            CssSection::Rules(CssMediaType::All, ""),
+        ])
+    );
+    assert_eq!(find_closing_brace("{ content: \"\\\"}\"; } div { }"), Some(18));
+}
+
+#[test]
+fn test_parse_scrollbar_pseudo_elements() {
+    let css = Css::new_from_string("
+        .a { color: red; }
+        div#b.c::-azul-scrollbar-thumb { background-color: blue; }
+        .d { color: green; }
+    ").unwrap();
+
+    assert_eq!(css.rules.len(), 3);
+    assert_eq!(css.rules[1].html_type, "div");
+    assert_eq!(css.rules[1].id, Some("b".into()));
+    assert_eq!(css.rules[1].classes, vec![String::from("c")]);
+    assert_eq!(&*css.rules[1].declaration.0, "-azul-scrollbar-thumb-color");
+
+    assert_eq!(
+        Css::new_from_string("div::after { color: red; }").err(),
+        Some(CssParseError::UnsupportedPseudoElement("::after"))
+    );
+    assert_eq!(
+        Css::new_from_string(".a::-azul-scrollbar-track { color: red; }").err(),
+        Some(CssParseError::InvalidPseudoElementRule("color: red"))
+    );
+}
+
+#[test]
+fn test_parse_pseudo_classes() {
+    let css = Css::new_from_string("
+        /* striped rows */
+        li.row:nth-child(odd) { background-color: grey; }
+        li:first-child:last-of-type { color: red; }
+        .a { color: green; }
+    ").unwrap();
+
+    assert_eq!(css.rules.len(), 3);
+    assert_eq!(css.rules[0].html_type, "li");
+    assert_eq!(css.rules[0].classes, vec![String::from("row")]);
+    assert_eq!(css.rules[0].pseudo_classes, vec![CssPseudoClass::NthChild(CssNthChildPattern { repeat: 2, offset: 1 })]);
+    assert_eq!(css.rules[1].pseudo_classes, vec![CssPseudoClass::FirstChild, CssPseudoClass::LastOfType]);
+    assert!(css.rules[2].pseudo_classes.is_empty());
+
+    assert_eq!(Css::new_from_string("div:hover { color: red; }").err(), Some(CssParseError::UnsupportedPseudoClass("hover")));
+    assert_eq!(Css::new_from_string("div > p:first-child { color: red; }").err(), Some(CssParseError::UnsupportedSelector("div > p:first-child")));
+}
+
+#[test]
+fn test_nth_child_pattern() {
+    let odd = CssNthChildPattern::from_str("odd").unwrap();
+    assert!(odd.matches(1) && !odd.matches(2) && odd.matches(3));
+
+    let every_third = CssNthChildPattern::from_str("3n").unwrap();
+    assert!(!every_third.matches(1) && every_third.matches(3) && every_third.matches(6));
+
+    let first_three = CssNthChildPattern::from_str("-n + 3").unwrap();
+    assert!(first_three.matches(1) && first_three.matches(3) && !first_three.matches(4));
+
+    assert_eq!(CssNthChildPattern::from_str("2"), Some(CssNthChildPattern { repeat: 0, offset: 2 }));
+    assert_eq!(CssNthChildPattern::from_str("2n1"), None);
+}
+
+#[test]
+fn test_parse_not_pseudo_class() {
+    let css = Css::new_from_string("button:not(.disabled, :first-child) { color: red; }").unwrap();
+
+    assert_eq!(css.rules[0].html_type, "button");
+    assert_eq!(css.rules[0].pseudo_classes, vec![CssPseudoClass::Not(vec![
+        CssNegatedSelector { html_type: "*".into(), id: None, classes: vec!["disabled".into()], pseudo_classes: vec![] },
+        CssNegatedSelector { html_type: "*".into(), id: None, classes: vec![], pseudo_classes: vec![CssPseudoClass::FirstChild] },
+    ])]);
+
+    assert_eq!(
+        Css::new_from_string("div:not(:not(.a)) { color: red; }").err(),
+        Some(CssParseError::UnsupportedPseudoClass("not(:not(.a))"))
+    );
+}
+
+#[test]
+fn test_parse_active_pseudo_class() {
+    let css = Css::new_from_string("button:active { background-color: grey; }").unwrap();
+    assert_eq!(css.rules[0].html_type, "button");
+    assert_eq!(css.rules[0].pseudo_classes, vec![CssPseudoClass::Active]);
+    assert_eq!(css.active_node, None);
+}
+
+#[test]
+fn test_parse_focus_pseudo_class() {
+    let css = Css::new_from_string("input:focus { border: 1px solid blue; }").unwrap();
+    assert_eq!(css.rules[0].pseudo_classes, vec![CssPseudoClass::Focus]);
+    assert_eq!(css.focused_node, None);
+}
+
+#[test]
+fn test_parse_disabled_pseudo_class() {
+    let css = Css::new_from_string("*:disabled { opacity: 0.5; }").unwrap();
+    assert_eq!(css.rules[0].html_type, "*");
+    assert_eq!(css.rules[0].pseudo_classes, vec![CssPseudoClass::Disabled]);
+}
+
+#[test]
+fn test_parse_placeholder_shown_pseudo_class() {
+    let css = Css::new_from_string(".input:placeholder-shown { color: grey; }").unwrap();
+    assert_eq!(css.rules[0].classes, vec![String::from("input")]);
+    assert_eq!(css.rules[0].pseudo_classes, vec![CssPseudoClass::PlaceholderShown]);
+}
+
+#[test]
+fn test_parse_before_pseudo_element() {
+    let css = Css::new_from_string("li::before { content: counter(item) \". \"; }").unwrap();
+    assert_eq!(css.rules[0].html_type, "li");
+    assert_eq!(&*css.rules[0].declaration.0, "-azul-before-content");
+
+    // the `;` in the string doesn't end the declaration
+    let css = Css::new_from_string("li::before { content: \"a;b\"; }").unwrap();
+    assert_eq!(css.rules.len(), 1);
+
+    // ... and neither does an `@` start an at-rule
+    let css = Css::new_from_string("li::before { content: \"@\"; }").unwrap();
+    assert_eq!(css.rules.len(), 1);
+
+    assert_eq!(
+        Css::new_from_string("li::before { color: red; }").err(),
+        Some(CssParseError::InvalidPseudoElementRule("color: red"))
+    );
+}
+
+#[test]
+fn test_all_is_applied_before_the_block() {
+    let css = Css::new_from_string("div { color: red; all: unset; } .a { width: 10px; } .b:first-child { color: red; all: inherit; }").unwrap();
+    let keys = css.rules.iter().map(|rule| &*rule.declaration.0).collect::<Vec<&str>>();
+    assert_eq!(keys, vec!["all", "color", "width", "all", "color"]);
+}
+
+#[test]
+fn test_build_script_error() {
+    let path = Path::new("style.css");
+    assert_eq!(get_build_script_error(path, "div { width: 10px; }"), None);
+    assert_eq!(
+        get_build_script_error(path, "div { width: 10px; "),
+        Some(String::from("invalid CSS in \"style.css\": Braces are not balanced properly"))
+    );
+}
+
+#[test]
+fn test_parse_drop_target_pseudo_class() {
+    let css = Css::new_from_string(".tab-bar:-azul-drop-target { background-color: blue; }").unwrap();
+    assert_eq!(css.rules[0].pseudo_classes, vec![CssPseudoClass::DropTarget]);
+    assert_eq!(css.is_drag_over, false);
+}
+
+#[test]
+fn test_replace_rules() {
+    let mut css = Css::new_from_string("div { color: black; }").unwrap();
+    css.set_forced_colors(true);
+    css.needs_relayout = false;
+
+    css.replace_rules(Css::new_from_string("div { color: white; } @page { margin: 10px; }").unwrap());
+    assert_eq!(css.rules.len(), 1);
+    assert_eq!(css.get_page_margin(), Some(10.0));
+    assert!(css.forced_colors);
+    assert!(css.needs_relayout);
 }
\ No newline at end of file
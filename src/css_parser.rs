@@ -1,5 +1,6 @@
 //! Contains utilities to convert strings (CSS strings) to servo types
 
+use std::fmt;
 use std::num::{ParseIntError, ParseFloatError};
 pub use {
     euclid::{TypedSize2D, SideOffsets2D},
@@ -10,7 +11,7 @@ pub use {
         LayoutSize, ExtendMode
     },
 };
-use webrender::api::{BorderStyle, BorderSide, LayoutRect};
+use webrender::api::{BorderStyle, BorderSide, LayoutRect, FilterOp, PropertyBinding, ImageRendering, MixBlendMode};
 use euclid::{TypedRotation2D, Angle, TypedPoint2D};
 
 pub(crate) const EM_HEIGHT: f32 = 16.0;
@@ -82,6 +83,8 @@ pub enum ParsedCssProperty {
     Background(Background),
     FontSize(FontSize),
     FontFamily(FontFamily),
+    IconFont(StyleIconFont),
+    FontDisplay(StyleFontDisplay),
     TextAlign(TextAlignmentHorz),
     BoxShadow(Option<BoxShadowPreDisplayItem>),
     LineHeight(LineHeight),
@@ -99,15 +102,131 @@ pub enum ParsedCssProperty {
     AlignItems(LayoutAlignItems),
     AlignContent(LayoutAlignContent),
     Overflow(LayoutOverflow),
+    Filter(Filter),
+    BackdropFilter(BackdropFilter),
+    CaretColor(CaretColor),
+    CaretShape(CaretShape),
+    UserSelect(StyleUserSelect),
+    PointerEvents(StylePointerEvents),
+    Appearance(StyleAppearance),
+    BackgroundClip(StyleBackgroundClip),
+    Resize(LayoutResize),
+    TableLayout(LayoutTableLayout),
+    VerticalAlign(StyleVerticalAlign),
+    ShapeOutside(StyleShapeOutside),
+    ColumnCount(LayoutColumnCount),
+    ColumnWidth(LayoutColumnWidth),
+    ColumnGap(LayoutColumnGap),
+    ColumnRule(StyleColumnRule),
+    ObjectFit(StyleObjectFit),
+    ObjectPosition(StyleObjectPosition),
+    ImageRendering(StyleImageRendering),
+    BorderImage(StyleBorderImage),
+    CounterReset(StyleCounterReset),
+    CounterIncrement(StyleCounterIncrement),
+    BeforeContent(StyleContent),
+    ListStyleType(StyleListStyleType),
+    ListStylePosition(StyleListStylePosition),
+    ListStyleImage(StyleListStyleImage),
+    WritingMode(LayoutWritingMode),
+    Direction(StyleDirection),
+    LogicalSize(LogicalSize),
+    LogicalBox(LogicalBoxProperty),
+    SideBorder(StyleBorderSide),
+    ScrollbarThumbColor(ScrollbarThumbColor),
+    ScrollbarTrackColor(ScrollbarTrackColor),
+    ScrollbarWidth(LayoutScrollbarWidth),
+    LetterSpacing(LetterSpacing),
+    WordSpacing(WordSpacing),
+    WordBreak(StyleWordBreak),
+    OverflowWrap(StyleOverflowWrap),
+    TextDecoration(StyleTextDecoration),
+    TextShadow(TextShadow),
+    FontFeatureSettings(FontFeatureSettings),
+    Opacity(StyleOpacity),
+    Visibility(StyleVisibility),
+    WillChange(StyleWillChange),
+    Isolation(StyleIsolation),
+    MixBlendMode(StyleMixBlendMode),
+    ScrollBehavior(StyleScrollBehavior),
+    ScrollSnapType(StyleScrollSnapType),
+    ScrollSnapAlign(StyleScrollSnapAlign),
+    ScrollMargin(StyleScrollMargin),
+    ScrollPadding(StyleScrollPadding),
+    Margin(LayoutMargin),
+    Padding(LayoutPadding),
+    Display(LayoutDisplay),
+    Float(LayoutFloat),
+    Clear(LayoutClear),
+    All(StyleAll),
 }
 
 impl_from_no_lifetimes!(BorderRadius, ParsedCssProperty::BorderRadius);
 impl_from_no_lifetimes!(Background, ParsedCssProperty::Background);
 impl_from_no_lifetimes!(FontSize, ParsedCssProperty::FontSize);
 impl_from_no_lifetimes!(FontFamily, ParsedCssProperty::FontFamily);
+impl_from_no_lifetimes!(StyleIconFont, ParsedCssProperty::IconFont);
+impl_from_no_lifetimes!(StyleFontDisplay, ParsedCssProperty::FontDisplay);
 impl_from_no_lifetimes!(LayoutOverflow, ParsedCssProperty::Overflow);
 impl_from_no_lifetimes!(TextAlignmentHorz, ParsedCssProperty::TextAlign);
 impl_from_no_lifetimes!(LineHeight, ParsedCssProperty::LineHeight);
+impl_from_no_lifetimes!(Filter, ParsedCssProperty::Filter);
+impl_from_no_lifetimes!(BackdropFilter, ParsedCssProperty::BackdropFilter);
+impl_from_no_lifetimes!(CaretColor, ParsedCssProperty::CaretColor);
+impl_from_no_lifetimes!(CaretShape, ParsedCssProperty::CaretShape);
+impl_from_no_lifetimes!(StyleUserSelect, ParsedCssProperty::UserSelect);
+impl_from_no_lifetimes!(StylePointerEvents, ParsedCssProperty::PointerEvents);
+impl_from_no_lifetimes!(StyleAppearance, ParsedCssProperty::Appearance);
+impl_from_no_lifetimes!(StyleBackgroundClip, ParsedCssProperty::BackgroundClip);
+impl_from_no_lifetimes!(LayoutResize, ParsedCssProperty::Resize);
+impl_from_no_lifetimes!(LayoutTableLayout, ParsedCssProperty::TableLayout);
+impl_from_no_lifetimes!(StyleVerticalAlign, ParsedCssProperty::VerticalAlign);
+impl_from_no_lifetimes!(StyleShapeOutside, ParsedCssProperty::ShapeOutside);
+impl_from_no_lifetimes!(LayoutColumnCount, ParsedCssProperty::ColumnCount);
+impl_from_no_lifetimes!(LayoutColumnWidth, ParsedCssProperty::ColumnWidth);
+impl_from_no_lifetimes!(LayoutColumnGap, ParsedCssProperty::ColumnGap);
+impl_from_no_lifetimes!(StyleColumnRule, ParsedCssProperty::ColumnRule);
+impl_from_no_lifetimes!(StyleObjectFit, ParsedCssProperty::ObjectFit);
+impl_from_no_lifetimes!(StyleObjectPosition, ParsedCssProperty::ObjectPosition);
+impl_from_no_lifetimes!(StyleImageRendering, ParsedCssProperty::ImageRendering);
+impl_from_no_lifetimes!(StyleBorderImage, ParsedCssProperty::BorderImage);
+impl_from_no_lifetimes!(StyleCounterReset, ParsedCssProperty::CounterReset);
+impl_from_no_lifetimes!(StyleCounterIncrement, ParsedCssProperty::CounterIncrement);
+impl_from_no_lifetimes!(StyleContent, ParsedCssProperty::BeforeContent);
+impl_from_no_lifetimes!(StyleListStyleType, ParsedCssProperty::ListStyleType);
+impl_from_no_lifetimes!(StyleListStylePosition, ParsedCssProperty::ListStylePosition);
+impl_from_no_lifetimes!(StyleListStyleImage, ParsedCssProperty::ListStyleImage);
+impl_from_no_lifetimes!(LayoutWritingMode, ParsedCssProperty::WritingMode);
+impl_from_no_lifetimes!(StyleDirection, ParsedCssProperty::Direction);
+impl_from_no_lifetimes!(LogicalSize, ParsedCssProperty::LogicalSize);
+impl_from_no_lifetimes!(LogicalBoxProperty, ParsedCssProperty::LogicalBox);
+impl_from_no_lifetimes!(StyleBorderSide, ParsedCssProperty::SideBorder);
+impl_from_no_lifetimes!(ScrollbarThumbColor, ParsedCssProperty::ScrollbarThumbColor);
+impl_from_no_lifetimes!(ScrollbarTrackColor, ParsedCssProperty::ScrollbarTrackColor);
+impl_from_no_lifetimes!(LayoutScrollbarWidth, ParsedCssProperty::ScrollbarWidth);
+impl_from_no_lifetimes!(LetterSpacing, ParsedCssProperty::LetterSpacing);
+impl_from_no_lifetimes!(WordSpacing, ParsedCssProperty::WordSpacing);
+impl_from_no_lifetimes!(StyleWordBreak, ParsedCssProperty::WordBreak);
+impl_from_no_lifetimes!(StyleOverflowWrap, ParsedCssProperty::OverflowWrap);
+impl_from_no_lifetimes!(StyleTextDecoration, ParsedCssProperty::TextDecoration);
+impl_from_no_lifetimes!(TextShadow, ParsedCssProperty::TextShadow);
+impl_from_no_lifetimes!(FontFeatureSettings, ParsedCssProperty::FontFeatureSettings);
+impl_from_no_lifetimes!(StyleOpacity, ParsedCssProperty::Opacity);
+impl_from_no_lifetimes!(StyleVisibility, ParsedCssProperty::Visibility);
+impl_from_no_lifetimes!(StyleAll, ParsedCssProperty::All);
+impl_from_no_lifetimes!(StyleWillChange, ParsedCssProperty::WillChange);
+impl_from_no_lifetimes!(StyleIsolation, ParsedCssProperty::Isolation);
+impl_from_no_lifetimes!(StyleMixBlendMode, ParsedCssProperty::MixBlendMode);
+impl_from_no_lifetimes!(StyleScrollBehavior, ParsedCssProperty::ScrollBehavior);
+impl_from_no_lifetimes!(StyleScrollSnapType, ParsedCssProperty::ScrollSnapType);
+impl_from_no_lifetimes!(StyleScrollSnapAlign, ParsedCssProperty::ScrollSnapAlign);
+impl_from_no_lifetimes!(StyleScrollMargin, ParsedCssProperty::ScrollMargin);
+impl_from_no_lifetimes!(StyleScrollPadding, ParsedCssProperty::ScrollPadding);
+impl_from_no_lifetimes!(LayoutMargin, ParsedCssProperty::Margin);
+impl_from_no_lifetimes!(LayoutPadding, ParsedCssProperty::Padding);
+impl_from_no_lifetimes!(LayoutDisplay, ParsedCssProperty::Display);
+impl_from_no_lifetimes!(LayoutFloat, ParsedCssProperty::Float);
+impl_from_no_lifetimes!(LayoutClear, ParsedCssProperty::Clear);
 
 impl_from_no_lifetimes!(LayoutWidth, ParsedCssProperty::Width);
 impl_from_no_lifetimes!(LayoutHeight, ParsedCssProperty::Height);
@@ -148,11 +267,101 @@ impl ParsedCssProperty {
             "background-color"  => Ok(parse_css_background_color(value)?.into()),
             "color"             => Ok(parse_css_text_color(value)?.into()),
             "border"            => Ok(parse_css_border(value)?.into()),
+            "border-top"        => Ok(StyleBorderSide::new(BoxSide::Top, parse_css_border_side(value)?).into()),
+            "border-right"      => Ok(StyleBorderSide::new(BoxSide::Right, parse_css_border_side(value)?).into()),
+            "border-bottom"     => Ok(StyleBorderSide::new(BoxSide::Bottom, parse_css_border_side(value)?).into()),
+            "border-left"       => Ok(StyleBorderSide::new(BoxSide::Left, parse_css_border_side(value)?).into()),
             "background"        => Ok(parse_css_background(value)?.into()),
             "font-size"         => Ok(parse_css_font_size(value)?.into()),
             "font-family"       => Ok(parse_css_font_family(value)?.into()),
+            "-azul-icon-font"   => Ok(parse_css_icon_font(value)?.into()),
+            "font-display"      => Ok(parse_style_font_display(value)?.into()),
+            "font-feature-settings" => Ok(parse_css_font_feature_settings(value)?.into()),
             "box-shadow"        => Ok(parse_css_box_shadow(value)?.into()),
+            "text-shadow"       => Ok(parse_css_text_shadow(value)?.into()),
             "line-height"       => Ok(parse_line_height(value)?.into()),
+            "letter-spacing"    => Ok(LetterSpacing(parse_spacing(value)?).into()),
+            "word-spacing"      => Ok(WordSpacing(parse_spacing(value)?).into()),
+            "word-break"        => Ok(parse_css_word_break(value)?.into()),
+            // `word-wrap` is the legacy name of `overflow-wrap`
+            "overflow-wrap" |
+            "word-wrap"         => Ok(parse_css_overflow_wrap(value)?.into()),
+            "text-decoration"   => Ok(parse_css_text_decoration(value)?.into()),
+            "text-decoration-line" => Ok(StyleTextDecoration { line: Some(parse_css_text_decoration_line(value)?), .. Default::default() }.into()),
+            "text-decoration-style" => Ok(StyleTextDecoration { style: Some(parse_css_text_decoration_style(value)?), .. Default::default() }.into()),
+            "text-decoration-color" => Ok(StyleTextDecoration { color: Some(parse_css_color(value)?), .. Default::default() }.into()),
+            "text-decoration-thickness" => Ok(StyleTextDecoration { thickness: Some(parse_pixel_value(value)?), .. Default::default() }.into()),
+            "filter"            => Ok(Filter(parse_css_filters(value)?).into()),
+            "backdrop-filter"   => Ok(BackdropFilter(parse_css_filters(value)?).into()),
+            "opacity"           => Ok(parse_css_opacity(value)?.into()),
+            "visibility"        => Ok(parse_css_visibility(value)?.into()),
+            "will-change"       => Ok(parse_css_will_change(value)?.into()),
+            "isolation"         => Ok(parse_css_isolation(value)?.into()),
+            "mix-blend-mode"    => Ok(parse_css_mix_blend_mode(value)?.into()),
+            "scroll-behavior"   => Ok(parse_css_scroll_behavior(value)?.into()),
+            "scroll-snap-type"  => Ok(parse_css_scroll_snap_type(value)?.into()),
+            "scroll-snap-align" => Ok(parse_css_scroll_snap_align(value)?.into()),
+            "scroll-margin"         => Ok(parse_css_scroll_margin(value)?.into()),
+            "scroll-margin-top"     => Ok(StyleScrollMargin { top: Some(parse_scroll_side_value(value)?), .. Default::default() }.into()),
+            "scroll-margin-right"   => Ok(StyleScrollMargin { right: Some(parse_scroll_side_value(value)?), .. Default::default() }.into()),
+            "scroll-margin-bottom"  => Ok(StyleScrollMargin { bottom: Some(parse_scroll_side_value(value)?), .. Default::default() }.into()),
+            "scroll-margin-left"    => Ok(StyleScrollMargin { left: Some(parse_scroll_side_value(value)?), .. Default::default() }.into()),
+            "scroll-padding"        => Ok(parse_css_scroll_padding(value)?.into()),
+            "scroll-padding-top"    => Ok(StyleScrollPadding { top: Some(parse_scroll_side_value(value)?), .. Default::default() }.into()),
+            "scroll-padding-right"  => Ok(StyleScrollPadding { right: Some(parse_scroll_side_value(value)?), .. Default::default() }.into()),
+            "scroll-padding-bottom" => Ok(StyleScrollPadding { bottom: Some(parse_scroll_side_value(value)?), .. Default::default() }.into()),
+            "scroll-padding-left"   => Ok(StyleScrollPadding { left: Some(parse_scroll_side_value(value)?), .. Default::default() }.into()),
+            "caret-color"       => Ok(parse_css_caret_color(value)?.into()),
+            "caret-shape"       => Ok(parse_css_caret_shape(value)?.into()),
+            "user-select"       => Ok(parse_css_user_select(value)?.into()),
+            "pointer-events"    => Ok(parse_css_pointer_events(value)?.into()),
+            "appearance"        => Ok(parse_css_appearance(value)?.into()),
+            "background-clip"   => Ok(parse_css_background_clip(value)?.into()),
+            "resize"            => Ok(parse_layout_resize(value)?.into()),
+            "table-layout"      => Ok(parse_layout_table_layout(value)?.into()),
+            "vertical-align"    => Ok(parse_style_vertical_align(value)?.into()),
+            "shape-outside"     => Ok(parse_css_shape_outside(value)?.into()),
+            "column-count"      => Ok(parse_layout_column_count(value)?.into()),
+            "column-width"      => Ok(parse_layout_column_width(value)?.into()),
+            "column-gap"        => Ok(parse_layout_column_gap(value)?.into()),
+            "column-rule"       => Ok(parse_css_column_rule(value)?.into()),
+            "column-rule-width" => Ok(StyleColumnRule { width: Some(parse_pixel_value(value)?), .. Default::default() }.into()),
+            "column-rule-style" => Ok(StyleColumnRule { style: Some(parse_border_style(value)?), .. Default::default() }.into()),
+            "column-rule-color" => Ok(StyleColumnRule { color: Some(parse_css_color(value)?), .. Default::default() }.into()),
+            "object-fit"        => Ok(parse_css_object_fit(value)?.into()),
+            "object-position"   => Ok(parse_css_object_position(value)?.into()),
+            "image-rendering"   => Ok(parse_css_image_rendering(value)?.into()),
+            "border-image"      => Ok(parse_css_border_image(value)?.into()),
+            "counter-reset"     => Ok(StyleCounterReset { counters: parse_css_counter_list(value, 0)? }.into()),
+            "counter-increment" => Ok(StyleCounterIncrement { counters: parse_css_counter_list(value, 1)? }.into()),
+            // `content` of the `::before` pseudo-element
+            "-azul-before-content" => Ok(parse_css_content(value)?.into()),
+            "list-style-type"   => Ok(parse_css_list_style_type(value)?.into()),
+            "list-style-position" => Ok(parse_css_list_style_position(value)?.into()),
+            "list-style-image"  => Ok(parse_css_list_style_image(value)?.into()),
+            "writing-mode"      => Ok(parse_layout_writing_mode(value)?.into()),
+            "inline-size"       => Ok(LogicalSize::InlineSize(parse_pixel_value(value)?).into()),
+            "block-size"        => Ok(LogicalSize::BlockSize(parse_pixel_value(value)?).into()),
+            "min-inline-size"   => Ok(LogicalSize::MinInlineSize(parse_pixel_value(value)?).into()),
+            "min-block-size"    => Ok(LogicalSize::MinBlockSize(parse_pixel_value(value)?).into()),
+            "max-inline-size"   => Ok(LogicalSize::MaxInlineSize(parse_pixel_value(value)?).into()),
+            "max-block-size"    => Ok(LogicalSize::MaxBlockSize(parse_pixel_value(value)?).into()),
+            "direction"         => Ok(parse_style_direction(value)?.into()),
+            "margin-inline-start"   => Ok(LogicalBoxProperty::Margin(LogicalSide::InlineStart, parse_layout_margin_value(value)?).into()),
+            "margin-inline-end"     => Ok(LogicalBoxProperty::Margin(LogicalSide::InlineEnd, parse_layout_margin_value(value)?).into()),
+            "margin-block-start"    => Ok(LogicalBoxProperty::Margin(LogicalSide::BlockStart, parse_layout_margin_value(value)?).into()),
+            "margin-block-end"      => Ok(LogicalBoxProperty::Margin(LogicalSide::BlockEnd, parse_layout_margin_value(value)?).into()),
+            "padding-inline-start"  => Ok(LogicalBoxProperty::Padding(LogicalSide::InlineStart, parse_layout_padding_value(value)?).into()),
+            "padding-inline-end"    => Ok(LogicalBoxProperty::Padding(LogicalSide::InlineEnd, parse_layout_padding_value(value)?).into()),
+            "padding-block-start"   => Ok(LogicalBoxProperty::Padding(LogicalSide::BlockStart, parse_layout_padding_value(value)?).into()),
+            "padding-block-end"     => Ok(LogicalBoxProperty::Padding(LogicalSide::BlockEnd, parse_layout_padding_value(value)?).into()),
+            "border-inline-start"   => Ok(LogicalBoxProperty::Border(LogicalSide::InlineStart, parse_css_border_side(value)?).into()),
+            "border-inline-end"     => Ok(LogicalBoxProperty::Border(LogicalSide::InlineEnd, parse_css_border_side(value)?).into()),
+            "border-block-start"    => Ok(LogicalBoxProperty::Border(LogicalSide::BlockStart, parse_css_border_side(value)?).into()),
+            "border-block-end"      => Ok(LogicalBoxProperty::Border(LogicalSide::BlockEnd, parse_css_border_side(value)?).into()),
+            "scrollbar-width"   => Ok(parse_layout_scrollbar_width(value)?.into()),
+            "-azul-scrollbar-thumb-color" => Ok(parse_css_scrollbar_thumb_color(value)?.into()),
+            "-azul-scrollbar-track-color" => Ok(parse_css_scrollbar_track_color(value)?.into()),
 
             "width"             => Ok(parse_layout_width(value)?.into()),
             "height"            => Ok(parse_layout_height(value)?.into()),
@@ -166,13 +375,7 @@ impl ParsedCssProperty {
             "justify-content"   => Ok(parse_layout_justify_content(value)?.into()),
             "align-items"       => Ok(parse_layout_align_items(value)?.into()),
             "align-content"     => Ok(parse_layout_align_content(value)?.into()),
-            "overflow"          => {
-                let overflow_both_directions = parse_layout_text_overflow(value)?;
-                Ok(LayoutOverflow {
-                    horizontal: TextOverflowBehaviour::Modified(overflow_both_directions),
-                    vertical: TextOverflowBehaviour::Modified(overflow_both_directions),
-                }.into())
-            },
+            "overflow"          => Ok(parse_layout_overflow(value)?.into()),
             "overflow-x"        => {
                 let overflow_x = parse_layout_text_overflow(value)?;
                 Ok(LayoutOverflow {
@@ -188,6 +391,20 @@ impl ParsedCssProperty {
                 }.into())
             },
             "text-align"        => Ok(parse_layout_text_align(value)?.into()),
+            "margin"            => Ok(parse_layout_margin(value)?.into()),
+            "margin-top"        => Ok(LayoutMargin { top: Some(parse_layout_margin_value(value)?), .. Default::default() }.into()),
+            "margin-right"      => Ok(LayoutMargin { right: Some(parse_layout_margin_value(value)?), .. Default::default() }.into()),
+            "margin-bottom"     => Ok(LayoutMargin { bottom: Some(parse_layout_margin_value(value)?), .. Default::default() }.into()),
+            "margin-left"       => Ok(LayoutMargin { left: Some(parse_layout_margin_value(value)?), .. Default::default() }.into()),
+            "padding"           => Ok(parse_layout_padding(value)?.into()),
+            "padding-top"       => Ok(LayoutPadding { top: Some(parse_layout_padding_value(value)?), .. Default::default() }.into()),
+            "padding-right"     => Ok(LayoutPadding { right: Some(parse_layout_padding_value(value)?), .. Default::default() }.into()),
+            "padding-bottom"    => Ok(LayoutPadding { bottom: Some(parse_layout_padding_value(value)?), .. Default::default() }.into()),
+            "padding-left"      => Ok(LayoutPadding { left: Some(parse_layout_padding_value(value)?), .. Default::default() }.into()),
+            "display"           => Ok(parse_layout_display(value)?.into()),
+            "float"             => Ok(parse_layout_float(value)?.into()),
+            "clear"             => Ok(parse_layout_clear(value)?.into()),
+            "all"               => Ok(parse_style_all(value)?.into()),
 
             _ => Err((key, value).into())
         }
@@ -227,6 +444,59 @@ impl LayoutOverflow {
             TextOverflowBehaviour::NotModified => false,
         }
     }
+
+    /// Returns whether the vertical scrollbar is drawn (on the right side): always for
+    /// `overflow-y: scroll`, for `auto` only if the content is overflowing vertically.
+    /// The content of the container is clipped in both directions as long as one direction
+    /// isn't `visible`, so `hidden` only differs from `scroll` in the scrollbar.
+    pub fn shows_vertical_scrollbar(&self, is_overflowing: bool) -> bool {
+        shows_scrollbar(self.vertical, is_overflowing)
+    }
+
+    /// Returns whether the horizontal scrollbar is drawn (at the bottom), see `shows_vertical_scrollbar()`
+    pub fn shows_horizontal_scrollbar(&self, is_overflowing: bool) -> bool {
+        shows_scrollbar(self.horizontal, is_overflowing)
+    }
+
+    /// Returns whether the content isn't clipped in either direction (`overflow: visible`,
+    /// the default). Only scroll containers (`hidden`, `scroll` or `auto`) can be resized.
+    pub fn is_visible(&self) -> bool {
+        use self::TextOverflowBehaviourInner::*;
+        let is_direction_visible = |direction: TextOverflowBehaviour| match direction {
+            TextOverflowBehaviour::Modified(Visible) | TextOverflowBehaviour::NotModified => true,
+            TextOverflowBehaviour::Modified(_) => false,
+        };
+        is_direction_visible(self.horizontal) && is_direction_visible(self.vertical)
+    }
+}
+
+fn shows_scrollbar(direction: TextOverflowBehaviour, is_overflowing: bool) -> bool {
+    use self::TextOverflowBehaviourInner::*;
+    match direction {
+        TextOverflowBehaviour::Modified(Scroll) => true,
+        TextOverflowBehaviour::Modified(Auto) => is_overflowing,
+        TextOverflowBehaviour::Modified(Hidden) |
+        TextOverflowBehaviour::Modified(Visible) |
+        TextOverflowBehaviour::NotModified => false,
+    }
+}
+
+/// Parses the `overflow` shorthand, either one value for both directions or
+/// two values for the horizontal and the vertical direction (`overflow: hidden scroll`)
+fn parse_layout_overflow<'a>(input: &'a str) -> Result<LayoutOverflow, InvalidValueErr<'a>> {
+    let mut values = input.split_whitespace();
+    let horizontal = parse_layout_text_overflow(values.next().unwrap_or(input))?;
+    let vertical = match values.next() {
+        Some(value) => parse_layout_text_overflow(value)?,
+        None => horizontal,
+    };
+    if values.next().is_some() {
+        return Err(InvalidValueErr(input));
+    }
+    Ok(LayoutOverflow {
+        horizontal: TextOverflowBehaviour::Modified(horizontal),
+        vertical: TextOverflowBehaviour::Modified(vertical),
+    })
 }
 
 /// Error containing all sub-errors that could happen during CSS parsing
@@ -244,6 +514,14 @@ pub enum CssParsingError<'a> {
     CssBackgroundParseError(CssBackgroundParseError<'a>),
     CssColorParseError(CssColorParseError<'a>),
     CssBorderRadiusParseError(CssBorderRadiusParseError<'a>),
+    CssFilterParseError(CssFilterParseError<'a>),
+    CssFontFeatureParseError(CssFontFeatureParseError<'a>),
+    CssMarginParseError(CssMarginParseError<'a>),
+    CssPaddingParseError(CssPaddingParseError<'a>),
+    CssBorderImageParseError(CssBorderImageParseError<'a>),
+    CssCounterParseError(CssCounterParseError<'a>),
+    CssContentParseError(CssContentParseError<'a>),
+    CssShapeOutsideParseError(CssShapeOutsideParseError<'a>),
     /// Key is not supported, i.e. `#div { aldfjasdflk: 400px }` results in an
     /// `UnsupportedCssKey("aldfjasdflk", "400px")` error
     UnsupportedCssKey(&'a str, &'a str),
@@ -258,6 +536,14 @@ impl_from!(CssImageParseError, CssParsingError::CssImageParseError);
 impl_from!(CssFontFamilyParseError, CssParsingError::CssFontFamilyParseError);
 impl_from!(CssBackgroundParseError, CssParsingError::CssBackgroundParseError);
 impl_from!(CssBorderRadiusParseError, CssParsingError::CssBorderRadiusParseError);
+impl_from!(CssFilterParseError, CssParsingError::CssFilterParseError);
+impl_from!(CssFontFeatureParseError, CssParsingError::CssFontFeatureParseError);
+impl_from!(CssMarginParseError, CssParsingError::CssMarginParseError);
+impl_from!(CssPaddingParseError, CssParsingError::CssPaddingParseError);
+impl_from!(CssBorderImageParseError, CssParsingError::CssBorderImageParseError);
+impl_from!(CssCounterParseError, CssParsingError::CssCounterParseError);
+impl_from!(CssContentParseError, CssParsingError::CssContentParseError);
+impl_from!(CssShapeOutsideParseError, CssParsingError::CssShapeOutsideParseError);
 
 impl<'a> From<(&'a str, &'a str)> for CssParsingError<'a> {
     fn from((a, b): (&'a str, &'a str)) -> Self {
@@ -326,6 +612,213 @@ impl PixelValue {
     }
 }
 
+/// Wrapper for the `margin` shorthand + `margin-{top,right,bottom,left}` properties
+///
+/// Sides that are `None` weren't set by the CSS rule, so that a `margin-left`
+/// doesn't reset a previously set `margin-right`
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct LayoutMargin {
+    pub top: Option<LayoutMarginValue>,
+    pub right: Option<LayoutMarginValue>,
+    pub bottom: Option<LayoutMarginValue>,
+    pub left: Option<LayoutMarginValue>,
+}
+
+impl LayoutMargin {
+
+    /// Creates a margin with the same value on all four sides
+    pub fn uniform(value: LayoutMarginValue) -> Self {
+        Self {
+            top: Some(value),
+            right: Some(value),
+            bottom: Some(value),
+            left: Some(value),
+        }
+    }
+
+    // "merges" two LayoutMargin properties, sides set in `other` take precedence
+    pub fn merge(&mut self, other: &LayoutMargin) {
+        fn merge_side(p: &mut Option<LayoutMarginValue>, other: &Option<LayoutMarginValue>) {
+            if other.is_some() {
+                *p = *other;
+            }
+        }
+
+        merge_side(&mut self.top, &other.top);
+        merge_side(&mut self.right, &other.right);
+        merge_side(&mut self.bottom, &other.bottom);
+        merge_side(&mut self.left, &other.left);
+    }
+}
+
+/// Value of a single margin side, i.e. `auto` or `10px`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LayoutMarginValue {
+    /// Consumes the available space on this side
+    Auto,
+    Fixed(PixelValue),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CssMarginParseError<'a> {
+    TooManyValues(&'a str),
+    PixelParseError(PixelParseError<'a>),
+}
+
+impl_from!(PixelParseError, CssMarginParseError::PixelParseError);
+
+/// Wrapper for the `padding` shorthand + `padding-{top,right,bottom,left}` properties
+///
+/// Like `LayoutMargin`, sides that are `None` weren't set by the CSS rule
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct LayoutPadding {
+    pub top: Option<LayoutPaddingValue>,
+    pub right: Option<LayoutPaddingValue>,
+    pub bottom: Option<LayoutPaddingValue>,
+    pub left: Option<LayoutPaddingValue>,
+}
+
+/// Value of a single padding side, i.e. `10px` or `10%`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LayoutPaddingValue {
+    Fixed(PixelValue),
+    /// Percentages refer to the *width* of the containing block, also for the
+    /// top and bottom padding (i.e. `padding-top: 56.25%` for a 16:9 aspect ratio)
+    Percentage(PercentageValue),
+}
+
+impl LayoutPaddingValue {
+    pub fn to_pixels(&self, containing_block_width: f32) -> f32 {
+        let (fixed, width_factor) = self.split();
+        fixed + width_factor * containing_block_width
+    }
+
+    /// Splits the value into the fixed pixels and the factor of the
+    /// containing block width, for building linear constraints
+    pub(crate) fn split(&self) -> (f32, f32) {
+        match *self {
+            LayoutPaddingValue::Fixed(p) => (p.to_pixels(), 0.0),
+            LayoutPaddingValue::Percentage(p) => (0.0, p.number),
+        }
+    }
+}
+
+impl LayoutPadding {
+
+    // "merges" two LayoutPadding properties, sides set in `other` take precedence
+    pub fn merge(&mut self, other: &LayoutPadding) {
+        fn merge_side(p: &mut Option<LayoutPaddingValue>, other: &Option<LayoutPaddingValue>) {
+            if other.is_some() {
+                *p = *other;
+            }
+        }
+
+        merge_side(&mut self.top, &other.top);
+        merge_side(&mut self.right, &other.right);
+        merge_side(&mut self.bottom, &other.bottom);
+        merge_side(&mut self.left, &other.left);
+    }
+
+    /// Sum of the left and right padding in pixels
+    pub fn horizontal(&self, containing_block_width: f32) -> f32 {
+        let (fixed, width_factor) = self.horizontal_parts();
+        fixed + width_factor * containing_block_width
+    }
+
+    /// Sum of the top and bottom padding in pixels
+    pub fn vertical(&self, containing_block_width: f32) -> f32 {
+        let (fixed, width_factor) = self.vertical_parts();
+        fixed + width_factor * containing_block_width
+    }
+
+    /// Sum of the left and right padding, split like `LayoutPaddingValue::split()`
+    pub(crate) fn horizontal_parts(&self) -> (f32, f32) {
+        add_padding_parts(self.left, self.right)
+    }
+
+    /// Sum of the top and bottom padding, split like `LayoutPaddingValue::split()`
+    pub(crate) fn vertical_parts(&self) -> (f32, f32) {
+        add_padding_parts(self.top, self.bottom)
+    }
+}
+
+fn add_padding_parts(a: Option<LayoutPaddingValue>, b: Option<LayoutPaddingValue>) -> (f32, f32) {
+    let (a_fixed, a_factor) = a.map(|a| a.split()).unwrap_or((0.0, 0.0));
+    let (b_fixed, b_factor) = b.map(|b| b.split()).unwrap_or((0.0, 0.0));
+    (a_fixed + b_fixed, a_factor + b_factor)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CssPaddingParseError<'a> {
+    TooManyValues(&'a str),
+    PixelParseError(PixelParseError<'a>),
+    PercentageParseError(PercentageParseError),
+}
+
+impl_from!(PixelParseError, CssPaddingParseError::PixelParseError);
+
+impl<'a> From<PercentageParseError> for CssPaddingParseError<'a> {
+    fn from(e: PercentageParseError) -> Self {
+        CssPaddingParseError::PercentageParseError(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssBorderImageParseError<'a> {
+    /// The declaration doesn't start with `url(...)` or `image(...)`
+    InvalidImage(&'a str),
+    UnclosedQuotes(&'a str),
+    /// The slices have to be one to four unitless numbers
+    InvalidSlice(&'a str),
+    InvalidRepeat(InvalidValueErr<'a>),
+}
+
+impl_from!(InvalidValueErr, CssBorderImageParseError::InvalidRepeat);
+
+impl<'a> From<UnclosedQuotesError<'a>> for CssBorderImageParseError<'a> {
+    fn from(err: UnclosedQuotesError<'a>) -> Self {
+        CssBorderImageParseError::UnclosedQuotes(err.0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CssCounterParseError<'a> {
+    /// Counter names have to be identifiers (and not `none`)
+    InvalidCounterName(&'a str),
+    /// A number that doesn't follow a counter name, i.e. `counter-reset: 2`
+    InvalidCounterValue(&'a str),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CssContentParseError<'a> {
+    UnclosedQuotes(&'a str),
+    /// Only strings, `counter(name)` and `counters(name, "separator")` are supported
+    InvalidContent(&'a str),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CssShapeOutsideParseError<'a> {
+    /// Only `none`, `circle()`, `ellipse()`, `inset()` and `polygon()` are supported
+    InvalidShape(&'a str),
+    /// The length is neither a pixel value nor a percentage
+    InvalidLength(&'a str),
+    /// Wrong number of arguments for the shape function
+    InvalidArguments(&'a str),
+}
+
+impl<'a> fmt::Display for CssShapeOutsideParseError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CssShapeOutsideParseError::*;
+        match self {
+            InvalidShape(s) => write!(f, "invalid shape: \"{}\", expected none, circle(), ellipse(), inset() or polygon()", s),
+            InvalidLength(s) => write!(f, "invalid length in shape: \"{}\", expected a pixel value or a percentage", s),
+            InvalidArguments(s) => write!(f, "wrong number of arguments for the shape function: \"{}\"", s),
+        }
+    }
+}
+
+impl<'a> ::std::error::Error for CssShapeOutsideParseError<'a> { }
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CssBorderRadiusParseError<'a> {
     TooManyValues(&'a str),
@@ -344,6 +837,8 @@ pub enum CssColorParseError<'a> {
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum CssImageParseError<'a> {
     UnclosedQuotes(&'a str),
+    /// The value doesn't start with `url(...)` or `image(...)`
+    InvalidImage(&'a str),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -374,6 +869,29 @@ pub enum CssShadowParseError<'a> {
 impl_from!(PixelParseError, CssShadowParseError::ValueParseErr);
 impl_from!(CssColorParseError, CssShadowParseError::ColorParseError);
 
+// splits the input at every character matching `is_separator` that is not inside of parentheses,
+// i.e. "2px rgba(0, 0, 0, 0.5)" => ["2px", "rgba(0, 0, 0, 0.5)"]
+fn split_outside_parentheses<'a, F: Fn(char) -> bool>(input: &'a str, is_separator: F) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut nesting = 0_usize;
+    let mut part_start = 0;
+
+    for (idx, ch) in input.char_indices() {
+        match ch {
+            '(' => nesting += 1,
+            ')' => nesting = nesting.saturating_sub(1),
+            c if nesting == 0 && is_separator(c) => {
+                parts.push(&input[part_start..idx]);
+                part_start = idx + c.len_utf8();
+            },
+            _ => { },
+        }
+    }
+
+    parts.push(&input[part_start..]);
+    parts.into_iter().map(|part| part.trim()).filter(|part| !part.is_empty()).collect()
+}
+
 /// parse the border-radius like "5px 10px" or "5px 10px 6px 10px"
 fn parse_css_border_radius<'a>(input: &'a str)
 -> Result<BorderRadius, CssBorderRadiusParseError<'a>>
@@ -450,6 +968,75 @@ pub enum PixelParseError<'a> {
     ValueParseErr(ParseFloatError),
 }
 
+// parses "auto", "0" or "10px"
+fn parse_layout_margin_value<'a>(input: &'a str)
+-> Result<LayoutMarginValue, PixelParseError<'a>>
+{
+    match input {
+        "auto" => Ok(LayoutMarginValue::Auto),
+        "0" => Ok(LayoutMarginValue::Fixed(PixelValue { metric: CssMetric::Px, number: 0.0 })),
+        other => Ok(LayoutMarginValue::Fixed(parse_pixel_value(other)?)),
+    }
+}
+
+// Splits the one to four values of a `margin` / `padding` shorthand
+// into the `[top, right, bottom, left]` components
+fn split_box_shorthand<'a>(input: &'a str) -> Option<[&'a str; 4]> {
+    let components = input.split_whitespace().collect::<Vec<&str>>();
+    match components.len() {
+        // all
+        1 => Some([components[0], components[0], components[0], components[0]]),
+        // vertical horizontal
+        2 => Some([components[0], components[1], components[0], components[1]]),
+        // top horizontal bottom
+        3 => Some([components[0], components[1], components[2], components[1]]),
+        // top right bottom left
+        4 => Some([components[0], components[1], components[2], components[3]]),
+        _ => None,
+    }
+}
+
+// parses "auto", "10px 5px", "10px auto 5px" or "1px 2px 3px 4px"
+fn parse_layout_margin<'a>(input: &'a str)
+-> Result<LayoutMargin, CssMarginParseError<'a>>
+{
+    let [top, right, bottom, left] = split_box_shorthand(input)
+        .ok_or(CssMarginParseError::TooManyValues(input))?;
+
+    Ok(LayoutMargin {
+        top: Some(parse_layout_margin_value(top)?),
+        right: Some(parse_layout_margin_value(right)?),
+        bottom: Some(parse_layout_margin_value(bottom)?),
+        left: Some(parse_layout_margin_value(left)?),
+    })
+}
+
+// parses "0", "10px" or "10%"
+fn parse_layout_padding_value<'a>(input: &'a str)
+-> Result<LayoutPaddingValue, CssPaddingParseError<'a>>
+{
+    match input {
+        "0" => Ok(LayoutPaddingValue::Fixed(PixelValue { metric: CssMetric::Px, number: 0.0 })),
+        other if other.ends_with('%') => Ok(LayoutPaddingValue::Percentage(parse_percentage_value(other)?)),
+        other => Ok(LayoutPaddingValue::Fixed(parse_pixel_value(other)?)),
+    }
+}
+
+// parses "10px", "10px 5px", "10px 5px 0" or "1px 2px 3px 4px"
+fn parse_layout_padding<'a>(input: &'a str)
+-> Result<LayoutPadding, CssPaddingParseError<'a>>
+{
+    let [top, right, bottom, left] = split_box_shorthand(input)
+        .ok_or(CssPaddingParseError::TooManyValues(input))?;
+
+    Ok(LayoutPadding {
+        top: Some(parse_layout_padding_value(top)?),
+        right: Some(parse_layout_padding_value(right)?),
+        bottom: Some(parse_layout_padding_value(bottom)?),
+        left: Some(parse_layout_padding_value(left)?),
+    })
+}
+
 /// parse a single value such as "15px"
 fn parse_pixel_value<'a>(input: &'a str)
 -> Result<PixelValue, PixelParseError<'a>>
@@ -513,16 +1100,57 @@ fn parse_percentage_value(input: &str)
 ///
 /// "blue" -> "00FF00" -> ColorF { r: 0, g: 255, b: 0 })
 /// "#00FF00" -> ColorF { r: 0, g: 255, b: 0 })
+/// "rgba(0, 255, 0, 0.5)" -> ColorF { r: 0, g: 255, b: 0, a: 128 })
 pub(crate) fn parse_css_color<'a>(input: &'a str)
 -> Result<ColorU, CssColorParseError<'a>>
 {
+    let input = input.trim();
     if input.starts_with('#') {
         parse_color_no_hash(&input[1..])
+    } else if input.starts_with("rgb(") || input.starts_with("rgba(") {
+        parse_color_rgb(input)
     } else {
         parse_color_builtin(input)
     }
 }
 
+// parses "rgb(0, 255, 0)", "rgba(0, 255, 0, 0.5)" or "rgb(0%, 100%, 0%, 50%)",
+// both functions accept an optional alpha value
+fn parse_color_rgb<'a>(input: &'a str)
+-> Result<ColorU, CssColorParseError<'a>>
+{
+    // percentages and alpha values are mapped onto 0 - 255
+    fn parse_component<'a>(component: &'a str, max: f32) -> Result<u8, CssColorParseError<'a>> {
+        let value = if component.ends_with('%') {
+            component[..component.len() - 1].trim().parse::<f32>().map(|percent| percent / 100.0 * 255.0)
+        } else {
+            component.parse::<f32>().map(|value| value / max * 255.0)
+        }.map_err(|_| CssColorParseError::InvalidColor(component))?;
+
+        Ok(value.max(0.0).min(255.0).round() as u8)
+    }
+
+    if !input.ends_with(')') {
+        return Err(CssColorParseError::InvalidColor(input));
+    }
+
+    let arguments = &input[input.find('(').unwrap() + 1..input.len() - 1];
+    let components = arguments.split(',').map(|c| c.trim()).collect::<Vec<&str>>();
+
+    match components.len() {
+        3 | 4 => Ok(ColorU {
+            r: parse_component(components[0], 255.0)?,
+            g: parse_component(components[1], 255.0)?,
+            b: parse_component(components[2], 255.0)?,
+            a: match components.get(3) {
+                Some(alpha) => parse_component(alpha, 1.0)?,
+                None => 255,
+            },
+        }),
+        _ => Err(CssColorParseError::InvalidColor(input)),
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct BackgroundColor(pub ColorU);
 
@@ -541,6 +1169,36 @@ fn parse_css_text_color<'a>(input: &'a str)
     parse_css_color(input).and_then(|ok| Ok(TextColor(ok)))
 }
 
+/// Color of the text cursor in text inputs
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CaretColor(pub ColorU);
+
+fn parse_css_caret_color<'a>(input: &'a str)
+-> Result<CaretColor, CssColorParseError<'a>>
+{
+    parse_css_color(input).and_then(|ok| Ok(CaretColor(ok)))
+}
+
+/// Color of the scrollbar thumb, set via the `::-azul-scrollbar-thumb` pseudo-element
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ScrollbarThumbColor(pub ColorU);
+
+fn parse_css_scrollbar_thumb_color<'a>(input: &'a str)
+-> Result<ScrollbarThumbColor, CssColorParseError<'a>>
+{
+    parse_css_color(input).and_then(|ok| Ok(ScrollbarThumbColor(ok)))
+}
+
+/// Color of the scrollbar track, set via the `::-azul-scrollbar-track` pseudo-element
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ScrollbarTrackColor(pub ColorU);
+
+fn parse_css_scrollbar_track_color<'a>(input: &'a str)
+-> Result<ScrollbarTrackColor, CssColorParseError<'a>>
+{
+    parse_css_color(input).and_then(|ok| Ok(ScrollbarTrackColor(ok)))
+}
+
 /// Parse a built-in background color
 ///
 /// "blue" -> "00FF00" -> ColorF { r: 0, g: 255, b: 0 })
@@ -697,6 +1355,17 @@ fn parse_color_builtin<'a>(input: &'a str)
         "Yellow"                 | "yellow"                     =>  "FFFF00",
         "YellowGreen"            | "yellow-green"               =>  "9ACD32",
         "Transparent"            | "transparent"                =>  "FFFFFFFF",
+        // System colors for `@media (forced-colors: active)`, with the
+        // values of the Windows "High Contrast Black" theme. The colors are
+        // fixed, they don't follow the high contrast theme of the OS.
+        "Canvas"                 | "canvas"                     =>  "000000",
+        "CanvasText"             | "canvastext"                 =>  "FFFFFF",
+        "LinkText"               | "linktext"                   =>  "FFFF00",
+        "ButtonFace"             | "buttonface"                 =>  "000000",
+        "ButtonText"             | "buttontext"                 =>  "FFFFFF",
+        "Highlight"              | "highlight"                  =>  "1AEBFF",
+        "HighlightText"          | "highlighttext"              =>  "000000",
+        "GrayText"               | "graytext"                   =>  "3FF23F",
         _ => { return Err(CssColorParseError::InvalidColor(input)); }
     };
     parse_color_no_hash(color)
@@ -832,6 +1501,16 @@ fn parse_css_border<'a>(input: &'a str)
     Ok((border_widths, border_details))
 }
 
+/// Parses the border of a single side (`border-top: 1px solid red`), same syntax as `border`
+fn parse_css_border_side<'a>(input: &'a str)
+-> Result<(f32, BorderSide), CssBorderParseError<'a>>
+{
+    match parse_css_border(input)? {
+        (widths, BorderDetails::Normal(normal)) => Ok((widths.top, normal.top)),
+        _ => Err(CssBorderParseError::InvalidBorderDeclaration(input)),
+    }
+}
+
 /// Parse a border style such as "none", "dotted", etc.
 ///
 /// "solid", "none", etc.
@@ -857,6 +1536,62 @@ pub struct BoxShadowPreDisplayItem {
     pub clip_mode: BoxShadowClipMode,
 }
 
+/// A single shadow of the `text-shadow` property
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextShadowItem {
+    pub offset: LayoutVector2D,
+    /// `None` = use the color of the text
+    pub color: Option<ColorF>,
+    pub blur_radius: f32,
+}
+
+/// `text-shadow` property: shadows drawn behind the text, the first shadow is drawn on top.
+/// `text-shadow: none` results in an empty list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextShadow(pub Vec<TextShadowItem>);
+
+// parses "none" or a comma-separated list of shadows, such as "2px 2px 4px rgba(0,0,0,0.5), 1px 1px red"
+fn parse_css_text_shadow<'a>(input: &'a str)
+-> Result<TextShadow, CssShadowParseError<'a>>
+{
+    if input.trim() == "none" {
+        return Ok(TextShadow(Vec::new()));
+    }
+
+    split_outside_parentheses(input, |c| c == ',')
+        .into_iter()
+        .map(parse_css_text_shadow_item)
+        .collect::<Result<Vec<TextShadowItem>, CssShadowParseError<'a>>>()
+        .map(TextShadow)
+}
+
+// parses a single shadow, such as "2px 2px 4px red" (h_offset, v_offset, blur, color),
+// the blur and the color are optional and the color may also come first
+fn parse_css_text_shadow_item<'a>(input: &'a str)
+-> Result<TextShadowItem, CssShadowParseError<'a>>
+{
+    let mut lengths = Vec::new();
+    let mut color = None;
+
+    for component in split_outside_parentheses(input, char::is_whitespace) {
+        match parse_pixel_value(component) {
+            Ok(length) => lengths.push(length.to_pixels()),
+            Err(_) if color.is_none() => color = Some(ColorF::from(parse_css_color(component)?)),
+            Err(_) => return Err(CssShadowParseError::TooManyComponents(input)),
+        }
+    }
+
+    match lengths.len() {
+        2 | 3 => Ok(TextShadowItem {
+            offset: LayoutVector2D::new(lengths[0], lengths[1]),
+            color: color,
+            blur_radius: lengths.get(2).cloned().unwrap_or(0.0),
+        }),
+        0 | 1 => Err(CssShadowParseError::InvalidSingleStatement(input)),
+        _ => Err(CssShadowParseError::TooManyComponents(input)),
+    }
+}
+
 /// Parses a CSS box-shadow
 fn parse_css_box_shadow<'a>(input: &'a str)
 -> Result<Option<BoxShadowPreDisplayItem>, CssShadowParseError<'a>>
@@ -1505,8 +2240,162 @@ pub struct LayoutMinHeight(pub PixelValue);
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub struct LayoutMaxHeight(pub PixelValue);
 
+/// `line-height` property
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum LineHeight {
+    /// `normal`: the natural line height of the font
+    Normal,
+    /// `1.5` or `150%`: multiple of the natural line height of the font
+    Multiplier(PercentageValue),
+    /// `24px`: absolute line height, independent of the font size
+    Absolute(PixelValue),
+}
+
+/// `letter-spacing` property: extra space after each character,
+/// `em` values are relative to the font size of the text
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct LetterSpacing(pub PixelValue);
+/// `word-spacing` property: extra space between two words,
+/// `em` values are relative to the font size of the text
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct WordSpacing(pub PixelValue);
+
+/// `word-break` property: where lines may break inside of words
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleWordBreak {
+    /// Only break at spaces
+    Normal,
+    /// Break between any two characters, even if the word would fit on the next line
+    BreakAll,
+    /// Don't break CJK text inside of words - since the text is only broken
+    /// at spaces anyway, this behaves like `normal`
+    KeepAll,
+}
+
+impl Default for StyleWordBreak {
+    fn default() -> Self {
+        StyleWordBreak::Normal
+    }
+}
+
+/// `overflow-wrap` (or `word-wrap`) property: whether words that are too
+/// long for a line may be broken
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleOverflowWrap {
+    /// Long words overflow the line
+    Normal,
+    /// Words are only broken if they don't fit on their own line
+    BreakWord,
+    /// Same as `break-word` (the only difference is how the minimum
+    /// width of the text is calculated, which azul doesn't do)
+    Anywhere,
+}
+
+impl Default for StyleOverflowWrap {
+    fn default() -> Self {
+        StyleOverflowWrap::Normal
+    }
+}
+
+/// `text-decoration` property: lines that are drawn under, over or through the text.
+/// `None` fields were not set (by the shorthand or the longhand properties).
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+pub struct StyleTextDecoration {
+    /// `text-decoration-line`, no lines are drawn if not set
+    pub line: Option<TextDecorationLine>,
+    /// `text-decoration-style`, `solid` if not set
+    pub style: Option<TextDecorationStyle>,
+    /// `text-decoration-color`, the text color if not set
+    pub color: Option<ColorU>,
+    /// `text-decoration-thickness`, calculated from the font size if not set
+    pub thickness: Option<PixelValue>,
+}
+
+impl StyleTextDecoration {
+
+    // "merges" two StyleTextDecoration properties, fields set in `other` take precedence
+    pub fn merge(&mut self, other: &StyleTextDecoration) {
+        if other.line.is_some() {
+            self.line = other.line;
+        }
+        if other.style.is_some() {
+            self.style = other.style;
+        }
+        if other.color.is_some() {
+            self.color = other.color;
+        }
+        if other.thickness.is_some() {
+            self.thickness = other.thickness;
+        }
+    }
+}
+
+/// `text-decoration-line` property, `none` if all fields are false
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct TextDecorationLine {
+    pub underline: bool,
+    pub overline: bool,
+    pub line_through: bool,
+}
+
+impl TextDecorationLine {
+    pub fn is_none(&self) -> bool {
+        !self.underline && !self.overline && !self.line_through
+    }
+}
+
+/// `text-decoration-style` property
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextDecorationStyle {
+    Solid,
+    Double,
+    Dotted,
+    Dashed,
+    Wavy,
+}
+
+impl Default for TextDecorationStyle {
+    fn default() -> Self {
+        TextDecorationStyle::Solid
+    }
+}
+
+/// Number of columns that the text of a node should be split into
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct LayoutColumnCount(pub usize);
+/// Minimum width of each text column
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct LayoutColumnWidth(pub PixelValue);
+/// Space between two text columns
 #[derive(Debug, PartialEq, Copy, Clone)]
-pub struct LineHeight(pub PercentageValue);
+pub struct LayoutColumnGap(pub PixelValue);
+/// `column-rule` property: line that is drawn in the middle of the gap between two
+/// text columns. `None` fields were not set (by the shorthand or the longhand properties).
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+pub struct StyleColumnRule {
+    /// `column-rule-width`, `3px` if not set
+    pub width: Option<PixelValue>,
+    /// `column-rule-style`, no rule is drawn if not set
+    pub style: Option<BorderStyle>,
+    /// `column-rule-color`, the text color if not set
+    pub color: Option<ColorU>,
+}
+
+impl StyleColumnRule {
+
+    // "merges" two StyleColumnRule properties, fields set in `other` take precedence
+    pub fn merge(&mut self, other: &StyleColumnRule) {
+        if other.width.is_some() {
+            self.width = other.width;
+        }
+        if other.style.is_some() {
+            self.style = other.style;
+        }
+        if other.color.is_some() {
+            self.color = other.color;
+        }
+    }
+}
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LayoutDirection {
@@ -1605,27 +2494,1105 @@ impl Default for TextAlignmentHorz {
     }
 }
 
+/// `visibility` property: hidden nodes are not drawn (and can't be clicked),
+/// but still take up space in the layout
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum TextAlignmentVert {
-    Top,
-    Center,
-    Bottom,
+pub enum StyleVisibility {
+    Visible,
+    Hidden,
 }
 
-impl Default for TextAlignmentVert {
+impl Default for StyleVisibility {
     fn default() -> Self {
-        TextAlignmentVert::Top
+        StyleVisibility::Visible
     }
 }
 
-#[derive(Default, Debug, Clone, PartialEq)]
-pub(crate) struct RectStyle {
-    /// Background color of this rectangle
-    pub(crate) background_color: Option<BackgroundColor>,
-    /// Shadow color
-    pub(crate) box_shadow: Option<BoxShadowPreDisplayItem>,
-    /// Gradient (location) + stops
-    pub(crate) background: Option<Background>,
+/// `all` property: resets every property of the node before the other
+/// declarations of the same block are applied
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleAll {
+    /// Every property is set to its initial value
+    Initial,
+    /// Every property is copied from the parent node
+    Inherit,
+    /// Inherited properties (fonts, text color, etc.) are copied from the parent,
+    /// all other properties are set to their initial value
+    Unset,
+}
+
+multi_type_parser!(parse_style_all, StyleAll,
+                    ["initial", Initial],
+                    ["inherit", Inherit],
+                    ["unset", Unset]);
+
+/// `will-change` property: hints that the given properties of the node are about
+/// to be animated, so the node (and its subtree) is promoted to its own compositing layer
+/// on the GPU and doesn't have to be re-rasterized on every frame of the animation.
+///
+/// Every layer costs GPU memory, so this should only be set right before an animation
+/// starts and removed again once it's finished - not as a blanket optimization.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct StyleWillChange {
+    pub transform: bool,
+    pub opacity: bool,
+    pub scroll_position: bool,
+}
+
+impl StyleWillChange {
+    /// Whether the node needs its own compositing layer (i.e. the value isn't `auto`)
+    pub fn creates_layer(&self) -> bool {
+        self.transform || self.opacity || self.scroll_position
+    }
+}
+
+/// `isolation` property: `isolate` always creates a new stacking context for the node,
+/// even if it has no `filter`, `opacity` or `will-change` set.
+///
+/// A `mix-blend-mode` only blends with the content inside of the nearest stacking context,
+/// so isolating a node prevents its children from blending with the content behind it:
+/// the stacking context stays open while the children are drawn, they only blend with the
+/// node itself and their previous siblings.
+///
+/// `overflow: hidden | scroll` doesn't create a stacking context, so clipping a node
+/// does *not* isolate it, and `isolation: isolate` doesn't clip its children.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleIsolation {
+    Auto,
+    Isolate,
+}
+
+impl Default for StyleIsolation {
+    fn default() -> Self {
+        StyleIsolation::Auto
+    }
+}
+
+/// `mix-blend-mode` property: how the node is blended with the content behind it.
+///
+/// The node is blended with everything inside of the nearest stacking context - without
+/// an `isolation: isolate` (or a `filter`, `opacity`, etc.) on a parent, this is the
+/// whole window, including the window background.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleMixBlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl Default for StyleMixBlendMode {
+    fn default() -> Self {
+        StyleMixBlendMode::Normal
+    }
+}
+
+impl StyleMixBlendMode {
+    pub(crate) fn to_mix_blend_mode(&self) -> MixBlendMode {
+        use self::StyleMixBlendMode::*;
+        match *self {
+            Normal => MixBlendMode::Normal,
+            Multiply => MixBlendMode::Multiply,
+            Screen => MixBlendMode::Screen,
+            Overlay => MixBlendMode::Overlay,
+            Darken => MixBlendMode::Darken,
+            Lighten => MixBlendMode::Lighten,
+            ColorDodge => MixBlendMode::ColorDodge,
+            ColorBurn => MixBlendMode::ColorBurn,
+            HardLight => MixBlendMode::HardLight,
+            SoftLight => MixBlendMode::SoftLight,
+            Difference => MixBlendMode::Difference,
+            Exclusion => MixBlendMode::Exclusion,
+            Hue => MixBlendMode::Hue,
+            Saturation => MixBlendMode::Saturation,
+            Color => MixBlendMode::Color,
+            Luminosity => MixBlendMode::Luminosity,
+        }
+    }
+}
+
+/// `counter-reset` property: the counters that start (again) at this node, with
+/// their start values, i.e. `counter-reset: chapter section 2`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StyleCounterReset {
+    pub counters: Vec<(String, i32)>,
+}
+
+/// `counter-increment` property: the counters that are incremented at this node,
+/// i.e. `counter-increment: list-item` (by 1) or `counter-increment: page 2`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StyleCounterIncrement {
+    pub counters: Vec<(String, i32)>,
+}
+
+/// `content` property of the `::before` pseudo-element, i.e. `content: counter(item) ". "`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StyleContent {
+    pub parts: Vec<ContentPart>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentPart {
+    /// `"text"`
+    Text(String),
+    /// `counter(name)`: the value of the innermost counter with this name
+    Counter(String),
+    /// `counters(name, ".")`: the values of all nested counters with this name,
+    /// joined by the separator, i.e. `1.2.1`
+    Counters(String, String),
+}
+
+/// `list-style-type` property: the marker in front of a list item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StyleListStyleType {
+    /// `•`
+    Disc,
+    /// `◦`
+    Circle,
+    /// `▪`
+    Square,
+    None,
+    /// A custom marker, i.e. `list-style-type: "- "`
+    String(String),
+}
+
+impl StyleListStyleType {
+    /// The text of the `::marker` pseudo-element, `None` for `list-style-type: none`
+    pub(crate) fn get_marker_text(&self) -> Option<String> {
+        use self::StyleListStyleType::*;
+        match *self {
+            Disc => Some("\u{2022} ".into()),
+            Circle => Some("\u{25E6} ".into()),
+            Square => Some("\u{25AA} ".into()),
+            None => Option::None,
+            String(ref s) => Some(s.clone()),
+        }
+    }
+}
+
+/// `list-style-position` property: whether the marker is drawn
+/// inside of the content box (in front of the text) or to the left of it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleListStylePosition {
+    Inside,
+    Outside,
+}
+
+impl Default for StyleListStylePosition {
+    fn default() -> Self {
+        StyleListStylePosition::Outside
+    }
+}
+
+multi_type_parser!(parse_css_list_style_position, StyleListStylePosition,
+                    ["inside", Inside],
+                    ["outside", Outside]);
+
+/// `list-style-image` property: an image (added with `AppState::add_image()`) that is drawn
+/// as the `::marker` instead of the `list-style-type`, scaled to the line height.
+///
+/// Only `list-style-position: outside` markers can be images. If the image isn't loaded,
+/// the `list-style-type` marker is drawn instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StyleListStyleImage {
+    None,
+    Image(CssImageId),
+}
+
+impl Default for StyleListStyleImage {
+    fn default() -> Self {
+        StyleListStyleImage::None
+    }
+}
+
+// parses "none", "url(bullet.png)" or "image("bullet")"
+fn parse_css_list_style_image<'a>(input: &'a str)
+-> Result<StyleListStyleImage, CssImageParseError<'a>>
+{
+    let input = input.trim();
+    if input == "none" {
+        return Ok(StyleListStyleImage::None);
+    }
+
+    let image_str = if (input.starts_with("url(") || input.starts_with("image(")) && input.ends_with(')') {
+        input[input.find('(').unwrap() + 1..input.len() - 1].trim()
+    } else {
+        return Err(CssImageParseError::InvalidImage(input));
+    };
+
+    let image = if image_str.starts_with('"') || image_str.starts_with('\'') {
+        parse_image(image_str)?
+    } else {
+        CssImageId(image_str.to_string())
+    };
+
+    Ok(StyleListStyleImage::Image(image))
+}
+
+impl StyleContent {
+    /// Generates the text, `counters` are the counters in scope of the node,
+    /// from the outermost to the innermost counter. Missing counters are `0`.
+    pub(crate) fn resolve(&self, counters: &[(String, i32)]) -> String {
+        let mut text = String::new();
+
+        for part in &self.parts {
+            match *part {
+                ContentPart::Text(ref t) => text.push_str(t),
+                ContentPart::Counter(ref name) => {
+                    let value = counters.iter().rev().find(|&&(ref n, _)| n == name).map(|&(_, v)| v).unwrap_or(0);
+                    text.push_str(&value.to_string());
+                },
+                ContentPart::Counters(ref name, ref separator) => {
+                    let values = counters.iter()
+                        .filter(|&&(ref n, _)| n == name)
+                        .map(|&(_, v)| v.to_string())
+                        .collect::<Vec<String>>();
+                    if values.is_empty() {
+                        text.push('0');
+                    } else {
+                        text.push_str(&values.join(separator));
+                    }
+                },
+            }
+        }
+
+        text
+    }
+}
+
+/// `scroll-behavior` property: whether programmatic scrolling of a scroll container
+/// (via `FakeWindow::scroll_by` or `FakeWindow::scroll_to_node`) jumps or is animated.
+/// Scrolling with the mouse wheel is always instant.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleScrollBehavior {
+    Auto,
+    Smooth,
+}
+
+impl Default for StyleScrollBehavior {
+    fn default() -> Self {
+        StyleScrollBehavior::Auto
+    }
+}
+
+/// `scroll-snap-type` property: whether a scroll container snaps to its children
+/// (the ones with a `scroll-snap-align`) once the user stops scrolling
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleScrollSnapType {
+    None,
+    Snap(ScrollSnapAxis, ScrollSnapStrictness),
+}
+
+impl Default for StyleScrollSnapType {
+    fn default() -> Self {
+        StyleScrollSnapType::None
+    }
+}
+
+/// In which direction a scroll container snaps: `block` is the same as `y`, `inline` the same as `x`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScrollSnapAxis {
+    X,
+    Y,
+    Both,
+}
+
+/// `mandatory` always snaps to the nearest snap point, `proximity` (the default)
+/// only snaps if the nearest snap point is close to the current scroll position
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScrollSnapStrictness {
+    Mandatory,
+    Proximity,
+}
+
+/// `scroll-snap-align` property: which edge of a child of a `scroll-snap-type`
+/// container is aligned with the same edge of the container when snapping
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleScrollSnapAlign {
+    /// The child is not a snap point
+    None,
+    Start,
+    Center,
+    End,
+}
+
+impl Default for StyleScrollSnapAlign {
+    fn default() -> Self {
+        StyleScrollSnapAlign::None
+    }
+}
+
+/// Wrapper for the `scroll-margin` shorthand + `scroll-margin-{top,right,bottom,left}`
+/// properties: grows the snap area of a `scroll-snap-align` child (and the area that
+/// `FakeWindow::scroll_to_node()` scrolls into view), so that the child doesn't end up
+/// exactly at the edge of the scroll container
+///
+/// Like `LayoutMargin`, sides that are `None` weren't set by the CSS rule
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct StyleScrollMargin {
+    pub top: Option<PixelValue>,
+    pub right: Option<PixelValue>,
+    pub bottom: Option<PixelValue>,
+    pub left: Option<PixelValue>,
+}
+
+/// Wrapper for the `scroll-padding` shorthand + `scroll-padding-{top,right,bottom,left}`
+/// properties: shrinks the area of a scroll container that its children snap to (or are
+/// scrolled into, see `FakeWindow::scroll_to_node()`), i.e. `scroll-padding-top` should
+/// be the height of a sticky header
+///
+/// Like `LayoutMargin`, sides that are `None` weren't set by the CSS rule
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct StyleScrollPadding {
+    pub top: Option<PixelValue>,
+    pub right: Option<PixelValue>,
+    pub bottom: Option<PixelValue>,
+    pub left: Option<PixelValue>,
+}
+
+macro_rules! impl_scroll_sides {($struct_name:ident) => (
+    impl $struct_name {
+
+        // "merges" two properties, sides set in `other` take precedence
+        pub fn merge(&mut self, other: &$struct_name) {
+            fn merge_side(p: &mut Option<PixelValue>, other: &Option<PixelValue>) {
+                if other.is_some() {
+                    *p = *other;
+                }
+            }
+
+            merge_side(&mut self.top, &other.top);
+            merge_side(&mut self.right, &other.right);
+            merge_side(&mut self.bottom, &other.bottom);
+            merge_side(&mut self.left, &other.left);
+        }
+
+        /// `[top, right, bottom, left]` in pixels, sides that aren't set are 0
+        pub(crate) fn to_pixels(&self) -> [f32; 4] {
+            let side = |p: Option<PixelValue>| p.map(|p| p.to_pixels()).unwrap_or(0.0);
+            [side(self.top), side(self.right), side(self.bottom), side(self.left)]
+        }
+    }
+)}
+
+impl_scroll_sides!(StyleScrollMargin);
+impl_scroll_sides!(StyleScrollPadding);
+
+/// `display` property: whether a node creates a block box or
+/// participates in the inline formatting context of its parent
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutDisplay {
+    /// Starts on a new line and stretches to the width of the parent
+    Block,
+    /// Flows left-to-right on the same line as its inline siblings,
+    /// the height is determined by the `line-height`, not by the `height`
+    Inline,
+    /// Flows like an inline node, but is sized like a block
+    InlineBlock,
+    /// Neither the node nor its children are drawn, i.e. `@media print { .toolbar { display: none; } }`
+    None,
+}
+
+impl Default for LayoutDisplay {
+    fn default() -> Self {
+        LayoutDisplay::Block
+    }
+}
+
+/// `float` property: a floated node is taken out of the normal flow and placed at
+/// the left or right edge of its parent, the following siblings flow around it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutFloat {
+    Left,
+    Right,
+    None,
+}
+
+impl Default for LayoutFloat {
+    fn default() -> Self {
+        LayoutFloat::None
+    }
+}
+
+/// `clear` property: places the node below the preceding floats of the given side
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutClear {
+    None,
+    Left,
+    Right,
+    Both,
+}
+
+impl Default for LayoutClear {
+    fn default() -> Self {
+        LayoutClear::None
+    }
+}
+
+impl LayoutClear {
+    pub fn clears_left(&self) -> bool {
+        *self == LayoutClear::Left || *self == LayoutClear::Both
+    }
+
+    pub fn clears_right(&self) -> bool {
+        *self == LayoutClear::Right || *self == LayoutClear::Both
+    }
+}
+
+/// Length in a `shape-outside` function - percentages are relative to the size of the node
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ShapeLength {
+    Fixed(PixelValue),
+    /// Normalized percentage, 50% = 0.5
+    Percentage(f32),
+}
+
+impl ShapeLength {
+    /// Returns the length in pixels, `reference` is the length that 100% refers to
+    pub fn resolve(&self, reference: f32) -> f32 {
+        match *self {
+            ShapeLength::Fixed(pixels) => pixels.to_pixels(),
+            ShapeLength::Percentage(p) => p * reference,
+        }
+    }
+}
+
+/// `shape-outside` property: the shape of a floated node that inline content flows around.
+/// Positions are relative to the top left corner of the node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StyleShapeOutside {
+    /// Content flows around the rectangle of the node
+    None,
+    /// `circle(50% at 50% 50%)` - a percentage radius is relative to
+    /// `sqrt(width^2 + height^2) / sqrt(2)` of the node
+    Circle { radius: ShapeLength, center: (ShapeLength, ShapeLength) },
+    /// `ellipse(50% 25% at 50% 50%)`
+    Ellipse { radius_x: ShapeLength, radius_y: ShapeLength, center: (ShapeLength, ShapeLength) },
+    /// `inset(10px 20px)`, insets from the top, right, bottom and left edge
+    Inset { top: ShapeLength, right: ShapeLength, bottom: ShapeLength, left: ShapeLength },
+    /// `polygon(0% 0%, 100% 0%, 0% 100%)`
+    Polygon(Vec<(ShapeLength, ShapeLength)>),
+}
+
+impl Default for StyleShapeOutside {
+    fn default() -> Self {
+        StyleShapeOutside::None
+    }
+}
+
+/// `table-layout` property of a table (a node whose children are the rows,
+/// the children of the rows are the cells)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutTableLayout {
+    /// Every cell is sized by its own content and properties
+    Auto,
+    /// The widths (and horizontal positions) of the columns are taken from the cells of
+    /// the first row - the cells of the other rows are only laid out within these widths
+    Fixed,
+}
+
+impl Default for LayoutTableLayout {
+    fn default() -> Self {
+        LayoutTableLayout::Auto
+    }
+}
+
+/// `vertical-align` property: for inline nodes, how the node is aligned to the previous
+/// inline node on the same line. For table cells (and other nodes with text), where the
+/// text is placed if the node is higher than the text.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleVerticalAlign {
+    Top,
+    Middle,
+    Bottom,
+    /// Aligns the baselines of the text, for text inside of a node this is the same as `Top`
+    Baseline,
+}
+
+impl Default for StyleVerticalAlign {
+    fn default() -> Self {
+        StyleVerticalAlign::Baseline
+    }
+}
+
+impl StyleVerticalAlign {
+    /// Where the text is placed inside of the node
+    pub(crate) fn to_text_alignment_vert(&self) -> TextAlignmentVert {
+        use self::StyleVerticalAlign::*;
+        match *self {
+            Top | Baseline => TextAlignmentVert::Top,
+            Middle => TextAlignmentVert::Center,
+            Bottom => TextAlignmentVert::Bottom,
+        }
+    }
+}
+
+impl LayoutDisplay {
+    /// Whether the node participates in the inline formatting context of its parent
+    pub fn is_inline_level(&self) -> bool {
+        *self == LayoutDisplay::Inline || *self == LayoutDisplay::InlineBlock
+    }
+}
+
+/// `scrollbar-width` property: how wide the scrollbar of a scroll container is
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutScrollbarWidth {
+    /// Default width of the scrollbar
+    Auto,
+    /// Narrower scrollbar
+    Thin,
+    /// No scrollbar is drawn, but the content can still be scrolled
+    None,
+}
+
+impl Default for LayoutScrollbarWidth {
+    fn default() -> Self {
+        LayoutScrollbarWidth::Auto
+    }
+}
+
+/// `writing-mode` property: determines the direction that lines of text
+/// are laid out in, which is needed to resolve logical properties (`inline-size`, etc.)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutWritingMode {
+    /// Lines are horizontal, from top to bottom (default)
+    HorizontalTb,
+    /// Lines are vertical, from right to left
+    VerticalRl,
+    /// Lines are vertical, from left to right
+    VerticalLr,
+}
+
+impl Default for LayoutWritingMode {
+    fn default() -> Self {
+        LayoutWritingMode::HorizontalTb
+    }
+}
+
+impl LayoutWritingMode {
+    pub fn is_vertical(&self) -> bool {
+        *self != LayoutWritingMode::HorizontalTb
+    }
+}
+
+/// Logical size properties, which are resolved to the physical `width` / `height`
+/// properties (depending on the `writing-mode`) after the cascade.
+///
+/// In a horizontal writing mode, the inline size is the width and the block size
+/// is the height, in a vertical writing mode it's the other way around.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LogicalSize {
+    InlineSize(PixelValue),
+    BlockSize(PixelValue),
+    MinInlineSize(PixelValue),
+    MinBlockSize(PixelValue),
+    MaxInlineSize(PixelValue),
+    MaxBlockSize(PixelValue),
+}
+
+impl LogicalSize {
+    /// Resolves the logical size to a physical size property (`width`, `min-height`, etc.)
+    pub fn to_physical(&self, writing_mode: LayoutWritingMode) -> ParsedCssProperty {
+        use self::LogicalSize::*;
+        match (*self, writing_mode.is_vertical()) {
+            (InlineSize(v), false) | (BlockSize(v), true) => LayoutWidth(v).into(),
+            (InlineSize(v), true) | (BlockSize(v), false) => LayoutHeight(v).into(),
+            (MinInlineSize(v), false) | (MinBlockSize(v), true) => LayoutMinWidth(v).into(),
+            (MinInlineSize(v), true) | (MinBlockSize(v), false) => LayoutMinHeight(v).into(),
+            (MaxInlineSize(v), false) | (MaxBlockSize(v), true) => LayoutMaxWidth(v).into(),
+            (MaxInlineSize(v), true) | (MaxBlockSize(v), false) => LayoutMaxHeight(v).into(),
+        }
+    }
+}
+
+/// `direction` property: whether the text runs from left to right or from right to
+/// left, which is needed to resolve the logical properties (`margin-inline-start`, etc.)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleDirection {
+    Ltr,
+    Rtl,
+}
+
+impl Default for StyleDirection {
+    fn default() -> Self {
+        StyleDirection::Ltr
+    }
+}
+
+/// Physical side of a box
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BoxSide {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+/// Logical side of a box: the inline sides are at the start and end of a line of text,
+/// the block sides at the start and end of the lines (see `LogicalSide::to_physical`)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LogicalSide {
+    InlineStart,
+    InlineEnd,
+    BlockStart,
+    BlockEnd,
+}
+
+impl LogicalSide {
+    /// Resolves the logical side to a physical side, i.e. in `direction: rtl`,
+    /// the inline start is on the right side
+    pub fn to_physical(&self, writing_mode: LayoutWritingMode, direction: StyleDirection) -> BoxSide {
+        use self::LogicalSide::*;
+        use self::LayoutWritingMode::*;
+
+        let is_rtl = direction == StyleDirection::Rtl;
+        match (*self, writing_mode) {
+            (BlockStart, HorizontalTb) => BoxSide::Top,
+            (BlockEnd, HorizontalTb) => BoxSide::Bottom,
+            (BlockStart, VerticalRl) | (BlockEnd, VerticalLr) => BoxSide::Right,
+            (BlockEnd, VerticalRl) | (BlockStart, VerticalLr) => BoxSide::Left,
+            (InlineStart, HorizontalTb) => if is_rtl { BoxSide::Right } else { BoxSide::Left },
+            (InlineEnd, HorizontalTb) => if is_rtl { BoxSide::Left } else { BoxSide::Right },
+            (InlineStart, _) => if is_rtl { BoxSide::Bottom } else { BoxSide::Top },
+            (InlineEnd, _) => if is_rtl { BoxSide::Top } else { BoxSide::Bottom },
+        }
+    }
+}
+
+/// Border of a single side, set via `border-top`, `border-left`, etc. -
+/// the borders of the other sides are kept
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StyleBorderSide {
+    pub side: BoxSide,
+    pub width: f32,
+    pub border: BorderSide,
+}
+
+impl StyleBorderSide {
+    fn new(side: BoxSide, (width, border): (f32, BorderSide)) -> Self {
+        Self { side: side, width: width, border: border }
+    }
+}
+
+/// Logical margin, padding and border properties (`margin-inline-start`, `padding-block-end`,
+/// `border-inline-end`, etc.), which are resolved to the physical properties (depending on
+/// the `writing-mode` and the `direction`) after the cascade, like the `LogicalSize`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LogicalBoxProperty {
+    Margin(LogicalSide, LayoutMarginValue),
+    Padding(LogicalSide, LayoutPaddingValue),
+    Border(LogicalSide, (f32, BorderSide)),
+}
+
+impl LogicalBoxProperty {
+    /// Resolves the logical property to a physical property (`margin-left`, `border-top`, etc.)
+    pub fn to_physical(&self, writing_mode: LayoutWritingMode, direction: StyleDirection) -> ParsedCssProperty {
+        match *self {
+            LogicalBoxProperty::Margin(side, value) => {
+                let mut margin = LayoutMargin::default();
+                match side.to_physical(writing_mode, direction) {
+                    BoxSide::Top => margin.top = Some(value),
+                    BoxSide::Right => margin.right = Some(value),
+                    BoxSide::Bottom => margin.bottom = Some(value),
+                    BoxSide::Left => margin.left = Some(value),
+                }
+                margin.into()
+            },
+            LogicalBoxProperty::Padding(side, value) => {
+                let mut padding = LayoutPadding::default();
+                match side.to_physical(writing_mode, direction) {
+                    BoxSide::Top => padding.top = Some(value),
+                    BoxSide::Right => padding.right = Some(value),
+                    BoxSide::Bottom => padding.bottom = Some(value),
+                    BoxSide::Left => padding.left = Some(value),
+                }
+                padding.into()
+            },
+            LogicalBoxProperty::Border(side, border) => {
+                StyleBorderSide::new(side.to_physical(writing_mode, direction), border).into()
+            },
+        }
+    }
+}
+
+/// `object-fit` property: how the image of an image node is scaled to fit the node
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleObjectFit {
+    /// Stretch the image to the size of the node (default)
+    Fill,
+    /// Scale the image (keeping the aspect ratio) so that it fits into the node
+    Contain,
+    /// Scale the image (keeping the aspect ratio) so that it covers the node, clipping the rest
+    Cover,
+    /// Keep the original size of the image
+    None,
+    /// Like `Contain`, but images are never scaled up, only down
+    ScaleDown,
+}
+
+impl Default for StyleObjectFit {
+    fn default() -> Self {
+        StyleObjectFit::Fill
+    }
+}
+
+/// `object-position` property: where the image is placed inside of the node if
+/// it doesn't fill the node completely. `0.0` is the left / top edge, `1.0` the right / bottom edge
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StyleObjectPosition {
+    pub horizontal: f32,
+    pub vertical: f32,
+}
+
+impl Default for StyleObjectPosition {
+    fn default() -> Self {
+        StyleObjectPosition {
+            horizontal: 0.5,
+            vertical: 0.5,
+        }
+    }
+}
+
+/// `image-rendering` property: how images are interpolated when they are scaled
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleImageRendering {
+    /// Smooth (linear) interpolation
+    Auto,
+    /// Nearest-neighbour interpolation, for pixel art or QR codes -
+    /// `crisp-edges` is treated the same way
+    Pixelated,
+}
+
+impl Default for StyleImageRendering {
+    fn default() -> Self {
+        StyleImageRendering::Auto
+    }
+}
+
+impl StyleImageRendering {
+    pub(crate) fn to_image_rendering(&self) -> ImageRendering {
+        match *self {
+            StyleImageRendering::Auto => ImageRendering::Auto,
+            StyleImageRendering::Pixelated => ImageRendering::Pixelated,
+        }
+    }
+}
+
+/// `border-image` property: draws an image instead of the border, which is split into a 3x3
+/// grid ("nine-slice scaling"), so that the corners keep their size when the node is resized.
+///
+/// The center of the image is only drawn if the `fill` keyword is set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleBorderImage {
+    pub image: CssImageId,
+    /// Inset of the slices from the top, right, bottom and left edge of the image, in image pixels
+    pub slice: [f32; 4],
+    /// Draw the center slice of the image in the content area
+    pub fill: bool,
+    pub repeat_horizontal: BorderImageRepeat,
+    pub repeat_vertical: BorderImageRepeat,
+}
+
+/// How the edge slices of a `border-image` are scaled to the length of the border
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BorderImageRepeat {
+    Stretch,
+    Repeat,
+    Round,
+    Space,
+}
+
+impl Default for BorderImageRepeat {
+    fn default() -> Self {
+        BorderImageRepeat::Stretch
+    }
+}
+
+/// `resize` property: in which directions the user can resize the rectangle
+/// by dragging the handle in the bottom right corner. Like in the CSS spec, this
+/// only has an effect on scroll containers (`overflow` other than `visible`)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutResize {
+    None,
+    Both,
+    Horizontal,
+    Vertical,
+}
+
+impl Default for LayoutResize {
+    fn default() -> Self {
+        LayoutResize::None
+    }
+}
+
+impl LayoutResize {
+    /// Returns whether a resize handle should be drawn for this rectangle
+    /// (if it is a scroll container, see `LayoutOverflow::is_visible`)
+    pub fn is_resizable(&self) -> bool {
+        *self != LayoutResize::None
+    }
+}
+
+/// Shape of the text cursor (`caret-shape`, not part of the CSS standard)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CaretShape {
+    /// `|`: Thin vertical bar in front of the character
+    Bar,
+    /// `█`: Rectangle covering the whole character
+    Block,
+    /// `_`: Horizontal line underneath the character
+    Underscore,
+}
+
+impl Default for CaretShape {
+    fn default() -> Self {
+        CaretShape::Bar
+    }
+}
+
+/// `user-select` property (inherited): whether the text of a node can be selected,
+/// see `FakeWindow::get_click_selection()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleUserSelect {
+    /// Same as `text`
+    Auto,
+    Text,
+    /// The text can't be selected, i.e. for labels of buttons
+    None,
+    /// Clicking the node selects all of its text
+    All,
+}
+
+impl Default for StyleUserSelect {
+    fn default() -> Self {
+        StyleUserSelect::Auto
+    }
+}
+
+/// `pointer-events` property (inherited): whether the node receives mouse events
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StylePointerEvents {
+    Auto,
+    /// The events go to the nodes behind this node
+    None,
+    /// Only the visible pixels receive events: clicks on the transparent pixels of an
+    /// image or an OpenGL texture (i.e. outside of the shapes of an `Svg`) go to the
+    /// nodes behind it. Other nodes behave like `auto`.
+    Painted,
+}
+
+impl Default for StylePointerEvents {
+    fn default() -> Self {
+        StylePointerEvents::Auto
+    }
+}
+
+/// `appearance` property. azul has no native widgets (all widgets are drawn with
+/// the CSS of the widget), so both values look the same - the property is only parsed
+/// so that stylesheets that reset the native styling with `appearance: none` can be loaded.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleAppearance {
+    Auto,
+    None,
+}
+
+impl Default for StyleAppearance {
+    fn default() -> Self {
+        StyleAppearance::Auto
+    }
+}
+
+/// `background-clip` property: the area that the background (color, gradient or image) is drawn in
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleBackgroundClip {
+    /// The background extends under the border
+    BorderBox,
+    /// The background ends at the inner edge of the border
+    PaddingBox,
+    /// The background is only drawn behind the content, not behind the padding
+    ContentBox,
+}
+
+impl Default for StyleBackgroundClip {
+    fn default() -> Self {
+        StyleBackgroundClip::BorderBox
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextAlignmentVert {
+    Top,
+    Center,
+    Bottom,
+}
+
+impl Default for TextAlignmentVert {
+    fn default() -> Self {
+        TextAlignmentVert::Top
+    }
+}
+
+/// A single filter function, such as `blur(10px)` or `brightness(0.8)`
+///
+/// Used by both the `filter` and the `backdrop-filter` property
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StyleFilter {
+    Blur(PixelValue),
+    Brightness(PercentageValue),
+    Contrast(PercentageValue),
+    Grayscale(PercentageValue),
+    /// Rotation in degrees
+    HueRotate(f32),
+    Invert(PercentageValue),
+    Opacity(PercentageValue),
+    Saturate(PercentageValue),
+    Sepia(PercentageValue),
+    /// `drop-shadow(offset-x, offset-y, blur-radius, color)`: unlike `box-shadow`, the shadow
+    /// follows the alpha channel of the node (i.e. the shape of an icon with transparent areas).
+    /// The color defaults to the text color.
+    DropShadow(TextShadowItem),
+}
+
+impl StyleFilter {
+    /// `current_color` is the text color of the node, used for shadows without a color
+    pub(crate) fn to_filter_op(&self, current_color: ColorF) -> FilterOp {
+        use self::StyleFilter::*;
+        match *self {
+            Blur(px)        => FilterOp::Blur(px.to_pixels()),
+            Brightness(p)   => FilterOp::Brightness(p.number),
+            Contrast(p)     => FilterOp::Contrast(p.number),
+            Grayscale(p)    => FilterOp::Grayscale(p.number),
+            HueRotate(deg)  => FilterOp::HueRotate(deg),
+            Invert(p)       => FilterOp::Invert(p.number),
+            Opacity(p)      => FilterOp::Opacity(PropertyBinding::Value(p.number), p.number),
+            Saturate(p)     => FilterOp::Saturate(p.number),
+            Sepia(p)        => FilterOp::Sepia(p.number),
+            DropShadow(s)   => FilterOp::DropShadow(s.offset, s.blur_radius, s.color.unwrap_or(current_color)),
+        }
+    }
+}
+
+/// `filter` property: applies the filters to the node itself
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter(pub Vec<StyleFilter>);
+
+/// `backdrop-filter` property: applies the filters to the content behind the node
+/// (for "frosted glass" effects).
+///
+/// **Warning**: This property has a significant GPU cost, since the content behind
+/// every node with a backdrop filter is drawn a second time, in an extra offscreen pass.
+/// Use it sparingly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackdropFilter(pub Vec<StyleFilter>);
+
+/// `opacity` property, `0.0` = fully transparent, `1.0` = fully opaque
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StyleOpacity(pub PercentageValue);
+
+// parses "0.5" or "50%", values outside of 0 - 1 are clamped
+fn parse_css_opacity(input: &str)
+-> Result<StyleOpacity, PercentageParseError>
+{
+    let opacity = parse_percentage_value(input)?;
+    Ok(StyleOpacity(PercentageValue { number: opacity.number.max(0.0).min(1.0) }))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssFilterParseError<'a> {
+    InvalidFilter(&'a str),
+    UnclosedFilter(&'a str),
+    InvalidAngle(&'a str),
+    PixelParseError(PixelParseError<'a>),
+    PercentageParseError(PercentageParseError),
+    ShadowParseError(CssShadowParseError<'a>),
+}
+
+impl_from!(PixelParseError, CssFilterParseError::PixelParseError);
+impl_from!(CssShadowParseError, CssFilterParseError::ShadowParseError);
+
+impl<'a> From<PercentageParseError> for CssFilterParseError<'a> {
+    fn from(e: PercentageParseError) -> Self {
+        CssFilterParseError::PercentageParseError(e)
+    }
+}
+
+// parses a list of filters, such as "blur(10px) brightness(0.8)" or "none"
+fn parse_css_filters<'a>(input: &'a str)
+-> Result<Vec<StyleFilter>, CssFilterParseError<'a>>
+{
+    let input = input.trim();
+    if input == "none" {
+        return Ok(Vec::new());
+    }
+
+    if !input.ends_with(')') {
+        return Err(CssFilterParseError::UnclosedFilter(input));
+    }
+
+    let mut filters = Vec::new();
+
+    // "blur(10px) drop-shadow(2px 2px rgb(0, 0, 0))" => ["blur(10px)", "drop-shadow(2px 2px rgb(0, 0, 0))"]
+    for filter in split_outside_parentheses(input, char::is_whitespace) {
+        if !filter.ends_with(')') {
+            return Err(CssFilterParseError::UnclosedFilter(filter));
+        }
+        filters.push(parse_css_filter(&filter[..filter.len() - 1])?);
+    }
+
+    Ok(filters)
+}
+
+// parses a single filter with the closing brace already stripped, i.e. "blur(10px"
+fn parse_css_filter<'a>(input: &'a str)
+-> Result<StyleFilter, CssFilterParseError<'a>>
+{
+    use self::StyleFilter::*;
+
+    let mut input_iter = input.splitn(2, '(');
+    let filter_type = input_iter.next().ok_or(CssFilterParseError::InvalidFilter(input))?.trim();
+    let argument = input_iter.next().ok_or(CssFilterParseError::UnclosedFilter(input))?.trim();
+
+    let filter = match filter_type {
+        "blur"          => Blur(parse_pixel_value(argument)?),
+        "brightness"    => Brightness(parse_percentage_value(argument)?),
+        "contrast"      => Contrast(parse_percentage_value(argument)?),
+        "grayscale"     => Grayscale(parse_percentage_value(argument)?),
+        "hue-rotate"    => {
+            let degrees = argument.trim_right_matches("deg").parse::<f32>()
+                .map_err(|_| CssFilterParseError::InvalidAngle(argument))?;
+            HueRotate(degrees)
+        },
+        "invert"        => Invert(parse_percentage_value(argument)?),
+        "opacity"       => Opacity(parse_percentage_value(argument)?),
+        "saturate"      => Saturate(parse_percentage_value(argument)?),
+        "sepia"         => Sepia(parse_percentage_value(argument)?),
+        "drop-shadow"   => DropShadow(parse_css_text_shadow_item(argument)?),
+        _ => return Err(CssFilterParseError::InvalidFilter(input)),
+    };
+
+    Ok(filter)
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub(crate) struct RectStyle {
+    /// Background color of this rectangle
+    pub(crate) background_color: Option<BackgroundColor>,
+    /// Shadow color
+    pub(crate) box_shadow: Option<BoxShadowPreDisplayItem>,
+    /// Gradient (location) + stops
+    pub(crate) background: Option<Background>,
     /// Border
     pub(crate) border: Option<(BorderWidths, BorderDetails)>,
     /// Border radius
@@ -1634,6 +3601,12 @@ pub(crate) struct RectStyle {
     pub(crate) font_size: Option<FontSize>,
     /// Font name / family
     pub(crate) font_family: Option<FontFamily>,
+    /// `-azul-icon-font` property
+    pub(crate) icon_font: Option<StyleIconFont>,
+    /// `font-display` property
+    pub(crate) font_display: Option<StyleFontDisplay>,
+    /// `font-feature-settings` property
+    pub(crate) font_feature_settings: Option<FontFeatureSettings>,
     /// Text color
     pub(crate) font_color: Option<TextColor>,
     /// Text alignment
@@ -1642,145 +3615,1190 @@ pub(crate) struct RectStyle {
     pub(crate) overflow: Option<LayoutOverflow>,
     /// `line-height` property
     pub(crate) line_height: Option<LineHeight>,
+    /// `letter-spacing` property
+    pub(crate) letter_spacing: Option<LetterSpacing>,
+    /// `word-spacing` property
+    pub(crate) word_spacing: Option<WordSpacing>,
+    /// `word-break` property
+    pub(crate) word_break: Option<StyleWordBreak>,
+    /// `overflow-wrap` property
+    pub(crate) overflow_wrap: Option<StyleOverflowWrap>,
+    /// `text-decoration` property
+    pub(crate) text_decoration: Option<StyleTextDecoration>,
+    /// `text-shadow` property
+    pub(crate) text_shadow: Option<TextShadow>,
+    /// `filter` property
+    pub(crate) filter: Option<Filter>,
+    /// `backdrop-filter` property
+    pub(crate) backdrop_filter: Option<BackdropFilter>,
+    /// `opacity` property
+    pub(crate) opacity: Option<StyleOpacity>,
+    /// `visibility` property
+    pub(crate) visibility: Option<StyleVisibility>,
+    /// `will-change` property
+    pub(crate) will_change: Option<StyleWillChange>,
+    /// `isolation` property
+    pub(crate) isolation: Option<StyleIsolation>,
+    /// `mix-blend-mode` property
+    pub(crate) mix_blend_mode: Option<StyleMixBlendMode>,
+    /// `scroll-behavior` property
+    pub(crate) scroll_behavior: Option<StyleScrollBehavior>,
+    /// `scroll-snap-type` property
+    pub(crate) scroll_snap_type: Option<StyleScrollSnapType>,
+    /// `scroll-snap-align` property
+    pub(crate) scroll_snap_align: Option<StyleScrollSnapAlign>,
+    /// Color of the text cursor
+    pub(crate) caret_color: Option<CaretColor>,
+    /// Shape of the text cursor
+    pub(crate) caret_shape: Option<CaretShape>,
+    /// `direction` property, the default `text-align` in `rtl` is `right`
+    pub(crate) direction: Option<StyleDirection>,
+    /// `user-select` property
+    pub(crate) user_select: Option<StyleUserSelect>,
+    /// `appearance` property (has no effect)
+    pub(crate) appearance: Option<StyleAppearance>,
+    /// `background-clip` property
+    pub(crate) background_clip: Option<StyleBackgroundClip>,
+    /// `column-count` property
+    pub(crate) column_count: Option<LayoutColumnCount>,
+    /// `column-width` property
+    pub(crate) column_width: Option<LayoutColumnWidth>,
+    /// `column-gap` property
+    pub(crate) column_gap: Option<LayoutColumnGap>,
+    /// `column-rule` property
+    pub(crate) column_rule: Option<StyleColumnRule>,
+    /// `vertical-align` property
+    pub(crate) vertical_align: Option<StyleVerticalAlign>,
+    /// `object-fit` property
+    pub(crate) object_fit: Option<StyleObjectFit>,
+    /// `object-position` property
+    pub(crate) object_position: Option<StyleObjectPosition>,
+    /// `image-rendering` property
+    pub(crate) image_rendering: Option<StyleImageRendering>,
+    /// `border-image` property
+    pub(crate) border_image: Option<StyleBorderImage>,
+    /// `counter-reset` property
+    pub(crate) counter_reset: Option<StyleCounterReset>,
+    /// `counter-increment` property
+    pub(crate) counter_increment: Option<StyleCounterIncrement>,
+    /// `content` property of the `::before` pseudo-element
+    pub(crate) before_content: Option<StyleContent>,
+    /// `list-style-type` property
+    pub(crate) list_style_type: Option<StyleListStyleType>,
+    /// `list-style-position` property
+    pub(crate) list_style_position: Option<StyleListStylePosition>,
+    /// `list-style-image` property
+    pub(crate) list_style_image: Option<StyleListStyleImage>,
+    /// Color of the scrollbar thumb (`::-azul-scrollbar-thumb`)
+    pub(crate) scrollbar_thumb_color: Option<ScrollbarThumbColor>,
+    /// Color of the scrollbar track (`::-azul-scrollbar-track`)
+    pub(crate) scrollbar_track_color: Option<ScrollbarTrackColor>,
+    /// `scrollbar-width` property
+    pub(crate) scrollbar_width: Option<LayoutScrollbarWidth>,
+}
+
+/// CSS keys of the properties that are inherited by default (fonts, text, `visibility`, etc.).
+/// Children only take over these declarations of their parent during the cascade,
+/// all other properties (`width`, `background`, `opacity`, ...) only apply to the node itself.
+const INHERITED_PROPERTIES: [&str; 26] = [
+    "color", "font-size", "font-family", "-azul-icon-font", "font-display", "font-feature-settings",
+    "direction", "text-align", "text-shadow", "line-height", "letter-spacing", "word-spacing", "word-break",
+    "overflow-wrap", "word-wrap", "visibility", "caret-color", "caret-shape", "user-select",
+    "pointer-events", "image-rendering", "list-style-type", "list-style-position", "list-style-image",
+    "-azul-scrollbar-thumb-color", "-azul-scrollbar-track-color",
+];
+
+/// Returns whether the property with the CSS key (i.e. `"line-height"`) is inherited by
+/// the children of the node, see `INHERITED_PROPERTIES`
+pub(crate) fn is_inherited_property(key: &str) -> bool {
+    INHERITED_PROPERTIES.iter().any(|property| *property == key)
+}
+
+impl RectStyle {
+    /// Copies only the properties that are inherited by default in CSS (the ones in
+    /// `INHERITED_PROPERTIES`), every other property is `None` - used by `all: unset`
+    pub(crate) fn inherited_properties(&self) -> Self {
+        Self {
+            font_size: self.font_size,
+            font_family: self.font_family.clone(),
+            icon_font: self.icon_font.clone(),
+            font_display: self.font_display,
+            font_feature_settings: self.font_feature_settings.clone(),
+            font_color: self.font_color,
+            text_align: self.text_align,
+            line_height: self.line_height,
+            letter_spacing: self.letter_spacing,
+            word_spacing: self.word_spacing,
+            word_break: self.word_break,
+            overflow_wrap: self.overflow_wrap,
+            text_shadow: self.text_shadow.clone(),
+            image_rendering: self.image_rendering,
+            visibility: self.visibility,
+            caret_color: self.caret_color,
+            caret_shape: self.caret_shape,
+            direction: self.direction,
+            user_select: self.user_select,
+            list_style_type: self.list_style_type.clone(),
+            list_style_position: self.list_style_position,
+            list_style_image: self.list_style_image.clone(),
+            scrollbar_thumb_color: self.scrollbar_thumb_color,
+            scrollbar_track_color: self.scrollbar_track_color,
+            .. Default::default()
+        }
+    }
+}
+
+// Layout constraints for a given rectangle, such as ""
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct RectLayout {
+    pub width: Option<LayoutWidth>,
+    pub height: Option<LayoutHeight>,
+    pub min_width: Option<LayoutMinWidth>,
+    pub min_height: Option<LayoutMinHeight>,
+    pub max_width: Option<LayoutMaxWidth>,
+    pub max_height: Option<LayoutMaxHeight>,
+    pub direction: Option<LayoutDirection>,
+    pub wrap: Option<LayoutWrap>,
+    pub justify_content: Option<LayoutJustifyContent>,
+    pub align_items: Option<LayoutAlignItems>,
+    pub align_content: Option<LayoutAlignContent>,
+    pub resize: Option<LayoutResize>,
+    pub writing_mode: Option<LayoutWritingMode>,
+    pub margin: Option<LayoutMargin>,
+    pub padding: Option<LayoutPadding>,
+    pub display: Option<LayoutDisplay>,
+    pub float: Option<LayoutFloat>,
+    pub clear: Option<LayoutClear>,
+    pub table_layout: Option<LayoutTableLayout>,
+    pub shape_outside: Option<StyleShapeOutside>,
+}
+
+typed_pixel_value_parser!(parse_layout_width, LayoutWidth);
+typed_pixel_value_parser!(parse_layout_height, LayoutHeight);
+typed_pixel_value_parser!(parse_layout_min_height, LayoutMinHeight);
+typed_pixel_value_parser!(parse_layout_min_width, LayoutMinWidth);
+typed_pixel_value_parser!(parse_layout_max_width, LayoutMaxWidth);
+typed_pixel_value_parser!(parse_layout_max_height, LayoutMaxHeight);
+
+typed_pixel_value_parser!(parse_layout_column_width, LayoutColumnWidth);
+typed_pixel_value_parser!(parse_layout_column_gap, LayoutColumnGap);
+
+// parses "1px solid #ccc", the width, style and color can be in any order.
+// Like all CSS shorthands, the parts that are left out are reset to their initial value
+fn parse_css_column_rule<'a>(input: &'a str)
+-> Result<StyleColumnRule, CssParsingError<'a>>
+{
+    let mut column_rule = StyleColumnRule {
+        width: Some(PixelValue { metric: CssMetric::Px, number: 3.0 }),
+        style: Some(BorderStyle::None),
+        color: None,
+    };
+
+    let mut width_set = false;
+    let mut style_set = false;
+    let mut color_set = false;
+
+    for value in input.split_whitespace() {
+        if !width_set {
+            if let Ok(width) = parse_pixel_value(value) {
+                column_rule.width = Some(width);
+                width_set = true;
+                continue;
+            }
+        }
+        if !style_set {
+            if let Ok(style) = parse_border_style(value) {
+                column_rule.style = Some(style);
+                style_set = true;
+                continue;
+            }
+        }
+        if !color_set {
+            if let Ok(color) = parse_css_color(value) {
+                column_rule.color = Some(color);
+                color_set = true;
+                continue;
+            }
+        }
+        return Err(InvalidValueErr(value).into());
+    }
+
+    Ok(column_rule)
+}
+
+// parses "3", zero columns are invalid
+fn parse_layout_column_count<'a>(input: &'a str)
+-> Result<LayoutColumnCount, InvalidValueErr<'a>>
+{
+    match input.parse::<usize>() {
+        Ok(0) | Err(_) => Err(InvalidValueErr(input)),
+        Ok(count) => Ok(LayoutColumnCount(count)),
+    }
+}
+
+// parses "normal", "1.5", "150%" or "24px"
+fn parse_line_height<'a>(input: &'a str)
+-> Result<LineHeight, CssParsingError<'a>>
+{
+    if input == "normal" {
+        Ok(LineHeight::Normal)
+    } else if input.ends_with('%') || input.parse::<f32>().is_ok() {
+        Ok(LineHeight::Multiplier(parse_percentage_value(input)?))
+    } else {
+        Ok(LineHeight::Absolute(parse_pixel_value(input)?))
+    }
+}
+
+// parses "normal" (= 0px), "4px" or "-0.1em"
+fn parse_spacing<'a>(input: &'a str)
+-> Result<PixelValue, PixelParseError<'a>>
+{
+    if input == "normal" {
+        Ok(PixelValue { metric: CssMetric::Px, number: 0.0 })
+    } else {
+        parse_pixel_value(input)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct FontSize(pub PixelValue);
+
+typed_pixel_value_parser!(parse_css_font_size, FontSize);
+
+/// A single OpenType feature, i.e. `"liga" 1`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FontFeature {
+    /// Four-letter feature tag, such as `liga`, `smcp`, `tnum` or `ss01`
+    pub tag: [u8; 4],
+    /// `0` disables the feature, `1` enables it. Some features (like
+    /// alternates) use higher values to select between multiple glyphs.
+    pub value: u32,
+}
+
+/// `font-feature-settings` property, `normal` results in an empty list.
+///
+/// **NOTE**: Webrender doesn't support setting OpenType features on font instances yet,
+/// so the features are parsed and stored, but don't affect the rendered text yet.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FontFeatureSettings(pub Vec<FontFeature>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssFontFeatureParseError<'a> {
+    /// Feature tag is not a quoted four-letter ASCII string, i.e. `liga` or `"ligatures"`
+    InvalidTag(&'a str),
+    /// Value is not `on`, `off` or a positive integer
+    InvalidValue(&'a str),
+}
+
+// parses "normal" or a comma-separated list such as "\"liga\" 1, \"tnum\", 'smcp' on"
+fn parse_css_font_feature_settings<'a>(input: &'a str)
+-> Result<FontFeatureSettings, CssFontFeatureParseError<'a>>
+{
+    let input = input.trim();
+    if input == "normal" {
+        return Ok(FontFeatureSettings(Vec::new()));
+    }
+
+    let mut features = Vec::new();
+
+    for feature in input.split(',') {
+        let mut feature_iter = feature.split_whitespace();
+        let tag = feature_iter.next().ok_or(CssFontFeatureParseError::InvalidTag(feature))?;
+
+        let is_quoted = tag.len() == 6 &&
+            ((tag.starts_with('"') && tag.ends_with('"')) || (tag.starts_with('\'') && tag.ends_with('\'')));
+        let tag_bytes = tag.as_bytes();
+        if !is_quoted || !tag_bytes[1..5].iter().all(|c| c.is_ascii_graphic()) {
+            return Err(CssFontFeatureParseError::InvalidTag(tag));
+        }
+
+        let value = match feature_iter.next() {
+            None | Some("on") => 1,
+            Some("off") => 0,
+            Some(value) => value.parse::<u32>().map_err(|_| CssFontFeatureParseError::InvalidValue(value))?,
+        };
+
+        if let Some(extra) = feature_iter.next() {
+            return Err(CssFontFeatureParseError::InvalidValue(extra));
+        }
+
+        features.push(FontFeature {
+            tag: [tag_bytes[1], tag_bytes[2], tag_bytes[3], tag_bytes[4]],
+            value: value,
+        });
+    }
+
+    Ok(FontFeatureSettings(features))
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FontFamily {
+    // parsed fonts, in order, i.e. "Webly Sleeky UI", "monospace", etc.
+    pub(crate) fonts: Vec<Font>
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum Font {
+    BuiltinFont(&'static str),
+    ExternalFont(String),
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum CssFontFamilyParseError<'a> {
+    InvalidFontFamily(&'a str),
+    UnrecognizedBuiltinFont(&'a str),
+    UnclosedQuotes(&'a str),
+}
+
+impl<'a> From<UnclosedQuotesError<'a>> for CssFontFamilyParseError<'a> {
+    fn from(err: UnclosedQuotesError<'a>) -> Self {
+        CssFontFamilyParseError::UnclosedQuotes(err.0)
+    }
+}
+
+/// `-azul-icon-font` property (not part of the CSS standard): the font that the `::before`
+/// content of the node is drawn with - or the text of the node, if it has no `::before` content.
+///
+/// Icon fonts (FontAwesome, Material Icons) have to be added with `App::add_font`, i.e.
+/// `.icon::before { content: "\f000"; -azul-icon-font: "FontAwesome"; }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleIconFont(pub Font);
+
+/// `font-display` property: what is drawn while a font of the `font-family` is still loading
+/// (see `AppState::add_font_from_file`). In CSS, this is a descriptor of `@font-face` -
+/// azul has no `@font-face` rules, so it is set on the nodes that use the font.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleFontDisplay {
+    /// Same as `Block`
+    Auto,
+    /// The text is invisible for up to 3 seconds, then the next font of the `font-family`
+    /// is used until the font has loaded
+    Block,
+    /// The next font of the `font-family` is used immediately, until the font has loaded
+    Swap,
+    /// The text is invisible for 100ms, then the next font is used - the font is only
+    /// swapped in if it loads within 3 seconds
+    Fallback,
+    /// The text is invisible for 100ms - if the font isn't loaded by then,
+    /// the next font is used for the rest of the run of the application
+    Optional,
+}
+
+impl Default for StyleFontDisplay {
+    fn default() -> Self {
+        StyleFontDisplay::Auto
+    }
+}
+
+multi_type_parser!(parse_style_font_display, StyleFontDisplay,
+                    ["auto", Auto],
+                    ["block", Block],
+                    ["swap", Swap],
+                    ["fallback", Fallback],
+                    ["optional", Optional]);
+
+// parses `"FontAwesome"` - there is no fallback font, so only a single (quoted) font is allowed
+fn parse_css_icon_font<'a>(input: &'a str) -> Result<StyleIconFont, CssFontFamilyParseError<'a>> {
+    let input = input.trim();
+    if !(input.starts_with('"') || input.starts_with('\'')) {
+        return Err(CssFontFamilyParseError::InvalidFontFamily(input));
+    }
+    let font_name = strip_quotes(input)?;
+    Ok(StyleIconFont(Font::ExternalFont(font_name.0.into())))
+}
+
+// parses a "font-family" declaration, such as:
+//
+// "Webly Sleeky UI", monospace
+// 'Webly Sleeky Ui', monospace
+// sans-serif
+pub(crate) fn parse_css_font_family<'a>(input: &'a str) -> Result<FontFamily, CssFontFamilyParseError<'a>> {
+    let multiple_fonts = input.split(',');
+    let mut fonts = Vec::with_capacity(1);
+
+    for font in multiple_fonts {
+        let font = font.trim();
+
+        let mut double_quote_iter = font.splitn(2, '"');
+        double_quote_iter.next();
+        let mut single_quote_iter = font.splitn(2, '\'');
+        single_quote_iter.next();
+
+        if double_quote_iter.next().is_some() || single_quote_iter.next().is_some() {
+            let stripped_font = strip_quotes(font)?;
+            fonts.push(Font::ExternalFont(stripped_font.0.into()));
+        } else {
+            match font {
+                "serif"      => fonts.push(Font::BuiltinFont("serif")),
+                "sans-serif" => fonts.push(Font::BuiltinFont("sans-serif")),
+                "monospace"  => fonts.push(Font::BuiltinFont("monospace")),
+                _ => return Err(CssFontFamilyParseError::UnrecognizedBuiltinFont(font)),
+            }
+        }
+    }
+
+    Ok(FontFamily {
+        fonts: fonts,
+    })
 }
 
-// Layout constraints for a given rectangle, such as ""
-#[derive(Default, Debug, Copy, Clone, PartialEq)]
-pub struct RectLayout {
-    pub width: Option<LayoutWidth>,
-    pub height: Option<LayoutHeight>,
-    pub min_width: Option<LayoutMinWidth>,
-    pub min_height: Option<LayoutMinHeight>,
-    pub max_width: Option<LayoutMaxWidth>,
-    pub max_height: Option<LayoutMaxHeight>,
-    pub direction: Option<LayoutDirection>,
-    pub wrap: Option<LayoutWrap>,
-    pub justify_content: Option<LayoutJustifyContent>,
-    pub align_items: Option<LayoutAlignItems>,
-    pub align_content: Option<LayoutAlignContent>,
+multi_type_parser!(parse_layout_direction, LayoutDirection,
+                    ["row", Horizontal],
+                    ["column", Vertical]);
+
+multi_type_parser!(parse_layout_wrap, LayoutWrap,
+                    ["wrap", Wrap],
+                    ["nowrap", NoWrap]);
+
+multi_type_parser!(parse_layout_justify_content, LayoutJustifyContent,
+                    ["start", Start],
+                    ["end", End],
+                    ["center", Center],
+                    ["space-between", SpaceBetween],
+                    ["space-around", SpaceAround]);
+
+multi_type_parser!(parse_layout_align_items, LayoutAlignItems,
+                    ["stretch", Stretch],
+                    ["start", Start],
+                    ["end", End],
+                    ["center", Center]);
+
+multi_type_parser!(parse_layout_align_content, LayoutAlignContent,
+                    ["stretch", Stretch],
+                    ["start", Start],
+                    ["end", End],
+                    ["center", Center],
+                    ["space-between", SpaceBetween],
+                    ["space-around", SpaceAround]);
+
+multi_type_parser!(parse_shape, Shape,
+                    ["circle", Circle],
+                    ["ellipse", Ellipse]);
+
+multi_type_parser!(parse_layout_text_overflow, TextOverflowBehaviourInner,
+                    ["auto", Auto],
+                    ["scroll", Scroll],
+                    ["visible", Visible],
+                    ["hidden", Hidden]);
+
+multi_type_parser!(parse_layout_text_align, TextAlignmentHorz,
+                    ["center", Center],
+                    ["left", Left],
+                    ["right", Right]);
+
+multi_type_parser!(parse_style_direction, StyleDirection,
+                    ["ltr", Ltr],
+                    ["rtl", Rtl]);
+
+multi_type_parser!(parse_layout_writing_mode, LayoutWritingMode,
+                    ["horizontal-tb", HorizontalTb],
+                    ["vertical-rl", VerticalRl],
+                    ["vertical-lr", VerticalLr]);
+
+// `collapse` only differs from `hidden` for table rows / columns
+multi_type_parser!(parse_css_visibility, StyleVisibility,
+                    ["visible", Visible],
+                    ["hidden", Hidden],
+                    ["collapse", Hidden]);
+
+// parses "auto" or a comma-separated list, such as "transform, opacity"
+fn parse_css_will_change<'a>(input: &'a str)
+-> Result<StyleWillChange, InvalidValueErr<'a>>
+{
+    let mut will_change = StyleWillChange::default();
+
+    if input.trim() == "auto" {
+        return Ok(will_change);
+    }
+
+    for property in input.split(',') {
+        match property.trim() {
+            "transform" => will_change.transform = true,
+            "opacity" => will_change.opacity = true,
+            "scroll-position" => will_change.scroll_position = true,
+            _ => return Err(InvalidValueErr(property.trim())),
+        }
+    }
+
+    Ok(will_change)
 }
 
-typed_pixel_value_parser!(parse_layout_width, LayoutWidth);
-typed_pixel_value_parser!(parse_layout_height, LayoutHeight);
-typed_pixel_value_parser!(parse_layout_min_height, LayoutMinHeight);
-typed_pixel_value_parser!(parse_layout_min_width, LayoutMinWidth);
-typed_pixel_value_parser!(parse_layout_max_width, LayoutMaxWidth);
-typed_pixel_value_parser!(parse_layout_max_height, LayoutMaxHeight);
+multi_type_parser!(parse_css_isolation, StyleIsolation,
+                    ["auto", Auto],
+                    ["isolate", Isolate]);
+
+multi_type_parser!(parse_css_word_break, StyleWordBreak,
+                    ["normal", Normal],
+                    ["break-all", BreakAll],
+                    ["keep-all", KeepAll]);
+
+multi_type_parser!(parse_css_overflow_wrap, StyleOverflowWrap,
+                    ["normal", Normal],
+                    ["break-word", BreakWord],
+                    ["anywhere", Anywhere]);
+
+multi_type_parser!(parse_css_text_decoration_style, TextDecorationStyle,
+                    ["solid", Solid],
+                    ["double", Double],
+                    ["dotted", Dotted],
+                    ["dashed", Dashed],
+                    ["wavy", Wavy]);
+
+// parses "none" or any combination of "underline", "overline" and "line-through", i.e. "underline overline"
+fn parse_css_text_decoration_line<'a>(input: &'a str)
+-> Result<TextDecorationLine, InvalidValueErr<'a>>
+{
+    let input = input.trim();
+    let mut line = TextDecorationLine::default();
+
+    if input == "none" {
+        return Ok(line);
+    }
+
+    for value in input.split_whitespace() {
+        match value {
+            "underline" if !line.underline => line.underline = true,
+            "overline" if !line.overline => line.overline = true,
+            "line-through" if !line.line_through => line.line_through = true,
+            _ => return Err(InvalidValueErr(value)),
+        }
+    }
+
+    if line.is_none() {
+        return Err(InvalidValueErr(input));
+    }
+
+    Ok(line)
+}
 
-fn parse_line_height(input: &str)
--> Result<LineHeight, PercentageParseError>
+// parses "underline dotted red 2px" - the line(s), style, color and thickness can be in any order.
+// Like all CSS shorthands, the parts that are left out are reset to their initial value
+fn parse_css_text_decoration<'a>(input: &'a str)
+-> Result<StyleTextDecoration, CssParsingError<'a>>
 {
-    parse_percentage_value(input).and_then(|e| Ok(LineHeight(e)))
+    let mut text_decoration = StyleTextDecoration {
+        line: Some(TextDecorationLine::default()),
+        style: Some(TextDecorationStyle::Solid),
+        color: None,
+        thickness: None,
+    };
+
+    let mut line_set = false;
+    let mut style_set = false;
+    let mut color_set = false;
+    let mut thickness_set = false;
+
+    for value in input.split_whitespace() {
+        if let Ok(line) = parse_css_text_decoration_line(value) {
+            let mut current_line = text_decoration.line.unwrap_or_default();
+            // "none" can't be combined with the other lines
+            if line_set && (line.is_none() || current_line.is_none()) {
+                return Err(InvalidValueErr(value).into());
+            }
+            if (line.underline && current_line.underline) ||
+               (line.overline && current_line.overline) ||
+               (line.line_through && current_line.line_through) {
+                return Err(InvalidValueErr(value).into());
+            }
+            current_line.underline |= line.underline;
+            current_line.overline |= line.overline;
+            current_line.line_through |= line.line_through;
+            text_decoration.line = Some(current_line);
+            line_set = true;
+            continue;
+        }
+        if !style_set {
+            if let Ok(style) = parse_css_text_decoration_style(value) {
+                text_decoration.style = Some(style);
+                style_set = true;
+                continue;
+            }
+        }
+        if !thickness_set {
+            if let Ok(thickness) = parse_pixel_value(value) {
+                text_decoration.thickness = Some(thickness);
+                thickness_set = true;
+                continue;
+            }
+        }
+        if !color_set {
+            if let Ok(color) = parse_css_color(value) {
+                text_decoration.color = Some(color);
+                color_set = true;
+                continue;
+            }
+        }
+        return Err(InvalidValueErr(value).into());
+    }
+
+    Ok(text_decoration)
 }
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-pub struct FontSize(pub PixelValue);
+multi_type_parser!(parse_css_mix_blend_mode, StyleMixBlendMode,
+                    ["normal", Normal],
+                    ["multiply", Multiply],
+                    ["screen", Screen],
+                    ["overlay", Overlay],
+                    ["darken", Darken],
+                    ["lighten", Lighten],
+                    ["color-dodge", ColorDodge],
+                    ["color-burn", ColorBurn],
+                    ["hard-light", HardLight],
+                    ["soft-light", SoftLight],
+                    ["difference", Difference],
+                    ["exclusion", Exclusion],
+                    ["hue", Hue],
+                    ["saturation", Saturation],
+                    ["color", Color],
+                    ["luminosity", Luminosity]);
+
+multi_type_parser!(parse_css_scroll_behavior, StyleScrollBehavior,
+                    ["auto", Auto],
+                    ["smooth", Smooth]);
 
-typed_pixel_value_parser!(parse_css_font_size, FontSize);
+multi_type_parser!(parse_css_object_fit, StyleObjectFit,
+                    ["fill", Fill],
+                    ["contain", Contain],
+                    ["cover", Cover],
+                    ["none", None],
+                    ["scale-down", ScaleDown]);
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct FontFamily {
-    // parsed fonts, in order, i.e. "Webly Sleeky UI", "monospace", etc.
-    pub(crate) fonts: Vec<Font>
+multi_type_parser!(parse_css_image_rendering, StyleImageRendering,
+                    ["auto", Auto],
+                    ["pixelated", Pixelated],
+                    ["crisp-edges", Pixelated]);
+
+multi_type_parser!(parse_border_image_repeat, BorderImageRepeat,
+                    ["stretch", Stretch],
+                    ["repeat", Repeat],
+                    ["round", Round],
+                    ["space", Space]);
+
+// parses "url(btn.png) 10 20 10 20 fill stretch round" - `image("btn")` works as well,
+// the slices, `fill` and the repeat modes are optional
+fn parse_css_border_image<'a>(input: &'a str)
+-> Result<StyleBorderImage, CssBorderImageParseError<'a>>
+{
+    let input = input.trim();
+
+    let (image_str, rest) = if input.starts_with("url(") || input.starts_with("image(") {
+        match input.find(')') {
+            Some(end) => (&input[input.find('(').unwrap() + 1..end], &input[end + 1..]),
+            None => return Err(CssBorderImageParseError::InvalidImage(input)),
+        }
+    } else {
+        return Err(CssBorderImageParseError::InvalidImage(input));
+    };
+
+    let image_str = image_str.trim();
+    let image = if image_str.starts_with('"') || image_str.starts_with('\'') {
+        strip_quotes(image_str)?.into()
+    } else {
+        CssImageId(image_str.to_string())
+    };
+
+    let mut slices = Vec::<f32>::new();
+    let mut fill = false;
+    let mut repeat = Vec::<BorderImageRepeat>::new();
+
+    for value in rest.split_whitespace() {
+        if value == "fill" {
+            fill = true;
+        } else if let Ok(number) = value.parse::<f32>() {
+            if slices.len() == 4 || !repeat.is_empty() || number < 0.0 {
+                return Err(CssBorderImageParseError::InvalidSlice(value));
+            }
+            slices.push(number);
+        } else {
+            if repeat.len() == 2 {
+                return Err(CssBorderImageParseError::InvalidRepeat(InvalidValueErr(value)));
+            }
+            repeat.push(parse_border_image_repeat(value)?);
+        }
+    }
+
+    // "10" - all sides, "10 20" - vertical / horizontal, "10 20 30" - top / horizontal / bottom
+    let slice = match slices.len() {
+        0 => [0.0; 4],
+        1 => [slices[0]; 4],
+        2 => [slices[0], slices[1], slices[0], slices[1]],
+        3 => [slices[0], slices[1], slices[2], slices[1]],
+        _ => [slices[0], slices[1], slices[2], slices[3]],
+    };
+
+    let repeat_horizontal = repeat.get(0).cloned().unwrap_or_default();
+    let repeat_vertical = repeat.get(1).cloned().unwrap_or(repeat_horizontal);
+
+    Ok(StyleBorderImage {
+        image: image,
+        slice: slice,
+        fill: fill,
+        repeat_horizontal: repeat_horizontal,
+        repeat_vertical: repeat_vertical,
+    })
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Hash)]
-pub enum Font {
-    BuiltinFont(&'static str),
-    ExternalFont(String),
+fn is_valid_counter_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let starts_with_letter = chars.next().map(|c| c.is_alphabetic() || c == '_' || c == '-').unwrap_or(false);
+    starts_with_letter && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-') && name != "none"
 }
 
-#[derive(Debug, PartialEq, Copy, Clone)]
-pub enum CssFontFamilyParseError<'a> {
-    InvalidFontFamily(&'a str),
-    UnrecognizedBuiltinFont(&'a str),
-    UnclosedQuotes(&'a str),
+// parses "none", "list-item" or "chapter section 2" - counters without a
+// number get the `default_value` (0 for `counter-reset`, 1 for `counter-increment`)
+fn parse_css_counter_list<'a>(input: &'a str, default_value: i32)
+-> Result<Vec<(String, i32)>, CssCounterParseError<'a>>
+{
+    let input = input.trim();
+    let mut counters = Vec::<(String, i32)>::new();
+
+    if input == "none" {
+        return Ok(counters);
+    }
+
+    // whether the last counter already had a number after its name
+    let mut last_has_value = true;
+
+    for value in input.split_whitespace() {
+        if let Ok(number) = value.parse::<i32>() {
+            if last_has_value {
+                return Err(CssCounterParseError::InvalidCounterValue(value));
+            }
+            counters.last_mut().unwrap().1 = number;
+            last_has_value = true;
+        } else if is_valid_counter_name(value) {
+            counters.push((value.to_string(), default_value));
+            last_has_value = false;
+        } else {
+            return Err(CssCounterParseError::InvalidCounterName(value));
+        }
+    }
+
+    Ok(counters)
+}
+
+// `"text" rest` -> `("text", " rest")`, the string has to start with a quote
+fn split_quoted_string<'a>(input: &'a str) -> Result<(&'a str, &'a str), CssContentParseError<'a>> {
+    let quote = input.chars().next().ok_or(CssContentParseError::UnclosedQuotes(input))?;
+    let end = input[1..].find(quote).ok_or(CssContentParseError::UnclosedQuotes(input))? + 1;
+    Ok((&input[1..end], &input[(end + 1)..]))
+}
+
+// resolves the escapes in a CSS string: a backslash followed by up to 6 hex digits (and an
+// optional space) is a unicode codepoint, i.e. `\f000`, any other escaped character stands
+// for itself. For convenience, `\uf000` is accepted as a codepoint, too.
+fn unescape_css_string(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+
+        let has_u_prefix = chars.peek() == Some(&'u');
+        if has_u_prefix {
+            chars.next();
+        }
+
+        let mut hex = String::new();
+        while hex.len() < 6 {
+            match chars.peek() {
+                Some(&h) if h.is_digit(16) => { hex.push(h); chars.next(); },
+                _ => break,
+            }
+        }
+
+        if hex.is_empty() {
+            // `\"` -> `"`, `\\` -> `\`
+            if has_u_prefix {
+                output.push('u');
+            } else if let Some(escaped) = chars.next() {
+                output.push(escaped);
+            }
+            continue;
+        }
+
+        // a single space after the hex digits ends the escape
+        if chars.peek() == Some(&' ') {
+            chars.next();
+        }
+
+        let codepoint = u32::from_str_radix(&hex, 16).ok()
+            .and_then(|c| if c == 0 { None } else { ::std::char::from_u32(c) })
+            .unwrap_or('\u{FFFD}');
+        output.push(codepoint);
+    }
+
+    output
+}
+
+// parses "none", `"- "`, `counter(item) ". "` or `counters(item, ".") " "`
+fn parse_css_content<'a>(input: &'a str)
+-> Result<StyleContent, CssContentParseError<'a>>
+{
+    let mut rest = input.trim();
+    let mut parts = Vec::<ContentPart>::new();
+
+    if rest == "none" || rest == "normal" {
+        return Ok(StyleContent { parts: parts });
+    }
+
+    while !rest.is_empty() {
+        if rest.starts_with('"') || rest.starts_with('\'') {
+            let (text, after) = split_quoted_string(rest)?;
+            parts.push(ContentPart::Text(unescape_css_string(text)));
+            rest = after;
+        } else if rest.starts_with("counter(") || rest.starts_with("counters(") {
+            let args_start = rest.find('(').unwrap() + 1;
+            let args_end = rest.find(')').ok_or(CssContentParseError::InvalidContent(rest))?;
+            let args = &rest[args_start..args_end];
+
+            let part = if rest.starts_with("counters(") {
+                let mut args = args.splitn(2, ',');
+                let name = args.next().unwrap().trim();
+                let separator = args.next().ok_or(CssContentParseError::InvalidContent(rest))?.trim();
+                let (separator, _) = split_quoted_string(separator)?;
+                ContentPart::Counters(name.to_string(), separator.to_string())
+            } else {
+                ContentPart::Counter(args.trim().to_string())
+            };
+
+            match part {
+                ContentPart::Counter(ref name) | ContentPart::Counters(ref name, _) if !is_valid_counter_name(name) => {
+                    return Err(CssContentParseError::InvalidContent(rest));
+                },
+                _ => { },
+            }
+
+            parts.push(part);
+            rest = &rest[(args_end + 1)..];
+        } else {
+            return Err(CssContentParseError::InvalidContent(rest));
+        }
+        rest = rest.trim_left();
+    }
+
+    Ok(StyleContent { parts: parts })
+}
+
+// parses "disc", "circle", "square", "none" or a string, i.e. `"- "`
+fn parse_css_list_style_type<'a>(input: &'a str)
+-> Result<StyleListStyleType, CssContentParseError<'a>>
+{
+    let input = input.trim();
+    match input {
+        "disc" => Ok(StyleListStyleType::Disc),
+        "circle" => Ok(StyleListStyleType::Circle),
+        "square" => Ok(StyleListStyleType::Square),
+        "none" => Ok(StyleListStyleType::None),
+        _ if input.starts_with('"') || input.starts_with('\'') => {
+            let (text, rest) = split_quoted_string(input)?;
+            if !rest.trim().is_empty() {
+                return Err(CssContentParseError::InvalidContent(rest));
+            }
+            Ok(StyleListStyleType::String(unescape_css_string(text)))
+        },
+        _ => Err(CssContentParseError::InvalidContent(input)),
+    }
+}
+
+// parses "center", "left top", "top left" or "25% 75%"
+fn parse_css_object_position<'a>(input: &'a str)
+-> Result<StyleObjectPosition, InvalidValueErr<'a>>
+{
+    #[derive(Copy, Clone, PartialEq)]
+    enum Axis { Horizontal, Vertical, Both }
+
+    fn parse_component<'a>(input: &'a str) -> Result<(Axis, f32), InvalidValueErr<'a>> {
+        match input {
+            "left" => Ok((Axis::Horizontal, 0.0)),
+            "right" => Ok((Axis::Horizontal, 1.0)),
+            "top" => Ok((Axis::Vertical, 0.0)),
+            "bottom" => Ok((Axis::Vertical, 1.0)),
+            "center" => Ok((Axis::Both, 0.5)),
+            _ => parse_percentage(input).map(|p| (Axis::Both, p / 100.0)).ok_or(InvalidValueErr(input)),
+        }
+    }
+
+    let mut position = StyleObjectPosition::default();
+    let components = input.split_whitespace().collect::<Vec<_>>();
+
+    match components.len() {
+        1 => {
+            let (axis, value) = parse_component(components[0])?;
+            match axis {
+                Axis::Vertical => position.vertical = value,
+                Axis::Horizontal => position.horizontal = value,
+                // a single percentage only sets the horizontal position
+                Axis::Both => if components[0] != "center" { position.horizontal = value },
+            }
+        },
+        2 => {
+            let (first_axis, first) = parse_component(components[0])?;
+            let (second_axis, second) = parse_component(components[1])?;
+            if first_axis == Axis::Vertical || second_axis == Axis::Horizontal {
+                // "top left"
+                if first_axis == Axis::Horizontal || second_axis == Axis::Vertical {
+                    return Err(InvalidValueErr(input));
+                }
+                position.horizontal = second;
+                position.vertical = first;
+            } else {
+                position.horizontal = first;
+                position.vertical = second;
+            }
+        },
+        _ => return Err(InvalidValueErr(input)),
+    }
+
+    Ok(position)
+}
+
+multi_type_parser!(parse_scroll_snap_axis, ScrollSnapAxis,
+                    ["x", X],
+                    ["y", Y],
+                    ["both", Both],
+                    ["block", Y],
+                    ["inline", X]);
+
+multi_type_parser!(parse_scroll_snap_strictness, ScrollSnapStrictness,
+                    ["mandatory", Mandatory],
+                    ["proximity", Proximity]);
+
+// parses "none", "x" or "x mandatory"
+fn parse_css_scroll_snap_type<'a>(input: &'a str)
+-> Result<StyleScrollSnapType, InvalidValueErr<'a>>
+{
+    let mut input_iter = input.split_whitespace();
+
+    let axis = match input_iter.next() {
+        Some("none") if input_iter.clone().next().is_none() => return Ok(StyleScrollSnapType::None),
+        Some(axis) => parse_scroll_snap_axis(axis)?,
+        None => return Err(InvalidValueErr(input)),
+    };
+
+    let strictness = match input_iter.next() {
+        Some(strictness) => parse_scroll_snap_strictness(strictness)?,
+        None => ScrollSnapStrictness::Proximity,
+    };
+
+    if input_iter.next().is_some() {
+        return Err(InvalidValueErr(input));
+    }
+
+    Ok(StyleScrollSnapType::Snap(axis, strictness))
+}
+
+multi_type_parser!(parse_css_scroll_snap_align, StyleScrollSnapAlign,
+                    ["none", None],
+                    ["start", Start],
+                    ["center", Center],
+                    ["end", End]);
+
+// parses "0" or "10px" (one side of `scroll-margin` / `scroll-padding`)
+fn parse_scroll_side_value<'a>(input: &'a str)
+-> Result<PixelValue, PixelParseError<'a>>
+{
+    match input {
+        "0" => Ok(PixelValue { metric: CssMetric::Px, number: 0.0 }),
+        other => parse_pixel_value(other),
+    }
+}
+
+// parses "16px", "16px 0" or "1px 2px 3px 4px"
+fn parse_css_scroll_margin<'a>(input: &'a str)
+-> Result<StyleScrollMargin, CssMarginParseError<'a>>
+{
+    let [top, right, bottom, left] = split_box_shorthand(input)
+        .ok_or(CssMarginParseError::TooManyValues(input))?;
+
+    Ok(StyleScrollMargin {
+        top: Some(parse_scroll_side_value(top)?),
+        right: Some(parse_scroll_side_value(right)?),
+        bottom: Some(parse_scroll_side_value(bottom)?),
+        left: Some(parse_scroll_side_value(left)?),
+    })
+}
+
+// parses "8px", "50px 0 0" or "1px 2px 3px 4px"
+fn parse_css_scroll_padding<'a>(input: &'a str)
+-> Result<StyleScrollPadding, CssPaddingParseError<'a>>
+{
+    let [top, right, bottom, left] = split_box_shorthand(input)
+        .ok_or(CssPaddingParseError::TooManyValues(input))?;
+
+    Ok(StyleScrollPadding {
+        top: Some(parse_scroll_side_value(top)?),
+        right: Some(parse_scroll_side_value(right)?),
+        bottom: Some(parse_scroll_side_value(bottom)?),
+        left: Some(parse_scroll_side_value(left)?),
+    })
 }
 
-impl<'a> From<UnclosedQuotesError<'a>> for CssFontFamilyParseError<'a> {
-    fn from(err: UnclosedQuotesError<'a>) -> Self {
-        CssFontFamilyParseError::UnclosedQuotes(err.0)
+multi_type_parser!(parse_layout_display, LayoutDisplay,
+                    ["block", Block],
+                    ["inline", Inline],
+                    ["inline-block", InlineBlock],
+                    ["none", None]);
+
+multi_type_parser!(parse_layout_float, LayoutFloat,
+                    ["left", Left],
+                    ["right", Right],
+                    ["none", None]);
+
+multi_type_parser!(parse_layout_clear, LayoutClear,
+                    ["none", None],
+                    ["left", Left],
+                    ["right", Right],
+                    ["both", Both]);
+
+multi_type_parser!(parse_layout_scrollbar_width, LayoutScrollbarWidth,
+                    ["auto", Auto],
+                    ["thin", Thin],
+                    ["none", None]);
+
+// parses "10px", "50%" or one of the keywords of a position ("left", "center", ...)
+fn parse_shape_length<'a>(input: &'a str) -> Result<ShapeLength, CssShapeOutsideParseError<'a>> {
+    match input {
+        "left" | "top" => Ok(ShapeLength::Percentage(0.0)),
+        "center" => Ok(ShapeLength::Percentage(0.5)),
+        "right" | "bottom" => Ok(ShapeLength::Percentage(1.0)),
+        _ => {
+            if let Some(percentage) = parse_percentage(input) {
+                Ok(ShapeLength::Percentage(percentage / 100.0))
+            } else {
+                parse_pixel_value(input).map(ShapeLength::Fixed)
+                    .map_err(|_| CssShapeOutsideParseError::InvalidLength(input))
+            }
+        }
     }
 }
 
-// parses a "font-family" declaration, such as:
-//
-// "Webly Sleeky UI", monospace
-// 'Webly Sleeky Ui', monospace
-// sans-serif
-pub(crate) fn parse_css_font_family<'a>(input: &'a str) -> Result<FontFamily, CssFontFamilyParseError<'a>> {
-    let multiple_fonts = input.split(',');
-    let mut fonts = Vec::with_capacity(1);
+// parses "circle(50% at 10px 20px)", "ellipse(20px 10px)", "inset(10px 5%)",
+// "polygon(0% 0%, 100% 0%, 50% 100%)" or "none" - the center defaults to "50% 50%"
+fn parse_css_shape_outside<'a>(input: &'a str)
+-> Result<StyleShapeOutside, CssShapeOutsideParseError<'a>>
+{
+    use self::CssShapeOutsideParseError::*;
 
-    for font in multiple_fonts {
-        let font = font.trim();
+    let input = input.trim();
+    if input == "none" {
+        return Ok(StyleShapeOutside::None);
+    }
 
-        let mut double_quote_iter = font.splitn(2, '"');
-        double_quote_iter.next();
-        let mut single_quote_iter = font.splitn(2, '\'');
-        single_quote_iter.next();
+    let args_start = input.find('(').ok_or(InvalidShape(input))?;
+    if !input.ends_with(')') {
+        return Err(InvalidShape(input));
+    }
+    let function = input[..args_start].trim();
+    let args = input[(args_start + 1)..(input.len() - 1)].trim();
+
+    // "50% 25% at 10px 20px" -> (["50%", "25%"], Some(center))
+    let split_center = |args: &'a str| -> Result<(Vec<&'a str>, (ShapeLength, ShapeLength)), CssShapeOutsideParseError<'a>> {
+        let tokens = args.split_whitespace().collect::<Vec<_>>();
+        match tokens.iter().position(|token| *token == "at") {
+            Some(at) => {
+                let position = &tokens[(at + 1)..];
+                if position.len() != 2 {
+                    return Err(InvalidArguments(args));
+                }
+                let center = (parse_shape_length(position[0])?, parse_shape_length(position[1])?);
+                Ok((tokens[..at].to_vec(), center))
+            },
+            None => Ok((tokens, (ShapeLength::Percentage(0.5), ShapeLength::Percentage(0.5)))),
+        }
+    };
 
-        if double_quote_iter.next().is_some() || single_quote_iter.next().is_some() {
-            let stripped_font = strip_quotes(font)?;
-            fonts.push(Font::ExternalFont(stripped_font.0.into()));
-        } else {
-            match font {
-                "serif"      => fonts.push(Font::BuiltinFont("serif")),
-                "sans-serif" => fonts.push(Font::BuiltinFont("sans-serif")),
-                "monospace"  => fonts.push(Font::BuiltinFont("monospace")),
-                _ => return Err(CssFontFamilyParseError::UnrecognizedBuiltinFont(font)),
+    match function {
+        "circle" => {
+            let (radii, center) = split_center(args)?;
+            let radius = match radii.len() {
+                0 => ShapeLength::Percentage(0.5),
+                1 => parse_shape_length(radii[0])?,
+                _ => return Err(InvalidArguments(args)),
+            };
+            Ok(StyleShapeOutside::Circle { radius: radius, center: center })
+        },
+        "ellipse" => {
+            let (radii, center) = split_center(args)?;
+            let (radius_x, radius_y) = match radii.len() {
+                0 => (ShapeLength::Percentage(0.5), ShapeLength::Percentage(0.5)),
+                2 => (parse_shape_length(radii[0])?, parse_shape_length(radii[1])?),
+                _ => return Err(InvalidArguments(args)),
+            };
+            Ok(StyleShapeOutside::Ellipse { radius_x: radius_x, radius_y: radius_y, center: center })
+        },
+        "inset" => {
+            let insets = args.split_whitespace().map(parse_shape_length).collect::<Result<Vec<_>, _>>()?;
+            // same order as the `margin` shorthand
+            let (top, right, bottom, left) = match insets.len() {
+                1 => (insets[0], insets[0], insets[0], insets[0]),
+                2 => (insets[0], insets[1], insets[0], insets[1]),
+                3 => (insets[0], insets[1], insets[2], insets[1]),
+                4 => (insets[0], insets[1], insets[2], insets[3]),
+                _ => return Err(InvalidArguments(args)),
+            };
+            Ok(StyleShapeOutside::Inset { top: top, right: right, bottom: bottom, left: left })
+        },
+        "polygon" => {
+            let points = args.split(',').map(|point| {
+                let coordinates = point.split_whitespace().collect::<Vec<_>>();
+                if coordinates.len() != 2 {
+                    return Err(InvalidArguments(point));
+                }
+                Ok((parse_shape_length(coordinates[0])?, parse_shape_length(coordinates[1])?))
+            }).collect::<Result<Vec<_>, _>>()?;
+            if points.len() < 3 {
+                return Err(InvalidArguments(args));
             }
-        }
+            Ok(StyleShapeOutside::Polygon(points))
+        },
+        _ => Err(InvalidShape(function)),
     }
-
-    Ok(FontFamily {
-        fonts: fonts,
-    })
 }
 
-multi_type_parser!(parse_layout_direction, LayoutDirection,
-                    ["row", Horizontal],
-                    ["column", Vertical]);
+multi_type_parser!(parse_layout_table_layout, LayoutTableLayout,
+                    ["auto", Auto],
+                    ["fixed", Fixed]);
 
-multi_type_parser!(parse_layout_wrap, LayoutWrap,
-                    ["wrap", Wrap],
-                    ["nowrap", NoWrap]);
+multi_type_parser!(parse_style_vertical_align, StyleVerticalAlign,
+                    ["top", Top],
+                    ["middle", Middle],
+                    ["bottom", Bottom],
+                    ["baseline", Baseline]);
 
-multi_type_parser!(parse_layout_justify_content, LayoutJustifyContent,
-                    ["start", Start],
-                    ["end", End],
-                    ["center", Center],
-                    ["space-between", SpaceBetween],
-                    ["space-around", SpaceAround]);
+multi_type_parser!(parse_layout_resize, LayoutResize,
+                    ["none", None],
+                    ["both", Both],
+                    ["horizontal", Horizontal],
+                    ["vertical", Vertical]);
 
-multi_type_parser!(parse_layout_align_items, LayoutAlignItems,
-                    ["stretch", Stretch],
-                    ["start", Start],
-                    ["end", End],
-                    ["center", Center]);
+multi_type_parser!(parse_css_user_select, StyleUserSelect,
+                    ["auto", Auto],
+                    ["text", Text],
+                    ["none", None],
+                    ["all", All]);
 
-multi_type_parser!(parse_layout_align_content, LayoutAlignContent,
-                    ["stretch", Stretch],
-                    ["start", Start],
-                    ["end", End],
-                    ["center", Center],
-                    ["space-between", SpaceBetween],
-                    ["space-around", SpaceAround]);
+multi_type_parser!(parse_css_pointer_events, StylePointerEvents,
+                    ["auto", Auto],
+                    ["none", None],
+                    ["painted", Painted]);
 
-multi_type_parser!(parse_shape, Shape,
-                    ["circle", Circle],
-                    ["ellipse", Ellipse]);
+multi_type_parser!(parse_css_background_clip, StyleBackgroundClip,
+                    ["border-box", BorderBox],
+                    ["padding-box", PaddingBox],
+                    ["content-box", ContentBox]);
 
-multi_type_parser!(parse_layout_text_overflow, TextOverflowBehaviourInner,
+multi_type_parser!(parse_css_appearance, StyleAppearance,
                     ["auto", Auto],
-                    ["scroll", Scroll],
-                    ["visible", Visible],
-                    ["hidden", Hidden]);
+                    ["none", None]);
 
-multi_type_parser!(parse_layout_text_align, TextAlignmentHorz,
-                    ["center", Center],
-                    ["left", Left],
-                    ["right", Right]);
+multi_type_parser!(parse_css_caret_shape, CaretShape,
+                    ["bar", Bar],
+                    ["block", Block],
+                    ["underscore", Underscore]);
 
 #[cfg(test)]
 mod css_tests {
@@ -2092,6 +5110,17 @@ mod css_tests {
         assert_eq!(parse_css_color("#EEE"), Ok(ColorU { r: 238, g: 238, b: 238, a: 255 }));
     }
 
+    #[test]
+    fn test_parse_css_color_rgb() {
+        assert_eq!(parse_css_color("rgb(240, 248, 255)"), Ok(ColorU { r: 240, g: 248, b: 255, a: 255 }));
+        assert_eq!(parse_css_color("rgba(0, 0, 0, 0.5)"), Ok(ColorU { r: 0, g: 0, b: 0, a: 128 }));
+        assert_eq!(parse_css_color("rgba(100%, 0%, 50%, 25%)"), Ok(ColorU { r: 255, g: 0, b: 128, a: 64 }));
+        assert_eq!(parse_css_color("rgb(300, -5, 0)"), Ok(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+        assert_eq!(parse_css_color("rgb(0, 0)"), Err(CssColorParseError::InvalidColor("rgb(0, 0)")));
+        assert_eq!(parse_css_color("rgb(0, red, 0)"), Err(CssColorParseError::InvalidColor("red")));
+        assert_eq!(parse_css_color("rgba(0, 0, 0, 0.5"), Err(CssColorParseError::InvalidColor("rgba(0, 0, 0, 0.5")));
+    }
+
     #[test]
     fn test_parse_pixel_value_1() {
         assert_eq!(parse_pixel_value("15px"), Ok(PixelValue { metric: CssMetric::Px, number: 15.0 }));
@@ -2168,4 +5197,557 @@ mod css_tests {
             CssImageId(String::from("Cat 01"))
         )));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_css_filters_1() {
+        assert_eq!(parse_css_filters("none"), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_parse_css_filters_2() {
+        assert_eq!(parse_css_filters("blur(10px) brightness(0.8)"), Ok(vec![
+            StyleFilter::Blur(PixelValue { metric: CssMetric::Px, number: 10.0 }),
+            StyleFilter::Brightness(PercentageValue { number: 0.8 }),
+        ]));
+    }
+
+    #[test]
+    fn test_parse_css_filters_3() {
+        assert_eq!(parse_css_filters("hue-rotate(90deg) invert(50%)"), Ok(vec![
+            StyleFilter::HueRotate(90.0),
+            StyleFilter::Invert(PercentageValue { number: 0.5 }),
+        ]));
+    }
+
+    #[test]
+    fn test_parse_css_filters_4() {
+        assert_eq!(parse_css_filters("blur(10px"), Err(CssFilterParseError::UnclosedFilter("blur(10px")));
+    }
+
+    #[test]
+    fn test_parse_css_caret_shape_1() {
+        assert_eq!(parse_css_caret_shape("block"), Ok(CaretShape::Block));
+        assert_eq!(parse_css_caret_shape("triangle"), Err(InvalidValueErr("triangle")));
+    }
+
+    #[test]
+    fn test_parse_layout_column_count_1() {
+        assert_eq!(parse_layout_column_count("3"), Ok(LayoutColumnCount(3)));
+        assert_eq!(parse_layout_column_count("0"), Err(InvalidValueErr("0")));
+    }
+
+    #[test]
+    fn test_logical_size_to_physical() {
+        let size = PixelValue { metric: CssMetric::Px, number: 50.0 };
+        assert_eq!(LogicalSize::InlineSize(size).to_physical(LayoutWritingMode::HorizontalTb),
+                   ParsedCssProperty::Width(LayoutWidth(size)));
+        assert_eq!(LogicalSize::InlineSize(size).to_physical(LayoutWritingMode::VerticalRl),
+                   ParsedCssProperty::Height(LayoutHeight(size)));
+    }
+
+    #[test]
+    fn test_logical_box_property_to_physical() {
+        let margin = match ParsedCssProperty::from_kv("margin-inline-start", "10px") {
+            Ok(ParsedCssProperty::LogicalBox(margin)) => margin,
+            other => panic!("not a logical property: {:?}", other),
+        };
+        let value = Some(LayoutMarginValue::Fixed(PixelValue { metric: CssMetric::Px, number: 10.0 }));
+
+        assert_eq!(margin.to_physical(LayoutWritingMode::HorizontalTb, StyleDirection::Ltr),
+                   ParsedCssProperty::Margin(LayoutMargin { left: value, .. Default::default() }));
+        assert_eq!(margin.to_physical(LayoutWritingMode::HorizontalTb, StyleDirection::Rtl),
+                   ParsedCssProperty::Margin(LayoutMargin { right: value, .. Default::default() }));
+        assert_eq!(margin.to_physical(LayoutWritingMode::VerticalRl, StyleDirection::Rtl),
+                   ParsedCssProperty::Margin(LayoutMargin { bottom: value, .. Default::default() }));
+        assert_eq!(LogicalSide::BlockStart.to_physical(LayoutWritingMode::VerticalRl, StyleDirection::Ltr), BoxSide::Right);
+    }
+
+    #[test]
+    fn test_parse_line_height() {
+        assert_eq!(parse_line_height("normal"), Ok(LineHeight::Normal));
+        assert_eq!(parse_line_height("1.5"), Ok(LineHeight::Multiplier(PercentageValue { number: 1.5 })));
+        assert_eq!(parse_line_height("150%"), Ok(LineHeight::Multiplier(PercentageValue { number: 1.5 })));
+        assert_eq!(parse_line_height("24px"), Ok(LineHeight::Absolute(PixelValue { metric: CssMetric::Px, number: 24.0 }))));
+    }
+
+    #[test]
+    fn test_parse_letter_and_word_spacing() {
+        assert_eq!(ParsedCssProperty::from_kv("letter-spacing", "-0.1em"),
+            Ok(ParsedCssProperty::LetterSpacing(LetterSpacing(PixelValue { metric: CssMetric::Em, number: -0.1 }))));
+        assert_eq!(ParsedCssProperty::from_kv("word-spacing", "normal"),
+            Ok(ParsedCssProperty::WordSpacing(WordSpacing(PixelValue { metric: CssMetric::Px, number: 0.0 }))));
+    }
+
+    #[test]
+    fn test_parse_text_shadow() {
+        assert_eq!(parse_css_text_shadow("none"), Ok(TextShadow(Vec::new())));
+        assert_eq!(
+            parse_css_text_shadow("2px 2px 4px #00000080, red 1px 1px"),
+            Ok(TextShadow(vec![
+                TextShadowItem {
+                    offset: LayoutVector2D::new(2.0, 2.0),
+                    color: Some(ColorF::from(ColorU { r: 0, g: 0, b: 0, a: 128 })),
+                    blur_radius: 4.0,
+                },
+                TextShadowItem {
+                    offset: LayoutVector2D::new(1.0, 1.0),
+                    color: Some(ColorF::from(ColorU { r: 255, g: 0, b: 0, a: 255 })),
+                    blur_radius: 0.0,
+                },
+            ]))
+        );
+        assert_eq!(
+            parse_css_text_shadow("2px 2px 4px rgba(0,0,0,0.5)"),
+            Ok(TextShadow(vec![
+                TextShadowItem {
+                    offset: LayoutVector2D::new(2.0, 2.0),
+                    color: Some(ColorF::from(ColorU { r: 0, g: 0, b: 0, a: 128 })),
+                    blur_radius: 4.0,
+                },
+            ]))
+        );
+        assert_eq!(parse_css_text_shadow("2px"), Err(CssShadowParseError::InvalidSingleStatement("2px")));
+    }
+
+    #[test]
+    fn test_parse_font_feature_settings() {
+        assert_eq!(parse_css_font_feature_settings("normal"), Ok(FontFeatureSettings(Vec::new())));
+        assert_eq!(
+            parse_css_font_feature_settings("\"liga\" 0, 'tnum', \"ss01\" on, \"salt\" 2"),
+            Ok(FontFeatureSettings(vec![
+                FontFeature { tag: *b"liga", value: 0 },
+                FontFeature { tag: *b"tnum", value: 1 },
+                FontFeature { tag: *b"ss01", value: 1 },
+                FontFeature { tag: *b"salt", value: 2 },
+            ]))
+        );
+        assert_eq!(parse_css_font_feature_settings("liga 1"), Err(CssFontFeatureParseError::InvalidTag("liga")));
+        assert_eq!(parse_css_font_feature_settings("\"liga\" yes"), Err(CssFontFeatureParseError::InvalidValue("yes")));
+    }
+
+    #[test]
+    fn test_parse_opacity() {
+        assert_eq!(parse_css_opacity("0.5"), Ok(StyleOpacity(PercentageValue { number: 0.5 })));
+        assert_eq!(parse_css_opacity("150%"), Ok(StyleOpacity(PercentageValue { number: 1.0 })));
+    }
+
+    #[test]
+    fn test_parse_visibility() {
+        assert_eq!(parse_css_visibility("hidden"), Ok(StyleVisibility::Hidden));
+        assert_eq!(parse_css_visibility("collapse"), Ok(StyleVisibility::Hidden));
+        assert_eq!(parse_css_visibility("visible"), Ok(StyleVisibility::Visible));
+    }
+
+    #[test]
+    fn test_parse_margin() {
+        let px = |number| LayoutMarginValue::Fixed(PixelValue { metric: CssMetric::Px, number });
+        assert_eq!(parse_layout_margin("auto"), Ok(LayoutMargin::uniform(LayoutMarginValue::Auto)));
+        assert_eq!(parse_layout_margin("10px auto"), Ok(LayoutMargin {
+            top: Some(px(10.0)),
+            right: Some(LayoutMarginValue::Auto),
+            bottom: Some(px(10.0)),
+            left: Some(LayoutMarginValue::Auto),
+        }));
+        assert_eq!(parse_layout_margin("1px 2px 3px 4px"), Ok(LayoutMargin {
+            top: Some(px(1.0)),
+            right: Some(px(2.0)),
+            bottom: Some(px(3.0)),
+            left: Some(px(4.0)),
+        }));
+        assert_eq!(parse_layout_margin("1px 2px 3px 4px 5px"), Err(CssMarginParseError::TooManyValues("1px 2px 3px 4px 5px")));
+
+        let mut margin = LayoutMargin { left: Some(LayoutMarginValue::Auto), .. Default::default() };
+        margin.merge(&LayoutMargin { right: Some(px(0.0)), .. Default::default() });
+        assert_eq!(margin.left, Some(LayoutMarginValue::Auto));
+        assert_eq!(margin.right, Some(px(0.0)));
+    }
+
+    #[test]
+    fn test_parse_padding_and_display() {
+        let px = |number| Some(LayoutPaddingValue::Fixed(PixelValue { metric: CssMetric::Px, number }));
+        assert_eq!(parse_layout_padding("0 5px 10px"), Ok(LayoutPadding {
+            top: px(0.0),
+            right: px(5.0),
+            bottom: px(10.0),
+            left: px(5.0),
+        }));
+        assert_eq!(parse_layout_padding("auto"), Err(CssPaddingParseError::PixelParseError(PixelParseError::InvalidComponent("auto"))));
+        assert_eq!(parse_layout_display("inline"), Ok(LayoutDisplay::Inline));
+        assert_eq!(parse_layout_display("inline-block"), Ok(LayoutDisplay::InlineBlock));
+        assert!(LayoutDisplay::InlineBlock.is_inline_level());
+        assert!(!LayoutDisplay::default().is_inline_level());
+        assert_eq!(parse_layout_display("none"), Ok(LayoutDisplay::None));
+        assert!(!LayoutDisplay::None.is_inline_level());
+    }
+
+    #[test]
+    fn test_parse_will_change() {
+        assert_eq!(parse_css_will_change("auto"), Ok(StyleWillChange::default()));
+        assert_eq!(parse_css_will_change("transform, opacity"), Ok(StyleWillChange {
+            transform: true,
+            opacity: true,
+            scroll_position: false,
+        }));
+        assert_eq!(parse_css_will_change("scroll-position"), Ok(StyleWillChange {
+            transform: false,
+            opacity: false,
+            scroll_position: true,
+        }));
+        assert_eq!(parse_css_will_change("transform, color"), Err(InvalidValueErr("color")));
+    }
+
+    #[test]
+    fn test_parse_scroll_behavior() {
+        assert_eq!(parse_css_scroll_behavior("smooth"), Ok(StyleScrollBehavior::Smooth));
+        assert_eq!(parse_css_scroll_behavior("auto"), Ok(StyleScrollBehavior::Auto));
+        assert_eq!(parse_css_scroll_behavior("instant"), Err(InvalidValueErr("instant")));
+    }
+
+    #[test]
+    fn test_parse_column_rule() {
+        assert_eq!(parse_css_column_rule("1px solid #ccc"), Ok(StyleColumnRule {
+            width: Some(PixelValue { metric: CssMetric::Px, number: 1.0 }),
+            style: Some(BorderStyle::Solid),
+            color: Some(ColorU { r: 204, g: 204, b: 204, a: 255 }),
+        }));
+        assert_eq!(parse_css_column_rule("dashed"), Ok(StyleColumnRule {
+            width: Some(PixelValue { metric: CssMetric::Px, number: 3.0 }),
+            style: Some(BorderStyle::Dashed),
+            color: None,
+        }));
+        assert_eq!(parse_css_column_rule("solid 1px dotted"), Err(InvalidValueErr("dotted").into()));
+
+        let mut column_rule = parse_css_column_rule("1px solid red").unwrap();
+        column_rule.merge(&StyleColumnRule { style: Some(BorderStyle::Double), .. Default::default() });
+        assert_eq!(column_rule.width, Some(PixelValue { metric: CssMetric::Px, number: 1.0 }));
+        assert_eq!(column_rule.style, Some(BorderStyle::Double));
+    }
+
+    #[test]
+    fn test_parse_object_fit_and_position() {
+        assert_eq!(parse_css_object_fit("scale-down"), Ok(StyleObjectFit::ScaleDown));
+        assert_eq!(parse_css_object_fit("cover"), Ok(StyleObjectFit::Cover));
+        assert_eq!(parse_css_object_position("center"), Ok(StyleObjectPosition { horizontal: 0.5, vertical: 0.5 }));
+        assert_eq!(parse_css_object_position("left top"), Ok(StyleObjectPosition { horizontal: 0.0, vertical: 0.0 }));
+        assert_eq!(parse_css_object_position("bottom right"), Ok(StyleObjectPosition { horizontal: 1.0, vertical: 1.0 }));
+        assert_eq!(parse_css_object_position("top"), Ok(StyleObjectPosition { horizontal: 0.5, vertical: 0.0 }));
+        assert_eq!(parse_css_object_position("25% 75%"), Ok(StyleObjectPosition { horizontal: 0.25, vertical: 0.75 }));
+        assert_eq!(parse_css_object_position("left right"), Err(InvalidValueErr("left right")));
+    }
+
+    #[test]
+    fn test_parse_image_rendering() {
+        assert_eq!(parse_css_image_rendering("pixelated"), Ok(StyleImageRendering::Pixelated));
+        assert_eq!(parse_css_image_rendering("crisp-edges"), Ok(StyleImageRendering::Pixelated));
+        assert_eq!(parse_css_image_rendering("auto"), Ok(StyleImageRendering::Auto));
+        assert_eq!(parse_css_image_rendering("smooth"), Err(InvalidValueErr("smooth")));
+    }
+
+    #[test]
+    fn test_parse_border_image() {
+        assert_eq!(parse_css_border_image("url(btn.png) 10 20 10 20 stretch round"), Ok(StyleBorderImage {
+            image: CssImageId(String::from("btn.png")),
+            slice: [10.0, 20.0, 10.0, 20.0],
+            fill: false,
+            repeat_horizontal: BorderImageRepeat::Stretch,
+            repeat_vertical: BorderImageRepeat::Round,
+        }));
+        assert_eq!(parse_css_border_image("image(\"button\") 8 fill"), Ok(StyleBorderImage {
+            image: CssImageId(String::from("button")),
+            slice: [8.0; 4],
+            fill: true,
+            repeat_horizontal: BorderImageRepeat::Stretch,
+            repeat_vertical: BorderImageRepeat::Stretch,
+        }));
+        assert_eq!(parse_css_border_image("btn.png 10"), Err(CssBorderImageParseError::InvalidImage("btn.png 10")));
+        assert_eq!(parse_css_border_image("url(btn.png) 1 2 3 4 5"), Err(CssBorderImageParseError::InvalidSlice("5")));
+        assert_eq!(parse_css_border_image("url(btn.png) 10 tile"),
+                   Err(CssBorderImageParseError::InvalidRepeat(InvalidValueErr("tile"))));
+    }
+
+    #[test]
+    fn test_parse_scroll_snap() {
+        assert_eq!(parse_css_scroll_snap_type("x mandatory"),
+                   Ok(StyleScrollSnapType::Snap(ScrollSnapAxis::X, ScrollSnapStrictness::Mandatory)));
+        assert_eq!(parse_css_scroll_snap_type("block"),
+                   Ok(StyleScrollSnapType::Snap(ScrollSnapAxis::Y, ScrollSnapStrictness::Proximity)));
+        assert_eq!(parse_css_scroll_snap_type("none"), Ok(StyleScrollSnapType::None));
+        assert_eq!(parse_css_scroll_snap_type("x always"), Err(InvalidValueErr("always")));
+        assert_eq!(parse_css_scroll_snap_align("center"), Ok(StyleScrollSnapAlign::Center));
+        assert_eq!(parse_css_scroll_snap_align("middle"), Err(InvalidValueErr("middle")));
+    }
+
+    #[test]
+    fn test_parse_isolation() {
+        assert_eq!(parse_css_isolation("isolate"), Ok(StyleIsolation::Isolate));
+        assert_eq!(parse_css_isolation("auto"), Ok(StyleIsolation::Auto));
+        assert_eq!(parse_css_isolation("isolated"), Err(InvalidValueErr("isolated")));
+    }
+
+    #[test]
+    fn test_parse_mix_blend_mode() {
+        assert_eq!(parse_css_mix_blend_mode("multiply"), Ok(StyleMixBlendMode::Multiply));
+        assert_eq!(parse_css_mix_blend_mode("color-dodge"), Ok(StyleMixBlendMode::ColorDodge));
+        assert_eq!(parse_css_mix_blend_mode("luminosity"), Ok(StyleMixBlendMode::Luminosity));
+        assert_eq!(parse_css_mix_blend_mode("plus-lighter"), Err(InvalidValueErr("plus-lighter")));
+    }
+
+    #[test]
+    fn test_parse_counter_list() {
+        assert_eq!(parse_css_counter_list("none", 0), Ok(Vec::new()));
+        assert_eq!(parse_css_counter_list("list-item", 1), Ok(vec![(String::from("list-item"), 1)]));
+        assert_eq!(parse_css_counter_list("chapter section -2", 0),
+            Ok(vec![(String::from("chapter"), 0), (String::from("section"), -2)]));
+        assert_eq!(parse_css_counter_list("2", 0), Err(CssCounterParseError::InvalidCounterValue("2")));
+        assert_eq!(parse_css_counter_list("a 1 2", 0), Err(CssCounterParseError::InvalidCounterValue("2")));
+        assert_eq!(parse_css_counter_list("a, b", 0), Err(CssCounterParseError::InvalidCounterName("a,")));
+    }
+
+    #[test]
+    fn test_parse_content() {
+        assert_eq!(parse_css_content("none"), Ok(StyleContent::default()));
+        assert_eq!(parse_css_content("counter(item) \". \""), Ok(StyleContent { parts: vec![
+            ContentPart::Counter(String::from("item")),
+            ContentPart::Text(String::from(". ")),
+        ]}));
+        assert_eq!(parse_css_content("counters(item, '.')"), Ok(StyleContent { parts: vec![
+            ContentPart::Counters(String::from("item"), String::from(".")),
+        ]}));
+        assert_eq!(parse_css_content("\"abc"), Err(CssContentParseError::UnclosedQuotes("\"abc")));
+        assert_eq!(parse_css_content("attr(title)"), Err(CssContentParseError::InvalidContent("attr(title)")));
+    }
+
+    #[test]
+    fn test_resolve_content() {
+        let content = parse_css_content("counters(item, \".\") \" \"").unwrap();
+        let counters = vec![(String::from("item"), 2), (String::from("other"), 5), (String::from("item"), 1)];
+        assert_eq!(content.resolve(&counters), "2.1 ");
+        assert_eq!(parse_css_content("counter(item)").unwrap().resolve(&counters), "1");
+        assert_eq!(parse_css_content("counter(missing)").unwrap().resolve(&counters), "0");
+    }
+
+    #[test]
+    fn test_parse_word_break_and_overflow_wrap() {
+        assert_eq!(parse_css_word_break("break-all"), Ok(StyleWordBreak::BreakAll));
+        assert_eq!(parse_css_word_break("break-word"), Err(InvalidValueErr("break-word")));
+        assert_eq!(ParsedCssProperty::from_kv("overflow-wrap", "anywhere"),
+            Ok(ParsedCssProperty::OverflowWrap(StyleOverflowWrap::Anywhere)));
+        assert_eq!(ParsedCssProperty::from_kv("word-wrap", "break-word"),
+            Ok(ParsedCssProperty::OverflowWrap(StyleOverflowWrap::BreakWord)));
+    }
+
+    #[test]
+    fn test_parse_text_decoration() {
+        assert_eq!(parse_css_text_decoration("underline dotted #ff0000 2px"), Ok(StyleTextDecoration {
+            line: Some(TextDecorationLine { underline: true, overline: false, line_through: false }),
+            style: Some(TextDecorationStyle::Dotted),
+            color: Some(ColorU { r: 255, g: 0, b: 0, a: 255 }),
+            thickness: Some(PixelValue { metric: CssMetric::Px, number: 2.0 }),
+        }));
+        assert_eq!(parse_css_text_decoration("underline overline line-through"), Ok(StyleTextDecoration {
+            line: Some(TextDecorationLine { underline: true, overline: true, line_through: true }),
+            style: Some(TextDecorationStyle::Solid),
+            color: None,
+            thickness: None,
+        }));
+        assert_eq!(parse_css_text_decoration_line("none"), Ok(TextDecorationLine::default()));
+        assert_eq!(parse_css_text_decoration_line("underline underline"), Err(InvalidValueErr("underline")));
+        assert!(parse_css_text_decoration("underline none").is_err());
+        assert!(parse_css_text_decoration("none underline").is_err());
+
+        let mut text_decoration = parse_css_text_decoration("line-through").unwrap();
+        text_decoration.merge(&StyleTextDecoration { style: Some(TextDecorationStyle::Wavy), .. Default::default() });
+        assert_eq!(text_decoration.style, Some(TextDecorationStyle::Wavy));
+        assert_eq!(text_decoration.line, Some(TextDecorationLine { underline: false, overline: false, line_through: true }));
+    }
+
+    #[test]
+    fn test_layout_overflow_is_visible() {
+        assert!(LayoutOverflow::default().is_visible());
+        assert!(!LayoutOverflow {
+            horizontal: TextOverflowBehaviour::NotModified,
+            vertical: TextOverflowBehaviour::Modified(TextOverflowBehaviourInner::Auto),
+        }.is_visible());
+    }
+
+    #[test]
+    fn test_parse_layout_overflow() {
+        use self::TextOverflowBehaviourInner::*;
+        let overflow = parse_layout_overflow("scroll hidden").unwrap();
+        assert_eq!(overflow, LayoutOverflow {
+            horizontal: TextOverflowBehaviour::Modified(Scroll),
+            vertical: TextOverflowBehaviour::Modified(Hidden),
+        });
+        assert!(overflow.shows_horizontal_scrollbar(false));
+        assert!(!overflow.shows_vertical_scrollbar(true));
+        assert_eq!(parse_layout_overflow("auto").unwrap().vertical, TextOverflowBehaviour::Modified(Auto));
+        assert!(parse_layout_overflow("auto").unwrap().shows_vertical_scrollbar(true));
+        assert!(!parse_layout_overflow("auto").unwrap().shows_vertical_scrollbar(false));
+        assert_eq!(parse_layout_overflow("auto scroll hidden"), Err(InvalidValueErr("auto scroll hidden")));
+    }
+
+    #[test]
+    fn test_parse_icon_font() {
+        assert_eq!(parse_css_icon_font("\"FontAwesome\""), Ok(StyleIconFont(Font::ExternalFont("FontAwesome".into()))));
+        assert_eq!(parse_css_icon_font("serif"), Err(CssFontFamilyParseError::InvalidFontFamily("serif")));
+        assert_eq!(unescape_css_string("\\f000"), "\u{f000}");
+        assert_eq!(unescape_css_string("\\uf000 text"), "\u{f000}text");
+        assert_eq!(unescape_css_string("\\\"quoted\\\""), "\"quoted\"");
+        assert_eq!(parse_css_content("\"\\f00c  \""), Ok(StyleContent { parts: vec![ContentPart::Text("\u{f00c} ".into())] }));
+    }
+
+    #[test]
+    fn test_parse_table_layout() {
+        assert_eq!(parse_layout_table_layout("fixed"), Ok(LayoutTableLayout::Fixed));
+        assert_eq!(ParsedCssProperty::from_kv("table-layout", "auto"),
+            Ok(ParsedCssProperty::TableLayout(LayoutTableLayout::Auto)));
+        assert_eq!(parse_layout_table_layout("static"), Err(InvalidValueErr("static")));
+    }
+
+    #[test]
+    fn test_parse_shape_outside() {
+        use self::ShapeLength::*;
+
+        assert_eq!(parse_css_shape_outside("circle(50%)"), Ok(StyleShapeOutside::Circle {
+            radius: Percentage(0.5),
+            center: (Percentage(0.5), Percentage(0.5)),
+        }));
+        assert_eq!(parse_css_shape_outside("ellipse(20px 10px at left 25%)"), Ok(StyleShapeOutside::Ellipse {
+            radius_x: Fixed(PixelValue { metric: CssMetric::Px, number: 20.0 }),
+            radius_y: Fixed(PixelValue { metric: CssMetric::Px, number: 10.0 }),
+            center: (Percentage(0.0), Percentage(0.25)),
+        }));
+        assert_eq!(parse_css_shape_outside("inset(10% 20%)"), Ok(StyleShapeOutside::Inset {
+            top: Percentage(0.1), right: Percentage(0.2), bottom: Percentage(0.1), left: Percentage(0.2),
+        }));
+        assert_eq!(parse_css_shape_outside("polygon(0% 0%, 100% 0%, 0% 100%)"), Ok(StyleShapeOutside::Polygon(vec![
+            (Percentage(0.0), Percentage(0.0)), (Percentage(1.0), Percentage(0.0)), (Percentage(0.0), Percentage(1.0)),
+        ])));
+        assert_eq!(parse_css_shape_outside("polygon(0% 0%, 100% 0%)"), Err(CssShapeOutsideParseError::InvalidArguments("0% 0%, 100% 0%")));
+        assert_eq!(parse_css_shape_outside("square(5px)"), Err(CssShapeOutsideParseError::InvalidShape("square")));
+    }
+
+    #[test]
+    fn test_parse_css_filters_drop_shadow() {
+        assert_eq!(parse_css_filters("drop-shadow(2px 4px 6px rgba(0, 0, 0, 0.5)) blur(1px)"), Ok(vec![
+            StyleFilter::DropShadow(TextShadowItem {
+                offset: LayoutVector2D::new(2.0, 4.0),
+                color: Some(ColorF::from(ColorU { r: 0, g: 0, b: 0, a: 128 })),
+                blur_radius: 6.0,
+            }),
+            StyleFilter::Blur(PixelValue { metric: CssMetric::Px, number: 1.0 }),
+        ]));
+        assert_eq!(parse_css_filters("drop-shadow(2px 2px)"), Ok(vec![
+            StyleFilter::DropShadow(TextShadowItem { offset: LayoutVector2D::new(2.0, 2.0), color: None, blur_radius: 0.0 }),
+        ]));
+        assert_eq!(parse_css_filters("drop-shadow(2px)"),
+                   Err(CssFilterParseError::ShadowParseError(CssShadowParseError::InvalidSingleStatement("2px"))));
+    }
+
+    #[test]
+    fn test_parse_style_vertical_align() {
+        assert_eq!(ParsedCssProperty::from_kv("vertical-align", "middle"),
+            Ok(ParsedCssProperty::VerticalAlign(StyleVerticalAlign::Middle)));
+        assert_eq!(parse_style_vertical_align("baseline"), Ok(StyleVerticalAlign::Baseline));
+        assert_eq!(parse_style_vertical_align("sub"), Err(InvalidValueErr("sub")));
+    }
+
+    #[test]
+    fn test_parse_style_font_display() {
+        assert_eq!(ParsedCssProperty::from_kv("font-display", "swap"),
+            Ok(ParsedCssProperty::FontDisplay(StyleFontDisplay::Swap)));
+        assert_eq!(parse_style_font_display("optional"), Ok(StyleFontDisplay::Optional));
+        assert_eq!(parse_style_font_display("lazy"), Err(InvalidValueErr("lazy")));
+    }
+
+    #[test]
+    fn test_parse_css_list_style_type() {
+        assert_eq!(ParsedCssProperty::from_kv("list-style-type", "square"),
+            Ok(ParsedCssProperty::ListStyleType(StyleListStyleType::Square)));
+        assert_eq!(parse_css_list_style_type("\"- \""), Ok(StyleListStyleType::String("- ".into())));
+        assert_eq!(parse_css_list_style_type("'\\2713'"), Ok(StyleListStyleType::String("\u{2713}".into())));
+        assert_eq!(parse_css_list_style_type("decimal"), Err(CssContentParseError::InvalidContent("decimal")));
+        assert_eq!(parse_css_list_style_type("\"-"), Err(CssContentParseError::UnclosedQuotes("\"-")));
+        assert_eq!(parse_css_list_style_position("inside"), Ok(StyleListStylePosition::Inside));
+        assert_eq!(StyleListStyleType::None.get_marker_text(), None);
+    }
+
+    #[test]
+    fn test_parse_padding_percentage() {
+        let padding = match ParsedCssProperty::from_kv("padding-top", "50%") {
+            Ok(ParsedCssProperty::Padding(p)) => p,
+            other => panic!("unexpected result: {:?}", other),
+        };
+        assert_eq!(padding.top, Some(LayoutPaddingValue::Percentage(PercentageValue { number: 0.5 })));
+
+        // the top padding refers to the width of the containing block, not the height
+        assert_eq!(padding.top.unwrap().to_pixels(200.0), 100.0);
+        assert_eq!(padding.vertical(200.0), 100.0);
+
+        let padding = parse_layout_padding("10% 5px").unwrap();
+        assert_eq!(padding.horizontal_parts(), (10.0, 0.0));
+        assert_eq!(padding.vertical_parts(), (0.0, 0.2));
+    }
+
+    #[test]
+    fn test_parse_style_all() {
+        assert_eq!(ParsedCssProperty::from_kv("all", "unset"), Ok(ParsedCssProperty::All(StyleAll::Unset)));
+        assert_eq!(parse_style_all("initial"), Ok(StyleAll::Initial));
+        assert_eq!(parse_style_all("inherit"), Ok(StyleAll::Inherit));
+        assert_eq!(parse_style_all("revert"), Err(InvalidValueErr("revert")));
+    }
+
+    #[test]
+    fn test_parse_layout_float_and_clear() {
+        assert_eq!(ParsedCssProperty::from_kv("float", "right"), Ok(ParsedCssProperty::Float(LayoutFloat::Right)));
+        assert_eq!(ParsedCssProperty::from_kv("clear", "both"), Ok(ParsedCssProperty::Clear(LayoutClear::Both)));
+        assert_eq!(parse_layout_float("center"), Err(InvalidValueErr("center")));
+        assert!(LayoutClear::Both.clears_left() && LayoutClear::Both.clears_right());
+        assert!(!LayoutClear::Left.clears_right());
+    }
+
+    #[test]
+    fn test_parse_scroll_margin_padding() {
+        let px = |number: f32| Some(PixelValue { metric: CssMetric::Px, number: number });
+        assert_eq!(parse_css_scroll_margin("16px"), Ok(StyleScrollMargin { top: px(16.0), right: px(16.0), bottom: px(16.0), left: px(16.0) }));
+        assert_eq!(parse_css_scroll_padding("50px 0 0"), Ok(StyleScrollPadding { top: px(50.0), right: px(0.0), bottom: px(0.0), left: px(0.0) }));
+        assert_eq!(parse_css_scroll_margin("1px 2px 3px 4px 5px"), Err(CssMarginParseError::TooManyValues("1px 2px 3px 4px 5px")));
+        assert_eq!(ParsedCssProperty::from_kv("scroll-padding-top", "8px"),
+                   Ok(ParsedCssProperty::ScrollPadding(StyleScrollPadding { top: px(8.0), .. Default::default() })));
+        assert_eq!(StyleScrollPadding { top: px(8.0), .. Default::default() }.to_pixels(), [8.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_parse_list_style_image() {
+        assert_eq!(parse_css_list_style_image("url(\"bullet.png\")"), Ok(StyleListStyleImage::Image(CssImageId(String::from("bullet.png")))));
+        assert_eq!(parse_css_list_style_image("image(bullet)"), Ok(StyleListStyleImage::Image(CssImageId(String::from("bullet")))));
+        assert_eq!(parse_css_list_style_image("none"), Ok(StyleListStyleImage::None));
+        assert_eq!(parse_css_list_style_image("bullet.png"), Err(CssImageParseError::InvalidImage("bullet.png")));
+    }
+
+    #[test]
+    fn test_parse_css_user_select() {
+        assert_eq!(parse_css_user_select("none"), Ok(StyleUserSelect::None));
+        assert_eq!(parse_css_user_select("all"), Ok(StyleUserSelect::All));
+        assert_eq!(parse_css_user_select("contain"), Err(InvalidValueErr("contain")));
+    }
+
+    #[test]
+    fn test_parse_css_pointer_events() {
+        assert_eq!(parse_css_pointer_events("painted"), Ok(StylePointerEvents::Painted));
+        assert_eq!(parse_css_pointer_events("visiblePainted"), Err(InvalidValueErr("visiblePainted")));
+    }
+
+    #[test]
+    fn test_parse_css_appearance() {
+        assert_eq!(ParsedCssProperty::from_kv("appearance", "none"), Ok(ParsedCssProperty::Appearance(StyleAppearance::None)));
+        assert_eq!(parse_css_appearance("button"), Err(InvalidValueErr("button")));
+    }
+
+    #[test]
+    fn test_parse_css_background_clip() {
+        assert_eq!(parse_css_background_clip("padding-box"), Ok(StyleBackgroundClip::PaddingBox));
+        assert_eq!(parse_css_background_clip("content-box"), Ok(StyleBackgroundClip::ContentBox));
+        assert_eq!(parse_css_background_clip("text"), Err(InvalidValueErr("text")));
+    }
+}
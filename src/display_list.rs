@@ -2,6 +2,8 @@
 #![allow(unused_macros)]
 
 use std::{
+    rc::Rc,
+    borrow::Cow,
     collections::BTreeMap,
     sync::atomic::{Ordering, AtomicUsize},
     fmt::Debug,
@@ -12,26 +14,36 @@ use euclid::{TypedRect, TypedSize2D};
 use cassowary::{Constraint, Solver, Variable};
 
 use {
-    FastHashMap,
+    FastHashMap, FastHashSet,
     resources::AppResources,
     traits::Layout,
     constraints::{DisplayRect, CssConstraint},
     ui_description::{UiDescription, StyledNode},
     window::UiSolver,
-    window_state::WindowSize,
+    window_state::{WindowSize, AaMode},
     id_tree::{Arena, NodeId},
     css_parser::{self, *},
     dom::{NodeData, NodeType::{self, *}},
     css::Css,
     cache::DomChangeSet,
     ui_description::CssConstraintList,
-    text_layout::{TextOverflowPass2, ScrollbarInfo},
+    text_layout::{TextOverflowPass2, ScrollbarInfo, TextLine, TextDecorationMetrics, FloatExclusion},
     images::ImageId,
     text_cache::TextId,
 };
 
 const DEFAULT_FONT_COLOR: TextColor = TextColor(ColorU { r: 0, b: 0, g: 0, a: 255 });
 const DEFAULT_BUILTIN_FONT_SANS_SERIF: css_parser::Font = Font::BuiltinFont("sans-serif");
+/// Default `column-gap` (CSS `normal` = 1em)
+const DEFAULT_COLUMN_GAP: f32 = EM_HEIGHT;
+/// Width of the `column-rule` if no `column-rule-width` is set (`medium` in CSS)
+const DEFAULT_COLUMN_RULE_WIDTH: f32 = 3.0;
+/// Height of one line of text relative to the font size, for `line-height: normal`
+const NORMAL_LINE_HEIGHT: f32 = 1.2;
+/// If more nodes than this have a `backdrop-filter` set, a warning is printed (only in debug mode)
+const MAX_BACKDROP_FILTER_NODES: usize = 8;
+/// If more nodes than this have a `will-change` hint, a warning is printed (only in debug mode)
+const MAX_WILL_CHANGE_NODES: usize = 32;
 
 pub(crate) struct DisplayList<'a, T: Layout + 'a> {
     pub(crate) ui_descr: &'a UiDescription<T>,
@@ -39,7 +51,7 @@ pub(crate) struct DisplayList<'a, T: Layout + 'a> {
 }
 
 /// DisplayRectangle is the main type which the layout parsing step gets operated on.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct DisplayRectangle<'a> {
     /// `Some(id)` if this rectangle has a callback attached to it
     /// Note: this is not the same as the `NodeId`!
@@ -51,6 +63,14 @@ pub(crate) struct DisplayRectangle<'a> {
     pub(crate) style: RectStyle,
     /// The layout properties of the node, parsed
     pub(crate) layout: RectLayout,
+    /// The text generated by the `content` of the `::before` pseudo-element
+    /// (with the values of the CSS counters filled in)
+    pub(crate) before_text: Option<String>,
+    /// The text of the `::marker` pseudo-element (`list-style-type`), if the marker
+    /// is drawn outside of the content box (`list-style-position: outside`)
+    pub(crate) marker_text: Option<String>,
+    /// The last `all` declaration of the node, the declarations before it are ignored
+    pub(crate) css_all: Option<StyleAll>,
 }
 
 /// It is not very efficient to re-create constraints on every call, the difference
@@ -61,6 +81,13 @@ pub(crate) struct DisplayRectangle<'a> {
 pub(crate) struct SolvedLayout<T: Layout> {
     // List of previously solved constraints
     pub(crate) solved_constraints: FastHashMap<NodeId, NodeData<T>>,
+    /// Bounds of the characters of the text nodes in the last frame, see `FakeWindow::get_char_bounds()`
+    pub(crate) char_rects: Rc<FastHashMap<NodeId, Vec<LayoutRect>>>,
+    /// Text nodes whose character bounds were queried via the `FakeWindow`, their
+    /// `char_rects` are computed on every layout (see `needs_char_rects()`)
+    pub(crate) char_rect_queries: FastHashSet<NodeId>,
+    /// The (inherited) `user-select` of the text nodes, if it isn't `auto`
+    pub(crate) user_select: Rc<FastHashMap<NodeId, StyleUserSelect>>,
 }
 
 /// This is used for caching large strings (in the `push_text` function)
@@ -91,12 +118,51 @@ impl<'a> TextInfo<'a> {
             Uncached(s) => s.is_empty(),
         }
     }
+
+    /// Returns the text, for mapping the characters to the positioned glyphs.
+    /// Returns `None` if the ID does not exist.
+    fn get_text<'b>(&'b self, app_resources: &'b AppResources)
+    -> Option<Cow<'b, str>>
+    {
+        use self::TextInfo::*;
+        use text_cache::LargeString;
+        use text_layout::SemanticWordItem;
+
+        match self {
+            Cached(text_id) => match app_resources.text_cache.cached_strings.get(text_id)? {
+                LargeString::Raw(r) => Some(Cow::Borrowed(r)),
+                // the number of spaces between the words isn't stored, assume a single space
+                LargeString::Cached { words, .. } => {
+                    let mut text = String::new();
+                    let mut previous_was_word = false;
+                    for word in words.iter() {
+                        match word {
+                            SemanticWordItem::Word(w) => {
+                                if previous_was_word {
+                                    text.push(' ');
+                                }
+                                text += &w.text;
+                            },
+                            SemanticWordItem::Tab => text.push('\t'),
+                            SemanticWordItem::Return => text.push('\n'),
+                        }
+                        previous_was_word = match word { SemanticWordItem::Word(_) => true, _ => false };
+                    }
+                    Some(Cow::Owned(text))
+                },
+            },
+            Uncached(s) => Some(Cow::Borrowed(s)),
+        }
+    }
 }
 
 impl<T: Layout> SolvedLayout<T> {
     pub fn empty() -> Self {
         Self {
             solved_constraints: FastHashMap::default(),
+            char_rects: Rc::new(FastHashMap::default()),
+            char_rect_queries: FastHashSet::default(),
+            user_select: Rc::new(FastHashMap::default()),
         }
     }
 }
@@ -109,6 +175,9 @@ impl<'a> DisplayRectangle<'a> {
             styled_node: styled_node,
             style: RectStyle::default(),
             layout: RectLayout::default(),
+            before_text: None,
+            marker_text: None,
+            css_all: None,
         }
     }
 }
@@ -121,14 +190,52 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
     /// layout. The layout is done only in the `into_display_list_builder` step.
     pub fn new_from_ui_description(ui_description: &'a UiDescription<T>) -> Self {
 
+        let counters = ui_description.collect_counters();
         let arena = ui_description.ui_descr_arena.borrow();
-        let display_rect_arena = arena.transform(|node, node_id| {
+        let mut display_rect_arena = arena.transform(|node, node_id| {
             let style = ui_description.styled_nodes.get(&node_id).unwrap_or(&ui_description.default_style_of_node);
             let mut rect = DisplayRectangle::new(node.tag, style);
             populate_css_properties(&mut rect, &ui_description.dynamic_css_overrides);
             rect
         });
 
+        // `all: inherit | unset` - the declarations after `all` are re-applied on top of the
+        // (already resolved) properties of the parent, in tree order so that parents come first
+        if let Some(root) = ui_description.ui_descr_root {
+            for node_id in root.descendants(&*arena).collect::<Vec<NodeId>>() {
+                let parent_id = match display_rect_arena[node_id].parent() {
+                    Some(parent_id) => parent_id,
+                    None => continue,
+                };
+                let (style, layout) = {
+                    let parent = &display_rect_arena[parent_id].data;
+                    match display_rect_arena[node_id].data.css_all {
+                        Some(StyleAll::Inherit) => (parent.style.clone(), parent.layout.clone()),
+                        Some(StyleAll::Unset) => (parent.style.inherited_properties(), RectLayout::default()),
+                        Some(StyleAll::Initial) | None => continue,
+                    }
+                };
+                let rect = &mut display_rect_arena[node_id].data;
+                rect.style = style;
+                rect.layout = layout;
+                populate_css_properties(rect, &ui_description.dynamic_css_overrides);
+            }
+        }
+
+        for node_id in arena.linear_iter() {
+            let rect = &mut display_rect_arena[node_id].data;
+            rect.before_text = rect.style.before_content.as_ref().map(|content|
+                content.resolve(counters.get(&node_id).map(|c| &c[..]).unwrap_or(&[])));
+            // `::marker`: an `inside` marker is part of the text, in front of the `::before` content
+            let marker_text = rect.style.list_style_type.as_ref().and_then(|l| l.get_marker_text());
+            match rect.style.list_style_position.unwrap_or_default() {
+                StyleListStylePosition::Inside => if let Some(marker) = marker_text {
+                    rect.before_text = Some(marker + rect.before_text.as_ref().map(|s| &s[..]).unwrap_or(""));
+                },
+                StyleListStylePosition::Outside => rect.marker_text = marker_text,
+            }
+        }
+
         Self {
             ui_descr: ui_description,
             rectangles: display_rect_arena,
@@ -143,6 +250,10 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
     {
         Self::update_image_resources(api, app_resources, resource_updates);
         Self::update_font_resources(api, app_resources, resource_updates);
+
+        // frames of the animated images that were removed and evicted lazy images
+        let deleted_image_keys = ::images::take_deleted_image_keys();
+        resource_updates.extend(deleted_image_keys.into_iter().map(ResourceUpdate::DeleteImage));
     }
 
     fn update_image_resources(
@@ -150,7 +261,7 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
         app_resources: &mut AppResources,
         resource_updates: &mut Vec<ResourceUpdate>)
     {
-        use images::{ImageState, ImageInfo};
+        use images::{ImageState, ImageInfo, get_alpha_mask};
 
         let mut updated_images = Vec::<(ImageId, (ImageData, ImageDescriptor))>::new();
         let mut to_delete_images = Vec::<(ImageId, Option<ImageKey>)>::new();
@@ -180,6 +291,11 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
         // uploaded yet
         for (resource_key, (data, descriptor)) in updated_images.into_iter() {
 
+            let alpha_mask = match data {
+                ImageData::Raw(ref bytes) => get_alpha_mask(descriptor.format, bytes).map(Rc::new),
+                _ => None,
+            };
+
             let key = api.generate_image_key();
             resource_updates.push(ResourceUpdate::AddImage(
                 AddImage { key, descriptor, data, tiling: None }
@@ -188,7 +304,8 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
             *app_resources.images.get_mut(&resource_key).unwrap() =
                 ImageState::Uploaded(ImageInfo {
                     key: key,
-                    descriptor: descriptor
+                    descriptor: descriptor,
+                    alpha_mask: alpha_mask,
             });
         }
     }
@@ -250,7 +367,8 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
         app_resources: &mut AppResources,
         render_api: &RenderApi,
         mut has_window_size_changed: bool,
-        window_size: &WindowSize)
+        window_size: &WindowSize,
+        text_aa_mode: AaMode)
     -> Option<DisplayListBuilder>
     {
         use euclid::TypedScale;
@@ -259,8 +377,7 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
 
         if let Some(root) = self.ui_descr.ui_descr_root {
             let local_changeset = ui_solver.dom_tree_cache.update(root, &*(self.ui_descr.ui_descr_arena.borrow()));
-            ui_solver.edit_variable_cache.initialize_new_rectangles(&mut ui_solver.solver, &local_changeset);
-            ui_solver.edit_variable_cache.remove_unused_variables(&mut ui_solver.solver);
+            ui_solver.edit_variable_cache.apply_diff(&mut ui_solver.solver, &local_changeset);
             changeset = Some(local_changeset);
         }
 
@@ -272,9 +389,45 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
                 let arena = &*self.ui_descr.ui_descr_arena.borrow();
                 let dom_hash = &ui_solver.dom_tree_cache.previous_layout.arena[rect_idx];
                 let display_rect = ui_solver.edit_variable_cache.map[&dom_hash.data];
-                let layout_contraints = create_layout_constraints(rect, rect_idx, &self.rectangles, window_size);
+                let (parent_rect, previous_sibling, floats) = {
+                    let get_display_rect = |node_id: NodeId| {
+                        let node_hash = &ui_solver.dom_tree_cache.previous_layout.arena[node_id];
+                        ui_solver.edit_variable_cache.map[&node_hash.data].1
+                    };
+                    let parent_rect = self.rectangles[rect_idx].parent().map(&get_display_rect);
+                    // floated siblings are not part of the normal flow
+                    let (previous_in_flow, floats) = get_floats(rect_idx, &self.rectangles);
+                    let previous_sibling = previous_in_flow.map(|sibling_idx| {
+                        (get_display_rect(sibling_idx), &self.rectangles[sibling_idx].data)
+                    });
+                    (parent_rect, previous_sibling, floats.map(&get_display_rect))
+                };
+                let mut layout_contraints = create_layout_constraints(rect, rect_idx, &self.rectangles, window_size);
+                if let Some(parent_rect) = parent_rect {
+                    let parent_width = ui_solver.solver.get_value(parent_rect.width) as f32;
+                    layout_contraints.extend(create_flow_constraints(rect, &parent_rect, parent_width, previous_sibling, &floats, &*app_resources));
+                    if let Some(margin) = rect.layout.margin {
+                        layout_contraints.extend(create_margin_constraints(&margin, &parent_rect));
+                    }
+                }
+                if let Some(column_cell) = get_fixed_table_column_cell(rect_idx, &self.rectangles) {
+                    let column_rect = {
+                        let node_hash = &ui_solver.dom_tree_cache.previous_layout.arena[column_cell];
+                        ui_solver.edit_variable_cache.map[&node_hash.data].1
+                    };
+                    layout_contraints.extend(create_fixed_table_cell_constraints(&column_rect));
+                }
+                if let Some(row) = get_table_row(rect_idx, &self.rectangles) {
+                    let row_rect = {
+                        let node_hash = &ui_solver.dom_tree_cache.previous_layout.arena[row];
+                        ui_solver.edit_variable_cache.map[&node_hash.data].1
+                    };
+                    layout_contraints.extend(create_table_cell_height_constraints(&row_rect));
+                }
                 let cassowary_constraints = css_constraints_to_cassowary_constraints(&display_rect.1, &layout_contraints);
                 ui_solver.solver.add_constraints(&cassowary_constraints).unwrap();
+                #[cfg(debug_assertions)]
+                ui_solver.added_constraints.extend(cassowary_constraints);
             }
 
             // if we push or pop constraints that means we also need to re-layout the window
@@ -307,43 +460,289 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
         // Upload image and font resources
         Self::update_resources(render_api, app_resources, &mut resource_updates);
 
-        for rect_idx in self.rectangles.linear_iter() {
+        #[cfg(debug_assertions)]
+        warn_on_too_many_backdrop_filters(&self.rectangles);
+        #[cfg(debug_assertions)]
+        warn_on_too_many_will_change_nodes(&self.rectangles);
+
+        let mut char_rects = FastHashMap::default();
+        let mut user_select = FastHashMap::default();
 
-            let display_rectangle = &self.rectangles[rect_idx].data;
+        if let Some(root) = self.ui_descr.ui_descr_root {
             let arena = self.ui_descr.ui_descr_arena.borrow();
-            let node_type = &arena[rect_idx].data.node_type;
+            let float_exclusions = get_float_exclusions(&self.rectangles, &*arena, ui_solver);
+            let mut painted_nodes = Vec::with_capacity(self.rectangles.nodes_len());
+            for root_sibling in root.following_siblings(&self.rectangles) {
+                self.push_rectangles_recursive(
+                    root_sibling,
+                    &*arena,
+                    &mut painted_nodes,
+                    &mut builder,
+                    full_screen_rect,
+                    app_resources,
+                    render_api,
+                    &mut resource_updates,
+                    text_aa_mode,
+                    css.focused_node,
+                    &float_exclusions,
+                    &ui_solver.solved_layout.char_rect_queries,
+                    &mut char_rects,
+                    &mut user_select);
+            }
+        }
 
-            // ask the solver what the bounds of the current rectangle is
-            // let bounds = ui_solver.query_bounds_of_rect(*rect_idx);
+        ui_solver.solved_layout.char_rects = Rc::new(char_rects);
+        ui_solver.solved_layout.user_select = Rc::new(user_select);
 
-            // temporary: fill the whole window with each rectangle
-            displaylist_handle_rect(
-                &mut builder,
-                display_rectangle,
-                node_type,
+        render_api.update_resources(resource_updates);
+
+        Some(builder)
+    }
+
+    /// Draws the rectangle, then its children: the stacking context of the rectangle (if any)
+    /// stays open while the children are drawn, so that the `opacity`, `filter`, etc.
+    /// of the rectangle applies to the whole subtree.
+    ///
+    /// `painted_nodes` are the nodes that have been drawn so far, in drawing order.
+    fn push_rectangles_recursive(
+        &self,
+        rect_idx: NodeId,
+        arena: &Arena<NodeData<T>>,
+        painted_nodes: &mut Vec<NodeId>,
+        builder: &mut DisplayListBuilder,
+        full_screen_rect: TypedRect<f32, LayoutPixel>,
+        app_resources: &mut AppResources,
+        render_api: &RenderApi,
+        resource_updates: &mut Vec<ResourceUpdate>,
+        text_aa_mode: AaMode,
+        focused_node: Option<NodeId>,
+        float_exclusions: &FastHashMap<NodeId, Vec<FloatExclusion>>,
+        char_rect_queries: &FastHashSet<NodeId>,
+        char_rects: &mut FastHashMap<NodeId, Vec<LayoutRect>>,
+        user_select: &mut FastHashMap<NodeId, StyleUserSelect>)
+    {
+        let display_rectangle = &self.rectangles[rect_idx].data;
+
+        // `display: none` hides the whole subtree, unlike `visibility: hidden`
+        if display_rectangle.layout.display == Some(LayoutDisplay::None) {
+            return;
+        }
+
+        let is_text = match arena[rect_idx].data.node_type { Label(_) | Text(_) => true, _ => false };
+        let is_focused = focused_node == Some(rect_idx);
+        let mut node_char_rects = Vec::new();
+
+        // ask the solver what the bounds of the current rectangle is
+        // let bounds = ui_solver.query_bounds_of_rect(*rect_idx);
+
+        if display_rectangle.style.backdrop_filter.is_some() {
+            self.push_backdrop_filter(
+                rect_idx,
+                arena,
+                painted_nodes,
+                builder,
+                full_screen_rect,
+                app_resources,
+                render_api,
+                resource_updates,
+                text_aa_mode,
+                float_exclusions);
+        }
+
+        // temporary: fill the whole window with each rectangle
+        let stacking_context_pushed = displaylist_handle_rect(
+            builder,
+            display_rectangle,
+            &arena[rect_idx].data.node_type,
+            full_screen_rect, /* replace this with the real bounds */
+            full_screen_rect.size.width, /* replace this with the width of the parent */
+            full_screen_rect,
+            app_resources,
+            render_api,
+            resource_updates,
+            text_aa_mode,
+            if is_text && needs_char_rects(rect_idx, &display_rectangle.style, is_focused, char_rect_queries) {
+                Some(&mut node_char_rects)
+            } else {
+                None
+            },
+            float_exclusions.get(&rect_idx).map(|e| &e[..]).unwrap_or(&[]));
+
+        painted_nodes.push(rect_idx);
+
+        // The focused text gets a text cursor behind its last character
+        if is_focused && is_text && display_rectangle.style.visibility != Some(StyleVisibility::Hidden) {
+            let glyph_bounds = get_text_cursor_bounds(&node_char_rects, &display_rectangle.style, &full_screen_rect);
+            push_text_cursor(builder, &display_rectangle.style, &glyph_bounds, &full_screen_rect);
+        }
+
+        // The children of a scroll container are drawn into a scroll frame, which
+        // webrender moves by the scroll offset of the container (see `send_display_list()`)
+        let is_scroll_container = display_rectangle.style.overflow.map(|o| !o.is_visible()).unwrap_or(false);
+        if is_scroll_container {
+            let scroll_frame_id = builder.define_scroll_frame(
+                Some(get_external_scroll_id(rect_idx, builder.pipeline_id)),
+                full_screen_rect,
                 full_screen_rect, /* replace this with the real bounds */
+                Vec::<ComplexClipRegion>::new(),
+                None,
+                ScrollSensitivity::Script);
+            builder.push_clip_id(scroll_frame_id);
+        }
+
+        if !node_char_rects.is_empty() {
+            char_rects.insert(rect_idx, node_char_rects);
+        }
+
+        if is_text {
+            // `user-select` is inherited from the parent nodes during the cascade
+            match display_rectangle.style.user_select.unwrap_or_default() {
+                StyleUserSelect::Auto => { },
+                node_user_select => { user_select.insert(rect_idx, node_user_select); },
+            }
+        }
+
+        for child_idx in rect_idx.children(&self.rectangles) {
+            self.push_rectangles_recursive(
+                child_idx,
+                arena,
+                painted_nodes,
+                builder,
                 full_screen_rect,
                 app_resources,
                 render_api,
-                &mut resource_updates);
+                resource_updates,
+                text_aa_mode,
+                focused_node,
+                float_exclusions,
+                char_rect_queries,
+                char_rects,
+                user_select);
         }
 
-        render_api.update_resources(resource_updates);
+        if is_scroll_container {
+            builder.pop_clip_id();
+        }
 
-        Some(builder)
+        if stacking_context_pushed {
+            builder.pop_stacking_context();
+        }
+    }
+
+    /// `backdrop-filter`: draws a copy of the `painted_nodes` (the content behind the node),
+    /// clipped to the bounds of the node and with the backdrop filters applied. The node
+    /// itself is drawn on top of the copy afterwards, so semi-transparent backgrounds show
+    /// the filtered content behind them ("frosted glass").
+    ///
+    /// NOTE: The copied nodes are drawn one by one, without the stacking contexts of their
+    /// parents and without hit-testing tags. Iframes are not copied, since a pipeline
+    /// can only be embedded once.
+    fn push_backdrop_filter(
+        &self,
+        rect_idx: NodeId,
+        arena: &Arena<NodeData<T>>,
+        painted_nodes: &[NodeId],
+        builder: &mut DisplayListBuilder,
+        full_screen_rect: TypedRect<f32, LayoutPixel>,
+        app_resources: &mut AppResources,
+        render_api: &RenderApi,
+        resource_updates: &mut Vec<ResourceUpdate>,
+        text_aa_mode: AaMode,
+        float_exclusions: &FastHashMap<NodeId, Vec<FloatExclusion>>)
+    {
+        let style = &self.rectangles[rect_idx].data.style;
+        let backdrop_filter = match style.backdrop_filter {
+            Some(ref backdrop_filter) => backdrop_filter,
+            None => return,
+        };
+
+        // temporary: the bounds of the node are the whole window, see `push_rectangles_recursive`
+        let bounds = full_screen_rect;
+        let current_color = style.font_color.unwrap_or(DEFAULT_FONT_COLOR).0.into();
+        let filters = backdrop_filter.0.iter().map(|f| f.to_filter_op(current_color)).collect::<Vec<FilterOp>>();
+
+        let rounded_corners = style.border_radius.map(|border_radius| ComplexClipRegion {
+            rect: bounds,
+            radii: border_radius,
+            mode: ClipMode::Clip,
+        });
+        let clip_id = builder.define_clip(bounds, rounded_corners, None);
+        builder.push_clip_id(clip_id);
+
+        builder.push_stacking_context(
+            &LayoutPrimitiveInfo::new(bounds),
+            ScrollPolicy::Scrollable,
+            None,
+            TransformStyle::Flat,
+            None,
+            MixBlendMode::Normal,
+            filters);
+
+        for painted_idx in painted_nodes {
+            let node_type = &arena[*painted_idx].data.node_type;
+            if let IFrame(_) = *node_type {
+                continue;
+            }
+
+            let mut copied_rect = self.rectangles[*painted_idx].data.clone();
+            copied_rect.tag = None;
+
+            let stacking_context_pushed = displaylist_handle_rect(
+                builder,
+                &copied_rect,
+                node_type,
+                full_screen_rect,
+                full_screen_rect.size.width,
+                full_screen_rect,
+                app_resources,
+                render_api,
+                resource_updates,
+                text_aa_mode,
+                None,
+                float_exclusions.get(painted_idx).map(|e| &e[..]).unwrap_or(&[]));
+
+            if stacking_context_pushed {
+                builder.pop_stacking_context();
+            }
+        }
+
+        builder.pop_stacking_context();
+        builder.pop_clip_id();
     }
 }
 
+/// ID of the scroll frame of the scroll container `node_id`, for setting its scroll offset.
+/// The ID stays the same across display lists, as long as the node stays at the same position in the DOM.
+pub(crate) fn get_external_scroll_id(node_id: NodeId, pipeline_id: PipelineId) -> ExternalScrollId {
+    ExternalScrollId(node_id.index.get() as u64, pipeline_id)
+}
+
+/// `char_rects` (if given) is filled with the bounds of the characters of the text of the node.
+/// `containing_block_width` is the width of the parent, for resolving percentage paddings.
+///
+/// Returns whether a stacking context was pushed for the node - it is left open, so that the
+/// children of the node can be drawn into it, the caller has to pop it afterwards.
 fn displaylist_handle_rect(
     builder: &mut DisplayListBuilder,
     rect: &DisplayRectangle,
     html_node: &NodeType,
     bounds: TypedRect<f32, LayoutPixel>,
+    containing_block_width: f32,
     full_screen_rect: TypedRect<f32, LayoutPixel>,
     app_resources: &mut AppResources,
     render_api: &RenderApi,
-    resource_updates: &mut Vec<ResourceUpdate>)
+    resource_updates: &mut Vec<ResourceUpdate>,
+    text_aa_mode: AaMode,
+    mut char_rects: Option<&mut Vec<LayoutRect>>,
+    float_exclusions: &[FloatExclusion])
+-> bool
 {
+    // Hidden nodes keep their layout, but nothing is drawn. Children with
+    // `visibility: visible` are still drawn, since they are separate rectangles.
+    if rect.style.visibility == Some(StyleVisibility::Hidden) {
+        return false;
+    }
+
     let info = LayoutPrimitiveInfo {
         rect: bounds,
         clip_rect: bounds,
@@ -360,6 +759,10 @@ fn displaylist_handle_rect(
         Some(builder.define_clip(bounds, vec![region], None))
     });
 
+    let stacking_context_pushed = push_filter_stacking_context(builder, &info, &rect.style);
+
+    let image_rendering = rect.style.image_rendering.unwrap_or_default().to_image_rendering();
+
     // Push the "outset" box shadow, before the clip is active
     push_box_shadow(
         builder,
@@ -372,17 +775,29 @@ fn displaylist_handle_rect(
         builder.push_clip_id(id);
     }
 
+    // `background-clip` only clips the background, it stays positioned on the border box
+    let background_info = LayoutPrimitiveInfo {
+        clip_rect: get_background_clip_rect(
+            &bounds,
+            containing_block_width,
+            rect.style.background_clip.unwrap_or_default(),
+            rect.style.border.as_ref().map(|&(ref widths, _)| widths),
+            rect.layout.padding.as_ref()),
+        .. info
+    };
+
     if let Some(ref bg_col) = rect.style.background_color {
-        push_rect(&info, builder, bg_col);
+        push_rect(&background_info, builder, bg_col);
     }
 
     if let Some(ref bg) = rect.style.background {
         push_background(
-            &info,
+            &background_info,
             &bounds,
             builder,
             bg,
-            &app_resources);
+            &app_resources,
+            image_rendering);
     };
 
     // Push the inset shadow (if any)
@@ -392,24 +807,133 @@ fn displaylist_handle_rect(
                     &full_screen_rect,
                     BoxShadowClipMode::Inset);
 
-    push_border(
-        &info,
-        builder,
-        &rect.style);
+    // A loaded `border-image` replaces the border, the `border` only sets the width of the slices
+    let has_border_image = match rect.style.border_image {
+        Some(ref border_image) => push_border_image(&info, builder, &bounds, app_resources, border_image, &rect.style, image_rendering),
+        None => false,
+    };
+
+    if !has_border_image {
+        push_border(
+            &info,
+            builder,
+            &rect.style);
+    }
+
+    let is_scroll_container = rect.style.overflow.map(|o| !o.is_visible()).unwrap_or(false);
+    if is_scroll_container && rect.layout.resize.map(|r| r.is_resizable()).unwrap_or(false) {
+        push_resize_handle(builder, &bounds);
+    }
+
+    // The `list-style-image` replaces the `list-style-type` marker, if the image is loaded
+    let has_marker_image = match (rect.style.list_style_image.as_ref(), rect.style.list_style_position.unwrap_or_default()) {
+        (Some(&StyleListStyleImage::Image(ref image)), StyleListStylePosition::Outside) =>
+            push_outside_list_marker_image(builder, &info, rect, image, app_resources, image_rendering),
+        _ => false,
+    };
+
+    if let Some(ref marker_text) = rect.marker_text {
+        if !has_marker_image {
+            push_outside_list_marker(builder, &info, rect, marker_text, app_resources, render_api, resource_updates, text_aa_mode);
+        }
+    }
+
+    // The `-azul-icon-font` replaces the font of the `::before` content
+    // (or of the text of the node, if it has no `::before` content)
+    let icon_style = rect.style.icon_font.as_ref().map(|icon_font| {
+        let mut icon_style = rect.style.clone();
+        icon_style.font_family = Some(FontFamily { fonts: vec![icon_font.0.clone()] });
+        icon_style
+    });
 
     // handle the special content of the node
+    // NOTE: the `::before` content is drawn in front of the text of labels, or as the text
+    // of divs - for cached texts, images, etc. the `::before` content is not drawn
     match html_node {
-        Div => { /* nothing special to do */ },
+        Div => {
+            if let Some(ref before_text) = rect.before_text {
+                push_text(
+                    &info,
+                    &TextInfo::Uncached(before_text),
+                    builder,
+                    icon_style.as_ref().unwrap_or(&rect.style),
+                    app_resources,
+                    &render_api,
+                    &bounds,
+                    resource_updates,
+                    text_aa_mode,
+                    None,
+                    &[]);
+            }
+        },
         Label(text) => {
-            push_text(
-                &info,
-                &TextInfo::Uncached(text),
-                builder,
-                &rect.style,
-                app_resources,
-                &render_api,
-                &bounds,
-                resource_updates);
+            match (&rect.before_text, &icon_style) {
+                (&Some(ref before_text), &Some(ref icon_style)) => {
+                    // The icon is drawn in a different font, so it can't be laid out together
+                    // with the text - the text starts (on a single line) after the icon
+                    push_text(
+                        &info,
+                        &TextInfo::Uncached(before_text),
+                        builder,
+                        icon_style,
+                        app_resources,
+                        &render_api,
+                        &bounds,
+                        resource_updates,
+                        text_aa_mode,
+                        None,
+                        &[]);
+
+                    let icon_width = rect.style.icon_font.as_ref().and_then(|icon_font| ::text_layout::get_text_width(
+                        app_resources,
+                        &icon_font.0,
+                        &rect.style.font_size.unwrap_or(DEFAULT_FONT_SIZE),
+                        before_text)
+                    ).unwrap_or(0.0).min(bounds.size.width);
+
+                    let mut text_bounds = bounds;
+                    text_bounds.origin.x += icon_width;
+                    text_bounds.size.width -= icon_width;
+                    let text_info = LayoutPrimitiveInfo { rect: text_bounds, .. info };
+
+                    push_text(
+                        &text_info,
+                        &TextInfo::Uncached(text),
+                        builder,
+                        &rect.style,
+                        app_resources,
+                        &render_api,
+                        &text_bounds,
+                        resource_updates,
+                        text_aa_mode,
+                        char_rects,
+                        float_exclusions);
+                },
+                _ => {
+                    let text = match rect.before_text {
+                        Some(ref before_text) => format!("{}{}", before_text, text),
+                        None => text.clone(),
+                    };
+                    push_text(
+                        &info,
+                        &TextInfo::Uncached(&text),
+                        builder,
+                        icon_style.as_ref().unwrap_or(&rect.style),
+                        app_resources,
+                        &render_api,
+                        &bounds,
+                        resource_updates,
+                        text_aa_mode,
+                        char_rects.as_mut().map(|c| &mut **c),
+                        float_exclusions);
+                    // the characters of the `::before` content are not part of the text of the node
+                    if let (Some(ref before_text), Some(char_rects)) = (&rect.before_text, char_rects) {
+                        use unicode_normalization::UnicodeNormalization;
+                        let before_len = before_text.nfc().count().min(char_rects.len());
+                        char_rects.drain(..before_len);
+                    }
+                },
+            }
         },
         Text(text_id) => {
             push_text(
@@ -420,21 +944,25 @@ fn displaylist_handle_rect(
                 app_resources,
                 &render_api,
                 &bounds,
-                resource_updates);
+                resource_updates,
+                text_aa_mode,
+                char_rects,
+                float_exclusions);
         },
         Image(image_id) => {
-            push_image(&info, builder, &bounds, app_resources, image_id);
+            let object_fit = rect.style.object_fit.unwrap_or_default();
+            let object_position = rect.style.object_position.unwrap_or_default();
+            push_image(&info, builder, &bounds, app_resources, image_id, object_fit, object_position, image_rendering);
         },
         GlTexture(texture) => {
 
-            use glium::GlObject;
-            use compositor::{ActiveTexture, ACTIVE_GL_TEXTURES};
+            use compositor::{CompositorImageSource, register_external_image};
 
             let opaque = true;
             let allow_mipmaps = true;
             let descriptor = ImageDescriptor::new(texture.inner.width(), texture.inner.height(), ImageFormat::BGRA8, opaque, allow_mipmaps);
             let key = render_api.generate_image_key();
-            let external_image_id = ExternalImageId(texture.inner.get_id() as u64);
+            let external_image_id = texture.external_image_id();
 
             let data = ImageData::External(ExternalImageData {
                 id: external_image_id,
@@ -442,7 +970,38 @@ fn displaylist_handle_rect(
                 image_type: ExternalImageType::TextureHandle(TextureTarget::Default),
             });
 
-            ACTIVE_GL_TEXTURES.lock().unwrap().insert(external_image_id, ActiveTexture { texture: texture.clone() });
+            register_external_image(external_image_id, CompositorImageSource::GlTexture(texture.clone()));
+
+            resource_updates.push(ResourceUpdate::AddImage(
+                AddImage { key, descriptor, data, tiling: None }
+            ));
+
+            builder.push_image(
+                &info,
+                bounds.size,
+                LayoutSize::zero(),
+                image_rendering,
+                AlphaType::Alpha,
+                key);
+        },
+        #[cfg(unix)]
+        SharedMemoryImage(image) => {
+
+            use compositor::{CompositorImageSource, register_external_image};
+
+            let opaque = false;
+            let allow_mipmaps = false;
+            let descriptor = ImageDescriptor::new(image.width, image.height, ImageFormat::BGRA8, opaque, allow_mipmaps);
+            let key = render_api.generate_image_key();
+
+            // The compositor hands the pixels to webrender as raw data, which is uploaded to the GPU
+            let data = ImageData::External(ExternalImageData {
+                id: image.key,
+                channel_index: 0,
+                image_type: ExternalImageType::Buffer,
+            });
+
+            register_external_image(image.key, CompositorImageSource::SharedMemory(*image));
 
             resource_updates.push(ResourceUpdate::AddImage(
                 AddImage { key, descriptor, data, tiling: None }
@@ -452,15 +1011,148 @@ fn displaylist_handle_rect(
                 &info,
                 bounds.size,
                 LayoutSize::zero(),
-                ImageRendering::Auto,
+                image_rendering,
                 AlphaType::Alpha,
                 key);
         },
+        AnimatedImage(animated_texture) => {
+            if let Some(key) = animated_texture.get_current_image_key(render_api, resource_updates) {
+                builder.push_image(
+                    &info,
+                    bounds.size,
+                    LayoutSize::zero(),
+                    image_rendering,
+                    AlphaType::Alpha,
+                    key);
+            }
+        },
+        LazyImage(lazy_image) => {
+            if let Some(key) = lazy_image.get_image_key(render_api, resource_updates) {
+                builder.push_image(
+                    &info,
+                    bounds.size,
+                    LayoutSize::zero(),
+                    image_rendering,
+                    AlphaType::Alpha,
+                    key);
+            }
+            // otherwise the image is loaded once it comes near the visible area of the
+            // window, see `Window::start_loading_visible_lazy_images()`
+        },
+        IFrame(iframe) => {
+            let iframe_rect = TypedRect::new(bounds.origin, TypedSize2D::new(iframe.width, iframe.height));
+            let iframe_info = LayoutPrimitiveInfo {
+                rect: iframe_rect,
+                clip_rect: iframe_rect.intersection(&bounds).unwrap_or(iframe_rect),
+                is_backface_visible: false,
+                tag: info.tag,
+            };
+            builder.push_iframe(&iframe_info, iframe.pipeline_id);
+        },
     }
 
     if clip_region_id.is_some() {
         builder.pop_clip_id();
     }
+
+    stacking_context_pushed
+}
+
+/// Pushes a new stacking context if the rectangle has a `filter`,
+/// an `opacity` below 1.0, a `mix-blend-mode` or `isolation: isolate` set. Returns whether a stacking context was pushed,
+/// so that the caller knows if it has to pop it again.
+///
+/// The stacking context is popped after the children of the node have been drawn, so the
+/// `opacity` applies to the node and its children as a group (CSS group opacity), instead
+/// of making each child transparent on its own.
+///
+/// The `backdrop-filter` is not part of this stacking context, it applies to the content
+/// behind the node, see `DisplayList::push_backdrop_filter`.
+///
+/// Nodes with a `will-change` hint always get their own stacking context (even without
+/// filters), so that WebRender caches the node together with its children as a separate
+/// picture: `will-change: transform`
+/// additionally pushes an identity transform, which creates a new reference frame that
+/// can later be moved without invalidating the rest of the display list.
+#[inline]
+fn push_filter_stacking_context(
+    builder: &mut DisplayListBuilder,
+    info: &PrimitiveInfo<LayoutPixel>,
+    style: &RectStyle)
+-> bool
+{
+    let mut filters = Vec::<FilterOp>::new();
+    let current_color = style.font_color.unwrap_or(DEFAULT_FONT_COLOR).0.into();
+
+    if let Some(ref filter) = style.filter {
+        filters.extend(filter.0.iter().map(|f| f.to_filter_op(current_color)));
+    }
+
+    if let Some(StyleOpacity(opacity)) = style.opacity {
+        if opacity.number < 1.0 {
+            filters.push(FilterOp::Opacity(PropertyBinding::Value(opacity.number), opacity.number));
+        }
+    }
+
+    let will_change = style.will_change.unwrap_or_default();
+
+    if will_change.opacity && filters.is_empty() {
+        filters.push(FilterOp::Opacity(PropertyBinding::Value(1.0), 1.0));
+    }
+
+    let is_isolated = style.isolation == Some(StyleIsolation::Isolate);
+    let mix_blend_mode = style.mix_blend_mode.unwrap_or_default();
+
+    if filters.is_empty() && !will_change.creates_layer() && !is_isolated && mix_blend_mode == StyleMixBlendMode::Normal {
+        return false;
+    }
+
+    let transform = if will_change.transform {
+        Some(PropertyBinding::Value(LayoutTransform::identity()))
+    } else {
+        None
+    };
+
+    builder.push_stacking_context(
+        info,
+        ScrollPolicy::Scrollable,
+        transform,
+        TransformStyle::Flat,
+        None,
+        mix_blend_mode.to_mix_blend_mode(),
+        filters);
+
+    true
+}
+
+/// Backdrop filters are expensive (each one needs an extra offscreen pass on the GPU),
+/// so warn the user if too many nodes use them at the same time
+#[cfg(debug_assertions)]
+fn warn_on_too_many_backdrop_filters<'a>(rectangles: &Arena<DisplayRectangle<'a>>) {
+    let backdrop_filter_count = rectangles.linear_iter()
+        .filter(|rect_idx| rectangles[*rect_idx].data.style.backdrop_filter.is_some())
+        .count();
+
+    if backdrop_filter_count > MAX_BACKDROP_FILTER_NODES {
+        warn!("{} nodes have a backdrop-filter set (recommended maximum: {}), \
+                   this may severely impact rendering performance",
+                   backdrop_filter_count, MAX_BACKDROP_FILTER_NODES);
+    }
+}
+
+/// Every `will-change` node allocates its own GPU layer, so warn the user if the
+/// hint is applied to too many nodes at once (usually by accident, via a broad CSS selector)
+#[cfg(debug_assertions)]
+fn warn_on_too_many_will_change_nodes<'a>(rectangles: &Arena<DisplayRectangle<'a>>) {
+    let will_change_count = rectangles.linear_iter()
+        .filter(|rect_idx| rectangles[*rect_idx].data.style.will_change.map(|w| w.creates_layer()).unwrap_or(false))
+        .count();
+
+    if will_change_count > MAX_WILL_CHANGE_NODES {
+        warn!("{} nodes have will-change set (recommended maximum: {}), \
+                   this may use a lot of GPU memory - only set will-change right before an animation starts",
+                   will_change_count, MAX_WILL_CHANGE_NODES);
+    }
 }
 
 #[inline]
@@ -481,7 +1173,10 @@ fn push_text<'a>(
     app_resources: &mut AppResources,
     render_api: &RenderApi,
     bounds: &TypedRect<f32, LayoutPixel>,
-    resource_updates: &mut Vec<ResourceUpdate>)
+    resource_updates: &mut Vec<ResourceUpdate>,
+    text_aa_mode: AaMode,
+    char_rects: Option<&mut Vec<LayoutRect>>,
+    float_exclusions: &[FloatExclusion])
 {
     use dom::NodeType::*;
     use euclid::{TypedPoint2D, Length};
@@ -499,7 +1194,12 @@ fn push_text<'a>(
 
     let font_size = style.font_size.unwrap_or(DEFAULT_FONT_SIZE);
     let font_size_app_units = Au((font_size.0.to_pixels() as i32) * AU_PER_PX as i32);
-    let font_id = font_family.fonts.get(0).unwrap_or(&DEFAULT_BUILTIN_FONT_SANS_SERIF);
+    let font_id = match select_font(font_family, style.font_display.unwrap_or_default(), app_resources) {
+        Some(f) => f,
+        // the font is still loading, the text is invisible until then (`font-display`)
+        None => return,
+    };
+    let font_id = &font_id;
     let font_result = push_font(font_id, font_size_app_units, resource_updates, app_resources, render_api);
 
     let font_instance_key = match font_result {
@@ -507,19 +1207,89 @@ fn push_text<'a>(
         None => return,
     };
 
-    let vert_alignment = TextAlignmentVert::Center; // TODO
+    // without `vertical-align`, the text is centered (for buttons, labels, etc.)
+    let vert_alignment = style.vertical_align.map(|v| v.to_text_alignment_vert()).unwrap_or(TextAlignmentVert::Center);
     let line_height = style.line_height;
 
-    let horz_alignment = style.text_align.unwrap_or(TextAlignmentHorz::default());
+    let horz_alignment = style.text_align.unwrap_or(match style.direction.unwrap_or_default() {
+        StyleDirection::Ltr => TextAlignmentHorz::default(),
+        StyleDirection::Rtl => TextAlignmentHorz::Right,
+    });
     let overflow_behaviour = style.overflow.unwrap_or(LayoutOverflow::default());
 
-    let scrollbar_style = ScrollbarInfo {
-        width: 17,
-        padding: 2,
-        background_color: BackgroundColor(ColorU { r: 241, g: 241, b: 241, a: 255 }),
-        triangle_color: BackgroundColor(ColorU { r: 163, g: 163, b: 163, a: 255 }),
-        bar_color: BackgroundColor(ColorU { r: 193, g: 193, b: 193, a: 255 }),
-    };
+    let scrollbar_width = style.scrollbar_width.unwrap_or_default();
+    let scrollbar_style = get_scrollbar_style(style, scrollbar_width);
+
+    let font_color = style.font_color.unwrap_or(DEFAULT_FONT_COLOR).0.into();
+    // TODO: pass `style.font_feature_settings` to webrender once font instances support OpenType features
+    let options = get_glyph_options(text_aa_mode);
+
+    let column_gap = style.column_gap.map(|g| g.0.to_pixels()).unwrap_or(DEFAULT_COLUMN_GAP);
+    let column_count = text_layout::get_column_count(
+        bounds.size.width,
+        style.column_count.map(|c| c.0),
+        style.column_width.map(|w| w.0.to_pixels()),
+        column_gap);
+
+    if column_count > 1 {
+        // Layout the text in one column that is high enough to hold the text
+        // of all columns, then distribute the lines across the columns
+        let column_bounds = text_layout::get_column_bounds(bounds, column_count, column_gap);
+        let mut single_column_bounds = column_bounds[0];
+        single_column_bounds.size.height *= column_count as f32;
+
+        let (positioned_glyphs, _) = text_layout::get_glyphs(
+            app_resources,
+            &single_column_bounds,
+            horz_alignment,
+            TextAlignmentVert::Top,
+            &font_id,
+            &font_size,
+            line_height,
+            style.letter_spacing,
+            style.word_spacing,
+            style.word_break.unwrap_or_default(),
+            style.overflow_wrap.unwrap_or_default(),
+            text,
+            &overflow_behaviour,
+            &scrollbar_style,
+            // the lines are moved into the columns afterwards, so they can't avoid the floats
+            &[]
+        );
+
+        let column_offset = column_bounds[0].size.width + column_gap;
+        let columns = text_layout::distribute_glyphs_into_columns(positioned_glyphs, column_count, column_offset);
+
+        if let (Some(char_rects), Some(text)) = (char_rects, text.get_text(app_resources)) {
+            let column_glyphs = columns.iter().flat_map(|c| c.iter().cloned()).collect::<Vec<GlyphInstance>>();
+            *char_rects = text_layout::get_char_rects(app_resources, &font_id, &font_size, &text, &column_glyphs);
+        }
+
+        let text_shadows_pushed = push_text_shadows(builder, &info, style, font_color);
+
+        for (column_glyphs, column_rect) in columns.iter().zip(column_bounds.iter()) {
+            let column_info = PrimitiveInfo {
+                rect: *column_rect,
+                clip_rect: *column_rect,
+                is_backface_visible: info.is_backface_visible,
+                tag: info.tag,
+            };
+            let text_lines = text_layout::get_text_decoration_lines(app_resources, &font_id, &font_size, column_glyphs);
+            push_text_decoration(builder, &column_info, style, font_color, &text_lines, TextDecorationLayer::BelowText);
+            builder.push_text(&column_info, column_glyphs, font_instance_key, font_color, Some(options));
+            push_text_decoration(builder, &column_info, style, font_color, &text_lines, TextDecorationLayer::AboveText);
+        }
+
+        if text_shadows_pushed {
+            builder.pop_all_shadows();
+        }
+
+        if let Some(column_rule) = style.column_rule {
+            push_column_rules(builder, &info, &column_bounds, column_gap, &column_rule, font_color);
+        }
+
+        return;
+    }
 
     let (positioned_glyphs, scrollbar_info) = text_layout::get_glyphs(
         app_resources,
@@ -529,47 +1299,502 @@ fn push_text<'a>(
         &font_id,
         &font_size,
         line_height,
+        style.letter_spacing,
+        style.word_spacing,
+        style.word_break.unwrap_or_default(),
+        style.overflow_wrap.unwrap_or_default(),
         text,
         &overflow_behaviour,
-        &scrollbar_style
+        &scrollbar_style,
+        float_exclusions
     );
 
-    let font_color = style.font_color.unwrap_or(DEFAULT_FONT_COLOR).0.into();
-    let flags = FontInstanceFlags::SUBPIXEL_BGR;
-    let options = GlyphOptions {
-        render_mode: FontRenderMode::Subpixel,
-        flags: flags,
-    };
+    if let (Some(char_rects), Some(text)) = (char_rects, text.get_text(app_resources)) {
+        *char_rects = text_layout::get_char_rects(app_resources, &font_id, &font_size, &text, &positioned_glyphs);
+    }
+
+    let text_lines = text_layout::get_text_decoration_lines(app_resources, &font_id, &font_size, &positioned_glyphs);
 
+    let text_shadows_pushed = push_text_shadows(builder, &info, style, font_color);
+    push_text_decoration(builder, &info, style, font_color, &text_lines, TextDecorationLayer::BelowText);
     builder.push_text(&info, &positioned_glyphs, font_instance_key, font_color, Some(options));
+    push_text_decoration(builder, &info, style, font_color, &text_lines, TextDecorationLayer::AboveText);
+    if text_shadows_pushed {
+        builder.pop_all_shadows();
+    }
 
     // If the rectangle should have a scrollbar, push a scrollbar onto the display list
-    push_scrollbar(builder, &overflow_behaviour, &scrollbar_info, &scrollbar_style, bounds, &style.border)
+    if scrollbar_width != LayoutScrollbarWidth::None {
+        push_scrollbar(builder, &overflow_behaviour, &scrollbar_info, &scrollbar_style, bounds, &style.border)
+    }
 }
 
-/// Adds a scrollbar to the left or bottom side of a rectangle.
-/// TODO: make styling configurable (like the width / style of the scrollbar)
-fn push_scrollbar(
+/// Draws the `::marker` of a `list-style-position: outside` list item,
+/// right-aligned to the left of the content box of the node
+fn push_outside_list_marker(
     builder: &mut DisplayListBuilder,
-    display_behaviour: &LayoutOverflow,
-    scrollbar_info: &TextOverflowPass2,
-    scrollbar_style: &ScrollbarInfo,
-    bounds: &TypedRect<f32, LayoutPixel>,
-    border: &Option<(BorderWidths, BorderDetails)>)
+    info: &PrimitiveInfo<LayoutPixel>,
+    rect: &DisplayRectangle,
+    marker_text: &str,
+    app_resources: &mut AppResources,
+    render_api: &RenderApi,
+    resource_updates: &mut Vec<ResourceUpdate>,
+    text_aa_mode: AaMode)
 {
-    use euclid::TypedPoint2D;
+    let marker_width = rect.style.font_family.as_ref()
+        .and_then(|font_family| select_font(font_family, rect.style.font_display.unwrap_or_default(), app_resources))
+        .and_then(|font_id| ::text_layout::get_text_width(
+            app_resources,
+            &font_id,
+            &rect.style.font_size.unwrap_or(DEFAULT_FONT_SIZE),
+            marker_text));
+
+    let marker_width = match marker_width {
+        Some(w) => w,
+        None => return,
+    };
 
-    // The border is inside the rectangle - subtract the border width on the left and bottom side,
-    // so that the scrollbar is laid out correctly
-    let mut bounds = *bounds;
-    if let Some((border_widths, _)) = border {
-        bounds.size.width -= border_widths.left;
-        bounds.size.height -= border_widths.bottom;
-    }
+    let mut marker_bounds = info.rect;
+    marker_bounds.origin.x -= marker_width;
+    marker_bounds.size.width = marker_width;
+    let marker_info = LayoutPrimitiveInfo { rect: marker_bounds, clip_rect: marker_bounds, .. *info };
 
-    {
-        // Background of scrollbar (vertical)
-        let scrollbar_vertical_background = TypedRect::<f32, LayoutPixel> {
+    let mut marker_style = rect.style.clone();
+    marker_style.text_align = Some(TextAlignmentHorz::Right);
+
+    push_text(
+        &marker_info,
+        &TextInfo::Uncached(marker_text),
+        builder,
+        &marker_style,
+        app_resources,
+        render_api,
+        &marker_bounds,
+        resource_updates,
+        text_aa_mode,
+        None,
+        &[]);
+}
+
+/// Draws the `list-style-image` of a `list-style-position: outside` list item, to the left
+/// of the content box of the node. Returns `false` if the image isn't loaded (yet).
+fn push_outside_list_marker_image(
+    builder: &mut DisplayListBuilder,
+    info: &PrimitiveInfo<LayoutPixel>,
+    rect: &DisplayRectangle,
+    css_image_id: &CssImageId,
+    app_resources: &AppResources,
+    image_rendering: ImageRendering)
+-> bool
+{
+    use images::ImageState;
+
+    let image_id = match app_resources.css_ids_to_image_ids.get(&css_image_id.0) {
+        Some(id) => id,
+        None => return false,
+    };
+
+    let image_size = match app_resources.images.get(image_id) {
+        Some(ImageState::Uploaded(image_info)) => TypedSize2D::new(image_info.descriptor.width as f32, image_info.descriptor.height as f32),
+        _ => return false,
+    };
+
+    let marker_bounds = match get_list_marker_image_bounds(&info.rect, image_size, get_inline_line_height(&rect.style)) {
+        Some(b) => b,
+        None => return false,
+    };
+    let marker_info = LayoutPrimitiveInfo { rect: marker_bounds, clip_rect: marker_bounds, .. *info };
+
+    push_image(&marker_info, builder, &marker_bounds, app_resources, image_id, StyleObjectFit::Fill, StyleObjectPosition::default(), image_rendering);
+    true
+}
+
+/// Scales the marker image to the `line_height` (keeping the aspect ratio) and places it in
+/// front of the first line of the `content_box`, with a gap of a quarter of the line height.
+/// Returns `None` for empty images.
+fn get_list_marker_image_bounds(content_box: &LayoutRect, image_size: TypedSize2D<f32, LayoutPixel>, line_height: f32)
+-> Option<LayoutRect>
+{
+    if image_size.width <= 0.0 || image_size.height <= 0.0 {
+        return None;
+    }
+    let width = image_size.width * line_height / image_size.height;
+    let gap = line_height / 4.0;
+    Some(LayoutRect::new(
+        LayoutPoint::new(content_box.origin.x - gap - width, content_box.origin.y),
+        TypedSize2D::new(width, line_height)))
+}
+
+/// The render mode of the glyphs overrides the render mode of the font instance, so the font
+/// instances (which are shared by all windows) don't have to be rebuilt when the mode changes
+fn get_glyph_options(text_aa_mode: AaMode) -> GlyphOptions {
+    match text_aa_mode {
+        AaMode::SubPixel => GlyphOptions { render_mode: FontRenderMode::Subpixel, flags: FontInstanceFlags::SUBPIXEL_BGR },
+        AaMode::Grayscale => GlyphOptions { render_mode: FontRenderMode::Alpha, flags: FontInstanceFlags::empty() },
+        AaMode::None => GlyphOptions { render_mode: FontRenderMode::Mono, flags: FontInstanceFlags::empty() },
+    }
+}
+
+/// Pushes the `text-shadow`s of the node. All text that is pushed afterwards is drawn with
+/// these shadows (same glyphs, offset, colored and blurred by webrender) until
+/// `builder.pop_all_shadows()` is called. Returns whether any shadows were pushed.
+fn push_text_shadows(
+    builder: &mut DisplayListBuilder,
+    info: &PrimitiveInfo<LayoutPixel>,
+    style: &RectStyle,
+    font_color: ColorF)
+-> bool
+{
+    let text_shadow = match style.text_shadow {
+        Some(ref text_shadow) if !text_shadow.0.is_empty() => text_shadow,
+        _ => return false,
+    };
+
+    // In CSS, the first shadow is drawn on top, in webrender the last pushed shadow is drawn on top
+    for shadow in text_shadow.0.iter().rev() {
+        builder.push_shadow(info, Shadow {
+            offset: shadow.offset,
+            // shadows without a color use the color of the text (`currentcolor`)
+            color: shadow.color.unwrap_or(font_color),
+            blur_radius: shadow.blur_radius,
+        });
+    }
+
+    true
+}
+
+/// In CSS, the underline and overline are drawn below the text, the line-through above the text
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum TextDecorationLayer {
+    BelowText,
+    AboveText,
+}
+
+/// Draws the `text-decoration` lines of the `layer` as rectangles.
+///
+/// `text_lines` are the lines of the text, as returned by `text_layout::get_text_decoration_lines`.
+fn push_text_decoration(
+    builder: &mut DisplayListBuilder,
+    info: &PrimitiveInfo<LayoutPixel>,
+    style: &RectStyle,
+    text_color: ColorF,
+    text_lines: &(Vec<TextLine>, TextDecorationMetrics),
+    layer: TextDecorationLayer)
+{
+    let (ref lines, ref metrics) = *text_lines;
+
+    let text_decoration = match style.text_decoration {
+        Some(ref d) => d,
+        None => return,
+    };
+
+    let decoration_line = text_decoration.line.unwrap_or_default();
+
+    let offsets = match layer {
+        TextDecorationLayer::BelowText => [
+            if decoration_line.underline { Some(metrics.underline_offset) } else { None },
+            if decoration_line.overline { Some(metrics.overline_offset) } else { None },
+        ],
+        TextDecorationLayer::AboveText => [
+            if decoration_line.line_through { Some(metrics.line_through_offset) } else { None },
+            None,
+        ],
+    };
+
+    let decoration_style = text_decoration.style.unwrap_or_default();
+    let thickness = text_decoration.thickness.map(|t| t.to_pixels()).unwrap_or(metrics.thickness);
+    let color = BackgroundColor(text_decoration.color.unwrap_or(text_color.into()));
+
+    for offset in offsets.iter().filter_map(|o| *o) {
+        for line in lines {
+            let center_y = line.baseline + offset;
+            for rect in get_text_decoration_rects(line.start_x, line.end_x, center_y, thickness, decoration_style) {
+                let decoration_info = PrimitiveInfo {
+                    rect: rect,
+                    clip_rect: info.clip_rect,
+                    is_backface_visible: info.is_backface_visible,
+                    tag: None,
+                };
+                push_rect(&decoration_info, builder, &color);
+            }
+        }
+    }
+}
+
+/// Returns the rectangles that make up a decoration line from `start_x` to `end_x`,
+/// vertically centered around `center_y`.
+///
+/// Dotted and dashed lines are split into squares / dashes, the curve of wavy lines
+/// is tessellated into small rectangles (one per `WAVY_SEGMENT_WIDTH`).
+fn get_text_decoration_rects(start_x: f32, end_x: f32, center_y: f32, thickness: f32, decoration_style: TextDecorationStyle)
+-> Vec<TypedRect<f32, LayoutPixel>>
+{
+    use euclid::TypedPoint2D;
+    use std::f32::consts::PI;
+
+    const WAVY_SEGMENT_WIDTH: f32 = 1.0;
+
+    let line_rect = |x: f32, y: f32, width: f32, height: f32| TypedRect::new(
+        TypedPoint2D::new(x, y - (height / 2.0)),
+        TypedSize2D::new(width, height));
+
+    let width = end_x - start_x;
+    if width <= 0.0 || thickness <= 0.0 {
+        return Vec::new();
+    }
+
+    // Repeats a segment of `segment_width` with a gap of `gap_width`, the last segment is cut off
+    let repeat = |segment_width: f32, gap_width: f32| {
+        let mut rects = Vec::new();
+        let mut x = start_x;
+        while x < end_x {
+            rects.push(line_rect(x, center_y, segment_width.min(end_x - x), thickness));
+            x += segment_width + gap_width;
+        }
+        rects
+    };
+
+    match decoration_style {
+        TextDecorationStyle::Solid => vec![line_rect(start_x, center_y, width, thickness)],
+        TextDecorationStyle::Double => vec![
+            line_rect(start_x, center_y - thickness, width, thickness),
+            line_rect(start_x, center_y + thickness, width, thickness),
+        ],
+        TextDecorationStyle::Dotted => repeat(thickness, thickness),
+        TextDecorationStyle::Dashed => repeat(thickness * 3.0, thickness * 2.0),
+        TextDecorationStyle::Wavy => {
+            let amplitude = thickness;
+            let wavelength = thickness * 6.0;
+            let wave_y = |x: f32| center_y + amplitude * ((x - start_x) * 2.0 * PI / wavelength).sin();
+
+            let mut rects = Vec::new();
+            let mut x = start_x;
+            while x < end_x {
+                let segment_width = WAVY_SEGMENT_WIDTH.min(end_x - x);
+                let (y_start, y_end) = (wave_y(x), wave_y(x + segment_width));
+                // the segment has to cover the slope of the curve, otherwise the steep parts get thinner
+                let segment_height = thickness + (y_end - y_start).abs();
+                rects.push(line_rect(x, (y_start + y_end) / 2.0, segment_width, segment_height));
+                x += segment_width;
+            }
+            rects
+        },
+    }
+}
+
+/// Returns the size and colors of the scrollbar, taking the `scrollbar-width` property
+/// and the `::-azul-scrollbar-thumb` / `::-azul-scrollbar-track` rules into account
+fn get_scrollbar_style(style: &RectStyle, scrollbar_width: LayoutScrollbarWidth) -> ScrollbarInfo {
+    let (width, padding) = match scrollbar_width {
+        LayoutScrollbarWidth::Auto => (17, 2),
+        LayoutScrollbarWidth::Thin => (8, 1),
+        // no space is reserved for the scrollbar, the content can still be scrolled
+        LayoutScrollbarWidth::None => (0, 0),
+    };
+
+    let thumb_color = style.scrollbar_thumb_color.map(|c| c.0).unwrap_or(ColorU { r: 193, g: 193, b: 193, a: 255 });
+    let track_color = style.scrollbar_track_color.map(|c| c.0).unwrap_or(ColorU { r: 241, g: 241, b: 241, a: 255 });
+
+    ScrollbarInfo {
+        width: width,
+        padding: padding,
+        background_color: BackgroundColor(track_color),
+        triangle_color: BackgroundColor(ColorU { r: 163, g: 163, b: 163, a: 255 }),
+        bar_color: BackgroundColor(thumb_color),
+    }
+}
+
+/// Width and height of the drag handle of a rectangle with `resize` set
+pub(crate) const RESIZE_HANDLE_SIZE: f32 = 10.0;
+
+/// Draws the drag handle in the bottom right corner of a rectangle with `resize` set
+fn push_resize_handle(
+    builder: &mut DisplayListBuilder,
+    bounds: &TypedRect<f32, LayoutPixel>)
+{
+    use euclid::TypedPoint2D;
+
+    const RESIZE_HANDLE_COLOR: BackgroundColor = BackgroundColor(ColorU { r: 163, g: 163, b: 163, a: 255 });
+
+    let handle_rect = TypedRect::<f32, LayoutPixel> {
+        origin: TypedPoint2D::new(
+            bounds.origin.x + bounds.size.width - RESIZE_HANDLE_SIZE,
+            bounds.origin.y + bounds.size.height - RESIZE_HANDLE_SIZE),
+        size: TypedSize2D::new(RESIZE_HANDLE_SIZE, RESIZE_HANDLE_SIZE),
+    };
+
+    push_triangle(&handle_rect, builder, &RESIZE_HANDLE_COLOR, TriangleDirection::PointUp);
+}
+
+/// Computing the bounds of the characters is expensive, so they are only computed for the text
+/// nodes that need them: the focused node (for the text cursor), nodes with `user-select: text`
+/// or `all` and the nodes that were queried before, see `FakeWindow::get_char_bounds()`.
+fn needs_char_rects(node_id: NodeId, style: &RectStyle, is_focused: bool, char_rect_queries: &FastHashSet<NodeId>) -> bool {
+    match style.user_select {
+        Some(StyleUserSelect::Text) | Some(StyleUserSelect::All) => true,
+        _ => is_focused || char_rect_queries.contains(&node_id),
+    }
+}
+
+/// Returns the bounds of the "glyph" behind the last character of the text (with the width
+/// of the last character), where the text cursor is drawn. For an empty text, the cursor
+/// is at the top left corner of the `bounds`.
+fn get_text_cursor_bounds(
+    char_rects: &[LayoutRect],
+    style: &RectStyle,
+    bounds: &TypedRect<f32, LayoutPixel>)
+-> TypedRect<f32, LayoutPixel>
+{
+    use euclid::TypedPoint2D;
+
+    match char_rects.last() {
+        Some(last_char) => TypedRect::new(
+            TypedPoint2D::new(last_char.max_x(), last_char.origin.y),
+            last_char.size),
+        None => {
+            let font_size = style.font_size.unwrap_or(DEFAULT_FONT_SIZE).0.to_pixels();
+            TypedRect::new(bounds.origin, TypedSize2D::new(font_size / 2.0, font_size))
+        },
+    }
+}
+
+/// Pushes the text cursor for a text input, in front of the glyph at `glyph_bounds`.
+///
+/// The color and shape of the cursor are taken from the `caret-color` and
+/// `caret-shape` properties (defaulting to a black bar).
+fn push_text_cursor(
+    builder: &mut DisplayListBuilder,
+    style: &RectStyle,
+    glyph_bounds: &TypedRect<f32, LayoutPixel>,
+    clip_rect: &TypedRect<f32, LayoutPixel>)
+{
+    use euclid::TypedPoint2D;
+
+    const CARET_THICKNESS: f32 = 1.0;
+
+    let caret_color = style.caret_color.unwrap_or(CaretColor(DEFAULT_FONT_COLOR.0));
+
+    let caret_rect = match style.caret_shape.unwrap_or_default() {
+        CaretShape::Bar => TypedRect::new(
+            glyph_bounds.origin,
+            TypedSize2D::new(CARET_THICKNESS, glyph_bounds.size.height)),
+        CaretShape::Block => *glyph_bounds,
+        CaretShape::Underscore => TypedRect::new(
+            TypedPoint2D::new(glyph_bounds.origin.x, glyph_bounds.origin.y + glyph_bounds.size.height - CARET_THICKNESS),
+            TypedSize2D::new(glyph_bounds.size.width, CARET_THICKNESS)),
+    };
+
+    let caret_info = PrimitiveInfo {
+        rect: caret_rect,
+        clip_rect: *clip_rect,
+        is_backface_visible: false,
+        tag: None,
+    };
+
+    push_rect(&caret_info, builder, &BackgroundColor(caret_color.0));
+}
+
+/// Draws the `column-rule` in the middle of each gap between two text columns.
+/// The rule doesn't take up any space, so it can overlap the text if it's wider than the gap.
+fn push_column_rules(
+    builder: &mut DisplayListBuilder,
+    info: &PrimitiveInfo<LayoutPixel>,
+    column_bounds: &[TypedRect<f32, LayoutPixel>],
+    column_gap: f32,
+    column_rule: &StyleColumnRule,
+    text_color: ColorF)
+{
+    use euclid::TypedPoint2D;
+
+    let rule_style = column_rule.style.unwrap_or(BorderStyle::None);
+    if rule_style == BorderStyle::None || rule_style == BorderStyle::Hidden {
+        return;
+    }
+
+    let rule_width = column_rule.width.map(|w| w.to_pixels()).unwrap_or(DEFAULT_COLUMN_RULE_WIDTH);
+    let rule_color = column_rule.color.map(|c| c.into()).unwrap_or(text_color);
+
+    let no_side = BorderSide { color: rule_color, style: BorderStyle::None };
+    let rule_side = BorderSide { color: rule_color, style: rule_style };
+
+    for column in column_bounds.iter().take(column_bounds.len().saturating_sub(1)) {
+        let rule_x = column.origin.x + column.size.width + (column_gap / 2.0) - (rule_width / 2.0);
+        let rule_rect = TypedRect::new(
+            TypedPoint2D::new(rule_x, column.origin.y),
+            TypedSize2D::new(rule_width, column.size.height));
+
+        let rule_info = PrimitiveInfo {
+            rect: rule_rect,
+            clip_rect: rule_rect,
+            is_backface_visible: info.is_backface_visible,
+            tag: info.tag,
+        };
+
+        let border_widths = BorderWidths { top: 0.0, right: 0.0, bottom: 0.0, left: rule_width };
+        let border_details = BorderDetails::Normal(NormalBorder {
+            left: rule_side,
+            right: no_side,
+            top: no_side,
+            bottom: no_side,
+            radius: BorderRadius::zero(),
+        });
+
+        builder.push_border(&rule_info, border_widths, border_details);
+    }
+}
+
+/// Adds the scrollbars to the right and / or bottom side of a rectangle, depending on the
+/// `overflow-x` / `overflow-y` and whether the text is overflowing the rectangle.
+/// The size and colors of the scrollbar are determined by `get_scrollbar_style()`
+fn push_scrollbar(
+    builder: &mut DisplayListBuilder,
+    display_behaviour: &LayoutOverflow,
+    scrollbar_info: &TextOverflowPass2,
+    scrollbar_style: &ScrollbarInfo,
+    bounds: &TypedRect<f32, LayoutPixel>,
+    border: &Option<(BorderWidths, BorderDetails)>)
+{
+    // The border is inside the rectangle - subtract the border width on the left and bottom side,
+    // so that the scrollbar is laid out correctly
+    let mut bounds = *bounds;
+    if let Some((border_widths, _)) = border {
+        bounds.size.width -= border_widths.left;
+        bounds.size.height -= border_widths.bottom;
+    }
+
+    let shows_vertical_scrollbar = display_behaviour.shows_vertical_scrollbar(scrollbar_info.vertical.is_overflowing());
+    let shows_horizontal_scrollbar = display_behaviour.shows_horizontal_scrollbar(scrollbar_info.horizontal.is_overflowing());
+
+    // If both scrollbars are shown, they don't overlap in the bottom right corner
+    if shows_vertical_scrollbar {
+        let mut vertical_bounds = bounds;
+        if shows_horizontal_scrollbar {
+            vertical_bounds.size.height -= scrollbar_style.width as f32;
+        }
+        push_vertical_scrollbar(builder, scrollbar_style, &vertical_bounds);
+    }
+
+    if shows_horizontal_scrollbar {
+        let mut horizontal_bounds = bounds;
+        if shows_vertical_scrollbar {
+            horizontal_bounds.size.width -= scrollbar_style.width as f32;
+        }
+        push_horizontal_scrollbar(builder, scrollbar_style, &horizontal_bounds);
+    }
+}
+
+fn push_vertical_scrollbar(
+    builder: &mut DisplayListBuilder,
+    scrollbar_style: &ScrollbarInfo,
+    bounds: &TypedRect<f32, LayoutPixel>)
+{
+    use euclid::TypedPoint2D;
+
+    let bounds = *bounds;
+
+    {
+        // Background of scrollbar (vertical)
+        let scrollbar_vertical_background = TypedRect::<f32, LayoutPixel> {
             origin: TypedPoint2D::new(bounds.origin.x + bounds.size.width - scrollbar_style.width as f32, bounds.origin.y),
             size: TypedSize2D::new(scrollbar_style.width as f32, bounds.size.height),
         };
@@ -629,6 +1854,77 @@ fn push_scrollbar(
     }
 }
 
+fn push_horizontal_scrollbar(
+    builder: &mut DisplayListBuilder,
+    scrollbar_style: &ScrollbarInfo,
+    bounds: &TypedRect<f32, LayoutPixel>)
+{
+    use euclid::TypedPoint2D;
+
+    let bounds = *bounds;
+
+    {
+        // Background of scrollbar (horizontal)
+        let scrollbar_horizontal_background = TypedRect::<f32, LayoutPixel> {
+            origin: TypedPoint2D::new(bounds.origin.x, bounds.origin.y + bounds.size.height - scrollbar_style.width as f32),
+            size: TypedSize2D::new(bounds.size.width, scrollbar_style.width as f32),
+        };
+
+        let scrollbar_horizontal_background_info = PrimitiveInfo {
+            rect: scrollbar_horizontal_background,
+            clip_rect: bounds,
+            is_backface_visible: false,
+            tag: None, // TODO: for hit testing
+        };
+
+        push_rect(&scrollbar_horizontal_background_info, builder, &scrollbar_style.background_color);
+    }
+
+    {
+        // Actual scroll bar
+        let scrollbar_horizontal_bar = TypedRect::<f32, LayoutPixel> {
+            origin: TypedPoint2D::new(
+                bounds.origin.x + scrollbar_style.width as f32,
+                bounds.origin.y + bounds.size.height - scrollbar_style.width as f32 + scrollbar_style.padding as f32),
+            size: TypedSize2D::new(
+                bounds.size.width - (scrollbar_style.width * 2) as f32,
+                (scrollbar_style.width - (scrollbar_style.padding * 2)) as f32),
+        };
+
+        let scrollbar_horizontal_bar_info = PrimitiveInfo {
+            rect: scrollbar_horizontal_bar,
+            clip_rect: bounds,
+            is_backface_visible: false,
+            tag: None, // TODO: for hit testing
+        };
+
+        push_rect(&scrollbar_horizontal_bar_info, builder, &scrollbar_style.bar_color);
+    }
+
+    {
+        // Triangle left
+        let mut scrollbar_triangle_rect = TypedRect::<f32, LayoutPixel> {
+            origin: TypedPoint2D::new(
+                bounds.origin.x + scrollbar_style.padding as f32,
+                bounds.origin.y + bounds.size.height - scrollbar_style.width as f32 + scrollbar_style.padding as f32),
+            size: TypedSize2D::new(
+                (scrollbar_style.width - (scrollbar_style.padding * 2)) as f32,
+                (scrollbar_style.width - (scrollbar_style.padding * 2)) as f32),
+        };
+
+        scrollbar_triangle_rect.origin.x += scrollbar_triangle_rect.size.width / 4.0;
+        scrollbar_triangle_rect.origin.y += scrollbar_triangle_rect.size.height / 4.0;
+        scrollbar_triangle_rect.size.width /= 2.0;
+        scrollbar_triangle_rect.size.height /= 2.0;
+
+        push_triangle(&scrollbar_triangle_rect, builder, &scrollbar_style.triangle_color, TriangleDirection::PointLeft);
+
+        // Triangle right
+        scrollbar_triangle_rect.origin.x += bounds.size.width - scrollbar_style.width as f32 + scrollbar_style.padding as f32;
+        push_triangle(&scrollbar_triangle_rect, builder, &scrollbar_style.triangle_color, TriangleDirection::PointRight);
+    }
+}
+
 enum TriangleDirection {
     PointUp,
     PointDown,
@@ -737,13 +2033,43 @@ fn push_box_shadow(
                              border_radius, pre_shadow.clip_mode);
 }
 
+/// Returns the area of the node that the background is drawn in, depending on the
+/// `background-clip`. Percentages of the padding are resolved against the width of
+/// the containing block.
+fn get_background_clip_rect(
+    bounds: &LayoutRect,
+    containing_block_width: f32,
+    background_clip: StyleBackgroundClip,
+    border: Option<&BorderWidths>,
+    padding: Option<&LayoutPadding>)
+-> LayoutRect
+{
+    let (mut top, mut right, mut bottom, mut left) = match (background_clip, border) {
+        (StyleBackgroundClip::BorderBox, _) | (_, None) => (0.0, 0.0, 0.0, 0.0),
+        (_, Some(border)) => (border.top, border.right, border.bottom, border.left),
+    };
+
+    if let (StyleBackgroundClip::ContentBox, Some(padding)) = (background_clip, padding) {
+        let to_pixels = |side: Option<LayoutPaddingValue>| side.map(|p| p.to_pixels(containing_block_width)).unwrap_or(0.0);
+        top += to_pixels(padding.top);
+        right += to_pixels(padding.right);
+        bottom += to_pixels(padding.bottom);
+        left += to_pixels(padding.left);
+    }
+
+    LayoutRect::new(
+        LayoutPoint::new(bounds.origin.x + left, bounds.origin.y + top),
+        LayoutSize::new((bounds.size.width - left - right).max(0.0), (bounds.size.height - top - bottom).max(0.0)))
+}
+
 #[inline]
 fn push_background(
     info: &PrimitiveInfo<LayoutPixel>,
     bounds: &TypedRect<f32, LayoutPixel>,
     builder: &mut DisplayListBuilder,
     background: &Background,
-    app_resources: &AppResources)
+    app_resources: &AppResources,
+    image_rendering: ImageRendering)
 {
     match background {
         Background::RadialGradient(gradient) => {
@@ -769,34 +2095,228 @@ fn push_background(
         },
         Background::Image(css_image_id) => {
             if let Some(image_id) = app_resources.css_ids_to_image_ids.get(&css_image_id.0) {
-                push_image(info, builder, bounds, app_resources, image_id);
+                push_image(info, builder, bounds, app_resources, image_id, StyleObjectFit::Fill, StyleObjectPosition::default(), image_rendering);
             }
         }
     }
 }
 
-fn push_image(
-    info: &PrimitiveInfo<LayoutPixel>,
-    builder: &mut DisplayListBuilder,
+fn push_image(
+    info: &PrimitiveInfo<LayoutPixel>,
+    builder: &mut DisplayListBuilder,
+    bounds: &TypedRect<f32, LayoutPixel>,
+    app_resources: &AppResources,
+    image_id: &ImageId,
+    object_fit: StyleObjectFit,
+    object_position: StyleObjectPosition,
+    image_rendering: ImageRendering)
+{
+    if let Some(image_info) = app_resources.images.get(image_id) {
+        use images::ImageState::*;
+        match image_info {
+            Uploaded(image_info) => {
+                // WebRender has no source rect for images, so instead of selecting a part
+                // of the texture, the whole image is positioned and clipped to the bounds
+                let image_size = TypedSize2D::new(image_info.descriptor.width as f32, image_info.descriptor.height as f32);
+                let image_rect = get_object_fit_rect(bounds, image_size, object_fit, object_position);
+                let image_info_rect = PrimitiveInfo {
+                    rect: image_rect,
+                    clip_rect: info.clip_rect.intersection(bounds).unwrap_or(*bounds),
+                    is_backface_visible: info.is_backface_visible,
+                    tag: info.tag,
+                };
+                builder.push_image(
+                        &image_info_rect,
+                        image_rect.size,
+                        LayoutSize::zero(),
+                        image_rendering,
+                        AlphaType::Alpha,
+                        image_info.key);
+            },
+            _ => { },
+        }
+    }
+}
+
+/// Draws the `border-image` as nine images (four corners, four edges and the center),
+/// the width of the slices in the layout is the `border` width (or the slice size,
+/// if the node has no border).
+///
+/// WebRender has no source rect for images, so each slice draws the whole image,
+/// scaled and offset so that only the slice is visible inside of its clip rect.
+/// Returns `false` if the image isn't loaded (yet), so that the border is drawn instead.
+///
+/// TODO: `repeat`, `round` and `space` are currently drawn like `stretch`
+fn push_border_image(
+    info: &PrimitiveInfo<LayoutPixel>,
+    builder: &mut DisplayListBuilder,
+    bounds: &TypedRect<f32, LayoutPixel>,
+    app_resources: &AppResources,
+    border_image: &StyleBorderImage,
+    style: &RectStyle,
+    image_rendering: ImageRendering)
+-> bool
+{
+    use euclid::TypedPoint2D;
+    use images::ImageState;
+
+    let image_info = match app_resources.css_ids_to_image_ids.get(&border_image.image.0)
+        .and_then(|image_id| app_resources.images.get(image_id))
+    {
+        Some(&ImageState::Uploaded(ref image_info)) => image_info,
+        _ => return false,
+    };
+
+    let image_width = image_info.descriptor.width as f32;
+    let image_height = image_info.descriptor.height as f32;
+
+    let slice_left = border_image.slice[3].min(image_width);
+    let slice_right = border_image.slice[1].min(image_width - slice_left);
+    let slice_top = border_image.slice[0].min(image_height);
+    let slice_bottom = border_image.slice[2].min(image_height - slice_top);
+
+    let (width_top, width_right, width_bottom, width_left) = match style.border {
+        Some((ref widths, _)) => (widths.top, widths.right, widths.bottom, widths.left),
+        None => (slice_top, slice_right, slice_bottom, slice_left),
+    };
+
+    let source_x = [0.0, slice_left, image_width - slice_right, image_width];
+    let source_y = [0.0, slice_top, image_height - slice_bottom, image_height];
+    let dest_x = [bounds.origin.x, bounds.origin.x + width_left, bounds.max_x() - width_right, bounds.max_x()];
+    let dest_y = [bounds.origin.y, bounds.origin.y + width_top, bounds.max_y() - width_bottom, bounds.max_y()];
+
+    for row in 0..3 {
+        for column in 0..3 {
+            if row == 1 && column == 1 && !border_image.fill {
+                continue;
+            }
+
+            let source_width = source_x[column + 1] - source_x[column];
+            let source_height = source_y[row + 1] - source_y[row];
+            let dest_width = dest_x[column + 1] - dest_x[column];
+            let dest_height = dest_y[row + 1] - dest_y[row];
+
+            if source_width <= 0.0 || source_height <= 0.0 || dest_width <= 0.0 || dest_height <= 0.0 {
+                continue;
+            }
+
+            let scale_x = dest_width / source_width;
+            let scale_y = dest_height / source_height;
+            let image_size = TypedSize2D::new(image_width * scale_x, image_height * scale_y);
+            let image_origin = TypedPoint2D::new(
+                dest_x[column] - source_x[column] * scale_x,
+                dest_y[row] - source_y[row] * scale_y);
+            let slice_rect = TypedRect::new(TypedPoint2D::new(dest_x[column], dest_y[row]), TypedSize2D::new(dest_width, dest_height));
+
+            let slice_info = PrimitiveInfo {
+                rect: TypedRect::new(image_origin, image_size),
+                clip_rect: match slice_rect.intersection(&info.clip_rect) {
+                    Some(r) => r,
+                    None => continue,
+                },
+                is_backface_visible: info.is_backface_visible,
+                tag: info.tag,
+            };
+
+            builder.push_image(
+                &slice_info,
+                image_size,
+                LayoutSize::zero(),
+                image_rendering,
+                AlphaType::Alpha,
+                image_info.key);
+        }
+    }
+
+    true
+}
+
+/// Returns whether the pixel of the image at the `point` isn't transparent, with the image
+/// placed in the `bounds` of the node according to the `object-fit` and `object-position`
+/// (for `pointer-events: painted`). Images that aren't loaded yet are transparent.
+pub(crate) fn is_image_painted_at(
+    app_resources: &AppResources,
+    image_id: &ImageId,
+    bounds: &LayoutRect,
+    object_fit: StyleObjectFit,
+    object_position: StyleObjectPosition,
+    point: LayoutPoint)
+-> bool
+{
+    use images::ImageState;
+
+    let image_info = match app_resources.images.get(image_id) {
+        Some(&ImageState::Uploaded(ref image_info)) => image_info,
+        _ => return false,
+    };
+
+    let image_size = TypedSize2D::new(image_info.descriptor.width as f32, image_info.descriptor.height as f32);
+    let image_rect = get_object_fit_rect(bounds, image_size, object_fit, object_position);
+
+    match get_image_pixel(&image_rect, image_size, point) {
+        Some((x, y)) => image_info.is_painted_at(x, y),
+        None => false,
+    }
+}
+
+/// Returns whether the pixel of the OpenGL texture (i.e. of an `Svg` widget) at the `point`
+/// isn't transparent, with the texture stretched to the `bounds` of the node
+/// (for `pointer-events: painted`)
+pub(crate) fn is_texture_painted_at(texture: &::dom::Texture, bounds: &LayoutRect, point: LayoutPoint) -> bool {
+    let texture_size = TypedSize2D::new(texture.inner.width() as f32, texture.inner.height() as f32);
+    let (x, y) = match get_image_pixel(bounds, texture_size, point) {
+        Some(pixel) => pixel,
+        None => return false,
+    };
+
+    // webrender draws the first row of the texture at the top of the node
+    let pixels: Vec<Vec<(u8, u8, u8, u8)>> = texture.inner.read();
+    pixels.get(y as usize).and_then(|row| row.get(x as usize)).map(|&(_, _, _, alpha)| alpha != 0).unwrap_or(false)
+}
+
+/// Returns which pixel of the image (of `image_size` pixels, drawn scaled to the `image_rect`)
+/// is at the `point`, `None` if the point is outside of the image
+fn get_image_pixel(image_rect: &LayoutRect, image_size: LayoutSize, point: LayoutPoint) -> Option<(u32, u32)> {
+    if !image_rect.contains(&point) || image_rect.size.width <= 0.0 || image_rect.size.height <= 0.0 {
+        return None;
+    }
+    let x = (point.x - image_rect.origin.x) * image_size.width / image_rect.size.width;
+    let y = (point.y - image_rect.origin.y) * image_size.height / image_rect.size.height;
+    Some((x as u32, y as u32))
+}
+
+/// Calculates where an image of `image_size` has to be drawn, so that it fits
+/// into the `bounds` according to the `object-fit` and `object-position`
+fn get_object_fit_rect(
     bounds: &TypedRect<f32, LayoutPixel>,
-    app_resources: &AppResources,
-    image_id: &ImageId)
+    image_size: TypedSize2D<f32, LayoutPixel>,
+    object_fit: StyleObjectFit,
+    object_position: StyleObjectPosition)
+-> TypedRect<f32, LayoutPixel>
 {
-    if let Some(image_info) = app_resources.images.get(image_id) {
-        use images::ImageState::*;
-        match image_info {
-            Uploaded(image_info) => {
-                builder.push_image(
-                        &info,
-                        bounds.size,
-                        LayoutSize::zero(),
-                        ImageRendering::Auto,
-                        AlphaType::Alpha,
-                        image_info.key);
-            },
-            _ => { },
-        }
+    use euclid::TypedPoint2D;
+
+    if image_size.width <= 0.0 || image_size.height <= 0.0 {
+        return *bounds;
     }
+
+    let contain_scale = (bounds.size.width / image_size.width).min(bounds.size.height / image_size.height);
+    let cover_scale = (bounds.size.width / image_size.width).max(bounds.size.height / image_size.height);
+
+    let scale = match object_fit {
+        StyleObjectFit::Fill => return *bounds,
+        StyleObjectFit::Contain => contain_scale,
+        StyleObjectFit::Cover => cover_scale,
+        StyleObjectFit::None => 1.0,
+        StyleObjectFit::ScaleDown => contain_scale.min(1.0),
+    };
+
+    let size = TypedSize2D::new(image_size.width * scale, image_size.height * scale);
+    let origin = TypedPoint2D::new(
+        bounds.origin.x + (bounds.size.width - size.width) * object_position.horizontal,
+        bounds.origin.y + (bounds.size.height - size.height) * object_position.vertical);
+
+    TypedRect::new(origin, size)
 }
 
 #[inline]
@@ -827,7 +2347,7 @@ fn push_font(
     use font::FontState;
 
     if font_size_app_units < MIN_AU || font_size_app_units > MAX_AU {
-        eprintln!("warning: too big or too small font size");
+        warn!("too big or too small font size: {:?}", font_size_app_units);
         return None;
     }
 
@@ -860,7 +2380,7 @@ fn push_font(
             Some(*font_instance_key)
         },
         _ => {
-            eprintln!("warning: trying to use font {:?} that isn't available", font_id);
+            warn!("trying to use font {:?} that isn't available", font_id);
             None
         },
     }
@@ -880,6 +2400,12 @@ fn populate_css_properties(rect: &mut DisplayRectangle, css_overrides: &FastHash
             Background(b)               => { rect.style.background = Some(b.clone());               },
             FontSize(f)                 => { rect.style.font_size = Some(*f);                       },
             FontFamily(f)               => { rect.style.font_family = Some(f.clone());              },
+            IconFont(f)                 => { rect.style.icon_font = Some(f.clone());                },
+            FontDisplay(d)              => { rect.style.font_display = Some(*d);                    },
+            ListStyleType(l)            => { rect.style.list_style_type = Some(l.clone());          },
+            ListStylePosition(l)        => { rect.style.list_style_position = Some(*l);             },
+            ListStyleImage(l)           => { rect.style.list_style_image = Some(l.clone());         },
+            FontFeatureSettings(f)      => { rect.style.font_feature_settings = Some(f.clone());    },
             Overflow(o)                 => {
                 if let Some(ref mut existing_overflow) = rect.style.overflow {
                     existing_overflow.merge(o);
@@ -890,6 +2416,54 @@ fn populate_css_properties(rect: &mut DisplayRectangle, css_overrides: &FastHash
             TextAlign(ta)               => { rect.style.text_align = Some(*ta);                     },
             BoxShadow(opt_box_shadow)   => { rect.style.box_shadow = *opt_box_shadow;               },
             LineHeight(lh)              => { rect.style.line_height = Some(*lh);                     },
+            LetterSpacing(l)            => { rect.style.letter_spacing = Some(*l);                  },
+            WordBreak(w)                => { rect.style.word_break = Some(*w);                      },
+            OverflowWrap(o)             => { rect.style.overflow_wrap = Some(*o);                   },
+            TextShadow(t)               => { rect.style.text_shadow = Some(t.clone());              },
+            WordSpacing(w)              => { rect.style.word_spacing = Some(*w);                    },
+            Filter(f)                   => { rect.style.filter = Some(f.clone());                   },
+            BackdropFilter(f)           => { rect.style.backdrop_filter = Some(f.clone());          },
+            Opacity(o)                  => { rect.style.opacity = Some(*o);                         },
+            Visibility(v)               => { rect.style.visibility = Some(*v);                      },
+            WillChange(w)               => { rect.style.will_change = Some(*w);                     },
+            Isolation(i)                => { rect.style.isolation = Some(*i);                       },
+            MixBlendMode(m)             => { rect.style.mix_blend_mode = Some(*m);                  },
+            ScrollBehavior(b)           => { rect.style.scroll_behavior = Some(*b);                 },
+            ScrollSnapType(t)           => { rect.style.scroll_snap_type = Some(*t);                },
+            ScrollSnapAlign(a)          => { rect.style.scroll_snap_align = Some(*a);               },
+            CaretColor(c)               => { rect.style.caret_color = Some(*c);                     },
+            CaretShape(c)               => { rect.style.caret_shape = Some(*c);                     },
+            UserSelect(u)               => { rect.style.user_select = Some(*u);                     },
+            Appearance(a)               => { rect.style.appearance = Some(*a);                      },
+            BackgroundClip(c)           => { rect.style.background_clip = Some(*c);                 },
+            ColumnCount(c)              => { rect.style.column_count = Some(*c);                    },
+            ColumnWidth(w)              => { rect.style.column_width = Some(*w);                    },
+            ColumnGap(g)                => { rect.style.column_gap = Some(*g);                      },
+            ObjectFit(f)                => { rect.style.object_fit = Some(*f);                      },
+            ObjectPosition(p)           => { rect.style.object_position = Some(*p);                 },
+            ImageRendering(r)           => { rect.style.image_rendering = Some(*r);                 },
+            BorderImage(b)              => { rect.style.border_image = Some(b.clone());             },
+            CounterReset(c)             => { rect.style.counter_reset = Some(c.clone());            },
+            CounterIncrement(c)         => { rect.style.counter_increment = Some(c.clone());        },
+            BeforeContent(c)            => { rect.style.before_content = Some(c.clone());           },
+            VerticalAlign(v)            => { rect.style.vertical_align = Some(*v);                  },
+            ColumnRule(r)               => {
+                if let Some(ref mut existing_rule) = rect.style.column_rule {
+                    existing_rule.merge(r);
+                } else {
+                    rect.style.column_rule = Some(*r)
+                }
+            },
+            TextDecoration(d)           => {
+                if let Some(ref mut existing_decoration) = rect.style.text_decoration {
+                    existing_decoration.merge(d);
+                } else {
+                    rect.style.text_decoration = Some(*d)
+                }
+            },
+            ScrollbarThumbColor(c)      => { rect.style.scrollbar_thumb_color = Some(*c);           },
+            ScrollbarTrackColor(c)      => { rect.style.scrollbar_track_color = Some(*c);           },
+            ScrollbarWidth(w)           => { rect.style.scrollbar_width = Some(*w);                 },
 
             Width(w)                    => { rect.layout.width = Some(*w);                          },
             Height(h)                   => { rect.layout.height = Some(*h);                         },
@@ -903,6 +2477,39 @@ fn populate_css_properties(rect: &mut DisplayRectangle, css_overrides: &FastHash
             JustifyContent(j)           => { rect.layout.justify_content = Some(*j);                },
             AlignItems(a)               => { rect.layout.align_items = Some(*a);                    },
             AlignContent(a)             => { rect.layout.align_content = Some(*a);                  },
+            Resize(r)                   => { rect.layout.resize = Some(*r);                         },
+            WritingMode(w)              => { rect.layout.writing_mode = Some(*w);                   },
+            Direction(d)                => { rect.style.direction = Some(*d);                       },
+            SideBorder(b)               => { apply_border_side(rect, b);                            },
+            Margin(m)                   => {
+                if let Some(ref mut existing_margin) = rect.layout.margin {
+                    existing_margin.merge(m);
+                } else {
+                    rect.layout.margin = Some(*m)
+                }
+            },
+            Padding(p)                  => {
+                if let Some(ref mut existing_padding) = rect.layout.padding {
+                    existing_padding.merge(p);
+                } else {
+                    rect.layout.padding = Some(*p)
+                }
+            },
+            Display(d)                  => { rect.layout.display = Some(*d);                        },
+            Float(f)                    => { rect.layout.float = Some(*f);                          },
+            Clear(c)                    => { rect.layout.clear = Some(*c);                          },
+            TableLayout(t)              => { rect.layout.table_layout = Some(*t);                   },
+            ShapeOutside(s)             => { rect.layout.shape_outside = Some(s.clone());           },
+            // only used when scrolling, see `UiDescription::get_scroll_margin()`
+            ScrollMargin(_) |
+            ScrollPadding(_)            => { },
+            // only used for hit-testing, see `UiDescription::get_pointer_events()`
+            PointerEvents(_)            => { },
+            // logical properties are resolved after the cascade, see below
+            LogicalSize(_) |
+            LogicalBox(_)               => { },
+            // `all` is resolved before the other declarations, see below
+            All(_)                      => { },
         }
     }
 
@@ -912,21 +2519,84 @@ fn populate_css_properties(rect: &mut DisplayRectangle, css_overrides: &FastHash
         discriminant(a) == discriminant(b)
     }
 
-    for constraint in &rect.styled_node.css_constraints.list {
+    /// Sets the border of one side (`border-top`, etc.), the other sides keep their border
+    fn apply_border_side(rect: &mut DisplayRectangle, border_side: &css_parser::StyleBorderSide) {
+        use css_parser::BoxSide;
+
+        let no_border = BorderSide {
+            color: ColorU { r: 0, g: 0, b: 0, a: 255 }.into(),
+            style: BorderStyle::None,
+        };
+
+        let (mut widths, mut normal_border) = match rect.style.border {
+            Some((widths, BorderDetails::Normal(normal_border))) => (widths, normal_border),
+            _ => (BorderWidths { top: 0.0, right: 0.0, bottom: 0.0, left: 0.0 }, NormalBorder {
+                top: no_border,
+                left: no_border,
+                right: no_border,
+                bottom: no_border,
+                radius: ::webrender::api::BorderRadius::zero(),
+            }),
+        };
+
+        match border_side.side {
+            BoxSide::Top => { widths.top = border_side.width; normal_border.top = border_side.border; },
+            BoxSide::Right => { widths.right = border_side.width; normal_border.right = border_side.border; },
+            BoxSide::Bottom => { widths.bottom = border_side.width; normal_border.bottom = border_side.border; },
+            BoxSide::Left => { widths.left = border_side.width; normal_border.left = border_side.border; },
+        }
+
+        rect.style.border = Some((widths, BorderDetails::Normal(normal_border)));
+    }
+
+    // Logical properties depend on the final `writing-mode` and `direction`, so
+    // they can only be resolved once all other properties have been applied
+    let mut logical_properties = Vec::<css_parser::LogicalSize>::new();
+    let mut logical_box_properties = Vec::<css_parser::LogicalBoxProperty>::new();
+
+    let styled_node = rect.styled_node;
+    let properties = styled_node.css_constraints.list.iter().map(|(_, constraint)| {
         use css::CssDeclaration::*;
         match constraint {
-            Static(static_property) => apply_parsed_css_property(rect, static_property),
+            Static(static_property) => static_property,
             Dynamic(dynamic_property) => {
                 let calculated_property = css_overrides.get(&dynamic_property.dynamic_id);
                 if let Some(overridden_property) = calculated_property {
                     assert!(property_type_matches(overridden_property, &dynamic_property.default),
                             "css values don't have the same discriminant type");
-                    apply_parsed_css_property(rect, overridden_property);
+                    overridden_property
                 } else {
-                    apply_parsed_css_property(rect, &dynamic_property.default);
+                    &dynamic_property.default
                 }
             }
         }
+    }).collect::<Vec<&ParsedCssProperty>>();
+
+    // `all` resets every property, so only the declarations after the last `all` are applied.
+    // The parser moves `all` in front of its block, see `css::push_css_rule()`.
+    let last_all = properties.iter().rposition(|property| match property { All(_) => true, _ => false });
+    rect.css_all = last_all.and_then(|pos| match properties[pos] { All(a) => Some(*a), _ => None });
+    let first_applied = last_all.map(|pos| pos + 1).unwrap_or(0);
+
+    for property in &properties[first_applied..] {
+        let property = *property;
+        if let LogicalSize(logical_size) = property {
+            logical_properties.push(*logical_size);
+        }
+        if let LogicalBox(logical_box_property) = property {
+            logical_box_properties.push(*logical_box_property);
+        }
+
+        apply_parsed_css_property(rect, property);
+    }
+
+    let writing_mode = rect.layout.writing_mode.unwrap_or_default();
+    for logical_property in logical_properties {
+        apply_parsed_css_property(rect, &logical_property.to_physical(writing_mode));
+    }
+    let direction = rect.style.direction.unwrap_or_default();
+    for logical_box_property in logical_box_properties {
+        apply_parsed_css_property(rect, &logical_box_property.to_physical(writing_mode, direction));
     }
 }
 
@@ -948,11 +2618,507 @@ fn create_layout_constraints<'a>(
                          .unwrap_or(window_size.width as f32);
     */
     layout_constraints.push(CssConstraint::Size((SizeConstraint::Width(200.0), Strength(STRONG))));
-    layout_constraints.push(CssConstraint::Size((SizeConstraint::Height(200.0), Strength(STRONG))));
+    // the height of inline nodes is determined by the line-height, see `create_flow_constraints`
+    if rect.layout.display != Some(LayoutDisplay::Inline) {
+        layout_constraints.push(CssConstraint::Size((SizeConstraint::Height(200.0), Strength(STRONG))));
+    }
 
     layout_constraints
 }
 
+/// Positions a rectangle relative to its previous sibling, depending on the `display` of both.
+///
+/// Block nodes always start below their previous sibling. Inline nodes flow left-to-right
+/// after a preceding inline sibling and start at the left edge of the parent otherwise.
+/// The height of an inline node is the line height plus the vertical padding, the
+/// horizontal padding widens the inline box and pushes the next inline sibling to the right.
+/// Other boxes are at least as high as their vertical padding.
+///
+/// TODO: Wrapping an inline node onto the next line when the line is full can't be
+/// expressed as a linear constraint, this needs a line-breaking pass before the solver runs.
+///
+/// `parent_width` is the width of the parent in the last solved layout, for resolving the
+/// percentage paddings in the (constant) vertical offset of `vertical-align`.
+///
+/// `previous_sibling` is the previous sibling in the normal flow, floated siblings are
+/// in `floats`, see `create_float_constraints`.
+fn create_flow_constraints<'a>(
+    rect: &DisplayRectangle<'a>,
+    parent: &DisplayRect,
+    parent_width: f32,
+    previous_sibling: Option<(DisplayRect, &DisplayRectangle<'a>)>,
+    floats: &Floats<DisplayRect>,
+    app_resources: &AppResources)
+-> Vec<CssConstraint>
+{
+    use cassowary::strength::*;
+    use constraints::{PaddingConstraint, Strength, Padding};
+
+    let mut flow_constraints = Vec::<CssConstraint>::new();
+    let display = rect.layout.display.unwrap_or_default();
+    let padding = rect.layout.padding.unwrap_or_default();
+
+    flow_constraints.extend(create_float_constraints(rect, parent, previous_sibling.map(|(sibling, _)| sibling), floats));
+    if rect.layout.float.unwrap_or_default() != LayoutFloat::None {
+        return flow_constraints;
+    }
+
+    // percentage paddings refer to the width of the containing block (the parent),
+    // also the vertical padding
+    let (vertical_padding, width_factor) = padding.vertical_parts();
+    if display == LayoutDisplay::Inline {
+        let height = get_inline_line_height(&rect.style) + vertical_padding;
+        flow_constraints.push(CssConstraint::Padding((PaddingConstraint::HeightFromWidth(parent.width, width_factor), Strength(STRONG), Padding(height))));
+    } else {
+        // the height of other boxes comes from the layout constraints, but the box is at least as high as its padding
+        flow_constraints.push(CssConstraint::Padding((PaddingConstraint::MinHeightFromWidth(parent.width, width_factor), Strength(REQUIRED), Padding(vertical_padding))));
+    }
+
+    if display.is_inline_level() {
+        let (horizontal_padding, width_factor) = padding.horizontal_parts();
+        flow_constraints.push(CssConstraint::Padding((PaddingConstraint::MinWidthFromWidth(parent.width, width_factor), Strength(REQUIRED), Padding(horizontal_padding))));
+    }
+
+    match previous_sibling {
+        Some((sibling, sibling_rect)) if display.is_inline_level() && sibling_rect.layout.display.unwrap_or_default().is_inline_level() => {
+            // continue on the same line as the previous inline node
+            let vertical_align = rect.style.vertical_align.unwrap_or_default();
+            let offset_top = get_inline_vertical_offset(vertical_align,
+                get_inline_box_metrics(rect, parent_width, app_resources),
+                get_inline_box_metrics(sibling_rect, parent_width, app_resources));
+            flow_constraints.push(CssConstraint::Padding((PaddingConstraint::AlignToRightOf(sibling.right), Strength(STRONG), Padding(0.0))));
+            flow_constraints.push(CssConstraint::Padding((PaddingConstraint::AlignTop(sibling.top), Strength(STRONG), Padding(offset_top))));
+        },
+        Some((sibling, _)) => {
+            // start a new line / block below the previous sibling
+            flow_constraints.push(CssConstraint::Padding((PaddingConstraint::AlignBelow(sibling.bottom), Strength(STRONG), Padding(0.0))));
+            flow_constraints.push(CssConstraint::Padding((PaddingConstraint::AlignLeft(parent.left), Strength(MEDIUM), Padding(0.0))));
+        },
+        None => {
+            flow_constraints.push(CssConstraint::Padding((PaddingConstraint::AlignTop(parent.top), Strength(MEDIUM), Padding(0.0))));
+            flow_constraints.push(CssConstraint::Padding((PaddingConstraint::AlignLeft(parent.left), Strength(MEDIUM), Padding(0.0))));
+        }
+    }
+
+    flow_constraints
+}
+
+/// The floated siblings that affect the position of a node, see `get_floats`
+#[derive(Debug, Clone, PartialEq)]
+struct Floats<T> {
+    /// Preceding `float: left` siblings that haven't been cleared yet
+    left: Vec<T>,
+    /// Preceding `float: right` siblings that haven't been cleared yet
+    right: Vec<T>,
+    /// Preceding floats that the node has to be placed below, because of its own `clear`
+    cleared: Vec<T>,
+}
+
+impl<T> Floats<T> {
+    fn map<U, F: Fn(T) -> U>(self, f: F) -> Floats<U> {
+        Floats {
+            left: self.left.into_iter().map(&f).collect(),
+            right: self.right.into_iter().map(&f).collect(),
+            cleared: self.cleared.into_iter().map(&f).collect(),
+        }
+    }
+}
+
+/// Returns the `shape-outside` of the floated siblings of each text node, which the lines of
+/// the text flow around. The floats are positioned relative to the solved bounds of the node.
+fn get_float_exclusions<'a, T: Layout>(
+    rectangles: &Arena<DisplayRectangle<'a>>,
+    arena: &Arena<NodeData<T>>,
+    ui_solver: &UiSolver<T>)
+-> FastHashMap<NodeId, Vec<FloatExclusion>>
+{
+    let mut float_exclusions = FastHashMap::default();
+
+    for rect_id in rectangles.linear_iter() {
+        match arena[rect_id].data.node_type {
+            NodeType::Label(_) | NodeType::Text(_) => { },
+            _ => continue,
+        }
+
+        let (_, floats) = get_floats(rect_id, rectangles);
+        if floats.left.is_empty() && floats.right.is_empty() {
+            continue;
+        }
+
+        let node_origin = match ui_solver.query_bounds_of_rect(rect_id) {
+            Some(bounds) => bounds.origin.to_vector(),
+            None => continue,
+        };
+
+        let floated_siblings = floats.left.iter().map(|id| (*id, LayoutFloat::Left))
+            .chain(floats.right.iter().map(|id| (*id, LayoutFloat::Right)));
+
+        let exclusions = floated_siblings.filter_map(|(float_id, float)| {
+            let float_bounds = ui_solver.query_bounds_of_rect(float_id)?;
+            Some(FloatExclusion {
+                shape: rectangles[float_id].data.layout.shape_outside.clone().unwrap_or_default(),
+                reference_box: float_bounds.translate(&-node_origin),
+                float: float,
+            })
+        }).collect::<Vec<FloatExclusion>>();
+
+        float_exclusions.insert(rect_id, exclusions);
+    }
+
+    float_exclusions
+}
+
+/// Returns the top and bottom edges of the nodes that a page break shouldn't cut through
+/// (texts, i.e. paragraphs and headings, images and iframes) and the bottom edge of the whole
+/// content, in the last solved layout. Nodes that are hidden via `display: none` are skipped.
+pub(crate) fn get_unbreakable_boxes<'a, T: Layout>(
+    rectangles: &Arena<DisplayRectangle<'a>>,
+    arena: &Arena<NodeData<T>>,
+    ui_solver: &UiSolver<T>)
+-> (Vec<(f32, f32)>, f32)
+{
+    let mut unbreakable_boxes = Vec::new();
+    let mut content_height = 0.0_f32;
+
+    for rect_id in rectangles.linear_iter() {
+        let is_hidden = rect_id.ancestors(rectangles).any(|id| rectangles[id].data.layout.display == Some(LayoutDisplay::None));
+        if is_hidden {
+            continue;
+        }
+
+        let bounds = match ui_solver.query_bounds_of_rect(rect_id) {
+            Some(bounds) => bounds,
+            None => continue,
+        };
+
+        content_height = content_height.max(bounds.max_y());
+        match arena[rect_id].data.node_type {
+            NodeType::Div => { },
+            _ => unbreakable_boxes.push((bounds.origin.y, bounds.max_y())),
+        }
+    }
+
+    (unbreakable_boxes, content_height)
+}
+
+/// Returns the vertical offsets at which the pages start, when printing content that is
+/// `content_height` high onto pages with room for `page_height` pixels (without the margins).
+///
+/// When a page would end in the middle of one of the `unbreakable_boxes`, the page ends
+/// above the box instead, so the box starts on the next page. Only boxes that don't fit
+/// onto a page at all are cut.
+pub(crate) fn get_page_breaks(unbreakable_boxes: &[(f32, f32)], content_height: f32, page_height: f32) -> Vec<f32> {
+    let mut page_starts = vec![0.0];
+    if page_height <= 0.0 {
+        return page_starts;
+    }
+
+    let mut page_start = 0.0;
+    while page_start + page_height < content_height {
+        let page_end = page_start + page_height;
+        let page_break = unbreakable_boxes.iter()
+            .filter(|&&(top, bottom)| top > page_start && top < page_end && bottom > page_end)
+            .map(|&(top, _)| top)
+            .fold(page_end, f32::min);
+        page_starts.push(page_break);
+        page_start = page_break;
+    }
+
+    page_starts
+}
+
+/// Returns the previous sibling of the node that isn't floated and the float stack of
+/// the preceding siblings: floats stay in the stack until a sibling `clear`s their side.
+fn get_floats<'a>(rect_id: NodeId, arena: &Arena<DisplayRectangle<'a>>) -> (Option<NodeId>, Floats<NodeId>) {
+
+    let mut previous_in_flow = None;
+    let mut floats = Floats { left: Vec::new(), right: Vec::new(), cleared: Vec::new() };
+
+    // preceding_siblings() includes the node itself
+    let mut siblings = rect_id.preceding_siblings(arena).collect::<Vec<NodeId>>();
+    siblings.reverse();
+
+    for sibling in siblings {
+        let layout = &arena[sibling].data.layout;
+        let clear = layout.clear.unwrap_or_default();
+        if clear.clears_left() {
+            floats.cleared.extend(floats.left.drain(..));
+        }
+        if clear.clears_right() {
+            floats.cleared.extend(floats.right.drain(..));
+        }
+        if sibling == rect_id {
+            break;
+        }
+        // only the node itself has to be placed below the cleared floats
+        floats.cleared.clear();
+        match layout.float.unwrap_or_default() {
+            LayoutFloat::Left => floats.left.push(sibling),
+            LayoutFloat::Right => floats.right.push(sibling),
+            LayoutFloat::None => previous_in_flow = Some(sibling),
+        }
+    }
+
+    (previous_in_flow, floats)
+}
+
+/// A floated node is placed at the edge of its parent, next to the previous float of the
+/// same side, starting below the previous in-flow sibling. Other nodes are narrowed by
+/// the floats in the stack, so that they don't overlap the floats.
+///
+/// TODO: Narrowing should only happen while the node is next to the float, which can't be
+/// expressed as a linear constraint - a node is narrowed until a sibling `clear`s the float.
+fn create_float_constraints(
+    rect: &DisplayRectangle,
+    parent: &DisplayRect,
+    previous_in_flow: Option<DisplayRect>,
+    floats: &Floats<DisplayRect>)
+-> Vec<CssConstraint>
+{
+    use cassowary::strength::*;
+    use constraints::{PaddingConstraint, Strength, Padding};
+
+    let mut float_constraints = floats.cleared.iter().map(|cleared| {
+        CssConstraint::Padding((PaddingConstraint::Below(cleared.bottom), Strength(REQUIRED), Padding(0.0)))
+    }).collect::<Vec<CssConstraint>>();
+
+    let float = rect.layout.float.unwrap_or_default();
+    if float == LayoutFloat::None {
+        if let Some(left_float) = floats.left.last() {
+            float_constraints.push(CssConstraint::Padding((PaddingConstraint::ToRightOf(left_float.right), Strength(STRONG), Padding(0.0))));
+        }
+        if let Some(right_float) = floats.right.last() {
+            float_constraints.push(CssConstraint::Padding((PaddingConstraint::ToLeftOf(right_float.left), Strength(STRONG), Padding(0.0))));
+        }
+        return float_constraints;
+    }
+
+    let is_left = float == LayoutFloat::Left;
+    let previous_float = if is_left { floats.left.last() } else { floats.right.last() };
+
+    match previous_float {
+        Some(previous_float) => {
+            let next_to_float = if is_left {
+                PaddingConstraint::AlignToRightOf(previous_float.right)
+            } else {
+                PaddingConstraint::AlignToLeftOf(previous_float.left)
+            };
+            float_constraints.push(CssConstraint::Padding((next_to_float, Strength(STRONG), Padding(0.0))));
+            float_constraints.push(CssConstraint::Padding((PaddingConstraint::AlignTop(previous_float.top), Strength(STRONG), Padding(0.0))));
+        },
+        None => {
+            let parent_edge = if is_left {
+                PaddingConstraint::AlignLeft(parent.left)
+            } else {
+                PaddingConstraint::AlignRight(parent.right)
+            };
+            float_constraints.push(CssConstraint::Padding((parent_edge, Strength(STRONG), Padding(0.0))));
+            let top_constraint = match previous_in_flow {
+                Some(sibling) => PaddingConstraint::AlignBelow(sibling.bottom),
+                None => PaddingConstraint::AlignTop(parent.top),
+            };
+            float_constraints.push(CssConstraint::Padding((top_constraint, Strength(STRONG), Padding(0.0))));
+        }
+    }
+
+    float_constraints
+}
+
+/// If the node is a cell of a `table-layout: fixed` table (the parent is a row, the
+/// grandparent the table) but not in the first row, returns the cell in the first row
+/// that determines the width of its column. Only the first row is looked at, so the cost
+/// doesn't grow with the number of rows.
+fn get_fixed_table_column_cell<'a>(rect_id: NodeId, arena: &Arena<DisplayRectangle<'a>>) -> Option<NodeId> {
+    let row = arena[rect_id].parent()?;
+    let table = arena[row].parent()?;
+
+    if arena[table].data.layout.table_layout != Some(LayoutTableLayout::Fixed) {
+        return None;
+    }
+
+    let first_row = arena[table].first_child()?;
+    if first_row == row {
+        return None;
+    }
+
+    // preceding_siblings() includes the node itself
+    let column_index = rect_id.preceding_siblings(arena).count() - 1;
+    first_row.children(arena).nth(column_index)
+}
+
+/// The cell of a `table-layout: fixed` table has the same width and horizontal position
+/// as the cell of its column in the first row, see `get_fixed_table_column_cell`
+fn create_fixed_table_cell_constraints(column_cell: &DisplayRect) -> Vec<CssConstraint> {
+    use cassowary::strength::*;
+    use constraints::{PaddingConstraint, Strength, Padding};
+
+    vec![
+        CssConstraint::Padding((PaddingConstraint::MatchWidth(column_cell.width), Strength(STRONG), Padding(0.0))),
+        CssConstraint::Padding((PaddingConstraint::AlignLeft(column_cell.left), Strength(STRONG), Padding(0.0))),
+    ]
+}
+
+/// If the node is a cell of a table (the grandparent has a `table-layout`), returns the row
+fn get_table_row<'a>(rect_id: NodeId, arena: &Arena<DisplayRectangle<'a>>) -> Option<NodeId> {
+    let row = arena[rect_id].parent()?;
+    let table = arena[row].parent()?;
+    arena[table].data.layout.table_layout.and(Some(row))
+}
+
+/// The cells of a row are as high as the row (the highest cell), the text of cells
+/// that are shorter than their content is placed according to the `vertical-align`
+fn create_table_cell_height_constraints(row: &DisplayRect) -> Vec<CssConstraint> {
+    use cassowary::strength::*;
+    use constraints::{PaddingConstraint, Strength, Padding};
+
+    vec![
+        CssConstraint::Padding((PaddingConstraint::MatchHeight(row.height), Strength(STRONG), Padding(0.0))),
+        CssConstraint::Padding((PaddingConstraint::AlignTop(row.top), Strength(STRONG), Padding(0.0))),
+    ]
+}
+
+/// Height of a `display: inline` node and the distance from its top edge to the baseline
+/// of its text. Returns `None` for other nodes, since their height isn't known up front.
+/// Percentage paddings are resolved against the `containing_block_width`.
+fn get_inline_box_metrics<'a>(rect: &DisplayRectangle<'a>, containing_block_width: f32, app_resources: &AppResources) -> Option<(f32, f32)> {
+
+    if rect.layout.display.unwrap_or_default() != LayoutDisplay::Inline {
+        return None;
+    }
+
+    let font_size = rect.style.font_size.unwrap_or(DEFAULT_FONT_SIZE);
+    let font_id = rect.style.font_family.as_ref()
+        .and_then(|family| family.fonts.get(0))
+        .unwrap_or(&DEFAULT_BUILTIN_FONT_SANS_SERIF);
+    // fall back to the usual proportions of a font if it isn't loaded (yet)
+    let (ascent, descent) = ::text_layout::get_font_vertical_metrics(app_resources, font_id, &font_size)
+        .unwrap_or_else(|| (font_size.0.to_pixels() * 0.8, font_size.0.to_pixels() * -0.2));
+
+    let padding = rect.layout.padding.unwrap_or_default();
+    let line_height = get_inline_line_height(&rect.style);
+    // the extra space of the line height is distributed evenly above and below the glyphs
+    let half_leading = (line_height - (ascent - descent)) / 2.0;
+
+    let padding_top = padding.top.map(|p| p.to_pixels(containing_block_width)).unwrap_or(0.0);
+    Some((line_height + padding.vertical(containing_block_width), padding_top + half_leading + ascent))
+}
+
+/// Distance between the top edge of an inline node and the top edge of the previous inline
+/// node on the same line, as `(height, baseline)` from `get_inline_box_metrics`. If the
+/// metrics of one of the nodes are unknown, the top edges are aligned.
+fn get_inline_vertical_offset(vertical_align: StyleVerticalAlign, metrics: Option<(f32, f32)>, previous_metrics: Option<(f32, f32)>) -> f32 {
+    use css_parser::StyleVerticalAlign::*;
+
+    let ((height, baseline), (previous_height, previous_baseline)) = match (metrics, previous_metrics) {
+        (Some(m), Some(p)) => (m, p),
+        _ => return 0.0,
+    };
+
+    match vertical_align {
+        Top => 0.0,
+        Middle => (previous_height - height) / 2.0,
+        Bottom => previous_height - height,
+        Baseline => previous_baseline - baseline,
+    }
+}
+
+/// Returns the first font of the `font-family` that can be drawn: fonts that are still loading
+/// (see `AppState::add_font_from_file()`) are skipped or hide the text (returns `None`),
+/// depending on the `font-display`. Falls back to the builtin sans-serif font.
+fn select_font(font_family: &FontFamily, font_display: StyleFontDisplay, app_resources: &AppResources) -> Option<Font> {
+    use font::{FontDisplayChoice, get_font_display_choice};
+
+    for font in &font_family.fonts {
+        let id = match *font {
+            Font::BuiltinFont(_) => return Some(font.clone()),
+            Font::ExternalFont(ref id) => id,
+        };
+        match get_font_display_choice(id, font_display, app_resources.font_loads.get(id)) {
+            FontDisplayChoice::UseFont if app_resources.font_data.contains_key(font) => return Some(font.clone()),
+            FontDisplayChoice::UseFont | FontDisplayChoice::UseFallback => continue,
+            FontDisplayChoice::Invisible => return None,
+        }
+    }
+
+    Some(DEFAULT_BUILTIN_FONT_SANS_SERIF)
+}
+
+/// Approximates the height of one line of text, without knowing the metrics of the font
+fn get_inline_line_height(style: &RectStyle) -> f32 {
+    let font_size = style.font_size.unwrap_or(DEFAULT_FONT_SIZE).0.to_pixels();
+    match style.line_height {
+        None | Some(LineHeight::Normal) => font_size * NORMAL_LINE_HEIGHT,
+        Some(LineHeight::Multiplier(multiplier)) => font_size * NORMAL_LINE_HEIGHT * multiplier.number,
+        Some(LineHeight::Absolute(pixels)) => pixels.to_pixels(),
+    }
+}
+
+/// Translates the `margin` of a rectangle into constraints relative to its parent.
+///
+/// Fixed margins keep the given distance to the parents edge. If both sides
+/// of an axis are `auto`, the rectangle is centered (both distances are equal),
+/// if only one side is `auto`, that side consumes the remaining space,
+/// which pushes the rectangle to the opposite edge of the parent.
+fn create_margin_constraints(margin: &LayoutMargin, parent: &DisplayRect)
+-> Vec<CssConstraint>
+{
+    use cassowary::strength::*;
+    use constraints::{SizeConstraint, PaddingConstraint, Strength, Padding};
+    use css_parser::LayoutMarginValue::*;
+
+    let mut margin_constraints = Vec::<CssConstraint>::new();
+
+    {
+        let mut push_axis = |start: Option<LayoutMarginValue>, end: Option<LayoutMarginValue>,
+                             align_start: PaddingConstraint, align_end: PaddingConstraint,
+                             bound_start: PaddingConstraint, bound_end: PaddingConstraint,
+                             center: SizeConstraint|
+        {
+            match (start, end) {
+                (Some(Auto), Some(Auto)) => {
+                    margin_constraints.push(CssConstraint::Size((center, Strength(STRONG))));
+                },
+                (Some(Auto), end) => {
+                    let end = end.and_then(|e| get_fixed_margin(e)).unwrap_or(0.0);
+                    margin_constraints.push(CssConstraint::Padding((align_end, Strength(STRONG), Padding(end))));
+                    margin_constraints.push(CssConstraint::Padding((bound_start, Strength(REQUIRED), Padding(0.0))));
+                },
+                (start, Some(Auto)) => {
+                    let start = start.and_then(|s| get_fixed_margin(s)).unwrap_or(0.0);
+                    margin_constraints.push(CssConstraint::Padding((align_start, Strength(STRONG), Padding(start))));
+                    margin_constraints.push(CssConstraint::Padding((bound_end, Strength(REQUIRED), Padding(0.0))));
+                },
+                (start, end) => {
+                    if let Some(start) = start.and_then(|s| get_fixed_margin(s)) {
+                        margin_constraints.push(CssConstraint::Padding((align_start, Strength(STRONG), Padding(start))));
+                    }
+                    if let Some(end) = end.and_then(|e| get_fixed_margin(e)) {
+                        margin_constraints.push(CssConstraint::Padding((align_end, Strength(STRONG), Padding(end))));
+                    }
+                },
+            }
+        };
+
+        push_axis(margin.left, margin.right,
+                  PaddingConstraint::AlignLeft(parent.left), PaddingConstraint::AlignRight(parent.right),
+                  PaddingConstraint::BoundLeft(parent.left), PaddingConstraint::BoundRight(parent.right),
+                  SizeConstraint::CenterHorizontal(parent.left, parent.right));
+        push_axis(margin.top, margin.bottom,
+                  PaddingConstraint::AlignTop(parent.top), PaddingConstraint::AlignBottom(parent.bottom),
+                  PaddingConstraint::BoundTop(parent.top), PaddingConstraint::BoundBottom(parent.bottom),
+                  SizeConstraint::CenterVertical(parent.top, parent.bottom));
+    }
+
+    margin_constraints
+}
+
+fn get_fixed_margin(value: LayoutMarginValue) -> Option<f32> {
+    match value {
+        LayoutMarginValue::Auto => None,
+        LayoutMarginValue::Fixed(px) => Some(px.to_pixels()),
+    }
+}
+
 fn css_constraints_to_cassowary_constraints(rect: &DisplayRect, css: &Vec<CssConstraint>)
 -> Vec<Constraint>
 {
@@ -1064,3 +3230,201 @@ impl<'a> Arena<DisplayRectangle<'a>> {
 fn __codecov_test_display_list_file() {
 
 }
+
+#[test]
+fn test_get_object_fit_rect() {
+    use euclid::TypedPoint2D;
+
+    let bounds = TypedRect::new(TypedPoint2D::new(10.0, 10.0), TypedSize2D::new(200.0, 100.0));
+    let image_size = TypedSize2D::new(100.0, 100.0);
+    let center = StyleObjectPosition::default();
+    let top_left = StyleObjectPosition { horizontal: 0.0, vertical: 0.0 };
+
+    assert_eq!(get_object_fit_rect(&bounds, image_size, StyleObjectFit::Fill, center), bounds);
+    assert_eq!(get_object_fit_rect(&bounds, image_size, StyleObjectFit::Contain, center),
+               TypedRect::new(TypedPoint2D::new(60.0, 10.0), TypedSize2D::new(100.0, 100.0)));
+    assert_eq!(get_object_fit_rect(&bounds, image_size, StyleObjectFit::Cover, top_left),
+               TypedRect::new(TypedPoint2D::new(10.0, 10.0), TypedSize2D::new(200.0, 200.0)));
+    assert_eq!(get_object_fit_rect(&bounds, TypedSize2D::new(50.0, 50.0), StyleObjectFit::ScaleDown, top_left),
+               TypedRect::new(TypedPoint2D::new(10.0, 10.0), TypedSize2D::new(50.0, 50.0)));
+}
+
+#[test]
+fn test_get_text_decoration_rects() {
+    use euclid::TypedPoint2D;
+
+    assert_eq!(get_text_decoration_rects(0.0, 10.0, 10.0, 2.0, TextDecorationStyle::Solid),
+               vec![TypedRect::new(TypedPoint2D::new(0.0, 9.0), TypedSize2D::new(10.0, 2.0))]);
+
+    let dots = get_text_decoration_rects(0.0, 5.0, 10.0, 1.0, TextDecorationStyle::Dotted);
+    assert_eq!(dots.iter().map(|r| r.origin.x).collect::<Vec<f32>>(), vec![0.0, 2.0, 4.0]);
+
+    let dashes = get_text_decoration_rects(0.0, 10.0, 10.0, 1.0, TextDecorationStyle::Dashed);
+    assert_eq!(dashes.iter().map(|r| (r.origin.x, r.size.width)).collect::<Vec<(f32, f32)>>(), vec![(0.0, 3.0), (5.0, 3.0)]);
+
+    // the wavy line covers the whole width
+    let wave = get_text_decoration_rects(0.0, 12.0, 10.0, 1.0, TextDecorationStyle::Wavy);
+    assert_eq!(wave.iter().map(|r| r.size.width).sum::<f32>(), 12.0);
+
+    assert!(get_text_decoration_rects(10.0, 10.0, 10.0, 1.0, TextDecorationStyle::Solid).is_empty());
+}
+
+#[test]
+fn test_get_fixed_table_column_cell() {
+    let styled_node = StyledNode::default();
+    let mut arena = Arena::<DisplayRectangle>::new();
+
+    let table = arena.new_node(DisplayRectangle::new(None, &styled_node));
+    arena[table].data.layout.table_layout = Some(LayoutTableLayout::Fixed);
+
+    let rows = (0..2).map(|_| {
+        let row = arena.new_node(DisplayRectangle::new(None, &styled_node));
+        table.append(row, &mut arena);
+        let cells = (0..3).map(|_| {
+            let cell = arena.new_node(DisplayRectangle::new(None, &styled_node));
+            row.append(cell, &mut arena);
+            cell
+        }).collect::<Vec<NodeId>>();
+        cells
+    }).collect::<Vec<Vec<NodeId>>>();
+
+    assert_eq!(get_fixed_table_column_cell(rows[1][2], &arena), Some(rows[0][2]));
+    assert_eq!(get_fixed_table_column_cell(rows[0][1], &arena), None);
+
+    arena[table].data.layout.table_layout = Some(LayoutTableLayout::Auto);
+    assert_eq!(get_fixed_table_column_cell(rows[1][2], &arena), None);
+}
+
+#[test]
+fn test_get_floats() {
+    let styled_node = StyledNode::default();
+    let mut arena = Arena::<DisplayRectangle>::new();
+
+    let parent = arena.new_node(DisplayRectangle::new(None, &styled_node));
+    let children = (0..5).map(|_| {
+        let child = arena.new_node(DisplayRectangle::new(None, &styled_node));
+        parent.append(child, &mut arena);
+        child
+    }).collect::<Vec<NodeId>>();
+
+    // <img float:left> <p> <img float:right> <p clear:left> <p>
+    arena[children[0]].data.layout.float = Some(LayoutFloat::Left);
+    arena[children[2]].data.layout.float = Some(LayoutFloat::Right);
+    arena[children[3]].data.layout.clear = Some(LayoutClear::Left);
+
+    assert_eq!(get_floats(children[1], &arena), (None, Floats { left: vec![children[0]], right: vec![], cleared: vec![] }));
+    assert_eq!(get_floats(children[3], &arena), (Some(children[1]), Floats { left: vec![], right: vec![children[2]], cleared: vec![children[0]] }));
+    assert_eq!(get_floats(children[4], &arena), (Some(children[3]), Floats { left: vec![], right: vec![children[2]], cleared: vec![] }));
+}
+
+#[test]
+fn test_get_inline_vertical_offset() {
+    use css_parser::StyleVerticalAlign::*;
+
+    // (height, baseline) of a small node next to a large node
+    let small = Some((10.0, 8.0));
+    let large = Some((20.0, 15.0));
+
+    assert_eq!(get_inline_vertical_offset(Top, small, large), 0.0);
+    assert_eq!(get_inline_vertical_offset(Middle, small, large), 5.0);
+    assert_eq!(get_inline_vertical_offset(Bottom, small, large), 10.0);
+    assert_eq!(get_inline_vertical_offset(Baseline, small, large), 7.0);
+    assert_eq!(get_inline_vertical_offset(Baseline, small, None), 0.0);
+}
+
+#[test]
+fn test_get_glyph_options() {
+    assert_eq!(get_glyph_options(AaMode::SubPixel).render_mode, FontRenderMode::Subpixel);
+    assert_eq!(get_glyph_options(AaMode::Grayscale).render_mode, FontRenderMode::Alpha);
+    assert_eq!(get_glyph_options(AaMode::None).flags, FontInstanceFlags::empty());
+}
+
+#[test]
+fn test_get_list_marker_image_bounds() {
+    let content_box = LayoutRect::new(LayoutPoint::new(40.0, 10.0), TypedSize2D::new(200.0, 100.0));
+
+    // a 32x16 image is scaled to the line height of 20px (40x20), with a gap of 5px to the content box
+    assert_eq!(get_list_marker_image_bounds(&content_box, TypedSize2D::new(32.0, 16.0), 20.0),
+               Some(LayoutRect::new(LayoutPoint::new(-5.0, 10.0), TypedSize2D::new(40.0, 20.0))));
+    assert_eq!(get_list_marker_image_bounds(&content_box, TypedSize2D::new(0.0, 0.0), 20.0), None);
+}
+
+#[test]
+fn test_get_page_breaks() {
+    // each page ends above the text that would be cut in half
+    assert_eq!(get_page_breaks(&[(0.0, 60.0), (60.0, 130.0), (130.0, 170.0)], 170.0, 100.0), vec![0.0, 60.0, 130.0]);
+    // the pages end between the texts, a text that is higher than a page is cut
+    assert_eq!(get_page_breaks(&[(0.0, 40.0), (40.0, 100.0), (100.0, 250.0)], 250.0, 100.0), vec![0.0, 100.0, 200.0]);
+    // content that fits onto one page
+    assert_eq!(get_page_breaks(&[(0.0, 40.0)], 40.0, 100.0), vec![0.0]);
+    assert_eq!(get_page_breaks(&[], 500.0, 0.0), vec![0.0]);
+}
+
+#[test]
+fn test_get_image_pixel() {
+    // a 10x10 image, scaled to 100x50 pixels
+    let image_rect = LayoutRect::new(LayoutPoint::new(100.0, 100.0), LayoutSize::new(100.0, 50.0));
+    let image_size = LayoutSize::new(10.0, 10.0);
+
+    assert_eq!(get_image_pixel(&image_rect, image_size, LayoutPoint::new(100.0, 100.0)), Some((0, 0)));
+    assert_eq!(get_image_pixel(&image_rect, image_size, LayoutPoint::new(155.0, 147.0)), Some((5, 9)));
+    assert_eq!(get_image_pixel(&image_rect, image_size, LayoutPoint::new(99.0, 120.0)), None);
+}
+
+#[test]
+fn test_get_background_clip_rect() {
+    let bounds = LayoutRect::new(LayoutPoint::new(10.0, 10.0), LayoutSize::new(100.0, 50.0));
+    let border = BorderWidths { top: 1.0, right: 2.0, bottom: 3.0, left: 4.0 };
+    let padding = LayoutPadding {
+        top: Some(LayoutPaddingValue::Fixed(PixelValue { metric: CssMetric::Px, number: 5.0 })),
+        .. Default::default()
+    };
+
+    assert_eq!(get_background_clip_rect(&bounds, 200.0, StyleBackgroundClip::BorderBox, Some(&border), Some(&padding)), bounds);
+    assert_eq!(get_background_clip_rect(&bounds, 200.0, StyleBackgroundClip::PaddingBox, Some(&border), Some(&padding)),
+        LayoutRect::new(LayoutPoint::new(14.0, 11.0), LayoutSize::new(94.0, 46.0)));
+    assert_eq!(get_background_clip_rect(&bounds, 200.0, StyleBackgroundClip::ContentBox, Some(&border), Some(&padding)),
+        LayoutRect::new(LayoutPoint::new(14.0, 16.0), LayoutSize::new(94.0, 41.0)));
+    assert_eq!(get_background_clip_rect(&bounds, 200.0, StyleBackgroundClip::ContentBox, None, None), bounds);
+
+    // percentages refer to the width of the containing block, not the width of the node
+    let padding = LayoutPadding {
+        left: Some(LayoutPaddingValue::Percentage(PercentageValue { number: 0.1 })),
+        .. Default::default()
+    };
+    assert_eq!(get_background_clip_rect(&bounds, 200.0, StyleBackgroundClip::ContentBox, None, Some(&padding)),
+        LayoutRect::new(LayoutPoint::new(30.0, 10.0), LayoutSize::new(80.0, 50.0)));
+}
+
+#[test]
+fn test_percentage_padding_layout() {
+    use cassowary::strength::REQUIRED;
+    use cassowary::WeightedRelation::*;
+
+    let styled_node = StyledNode::default();
+    let app_resources = AppResources::default();
+    let parent = DisplayRect::default();
+    let percentage = |number| Some(LayoutPaddingValue::Percentage(PercentageValue { number: number }));
+
+    // `padding: 10% 0` on an inline node in a 300px wide parent: 30px above and below the line
+    let mut inline = DisplayRectangle::new(None, &styled_node);
+    inline.layout.display = Some(LayoutDisplay::Inline);
+    inline.layout.padding = Some(LayoutPadding { top: percentage(0.1), bottom: percentage(0.1), .. Default::default() });
+    let inline_rect = DisplayRect::default();
+
+    // `padding-top: 100%` on a block node makes it (at least) as high as the parent is wide
+    let mut block = DisplayRectangle::new(None, &styled_node);
+    block.layout.padding = Some(LayoutPadding { top: percentage(1.0), .. Default::default() });
+    let block_rect = DisplayRect::default();
+
+    let mut solver = Solver::new();
+    solver.add_constraint(parent.width | EQ(REQUIRED) | 300.0).unwrap();
+    for &(rect, display_rect) in [(&inline, &inline_rect), (&block, &block_rect)].iter() {
+        let mut constraints = create_layout_constraints(rect, NodeId::new(0), &Arena::new(), &WindowSize::default());
+        constraints.extend(create_flow_constraints(rect, &parent, 300.0, None, &Floats { left: vec![], right: vec![], cleared: vec![] }, &app_resources));
+        solver.add_constraints(&css_constraints_to_cassowary_constraints(display_rect, &constraints)).unwrap();
+    }
+
+    assert_eq!(solver.get_value(inline_rect.height) as f32, get_inline_line_height(&inline.style) + 60.0);
+    assert_eq!(solver.get_value(block_rect.height), 300.0);
+}
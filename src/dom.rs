@@ -6,18 +6,21 @@ use std::{
     sync::atomic::{AtomicUsize, Ordering},
     collections::BTreeMap,
 };
-use webrender::api::ColorU;
+use webrender::api::{ColorU, PipelineId, ExternalImageId};
 use glium::{Texture2d, framebuffer::SimpleFrameBuffer};
 use {
     window::WindowEvent,
     svg::SvgLayerId,
-    images::ImageId,
-    cache::DomHash,
+    images::{ImageId, AnimatedTexture, LazyImage, DEFAULT_LAZY_LOAD_DISTANCE},
+    cache::{DomHash, DomMemoCache},
     text_cache::TextId,
     traits::Layout,
     app_state::AppState,
     id_tree::{NodeId, Arena},
+    FastHashMap,
 };
+#[cfg(unix)]
+use compositor::SharedMemoryExternalImage;
 
 /// This is only accessed from the main thread, so it's safe to use
 pub(crate) static NODE_ID: AtomicUsize = AtomicUsize::new(0);
@@ -92,6 +95,19 @@ pub enum NodeType {
     /// Equality and Hash values are only checked by the OpenGl texture ID,
     /// azul does not check that the contents of two textures are the same
     GlTexture(Texture),
+    /// Embedded webrender pipeline, similar to an `<iframe>` in HTML.
+    /// Create the pipeline via `App::add_embedded_pipeline()`
+    IFrame(IFrame),
+    /// Animated image (GIF), the displayed frame depends on the time since the
+    /// image was loaded. The window is redrawn whenever the frame changes.
+    AnimatedImage(AnimatedTexture),
+    /// Image that is loaded on a background thread once it comes near the visible
+    /// area of the window, see `Dom::image_lazy()`
+    LazyImage(LazyImage),
+    /// Image that is written to by another process, re-read from the shared
+    /// memory on every frame (see `SharedMemoryExternalImage`)
+    #[cfg(unix)]
+    SharedMemoryImage(SharedMemoryExternalImage),
 }
 
 impl NodeType {
@@ -100,12 +116,34 @@ impl NodeType {
         match self {
             Div => "div",
             Label(_) | Text(_) => "p",
-            Image(_) => "image",
+            Image(_) | AnimatedImage(_) | LazyImage(_) => "image",
             GlTexture(_) => "texture",
+            #[cfg(unix)]
+            SharedMemoryImage(_) => "texture",
+            IFrame(_) => "iframe",
         }
     }
 }
 
+/// A seperate webrender pipeline that is embedded into the DOM.
+/// The parent pipeline clips the content to `width` x `height` pixels.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct IFrame {
+    pub pipeline_id: PipelineId,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Hash for IFrame {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pipeline_id.hash(state);
+        self.width.to_bits().hash(state);
+        self.height.to_bits().hash(state);
+    }
+}
+
+impl Eq for IFrame { }
+
 /// OpenGL texture, use `ReadOnlyWindow::create_texture` to create a texture
 ///
 /// **WARNING**: Don't forget to call `ReadOnlyWindow::unbind_framebuffer()`
@@ -136,6 +174,13 @@ impl Texture {
     pub fn as_surface<'a>(&'a self) -> SimpleFrameBuffer<'a> {
         self.inner.as_surface()
     }
+
+    /// The ID of the texture in the compositor, for updating the texture
+    /// from other threads via a `TextureUpdateSender`
+    pub fn external_image_id(&self) -> ExternalImageId {
+        use glium::GlObject;
+        ExternalImageId(self.inner.get_id() as u64)
+    }
 }
 
 impl Hash for Texture {
@@ -190,6 +235,47 @@ pub enum On {
     MouseLeave,
     /// Mousewheel / touchpad scrolling
     Scroll,
+    /// The left mouse button has been pressed on the element, which starts a
+    /// drag-and-drop, see `AppState::get_drag_and_drop()`
+    DragStart,
+    /// A drag-and-drop has ended over the element, which can be in a different window
+    /// than the element where the drag started. `WindowEvent::drag_and_drop` contains
+    /// the source and the target of the drag.
+    Drop,
+    /// Two fingers have been moved towards or away from each other on the element
+    /// (hit-tested at the center of the fingers). `WindowEvent::pinch_gesture` contains
+    /// the scale factor. Requires `WindowCreateOptions::enable_touch`.
+    ///
+    /// azul doesn't zoom the scroll containers by itself, the callback has to apply
+    /// the scale to the zoom level of the application.
+    Pinch,
+    /// Two fingers have been rotated around each other on the element (hit-tested at
+    /// the center of the fingers). `WindowEvent::rotate_gesture` contains the angle.
+    /// Requires `WindowCreateOptions::enable_touch`.
+    ///
+    /// There is no CSS `transform` yet, so the callback has to apply the rotation itself
+    /// (i.e. to the rotation of a map or a diagram that is drawn with an `Svg`).
+    Rotate,
+    /// A finger has touched the element. `WindowEvent::touch` contains the ID of the finger.
+    /// Requires `WindowCreateOptions::enable_touch`.
+    ///
+    /// Every finger is dispatched separately (and hit-tested at the position of the finger).
+    /// The first finger is also simulated as the left mouse button (`On::MouseDown`, etc.),
+    /// the other fingers only invoke the `On::TouchStart` / `TouchMove` / `TouchEnd` callbacks.
+    TouchStart,
+    /// A finger has moved on the element, see `On::TouchStart`
+    TouchMove,
+    /// A finger has been lifted from the element (or the touch has been cancelled by
+    /// the OS), see `On::TouchStart`
+    TouchEnd,
+    /// A key has been pressed while the element has the keyboard focus (see
+    /// `FakeWindow::set_focus()`). `KeyboardState::current_virtual_keycode` contains the key.
+    VirtualKeyDown,
+    /// A key has been released while the element has the keyboard focus, see `On::VirtualKeyDown`
+    VirtualKeyUp,
+    /// A character has been typed while the element has the keyboard focus, i.e. for text
+    /// inputs. `KeyboardState::current_char` contains the character.
+    TextInput,
 }
 
 #[derive(PartialEq, Eq)]
@@ -204,6 +290,12 @@ pub(crate) struct NodeData<T: Layout> {
     pub events: CallbackList<T>,
     /// Tag for hit-testing
     pub tag: Option<u64>,
+    /// Disabled nodes don't receive any mouse or keyboard events and match `:disabled`
+    pub disabled: bool,
+    /// Set on text inputs that are empty and show their placeholder, matches `:placeholder-shown`
+    pub placeholder_shown: bool,
+    /// `tabindex`: nodes with a tab index can receive the keyboard focus
+    pub tab_index: Option<isize>,
 }
 
 impl<T: Layout> Hash for NodeData<T> {
@@ -214,6 +306,9 @@ impl<T: Layout> Hash for NodeData<T> {
             class.hash(state);
         }
         self.events.hash(state);
+        self.disabled.hash(state);
+        self.placeholder_shown.hash(state);
+        self.tab_index.hash(state);
     }
 }
 
@@ -235,6 +330,9 @@ impl<T: Layout> Clone for NodeData<T> {
             classes: self.classes.clone(),
             events: self.events.special_clone(),
             tag: self.tag.clone(),
+            disabled: self.disabled,
+            placeholder_shown: self.placeholder_shown,
+            tab_index: self.tab_index,
         }
     }
 }
@@ -247,13 +345,19 @@ impl<T: Layout> fmt::Debug for NodeData<T> {
                 \tid: {:?}, \
                 \tclasses: {:?}, \
                 \tevents: {:?}, \
-                \ttag: {:?} \
+                \ttag: {:?}, \
+                \tdisabled: {:?}, \
+                \tplaceholder_shown: {:?}, \
+                \ttab_index: {:?} \
             }}",
         self.node_type,
         self.id,
         self.classes,
         self.events,
-        self.tag)
+        self.tag,
+        self.disabled,
+        self.placeholder_shown,
+        self.tab_index)
     }
 }
 
@@ -274,6 +378,9 @@ impl<T: Layout> NodeData<T> {
             classes: Vec::new(),
             events: CallbackList::<T>::new(),
             tag: None,
+            disabled: false,
+            placeholder_shown: false,
+            tab_index: None,
         }
     }
 
@@ -286,6 +393,9 @@ impl<T: Layout> NodeData<T> {
             classes: self.classes.clone(),
             events: self.events.special_clone(),
             tag: self.tag.clone(),
+            disabled: self.disabled,
+            placeholder_shown: self.placeholder_shown,
+            tab_index: self.tab_index,
         }
     }
 }
@@ -353,6 +463,118 @@ impl<T: Layout> Dom<T> {
         }
     }
 
+    /// Creates a DOM that embeds the content of a seperate webrender pipeline
+    #[inline]
+    pub fn iframe(pipeline_id: PipelineId, width: f32, height: f32) -> Self {
+        Self::new(NodeType::IFrame(IFrame {
+            pipeline_id: pipeline_id,
+            width: width,
+            height: height,
+        }))
+    }
+
+    /// Creates a DOM that plays an animated image
+    #[inline]
+    pub fn animated_image(anim: AnimatedTexture) -> Self {
+        Self::new(NodeType::AnimatedImage(anim))
+    }
+
+    /// Creates a DOM that shows the `placeholder` until the image at `url` has been loaded.
+    ///
+    /// The image starts loading on a background thread once the node comes within
+    /// `DEFAULT_LAZY_LOAD_DISTANCE` pixels of the window, the window is redrawn when the
+    /// image has finished loading. Use `get_lazy_image_state()` to query the load state.
+    ///
+    /// **NOTE**: Only local files (`path/to/image.png` or `file:///path/to/image.png`)
+    /// can be loaded, there is no HTTP client in azul.
+    #[inline]
+    pub fn image_lazy(url: String, placeholder: Dom<T>) -> Self {
+        Self::image_lazy_with_distance(url, DEFAULT_LAZY_LOAD_DISTANCE, placeholder)
+    }
+
+    /// Same as `image_lazy()`, but starts loading the image once the node is
+    /// within `load_distance` pixels of the window
+    pub fn image_lazy_with_distance(url: String, load_distance: u32, placeholder: Dom<T>) -> Self {
+        let lazy_image = LazyImage::new(url, load_distance);
+        let show_placeholder = lazy_image.has_placeholder();
+        let dom = Self::new(NodeType::LazyImage(lazy_image));
+        if show_placeholder {
+            dom.with_child(placeholder)
+        } else {
+            dom
+        }
+    }
+
+    /// Returns whether any animated image in this DOM has advanced to a new frame
+    /// or any lazy image has finished loading since the DOM was last rendered
+    pub(crate) fn has_new_animation_frame(&self) -> bool {
+        self.arena.borrow().nodes.iter().any(|node| match node.data.node_type {
+            NodeType::AnimatedImage(ref anim) => anim.has_new_frame(),
+            NodeType::LazyImage(ref lazy_image) => lazy_image.has_finished_loading(),
+            _ => false,
+        })
+    }
+
+    /// Returns the URLs of all lazy images in this DOM, see `images::evict_lazy_images()`
+    pub(crate) fn get_lazy_image_urls(&self) -> Vec<String> {
+        self.arena.borrow().nodes.iter().filter_map(|node| match node.data.node_type {
+            NodeType::LazyImage(ref lazy_image) => Some(lazy_image.url().to_string()),
+            _ => None,
+        }).collect()
+    }
+
+    /// Creates a DOM fragment: the `children` are added as siblings of each other,
+    /// without a wrapper node. When the fragment is added to a parent via `add_child()`,
+    /// all of its nodes become direct children of that parent, as if the fragment
+    /// didn't exist, so no extra layout box is created.
+    ///
+    /// **NOTE**: Since every DOM needs a root node, an empty fragment results
+    /// in a single empty `Div`. The DOM returned from `Layout::layout()` has to have
+    /// a single root node, a fragment should always be added to a parent node.
+    pub fn fragment(children: Vec<Dom<T>>) -> Self {
+        let mut children = children.into_iter();
+        let mut fragment = match children.next() {
+            Some(first) => first,
+            None => return Self::new(NodeType::Div),
+        };
+        for child in children {
+            fragment.add_sibling(child);
+        }
+        fragment
+    }
+
+    /// Memoizes the DOM returned by the `builder`: If `memoize()` was called with
+    /// the same `key` during the last frame, the previously built DOM is reused and the
+    /// `builder` isn't called. The `key` should be a hash of all the inputs that the
+    /// sub-DOM depends on, so that the sub-DOM is rebuilt whenever one of them changes.
+    ///
+    /// Memoized DOMs that weren't used during a frame are removed from the cache.
+    pub fn memoize<F>(key: u64, builder: F) -> Self where F: FnOnce() -> Self, T: 'static {
+        if let Some(dom) = DomMemoCache::get::<T>(key) {
+            return dom;
+        }
+        let dom = builder();
+        DomMemoCache::insert(key, &dom);
+        dom
+    }
+
+    /// Copies the nodes of the DOM into a new arena, while `clone()` only
+    /// clones the reference to the arena. Nodes with callbacks get new
+    /// hit-testing tags, since the tags are only unique within one frame.
+    pub(crate) fn deep_clone(&self) -> Self {
+        let mut arena = self.arena.borrow().clone();
+        for node in arena.nodes.iter_mut() {
+            if node.data.tag.is_some() {
+                node.data.tag = Some(NODE_ID.fetch_add(1, Ordering::SeqCst) as u64);
+            }
+        }
+        Self {
+            arena: Rc::new(RefCell::new(arena)),
+            root: self.root,
+            head: self.head,
+        }
+    }
+
     /// Adds a sibling to the current DOM
     pub fn add_sibling(&mut self, sibling: Self) {
         use id_tree::Node;
@@ -367,15 +589,15 @@ impl<T: Layout> Dom<T> {
 
             let node: &mut Node<NodeData<T>> = &mut sibling_arena[NodeId::new(node_id)];
 
-            {
-                let mut b_node_parent_is_some = false;
-                if let Some(parent) = node.parent_mut() {
-                    *parent = *parent + self_len;
-                    b_node_parent_is_some = true;
-                }
-                if !b_node_parent_is_some {
-                    node.parent = self_arena[self.head].parent;
-                }
+            // only the nodes at the root level of the sibling have no parent,
+            // their children have to keep their parents and siblings
+            let is_root_level = node.parent.is_none();
+
+            if let Some(parent) = node.parent_mut() {
+                *parent = *parent + self_len;
+            }
+            if is_root_level {
+                node.parent = self_arena[self.head].parent;
             }
 
             {
@@ -384,7 +606,7 @@ impl<T: Layout> Dom<T> {
                     *previous_sibling = *previous_sibling + self_len;
                     b_node_previous_sibling_is_some = true;
                 }
-                if !b_node_previous_sibling_is_some {
+                if !b_node_previous_sibling_is_some && is_root_level {
                     node.previous_sibling = Some(self.head);
                 }
             }
@@ -402,9 +624,14 @@ impl<T: Layout> Dom<T> {
             }
         }
 
-        let head_node_id = NodeId::new(self_len);
-        self_arena[self.head].next_sibling = Some(head_node_id);
-        self.head = head_node_id;
+        // the sibling may itself consist of multiple siblings (i.e. a fragment),
+        // the new head is the last one of them
+        let new_head = sibling.head + self_len;
+        self_arena[self.head].next_sibling = Some(NodeId::new(self_len));
+        if let Some(parent) = self_arena[self.head].parent {
+            self_arena[parent].last_child = Some(new_head);
+        }
+        self.head = new_head;
         (&mut *self_arena).append(&mut sibling_arena);
     }
 
@@ -474,6 +701,89 @@ impl<T: Layout> Dom<T> {
         (&mut *self_arena).append(&mut child_arena);
     }
 
+    /// Transforms each direct child of the root node, i.e. to wrap, style or
+    /// replace the children of a list
+    pub fn map_children<F>(self, f: F) -> Self where F: Fn(Dom<T>) -> Dom<T> {
+        self.replace_children(|children| children.into_iter().map(|child| f(child)).collect())
+    }
+
+    /// Removes the direct children of the root node for which the `predicate` returns `false`
+    pub fn filter_children<F>(self, predicate: F) -> Self where F: Fn(&Dom<T>) -> bool {
+        self.replace_children(|children| children.into_iter().filter(|child| predicate(child)).collect())
+    }
+
+    /// Same as `with_child`, but adds all children of an iterator,
+    /// i.e. `Dom::new(NodeType::Div).with_children(items.iter().map(render_item))`
+    #[inline]
+    pub fn with_children<I>(mut self, children: I) -> Self where I: IntoIterator<Item = Dom<T>> {
+        for child in children {
+            self.add_child(child);
+        }
+        self
+    }
+
+    /// Splits the children of the root node into seperate DOMs, replaces them with the
+    /// DOMs returned by `f` and adds them back to a copy of the root node.
+    /// Siblings of the root node (if the DOM is a fragment) are kept unchanged.
+    fn replace_children<F>(&self, f: F) -> Self where F: FnOnce(Vec<Dom<T>>) -> Vec<Dom<T>> {
+
+        let (root_data, children, siblings) = {
+            let arena = self.arena.borrow();
+            let children = self.root.children(&*arena).map(|child| self.get_subtree(child)).collect();
+            let siblings = self.root.following_siblings(&*arena).skip(1).map(|sibling| self.get_subtree(sibling)).collect::<Vec<Dom<T>>>();
+            (arena[self.root].data.clone(), children, siblings)
+        };
+
+        let mut arena = Arena::new();
+        let root = arena.new_node(root_data);
+        let mut dom = Self {
+            arena: Rc::new(RefCell::new(arena)),
+            root: root,
+            head: root,
+        };
+
+        for child in f(children) {
+            dom.add_child(child);
+        }
+        for sibling in siblings {
+            dom.add_sibling(sibling);
+        }
+
+        dom
+    }
+
+    /// Copies a node and all of its descendants into a new DOM
+    fn get_subtree(&self, node_id: NodeId) -> Self {
+        use id_tree::Node;
+
+        let arena = self.arena.borrow();
+
+        let new_ids = node_id.descendants(&*arena).enumerate()
+            .map(|(new_index, old_id)| (old_id, NodeId::new(new_index)))
+            .collect::<BTreeMap<NodeId, NodeId>>();
+        let get_new_id = |old_id: Option<NodeId>| old_id.and_then(|id| new_ids.get(&id).cloned());
+
+        let nodes = node_id.descendants(&*arena).map(|old_id| {
+            let node = &arena[old_id];
+            // the new root node has no parent or siblings
+            let is_root = old_id == node_id;
+            Node {
+                parent: if is_root { None } else { get_new_id(node.parent) },
+                previous_sibling: if is_root { None } else { get_new_id(node.previous_sibling) },
+                next_sibling: if is_root { None } else { get_new_id(node.next_sibling) },
+                first_child: get_new_id(node.first_child),
+                last_child: get_new_id(node.last_child),
+                data: node.data.clone(),
+            }
+        }).collect();
+
+        Self {
+            arena: Rc::new(RefCell::new(Arena::from_nodes(nodes))),
+            root: NodeId::new(0),
+            head: NodeId::new(0),
+        }
+    }
+
     /// Same as `id`, but easier to use for method chaining in a builder-style pattern
     #[inline]
     pub fn with_id<S: Into<String>>(mut self, id: S) -> Self {
@@ -495,6 +805,27 @@ impl<T: Layout> Dom<T> {
         self
     }
 
+    /// Same as `set_disabled`, but easier to use for method chaining in a builder-style pattern
+    #[inline]
+    pub fn with_disabled(mut self, disabled: bool) -> Self {
+        self.set_disabled(disabled);
+        self
+    }
+
+    /// Same as `set_placeholder_shown`, but easier to use for method chaining in a builder-style pattern
+    #[inline]
+    pub fn with_placeholder_shown(mut self, placeholder_shown: bool) -> Self {
+        self.set_placeholder_shown(placeholder_shown);
+        self
+    }
+
+    /// Same as `set_tab_index`, but easier to use for method chaining in a builder-style pattern
+    #[inline]
+    pub fn with_tab_index(mut self, tab_index: isize) -> Self {
+        self.set_tab_index(tab_index);
+        self
+    }
+
     #[inline]
     pub fn with_child(mut self, child: Self) -> Self {
         self.add_child(child);
@@ -517,11 +848,90 @@ impl<T: Layout> Dom<T> {
         self.arena.borrow_mut()[self.head].data.classes.push(class.into());
     }
 
+    /// Disables the node: the callbacks of the node are not called anymore
+    /// (the events go through to the nodes below) and the node matches `:disabled`
+    #[inline]
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.arena.borrow_mut()[self.head].data.disabled = disabled;
+    }
+
+    /// Marks the node as a text input that currently shows its placeholder
+    /// (because the entered text is empty), the node then matches `:placeholder-shown`
+    #[inline]
+    pub fn set_placeholder_shown(&mut self, placeholder_shown: bool) {
+        self.arena.borrow_mut()[self.head].data.placeholder_shown = placeholder_shown;
+    }
+
+    /// Makes the node focusable, like the HTML `tabindex`: nodes with a positive tab index
+    /// are focused first (in ascending order), then the nodes with a tab index of `0`
+    /// (in document order). Nodes with a negative tab index can only be focused with
+    /// `FakeWindow::set_focus()`.
+    #[inline]
+    pub fn set_tab_index(&mut self, tab_index: isize) {
+        self.arena.borrow_mut()[self.head].data.tab_index = Some(tab_index);
+    }
+
     #[inline]
     pub fn set_callback(&mut self, on: On, callback: Callback<T>) {
         self.arena.borrow_mut()[self.head].data.events.callbacks.insert(on, callback);
         self.arena.borrow_mut()[self.head].data.tag = Some(NODE_ID.fetch_add(1, Ordering::SeqCst) as u64);
     }
+
+    /// Returns the node that is focused first (see `set_tab_index()`), ignoring disabled nodes
+    pub(crate) fn get_first_focusable_node(&self) -> Option<NodeId> {
+        let arena = self.arena.borrow();
+        let focusable_nodes = arena.linear_iter()
+            .filter(|node_id| !arena[*node_id].data.disabled)
+            .filter_map(|node_id| arena[node_id].data.tab_index.map(|tab_index| (node_id, tab_index)))
+            .collect::<Vec<(NodeId, isize)>>();
+
+        // min_by_key returns the first node (in document order) with the lowest tab index
+        focusable_nodes.iter().filter(|&&(_, tab_index)| tab_index > 0).min_by_key(|&&(_, tab_index)| tab_index)
+            .or_else(|| focusable_nodes.iter().find(|&&(_, tab_index)| tab_index == 0))
+            .map(|&(node_id, _)| node_id)
+    }
+
+    /// Returns the node that the Tab key (or Shift + Tab, if `forward` is false) moves the
+    /// keyboard focus to from the `focused_node`: the nodes with a positive tab index in
+    /// ascending order, then the nodes with a tab index of `0` in document order. Wraps
+    /// around at the end, ignores disabled nodes and nodes with a negative tab index.
+    pub(crate) fn get_next_focusable_node(&self, focused_node: Option<NodeId>, forward: bool) -> Option<NodeId> {
+        let arena = self.arena.borrow();
+        let mut tab_order = arena.linear_iter()
+            .filter(|node_id| !arena[*node_id].data.disabled)
+            .filter_map(|node_id| arena[node_id].data.tab_index.map(|tab_index| (node_id, tab_index)))
+            .filter(|&(_, tab_index)| tab_index >= 0)
+            .collect::<Vec<(NodeId, isize)>>();
+
+        // sort_by_key is stable, so nodes with the same tab index stay in document order
+        tab_order.sort_by_key(|&(_, tab_index)| if tab_index == 0 { isize::max_value() } else { tab_index });
+
+        if tab_order.is_empty() {
+            return None;
+        }
+
+        let next_index = match focused_node.and_then(|focused| tab_order.iter().position(|&(node_id, _)| node_id == focused)) {
+            Some(index) if forward => (index + 1) % tab_order.len(),
+            Some(index) => (index + tab_order.len() - 1) % tab_order.len(),
+            None if forward => 0,
+            None => tab_order.len() - 1,
+        };
+
+        Some(tab_order[next_index].0)
+    }
+
+    /// Returns the nodes that have a CSS ID (see `with_id()`), the first node wins if
+    /// multiple nodes have the same ID. See `FakeWindow::get_node_by_id()`
+    pub(crate) fn get_css_ids(&self) -> FastHashMap<String, NodeId> {
+        let arena = self.arena.borrow();
+        let mut css_ids = FastHashMap::default();
+        for node_id in arena.linear_iter() {
+            if let Some(ref id) = arena[node_id].data.id {
+                css_ids.entry(id.clone()).or_insert(node_id);
+            }
+        }
+        css_ids
+    }
 }
 
 impl<T: Layout> Dom<T> {
@@ -534,6 +944,10 @@ impl<T: Layout> Dom<T> {
         for item in self.root.traverse(&*self.arena.borrow()) {
             let mut cb_id_list = BTreeMap::<On, u64>::new();
             let item = &self.arena.borrow()[item.inner_value()];
+            // disabled nodes don't respond to any events
+            if item.data.disabled {
+                continue;
+            }
             for (on, callback) in item.data.events.callbacks.iter() {
                 let callback_id = CALLBACK_ID.fetch_add(1, Ordering::SeqCst) as u64;
                 callback_list.insert(callback_id, *callback);
@@ -546,6 +960,17 @@ impl<T: Layout> Dom<T> {
     }
 }
 
+/// Minimal `Layout` for the tests that only need a `Dom<T>`
+#[cfg(test)]
+pub(crate) struct TestLayout { }
+
+#[cfg(test)]
+impl Layout for TestLayout {
+    fn layout(&self) -> Dom<Self> {
+        Dom::new(NodeType::Div)
+    }
+}
+
 #[test]
 fn test_dom_sibling_1() {
 
@@ -604,4 +1029,104 @@ fn test_dom_sibling_1() {
                 ].next_sibling().expect("first child has no second sibling")
             ].first_child().expect("second sibling has no first child")
         ].data.id);
+}
+
+#[test]
+fn test_dom_fragment() {
+    let fragment = Dom::<TestLayout>::fragment(vec![
+        Dom::new(NodeType::Div).with_id("fragment-1")
+            .with_child(Dom::new(NodeType::Div).with_id("fragment-1-child-1")),
+        Dom::new(NodeType::Div).with_id("fragment-2"),
+        Dom::new(NodeType::Div).with_id("fragment-3"),
+    ]);
+
+    let dom = Dom::new(NodeType::Div).with_child(fragment);
+    let arena = dom.arena.borrow();
+
+    let children = dom.root.children(&*arena)
+        .map(|child| arena[child].data.id.clone().unwrap())
+        .collect::<Vec<String>>();
+    assert_eq!(children, vec!["fragment-1", "fragment-2", "fragment-3"]);
+
+    let first_child = arena[dom.root].first_child().unwrap();
+    let grandchild = arena[first_child].first_child().unwrap();
+    assert_eq!(arena[grandchild].data.id, Some(String::from("fragment-1-child-1")));
+    assert_eq!(arena[grandchild].previous_sibling(), None);
+    assert_eq!(arena[grandchild].parent(), Some(first_child));
+}
+
+#[test]
+fn test_dom_map_and_filter_children() {
+    let dom = Dom::<TestLayout>::new(NodeType::Div)
+        .with_children((0..4).map(|i| Dom::new(NodeType::Label(format!("{}", i)))
+            .with_child(Dom::new(NodeType::Div))))
+        .filter_children(|child| child.arena.borrow()[child.root].data.node_type != NodeType::Label(String::from("2")))
+        .map_children(|child| child.with_class("item"));
+
+    let arena = dom.arena.borrow();
+    let children = dom.root.children(&*arena).collect::<Vec<NodeId>>();
+    assert_eq!(children.len(), 3);
+    assert_eq!(arena[children[2]].data.node_type, NodeType::Label(String::from("3")));
+    for child in children {
+        assert_eq!(arena[child].data.classes, vec![String::from("item")]);
+        assert!(arena[child].first_child().is_some());
+        assert_eq!(arena[child].parent(), Some(dom.root));
+    }
+}
+
+#[test]
+fn test_disabled_node_has_no_callbacks() {
+    fn on_click(_: &mut AppState<TestLayout>, _: WindowEvent) -> UpdateScreen {
+        UpdateScreen::Redraw
+    }
+
+    let dom = Dom::<TestLayout>::new(NodeType::Div)
+        .with_child(Dom::new(NodeType::Div).with_callback(On::MouseUp, Callback(on_click)))
+        .with_child(Dom::new(NodeType::Div).with_callback(On::MouseUp, Callback(on_click)).with_disabled(true));
+
+    let mut callback_list = BTreeMap::new();
+    let mut nodes_to_callback_id_list = BTreeMap::new();
+    dom.collect_callbacks(&mut callback_list, &mut nodes_to_callback_id_list);
+
+    assert_eq!(callback_list.len(), 1);
+    assert_eq!(nodes_to_callback_id_list.len(), 1);
+}
+
+#[test]
+fn test_get_first_focusable_node() {
+    let dom = Dom::<TestLayout>::new(NodeType::Div)
+        .with_child(Dom::new(NodeType::Div).with_tab_index(0))
+        .with_child(Dom::new(NodeType::Div).with_tab_index(2))
+        .with_child(Dom::new(NodeType::Div).with_tab_index(1).with_disabled(true))
+        .with_child(Dom::new(NodeType::Div).with_tab_index(2));
+    assert_eq!(dom.get_first_focusable_node(), Some(NodeId::new(2)));
+
+    let dom = Dom::<TestLayout>::new(NodeType::Div)
+        .with_child(Dom::new(NodeType::Div).with_tab_index(-1))
+        .with_child(Dom::new(NodeType::Div).with_tab_index(0));
+    assert_eq!(dom.get_first_focusable_node(), Some(NodeId::new(2)));
+
+    assert_eq!(Dom::<TestLayout>::new(NodeType::Div).get_first_focusable_node(), None);
+}
+
+#[test]
+fn test_get_next_focusable_node() {
+    let dom = Dom::<TestLayout>::new(NodeType::Div)
+        .with_child(Dom::new(NodeType::Div).with_tab_index(0))
+        .with_child(Dom::new(NodeType::Div).with_tab_index(2))
+        .with_child(Dom::new(NodeType::Div).with_tab_index(1).with_disabled(true))
+        .with_child(Dom::new(NodeType::Div).with_tab_index(-1))
+        .with_child(Dom::new(NodeType::Div).with_tab_index(1));
+
+    // tab index 1, then 2, then 0, then back to the start
+    assert_eq!(dom.get_next_focusable_node(None, true), Some(NodeId::new(5)));
+    assert_eq!(dom.get_next_focusable_node(Some(NodeId::new(5)), true), Some(NodeId::new(2)));
+    assert_eq!(dom.get_next_focusable_node(Some(NodeId::new(2)), true), Some(NodeId::new(1)));
+    assert_eq!(dom.get_next_focusable_node(Some(NodeId::new(1)), true), Some(NodeId::new(5)));
+
+    // Shift + Tab goes backwards
+    assert_eq!(dom.get_next_focusable_node(Some(NodeId::new(5)), false), Some(NodeId::new(1)));
+    assert_eq!(dom.get_next_focusable_node(None, false), Some(NodeId::new(1)));
+
+    assert_eq!(Dom::<TestLayout>::new(NodeType::Div).get_next_focusable_node(None, true), None);
 }
\ No newline at end of file
@@ -1,7 +1,18 @@
 //! Module for loading and handling fonts
+use std::{
+    fs,
+    thread,
+    path::PathBuf,
+    sync::{Mutex, atomic::{AtomicUsize, Ordering}},
+    time::{Duration, Instant},
+};
 use webrender::api::FontKey;
 use rusttype::{Font, FontCollection};
 use rusttype::Error as RusttypeError;
+use {
+    FastHashMap,
+    css_parser::StyleFontDisplay,
+};
 
 #[derive(Debug, Clone)]
 pub(crate) enum FontState {
@@ -34,6 +45,29 @@ impl From<RusttypeError> for FontError {
     }
 }
 
+impl ::std::fmt::Display for FontError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        use self::FontError::*;
+        match self {
+            UploadError => write!(f, "Font failed to upload to the GPU"),
+            InvalidFormat => write!(f, "Invalid font format"),
+            ParseError(e) => write!(f, "Could not parse the font: {}", e),
+            IoError(e) => write!(f, "IO error: {}", e),
+        }
+    }
+}
+
+impl ::std::error::Error for FontError {
+    fn source(&self) -> Option<&(::std::error::Error + 'static)> {
+        use self::FontError::*;
+        match self {
+            ParseError(e) => Some(e),
+            IoError(e) => Some(e),
+            UploadError | InvalidFormat => None,
+        }
+    }
+}
+
 /// Read font data to get font information, v_metrics, glyph info etc.
 pub(crate) fn rusttype_load_font<'a>(data: Vec<u8>) -> Result<Font<'a>, FontError> {
     let collection = FontCollection::from_bytes(data)?;
@@ -41,10 +75,221 @@ pub(crate) fn rusttype_load_font<'a>(data: Vec<u8>) -> Result<Font<'a>, FontErro
     Ok(font)
 }
 
+/// Load state of a font that is loaded via `AppState::add_font_from_file()`,
+/// see `AppState::get_font_load_state()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontLoadState {
+    /// The font is being read and parsed on a background thread
+    Loading,
+    /// The font can be used, i.e. `AppState::has_font()` returns true
+    Loaded,
+    /// The font couldn't be loaded or added, contains the error message
+    Failed(String),
+}
+
+/// A font loaded via `AppState::add_font_from_file()` that has finished loading.
+/// Stored in the `AppResources`, since `font-display: fallback | optional` depend on
+/// how long the font took to load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FinishedFontLoad {
+    /// The font has been added to the `AppResources`
+    Added { load_time: Duration },
+    Failed(String),
+}
+
+enum LoadingFontData {
+    Loading,
+    /// The font has been read, but not yet added to the `AppResources`
+    Loaded(Vec<u8>),
+    Failed(String),
+}
+
+struct LoadingFont {
+    data: LoadingFontData,
+    started: Instant,
+}
+
+lazy_static! {
+    /// Fonts added via `AppState::add_font_from_file()` that haven't been taken by
+    /// `take_loaded_fonts()` yet, keyed by the font ID
+    static ref LOADING_FONTS: Mutex<FastHashMap<String, LoadingFont>> = Mutex::new(FastHashMap::default());
+}
+
+/// Number of entries in `LOADING_FONTS`, so that the fonts of the text nodes can be
+/// selected without locking the mutex while no font is loading
+static NUM_LOADING_FONTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Starts reading and parsing the font file on a background thread.
+/// Does nothing if a font with the same ID is already loading.
+pub(crate) fn load_font_async(id: String, path: PathBuf) {
+    {
+        let mut loading_fonts = LOADING_FONTS.lock().unwrap();
+        if loading_fonts.contains_key(&id) {
+            return;
+        }
+        loading_fonts.insert(id.clone(), LoadingFont {
+            data: LoadingFontData::Loading,
+            started: Instant::now(),
+        });
+        NUM_LOADING_FONTS.store(loading_fonts.len(), Ordering::SeqCst);
+    }
+
+    thread::spawn(move || {
+        let data = match fs::read(&path) {
+            // parse the font here, so that errors are reported before the font is added
+            Ok(bytes) => match rusttype_load_font(bytes.clone()) {
+                Ok(_) => LoadingFontData::Loaded(bytes),
+                Err(e) => LoadingFontData::Failed(format!("{}", e)),
+            },
+            Err(e) => LoadingFontData::Failed(format!("{}", FontError::IoError(e))),
+        };
+        if let LoadingFontData::Failed(ref e) = data {
+            warn!("could not load font \"{}\" from {:?}: {}", id, path, e);
+        }
+        if let Some(font) = LOADING_FONTS.lock().unwrap().get_mut(&id) {
+            font.data = data;
+        }
+    });
+}
+
+/// Removes the fonts that have finished loading since the last call and returns them (the ID,
+/// the file contents or the error and how long it took to load the font), along with
+/// whether there are fonts that are still loading
+pub(crate) fn take_loaded_fonts() -> (Vec<(String, Result<Vec<u8>, String>, Duration)>, bool) {
+    use std::mem;
+
+    if NUM_LOADING_FONTS.load(Ordering::SeqCst) == 0 {
+        return (Vec::new(), false);
+    }
+
+    let mut loading_fonts = LOADING_FONTS.lock().unwrap();
+    let mut loaded_fonts = Vec::new();
+
+    loading_fonts.retain(|id, font| {
+        let result = match mem::replace(&mut font.data, LoadingFontData::Loading) {
+            LoadingFontData::Loading => return true,
+            LoadingFontData::Loaded(bytes) => Ok(bytes),
+            LoadingFontData::Failed(e) => Err(e),
+        };
+        loaded_fonts.push((id.clone(), result, font.started.elapsed()));
+        false
+    });
+
+    NUM_LOADING_FONTS.store(loading_fonts.len(), Ordering::SeqCst);
+
+    (loaded_fonts, !loading_fonts.is_empty())
+}
+
+/// Returns whether the font with the `id` is loading on a background thread
+pub(crate) fn is_font_loading(id: &str) -> bool {
+    NUM_LOADING_FONTS.load(Ordering::SeqCst) != 0 && LOADING_FONTS.lock().unwrap().contains_key(id)
+}
+
+/// What to draw instead of a font that is loaded via `AppState::add_font_from_file()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum FontDisplayChoice {
+    /// Draw the text with the font
+    UseFont,
+    /// Draw the text with the next font of the `font-family`
+    UseFallback,
+    /// Don't draw the text, the font might be available soon
+    Invisible,
+}
+
+/// Chooses what to draw for the font with the `id`, depending on the `font-display`.
+/// `finished` is the result of the font if it has finished loading (see `AppResources::font_loads`).
+/// Returns `UseFont` for fonts that weren't added via `AppState::add_font_from_file()`.
+pub(crate) fn get_font_display_choice(id: &str, font_display: StyleFontDisplay, finished: Option<&FinishedFontLoad>) -> FontDisplayChoice {
+    match finished {
+        Some(FinishedFontLoad::Added { load_time }) => return resolve_font_display(font_display, *load_time, Some(*load_time)),
+        Some(FinishedFontLoad::Failed(_)) => return FontDisplayChoice::UseFallback,
+        None => { },
+    }
+
+    if NUM_LOADING_FONTS.load(Ordering::SeqCst) == 0 {
+        return FontDisplayChoice::UseFont;
+    }
+
+    let loading_fonts = LOADING_FONTS.lock().unwrap();
+    let font = match loading_fonts.get(id) {
+        Some(font) => font,
+        None => return FontDisplayChoice::UseFont,
+    };
+
+    match font.data {
+        LoadingFontData::Failed(_) => FontDisplayChoice::UseFallback,
+        // until the font is added to the app resources, it can't be drawn
+        LoadingFontData::Loading | LoadingFontData::Loaded(_) => {
+            resolve_font_display(font_display, font.started.elapsed(), None)
+        },
+    }
+}
+
+/// The block period (the text is invisible) and the swap period (the fallback font is used,
+/// but the font is swapped in once it has loaded) of the `font-display`
+fn get_font_display_periods(font_display: StyleFontDisplay) -> (Duration, Option<Duration>) {
+    use css_parser::StyleFontDisplay::*;
+    match font_display {
+        Auto | Block => (Duration::from_secs(3), None),
+        Swap => (Duration::from_secs(0), None),
+        Fallback => (Duration::from_millis(100), Some(Duration::from_secs(3))),
+        Optional => (Duration::from_millis(100), Some(Duration::from_secs(0))),
+    }
+}
+
+/// `elapsed` is the time since the font started loading, `load_time`
+/// how long it took to load the font (`None` if it isn't loaded yet)
+fn resolve_font_display(font_display: StyleFontDisplay, elapsed: Duration, load_time: Option<Duration>) -> FontDisplayChoice {
+    let (block_period, swap_period) = get_font_display_periods(font_display);
+
+    match load_time {
+        Some(load_time) => match swap_period {
+            // the font loaded too late, keep using the fallback font
+            Some(swap_period) if load_time > block_period + swap_period => FontDisplayChoice::UseFallback,
+            _ => FontDisplayChoice::UseFont,
+        },
+        None if elapsed < block_period => FontDisplayChoice::Invisible,
+        None => FontDisplayChoice::UseFallback,
+    }
+}
+
 // Empty test, for some reason codecov doesn't detect any files (and therefore
 // doesn't report codecov % correctly) except if they have at least one test in
 // the file. This is an empty test, which should be updated later on
 #[test]
 fn __codecov_test_font_file() {
 
+}
+
+#[test]
+fn test_resolve_font_display() {
+    use css_parser::StyleFontDisplay::*;
+    use self::FontDisplayChoice::*;
+
+    let ms = Duration::from_millis;
+
+    // still loading
+    assert_eq!(resolve_font_display(Block, ms(50), None), Invisible);
+    assert_eq!(resolve_font_display(Block, ms(4000), None), UseFallback);
+    assert_eq!(resolve_font_display(Swap, ms(0), None), UseFallback);
+    assert_eq!(resolve_font_display(Optional, ms(50), None), Invisible);
+
+    // loaded after one second
+    assert_eq!(resolve_font_display(Swap, ms(2000), Some(ms(1000))), UseFont);
+    assert_eq!(resolve_font_display(Fallback, ms(2000), Some(ms(1000))), UseFont);
+    assert_eq!(resolve_font_display(Optional, ms(2000), Some(ms(1000))), UseFallback);
+    assert_eq!(resolve_font_display(Fallback, ms(5000), Some(ms(4000))), UseFallback);
+}
+
+#[test]
+fn test_get_font_display_choice_of_finished_fonts() {
+    use css_parser::StyleFontDisplay::*;
+    use self::FontDisplayChoice::*;
+
+    let added = FinishedFontLoad::Added { load_time: Duration::from_millis(1000) };
+    let failed = FinishedFontLoad::Failed(String::from("invalid font"));
+
+    assert_eq!(get_font_display_choice("a", Swap, Some(&added)), UseFont);
+    assert_eq!(get_font_display_choice("a", Optional, Some(&added)), UseFallback);
+    assert_eq!(get_font_display_choice("a", Block, Some(&failed)), UseFallback);
 }
\ No newline at end of file
@@ -1,10 +1,23 @@
 //! Module for loading and handling images
 
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{
+    fmt,
+    rc::Rc,
+    cell::{Cell, RefCell},
+    hash::{Hash, Hasher},
+    time::{Duration, Instant},
+    sync::{Mutex, atomic::{AtomicUsize, Ordering}},
+    thread,
+};
 use webrender::api::ImageFormat as WebrenderImageFormat;
 use image::{ImageResult, ImageFormat, guess_format};
-use image::{self, ImageError, DynamicImage, GenericImage};
-use webrender::api::{ImageData, ImageDescriptor, ImageKey};
+use image::{self, ImageError, DynamicImage, GenericImage, RgbaImage};
+use webrender::api::{ImageData, ImageDescriptor, ImageKey, RenderApi, ResourceUpdate, AddImage};
+use {
+    FastHashMap, FastHashSet,
+    dom::Texture,
+    window::ReadOnlyWindow,
+};
 
 static IMAGE_ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
@@ -40,6 +53,22 @@ pub enum ImageType {
 pub(crate) struct ImageInfo {
     pub(crate) key: ImageKey,
     pub(crate) descriptor: ImageDescriptor,
+    /// Alpha of each pixel (row by row), for `pointer-events: painted`.
+    /// `None` if the image is opaque.
+    pub(crate) alpha_mask: Option<Rc<Vec<u8>>>,
+}
+
+impl ImageInfo {
+    /// Returns whether the pixel at (`x`, `y`) isn't fully transparent
+    pub(crate) fn is_painted_at(&self, x: u32, y: u32) -> bool {
+        if x >= self.descriptor.width || y >= self.descriptor.height {
+            return false;
+        }
+        match self.alpha_mask {
+            Some(ref alpha_mask) => alpha_mask[(y * self.descriptor.width + x) as usize] != 0,
+            None => true,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -162,6 +191,14 @@ pub(crate) fn prepare_image(image_decoded: DynamicImage)
     Ok((data, descriptor))
 }
 
+/// Returns the alpha channel of a `BGRA8` image, `None` for opaque images and other formats
+pub(crate) fn get_alpha_mask(format: WebrenderImageFormat, bytes: &[u8]) -> Option<Vec<u8>> {
+    if format != WebrenderImageFormat::BGRA8 || is_image_opaque(format, bytes) {
+        return None;
+    }
+    Some(bytes.chunks(4).map(|pixel| pixel[3]).collect())
+}
+
 pub(crate) fn is_image_opaque(format: WebrenderImageFormat, bytes: &[u8]) -> bool {
     match format {
         WebrenderImageFormat::BGRA8 => {
@@ -190,9 +227,556 @@ pub(crate) fn premultiply(data: &mut [u8]) {
     }
 }
 
+/// How an `AnimatedTexture` should behave once it reaches the last frame
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AnimationMode {
+    /// Start again at the first frame (default)
+    Loop,
+    /// Stop at the last frame
+    Once,
+    /// Play the animation backwards, then forwards again, etc.
+    PingPong,
+}
+
+impl Default for AnimationMode {
+    fn default() -> Self {
+        AnimationMode::Loop
+    }
+}
+
+/// GIFs with a frame delay of 0 are displayed with this delay instead (same as browsers do)
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+struct AnimatedFrame {
+    image: RgbaImage,
+    delay: Duration,
+    /// Texture of this frame, uploaded on the first call to `AnimatedTexture::current_frame`
+    texture: Option<Texture>,
+    /// Webrender image key of this frame, uploaded when the frame is first displayed in the DOM
+    image_key: Option<ImageKey>,
+}
+
+struct AnimatedTextureInner {
+    frames: Vec<AnimatedFrame>,
+    mode: AnimationMode,
+    start_time: Instant,
+    /// Which frame was last pushed into the display list
+    last_displayed_frame: Cell<Option<usize>>,
+}
+
+lazy_static! {
+    /// Image keys of the frames of dropped `AnimatedTexture`s and of evicted lazy images,
+    /// deleted from webrender the next time the resources are updated (see `take_deleted_image_keys()`)
+    static ref DELETED_IMAGE_KEYS: Mutex<Vec<ImageKey>> = Mutex::new(Vec::new());
+}
+
+/// Once the last clone of an `AnimatedTexture` is dropped (i.e. the image was removed
+/// from the DOM and from the data model), the uploaded frames are deleted
+impl Drop for AnimatedTextureInner {
+    fn drop(&mut self) {
+        let image_keys = self.frames.iter().filter_map(|frame| frame.image_key);
+        DELETED_IMAGE_KEYS.lock().unwrap().extend(image_keys);
+    }
+}
+
+/// Returns the image keys of the dropped `AnimatedTexture`s and evicted lazy images, which have to be deleted
+pub(crate) fn take_deleted_image_keys() -> Vec<ImageKey> {
+    ::std::mem::replace(&mut *DELETED_IMAGE_KEYS.lock().unwrap(), Vec::new())
+}
+
+/// Image with multiple frames (animated GIF), which advances the displayed
+/// frame depending on the time since the animation was loaded.
+///
+/// The `AnimatedTexture` is cheap to clone, all clones share the same frames.
+///
+/// **NOTE**: The `image` crate can't decode APNG files yet, so for APNG
+/// (and all other non-GIF formats) only the first frame is loaded.
+#[derive(Clone)]
+pub struct AnimatedTexture {
+    inner: Rc<RefCell<AnimatedTextureInner>>,
+}
+
+impl fmt::Debug for AnimatedTexture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let inner = self.inner.borrow();
+        write!(f, "AnimatedTexture {{ frames: {}, mode: {:?} }}", inner.frames.len(), inner.mode)
+    }
+}
+
+/// Two animated textures are only equal if they are clones of each other,
+/// the frames are not compared
+impl PartialEq for AnimatedTexture {
+    fn eq(&self, other: &AnimatedTexture) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for AnimatedTexture { }
+
+impl Hash for AnimatedTexture {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (&*self.inner as *const RefCell<AnimatedTextureInner> as usize).hash(state);
+    }
+}
+
+impl AnimatedTexture {
+    /// Decodes all frames (and the delays between the frames) of an animated GIF
+    pub fn from_bytes(bytes: &[u8]) -> Result<AnimatedTexture, ImageError> {
+        use image::{ImageDecoder, gif::Decoder as GifDecoder};
+
+        let frames = match guess_format(bytes)? {
+            ImageFormat::GIF => {
+                GifDecoder::new(bytes).into_frames()?.into_iter().map(|frame| {
+                    let delay = Duration::from_millis(frame.delay().to_integer() as u64);
+                    AnimatedFrame {
+                        image: frame.into_buffer(),
+                        delay: if delay == Duration::from_millis(0) { DEFAULT_FRAME_DELAY } else { delay },
+                        texture: None,
+                        image_key: None,
+                    }
+                }).collect::<Vec<AnimatedFrame>>()
+            },
+            _ => {
+                vec![AnimatedFrame {
+                    image: image::load_from_memory(bytes)?.to_rgba(),
+                    delay: DEFAULT_FRAME_DELAY,
+                    texture: None,
+                    image_key: None,
+                }]
+            }
+        };
+
+        if frames.is_empty() {
+            return Err(ImageError::ImageEnd);
+        }
+
+        Ok(AnimatedTexture {
+            inner: Rc::new(RefCell::new(AnimatedTextureInner {
+                frames: frames,
+                mode: AnimationMode::default(),
+                start_time: Instant::now(),
+                last_displayed_frame: Cell::new(None),
+            }))
+        })
+    }
+
+    /// Sets how the animation should behave after the last frame
+    pub fn with_mode(self, mode: AnimationMode) -> Self {
+        self.inner.borrow_mut().mode = mode;
+        self
+    }
+
+    /// Restarts the animation at the first frame
+    pub fn restart(&self) {
+        self.inner.borrow_mut().start_time = Instant::now();
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.inner.borrow().frames.len()
+    }
+
+    /// Index of the frame that should be displayed at the current wall time
+    pub fn current_frame_index(&self) -> usize {
+        let inner = self.inner.borrow();
+        let delays = inner.frames.iter().map(|f| f.delay).collect::<Vec<Duration>>();
+        get_frame_index(&delays, inner.mode, inner.start_time.elapsed())
+    }
+
+    /// Returns the texture for the current frame (uploads the frame to the GPU if necessary)
+    pub fn current_frame(&self, window: &ReadOnlyWindow) -> Texture {
+        use glium::texture::{RawImage2d, texture2d::Texture2d};
+
+        let frame_index = self.current_frame_index();
+        let mut inner = self.inner.borrow_mut();
+        let frame = &mut inner.frames[frame_index];
+
+        if let Some(ref texture) = frame.texture {
+            return texture.clone();
+        }
+
+        let dimensions = frame.image.dimensions();
+        let raw_image = RawImage2d::from_raw_rgba_reversed(&frame.image.clone().into_raw(), dimensions);
+        let texture = Texture::new(Texture2d::new(&*window.inner, raw_image).unwrap());
+        frame.texture = Some(texture.clone());
+        texture
+    }
+
+    /// Returns whether the animation has advanced to a new frame since it was last displayed
+    pub(crate) fn has_new_frame(&self) -> bool {
+        let current_frame = self.current_frame_index();
+        self.inner.borrow().last_displayed_frame.get() != Some(current_frame)
+    }
+
+    /// Returns the image key for the current frame - the frame gets uploaded to
+    /// webrender the first time it is displayed.
+    pub(crate) fn get_current_image_key(&self, render_api: &RenderApi, resource_updates: &mut Vec<ResourceUpdate>)
+    -> Option<ImageKey>
+    {
+        let frame_index = self.current_frame_index();
+        let mut inner = self.inner.borrow_mut();
+        inner.last_displayed_frame.set(Some(frame_index));
+        let frame = &mut inner.frames[frame_index];
+
+        if frame.image_key.is_none() {
+            let (data, descriptor) = prepare_image(DynamicImage::ImageRgba8(frame.image.clone())).ok()?;
+            let key = render_api.generate_image_key();
+            resource_updates.push(ResourceUpdate::AddImage(
+                AddImage { key, descriptor, data, tiling: None }
+            ));
+            frame.image_key = Some(key);
+        }
+
+        frame.image_key
+    }
+}
+
+/// Default distance (in pixels) from the edge of the window at which
+/// images created with `Dom::image_lazy()` start loading
+pub const DEFAULT_LAZY_LOAD_DISTANCE: u32 = 500;
+
+/// Load state of an image created with `Dom::image_lazy()`, see `get_lazy_image_state()`
+#[derive(Debug, Clone)]
+pub enum ImageLoadState {
+    /// The image hasn't come near the visible area of the window yet
+    Pending,
+    /// The image is being loaded and decoded on a background thread
+    Loading,
+    /// The image has been loaded
+    Loaded(Texture),
+    /// The image couldn't be loaded, contains the error message
+    Failed(String),
+}
+
+enum LazyImageData {
+    Pending,
+    Loading,
+    Loaded(RgbaImage),
+    Failed(String),
+}
+
+struct LazyImageEntry {
+    data: LazyImageData,
+    /// Webrender image key, uploaded when the image is first displayed after loading
+    image_key: Option<ImageKey>,
+    /// When the image was last part of a DOM, the least recently used images are evicted first
+    last_used: Instant,
+}
+
+/// Maximum number of loaded lazy images that are kept in memory while they aren't part of
+/// any DOM (i.e. images that were scrolled out of a virtual list), see `evict_lazy_images()`
+const MAX_CACHED_LAZY_IMAGES: usize = 64;
+
+lazy_static! {
+    /// Load state of all images created via `Dom::image_lazy()`, keyed by URL. The entries
+    /// are written to by the loading threads, so the DOM only has to store the URL.
+    static ref LAZY_IMAGES: Mutex<FastHashMap<String, LazyImageEntry>> = Mutex::new(FastHashMap::default());
+}
+
+thread_local! {
+    /// Textures of loaded lazy images, created on the first call to `get_lazy_image_state()`.
+    /// Textures can't be sent across threads, so they can't be stored in `LAZY_IMAGES`.
+    static LAZY_IMAGE_TEXTURES: RefCell<FastHashMap<String, Texture>> = RefCell::new(FastHashMap::default());
+}
+
+/// Image node that is only loaded once it comes near the visible area of the window,
+/// created via `Dom::image_lazy()`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LazyImage {
+    url: String,
+    /// Distance in pixels from the edge of the window at which the image starts loading
+    load_distance: u32,
+    /// Whether the image wasn't loaded yet when the DOM was created,
+    /// i.e. whether the DOM contains the placeholder instead of the image
+    is_placeholder: bool,
+}
+
+impl LazyImage {
+    pub(crate) fn new(url: String, load_distance: u32) -> Self {
+        let mut lazy_images = LAZY_IMAGES.lock().unwrap();
+        let entry = lazy_images.entry(url.clone()).or_insert(LazyImageEntry {
+            data: LazyImageData::Pending,
+            image_key: None,
+            last_used: Instant::now(),
+        });
+        entry.last_used = Instant::now();
+        let is_placeholder = match entry.data {
+            LazyImageData::Loaded(_) => false,
+            _ => true,
+        };
+
+        Self {
+            url: url,
+            load_distance: load_distance,
+            is_placeholder: is_placeholder,
+        }
+    }
+
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub(crate) fn load_distance(&self) -> f32 {
+        self.load_distance as f32
+    }
+
+    pub(crate) fn has_placeholder(&self) -> bool {
+        self.is_placeholder
+    }
+
+    /// Returns whether the image has finished loading after the DOM was created
+    /// (showing the placeholder), so that the DOM has to be re-created
+    pub(crate) fn has_finished_loading(&self) -> bool {
+        self.is_placeholder && match LAZY_IMAGES.lock().unwrap().get(&self.url) {
+            Some(LazyImageEntry { data: LazyImageData::Loaded(_), .. }) => true,
+            _ => false,
+        }
+    }
+
+    /// Starts loading and decoding the image on a background thread, if it isn't loaded already
+    pub(crate) fn start_loading(&self) {
+        {
+            // the entry may have been evicted while the DOM still contained the image
+            let mut lazy_images = LAZY_IMAGES.lock().unwrap();
+            let entry = lazy_images.entry(self.url.clone()).or_insert(LazyImageEntry {
+                data: LazyImageData::Pending,
+                image_key: None,
+                last_used: Instant::now(),
+            });
+            match entry.data {
+                LazyImageData::Pending => { },
+                _ => return,
+            }
+            entry.data = LazyImageData::Loading;
+        }
+
+        let url = self.url.clone();
+        thread::spawn(move || {
+            let data = match load_image_from_url(&url) {
+                Ok(image) => LazyImageData::Loaded(image),
+                Err(e) => {
+                    warn!("could not load lazy image \"{}\": {}", url, e);
+                    LazyImageData::Failed(e)
+                },
+            };
+            if let Some(entry) = LAZY_IMAGES.lock().unwrap().get_mut(&url) {
+                entry.data = data;
+            }
+        });
+    }
+
+    /// Returns the image key of the loaded image, uploads the image to webrender if necessary.
+    /// Returns `None` if the image isn't loaded yet.
+    pub(crate) fn get_image_key(&self, render_api: &RenderApi, resource_updates: &mut Vec<ResourceUpdate>)
+    -> Option<ImageKey>
+    {
+        let mut lazy_images = LAZY_IMAGES.lock().unwrap();
+        let entry = lazy_images.get_mut(&self.url)?;
+
+        if entry.image_key.is_none() {
+            let image = match entry.data {
+                LazyImageData::Loaded(ref image) => image.clone(),
+                _ => return None,
+            };
+            let (data, descriptor) = prepare_image(DynamicImage::ImageRgba8(image)).ok()?;
+            let key = render_api.generate_image_key();
+            resource_updates.push(ResourceUpdate::AddImage(
+                AddImage { key, descriptor, data, tiling: None }
+            ));
+            entry.image_key = Some(key);
+        }
+
+        entry.image_key
+    }
+}
+
+/// Removes the lazy images that aren't part of any DOM (`used_urls`): entries that aren't
+/// loaded are removed right away, loaded images only once more than `MAX_CACHED_LAZY_IMAGES`
+/// are unused (the least recently used first). Evicted images are loaded again once they are
+/// used in a DOM again. Images that are still loading are kept.
+pub(crate) fn evict_lazy_images(used_urls: &FastHashSet<String>) {
+    let mut lazy_images = LAZY_IMAGES.lock().unwrap();
+
+    let mut unused_loaded_images = Vec::new();
+    let mut evicted_urls = Vec::new();
+
+    for (url, entry) in lazy_images.iter() {
+        if used_urls.contains(url) {
+            continue;
+        }
+        match entry.data {
+            LazyImageData::Loading => { },
+            LazyImageData::Loaded(_) => unused_loaded_images.push((entry.last_used, url.clone())),
+            LazyImageData::Pending | LazyImageData::Failed(_) => evicted_urls.push(url.clone()),
+        }
+    }
+
+    if unused_loaded_images.len() > MAX_CACHED_LAZY_IMAGES {
+        unused_loaded_images.sort_by_key(|&(last_used, _)| last_used);
+        let evicted_count = unused_loaded_images.len() - MAX_CACHED_LAZY_IMAGES;
+        evicted_urls.extend(unused_loaded_images.into_iter().take(evicted_count).map(|(_, url)| url));
+    }
+
+    for url in evicted_urls {
+        if let Some(image_key) = lazy_images.remove(&url).and_then(|entry| entry.image_key) {
+            DELETED_IMAGE_KEYS.lock().unwrap().push(image_key);
+        }
+        LAZY_IMAGE_TEXTURES.with(|textures| { textures.borrow_mut().remove(&url); });
+    }
+}
+
+/// Returns the load state of an image created with `Dom::image_lazy()`.
+/// Returns `None` if no lazy image with this URL has been created yet.
+pub fn get_lazy_image_state(url: &str, window: &ReadOnlyWindow) -> Option<ImageLoadState> {
+    use glium::texture::{RawImage2d, texture2d::Texture2d};
+
+    let lazy_images = LAZY_IMAGES.lock().unwrap();
+
+    let state = match lazy_images.get(url)?.data {
+        LazyImageData::Pending => ImageLoadState::Pending,
+        LazyImageData::Loading => ImageLoadState::Loading,
+        LazyImageData::Failed(ref e) => ImageLoadState::Failed(e.clone()),
+        LazyImageData::Loaded(ref image) => LAZY_IMAGE_TEXTURES.with(|textures| {
+            let mut textures = textures.borrow_mut();
+            let texture = textures.entry(url.to_string()).or_insert_with(|| {
+                let dimensions = image.dimensions();
+                let raw_image = RawImage2d::from_raw_rgba_reversed(&image.clone().into_raw(), dimensions);
+                Texture::new(Texture2d::new(&*window.inner, raw_image).unwrap())
+            });
+            ImageLoadState::Loaded(texture.clone())
+        }),
+    };
+
+    Some(state)
+}
+
+/// Loads and decodes an image from a file path or a `file://` URL.
+///
+/// **NOTE**: There is no HTTP client in azul, so other URL schemes are not supported.
+fn load_image_from_url(url: &str) -> Result<RgbaImage, String> {
+    const FILE_SCHEME: &str = "file://";
+
+    let path = if url.starts_with(FILE_SCHEME) {
+        &url[FILE_SCHEME.len()..]
+    } else if url.contains("://") {
+        return Err(format!("unsupported URL scheme: {}", url));
+    } else {
+        url
+    };
+
+    let bytes = ::std::fs::read(path).map_err(|e| format!("{}", e))?;
+    image::load_from_memory(&bytes).map(|image| image.to_rgba()).map_err(|e| format!("{}", e))
+}
+
+/// Calculates which frame to display after `elapsed` time has passed since the start of the animation
+fn get_frame_index(delays: &[Duration], mode: AnimationMode, elapsed: Duration) -> usize {
+    fn to_millis(d: Duration) -> u64 {
+        d.as_secs() * 1000 + d.subsec_nanos() as u64 / 1_000_000
+    }
+
+    fn frame_at(delays: &[Duration], mut time: u64) -> usize {
+        for (idx, delay) in delays.iter().enumerate() {
+            let delay = to_millis(*delay);
+            if time < delay {
+                return idx;
+            }
+            time -= delay;
+        }
+        delays.len().saturating_sub(1)
+    }
+
+    let total: u64 = delays.iter().map(|d| to_millis(*d)).sum();
+    if total == 0 {
+        return 0;
+    }
+
+    let elapsed = to_millis(elapsed);
+
+    match mode {
+        AnimationMode::Loop => frame_at(delays, elapsed % total),
+        AnimationMode::Once => frame_at(delays, elapsed.min(total - 1)),
+        AnimationMode::PingPong => {
+            let time = elapsed % (total * 2);
+            if time < total {
+                frame_at(delays, time)
+            } else {
+                delays.len() - 1 - frame_at(delays, time - total)
+            }
+        }
+    }
+}
+
 #[test]
 fn test_premultiply() {
     let mut color = [255, 0, 0, 127];
     premultiply(&mut color);
     assert_eq!(color, [127, 0, 0, 127]);
+}
+
+#[test]
+fn test_get_alpha_mask() {
+    let bytes = [0, 0, 255, 255, 0, 0, 0, 0];
+    assert_eq!(get_alpha_mask(WebrenderImageFormat::BGRA8, &bytes), Some(vec![255, 0]));
+    assert_eq!(get_alpha_mask(WebrenderImageFormat::BGRA8, &[0, 0, 255, 255]), None);
+    assert_eq!(get_alpha_mask(WebrenderImageFormat::R8, &bytes), None);
+}
+
+#[test]
+fn test_get_frame_index() {
+    let delays = [Duration::from_millis(100); 3];
+    assert_eq!(get_frame_index(&delays, AnimationMode::Loop, Duration::from_millis(150)), 1);
+    assert_eq!(get_frame_index(&delays, AnimationMode::Loop, Duration::from_millis(350)), 0);
+    assert_eq!(get_frame_index(&delays, AnimationMode::Once, Duration::from_millis(350)), 2);
+    assert_eq!(get_frame_index(&delays, AnimationMode::PingPong, Duration::from_millis(350)), 2);
+    assert_eq!(get_frame_index(&delays, AnimationMode::PingPong, Duration::from_millis(450)), 1);
+}
+
+#[test]
+fn test_load_image_from_url_unsupported_scheme() {
+    assert_eq!(
+        load_image_from_url("https://example.com/image.png").err(),
+        Some(String::from("unsupported URL scheme: https://example.com/image.png"))
+    );
+}
+
+#[test]
+fn test_dropped_animated_texture_deletes_its_frames() {
+    use webrender::api::IdNamespace;
+
+    let image_key = ImageKey::new(IdNamespace(::std::u32::MAX), 1);
+    let animated_texture = AnimatedTexture {
+        inner: Rc::new(RefCell::new(AnimatedTextureInner {
+            frames: vec![AnimatedFrame {
+                image: RgbaImage::new(1, 1),
+                delay: DEFAULT_FRAME_DELAY,
+                texture: None,
+                image_key: Some(image_key),
+            }],
+            mode: AnimationMode::default(),
+            start_time: Instant::now(),
+            last_displayed_frame: Cell::new(None),
+        })),
+    };
+
+    let clone = animated_texture.clone();
+    drop(animated_texture);
+    assert!(!take_deleted_image_keys().contains(&image_key));
+    drop(clone);
+    assert!(take_deleted_image_keys().contains(&image_key));
+}
+
+#[test]
+fn test_evict_lazy_images() {
+    let used_url = String::from("test_evict_lazy_images/used.png");
+    let unused_url = String::from("test_evict_lazy_images/unused.png");
+
+    LazyImage::new(used_url.clone(), DEFAULT_LAZY_LOAD_DISTANCE);
+    LazyImage::new(unused_url.clone(), DEFAULT_LAZY_LOAD_DISTANCE);
+
+    let mut used_urls = FastHashSet::default();
+    used_urls.insert(used_url.clone());
+    evict_lazy_images(&used_urls);
+
+    let lazy_images = LAZY_IMAGES.lock().unwrap();
+    assert!(lazy_images.contains_key(&used_url));
+    assert!(!lazy_images.contains_key(&unused_url));
 }
\ No newline at end of file
@@ -0,0 +1,211 @@
+//! Snapshots of the DOM of a window, for debugging layout and styling problems
+
+use std::collections::{HashMap, BTreeMap};
+use webrender::api::LayoutRect;
+use cassowary::{Constraint, Variable, RelationalOperator};
+use {
+    FastHashMap,
+    css::CssDeclaration,
+    dom::NodeData,
+    id_tree::{Arena, NodeId},
+    traits::Layout,
+    ui_description::UiDescription,
+    window::{UiSolver, WindowId},
+};
+
+/// The DOM of a window, as it was constructed by `Layout::layout()` and styled
+/// by the CSS of the window. Returned by `App::inspect_dom()`.
+///
+/// With the `serialize` feature enabled, this can be serialized (i.e. to JSON, via
+/// `serde_json`) in order to hand it to external debugging tools.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct DomInspectorResult {
+    /// The window that was inspected
+    pub window_id: WindowId,
+    /// The root node of the DOM, `None` if the DOM is empty
+    pub root: Option<InspectedNode>,
+}
+
+/// A single node of the `DomInspectorResult`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct InspectedNode {
+    /// Index of the node in the DOM arena
+    pub node_id: usize,
+    /// The CSS type of the node, i.e. `"div"` or `"p"`
+    pub node_type: String,
+    /// `.myclass .otherclass`
+    pub css_classes: Vec<String>,
+    /// The CSS properties that apply to this node after the cascade (the
+    /// last declaration wins), as `"background-color" => "BackgroundColor(...)"`.
+    /// The values are the debug representation of the parsed property.
+    pub computed_css: HashMap<String, String>,
+    /// The values of the CSS counters (`counter-reset` / `counter-increment`) in
+    /// scope of this node, from the outermost to the innermost counter
+    pub counters: Vec<(String, i32)>,
+    /// The position and size of the node, from the last solved layout
+    /// (`None` if the node wasn't laid out yet)
+    pub layout_rect: Option<LayoutRect>,
+    pub children: Vec<InspectedNode>,
+}
+
+impl DomInspectorResult {
+    pub(crate) fn new<T: Layout>(window_id: WindowId, ui_description: &UiDescription<T>, ui_solver: &UiSolver<T>) -> Self {
+        let arena = ui_description.ui_descr_arena.borrow();
+        let counters = ui_description.collect_counters();
+        Self {
+            window_id: window_id,
+            root: ui_description.ui_descr_root.map(|root| inspect_node(root, &*arena, ui_description, ui_solver, &counters)),
+        }
+    }
+}
+
+fn inspect_node<T: Layout>(
+    node_id: NodeId,
+    arena: &Arena<NodeData<T>>,
+    ui_description: &UiDescription<T>,
+    ui_solver: &UiSolver<T>,
+    counters: &BTreeMap<NodeId, Vec<(String, i32)>>)
+-> InspectedNode
+{
+    let node_data = &arena[node_id].data;
+    InspectedNode {
+        node_id: node_id.index.get(),
+        node_type: node_data.node_type.get_css_id().to_string(),
+        css_classes: node_data.classes.clone(),
+        computed_css: get_computed_css(node_id, ui_description),
+        counters: counters.get(&node_id).cloned().unwrap_or_default(),
+        layout_rect: ui_solver.query_bounds_of_rect(node_id),
+        children: node_id.children(arena).map(|child| inspect_node(child, arena, ui_description, ui_solver, counters)).collect(),
+    }
+}
+
+fn get_computed_css<T: Layout>(node_id: NodeId, ui_description: &UiDescription<T>)
+-> HashMap<String, String>
+{
+    let mut computed_css = HashMap::new();
+
+    let styled_node = match ui_description.styled_nodes.get(&node_id) {
+        Some(s) => s,
+        None => return computed_css,
+    };
+
+    for &(ref key, ref declaration) in &styled_node.css_constraints.list {
+        let property = match *declaration {
+            CssDeclaration::Static(ref property) => property,
+            CssDeclaration::Dynamic(ref dynamic) => ui_description.dynamic_css_overrides
+                .get(&dynamic.dynamic_id).unwrap_or(&dynamic.default),
+        };
+        computed_css.insert(key.to_string(), format!("{:?}", property));
+    }
+
+    computed_css
+}
+
+/// A constraint of the layout solver of a window, returned by `App::debug_constraints()`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintDebugInfo {
+    /// The constraint, with the variables named after their node, i.e. `"Node(42).width >= 100"`
+    pub description: String,
+    /// Strength of the constraint, see `cassowary::strength`
+    pub strength: f64,
+    /// Whether the last solved layout fulfills the constraint
+    pub is_satisfied: bool,
+    /// How far the last solved layout is off from fulfilling the constraint (`0.0` if it is satisfied)
+    pub violation: f64,
+}
+
+/// Floating-point tolerance for `ConstraintDebugInfo::is_satisfied`
+const CONSTRAINT_EPSILON: f64 = 1e-6;
+
+impl ConstraintDebugInfo {
+    /// `variable_names` maps the variables to their name (variables without a name
+    /// are printed as `Variable(_)`), `get_value` returns the solved value of a variable
+    pub(crate) fn new<F: Fn(Variable) -> f64>(constraint: &Constraint, variable_names: &FastHashMap<Variable, String>, get_value: F) -> Self {
+        let expression = constraint.expr();
+
+        let mut description = String::new();
+        for (term_idx, term) in expression.terms.iter().enumerate() {
+            let name = variable_names.get(&term.variable).cloned().unwrap_or_else(|| String::from("Variable(_)"));
+            let coefficient = if term_idx == 0 {
+                if term.coefficient < 0.0 { description.push('-'); }
+                term.coefficient.abs()
+            } else {
+                description.push_str(if term.coefficient < 0.0 { " - " } else { " + " });
+                term.coefficient.abs()
+            };
+            if coefficient != 1.0 {
+                description.push_str(&format!("{} * ", coefficient));
+            }
+            description.push_str(&name);
+        }
+
+        let operator = match constraint.op() {
+            RelationalOperator::LessOrEqual => "<=",
+            RelationalOperator::Equal => "==",
+            RelationalOperator::GreaterOrEqual => ">=",
+        };
+        // the expression is `terms + constant (op) 0`, print it as `terms (op) -constant`
+        description.push_str(&format!(" {} {}", operator, -expression.constant + 0.0));
+
+        let value = expression.terms.iter().map(|term| term.coefficient * get_value(term.variable)).sum::<f64>() + expression.constant;
+        let violation = match constraint.op() {
+            RelationalOperator::LessOrEqual => value.max(0.0),
+            RelationalOperator::Equal => value.abs(),
+            RelationalOperator::GreaterOrEqual => (-value).max(0.0),
+        };
+
+        Self {
+            description: description,
+            strength: constraint.strength(),
+            is_satisfied: violation < CONSTRAINT_EPSILON,
+            violation: violation,
+        }
+    }
+}
+
+#[test]
+fn test_constraint_debug_info() {
+    use cassowary::{WeightedRelation::*, strength::{REQUIRED, STRONG}};
+
+    let width = Variable::new();
+    let parent_width = Variable::new();
+    let mut variable_names = FastHashMap::default();
+    variable_names.insert(width, String::from("Node(42).width"));
+    variable_names.insert(parent_width, String::from("Node(3).width"));
+
+    let get_value = |variable: Variable| if variable == width { 50.0 } else { 200.0 };
+
+    let info = ConstraintDebugInfo::new(&(width |GE(REQUIRED)| 100.0), &variable_names, &get_value);
+    assert_eq!(info.description, "Node(42).width >= 100");
+    assert_eq!(info.strength, REQUIRED);
+    assert!(!info.is_satisfied);
+    assert_eq!(info.violation, 50.0);
+
+    let info = ConstraintDebugInfo::new(&(width |EQ(STRONG)| parent_width * 0.25), &variable_names, &get_value);
+    assert_eq!(info.description, "Node(42).width - 0.25 * Node(3).width == 0");
+    assert!(info.is_satisfied);
+}
+
+#[test]
+fn test_get_computed_css() {
+    use dom::TestLayout;
+    use css_parser::ParsedCssProperty;
+    use ui_description::StyledNode;
+
+    let node_id = NodeId::new(0);
+    let mut ui_description = UiDescription::<TestLayout>::default();
+    let mut styled_node = StyledNode::default();
+    for &(key, value) in &[("opacity", "0.5"), ("visibility", "hidden"), ("opacity", "1")] {
+        let property = ParsedCssProperty::from_kv(key, value).unwrap();
+        styled_node.css_constraints.list.push((key.into(), CssDeclaration::Static(property)));
+    }
+    ui_description.styled_nodes.insert(node_id, styled_node);
+
+    let computed_css = get_computed_css(node_id, &ui_description);
+    assert_eq!(computed_css.len(), 2);
+    assert_eq!(computed_css["opacity"], format!("{:?}", ParsedCssProperty::from_kv("opacity", "1").unwrap()));
+    assert!(computed_css["visibility"].contains("Hidden"));
+    assert!(get_computed_css(NodeId::new(1), &ui_description).is_empty());
+}
@@ -38,6 +38,8 @@ pub extern crate image;
 
 #[macro_use]
 extern crate lazy_static;
+#[macro_use]
+extern crate log;
 extern crate palette;
 extern crate euclid;
 extern crate lyon;
@@ -52,9 +54,18 @@ extern crate unicode_normalization;
 extern crate harfbuzz_rs;
 extern crate tinyfiledialogs;
 extern crate clipboard2;
+extern crate raqote;
+extern crate bincode;
+#[cfg(feature = "serialize")]
+#[macro_use]
+extern crate serde_derive;
+#[cfg(feature = "serialize")]
+extern crate serde;
 
 #[cfg(not(target_os = "linux"))]
 extern crate nfd;
+#[cfg(target_os = "windows")]
+extern crate winapi;
 
 /// DOM / HTML node handling
 pub mod dom;
@@ -68,6 +79,8 @@ pub mod task;
 pub mod svg;
 /// Built-in widgets
 pub mod widgets;
+/// CPU-rasterized 2D drawing canvas
+pub mod canvas;
 /// Bindings to the native file-chooser, color picker, etc. dialogs
 pub mod dialogs;
 /// Global application (Initialization starts here)
@@ -108,8 +121,14 @@ mod menu;
 mod compositor;
 // /// Platform extensions (non-portable window extensions for Win32, Wayland, X11, Cocoa)
 // mod platform_ext;
+/// Fallback logger for azul-internal diagnostics
+mod logging;
 /// Module for caching long texts (including their layout / character positions) across multiple frames
 mod text_cache;
+/// DOM snapshots for debugging, see `App::inspect_dom()`
+mod inspector;
+/// On-disk cache for the compiled WebRender shaders
+mod shader_cache;
 
 /// Faster implementation of a HashMap
 type FastHashMap<T, U> = ::std::collections::HashMap<T, U, ::std::hash::BuildHasherDefault<::twox_hash::XxHash>>;
@@ -117,25 +136,43 @@ type FastHashSet<T> = ::std::collections::HashSet<T, ::std::hash::BuildHasherDef
 
 /// Quick exports of common types
 pub mod prelude {
-    pub use app::App;
+    pub use app::{App, AppEvent, AppRunResult};
     pub use app_state::AppState;
-    pub use css::{Css, FakeCss};
-    pub use dom::{Dom, NodeType, Callback, On, UpdateScreen};
+    pub use css::{Css, FakeCss, CssMediaType};
+    pub use dom::{Dom, NodeType, Callback, On, UpdateScreen, IFrame};
     pub use traits::{Layout, ModifyAppState};
     pub use window::{MonitorIter, Window, WindowCreateOptions, WindowId,
                      MouseMode, UpdateBehaviour, UpdateMode,
-                     WindowMonitorTarget, RendererType, WindowEvent, WindowInfo, ReadOnlyWindow};
-    pub use window_state::WindowState;
-    pub use images::ImageType;
+                     WindowMonitorTarget, RendererType, WindowEvent, DragAndDrop, WindowInfo, ReadOnlyWindow, DownloadError, RenderCallback, WindowRegistry,
+                     SimulatedWindowOps};
+    pub use id_tree::NodeId;
+    pub use window_state::{WindowState, MouseState, AaMode, PinchGesture, RotateGesture, TouchId, TouchPoint, MultiTouchState};
+    pub use inspector::{DomInspectorResult, InspectedNode, ConstraintDebugInfo};
+    pub use font::FontLoadState;
+    pub use text_layout::FontVerticalMetrics;
+    pub use cache::DomTreeCache;
+    pub use images::{ImageType, AnimatedTexture, AnimationMode, LazyImage, ImageLoadState, get_lazy_image_state};
     pub use css_parser::{
         ParsedCssProperty, BorderRadius, BackgroundColor, TextColor,
         BorderWidths, BorderDetails, Background, FontSize,
-        FontFamily, TextOverflowBehaviour, TextOverflowBehaviourInner, TextAlignmentHorz,
+        FontFamily, StyleIconFont, StyleFontDisplay, TextOverflowBehaviour, TextOverflowBehaviourInner, TextAlignmentHorz,
         BoxShadowPreDisplayItem, LayoutWidth, LayoutHeight,
         LayoutMinWidth, LayoutMinHeight, LayoutMaxWidth,
         LayoutMaxHeight, LayoutWrap, LayoutDirection,
         LayoutJustifyContent, LayoutAlignItems, LayoutAlignContent,
         LinearGradientPreInfo, RadialGradientPreInfo, CssImageId,
+        StyleFilter, Filter, BackdropFilter, CaretColor, CaretShape, StyleUserSelect, StylePointerEvents, StyleAppearance, StyleBackgroundClip,
+        LayoutResize, LayoutColumnCount, LayoutColumnWidth, LayoutColumnGap, StyleColumnRule,
+        LayoutWritingMode, LogicalSize, StyleDirection, BoxSide, LogicalSide, LogicalBoxProperty, StyleBorderSide,
+        ScrollbarThumbColor, ScrollbarTrackColor, LayoutScrollbarWidth,
+        LetterSpacing, WordSpacing, StyleWordBreak, StyleOverflowWrap,
+        StyleTextDecoration, TextDecorationLine, TextDecorationStyle, TextShadow, TextShadowItem,
+        FontFeatureSettings, FontFeature, StyleOpacity, StyleVisibility, StyleAll, StyleWillChange, StyleIsolation, StyleMixBlendMode, StyleScrollBehavior,
+        StyleScrollSnapType, StyleScrollSnapAlign, ScrollSnapAxis, ScrollSnapStrictness, StyleScrollMargin, StyleScrollPadding,
+        StyleObjectFit, StyleObjectPosition, StyleImageRendering, StyleBorderImage, BorderImageRepeat,
+        StyleCounterReset, StyleCounterIncrement, StyleContent, ContentPart, StyleListStyleType, StyleListStylePosition, StyleListStyleImage,
+        LayoutMargin, LayoutMarginValue, LayoutPadding, LayoutPaddingValue, LayoutDisplay, LayoutFloat, LayoutClear, LayoutTableLayout, StyleVerticalAlign,
+        StyleShapeOutside, ShapeLength,
 
         LayoutPixel, TypedSize2D, BoxShadowClipMode, ColorU, ColorF, LayoutVector2D,
         Gradient, SideOffsets2D, RadialGradient, LayoutPoint, LayoutSize,
@@ -143,6 +180,11 @@ pub mod prelude {
     };
 
     pub use svg::{SvgLayerId, SvgLayer, SvgCache};
+    pub use canvas::{Canvas2d, Canvas2dFont, Path2d, Transform2d};
+    pub use webrender::api::{PipelineId, ExternalImageId};
+    pub use compositor::{Compositor, TextureUpdateSender};
+    #[cfg(unix)]
+    pub use compositor::SharedMemoryExternalImage;
 }
 
 /// Re-exports of errors
@@ -153,12 +195,16 @@ pub mod errors {
         PercentageParseError,
         CssBackgroundParseError, CssColorParseError, CssBorderRadiusParseError,
         CssDirectionParseError, CssGradientStopParseError, CssShapeParseError,
+        CssFilterParseError, CssFontFeatureParseError, CssMarginParseError, CssPaddingParseError,
+        CssBorderImageParseError, CssCounterParseError, CssContentParseError,
+        CssShapeOutsideParseError,
     };
     pub use simplecss::Error as CssSyntaxError;
     pub use css::{CssParseError, DynamicCssParseError};
     pub use svg::SvgParseError;
     pub use font::FontError;
-    pub use window::WindowCreateError;
+    pub use window::{WindowCreateError, CaptureError};
+    pub use cache::CacheError;
     pub use image::ImageError;
     // TODO: re-export the sub-types of ClipboardError!
     pub use clipboard2::ClipboardError;
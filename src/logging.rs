@@ -0,0 +1,40 @@
+//! Minimal logger that prints azul-internal messages to stderr.
+//!
+//! azul uses the `log` crate for all diagnostics - if the application installs its
+//! own logger (`env_logger`, etc.), azul leaves it alone and this logger is not used.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use log::{self, Log, Metadata, Record, LevelFilter};
+
+static AZUL_LOGGER: AzulLogger = AzulLogger;
+/// Whether the `AZUL_LOGGER` was installed as the global logger (or if the user
+/// has installed his own logger before the first window was created)
+static AZUL_LOGGER_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+struct AzulLogger;
+
+impl Log for AzulLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}: {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) { }
+}
+
+/// Installs the azul logger with the given level, if no other logger is installed yet
+pub(crate) fn set_up_logging(level: LevelFilter) {
+    if log::set_logger(&AZUL_LOGGER).is_ok() {
+        AZUL_LOGGER_INSTALLED.store(true, Ordering::SeqCst);
+    }
+
+    // Don't override the level of a user-installed logger
+    if AZUL_LOGGER_INSTALLED.load(Ordering::SeqCst) {
+        log::set_max_level(level);
+    }
+}
@@ -3,6 +3,7 @@ use css_parser::FontSize;
 use text_layout::RUSTTYPE_SIZE_HACK;
 use text_layout::PX_TO_PT;
 use text_layout::split_text_into_words;
+use text_layout::FontVerticalMetrics;
 use webrender::api::Epoch;
 use dom::Texture;
 use text_cache::TextCache;
@@ -12,7 +13,7 @@ use webrender::api::{ImageKey, FontKey, FontInstanceKey};
 use FastHashMap;
 use std::io::Read;
 use images::{ImageState, ImageType};
-use font::{FontState, FontError};
+use font::{FontState, FontError, FontLoadState, FinishedFontLoad};
 use image::{self, ImageError, DynamicImage, GenericImage};
 use webrender::api::{ImageData, ImageDescriptor, ImageFormat};
 use std::collections::hash_map::Entry::*;
@@ -52,6 +53,8 @@ pub(crate) struct AppResources<'a> {
     // the font instance key (if there is any). If there is no font instance key,
     // we first need to create one.
     pub(crate) fonts: FastHashMap<FontKey, FastHashMap<Au, FontInstanceKey>>,
+    /// Fonts loaded via `AppState::add_font_from_file()` that have finished loading
+    pub(crate) font_loads: FastHashMap<String, FinishedFontLoad>,
     /// Stores long texts across frames
     pub(crate) text_cache: TextCache,
     /// Keyboard clipboard storage and retrieval functionality
@@ -64,6 +67,7 @@ impl<'a> Default for AppResources<'a> {
             css_ids_to_image_ids: FastHashMap::default(),
             fonts: FastHashMap::default(),
             font_data: FastHashMap::default(),
+            font_loads: FastHashMap::default(),
             images: FastHashMap::default(),
             text_cache: TextCache::default(),
             clipboard: SystemClipboard::new().unwrap(),
@@ -153,6 +157,42 @@ impl<'a> AppResources<'a> {
         }
     }
 
+    /// Adds the fonts that have finished loading on a background thread (see
+    /// `AppState::add_font_from_file()`). Returns whether the windows have to be re-layouted,
+    /// i.e. a font has finished loading or a font is still loading (the `font-display`
+    /// periods might end).
+    pub(crate) fn add_loaded_fonts(&mut self) -> bool {
+        use font;
+
+        let (loaded_fonts, is_loading) = font::take_loaded_fonts();
+        let has_new_fonts = !loaded_fonts.is_empty();
+
+        for (id, result, load_time) in loaded_fonts {
+            let finished = match result.and_then(|data| self.add_font(id.clone(), &mut &data[..]).map_err(|e| format!("{}", e))) {
+                Ok(_) => FinishedFontLoad::Added { load_time: load_time },
+                Err(e) => {
+                    warn!("could not add font \"{}\": {}", id, e);
+                    FinishedFontLoad::Failed(e)
+                },
+            };
+            self.font_loads.insert(id, finished);
+        }
+
+        has_new_fonts || is_loading
+    }
+
+    /// See `AppState::get_font_load_state()`
+    pub(crate) fn get_font_load_state(&self, id: &str) -> Option<FontLoadState> {
+        use font;
+
+        match self.font_loads.get(id) {
+            Some(FinishedFontLoad::Added { .. }) => Some(FontLoadState::Loaded),
+            Some(FinishedFontLoad::Failed(e)) => Some(FontLoadState::Failed(e.clone())),
+            None if font::is_font_loading(id) => Some(FontLoadState::Loading),
+            None => None,
+        }
+    }
+
     /// Checks if a font is currently registered and ready-to-use
     pub(crate) fn has_font<S: Into<String>>(&mut self, id: S)
         -> bool
@@ -165,7 +205,9 @@ impl<'a> AppResources<'a> {
         -> Option<()>
     {
         // TODO: can fonts that haven't been uploaded yet be deleted?
-        match self.font_data.get_mut(&ExternalFont(id.into())) {
+        let id = id.into();
+        self.font_loads.remove(&id);
+        match self.font_data.get_mut(&ExternalFont(id)) {
             None => None,
             Some(v) => {
                 let to_delete_font_key = match v.1 {
@@ -180,6 +222,19 @@ impl<'a> AppResources<'a> {
         }
     }
 
+    /// See `AppState::get_font_metrics()`
+    pub(crate) fn get_font_metrics(&self, font_family: &str, font_size: f32)
+        -> Option<FontVerticalMetrics>
+    {
+        use text_layout;
+
+        let font_family = css_parser::parse_css_font_family(font_family).ok()?;
+        font_family.fonts.iter()
+            .filter_map(|font| self.font_data.get(font))
+            .next()
+            .map(|&(ref font, _)| text_layout::get_font_metrics(font, font_size))
+    }
+
     pub(crate) fn add_text_uncached<S: Into<String>>(&mut self, text: S)
     -> TextId
     {
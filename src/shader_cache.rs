@@ -0,0 +1,132 @@
+//! Persists the compiled shaders of WebRender on disk, see `WindowCreateOptions::shader_cache_path`.
+//!
+//! Compiling the shaders takes 200 - 1000ms on startup. WebRender hands the compiled program
+//! binaries to the `ProgramCacheObserver`, which writes them to a file when the renderer is
+//! shut down. On the next startup, the binaries are loaded back into the `ProgramCache`.
+//!
+//! Cache file layout: `CACHE_FILE_MAGIC`, the version hash (u64, little endian), then the
+//! bincode-serialized `Vec<ProgramBinary>`. The cache is discarded if the version hash doesn't
+//! match, i.e. after a GPU driver update.
+
+use std::{
+    rc::Rc,
+    sync::Arc,
+    cell::{Cell, RefCell},
+    fs::File,
+    io::{self, Read, Write, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    hash::{Hash, Hasher},
+};
+use webrender::{ProgramCache, ProgramCacheObserver, ProgramBinary};
+use gleam::gl::{self, Gl};
+use twox_hash::XxHash;
+use bincode;
+
+/// Magic bytes at the start of every cache file
+const CACHE_FILE_MAGIC: &[u8; 8] = b"AZULSHDR";
+
+/// Writes the program binaries to the cache file when it is dropped (when the renderer shuts down)
+struct DiskProgramCacheObserver {
+    path: PathBuf,
+    version_hash: u64,
+    binaries: RefCell<Vec<Arc<ProgramBinary>>>,
+    /// Whether programs were added or rejected since the cache file was loaded
+    is_dirty: Cell<bool>,
+}
+
+impl ProgramCacheObserver for DiskProgramCacheObserver {
+    fn notify_binary_added(&self, program_binary: &Arc<ProgramBinary>) {
+        self.binaries.borrow_mut().push(program_binary.clone());
+        self.is_dirty.set(true);
+    }
+
+    fn notify_program_binary_failed(&self, program_binary: &Arc<ProgramBinary>) {
+        // The driver rejected the binary, it will be recompiled (and added again)
+        self.binaries.borrow_mut().retain(|binary| !Arc::ptr_eq(binary, program_binary));
+        self.is_dirty.set(true);
+    }
+}
+
+impl Drop for DiskProgramCacheObserver {
+    fn drop(&mut self) {
+        if !self.is_dirty.get() {
+            return;
+        }
+        if let Err(e) = save_program_binaries(&self.path, self.version_hash, &self.binaries.borrow()) {
+            warn!("could not write the shader cache to {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Creates a `ProgramCache` that is backed by the cache file at `path`
+/// (if the file exists and was created by the same GPU driver + azul version)
+pub(crate) fn create_disk_program_cache(path: &Path, gl: &Gl) -> Rc<ProgramCache> {
+
+    let version_hash = get_version_hash(gl);
+
+    let binaries = match load_program_binaries(path, version_hash) {
+        Ok(binaries) => binaries.into_iter().map(Arc::new).collect(),
+        Err(e) => {
+            // The cache file doesn't exist on the first startup
+            if e.kind() != io::ErrorKind::NotFound {
+                info!("discarding the shader cache at {:?}: {}", path, e);
+            }
+            Vec::new()
+        }
+    };
+
+    let observer = DiskProgramCacheObserver {
+        path: path.to_path_buf(),
+        version_hash: version_hash,
+        binaries: RefCell::new(binaries),
+        is_dirty: Cell::new(false),
+    };
+
+    let loaded_binaries = observer.binaries.borrow().clone();
+    let program_cache = ProgramCache::new(Some(Box::new(observer)));
+    for binary in loaded_binaries {
+        program_cache.load_program_binary(binary);
+    }
+
+    program_cache
+}
+
+/// Hash of the GPU driver and the azul version - azul pins the WebRender revision,
+/// so the azul version also invalidates the cache when WebRender is updated.
+fn get_version_hash(gl: &Gl) -> u64 {
+    let mut hasher = XxHash::default();
+    gl.get_string(gl::VENDOR).hash(&mut hasher);
+    gl.get_string(gl::RENDERER).hash(&mut hasher);
+    gl.get_string(gl::VERSION).hash(&mut hasher);
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    hasher.finish()
+}
+
+fn load_program_binaries(path: &Path, version_hash: u64) -> io::Result<Vec<ProgramBinary>> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut header = [0_u8; 16];
+    reader.read_exact(&mut header)?;
+
+    if &header[..8] != CACHE_FILE_MAGIC {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a shader cache file"));
+    }
+    let file_version_hash = header[8..].iter().rev().fold(0_u64, |hash, byte| (hash << 8) | *byte as u64);
+    if file_version_hash != version_hash {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "the GPU driver or azul version has changed"));
+    }
+
+    bincode::deserialize_from(reader).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn save_program_binaries(path: &Path, version_hash: u64, binaries: &[Arc<ProgramBinary>]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    let version_bytes = (0..8).map(|byte| (version_hash >> (byte * 8)) as u8).collect::<Vec<u8>>();
+    writer.write_all(CACHE_FILE_MAGIC)?;
+    writer.write_all(&version_bytes)?;
+
+    let binaries = binaries.iter().map(|binary| &**binary).collect::<Vec<&ProgramBinary>>();
+    bincode::serialize_into(&mut writer, &binaries).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    writer.flush()
+}
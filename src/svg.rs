@@ -495,6 +495,26 @@ impl From<IoError> for SvgParseError {
     }
 }
 
+impl fmt::Display for SvgParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::SvgParseError::*;
+        match self {
+            FailedToParseSvg(e) => write!(f, "Could not parse the SVG: {:?}", e),
+            IoError(e) => write!(f, "Could not read the SVG: {}", e),
+        }
+    }
+}
+
+impl ::std::error::Error for SvgParseError {
+    fn source(&self) -> Option<&(::std::error::Error + 'static)> {
+        match self {
+            SvgParseError::IoError(e) => Some(e),
+            // usvg errors don't implement std::error::Error
+            SvgParseError::FailedToParseSvg(_) => None,
+        }
+    }
+}
+
 pub struct SvgLayer<T: Layout> {
     pub data: LayerType,
     pub callbacks: SvgCallbacks<T>,
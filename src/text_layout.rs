@@ -1,14 +1,16 @@
 #![allow(unused_variables, dead_code)]
 
-use webrender::api::{LayoutPixel, GlyphInstance};
+use webrender::api::{LayoutPixel, LayoutRect, GlyphInstance};
 use euclid::{Length, TypedRect, TypedSize2D, TypedPoint2D};
-use rusttype::{Font, Scale, GlyphId};
+use rusttype::{Font, Scale, GlyphId, VMetrics};
 use {
     resources::AppResources,
     display_list::TextInfo,
     css_parser::{
         TextAlignmentHorz, FontSize, BackgroundColor, 
-        Font as FontId, TextAlignmentVert, LineHeight, LayoutOverflow
+        Font as FontId, TextAlignmentVert, LineHeight, LayoutOverflow,
+        LetterSpacing, WordSpacing, PixelValue, CssMetric,
+        StyleWordBreak, StyleOverflowWrap, StyleShapeOutside, LayoutFloat,
     },
 };
 
@@ -39,6 +41,16 @@ pub(crate) enum SemanticWordItem {
     Return,
 }
 
+/// The `shape-outside` of a floated sibling, which the lines of a text flow around
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FloatExclusion {
+    pub(crate) shape: StyleShapeOutside,
+    /// Rectangle of the floated node, relative to the origin of the text bounds
+    pub(crate) reference_box: TypedRect<f32, LayoutPixel>,
+    /// Left floats push the start of the lines to the right, right floats the end to the left
+    pub(crate) float: LayoutFloat,
+}
+
 impl SemanticWordItem {
     pub fn is_return(&self) -> bool {
         use self::SemanticWordItem::*;
@@ -116,6 +128,116 @@ pub(crate) struct ScrollbarInfo {
     pub(crate) background_color: BackgroundColor,
 }
 
+/// Returns the factor that the natural line height of the font has to be scaled by.
+/// The `line-height` is inherited from the parent node, like all text properties.
+fn get_line_height_multiplier(line_height: Option<LineHeight>, natural_v_metrics: VMetrics) -> f32 {
+    match line_height {
+        None | Some(LineHeight::Normal) => 1.0,
+        Some(LineHeight::Multiplier(multiplier)) => multiplier.number,
+        Some(LineHeight::Absolute(pixels)) => {
+            let natural_line_height = natural_v_metrics.ascent - natural_v_metrics.descent + natural_v_metrics.line_gap;
+            if natural_line_height > 0.0 {
+                pixels.to_pixels() / natural_line_height
+            } else {
+                1.0
+            }
+        },
+    }
+}
+
+/// Resolves a `letter-spacing` / `word-spacing` value to pixels, `em` is relative to the font size
+fn resolve_spacing(spacing: PixelValue, font_size: &FontSize) -> f32 {
+    match spacing.metric {
+        CssMetric::Em => spacing.number * font_size.0.to_pixels(),
+        _ => spacing.to_pixels(),
+    }
+}
+
+/// Adds the `letter-spacing` after each glyph of each word. Negative values tighten the text.
+fn apply_letter_spacing(words: &mut [SemanticWordItem], letter_spacing: f32) {
+    for word in words.iter_mut() {
+        if let SemanticWordItem::Word(ref mut w) = word {
+            for (idx, glyph) in w.glyphs.iter_mut().enumerate() {
+                glyph.point.x += idx as f32 * letter_spacing;
+            }
+            w.total_width += w.glyphs.len() as f32 * letter_spacing;
+        }
+    }
+}
+
+/// Splits the words that overflow a line of `max_width` into multiple words, so that
+/// `words_to_left_aligned_glyphs` puts each part on a new line. Simulates the line
+/// breaking of `words_to_left_aligned_glyphs`, so both functions have to be kept in sync.
+///
+/// If `break_all` is set, words are broken at the point where they overflow the line
+/// (`word-break: break-all`), otherwise words are only broken if they don't fit on their
+/// own line (`overflow-wrap: break-word`). At least one character is put on each line.
+fn break_words(words: Vec<SemanticWordItem>, max_width: f32, font_metrics: &FontMetrics, break_all: bool)
+-> Vec<SemanticWordItem>
+{
+    use self::SemanticWordItem::*;
+
+    let mut broken_words = Vec::with_capacity(words.len());
+    let mut word_caret = 0.0;
+
+    for word in words {
+        match word {
+            Word(mut word) => {
+                if word_caret + word.total_width > max_width {
+                    if !break_all && word_caret > 0.0 {
+                        // break-word: try to put the word on its own line first
+                        word_caret = 0.0;
+                    }
+                    while (break_all || word.total_width > max_width) && word_caret + word.total_width > max_width {
+                        // Number of glyphs that still fit on the current line - the first n glyphs
+                        // fit if glyph n (the first glyph of the next line) starts before max_width
+                        let split_point = word.glyphs.iter().skip(1)
+                            .take_while(|g| word_caret + g.point.x <= max_width)
+                            .count();
+                        let split_point = if word_caret == 0.0 { split_point.max(1) } else { split_point };
+
+                        if split_point >= word.glyphs.len() {
+                            break;
+                        }
+
+                        if split_point == 0 {
+                            // Not even one character fits, continue on the next line
+                            word_caret = 0.0;
+                            continue;
+                        }
+
+                        let split_x = word.glyphs[split_point].point.x;
+                        let mut tail_glyphs = word.glyphs.split_off(split_point);
+                        tail_glyphs.iter_mut().for_each(|g| g.point.x -= split_x);
+                        let tail_text = word.text.chars().skip(split_point).collect::<String>();
+                        let head_text = word.text.chars().take(split_point).collect::<String>();
+
+                        broken_words.push(Word(self::Word { text: head_text, glyphs: word.glyphs, total_width: split_x }));
+                        word = self::Word { text: tail_text, glyphs: tail_glyphs, total_width: word.total_width - split_x };
+                        word_caret = 0.0;
+                    }
+                }
+
+                if word_caret + word.total_width > max_width {
+                    word_caret = 0.0;
+                }
+                word_caret += word.total_width + font_metrics.space_width;
+                broken_words.push(Word(word));
+            },
+            Tab => {
+                word_caret += font_metrics.tab_width;
+                broken_words.push(Tab);
+            },
+            Return => {
+                word_caret = 0.0;
+                broken_words.push(Return);
+            },
+        }
+    }
+
+    broken_words
+}
+
 /// Temporary struct so I don't have to pass the three parameters around seperately all the time
 #[derive(Debug, Copy, Clone)]
 struct FontMetrics {
@@ -154,7 +276,8 @@ fn get_string_from_words(words: &[SemanticWordItem]) -> String {
 ///    or the `align-self` on the child node: horizontal alignment of the text
 /// - `font`: The font to use for layouting (only the ID)
 /// - `font_size`: The font size (without line height)
-/// - `line_height`: The line height (100% = 1.0). I.e. `line-height = 1.2;` scales the text vertically by 1.2x
+/// - `line_height`: The line height (100% = 1.0). I.e. `line-height: 1.2;` scales the text vertically by 1.2x,
+///   `line-height: 24px;` sets the distance between two lines to 24px
 /// - `text`: The actual text to layout. Will be unicode-normalized after the Unicode Normalization Form C
 ///   (canonical decomposition followed by canonical composition).
 /// - `overflow`: If the scrollbars should be show, parsed from the `overflow-{x / y}` fields
@@ -181,9 +304,14 @@ pub(crate) fn get_glyphs<'a>(
     target_font_id: &FontId,
     target_font_size: &FontSize,
     line_height: Option<LineHeight>,
+    letter_spacing: Option<LetterSpacing>,
+    word_spacing: Option<WordSpacing>,
+    word_break: StyleWordBreak,
+    overflow_wrap: StyleOverflowWrap,
     text: &TextInfo<'a>,
     overflow: &LayoutOverflow,
-    scrollbar_info: &ScrollbarInfo)
+    scrollbar_info: &ScrollbarInfo,
+    float_exclusions: &[FloatExclusion])
 -> (Vec<GlyphInstance>, TextOverflowPass2)
 {
     use css_parser::{TextOverflowBehaviour, TextOverflowBehaviourInner};
@@ -193,11 +321,13 @@ pub(crate) fn get_glyphs<'a>(
         .expect("Drawing with invalid font!");
 
     let target_font_size_f32 = target_font_size.0.to_pixels() * RUSTTYPE_SIZE_HACK * PX_TO_PT;
-    let line_height = match line_height { Some(lh) => (lh.0).number, None => 1.0 };
-    let font_size_with_line_height = Scale::uniform(target_font_size_f32 * line_height);
     let font_size_no_line_height = Scale::uniform(target_font_size_f32);
+    let line_height = get_line_height_multiplier(line_height, target_font.0.v_metrics(font_size_no_line_height));
+    let font_size_with_line_height = Scale::uniform(target_font_size_f32 * line_height);
     let space_width = target_font.0.glyph(' ').scaled(font_size_no_line_height).h_metrics().advance_width;
     let tab_width = 4.0 * space_width; // TODO: make this configurable
+    // word-spacing only affects the spaces, not the tabs
+    let space_width = space_width + word_spacing.map(|w| resolve_spacing(w.0, target_font_size)).unwrap_or(0.0);
 
     let v_metrics_scaled = target_font.0.v_metrics(font_size_with_line_height);
     let v_advance_scaled = v_metrics_scaled.ascent - v_metrics_scaled.descent + v_metrics_scaled.line_gap;
@@ -263,6 +393,12 @@ pub(crate) fn get_glyphs<'a>(
         }
     }
 
+    // Apply the letter-spacing before the overflow is estimated,
+    // since it changes the width of the words
+    if let Some(letter_spacing) = letter_spacing {
+        apply_letter_spacing(&mut words, resolve_spacing(letter_spacing.0, target_font_size));
+    }
+
     // (2) Calculate the additions / subtractions that have to be take into account
     // let harfbuzz_adjustments = calculate_harfbuzz_adjustments(&text, &target_font.0);
 
@@ -276,9 +412,21 @@ pub(crate) fn get_glyphs<'a>(
 
     let max_horizontal_text_width = if overflow.allows_horizontal_overflow() { None } else { Some(new_size.width) };
 
-    // (5) Align text to the left, initial layout of glyphs
+    // (4.5) Split the words that are allowed to break mid-word, so that the
+    // line breaking in step (5) can put the rest of the word on the next line
+    if let Some(max_width) = max_horizontal_text_width {
+        // NOTE: `keep-all` only differs from `normal` for CJK text, `anywhere` only
+        // differs from `break-word` in the min-content width - both aren't implemented
+        let break_all = word_break == StyleWordBreak::BreakAll;
+        let break_word = overflow_wrap != StyleOverflowWrap::Normal;
+        if break_all || break_word {
+            words = break_words(words, max_width, &font_metrics, break_all);
+        }
+    }
+
+    // (5) Align text to the left, initial layout of glyphs (around the floats)
     let (mut positioned_glyphs, line_break_offsets) =
-        words_to_left_aligned_glyphs(words, &target_font.0, max_horizontal_text_width, &font_metrics);
+        words_to_left_aligned_glyphs(words, &target_font.0, max_horizontal_text_width, &font_metrics, float_exclusions);
 
     // (6) Add the harfbuzz adjustments to the positioned glyphs
     // apply_harfbuzz_adjustments(&mut positioned_glyphs, harfbuzz_adjustments);
@@ -552,16 +700,16 @@ fn estimate_overflow_pass_2(
     // scrollbar gets shown on the right edge, so we need to subtract from the
     // **width** of the rectangle.
 
-    if pass1.horizontal.is_overflowing() {
+    if overflow.shows_horizontal_scrollbar(pass1.horizontal.is_overflowing()) {
         new_size.height -= scrollbar_info.width as f32;
     }
 
-    if pass1.vertical.is_overflowing() {
+    if overflow.shows_vertical_scrollbar(pass1.vertical.is_overflowing()) {
         new_size.width -= scrollbar_info.width as f32;
     }
 
-    // If the words are not overflowing, just take the result from the first pass
-    let recalc_scrollbar_info = if pass1.horizontal.is_overflowing() || pass1.vertical.is_overflowing() {
+    // If there are no scrollbars, just take the result from the first pass
+    let recalc_scrollbar_info = if new_size != *rect_dimensions {
         estimate_overflow_pass_1(words, &new_size, font_metrics, overflow)
     } else {
         pass1
@@ -601,16 +749,27 @@ fn calculate_harfbuzz_adjustments<'a>(text: &str, font: &Font<'a>)
 
 /// If `max_horizontal_width` is `None`, it means that the text is allowed to overflow
 /// the rectangle horizontally
+///
+/// Each line is shortened by the `float_exclusions` next to it (see `get_line_bounds`),
+/// a word that doesn't fit next to the floats starts on the first line below them. The
+/// lines are assumed to start at the top of the bounds, so the floats are only avoided
+/// exactly if the text is aligned to the top.
 #[inline(always)]
 fn words_to_left_aligned_glyphs<'a>(
     words: Vec<SemanticWordItem>,
     font: &Font<'a>,
     max_horizontal_width: Option<f32>,
-    font_metrics: &FontMetrics)
+    font_metrics: &FontMetrics,
+    float_exclusions: &[FloatExclusion])
 -> (Vec<GlyphInstance>, Vec<(usize, f32)>)
 {
     let FontMetrics { space_width, tab_width, vertical_advance, offset_top } = *font_metrics;
 
+    let line_bounds = |line_num: usize| {
+        let line_top = line_num as f32 * vertical_advance;
+        get_line_bounds(float_exclusions, line_top, line_top + vertical_advance, max_horizontal_width)
+    };
+
     // left_aligned_glyphs stores the X and Y coordinates of the positioned glyphs,
     // left-aligned
     let mut left_aligned_glyphs = Vec::<GlyphInstance>::new();
@@ -627,23 +786,38 @@ fn words_to_left_aligned_glyphs<'a>(
     let mut line_break_offsets = Vec::<(usize, WordCaretMax)>::new();
 
     // word_caret is the current X position of the "pen" we are writing with
-    let mut word_caret = 0.0;
     let mut current_line_num = 0;
+    let (mut line_start, mut line_end) = line_bounds(current_line_num);
+    let mut word_caret = line_start;
     let mut max_word_caret = 0.0;
 
     for word in words {
         use self::SemanticWordItem::*;
         match word {
             Word(word) => {
-                let text_overflows_rect = match max_horizontal_width {
-                    Some(max) => word_caret + word.total_width > max,
+                // Skip the lines next to a float that are too narrow for the word
+                if word_caret == line_start {
+                    while line_end.map(|end| line_start + word.total_width > end).unwrap_or(false) &&
+                          (line_start > 0.0 || line_end != max_horizontal_width)
+                    {
+                        current_line_num += 1;
+                        let (next_start, next_end) = line_bounds(current_line_num);
+                        line_start = next_start;
+                        line_end = next_end;
+                        word_caret = line_start;
+                    }
+                }
+
+                let text_overflows_rect = match line_end {
+                    // A word that is wider than the whole line stays on the line
+                    Some(end) => word_caret > line_start && word_caret + word.total_width > end,
                     // If we don't have a maximum horizontal width, the text can overflow the
                     // bounding rectangle in the horizontal direction
                     None => false,
                 };
 
                 if text_overflows_rect {
-                    let space_until_horz_return = match max_horizontal_width {
+                    let space_until_horz_return = match line_end {
                         Some(s) => WordCaretMax::SomeMaxWidth(s - word_caret),
                         None => WordCaretMax::NoMaxWidth(word_caret),
                     };
@@ -651,8 +825,11 @@ fn words_to_left_aligned_glyphs<'a>(
                     if word_caret > max_word_caret {
                         max_word_caret = word_caret;
                     }
-                    word_caret = 0.0;
                     current_line_num += 1;
+                    let (next_start, next_end) = line_bounds(current_line_num);
+                    line_start = next_start;
+                    line_end = next_end;
+                    word_caret = line_start;
                 }
 
                 for mut glyph in word.glyphs {
@@ -671,7 +848,7 @@ fn words_to_left_aligned_glyphs<'a>(
             },
             Return => {
                 // TODO: dupliated code
-                let space_until_horz_return = match max_horizontal_width {
+                let space_until_horz_return = match line_end {
                     Some(s) => WordCaretMax::SomeMaxWidth(s - word_caret),
                     None => WordCaretMax::NoMaxWidth(word_caret),
                 };
@@ -679,15 +856,18 @@ fn words_to_left_aligned_glyphs<'a>(
                 if word_caret > max_word_caret {
                     max_word_caret = word_caret;
                 }
-                word_caret = 0.0;
                 current_line_num += 1;
+                let (next_start, next_end) = line_bounds(current_line_num);
+                line_start = next_start;
+                line_end = next_end;
+                word_caret = line_start;
             },
         }
     }
 
     // push the infos about the last line
     if !left_aligned_glyphs.is_empty() {
-        let space_until_horz_return = match max_horizontal_width {
+        let space_until_horz_return = match line_end {
             Some(s) => WordCaretMax::SomeMaxWidth(s - word_caret),
             None => WordCaretMax::NoMaxWidth(word_caret),
         };
@@ -806,6 +986,408 @@ fn align_text_vert(alignment: TextAlignmentVert, glyphs: &mut [GlyphInstance], l
     glyphs.iter_mut().for_each(|g| g.point.y += space_to_add);
 }
 
+/// Calculates how many columns the text should be split into. `column_count` is the
+/// maximum number of columns, `column_width` the minimum width of a single column.
+/// Always returns at least one column.
+pub(crate) fn get_column_count(total_width: f32, column_count: Option<usize>, column_width: Option<f32>, column_gap: f32)
+-> usize
+{
+    let max_columns_by_width = column_width.map(|w| ((total_width + column_gap) / (w + column_gap)).floor() as usize);
+
+    let count = match (column_count, max_columns_by_width) {
+        (Some(c), Some(w)) => c.min(w),
+        (Some(c), None) => c,
+        (None, Some(w)) => w,
+        (None, None) => 1,
+    };
+
+    count.max(1)
+}
+
+/// Divides the `bounds` into `column_count` equally-sized columns, separated by `column_gap`
+pub(crate) fn get_column_bounds(bounds: &TypedRect<f32, LayoutPixel>, column_count: usize, column_gap: f32)
+-> Vec<TypedRect<f32, LayoutPixel>>
+{
+    let total_gap = column_gap * (column_count.saturating_sub(1)) as f32;
+    let column_width = ((bounds.size.width - total_gap) / column_count as f32).max(0.0);
+
+    (0..column_count).map(|column_idx| {
+        TypedRect::new(
+            TypedPoint2D::new(bounds.origin.x + (column_idx as f32 * (column_width + column_gap)), bounds.origin.y),
+            TypedSize2D::new(column_width, bounds.size.height))
+    }).collect()
+}
+
+/// Takes glyphs that were layouted in one long column (with the width of a single
+/// column) and distributes the lines across `column_count` columns.
+///
+/// Since all lines have the same height, the columns are balanced by giving every
+/// column the same number of lines (only the last column can contain less lines).
+/// `column_offset` is the horizontal distance between the start of two columns
+/// (`column width + column gap`).
+pub(crate) fn distribute_glyphs_into_columns(glyphs: Vec<GlyphInstance>, column_count: usize, column_offset: f32)
+-> Vec<Vec<GlyphInstance>>
+{
+    let mut columns = vec![Vec::<GlyphInstance>::new(); column_count.max(1)];
+
+    if glyphs.is_empty() {
+        return columns;
+    }
+
+    // Glyphs on the same line have the same y position (see `words_to_left_aligned_glyphs`)
+    let mut line_positions = Vec::<f32>::new();
+    for glyph in &glyphs {
+        if line_positions.last() != Some(&glyph.point.y) {
+            line_positions.push(glyph.point.y);
+        }
+    }
+
+    let lines_per_column = (line_positions.len() + columns.len() - 1) / columns.len();
+
+    let mut current_line = 0;
+    for mut glyph in glyphs {
+        if line_positions[current_line] != glyph.point.y {
+            current_line += 1;
+        }
+        let column_idx = current_line / lines_per_column;
+        let first_line_in_column = column_idx * lines_per_column;
+        glyph.point.x += column_idx as f32 * column_offset;
+        glyph.point.y -= line_positions[first_line_in_column] - line_positions[0];
+        columns[column_idx].push(glyph);
+    }
+
+    columns
+}
+
+/// Returns the width of the text if it is laid out on a single line, including one space
+/// after the text (as the words are laid out in `words_to_left_aligned_glyphs`).
+/// Returns `None` if the font hasn't been added to the `AppResources`.
+pub(crate) fn get_text_width<'a>(app_resources: &AppResources<'a>, font_id: &FontId, font_size: &FontSize, text: &str)
+-> Option<f32>
+{
+    let font = &app_resources.font_data.get(font_id)?.0;
+    let scale = Scale::uniform(font_size.0.to_pixels() * RUSTTYPE_SIZE_HACK * PX_TO_PT);
+    let space_width = font.glyph(' ').scaled(scale).h_metrics().advance_width;
+
+    Some(split_text_into_words(text, font, scale).iter().map(|word| match word {
+        SemanticWordItem::Word(w) => w.total_width + space_width,
+        SemanticWordItem::Tab => 4.0 * space_width,
+        SemanticWordItem::Return => 0.0,
+    }).sum())
+}
+
+/// Ascent and descent (negative) of the font at the `font_size`, as used by `get_glyphs`.
+/// Returns `None` if the font isn't loaded.
+pub(crate) fn get_font_vertical_metrics<'a>(app_resources: &AppResources<'a>, font_id: &FontId, font_size: &FontSize)
+-> Option<(f32, f32)>
+{
+    let font = &app_resources.font_data.get(font_id)?.0;
+    let v_metrics = font.v_metrics(Scale::uniform(font_size.0.to_pixels() * RUSTTYPE_SIZE_HACK * PX_TO_PT));
+    Some((v_metrics.ascent, v_metrics.descent))
+}
+
+/// Vertical metrics of a font at a certain font size (in pixels), see `AppState::get_font_metrics()`.
+///
+/// All values are relative to the baseline, positive values go upwards.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FontVerticalMetrics {
+    /// Height of the highest glyph above the baseline
+    pub ascent: f32,
+    /// Depth of the lowest glyph below the baseline (negative)
+    pub descent: f32,
+    /// Height of the capital letters ("H")
+    pub cap_height: f32,
+    /// Height of the lowercase letters ("x")
+    pub x_height: f32,
+    /// Additional space between two lines (`ascent - descent + line_gap` is the line height)
+    pub line_gap: f32,
+    /// Position of the underline (negative, same as `text-decoration: underline`)
+    pub underline_offset: f32,
+}
+
+/// Returns the metrics of the font at the `font_size` (in pixels), at the same scale as `get_glyphs`
+pub(crate) fn get_font_metrics<'a>(font: &Font<'a>, font_size: f32) -> FontVerticalMetrics {
+    let scale = Scale::uniform(font_size * RUSTTYPE_SIZE_HACK * PX_TO_PT);
+    let v_metrics = font.v_metrics(scale);
+
+    // rusttype doesn't expose the `OS/2` table, so the heights are measured from the glyphs
+    let glyph_height = |c: char| font.glyph(c).scaled(scale).exact_bounding_box().map(|bounds| -bounds.min.y);
+
+    FontVerticalMetrics {
+        ascent: v_metrics.ascent,
+        descent: v_metrics.descent,
+        cap_height: glyph_height('H').unwrap_or(v_metrics.ascent),
+        x_height: glyph_height('x').unwrap_or(v_metrics.ascent / 2.0),
+        line_gap: v_metrics.line_gap,
+        underline_offset: v_metrics.descent / 2.0,
+    }
+}
+
+/// Horizontal extent and baseline of one line of positioned glyphs,
+/// used to draw the `text-decoration` lines
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct TextLine {
+    pub baseline: f32,
+    pub start_x: f32,
+    pub end_x: f32,
+}
+
+/// Position of the `text-decoration` lines, relative to the baseline (positive values go
+/// downwards), and the thickness of the lines if `text-decoration-thickness` isn't set
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct TextDecorationMetrics {
+    pub underline_offset: f32,
+    pub overline_offset: f32,
+    pub line_through_offset: f32,
+    pub thickness: f32,
+}
+
+/// Returns the lines of the positioned glyphs and where the decoration lines should be drawn
+pub(crate) fn get_text_decoration_lines<'a>(
+    app_resources: &AppResources<'a>,
+    font_id: &FontId,
+    font_size: &FontSize,
+    positioned_glyphs: &[GlyphInstance])
+-> (Vec<TextLine>, TextDecorationMetrics)
+{
+    let font = &app_resources.font_data.get(font_id).expect("Drawing with invalid font!").0;
+
+    // Same scale as the glyphs were laid out with, see `get_glyphs`
+    let scale = Scale::uniform(font_size.0.to_pixels() * RUSTTYPE_SIZE_HACK * PX_TO_PT);
+    let v_metrics = font.v_metrics(scale);
+
+    // rusttype doesn't expose the underline position of the font (from the `post` table),
+    // so the lines are placed relative to the ascent, descent and the height of the "x"
+    let x_height = font.glyph('x').scaled(scale).exact_bounding_box()
+        .map(|bounds| -bounds.min.y)
+        .unwrap_or(v_metrics.ascent / 2.0);
+
+    let metrics = TextDecorationMetrics {
+        underline_offset: -v_metrics.descent / 2.0,
+        overline_offset: -v_metrics.ascent,
+        line_through_offset: -x_height / 2.0,
+        thickness: (font_size.0.to_pixels() / 14.0).max(1.0),
+    };
+
+    let lines = get_text_lines(positioned_glyphs, |glyph| {
+        font.glyph(GlyphId(glyph.index)).scaled(scale).h_metrics().advance_width
+    });
+
+    (lines, metrics)
+}
+
+/// Groups the glyphs into lines (glyphs on the same line have the same y position),
+/// a line ends at the last glyph plus its `glyph_advance`
+fn get_text_lines<F: Fn(&GlyphInstance) -> f32>(positioned_glyphs: &[GlyphInstance], glyph_advance: F)
+-> Vec<TextLine>
+{
+    let mut lines = Vec::<TextLine>::new();
+
+    for glyph in positioned_glyphs {
+        let glyph_end = glyph.point.x + glyph_advance(glyph);
+        match lines.last_mut() {
+            Some(ref mut line) if line.baseline == glyph.point.y => {
+                line.start_x = line.start_x.min(glyph.point.x);
+                line.end_x = line.end_x.max(glyph_end);
+                continue;
+            },
+            _ => { },
+        }
+        lines.push(TextLine { baseline: glyph.point.y, start_x: glyph.point.x, end_x: glyph_end });
+    }
+
+    lines
+}
+
+/// Returns the bounds of each (NFC-normalized) character of the `text`, in the same
+/// coordinates as the `positioned_glyphs`. Used for `FakeWindow::get_char_bounds()`.
+pub(crate) fn get_char_rects<'a>(
+    app_resources: &AppResources<'a>,
+    font_id: &FontId,
+    font_size: &FontSize,
+    text: &str,
+    positioned_glyphs: &[GlyphInstance])
+-> Vec<LayoutRect>
+{
+    use unicode_normalization::UnicodeNormalization;
+
+    let font = match app_resources.font_data.get(font_id) {
+        Some(f) => &f.0,
+        None => return Vec::new(),
+    };
+
+    let scale = Scale::uniform(font_size.0.to_pixels() * RUSTTYPE_SIZE_HACK * PX_TO_PT);
+    let v_metrics = font.v_metrics(scale);
+    let space_width = font.glyph(' ').scaled(scale).h_metrics().advance_width;
+
+    layout_char_rects(text.nfc(), positioned_glyphs, space_width, v_metrics.ascent, v_metrics.descent, |glyph| {
+        font.glyph(GlyphId(glyph.index)).scaled(scale).h_metrics().advance_width
+    })
+}
+
+/// Every character except for whitespace has exactly one glyph (see `split_text_into_words`),
+/// the whitespace is placed after the previous character.
+fn layout_char_rects<I: Iterator<Item=char>, F: Fn(&GlyphInstance) -> f32>(
+    chars: I,
+    positioned_glyphs: &[GlyphInstance],
+    space_width: f32,
+    ascent: f32,
+    descent: f32,
+    glyph_advance: F)
+-> Vec<LayoutRect>
+{
+    let line_height = ascent - descent;
+    let mut glyphs = positioned_glyphs.iter();
+    let mut rects = Vec::<LayoutRect>::new();
+
+    let (mut pen_x, mut baseline) = match positioned_glyphs.first() {
+        Some(glyph) => (glyph.point.x, glyph.point.y),
+        None => (0.0, ascent),
+    };
+
+    for c in chars {
+        let (x, width) = match c {
+            ' ' => (pen_x, space_width),
+            '\t' => (pen_x, 4.0 * space_width),
+            '\n' => (pen_x, 0.0),
+            _ => match glyphs.next() {
+                Some(glyph) => {
+                    baseline = glyph.point.y;
+                    (glyph.point.x, glyph_advance(glyph))
+                },
+                // text-overflow may have removed the glyphs at the end of the text
+                None => break,
+            },
+        };
+        pen_x = x + width;
+        rects.push(TypedRect::new(TypedPoint2D::new(x, baseline - ascent), TypedSize2D::new(width, line_height)));
+    }
+
+    rects
+}
+
+/// Returns the index of the character rectangle that is closest to `(x, y)`:
+/// the closest line first, then the closest character on that line.
+pub(crate) fn get_char_at_position(char_rects: &[LayoutRect], x: f32, y: f32)
+-> Option<usize>
+{
+    fn distance(min: f32, max: f32, pos: f32) -> f32 {
+        if pos < min { min - pos } else if pos > max { pos - max } else { 0.0 }
+    }
+
+    char_rects.iter().enumerate().min_by(|(_, a), (_, b)| {
+        let a_distance = (distance(a.min_y(), a.max_y(), y), distance(a.min_x(), a.max_x(), x));
+        let b_distance = (distance(b.min_y(), b.max_y(), y), distance(b.min_x(), b.max_x(), x));
+        a_distance.partial_cmp(&b_distance).unwrap_or(::std::cmp::Ordering::Equal)
+    }).map(|(idx, _)| idx)
+}
+
+/// Returns the horizontal interval (`min_x`, `max_x`) that the `shape-outside` of a floated
+/// node covers on the line of text between `line_top` and `line_bottom`, or `None` if the
+/// line doesn't touch the shape. The inline content of the line has to flow around it.
+/// `reference_box` is the rectangle of the floated node.
+pub(crate) fn get_shape_outside_exclusion(
+    shape: &StyleShapeOutside,
+    reference_box: &TypedRect<f32, LayoutPixel>,
+    line_top: f32,
+    line_bottom: f32)
+-> Option<(f32, f32)>
+{
+    let (x, y) = (reference_box.origin.x, reference_box.origin.y);
+    let (width, height) = (reference_box.size.width, reference_box.size.height);
+
+    let overlaps_line = |top: f32, bottom: f32| line_bottom > top && line_top < bottom;
+
+    // widest part of the ellipse between line_top and line_bottom
+    let ellipse_exclusion = |center_x: f32, center_y: f32, radius_x: f32, radius_y: f32| {
+        if radius_x <= 0.0 || radius_y <= 0.0 || !overlaps_line(center_y - radius_y, center_y + radius_y) {
+            return None;
+        }
+        let nearest_y = center_y.max(line_top).min(line_bottom);
+        let dy = (nearest_y - center_y) / radius_y;
+        let dx = radius_x * (1.0 - dy * dy).max(0.0).sqrt();
+        Some((center_x - dx, center_x + dx))
+    };
+
+    match *shape {
+        StyleShapeOutside::None => {
+            if overlaps_line(y, y + height) { Some((x, x + width)) } else { None }
+        },
+        StyleShapeOutside::Circle { radius, center } => {
+            let radius = radius.resolve((width * width + height * height).sqrt() / ::std::f32::consts::SQRT_2);
+            ellipse_exclusion(x + center.0.resolve(width), y + center.1.resolve(height), radius, radius)
+        },
+        StyleShapeOutside::Ellipse { radius_x, radius_y, center } => {
+            ellipse_exclusion(x + center.0.resolve(width), y + center.1.resolve(height), radius_x.resolve(width), radius_y.resolve(height))
+        },
+        StyleShapeOutside::Inset { top, right, bottom, left } => {
+            let (min_x, max_x) = (x + left.resolve(width), x + width - right.resolve(width));
+            if min_x <= max_x && overlaps_line(y + top.resolve(height), y + height - bottom.resolve(height)) {
+                Some((min_x, max_x))
+            } else {
+                None
+            }
+        },
+        StyleShapeOutside::Polygon(ref points) => {
+            let points = points.iter()
+                .map(|&(px, py)| (x + px.resolve(width), y + py.resolve(height)))
+                .collect::<Vec<(f32, f32)>>();
+
+            // Clip every edge to the line, the exclusion spans all the clipped edges
+            let mut exclusion: Option<(f32, f32)> = None;
+            for (idx, &(x1, y1)) in points.iter().enumerate() {
+                let (x2, y2) = points[(idx + 1) % points.len()];
+                let x_at = |t: f32| x1 + (x2 - x1) * t;
+
+                let (t_min, t_max) = if y1 == y2 {
+                    if y1 < line_top || y1 > line_bottom {
+                        continue;
+                    }
+                    (0.0, 1.0)
+                } else {
+                    let t_top = (line_top - y1) / (y2 - y1);
+                    let t_bottom = (line_bottom - y1) / (y2 - y1);
+                    let (t_min, t_max) = (t_top.min(t_bottom).max(0.0), t_top.max(t_bottom).min(1.0));
+                    if t_min > t_max {
+                        continue;
+                    }
+                    (t_min, t_max)
+                };
+
+                let (edge_min, edge_max) = (x_at(t_min).min(x_at(t_max)), x_at(t_min).max(x_at(t_max)));
+                exclusion = Some(match exclusion {
+                    Some((min_x, max_x)) => (min_x.min(edge_min), max_x.max(edge_max)),
+                    None => (edge_min, edge_max),
+                });
+            }
+            exclusion
+        },
+    }
+}
+
+/// Returns the horizontal space (`start`, `end`) of the line of text between `line_top` and
+/// `line_bottom`, without the `shape-outside` of the floats next to the line. The `end` is
+/// `None` if the text may overflow horizontally, then only the left floats are avoided.
+fn get_line_bounds(float_exclusions: &[FloatExclusion], line_top: f32, line_bottom: f32, max_width: Option<f32>)
+-> (f32, Option<f32>)
+{
+    let mut start: f32 = 0.0;
+    let mut end = max_width;
+
+    for exclusion in float_exclusions {
+        let (min_x, max_x) = match get_shape_outside_exclusion(&exclusion.shape, &exclusion.reference_box, line_top, line_bottom) {
+            Some(e) => e,
+            None => continue,
+        };
+        match exclusion.float {
+            LayoutFloat::Right => end = end.map(|end| end.min(min_x)),
+            LayoutFloat::Left | LayoutFloat::None => start = start.max(max_x),
+        }
+    }
+
+    (start, end)
+}
+
 /// Adds the X and Y offset to each glyph in the positioned glyph
 #[inline(always)]
 fn add_origin(positioned_glyphs: &mut [GlyphInstance], x: f32, y: f32)
@@ -816,6 +1398,13 @@ fn add_origin(positioned_glyphs: &mut [GlyphInstance], x: f32, y: f32)
     }
 }
 
+#[test]
+fn test_get_column_count() {
+    assert_eq!(get_column_count(600.0, Some(3), None, 10.0), 3);
+    assert_eq!(get_column_count(600.0, Some(3), Some(400.0), 10.0), 1);
+    assert_eq!(get_column_count(600.0, None, Some(195.0), 10.0), 2);
+}
+
 #[test]
 fn test_it_should_add_origin() {
     let mut instances = vec![
@@ -835,4 +1424,156 @@ fn test_it_should_add_origin() {
     assert_eq!(instances[0].point.y as usize, 0);
     assert_eq!(instances[1].point.x as usize, 33);
     assert_eq!(instances[1].point.y as usize, 10);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_apply_letter_spacing() {
+    let glyphs = (0..3).map(|i| GlyphInstance { index: i, point: TypedPoint2D::new(i as f32 * 10.0, 0.0) }).collect();
+    let mut words = vec![
+        SemanticWordItem::Word(Word { text: "abc".into(), glyphs: glyphs, total_width: 30.0 }),
+        SemanticWordItem::Tab,
+    ];
+
+    apply_letter_spacing(&mut words, 2.0);
+
+    match words[0] {
+        SemanticWordItem::Word(ref w) => {
+            assert_eq!(w.glyphs.iter().map(|g| g.point.x).collect::<Vec<f32>>(), vec![0.0, 12.0, 24.0]);
+            assert_eq!(w.total_width, 36.0);
+        },
+        _ => panic!("expected a word"),
+    }
+}
+
+#[test]
+fn test_break_words() {
+    let font_metrics = FontMetrics { space_width: 5.0, tab_width: 20.0, vertical_advance: 10.0, offset_top: 0.0 };
+    let word = |text: &str| {
+        let glyphs = (0..text.len()).map(|i| GlyphInstance { index: i as u32, point: TypedPoint2D::new(i as f32 * 10.0, 0.0) }).collect();
+        SemanticWordItem::Word(Word { text: text.into(), glyphs: glyphs, total_width: text.len() as f32 * 10.0 })
+    };
+    let texts = |words: &[SemanticWordItem]| words.iter().filter_map(|w| match w {
+        SemanticWordItem::Word(w) => Some(w.text.clone()),
+        _ => None,
+    }).collect::<Vec<String>>();
+
+    // break-word: "ab" fits on the first line, "cdefgh" doesn't fit on its own line
+    let broken = break_words(vec![word("ab"), word("cdefgh"), word("ij")], 40.0, &font_metrics, false);
+    assert_eq!(texts(&broken), vec!["ab", "cdef", "gh", "ij"]);
+
+    match broken[2] {
+        SemanticWordItem::Word(ref w) => {
+            assert_eq!(w.glyphs.iter().map(|g| g.point.x).collect::<Vec<f32>>(), vec![0.0, 10.0]);
+            assert_eq!(w.total_width, 20.0);
+        },
+        _ => panic!("expected a word"),
+    }
+
+    // break-all: "cdefgh" starts on the same line as "ab"
+    let broken = break_words(vec![word("ab"), word("cdefgh"), word("ij")], 40.0, &font_metrics, true);
+    assert_eq!(texts(&broken), vec!["ab", "c", "defg", "h", "ij"]);
+
+    // break-word doesn't break words that fit on their own line
+    let broken = break_words(vec![word("ab"), word("cdef")], 40.0, &font_metrics, false);
+    assert_eq!(texts(&broken), vec!["ab", "cdef"]);
+}
+
+#[test]
+fn test_get_text_lines() {
+    let glyph = |x: f32, y: f32| GlyphInstance { index: 0, point: TypedPoint2D::new(x, y) };
+    let glyphs = vec![glyph(0.0, 10.0), glyph(10.0, 10.0), glyph(30.0, 10.0), glyph(5.0, 25.0)];
+
+    assert_eq!(get_text_lines(&glyphs, |_| 8.0), vec![
+        TextLine { baseline: 10.0, start_x: 0.0, end_x: 38.0 },
+        TextLine { baseline: 25.0, start_x: 5.0, end_x: 13.0 },
+    ]);
+}
+
+#[test]
+fn test_get_shape_outside_exclusion() {
+    use css_parser::ShapeLength::*;
+
+    // rounded, to ignore floating-point errors
+    let exclusion = |shape: &StyleShapeOutside, rect: &TypedRect<f32, LayoutPixel>, top: f32, bottom: f32|
+        get_shape_outside_exclusion(shape, rect, top, bottom).map(|(min_x, max_x)| (min_x.round(), max_x.round()));
+
+    let float_rect = TypedRect::new(TypedPoint2D::new(0.0, 0.0), TypedSize2D::new(100.0, 100.0));
+    let circle = StyleShapeOutside::Circle { radius: Percentage(0.5), center: (Percentage(0.5), Percentage(0.5)) };
+
+    // The text arcs around the circle: the lines at the top and bottom are less covered
+    assert_eq!(exclusion(&circle, &float_rect, 45.0, 55.0), Some((0.0, 100.0)));
+    assert_eq!(exclusion(&circle, &float_rect, 0.0, 10.0), Some((20.0, 80.0)));
+    assert_eq!(exclusion(&circle, &float_rect, 90.0, 100.0), Some((20.0, 80.0)));
+    assert_eq!(exclusion(&circle, &float_rect, 110.0, 120.0), None);
+
+    let triangle = StyleShapeOutside::Polygon(vec![
+        (Percentage(0.0), Percentage(0.0)), (Percentage(1.0), Percentage(0.0)), (Percentage(0.0), Percentage(1.0)),
+    ]);
+    assert_eq!(exclusion(&triangle, &float_rect, 40.0, 60.0), Some((0.0, 60.0)));
+
+    assert_eq!(exclusion(&StyleShapeOutside::None, &float_rect, 40.0, 60.0), Some((0.0, 100.0)));
+}
+
+#[test]
+fn test_get_line_bounds() {
+    use css_parser::ShapeLength::*;
+
+    let left_circle = FloatExclusion {
+        shape: StyleShapeOutside::Circle { radius: Percentage(0.5), center: (Percentage(0.5), Percentage(0.5)) },
+        reference_box: TypedRect::new(TypedPoint2D::new(0.0, 0.0), TypedSize2D::new(100.0, 100.0)),
+        float: LayoutFloat::Left,
+    };
+    let right_box = FloatExclusion {
+        shape: StyleShapeOutside::None,
+        reference_box: TypedRect::new(TypedPoint2D::new(250.0, 0.0), TypedSize2D::new(50.0, 20.0)),
+        float: LayoutFloat::Right,
+    };
+    let exclusions = [left_circle, right_box];
+
+    // rounded, to ignore floating-point errors
+    let line_bounds = |top: f32, max_width: Option<f32>| {
+        let (start, end) = get_line_bounds(&exclusions, top, top + 10.0, max_width);
+        (start.round(), end.map(|end| end.round()))
+    };
+
+    // The lines next to the circle start where the circle ends on that line
+    assert_eq!(line_bounds(0.0, Some(300.0)), (80.0, Some(250.0)));
+    assert_eq!(line_bounds(45.0, Some(300.0)), (100.0, Some(300.0)));
+    assert_eq!(line_bounds(120.0, Some(300.0)), (0.0, Some(300.0)));
+    // Without a maximum width, the right floats are ignored
+    assert_eq!(line_bounds(0.0, None), (80.0, None));
+}
+
+#[test]
+fn test_layout_char_rects() {
+    let glyph = |x: f32, y: f32| GlyphInstance { index: 0, point: TypedPoint2D::new(x, y) };
+    // "ab c\nd": the space and the newline don't have any glyphs
+    let glyphs = vec![glyph(0.0, 10.0), glyph(8.0, 10.0), glyph(20.0, 10.0), glyph(0.0, 25.0)];
+    let rects = layout_char_rects("ab c\nd".chars(), &glyphs, 4.0, 10.0, -5.0, |_| 8.0);
+
+    let rect = |x: f32, y: f32, width: f32| TypedRect::new(TypedPoint2D::new(x, y), TypedSize2D::new(width, 15.0));
+    assert_eq!(rects, vec![
+        rect(0.0, 0.0, 8.0), rect(8.0, 0.0, 8.0), rect(16.0, 0.0, 4.0),
+        rect(20.0, 0.0, 8.0), rect(28.0, 0.0, 0.0), rect(0.0, 15.0, 8.0),
+    ]);
+
+    assert_eq!(get_char_at_position(&rects, 10.0, 5.0), Some(1));
+    assert_eq!(get_char_at_position(&rects, 100.0, 5.0), Some(3));
+    assert_eq!(get_char_at_position(&rects, 100.0, 100.0), Some(5));
+    assert_eq!(get_char_at_position(&[], 0.0, 0.0), None);
+}
+
+#[test]
+fn test_get_font_metrics() {
+    const TEST_FONT: &[u8] = include_bytes!("../assets/fonts/weblysleekuil.ttf");
+    let font = ::font::rusttype_load_font(TEST_FONT.to_vec()).unwrap();
+
+    let metrics = get_font_metrics(&font, 16.0);
+    assert!(metrics.ascent > metrics.cap_height);
+    assert!(metrics.cap_height > metrics.x_height);
+    assert!(metrics.x_height > 0.0);
+    assert!(metrics.descent < metrics.underline_offset && metrics.underline_offset < 0.0);
+
+    let double_size = get_font_metrics(&font, 32.0);
+    assert!((double_size.ascent - metrics.ascent * 2.0).abs() < 0.01);
+}
@@ -6,9 +6,9 @@ use std::{
     sync::{Arc, Mutex},
 };
 use {
-    dom::{NodeData, Dom},
+    dom::{NodeData, Dom, On},
     ui_description::{StyledNode, CssConstraintList, UiDescription},
-    css::{Css, CssRule},
+    css::{Css, CssRule, CssPseudoClass, CssMediaType},
     window::WindowInfo,
     id_tree::{NodeId, Arena},
     css_parser::{ParsedCssProperty, CssParsingError},
@@ -42,6 +42,8 @@ pub(crate) struct ParsedCss<'a> {
     pub(crate) pure_div_rules: Vec<&'a CssRule>,
     pub(crate) pure_class_rules: Vec<&'a CssRule>,
     pub(crate) pure_id_rules: Vec<&'a CssRule>,
+    pub(crate) pseudo_class_rules: Vec<&'a CssRule>,
+    pub(crate) forced_colors_rules: Vec<&'a CssRule>,
 }
 
 /// Convenience trait for the `css.set_dynamic_property()` function.
@@ -115,11 +117,29 @@ impl<'a> ParsedCss<'a> {
 
         // note: the following passes can be done in parallel ...
 
+        // Only rules for the currently active media type (`@media print { }`, etc.)
+        let active_rules: Vec<&CssRule> = css.rules.iter().filter(|rule|
+            css.is_media_active(rule.media)
+        ).collect();
+
+        // Rules in `@media (forced-colors: active) { }`, these override all other rules
+        let (forced_colors_rules, active_rules): (Vec<&CssRule>, Vec<&CssRule>) = active_rules.into_iter().partition(|rule|
+            rule.media == CssMediaType::ForcedColors
+        );
+
+        // Rules with pseudo-classes, these depend on the position of the node in the DOM
+        // li.row:nth-child(odd) {
+        //    background-color: grey;
+        // }
+        let (pseudo_class_rules, active_rules): (Vec<&CssRule>, Vec<&CssRule>) = active_rules.into_iter().partition(|rule|
+            !rule.pseudo_classes.is_empty()
+        );
+
         // Global rules
         // * {
         //    background-color: blue;
         // }
-        let pure_global_rules: Vec<&CssRule> = css.rules.iter().filter(|rule|
+        let pure_global_rules: Vec<&CssRule> = active_rules.iter().cloned().filter(|rule|
             rule.html_type == "*" && rule.id.is_none() && rule.classes.is_empty()
         ).collect();
 
@@ -127,7 +147,7 @@ impl<'a> ParsedCss<'a> {
         // button {
         //    justify-content: center;
         // }
-        let pure_div_rules: Vec<&CssRule> = css.rules.iter().filter(|rule|
+        let pure_div_rules: Vec<&CssRule> = active_rules.iter().cloned().filter(|rule|
             rule.html_type != "*" && rule.id.is_none() && rule.classes.is_empty()
         ).collect();
 
@@ -137,7 +157,7 @@ impl<'a> ParsedCss<'a> {
         // .something .otherclass {
         //    text-color: red;
         // }
-        let pure_class_rules: Vec<&CssRule> = css.rules.iter().filter(|rule|
+        let pure_class_rules: Vec<&CssRule> = active_rules.iter().cloned().filter(|rule|
             rule.id.is_none() && !rule.classes.is_empty()
         ).collect();
 
@@ -145,7 +165,7 @@ impl<'a> ParsedCss<'a> {
         // #something {
         //    background-color: red;
         // }
-        let pure_id_rules: Vec<&CssRule> = css.rules.iter().filter(|rule|
+        let pure_id_rules: Vec<&CssRule> = active_rules.iter().cloned().filter(|rule|
             rule.id.is_some() && rule.classes.is_empty()
         ).collect();
 
@@ -154,6 +174,8 @@ impl<'a> ParsedCss<'a> {
             pure_div_rules: pure_div_rules,
             pure_class_rules: pure_class_rules,
             pure_id_rules: pure_id_rules,
+            pseudo_class_rules: pseudo_class_rules,
+            forced_colors_rules: forced_colors_rules,
         }
     }
 }
@@ -166,10 +188,7 @@ fn match_dom_css_selectors<'a, T: Layout>(
     parent_z_level: u32)
 -> UiDescription<T>
 {
-    let mut root_constraints = CssConstraintList::default();
-    for global_rule in &parsed_css.pure_global_rules {
-        push_rule(&mut root_constraints, global_rule);
-    }
+    let root_constraints = CssConstraintList::default();
 
     let arena_borrow = &*(*arena).borrow();
     let mut styled_nodes = BTreeMap::<NodeId, StyledNode>::new();
@@ -203,8 +222,10 @@ fn match_dom_css_selectors_inner<'a, T: Layout>(
 {
     let mut styled_nodes = BTreeMap::<NodeId, StyledNode>::new();
 
-    let mut current_constraints = parent_constraints.clone();
-    cascade_constraints(&arena[root].data, &mut current_constraints, parsed_css, css);
+    // Only the inherited properties of the parent (`color`, `font-size`, etc.) apply to
+    // the children, they come first so that the rules that match the node itself win
+    let mut current_constraints = parent_constraints.inherited();
+    cascade_constraints(root, arena, &mut current_constraints, parsed_css, css);
 
     let current_node = StyledNode {
         z_level: parent_z_level,
@@ -223,11 +244,18 @@ fn match_dom_css_selectors_inner<'a, T: Layout>(
 /// Cascade the rules, put them into the list
 #[allow(unused_variables)]
 fn cascade_constraints<'a, T: Layout>(
-    node: &NodeData<T>,
+    node_id: NodeId,
+    arena: &Arena<NodeData<T>>,
     list: &mut CssConstraintList,
     parsed_css: &ParsedCss<'a>,
     css: &Css)
 {
+    let node = &arena[node_id].data;
+
+    for global_rule in &parsed_css.pure_global_rules {
+        push_rule(list, global_rule);
+    }
+
     for div_rule in &parsed_css.pure_div_rules {
         if *node.node_type.get_css_id() == div_rule.html_type {
             push_rule(list, div_rule);
@@ -261,23 +289,91 @@ fn cascade_constraints<'a, T: Layout>(
     }
 
     // first attribute for "id = something"
-    let node_id = &node.id;
-
-    if let Some(ref node_id) = *node_id {
+    if let Some(ref node_css_id) = node.id {
         // if the node has an ID
         for id_rule in &parsed_css.pure_id_rules {
-            if *id_rule.id.as_ref().unwrap() == *node_id {
+            if *id_rule.id.as_ref().unwrap() == *node_css_id {
                 push_rule(list, id_rule);
             }
         }
     }
 
+    // rules with pseudo-classes are more specific than the rules above
+    for pseudo_class_rule in &parsed_css.pseudo_class_rules {
+        if pseudo_class_rule_matches(pseudo_class_rule, node_id, arena, css) {
+            push_rule(list, pseudo_class_rule);
+        }
+    }
+
+    // high contrast overrides have to win over the regular styles
+    for forced_colors_rule in &parsed_css.forced_colors_rules {
+        if pseudo_class_rule_matches(forced_colors_rule, node_id, arena, css) {
+            push_rule(list, forced_colors_rule);
+        }
+    }
+
     // TODO: all the mixed rules
 }
 
+/// Returns whether the selector of a rule with pseudo-classes matches the node
+fn pseudo_class_rule_matches<T: Layout>(rule: &CssRule, node_id: NodeId, arena: &Arena<NodeData<T>>, css: &Css) -> bool {
+    selector_matches(&rule.html_type, &rule.id, &rule.classes, &rule.pseudo_classes, node_id, arena, css)
+}
+
+/// Returns whether a selector such as `li.row:first-child` matches the node
+fn selector_matches<T: Layout>(
+    html_type: &str,
+    id: &Option<String>,
+    classes: &[String],
+    pseudo_classes: &[CssPseudoClass],
+    node_id: NodeId,
+    arena: &Arena<NodeData<T>>,
+    css: &Css)
+-> bool
+{
+    let node = &arena[node_id].data;
+
+    (html_type == "*" || html_type == node.node_type.get_css_id()) &&
+    id.as_ref().map(|id| node.id.as_ref() == Some(id)).unwrap_or(true) &&
+    classes.iter().all(|class| node.classes.contains(class)) &&
+    pseudo_classes.iter().all(|pseudo_class| pseudo_class_matches(pseudo_class, node_id, arena, css))
+}
+
+/// Evaluates a pseudo-class by looking at the siblings of the node (or the node itself for `:not()`),
+/// the `css` knows the node that is currently pressed (for `:active`) and whether a drag is over the window
+fn pseudo_class_matches<T: Layout>(pseudo_class: &CssPseudoClass, node_id: NodeId, arena: &Arena<NodeData<T>>, css: &Css) -> bool {
+    use css::CssPseudoClass::*;
+
+    let css_id = arena[node_id].data.node_type.get_css_id();
+    let is_same_type = |sibling: &NodeId| arena[*sibling].data.node_type.get_css_id() == css_id;
+
+    // NOTE: both iterators include the node itself, so the indices start at 1
+    let index = || node_id.preceding_siblings(arena).count();
+    let index_from_end = || node_id.following_siblings(arena).count();
+    let index_of_type = || node_id.preceding_siblings(arena).filter(|s| is_same_type(s)).count();
+    let index_of_type_from_end = || node_id.following_siblings(arena).filter(|s| is_same_type(s)).count();
+
+    match pseudo_class {
+        FirstChild => index() == 1,
+        LastChild => index_from_end() == 1,
+        OnlyChild => index() == 1 && index_from_end() == 1,
+        FirstOfType => index_of_type() == 1,
+        LastOfType => index_of_type_from_end() == 1,
+        NthChild(pattern) => pattern.matches(index()),
+        NthOfType(pattern) => pattern.matches(index_of_type()),
+        Not(selectors) => !selectors.iter().any(|selector| selector_matches(
+            &selector.html_type, &selector.id, &selector.classes, &selector.pseudo_classes, node_id, arena, css)),
+        Active => css.active_node == Some(node_id),
+        Focus => css.focused_node == Some(node_id),
+        Disabled => arena[node_id].data.disabled,
+        PlaceholderShown => arena[node_id].data.placeholder_shown,
+        DropTarget => css.is_drag_over && arena[node_id].data.events.callbacks.contains_key(&On::Drop),
+    }
+}
+
 #[inline]
 fn push_rule(list: &mut CssConstraintList, rule: &CssRule) {
-    list.list.push(rule.declaration.1.clone());
+    list.list.push(rule.declaration.clone());
 }
 
 // Empty test, for some reason codecov doesn't detect any files (and therefore
@@ -286,4 +382,20 @@ fn push_rule(list: &mut CssConstraintList, rule: &CssRule) {
 #[test]
 fn __codecov_test_traits_file() {
 
+}
+
+#[test]
+fn test_cascade_only_inherits_inherited_properties() {
+    use dom::{NodeType, TestLayout};
+
+    let css = Css::new_from_string(".parent { color: red; width: 100px; }").unwrap();
+    let dom = Dom::<TestLayout>::new(NodeType::Div).with_class("parent")
+        .with_child(Dom::new(NodeType::Div));
+
+    let ui_description = TestLayout::style_dom(&dom, &css);
+    let child_keys = ui_description.styled_nodes[&NodeId::new(1)].css_constraints.list.iter()
+        .map(|&(ref key, _)| &**key)
+        .collect::<Vec<&str>>();
+
+    assert_eq!(child_keys, vec!["color"]);
 }
\ No newline at end of file
@@ -5,7 +5,7 @@ use std::{
 };
 use {
     FastHashMap,
-    css_parser::ParsedCssProperty,
+    css_parser::{ParsedCssProperty, is_inherited_property, StyleScrollBehavior, StyleScrollMargin, StyleScrollPadding, StylePointerEvents, LayoutOverflow, LayoutResize},
     id_tree::{Arena, NodeId},
     traits::Layout,
     ui_state::UiState,
@@ -58,6 +58,180 @@ impl<T: Layout> UiDescription<T> {
     {
         T::style_dom(&ui_state.dom, style)
     }
+
+    /// Returns the last value of a CSS property that applies to the node after the cascade,
+    /// `None` if the node doesn't exist, has no style or the property isn't set.
+    /// `get_value` selects the property, i.e. `|p| match p { ParsedCssProperty::Opacity(o) => Some(*o), _ => None }`
+    pub(crate) fn get_css_property<R, F: Fn(&ParsedCssProperty) -> Option<R>>(&self, node_id: NodeId, get_value: F) -> Option<R> {
+        let styled_node = self.styled_nodes.get(&node_id)?;
+
+        styled_node.css_constraints.list.iter().rev().filter_map(|&(_, ref declaration)| {
+            let property = match *declaration {
+                CssDeclaration::Static(ref property) => property,
+                CssDeclaration::Dynamic(ref dynamic) => self.dynamic_css_overrides
+                    .get(&dynamic.dynamic_id).unwrap_or(&dynamic.default),
+            };
+            get_value(property)
+        }).next()
+    }
+
+    /// Calls `f` with all CSS properties of the node, from the lowest to the highest
+    /// priority - for properties like `scroll-margin` whose longhands are merged
+    fn for_each_css_property<F: FnMut(&ParsedCssProperty)>(&self, node_id: NodeId, mut f: F) {
+        let styled_node = match self.styled_nodes.get(&node_id) {
+            Some(s) => s,
+            None => return,
+        };
+
+        for &(_, ref declaration) in styled_node.css_constraints.list.iter() {
+            let property = match *declaration {
+                CssDeclaration::Static(ref property) => property,
+                CssDeclaration::Dynamic(ref dynamic) => self.dynamic_css_overrides
+                    .get(&dynamic.dynamic_id).unwrap_or(&dynamic.default),
+            };
+            f(property);
+        }
+    }
+
+    /// Evaluates the `counter-reset` and `counter-increment` properties of all nodes in
+    /// document order. Returns the counters in scope of each node (after the node has reset /
+    /// incremented them), from the outermost to the innermost counter. Nodes without any
+    /// counters in scope are left out.
+    ///
+    /// A `counter-reset` starts a new counter for the node, its following siblings and their
+    /// children - so nested lists get their own counters of the same name, which allows
+    /// "1.1, 1.2, 2.1" numbering via `counters(name, ".")`.
+    pub(crate) fn collect_counters(&self) -> BTreeMap<NodeId, Vec<(String, i32)>> {
+        let mut node_counters = BTreeMap::new();
+        if let Some(root) = self.ui_descr_root {
+            let arena = self.ui_descr_arena.borrow();
+            self.collect_counters_of_siblings(root, &*arena, 0, &mut Vec::new(), &mut node_counters);
+        }
+        node_counters
+    }
+
+    /// `counters` are the counters in scope, as `(name, value, depth of the node that reset the counter)`
+    fn collect_counters_of_siblings(
+        &self,
+        first_sibling: NodeId,
+        arena: &Arena<NodeData<T>>,
+        depth: usize,
+        counters: &mut Vec<(String, i32, usize)>,
+        node_counters: &mut BTreeMap<NodeId, Vec<(String, i32)>>)
+    {
+        let counters_of_parent = counters.len();
+
+        // NOTE: the iterator includes the node itself
+        for node_id in first_sibling.following_siblings(arena) {
+
+            let counter_reset = self.get_css_property(node_id, |property| match *property {
+                ParsedCssProperty::CounterReset(ref reset) => Some(reset.counters.clone()),
+                _ => None,
+            }).unwrap_or_default();
+
+            for (name, value) in counter_reset {
+                // Resetting a counter of a previous sibling replaces it instead of nesting it
+                match counters.iter_mut().skip(counters_of_parent).rev().find(|c| c.0 == name && c.2 == depth) {
+                    Some(counter) => counter.1 = value,
+                    None => counters.push((name, value, depth)),
+                }
+            }
+
+            let counter_increment = self.get_css_property(node_id, |property| match *property {
+                ParsedCssProperty::CounterIncrement(ref increment) => Some(increment.counters.clone()),
+                _ => None,
+            }).unwrap_or_default();
+
+            for (name, value) in counter_increment {
+                // Incrementing a counter that isn't in scope implicitly resets it to 0
+                match counters.iter_mut().rev().find(|c| c.0 == name) {
+                    Some(counter) => counter.1 += value,
+                    None => counters.push((name, value, depth)),
+                }
+            }
+
+            if !counters.is_empty() {
+                node_counters.insert(node_id, counters.iter().map(|&(ref name, value, _)| (name.clone(), value)).collect());
+            }
+
+            if let Some(first_child) = arena[node_id].first_child() {
+                self.collect_counters_of_siblings(first_child, arena, depth + 1, counters, node_counters);
+            }
+        }
+
+        // The counters that were reset by these siblings go out of scope
+        counters.truncate(counters_of_parent);
+    }
+
+    /// Returns the `scroll-behavior` of the node, `auto` if the node doesn't exist or has no style
+    pub(crate) fn get_scroll_behavior(&self, node_id: NodeId) -> StyleScrollBehavior {
+        self.get_css_property(node_id, |property| match *property {
+            ParsedCssProperty::ScrollBehavior(behavior) => Some(behavior),
+            _ => None,
+        }).unwrap_or_default()
+    }
+
+    /// Returns the `pointer-events` of the node (inherited from the parent nodes during the cascade)
+    pub(crate) fn get_pointer_events(&self, node_id: NodeId) -> StylePointerEvents {
+        self.get_css_property(node_id, |property| match *property {
+            ParsedCssProperty::PointerEvents(pointer_events) => Some(pointer_events),
+            _ => None,
+        }).unwrap_or_default()
+    }
+
+    /// Returns the `scroll-margin` of the node (merged with the `scroll-margin-*` longhands)
+    pub(crate) fn get_scroll_margin(&self, node_id: NodeId) -> StyleScrollMargin {
+        let mut scroll_margin = StyleScrollMargin::default();
+        self.for_each_css_property(node_id, |property| if let ParsedCssProperty::ScrollMargin(ref m) = *property {
+            scroll_margin.merge(m);
+        });
+        scroll_margin
+    }
+
+    /// Returns the `scroll-padding` of the node (merged with the `scroll-padding-*` longhands)
+    pub(crate) fn get_scroll_padding(&self, node_id: NodeId) -> StyleScrollPadding {
+        let mut scroll_padding = StyleScrollPadding::default();
+        self.for_each_css_property(node_id, |property| if let ParsedCssProperty::ScrollPadding(ref p) = *property {
+            scroll_padding.merge(p);
+        });
+        scroll_padding
+    }
+
+    /// Returns the `resize` property of the node - or `LayoutResize::None` if the
+    /// node isn't a scroll container, since only scroll containers can be resized
+    pub(crate) fn get_resize(&self, node_id: NodeId) -> LayoutResize {
+        let resize = self.get_css_property(node_id, |property| match *property {
+            ParsedCssProperty::Resize(resize) => Some(resize),
+            _ => None,
+        }).unwrap_or_default();
+
+        if !resize.is_resizable() || self.get_overflow(node_id).is_visible() {
+            LayoutResize::None
+        } else {
+            resize
+        }
+    }
+
+    /// Returns the `overflow` of the node - nodes with an `overflow` other than
+    /// `visible` are scroll containers, see `LayoutOverflow::is_visible()`
+    pub(crate) fn get_overflow(&self, node_id: NodeId) -> LayoutOverflow {
+        // `overflow`, `overflow-x` and `overflow-y` are merged in the order of the cascade
+        let mut overflow = LayoutOverflow::default();
+        if let Some(styled_node) = self.styled_nodes.get(&node_id) {
+            for &(_, ref declaration) in &styled_node.css_constraints.list {
+                let property = match *declaration {
+                    CssDeclaration::Static(ref property) => property,
+                    CssDeclaration::Dynamic(ref dynamic) => self.dynamic_css_overrides
+                        .get(&dynamic.dynamic_id).unwrap_or(&dynamic.default),
+                };
+                if let ParsedCssProperty::Overflow(ref o) = *property {
+                    overflow.merge(o);
+                }
+            }
+        }
+
+        overflow
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -70,7 +244,19 @@ pub(crate) struct StyledNode {
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub(crate) struct CssConstraintList {
-    pub(crate) list: Vec<CssDeclaration>
+    /// The declarations in the order of the cascade, along with the CSS key that they
+    /// were declared with (i.e. `"margin-top"`), shared with the `CssRule`
+    pub(crate) list: Vec<(Rc<str>, CssDeclaration)>
+}
+
+impl CssConstraintList {
+    /// Returns the declarations that the children of the node inherit (`color`, `font-size`, etc.),
+    /// in the same order - see `css_parser::is_inherited_property`
+    pub(crate) fn inherited(&self) -> Self {
+        Self {
+            list: self.list.iter().filter(|&&(ref key, _)| is_inherited_property(key)).cloned().collect(),
+        }
+    }
 }
 
 // Empty test, for some reason codecov doesn't detect any files (and therefore
@@ -79,4 +265,42 @@ pub(crate) struct CssConstraintList {
 #[test]
 fn __codecov_test_ui_description_file() {
 
+}
+#[test]
+fn test_collect_nested_counters() {
+    use dom::{Dom, NodeType, TestLayout};
+
+    // <ol> <li>1</li> <li>2 <ol> <li>2.1</li> </ol> </li> <li>3</li> </ol>
+    let dom = Dom::<TestLayout>::new(NodeType::Div).with_class("ol")
+        .with_child(Dom::new(NodeType::Div).with_class("li"))
+        .with_child(Dom::new(NodeType::Div).with_class("li")
+            .with_child(Dom::new(NodeType::Div).with_class("ol")
+                .with_child(Dom::new(NodeType::Div).with_class("li"))))
+        .with_child(Dom::new(NodeType::Div).with_class("li"));
+
+    let mut ui_description = UiDescription::<TestLayout>::default();
+    ui_description.ui_descr_arena = dom.arena.clone();
+    ui_description.ui_descr_root = Some(dom.root);
+
+    for node_id in dom.arena.borrow().linear_iter() {
+        let (key, value) = if dom.arena.borrow()[node_id].data.classes[0] == "ol" {
+            ("counter-reset", "item")
+        } else {
+            ("counter-increment", "item")
+        };
+        let mut styled_node = StyledNode::default();
+        let property = ParsedCssProperty::from_kv(key, value).unwrap();
+        styled_node.css_constraints.list.push((Rc::from(key), CssDeclaration::Static(property)));
+        ui_description.styled_nodes.insert(node_id, styled_node);
+    }
+
+    let counters = ui_description.collect_counters();
+    let item = |values: &[i32]| values.iter().map(|v| (String::from("item"), *v)).collect::<Vec<_>>();
+
+    assert_eq!(counters[&NodeId::new(0)], item(&[0]));
+    assert_eq!(counters[&NodeId::new(1)], item(&[1]));
+    assert_eq!(counters[&NodeId::new(2)], item(&[2]));
+    assert_eq!(counters[&NodeId::new(3)], item(&[2, 0]));
+    assert_eq!(counters[&NodeId::new(4)], item(&[2, 1]));
+    assert_eq!(counters[&NodeId::new(5)], item(&[3]));
 }
\ No newline at end of file
@@ -6,6 +6,7 @@ use {
     window::WindowInfo,
     traits::Layout,
     dom::{NODE_ID, CALLBACK_ID, Callback, Dom, On},
+    cache::DomMemoCache,
     app_state::AppState,
 };
 
@@ -30,27 +31,54 @@ impl<T: Layout> fmt::Debug for UiState<T> {
 }
 
 impl<T: Layout> UiState<T> {
-    #[allow(unused_imports, unused_variables)]
     pub(crate) fn from_app_state(app_state: &AppState<T>, window_info: WindowInfo) -> Self
     {
-        use dom::{Dom, On, NodeType};
         use std::sync::atomic::Ordering;
 
-        // Only shortly lock the data to get the dom out
-         let dom: Dom<T> = {
-            let dom_lock = app_state.data.lock().unwrap();
-            #[cfg(test)]{
-                Dom::<T>::new(NodeType::Div)
-            }
-            
-            #[cfg(not(test))]{
-                dom_lock.layout(window_info)
-            }
-        };
+        let dom = Self::call_layout(app_state, window_info);
 
+        DomMemoCache::remove_unused();
         NODE_ID.swap(0, Ordering::SeqCst);
         CALLBACK_ID.swap(0, Ordering::SeqCst);
 
+        Self::from_dom(dom)
+    }
+
+    /// Same as `from_app_state()`, but leaves the state of the windows untouched:
+    /// the IDs of the nodes and callbacks and the memoized DOMs (see `Dom::memoize()`)
+    /// stay the same, so that `App::inspect_dom()` can be called between two frames.
+    pub(crate) fn from_app_state_isolated(app_state: &AppState<T>, window_info: WindowInfo) -> Self {
+        use std::sync::atomic::Ordering;
+
+        let node_id = NODE_ID.load(Ordering::SeqCst);
+        let callback_id = CALLBACK_ID.load(Ordering::SeqCst);
+
+        let ui_state = DomMemoCache::isolated(|| Self::from_dom(Self::call_layout(app_state, window_info)));
+
+        NODE_ID.store(node_id, Ordering::SeqCst);
+        CALLBACK_ID.store(callback_id, Ordering::SeqCst);
+
+        ui_state
+    }
+
+    #[allow(unused_imports, unused_variables)]
+    fn call_layout(app_state: &AppState<T>, window_info: WindowInfo) -> Dom<T> {
+        use dom::NodeType;
+
+        // Only shortly lock the data to get the dom out
+        let dom_lock = app_state.data.lock().unwrap();
+        #[cfg(test)]{
+            Dom::<T>::new(NodeType::Div)
+        }
+
+        #[cfg(not(test))]{
+            dom_lock.layout(window_info)
+        }
+    }
+
+    /// Collects the callbacks of a DOM that is not the DOM of a window (i.e. the DOM of an
+    /// embedded pipeline). The IDs of the callbacks continue after the IDs of the window.
+    pub(crate) fn from_dom(dom: Dom<T>) -> Self {
         let mut callback_list = BTreeMap::<u64, Callback<T>>::new();
         let mut node_ids_to_callbacks_list = BTreeMap::<u64, BTreeMap<On, u64>>::new();
         dom.collect_callbacks(&mut callback_list, &mut node_ids_to_callbacks_list);
@@ -1,10 +1,17 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use glium::glutin::VirtualKeyCode;
 use {
     svg::{SvgCache, SvgLayerId},
-    window::ReadOnlyWindow,
+    window::{ReadOnlyWindow, WindowEvent, WindowInfo},
+    window_state::MouseState,
     traits::Layout,
-    dom::{Dom, NodeType},
+    dom::{Dom, NodeType, On, Callback, UpdateScreen},
+    app_state::AppState,
     images::ImageId,
+    ui_state::UiState,
+    window::UiSolver,
 };
+use webrender::api::{LayoutRect, LayoutPoint};
 
 // --- button
 
@@ -217,7 +224,7 @@ impl Label {
     }
 }
 
-// -- checkbox (TODO)
+// --- checkbox
 
 /// State of a checkbox (disabled, checked, etc.)
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
@@ -238,10 +245,1298 @@ pub enum CheckboxState {
     Unchecked
 }
 
+/// Checkbox with a label. The checkbox doesn't store any state itself - the
+/// `on_change` callback should toggle the value in your data model, which then
+/// re-renders the checkbox with the new state.
+///
+/// The appearance is completely done in CSS (see the `__azul-native-checkbox-*` classes).
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Checkbox {
+    pub state: CheckboxState,
+    pub label: String,
+}
+
+impl Checkbox {
+    pub fn new<S>(checked: bool, label: S)
+    -> Self where S: Into<String>
+    {
+        Self {
+            state: if checked { CheckboxState::Checked } else { CheckboxState::Unchecked },
+            label: label.into(),
+        }
+    }
+
+    pub fn with_state(self, state: CheckboxState)
+    -> Self
+    {
+        Self { state: state, .. self }
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.state == CheckboxState::Checked
+    }
+
+    /// Returns the new value of the checkbox if the key toggles it (space), `None` otherwise.
+    ///
+    /// Use this inside of the `on_change` callback if it was called for a key press
+    /// (`app_state.windows[event.window].get_keyboard_state().current_virtual_keycode`).
+    pub fn toggle_on_key(&self, key: VirtualKeyCode) -> Option<bool> {
+        match (key, self.state) {
+            (_, CheckboxState::Disabled { .. }) => None,
+            (VirtualKeyCode::Space, _) => Some(!self.is_checked()),
+            _ => None,
+        }
+    }
+
+    /// `on_change` is called when the checkbox (or its label) is clicked or when a key
+    /// is pressed while the checkbox has the keyboard focus (see `Checkbox::toggle_on_key`)
+    pub fn dom<T>(self, on_change: Callback<T>)
+    -> Dom<T> where T: Layout
+    {
+        use self::CheckboxState::*;
+
+        let mut checkbox_box = Dom::new(NodeType::Div).with_class("__azul-native-checkbox-box");
+        match self.state {
+            Checked => {
+                checkbox_box.set_class("__azul-native-checkbox-checked");
+                checkbox_box.add_child(Dom::new(NodeType::Label("\u{2714}".into())));
+            },
+            Disabled { .. } => checkbox_box.set_class("__azul-native-checkbox-disabled"),
+            Active | Unchecked => { },
+        }
+
+        let mut checkbox_root = Dom::new(NodeType::Div)
+            .with_class("__azul-native-checkbox")
+            .with_child(checkbox_box)
+            .with_child(Dom::new(NodeType::Label(self.label)));
+
+        let fires_callback = match self.state {
+            Disabled { fire_on_click } => fire_on_click,
+            _ => true,
+        };
+
+        if fires_callback {
+            checkbox_root.set_tab_index(0);
+            checkbox_root.set_callback(On::LeftMouseUp, on_change);
+            checkbox_root.set_callback(On::VirtualKeyDown, on_change);
+        }
+
+        checkbox_root
+    }
+}
+
+// --- radio group
+
+/// Group of radio buttons, of which only one can be selected at a time.
+///
+/// Since the group only renders the currently `selected` option, clicking one
+/// option automatically deselects all others once the `on_change` callback has
+/// updated the selection in your data model.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct RadioGroup {
+    pub options: Vec<String>,
+    pub selected: usize,
+}
+
+impl RadioGroup {
+    pub fn new(options: Vec<String>, selected: usize)
+    -> Self
+    {
+        Self { options, selected }
+    }
+
+    /// Returns the index of the option that was clicked, to be used inside of the
+    /// `on_change` callback (each option is a direct child of the radio group).
+    pub fn get_clicked_option(event: &WindowEvent) -> Option<usize> {
+        event.number_of_previous_siblings
+    }
+
+    /// Returns the newly selected option if the key navigates the radio group
+    /// (arrow keys, wraps around at the start / end), `None` otherwise.
+    ///
+    /// Use this inside of the `on_change` callback if it was called for a key press
+    /// (`app_state.windows[event.window].get_keyboard_state().current_virtual_keycode`).
+    pub fn select_on_key(&self, key: VirtualKeyCode) -> Option<usize> {
+        use glium::glutin::VirtualKeyCode::*;
+
+        if self.options.is_empty() {
+            return None;
+        }
+
+        let last = self.options.len() - 1;
+        match key {
+            Up | Left => Some(if self.selected == 0 { last } else { self.selected - 1 }),
+            Down | Right => Some(if self.selected >= last { 0 } else { self.selected + 1 }),
+            _ => None,
+        }
+    }
+
+    /// `on_change` is called when any of the options is clicked, use
+    /// `RadioGroup::get_clicked_option` to get the clicked option. It is also called when
+    /// a key is pressed while the radio group has the keyboard focus, use
+    /// `RadioGroup::select_on_key` to get the newly selected option in that case.
+    pub fn dom<T>(self, on_change: Callback<T>)
+    -> Dom<T> where T: Layout
+    {
+        let mut radio_group = Dom::new(NodeType::Div)
+            .with_class("__azul-native-radio-group")
+            .with_tab_index(0)
+            .with_callback(On::VirtualKeyDown, on_change);
+
+        for (idx, option) in self.options.into_iter().enumerate() {
+            let mut circle = Dom::new(NodeType::Div).with_class("__azul-native-radio-button-circle");
+            if idx == self.selected {
+                circle.set_class("__azul-native-radio-button-selected");
+            }
+
+            radio_group.add_child(Dom::new(NodeType::Div)
+                .with_class("__azul-native-radio-button")
+                .with_callback(On::LeftMouseUp, on_change)
+                .with_child(circle)
+                .with_child(Dom::new(NodeType::Label(option))));
+        }
+
+        radio_group
+    }
+}
+
+// --- toggle switch
+
+/// iOS-style on / off switch: a pill-shaped track (`.toggle-track`) with a
+/// thumb (`.toggle-thumb`) that is positioned on the left (`.toggle-off`)
+/// or on the right (`.toggle-on`) side of the track.
+///
+/// Like the `Checkbox`, the switch doesn't store any state itself, the
+/// `on_change` callback should update the value in your data model.
+///
+/// **NOTE**: There are no CSS transitions yet, so the thumb jumps to
+/// the other side instead of sliding.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct ToggleSwitch {
+    pub value: bool,
+}
+
+/// Minimum distance (in pixels) that the cursor has to move between pressing and releasing
+/// the mouse button, so that the `ToggleSwitch` treats it as a drag instead of a click
+const TOGGLE_SWITCH_DRAG_THRESHOLD: f32 = 4.0;
+
+impl ToggleSwitch {
+    pub fn new(value: bool)
+    -> Self
+    {
+        Self { value }
+    }
+
+    /// Returns the new value of the switch after it was clicked or dragged, to be used inside
+    /// of the `on_change` callback (`mouse_state` is `app_state.windows[event.window].get_mouse_state()`).
+    ///
+    /// A click toggles the switch. If the thumb was dragged to the left or to the right
+    /// instead, the switch takes the value of that side (dragging an "on" switch further
+    /// to the right keeps it on).
+    pub fn get_new_value(&self, event: &WindowEvent, mouse_state: &MouseState) -> bool {
+        let drag_distance = match mouse_state.left_down_position {
+            Some((press_x, _)) => event.cursor_in_viewport.0 - press_x as f32,
+            None => 0.0,
+        };
+
+        if drag_distance.abs() < TOGGLE_SWITCH_DRAG_THRESHOLD {
+            !self.value
+        } else {
+            drag_distance > 0.0
+        }
+    }
+
+    /// Returns the new value of the switch if the key toggles it (space), `None` otherwise.
+    ///
+    /// Use this inside of the `on_change` callback if it was called for a key press
+    /// (`app_state.windows[event.window].get_keyboard_state().current_virtual_keycode`).
+    pub fn toggle_on_key(&self, key: VirtualKeyCode) -> Option<bool> {
+        match key {
+            VirtualKeyCode::Space => Some(!self.value),
+            _ => None,
+        }
+    }
+
+    /// `on_change` is called when the switch is clicked, use
+    /// `ToggleSwitch::get_new_value` to get the new value of the switch. It is also called
+    /// when a key is pressed while the switch has the keyboard focus, use
+    /// `ToggleSwitch::toggle_on_key` to get the new value in that case.
+    pub fn dom<T>(self, on_change: Callback<T>)
+    -> Dom<T> where T: Layout
+    {
+        Dom::new(NodeType::Div)
+            .with_class("toggle-track")
+            .with_class(if self.value { "toggle-on" } else { "toggle-off" })
+            .with_tab_index(0)
+            .with_callback(On::LeftMouseUp, on_change)
+            .with_callback(On::VirtualKeyDown, on_change)
+            .with_child(Dom::new(NodeType::Div).with_class("toggle-thumb"))
+    }
+}
+
+// --- chip input
+
+/// Input field for multiple values ("chips" / "tags"), i.e. for
+/// email addresses, tags or filters.
+///
+/// The typed characters are sent to the `on_text_input` callback while the chip input
+/// has the keyboard focus. Forward them to `ChipInput::handle_char`, which returns
+/// whether the values changed (so that you can run your own `on_change` logic).
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct ChipInput {
+    /// Values that have been entered (one chip per value)
+    pub values: Vec<String>,
+    /// Text that is currently being typed, but not yet added as a chip
+    pub text: String,
+    /// Optional autocomplete suggestions
+    pub suggestions: Vec<String>,
+    /// Maximum number of chips, `None` = unlimited (default)
+    pub max_chips: Option<usize>,
+}
+
+impl ChipInput {
+    pub fn new(values: Vec<String>)
+    -> Self
+    {
+        Self {
+            values: values,
+            text: String::new(),
+            suggestions: Vec::new(),
+            max_chips: None,
+        }
+    }
+
+    pub fn with_suggestions(self, suggestions: Vec<String>)
+    -> Self
+    {
+        Self { suggestions, .. self }
+    }
+
+    pub fn with_max_chips(self, max_chips: usize)
+    -> Self
+    {
+        Self { max_chips: Some(max_chips), .. self }
+    }
+
+    /// Adds a new chip, returns false if the value is empty or the max. number of chips is reached
+    pub fn add_chip<S: Into<String>>(&mut self, value: S) -> bool {
+        let value = value.into().trim().to_string();
+        let is_full = self.max_chips.map(|max| self.values.len() >= max).unwrap_or(false);
+        if value.is_empty() || is_full {
+            return false;
+        }
+        self.values.push(value);
+        true
+    }
+
+    pub fn remove_chip(&mut self, index: usize) -> Option<String> {
+        if index < self.values.len() {
+            Some(self.values.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Handles one typed character: Enter or comma adds the current text as a chip,
+    /// Backspace deletes the last character (or the last chip, if the text is empty).
+    ///
+    /// Returns true if a chip was added or removed
+    pub fn handle_char(&mut self, c: char) -> bool {
+        match c {
+            '\r' | '\n' | ',' => {
+                let text = self.text.clone();
+                let added = self.add_chip(text);
+                if added {
+                    self.text.clear();
+                }
+                added
+            },
+            '\u{8}' => {
+                if self.text.pop().is_none() {
+                    self.values.pop().is_some()
+                } else {
+                    false
+                }
+            },
+            c if !c.is_control() => {
+                self.text.push(c);
+                false
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns the suggestions that start with the currently typed text
+    pub fn get_matching_suggestions(&self) -> Vec<&String> {
+        if self.text.is_empty() {
+            return Vec::new();
+        }
+        let text = self.text.to_lowercase();
+        self.suggestions.iter()
+            .filter(|s| s.to_lowercase().starts_with(&text) && !self.values.contains(*s))
+            .collect()
+    }
+
+    /// Inside of the `on_remove` callback: returns the index of the chip that should be removed
+    pub fn get_removed_chip(event: &WindowEvent) -> Option<usize> {
+        // Each chip consists of a label and a remove button
+        event.number_of_previous_siblings.map(|siblings| siblings / 2)
+    }
+
+    /// Inside of the `on_select_suggestion` callback: returns the index of the clicked
+    /// suggestion (in the list returned by `get_matching_suggestions`)
+    pub fn get_clicked_suggestion(event: &WindowEvent) -> Option<usize> {
+        event.number_of_previous_siblings
+    }
+
+    /// `on_text_input` is called when a character is typed while the chip input has the
+    /// keyboard focus (`app_state.windows[event.window].get_keyboard_state().current_char`),
+    /// `on_remove` is called when the `×` button of a chip is clicked,
+    /// `on_select_suggestion` is called when one of the suggestions is clicked
+    pub fn dom<T>(self, on_text_input: Callback<T>, on_remove: Callback<T>, on_select_suggestion: Callback<T>)
+    -> Dom<T> where T: Layout
+    {
+        let mut chips = Dom::new(NodeType::Div).with_class("__azul-native-chip-input-chips");
+
+        for value in &self.values {
+            chips.add_child(Dom::new(NodeType::Label(value.clone())).with_class("__azul-native-chip"));
+            chips.add_child(Dom::new(NodeType::Label("\u{d7}".into()))
+                .with_class("__azul-native-chip-remove")
+                .with_callback(On::LeftMouseUp, on_remove));
+        }
+
+        chips.add_child(Dom::new(NodeType::Label(self.text.clone())).with_class("__azul-native-chip-input-text"));
+
+        let mut chip_input = Dom::new(NodeType::Div)
+            .with_class("__azul-native-chip-input")
+            .with_tab_index(0)
+            .with_callback(On::TextInput, on_text_input)
+            .with_child(chips);
+
+        let suggestions = self.get_matching_suggestions();
+        if !suggestions.is_empty() {
+            let mut suggestion_list = Dom::new(NodeType::Div).with_class("__azul-native-chip-input-suggestions");
+            for suggestion in suggestions {
+                suggestion_list.add_child(Dom::new(NodeType::Label(suggestion.clone()))
+                    .with_class("__azul-native-chip-input-suggestion")
+                    .with_callback(On::LeftMouseUp, on_select_suggestion));
+            }
+            chip_input.add_child(suggestion_list);
+        }
+
+        chip_input
+    }
+}
+
+// --- wizard
+
+/// One step of a `Wizard`
+pub struct WizardStep<T: Layout> {
+    /// Title, shown next to the step number in the step indicator
+    pub title: String,
+    /// Renders the content of the step
+    pub content: fn(&T) -> Dom<T>,
+    /// Returns whether the user can advance to the next step
+    pub validate: fn(&T) -> bool,
+}
+
+impl<T: Layout> Clone for WizardStep<T> {
+    fn clone(&self) -> Self {
+        Self {
+            title: self.title.clone(),
+            content: self.content,
+            validate: self.validate,
+        }
+    }
+}
+
+/// Whether a step is already done, currently active or not yet reached
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum WizardStepState {
+    Completed,
+    Current,
+    Upcoming,
+}
+
+/// Multi-step form with a step indicator, the content of the current step
+/// and "Previous" / "Next" (or "Finish") buttons.
+///
+/// The wizard should be stored in your data model, since it keeps the
+/// index of the current step. Inside of the `on_next` callback, check
+/// `Wizard::can_advance` before calling `Wizard::next`.
+pub struct Wizard<T: Layout> {
+    pub steps: Vec<WizardStep<T>>,
+    current_step: usize,
+}
+
+impl<T: Layout> Clone for Wizard<T> {
+    fn clone(&self) -> Self {
+        Self {
+            steps: self.steps.clone(),
+            current_step: self.current_step,
+        }
+    }
+}
+
+impl<T: Layout> Wizard<T> {
+    pub fn new(steps: Vec<WizardStep<T>>)
+    -> Self
+    {
+        Self { steps, current_step: 0 }
+    }
+
+    pub fn current_step(&self) -> usize {
+        self.current_step
+    }
+
+    pub fn is_last_step(&self) -> bool {
+        self.current_step + 1 >= self.steps.len()
+    }
+
+    pub fn get_step_state(&self, step: usize) -> WizardStepState {
+        use std::cmp::Ordering::*;
+        match step.cmp(&self.current_step) {
+            Less => WizardStepState::Completed,
+            Equal => WizardStepState::Current,
+            Greater => WizardStepState::Upcoming,
+        }
+    }
+
+    /// Runs the `validate` function of the current step
+    pub fn can_advance(&self, data: &T) -> bool {
+        self.steps.get(self.current_step).map(|step| (step.validate)(data)).unwrap_or(false)
+    }
+
+    /// Goes to the next step, returns false if the wizard is already at the last step.
+    /// Note: this does not validate the current step, see `can_advance`.
+    pub fn next(&mut self) -> bool {
+        if self.is_last_step() {
+            false
+        } else {
+            self.current_step += 1;
+            true
+        }
+    }
+
+    /// Goes to the previous step, returns false if the wizard is already at the first step
+    pub fn previous(&mut self) -> bool {
+        if self.current_step == 0 {
+            false
+        } else {
+            self.current_step -= 1;
+            true
+        }
+    }
+
+    /// `on_next` is called when the "Next" button is clicked, `on_finish`
+    /// replaces `on_next` on the last step.
+    pub fn dom(&self, data: &T, on_previous: Callback<T>, on_next: Callback<T>, on_finish: Callback<T>)
+    -> Dom<T>
+    {
+        let mut step_indicator = Dom::new(NodeType::Div).with_class("__azul-native-wizard-steps");
+
+        for (idx, step) in self.steps.iter().enumerate() {
+            let state = self.get_step_state(idx);
+            let (circle_text, state_class) = match state {
+                WizardStepState::Completed => ("\u{2714}".to_string(), "__azul-native-wizard-step-completed"),
+                WizardStepState::Current => ((idx + 1).to_string(), "__azul-native-wizard-step-current"),
+                WizardStepState::Upcoming => ((idx + 1).to_string(), "__azul-native-wizard-step-upcoming"),
+            };
+
+            step_indicator.add_child(Dom::new(NodeType::Div)
+                .with_class("__azul-native-wizard-step")
+                .with_class(state_class)
+                .with_child(Dom::new(NodeType::Label(circle_text))
+                    .with_class("__azul-native-wizard-step-circle")
+                    .with_class(state_class))
+                .with_child(Dom::new(NodeType::Label(step.title.clone()))));
+        }
+
+        let content = match self.steps.get(self.current_step) {
+            Some(step) => (step.content)(data),
+            None => Dom::new(NodeType::Div),
+        };
+
+        let mut buttons = Dom::new(NodeType::Div).with_class("__azul-native-wizard-buttons");
+
+        if self.current_step > 0 {
+            buttons.add_child(Button::with_label("Previous").dom().with_callback(On::LeftMouseUp, on_previous));
+        }
+
+        if self.is_last_step() {
+            buttons.add_child(Button::with_label("Finish").dom().with_callback(On::LeftMouseUp, on_finish));
+        } else {
+            buttons.add_child(Button::with_label("Next").dom().with_callback(On::LeftMouseUp, on_next));
+        }
+
+        Dom::new(NodeType::Div)
+            .with_class("__azul-native-wizard")
+            .with_child(step_indicator)
+            .with_child(Dom::new(NodeType::Div).with_class("__azul-native-wizard-content").with_child(content))
+            .with_child(buttons)
+    }
+}
+
+// --- searchable list
+
+/// How the search text is matched against the items of a `SearchableList`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SearchMatchMode {
+    /// Case-insensitive substring match (default)
+    Substring,
+    /// Case-sensitive substring match
+    CaseSensitiveSubstring,
+    /// Case-insensitive match at the start of the item
+    Prefix,
+}
+
+impl Default for SearchMatchMode {
+    fn default() -> Self {
+        SearchMatchMode::Substring
+    }
+}
+
+impl SearchMatchMode {
+    /// Returns the byte range of the first match of `search` inside of `item`
+    pub fn find_match(&self, item: &str, search: &str) -> Option<(usize, usize)> {
+        use self::SearchMatchMode::*;
+
+        let case_sensitive = *self == CaseSensitiveSubstring;
+        let chars_match = |a: char, b: char| {
+            if case_sensitive { a == b } else { a.to_lowercase().eq(b.to_lowercase()) }
+        };
+
+        let match_at = |start: usize| -> Option<usize> {
+            let mut search_chars = search.chars();
+            let mut end = start;
+            for (idx, c) in item[start..].char_indices() {
+                match search_chars.next() {
+                    Some(s) if chars_match(c, s) => { end = start + idx + c.len_utf8(); },
+                    Some(_) => return None,
+                    None => return Some(end),
+                }
+            }
+            if search_chars.next().is_none() { Some(end) } else { None }
+        };
+
+        match *self {
+            Prefix => match_at(0).map(|end| (0, end)),
+            Substring | CaseSensitiveSubstring => {
+                item.char_indices()
+                    .map(|(idx, _)| idx)
+                    .chain(::std::iter::once(item.len()))
+                    .filter_map(|start| match_at(start).map(|end| (start, end)))
+                    .next()
+            }
+        }
+    }
+}
+
+/// Text input with a list of items below it, which is filtered by the entered text
+/// (for command palettes, search bars, autocomplete dropdowns, etc.)
+///
+/// The typed characters and pressed keys are sent to the `on_input` callback while the
+/// list has the keyboard focus. Forward them to `SearchableList::handle_char`
+/// and `SearchableList::handle_key`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct SearchableList {
+    pub items: Vec<String>,
+    pub search_text: String,
+    pub match_mode: SearchMatchMode,
+    /// Index of the highlighted item in the filtered list
+    pub highlighted: usize,
+    /// Text that is shown in the input while the search text is empty,
+    /// the input matches `:placeholder-shown` while it is visible
+    pub placeholder: Option<String>,
+}
+
+impl SearchableList {
+    pub fn new(items: Vec<String>)
+    -> Self
+    {
+        Self {
+            items: items,
+            search_text: String::new(),
+            match_mode: SearchMatchMode::default(),
+            highlighted: 0,
+            placeholder: None,
+        }
+    }
+
+    pub fn with_match_mode(self, match_mode: SearchMatchMode)
+    -> Self
+    {
+        Self { match_mode, .. self }
+    }
+
+    pub fn with_placeholder<S: Into<String>>(self, placeholder: S)
+    -> Self
+    {
+        Self { placeholder: Some(placeholder.into()), .. self }
+    }
+
+    /// Returns the `(index in self.items, item, byte range of the match)` of all matching items
+    pub fn get_filtered_items(&self) -> Vec<(usize, &str, (usize, usize))> {
+        self.items.iter().enumerate().filter_map(|(idx, item)| {
+            self.match_mode.find_match(item, &self.search_text).map(|range| (idx, item.as_str(), range))
+        }).collect()
+    }
+
+    /// Handles one typed character, returns the index (in `self.items`)
+    /// of the selected item if the character was Enter
+    pub fn handle_char(&mut self, c: char) -> Option<usize> {
+        match c {
+            '\r' | '\n' => {
+                return self.get_filtered_items().get(self.highlighted).map(|&(idx, _, _)| idx);
+            },
+            '\u{8}' => { self.search_text.pop(); },
+            c if !c.is_control() => self.search_text.push(c),
+            _ => { },
+        }
+        self.highlighted = 0;
+        None
+    }
+
+    /// Moves the highlighted item up / down with the arrow keys, returns
+    /// whether the highlight has changed
+    pub fn handle_key(&mut self, key: VirtualKeyCode) -> bool {
+        let filtered_len = self.get_filtered_items().len();
+        match key {
+            VirtualKeyCode::Up if self.highlighted > 0 => {
+                self.highlighted -= 1;
+                true
+            },
+            VirtualKeyCode::Down if self.highlighted + 1 < filtered_len => {
+                self.highlighted += 1;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Inside of the `on_select` callback: returns the index (in `self.items`) of the clicked item
+    pub fn get_clicked_item(&self, event: &WindowEvent) -> Option<usize> {
+        let filtered_index = event.number_of_previous_siblings?;
+        self.get_filtered_items().get(filtered_index).map(|&(idx, _, _)| idx)
+    }
+
+    /// `on_input` is called when a character is typed (`current_char`) or a key is pressed
+    /// (`current_virtual_keycode` of `app_state.windows[event.window].get_keyboard_state()`)
+    /// while the list has the keyboard focus, `on_select` is called when an item of the
+    /// filtered list is clicked
+    pub fn dom<T>(self, on_input: Callback<T>, on_select: Callback<T>)
+    -> Dom<T> where T: Layout
+    {
+        let mut list = Dom::new(NodeType::Div).with_class("__azul-native-searchable-list-items");
+
+        for (filtered_idx, (_, item, (start, end))) in self.get_filtered_items().into_iter().enumerate() {
+            // Split the item into three labels, so that the matching part can be highlighted
+            let mut item_dom = Dom::new(NodeType::Div)
+                .with_class("__azul-native-searchable-list-item")
+                .with_callback(On::LeftMouseUp, on_select);
+
+            if filtered_idx == self.highlighted {
+                item_dom.set_class("__azul-native-searchable-list-item-selected");
+            }
+
+            if start > 0 {
+                item_dom.add_child(Dom::new(NodeType::Label(item[..start].into())));
+            }
+            if end > start {
+                item_dom.add_child(Dom::new(NodeType::Label(item[start..end].into()))
+                    .with_class("__azul-native-searchable-list-match"));
+            }
+            if end < item.len() {
+                item_dom.add_child(Dom::new(NodeType::Label(item[end..].into())));
+            }
+
+            list.add_child(item_dom);
+        }
+
+        // NOTE: the placeholder is only swapped in, there are no CSS transitions
+        // or sibling selectors yet, so "floating labels" have to be done via `:placeholder-shown`
+        // on the input itself
+        let (input_text, placeholder_shown) = match self.placeholder {
+            Some(ref placeholder) if self.search_text.is_empty() => (placeholder.clone(), true),
+            _ => (self.search_text.clone(), false),
+        };
+
+        Dom::new(NodeType::Div)
+            .with_class("__azul-native-searchable-list")
+            .with_tab_index(0)
+            .with_callback(On::TextInput, on_input)
+            .with_callback(On::VirtualKeyDown, on_input)
+            .with_child(Dom::new(NodeType::Label(input_text))
+                .with_class("__azul-native-searchable-list-input")
+                .with_placeholder_shown(placeholder_shown))
+            .with_child(list)
+    }
+}
+
+// --- drawer
+
+static DRAWER_ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// ID of a `Drawer`, used to open / close the drawer via `FakeWindow::open_drawer`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DrawerId {
+    id: usize,
+}
+
+impl DrawerId {
+    pub fn new() -> Self {
+        Self { id: DRAWER_ID_COUNTER.fetch_add(1, Ordering::SeqCst) }
+    }
+}
+
+/// Which side of the window the drawer is attached to
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DrawerSide {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// Options for the behaviour of the drawer. The width / height of the drawer
+/// is set via the `__azul-native-drawer-horizontal` and `__azul-native-drawer-vertical`
+/// CSS classes, since inline styles are not supported.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DrawerOptions {
+    /// Whether clicking on the (darkened) main content closes the drawer
+    pub close_on_overlay_click: bool,
+    /// Whether swiping from the edge of the window should open the drawer, see `Drawer::is_edge_swipe`
+    pub open_on_edge_swipe: bool,
+}
+
+impl Default for DrawerOptions {
+    fn default() -> Self {
+        Self {
+            close_on_overlay_click: true,
+            open_on_edge_swipe: false,
+        }
+    }
+}
+
+/// Sliding sidebar, that is shown next to the main content of the window if it
+/// has been opened via `FakeWindow::open_drawer`. While the drawer is open, the
+/// main content is darkened by the `__azul-native-drawer-overlay` class.
+///
+/// **NOTE**: Since the layout has no absolute positioning and no CSS transitions yet,
+/// the drawer takes up space next to the main content (instead of overlapping it)
+/// and appears without a slide-in animation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Drawer {
+    pub id: DrawerId,
+    pub side: DrawerSide,
+    pub options: DrawerOptions,
+}
+
+/// Distance from the window edge (in pixels) in which a swipe has to start to open a drawer
+const DRAWER_EDGE_SWIPE_AREA: f32 = 20.0;
+/// Minimum distance (in pixels) of a swipe to open a drawer
+const DRAWER_MIN_SWIPE_DISTANCE: f32 = 50.0;
+
+impl Drawer {
+    pub fn new(id: DrawerId, side: DrawerSide, options: DrawerOptions)
+    -> Self
+    {
+        Self { id, side, options }
+    }
+
+    /// Returns whether a swipe from `start` to `end` (window coordinates) starts at the
+    /// edge of the window on the side of the drawer and moves towards the center, i.e.
+    /// whether the drawer should be opened. Always false if `open_on_edge_swipe` is disabled.
+    pub fn is_edge_swipe(&self, start: (f32, f32), end: (f32, f32), window_size: (f32, f32)) -> bool {
+        use self::DrawerSide::*;
+
+        if !self.options.open_on_edge_swipe {
+            return false;
+        }
+
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        match self.side {
+            Left => start.0 <= DRAWER_EDGE_SWIPE_AREA && dx >= DRAWER_MIN_SWIPE_DISTANCE,
+            Right => start.0 >= window_size.0 - DRAWER_EDGE_SWIPE_AREA && -dx >= DRAWER_MIN_SWIPE_DISTANCE,
+            Top => start.1 <= DRAWER_EDGE_SWIPE_AREA && dy >= DRAWER_MIN_SWIPE_DISTANCE,
+            Bottom => start.1 >= window_size.1 - DRAWER_EDGE_SWIPE_AREA && -dy >= DRAWER_MIN_SWIPE_DISTANCE,
+        }
+    }
+
+    /// Returns the `main_content` with the `drawer_content` attached to the side
+    /// of it, if the drawer is currently open
+    pub fn dom<T>(self, main_content: Dom<T>, drawer_content: Dom<T>, window_info: &WindowInfo)
+    -> Dom<T> where T: Layout
+    {
+        use self::DrawerSide::*;
+
+        if !window_info.is_drawer_open(self.id) {
+            return main_content;
+        }
+
+        let (container_class, drawer_class) = match self.side {
+            Left | Right => ("__azul-native-drawer-container-horizontal", "__azul-native-drawer-horizontal"),
+            Top | Bottom => ("__azul-native-drawer-container-vertical", "__azul-native-drawer-vertical"),
+        };
+
+        let mut overlay = Dom::new(NodeType::Div)
+            .with_class("__azul-native-drawer-overlay")
+            .with_child(main_content);
+
+        if self.options.close_on_overlay_click {
+            overlay.set_callback(On::LeftMouseUp, Callback(close_drawers_on_overlay_click));
+        }
+
+        let drawer = Dom::new(NodeType::Div)
+            .with_class(DRAWER_CLASS)
+            .with_class(drawer_class)
+            .with_child(drawer_content);
+
+        let container = Dom::new(NodeType::Div).with_class(container_class);
+
+        match self.side {
+            Left | Top => container.with_child(drawer).with_child(overlay),
+            Right | Bottom => container.with_child(overlay).with_child(drawer),
+        }
+    }
+}
+
+/// Class of the node that contains the `drawer_content`, see `get_drawer_bounds`
+const DRAWER_CLASS: &str = "__azul-native-drawer";
+
+/// Returns the bounds of the open drawers in the last layout of the window.
+///
+/// Since the overlay covers the whole window, the clicks inside of a drawer hit the
+/// overlay, too - `close_drawers_on_overlay_click` uses these bounds to ignore them.
+pub(crate) fn get_drawer_bounds<T: Layout>(ui_state: &UiState<T>, ui_solver: &UiSolver<T>) -> Vec<LayoutRect> {
+    let arena = ui_state.dom.arena.borrow();
+    arena.linear_iter()
+        .filter(|node_id| arena[*node_id].data.classes.iter().any(|class| class == DRAWER_CLASS))
+        .filter_map(|node_id| ui_solver.query_bounds_of_rect(node_id))
+        .collect()
+}
+
+/// Callback for the overlay - closes all drawers of the window that was clicked,
+/// unless the click was inside of a drawer
+fn close_drawers_on_overlay_click<T: Layout>(app_state: &mut AppState<T>, event: WindowEvent) -> UpdateScreen {
+    let (cursor_x, cursor_y) = event.cursor_in_viewport;
+    let cursor = LayoutPoint::new(cursor_x, cursor_y);
+    match app_state.windows.get_mut(event.window) {
+        Some(window) if !window.drawer_bounds.iter().any(|bounds| bounds.contains(&cursor)) => {
+            window.close_all_drawers();
+            UpdateScreen::Redraw
+        },
+        _ => UpdateScreen::DontRedraw,
+    }
+}
+
+// --- status bar
+
+/// Horizontal bar (usually at the bottom of the window) with a left, center and right zone.
+/// The height, colors, font and border are set via the `__azul-native-status-bar` CSS class.
+///
+/// To make a zone interactive, add a callback to the `Dom` of the zone.
+pub struct StatusBar<T: Layout> {
+    pub left: Dom<T>,
+    pub center: Dom<T>,
+    pub right: Dom<T>,
+}
+
+impl<T: Layout> StatusBar<T> {
+    pub fn new(left: Dom<T>, center: Dom<T>, right: Dom<T>)
+    -> Self
+    {
+        Self { left, center, right }
+    }
+
+    /// Status bar with one text on the left and one text on the right side
+    pub fn text(left: &str, right: &str)
+    -> Self
+    {
+        Self {
+            left: Dom::new(NodeType::Label(left.into())),
+            center: Dom::new(NodeType::Div),
+            right: Dom::new(NodeType::Label(right.into())),
+        }
+    }
+
+    pub fn dom(self)
+    -> Dom<T>
+    {
+        Dom::new(NodeType::Div)
+            .with_class("__azul-native-status-bar")
+            .with_child(Dom::new(NodeType::Div).with_class("__azul-native-status-bar-left").with_child(self.left))
+            .with_child(Dom::new(NodeType::Div).with_class("__azul-native-status-bar-center").with_child(self.center))
+            .with_child(Dom::new(NodeType::Div).with_class("__azul-native-status-bar-right").with_child(self.right))
+    }
+}
+
+// --- toolbar
+
+/// Item of a `Toolbar`
+pub enum ToolbarItem<T: Layout> {
+    /// Button with a text and an optional icon
+    Button { label: String, icon: Option<ImageId>, callback: Callback<T> },
+    /// Vertical line between two items
+    Separator,
+    /// Button that opens a dropdown menu below it
+    Dropdown { label: String, items: Vec<ToolbarMenuItem<T>> },
+    /// Empty space between two items
+    Spacer,
+}
+
+impl<T: Layout> ToolbarItem<T> {
+    fn is_focusable(&self) -> bool {
+        match self {
+            ToolbarItem::Button { .. } | ToolbarItem::Dropdown { .. } => true,
+            ToolbarItem::Separator | ToolbarItem::Spacer => false,
+        }
+    }
+}
+
+/// Item of a `ToolbarItem::Dropdown` menu
+pub enum ToolbarMenuItem<T: Layout> {
+    Item { label: String, callback: Callback<T> },
+    Separator,
+}
+
+impl<T: Layout> ToolbarMenuItem<T> {
+    fn is_selectable(&self) -> bool {
+        match self {
+            ToolbarMenuItem::Item { .. } => true,
+            ToolbarMenuItem::Separator => false,
+        }
+    }
+}
+
+/// Menu bar / toolbar. The toolbar should be stored in your data model,
+/// since it keeps track of the open dropdown and the keyboard focus.
+///
+/// The pressed keys are sent to the `on_key` callback while the toolbar has the keyboard
+/// focus, forward them to `Toolbar::handle_key`: Alt activates the toolbar, the arrow keys
+/// navigate the items / dropdown menus, Enter activates the focused item, Escape
+/// closes the dropdown (or deactivates the toolbar). Since keyboard events only go to
+/// the focused node, pressing Alt while another node is focused doesn't reach the toolbar.
+pub struct Toolbar<T: Layout> {
+    pub items: Vec<ToolbarItem<T>>,
+    /// Item that has the keyboard focus (`None` if the toolbar isn't activated)
+    pub focused_item: Option<usize>,
+    /// The `ToolbarItem::Dropdown` that is currently open
+    pub open_dropdown: Option<usize>,
+    /// The highlighted item in the open dropdown menu
+    pub highlighted_menu_item: Option<usize>,
+}
+
+/// Returns the next (or previous) index for which `is_selectable` is true, wrapping around at the end
+fn get_next_selectable_index<F: Fn(usize) -> bool>(len: usize, current: Option<usize>, forward: bool, is_selectable: F)
+-> Option<usize>
+{
+    for offset in 1..(len + 1) {
+        let idx = match (current, forward) {
+            (None, true) => offset - 1,
+            (None, false) => len - offset,
+            (Some(c), true) => (c + offset) % len,
+            (Some(c), false) => (c + len - (offset % len)) % len,
+        };
+        if is_selectable(idx) {
+            return Some(idx);
+        }
+    }
+    None
+}
+
+impl<T: Layout> Toolbar<T> {
+    pub fn new(items: Vec<ToolbarItem<T>>)
+    -> Self
+    {
+        Self {
+            items: items,
+            focused_item: None,
+            open_dropdown: None,
+            highlighted_menu_item: None,
+        }
+    }
+
+    /// Inside of the `on_dropdown_click` callback: returns the index of the clicked item
+    pub fn get_clicked_item(event: &WindowEvent) -> Option<usize> {
+        event.number_of_previous_siblings
+    }
+
+    /// Opens the dropdown (or closes it, if it is already open)
+    pub fn toggle_dropdown(&mut self, item: usize) {
+        if self.open_dropdown == Some(item) {
+            self.close_dropdown();
+        } else if let Some(ToolbarItem::Dropdown { .. }) = self.items.get(item) {
+            self.open_dropdown = Some(item);
+            self.highlighted_menu_item = None;
+        }
+    }
+
+    pub fn close_dropdown(&mut self) {
+        self.open_dropdown = None;
+        self.highlighted_menu_item = None;
+    }
+
+    /// Closes the dropdown and removes the keyboard focus
+    pub fn deactivate(&mut self) {
+        self.close_dropdown();
+        self.focused_item = None;
+    }
+
+    fn move_focus(&mut self, forward: bool) {
+        let items = &self.items;
+        self.focused_item = get_next_selectable_index(items.len(), self.focused_item, forward, |idx| items[idx].is_focusable());
+        if self.open_dropdown.is_some() {
+            self.close_dropdown();
+            if let Some(focused) = self.focused_item {
+                self.toggle_dropdown(focused);
+            }
+        }
+    }
+
+    fn move_menu_highlight(&mut self, forward: bool) {
+        let toolbar_items = &self.items;
+        if let Some(ToolbarItem::Dropdown { items, .. }) = self.open_dropdown.and_then(|d| toolbar_items.get(d)) {
+            self.highlighted_menu_item = get_next_selectable_index(items.len(), self.highlighted_menu_item, forward, |idx| items[idx].is_selectable());
+        }
+    }
+
+    /// Handles a key press, returns the callback of the item that was activated (via Enter),
+    /// which you then have to call yourself.
+    pub fn handle_key(&mut self, key: VirtualKeyCode) -> Option<Callback<T>> {
+        use glium::glutin::VirtualKeyCode::*;
+
+        match key {
+            LAlt | RAlt => {
+                if self.focused_item.is_some() {
+                    self.deactivate();
+                } else {
+                    self.move_focus(true);
+                }
+            },
+            _ if self.focused_item.is_none() => { },
+            Escape => {
+                if self.open_dropdown.is_some() {
+                    self.close_dropdown();
+                } else {
+                    self.deactivate();
+                }
+            },
+            Left => self.move_focus(false),
+            Right => self.move_focus(true),
+            Down | Up => {
+                if self.open_dropdown.is_none() {
+                    if let Some(focused) = self.focused_item {
+                        self.toggle_dropdown(focused);
+                    }
+                }
+                self.move_menu_highlight(key == Down);
+            },
+            Return => {
+                let callback = match (self.open_dropdown, self.highlighted_menu_item) {
+                    (Some(dropdown), Some(menu_item)) => match self.items.get(dropdown) {
+                        Some(ToolbarItem::Dropdown { items, .. }) => match items.get(menu_item) {
+                            Some(ToolbarMenuItem::Item { callback, .. }) => Some(*callback),
+                            _ => None,
+                        },
+                        _ => None,
+                    },
+                    _ => match self.focused_item.and_then(|f| self.items.get(f)) {
+                        Some(ToolbarItem::Button { callback, .. }) => Some(*callback),
+                        _ => None,
+                    },
+                };
+
+                match callback {
+                    Some(_) => self.deactivate(),
+                    None => {
+                        if let Some(focused) = self.focused_item {
+                            self.toggle_dropdown(focused);
+                        }
+                    },
+                }
+
+                return callback;
+            },
+            _ => { },
+        }
+
+        None
+    }
+
+    /// `on_key` is called when a key is pressed while the toolbar has the keyboard focus
+    /// (`app_state.windows[event.window].get_keyboard_state().current_virtual_keycode`).
+    /// `on_dropdown_click` is called when a `ToolbarItem::Dropdown` is clicked,
+    /// use `Toolbar::get_clicked_item` and `Toolbar::toggle_dropdown` to open the dropdown.
+    pub fn dom(&self, on_key: Callback<T>, on_dropdown_click: Callback<T>)
+    -> Dom<T>
+    {
+        let mut toolbar = Dom::new(NodeType::Div)
+            .with_class("__azul-native-toolbar")
+            .with_tab_index(0)
+            .with_callback(On::VirtualKeyDown, on_key);
+
+        for (idx, item) in self.items.iter().enumerate() {
+            let mut item_dom = match item {
+                ToolbarItem::Button { label, icon, callback } => {
+                    let mut button = Dom::new(NodeType::Div).with_class("__azul-native-toolbar-button");
+                    if let Some(icon) = icon {
+                        button.add_child(Dom::new(NodeType::Image(*icon)));
+                    }
+                    button.add_child(Dom::new(NodeType::Label(label.clone())));
+                    button.with_callback(On::LeftMouseUp, *callback)
+                },
+                ToolbarItem::Separator => Dom::new(NodeType::Div).with_class("__azul-native-toolbar-separator"),
+                ToolbarItem::Spacer => Dom::new(NodeType::Div).with_class("__azul-native-toolbar-spacer"),
+                ToolbarItem::Dropdown { label, items } => {
+                    let mut dropdown = Dom::new(NodeType::Div)
+                        .with_class("__azul-native-toolbar-dropdown")
+                        .with_callback(On::LeftMouseUp, on_dropdown_click)
+                        .with_child(Dom::new(NodeType::Label(label.clone())));
+
+                    // The menu is a child of the dropdown, so that it is positioned below the button
+                    if self.open_dropdown == Some(idx) {
+                        let mut menu = Dom::new(NodeType::Div).with_class("__azul-native-toolbar-dropdown-menu");
+                        for (menu_idx, menu_item) in items.iter().enumerate() {
+                            let mut menu_item_dom = match menu_item {
+                                ToolbarMenuItem::Item { label, callback } => {
+                                    Dom::new(NodeType::Label(label.clone()))
+                                        .with_class("__azul-native-toolbar-menu-item")
+                                        .with_callback(On::LeftMouseUp, *callback)
+                                },
+                                ToolbarMenuItem::Separator => {
+                                    Dom::new(NodeType::Div).with_class("__azul-native-toolbar-menu-separator")
+                                },
+                            };
+                            if self.highlighted_menu_item == Some(menu_idx) {
+                                menu_item_dom.set_class("__azul-native-toolbar-menu-item-highlighted");
+                            }
+                            menu.add_child(menu_item_dom);
+                        }
+                        dropdown.add_child(menu);
+                    }
+
+                    dropdown
+                },
+            };
+
+            item_dom.set_class("__azul-native-toolbar-item");
+            if self.focused_item == Some(idx) {
+                item_dom.set_class("__azul-native-toolbar-item-focused");
+            }
+            toolbar.add_child(item_dom);
+        }
+
+        toolbar
+    }
+}
+
 // Empty test, for some reason codecov doesn't detect any files (and therefore
 // doesn't report codecov % correctly) except if they have at least one test in
 // the file. This is an empty test, which should be updated later on
 #[test]
 fn __codecov_test_widgets_file() {
 
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_checkbox_dom_is_focusable() {
+    use dom::TestLayout;
+
+    fn on_change(_: &mut AppState<TestLayout>, _: WindowEvent) -> UpdateScreen {
+        UpdateScreen::DontRedraw
+    }
+
+    let dom = Checkbox::new(false, "a").dom(Callback(on_change));
+    let arena = dom.arena.borrow();
+    assert_eq!(arena[dom.head].data.tab_index, Some(0));
+    assert!(arena[dom.head].data.events.callbacks.contains_key(&On::VirtualKeyDown));
+
+    let disabled = Checkbox::new(false, "a")
+        .with_state(CheckboxState::Disabled { fire_on_click: false })
+        .dom(Callback(on_change));
+    assert_eq!(disabled.arena.borrow()[disabled.head].data.tab_index, None);
+}
+
+#[test]
+fn test_radio_group_select_on_key() {
+    let group = RadioGroup::new(vec!["a".into(), "b".into(), "c".into()], 0);
+    assert_eq!(group.select_on_key(VirtualKeyCode::Down), Some(1));
+    assert_eq!(group.select_on_key(VirtualKeyCode::Up), Some(2));
+    assert_eq!(group.select_on_key(VirtualKeyCode::A), None);
+}
+
+#[test]
+fn test_chip_input_handle_char() {
+    let mut input = ChipInput::new(Vec::new()).with_max_chips(1);
+    assert!(!input.handle_char('a'));
+    assert!(input.handle_char(','));
+    assert_eq!(input.values, vec![String::from("a")]);
+    input.handle_char('b');
+    assert!(!input.handle_char('\r'));
+    assert!(!input.handle_char('\u{8}'));
+    assert!(input.handle_char('\u{8}'));
+    assert!(input.values.is_empty());
+}
+
+#[test]
+fn test_search_match_mode() {
+    assert_eq!(SearchMatchMode::Substring.find_match("Open File", "file"), Some((5, 9)));
+    assert_eq!(SearchMatchMode::CaseSensitiveSubstring.find_match("Open File", "file"), None);
+    assert_eq!(SearchMatchMode::Prefix.find_match("Open File", "op"), Some((0, 2)));
+    assert_eq!(SearchMatchMode::Prefix.find_match("Open File", "file"), None);
+    assert_eq!(SearchMatchMode::Substring.find_match("Open File", ""), Some((0, 0)));
+}
+
+#[test]
+fn test_get_next_selectable_index() {
+    let selectable = [false, true, false, true];
+    assert_eq!(get_next_selectable_index(4, None, true, |i| selectable[i]), Some(1));
+    assert_eq!(get_next_selectable_index(4, Some(1), true, |i| selectable[i]), Some(3));
+    assert_eq!(get_next_selectable_index(4, Some(3), true, |i| selectable[i]), Some(1));
+    assert_eq!(get_next_selectable_index(4, Some(1), false, |i| selectable[i]), Some(3));
+    assert_eq!(get_next_selectable_index(4, None, true, |_| false), None);
+}
+
+#[test]
+fn test_toggle_switch_get_new_value() {
+    let at = |x: f32| WindowEvent { cursor_in_viewport: (x, 0.0), .. WindowEvent::mock() };
+    let pressed_at = |x: f64| MouseState { left_down_position: Some((x, 0.0)), .. MouseState::default() };
+
+    let off = ToggleSwitch::new(false);
+    let on = ToggleSwitch::new(true);
+
+    // clicks toggle the switch
+    assert!(off.get_new_value(&at(10.0), &pressed_at(10.0)));
+    assert!(!on.get_new_value(&at(10.0), &pressed_at(11.0)));
+
+    // drags set the value of the side that the thumb was dragged to
+    assert!(off.get_new_value(&at(30.0), &pressed_at(10.0)));
+    assert!(on.get_new_value(&at(30.0), &pressed_at(10.0)));
+    assert!(!on.get_new_value(&at(10.0), &pressed_at(30.0)));
+    assert!(!off.get_new_value(&at(10.0), &pressed_at(30.0)));
+}
+
+#[test]
+fn test_close_drawers_on_overlay_click() {
+    use webrender::api::LayoutSize;
+    use window::FakeWindow;
+    use dom::TestLayout;
+
+    let drawer = DrawerId::new();
+    let mut window = FakeWindow::new_simulated();
+    window.open_drawer(drawer);
+    window.drawer_bounds = vec![LayoutRect::new(LayoutPoint::new(0.0, 0.0), LayoutSize::new(200.0, 600.0))];
+
+    let mut app_state = AppState::new(TestLayout { });
+    app_state.windows.push(window);
+    let click_at = |x: f32| WindowEvent { cursor_in_viewport: (x, 100.0), .. WindowEvent::mock() };
+
+    // clicks inside of the drawer keep it open
+    assert_eq!(close_drawers_on_overlay_click(&mut app_state, click_at(100.0)), UpdateScreen::DontRedraw);
+    assert!(app_state.windows[0].is_drawer_open(drawer));
+
+    // clicks on the overlay close it
+    assert_eq!(close_drawers_on_overlay_click(&mut app_state, click_at(300.0)), UpdateScreen::Redraw);
+    assert!(!app_state.windows[0].is_drawer_open(drawer));
+}
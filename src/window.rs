@@ -1,13 +1,20 @@
 //! Window creation module
 
 use std::{
-    time::Duration,
+    time::{Duration, Instant},
     fmt,
-    rc::Rc
+    rc::{Rc, Weak},
+    cell::RefCell,
+    any::Any,
+    collections::BTreeMap,
+    sync::atomic::{AtomicUsize, Ordering},
+    path::{Path, PathBuf},
+    io::Error as IoError,
+    ops::Range,
 };
 use webrender::{
     api::*,
-    Renderer, RendererOptions, RendererKind,
+    Renderer, RendererOptions, RendererKind, ProgramCache,
     // renderer::RendererError; -- not currently public in WebRender
 };
 use glium::{
@@ -18,6 +25,7 @@ use glium::{
     backend::{Context, Facade, glutin::DisplayCreationError},
 };
 use gleam::gl::{self, Gl};
+use log::LevelFilter;
 use euclid::TypedScale;
 use cassowary::{
     Variable, Solver,
@@ -25,25 +33,80 @@ use cassowary::{
 };
 
 use {
-    dom::Texture,
+    FastHashMap, FastHashSet,
+    dom::{Dom, Texture},
     css::{Css, FakeCss},
-    window_state::{WindowState, MouseState, KeyboardState, WindowPosition},
+    window_state::{WindowState, MouseState, KeyboardState, WindowPosition, AaMode, TouchState, PinchGesture, RotateGesture, TouchId, TouchPoint, MultiTouchState},
     display_list::SolvedLayout,
     traits::Layout,
-    cache::{EditVariableCache, DomTreeCache},
+    cache::{EditVariableCache, DomTreeCache, ResizeInteraction},
     id_tree::NodeId,
     compositor::Compositor,
     app::FrameEventInfo,
+    widgets::DrawerId,
+    css_parser::{LayoutResize, StyleScrollBehavior, StyleScrollSnapType, StyleScrollSnapAlign, ScrollSnapAxis, ScrollSnapStrictness, ParsedCssProperty, StyleUserSelect},
+    dom::UpdateScreen,
+    ui_description::UiDescription,
+    shader_cache::create_disk_program_cache,
 };
 
 /// azul-internal ID for a window
 #[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
 pub struct WindowId {
     pub(crate) id: usize,
 }
 
 impl WindowId {
     pub fn new(id: usize) -> Self { Self { id: id } }
+
+    /// Allocates the ID for a new window
+    pub(crate) fn next() -> Self { Self::new(NEXT_WINDOW_ID.fetch_add(1, Ordering::SeqCst)) }
+}
+
+/// Counter for the `WindowId`s, so that the ID of a window stays the same
+/// when other windows are closed
+static NEXT_WINDOW_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Maps the ID of each open window to its display, so that code that only
+/// knows the `WindowId` (i.e. a callback in a different window) can access the window
+/// without keeping it alive
+#[derive(Default)]
+pub struct WindowRegistry {
+    windows: BTreeMap<WindowId, Weak<Display>>,
+}
+
+impl WindowRegistry {
+
+    pub(crate) fn register(&mut self, id: WindowId, display: &Rc<Display>) {
+        self.windows.insert(id, Rc::downgrade(display));
+    }
+
+    pub(crate) fn remove(&mut self, id: WindowId) {
+        self.windows.remove(&id);
+    }
+
+    /// Removes the windows whose display has already been dropped
+    pub(crate) fn remove_closed_windows(&mut self) {
+        self.windows.retain(|_, display| display.upgrade().is_some());
+    }
+
+    /// Returns the window with the given ID, if it is still open
+    pub fn get(&self, id: WindowId) -> Option<ReadOnlyWindow> {
+        self.windows.get(&id)
+            .and_then(|display| display.upgrade())
+            .map(|display| ReadOnlyWindow { inner: display })
+    }
+
+    /// Returns whether a window with the given ID is still open
+    pub fn contains(&self, id: WindowId) -> bool {
+        self.windows.get(&id).map(|display| display.upgrade().is_some()).unwrap_or(false)
+    }
+
+    /// Returns the IDs of all open windows
+    pub fn window_ids(&self) -> Vec<WindowId> {
+        self.windows.keys().cloned().collect()
+    }
 }
 
 /// User-modifiable fake window
@@ -57,15 +120,268 @@ pub struct FakeWindow {
     /// the user can create textures and other OpenGL content in the window
     /// but not change any window properties from underneath - this would
     /// lead to mismatch between the
-    pub(crate) read_only_window: Rc<Display>,
+    ///
+    /// `None` for the windows created by `App::simulate_callback()`, which have no OpenGL context
+    pub(crate) read_only_window: Option<Rc<Display>>,
+    /// Drawers (sliding sidebars) that are currently open in this window
+    pub(crate) open_drawers: FastHashSet<DrawerId>,
+    /// Copied from `WindowCreateOptions::enable_wr_capture`
+    pub(crate) wr_capture_enabled: bool,
+    /// Directory that webrender should save a capture of the current frame to (on the next frame)
+    pub(crate) pending_wr_capture: Option<PathBuf>,
+    /// ID of the real window
+    pub(crate) window_id: WindowId,
+    /// Scroll requests from `scroll_by()`, started at the end of the current frame
+    pub(crate) pending_scrolls: Vec<(NodeId, f32, f32)>,
+    /// Nodes from `scroll_to_node()`, scrolled into view at the end of the current frame
+    pub(crate) pending_scrolls_into_view: Vec<NodeId>,
+    /// Scroll animations from `animate_scroll()` (node, target offset, duration), started at the end of the current frame
+    pub(crate) pending_scroll_animations: Vec<(NodeId, (f32, f32), Duration)>,
+    /// CSS from `switch_css()`, replaces the CSS of the window on the next frame
+    pub(crate) pending_css: Option<Css>,
+    /// Windows from `open_new_window()`, created at the end of the current frame
+    pub(crate) pending_new_windows: Vec<WindowCreateRequest>,
+    /// Node that has the keyboard focus
+    pub(crate) focused_node: Option<NodeId>,
+    /// Bounds of the characters of the text nodes, from the last layout of the window
+    pub(crate) char_rects: Rc<FastHashMap<NodeId, Vec<LayoutRect>>>,
+    /// Text nodes that the character bounds were queried for in the current frame
+    /// (see `get_char_bounds()`), their bounds are computed on the next layouts
+    pub(crate) char_rect_queries: RefCell<FastHashSet<NodeId>>,
+    /// `user-select` of the text nodes, from the last layout of the window
+    pub(crate) user_select: Rc<FastHashMap<NodeId, StyleUserSelect>>,
+    /// Bounds of the open drawers, from the last layout of the window
+    pub(crate) drawer_bounds: Vec<LayoutRect>,
+    /// Nodes with a CSS ID (`#id`), from the last layout of the window, see `get_node_by_id()`
+    pub(crate) css_ids: Rc<FastHashMap<String, NodeId>>,
 }
 
 impl FakeWindow {
+
+    /// Returns the ID of the window, i.e. for `AppState::send_event_to_window()`
+    pub fn get_window_id(&self) -> WindowId {
+        self.window_id
+    }
+
+    /// Returns the node with the CSS ID `id` (see `Dom::with_id()`) in the last layout
+    /// of the window, i.e. for `scroll_to_node()`. The `NodeId` is the position of the
+    /// node in the DOM, so it is only valid until the structure of the DOM changes.
+    pub fn get_node_by_id(&self, id: &str) -> Option<NodeId> {
+        self.css_ids.get(id).cloned()
+    }
+
+    /// Scrolls the scroll container `node_id` by `scroll_x` / `scroll_y` pixels
+    /// (relative to its current scroll offset).
+    ///
+    /// If the node has `scroll-behavior: smooth` set, the scroll offset is animated
+    /// over the next few frames instead of jumping to the new position immediately.
+    pub fn scroll_by(&mut self, node_id: NodeId, scroll_x: f32, scroll_y: f32) {
+        self.pending_scrolls.push((node_id, scroll_x, scroll_y));
+    }
+
+    /// Scrolls the node into view: its nearest scroll container (the closest parent with
+    /// an `overflow` other than `visible`) is scrolled so that the top left corner of
+    /// the node lines up with the top left corner of the container.
+    ///
+    /// The `scroll-margin` of the node is kept free around it and the `scroll-padding` of
+    /// the container moves the alignment edge inwards, i.e. `scroll-padding-top` should be
+    /// the height of a sticky header, so that the node isn't hidden behind the header.
+    /// Like `scroll_by()`, the scroll is animated for `scroll-behavior: smooth` containers.
+    pub fn scroll_to_node(&mut self, node_id: NodeId) {
+        self.pending_scrolls_into_view.push(node_id);
+    }
+
+    /// Scrolls the scroll container `node_id` from its current scroll offset to the `target`
+    /// offset (x, y), animated over `duration` with an ease-in-out curve, regardless of the
+    /// `scroll-behavior` of the node.
+    ///
+    /// Unlike `scroll_by()`, the `target` is absolute, `(0.0, 0.0)` scrolls back to the start.
+    /// A new scroll on the same node replaces the running animation, starting from the
+    /// current (animated) offset. The window is redrawn on every frame while an animation is running.
+    pub fn animate_scroll(&mut self, node_id: NodeId, target: (f32, f32), duration: Duration) {
+        self.pending_scroll_animations.push((node_id, target, duration));
+    }
+
+    /// Returns the bounds of the character at `char_index` in the text of the `node_id`
+    /// (in window coordinates, as laid out in the last frame), i.e. for positioning a
+    /// text cursor. Returns `None` if the node has no text or the index is out of bounds.
+    ///
+    /// `char_index` counts the (NFC-normalized) `char`s of the text, including whitespace.
+    ///
+    /// The character bounds are only laid out for the focused node, nodes with `user-select: text`
+    /// or `all` and the nodes that were queried before: the first query for any other node returns
+    /// `None` and the window is re-layouted, the bounds are available from the next frame on.
+    ///
+    /// **NOTE**: The scroll offsets of the parent scroll containers are not applied yet.
+    pub fn get_char_bounds(&self, node_id: NodeId, char_index: usize) -> Option<LayoutRect> {
+        self.get_char_rects(node_id)?.get(char_index).cloned()
+    }
+
+    /// Returns the index of the character in the text of the `node_id` that is closest
+    /// to the window coordinates (`x`, `y`), i.e. for placing the text cursor on a click
+    /// (see `get_char_bounds()`). Returns `None` if the node has no text.
+    pub fn get_char_at_position(&self, node_id: NodeId, x: f32, y: f32) -> Option<usize> {
+        ::text_layout::get_char_at_position(self.get_char_rects(node_id)?, x, y)
+    }
+
+    /// Returns the character bounds of the node, or remembers the node for the next layout
+    fn get_char_rects(&self, node_id: NodeId) -> Option<&Vec<LayoutRect>> {
+        let char_rects = self.char_rects.get(&node_id);
+        if char_rects.is_none() {
+            self.char_rect_queries.borrow_mut().insert(node_id);
+        }
+        char_rects
+    }
+
+    /// Returns the (inherited) `user-select` of the text node `node_id`, as laid out in the last frame
+    pub fn get_user_select(&self, node_id: NodeId) -> StyleUserSelect {
+        self.user_select.get(&node_id).cloned().unwrap_or_default()
+    }
+
+    /// Returns the range of characters that a click at the window coordinates (`x`, `y`)
+    /// selects in the text of the `node_id`, depending on its `user-select`: the whole text
+    /// for `user-select: all`, the empty range at the clicked character (the start of a
+    /// selection drag) for `auto` / `text` and `None` for `user-select: none`.
+    pub fn get_click_selection(&self, node_id: NodeId, x: f32, y: f32) -> Option<Range<usize>> {
+        let char_rects = self.get_char_rects(node_id)?;
+        match self.get_user_select(node_id) {
+            StyleUserSelect::None => None,
+            StyleUserSelect::All => Some(0..char_rects.len()),
+            StyleUserSelect::Auto | StyleUserSelect::Text => {
+                let char_index = ::text_layout::get_char_at_position(char_rects, x, y)?;
+                Some(char_index..char_index)
+            },
+        }
+    }
+
+    /// Opens the drawer with the given ID (on the next redraw)
+    pub fn open_drawer(&mut self, id: DrawerId) {
+        self.open_drawers.insert(id);
+    }
+
+    /// Closes the drawer with the given ID (on the next redraw)
+    pub fn close_drawer(&mut self, id: DrawerId) {
+        self.open_drawers.remove(&id);
+    }
+
+    /// Saves the current state of webrender (display lists, resources, etc.) into
+    /// the `output_dir` at the end of the current frame. The capture can then
+    /// be loaded into `wrench` in order to debug rendering issues.
+    ///
+    /// Requires azul to be compiled with the `wr-capture` feature and the
+    /// window to be created with `WindowCreateOptions::enable_wr_capture`.
+    pub fn trigger_wr_capture(&mut self, output_dir: &Path) -> Result<(), CaptureError> {
+        if !cfg!(feature = "wr-capture") {
+            return Err(CaptureError::FeatureNotEnabled);
+        }
+        if !self.wr_capture_enabled {
+            return Err(CaptureError::NotEnabledForWindow);
+        }
+        ::std::fs::create_dir_all(output_dir)?;
+        self.pending_wr_capture = Some(output_dir.to_path_buf());
+        Ok(())
+    }
+
+    /// Replaces the entire CSS of the window (on the next frame), i.e. for switching
+    /// between a light and a dark theme. All nodes are re-styled and re-layouted.
+    ///
+    /// The dynamic CSS properties (see `FakeCss`) are not affected.
+    pub fn switch_css(&mut self, new_css: Css) {
+        self.pending_css = Some(new_css);
+    }
+
+    /// Opens a new window from within a callback (i.e. for "File -> New Window").
+    /// The window is created at the end of the current frame (so the window list isn't
+    /// modified while the callbacks of the current windows are running) and drawn for
+    /// the first time on the next frame.
+    ///
+    /// The returned ID is valid once the window is created. Since the OS window doesn't
+    /// exist yet, this currently never returns an error - if creating the window fails,
+    /// the error is logged and the ID never becomes valid (see `WindowRegistry::window_ids()`).
+    pub fn open_new_window(&mut self, options: WindowCreateOptions, css: Css) -> Result<WindowId, WindowCreateError> {
+        let id = WindowId::next();
+        self.pending_new_windows.push(WindowCreateRequest { id, options, css });
+        Ok(id)
+    }
+
+    /// Returns the node that has the keyboard focus. When the window is opened, the focus
+    /// is set to the `WindowCreateOptions::initial_focus` or to the first focusable node
+    /// (see `Dom::set_tab_index()`). The focused node receives the `On::VirtualKeyDown`,
+    /// `On::VirtualKeyUp` and `On::TextInput` events and matches the `:focus` selector.
+    /// Tab / Shift + Tab and clicking on a focusable node move the focus.
+    pub fn get_focused_node(&self) -> Option<NodeId> {
+        self.focused_node
+    }
+
+    /// Moves the keyboard focus to the node
+    pub fn set_focus(&mut self, node_id: NodeId) {
+        self.focused_node = Some(node_id);
+    }
+
+    /// Removes the keyboard focus from the focused node
+    pub fn clear_focus(&mut self) {
+        self.focused_node = None;
+    }
+
+    /// Changes the background color of the window, without re-running the layout
+    pub fn set_background_color(&mut self, color: ColorF) {
+        self.state.background_color = color;
+    }
+
+    /// Switches the text between sub-pixel and grayscale anti-aliasing, see `set_text_aa_mode()`
+    pub fn set_subpixel_aa(&mut self, enable: bool) {
+        self.set_text_aa_mode(if enable { AaMode::SubPixel } else { AaMode::Grayscale });
+    }
+
+    /// Changes the anti-aliasing of the text (on the next frame), i.e. to switch to grayscale
+    /// anti-aliasing when the background becomes dark. `AaMode::SubPixel` falls back to
+    /// grayscale if the window was created with `WindowCreateOptions::subpixel_aa: false`.
+    pub fn set_text_aa_mode(&mut self, mode: AaMode) {
+        self.state.text_aa_mode = mode;
+    }
+
+    /// Closes all open drawers of this window
+    pub fn close_all_drawers(&mut self) {
+        self.open_drawers.clear();
+    }
+
+    pub fn is_drawer_open(&self, id: DrawerId) -> bool {
+        self.open_drawers.contains(&id)
+    }
+
     /// Returns a read-only window which can be used to create / draw
     /// custom OpenGL texture during the `.layout()` phase
+    ///
+    /// ## Panics
+    ///
+    /// Panics inside of `App::simulate_callback()`, since simulated windows have no OpenGL context
     pub fn get_window(&self) -> ReadOnlyWindow {
         ReadOnlyWindow {
-            inner: self.read_only_window.clone()
+            inner: self.read_only_window.clone().expect("FakeWindow::get_window() called on a simulated window"),
+        }
+    }
+
+    /// Creates a window without any OS or GPU resources, for `App::simulate_callback()`
+    pub(crate) fn new_simulated() -> Self {
+        Self {
+            css: FakeCss::default(),
+            state: WindowState::default(),
+            read_only_window: None,
+            open_drawers: FastHashSet::default(),
+            wr_capture_enabled: false,
+            pending_wr_capture: None,
+            window_id: WindowId::next(),
+            pending_scrolls: Vec::new(),
+            pending_scrolls_into_view: Vec::new(),
+            pending_scroll_animations: Vec::new(),
+            pending_css: None,
+            pending_new_windows: Vec::new(),
+            focused_node: None,
+            char_rects: Rc::new(FastHashMap::default()),
+            char_rect_queries: RefCell::new(FastHashSet::default()),
+            user_select: Rc::new(FastHashMap::default()),
+            drawer_bounds: Vec::new(),
+            css_ids: Rc::new(FastHashMap::default()),
         }
     }
 
@@ -77,6 +393,16 @@ impl FakeWindow {
         self.state.mouse_state = *mouse;
     }
 
+    pub(crate) fn set_touches(&mut self, touches: &MultiTouchState) {
+        self.state.touch_state.touches = touches.clone();
+    }
+
+    /// Returns a copy of the fingers that are currently touching the screen
+    /// (requires `WindowCreateOptions::enable_touch`)
+    pub fn get_touches(&self) -> MultiTouchState {
+        self.state.touch_state.touches.clone()
+    }
+
     /// Returns a copy of the current keyboard keyboard state. We don't want the library
     /// user to be able to modify this state, only to read it.
     pub fn get_keyboard_state(&self) -> KeyboardState {
@@ -91,6 +417,52 @@ impl FakeWindow {
 
 }
 
+/// The changes that a callback made to a simulated `FakeWindow`, recorded
+/// by `App::simulate_callback()` so that they can be asserted on in unit tests
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedWindowOps {
+    /// Whether the callback returned `UpdateScreen::Redraw`
+    pub redraw_requested: bool,
+    /// The new title of the window, if the callback changed it
+    pub new_title: Option<String>,
+    /// The new background color of the window, if the callback changed it
+    pub new_background_color: Option<ColorF>,
+    /// Drawers that were left open by the callback, see `FakeWindow::open_drawer()`
+    pub open_drawers: Vec<DrawerId>,
+    /// Calls to `FakeWindow::scroll_by()`
+    pub scrolls: Vec<(NodeId, f32, f32)>,
+    /// Calls to `FakeWindow::scroll_to_node()`
+    pub scrolls_into_view: Vec<NodeId>,
+    /// The dynamic CSS properties that the callback has overridden via the `FakeCss`
+    pub overridden_css_properties: Vec<String>,
+}
+
+impl SimulatedWindowOps {
+    /// Compares the simulated window after the callback ran to a freshly simulated window
+    pub(crate) fn new(window: &FakeWindow, update_screen: UpdateScreen) -> Self {
+        let default_state = WindowState::default();
+
+        let mut open_drawers = window.open_drawers.iter().cloned().collect::<Vec<_>>();
+        open_drawers.sort();
+        let mut overridden_css_properties = window.css.dynamic_css_overrides.keys().cloned().collect::<Vec<_>>();
+        overridden_css_properties.sort();
+
+        Self {
+            redraw_requested: update_screen == UpdateScreen::Redraw,
+            new_title: if window.state.title != default_state.title { Some(window.state.title.clone()) } else { None },
+            new_background_color: if window.state.background_color != default_state.background_color {
+                Some(window.state.background_color)
+            } else {
+                None
+            },
+            open_drawers: open_drawers,
+            scrolls: window.pending_scrolls.clone(),
+            scrolls_into_view: window.pending_scrolls_into_view.clone(),
+            overridden_css_properties: overridden_css_properties,
+        }
+    }
+}
+
 /// Read-only window which can be used to create / draw
 /// custom OpenGL texture during the `.layout()` phase
 pub struct ReadOnlyWindow {
@@ -126,12 +498,57 @@ impl ReadOnlyWindow {
         }
     }
 
+    /// Reads the pixels of the `texture` back from the GPU, i.e. for saving a screenshot
+    /// or comparing the texture in a test. The bytes are RGBA8, row by row from the top
+    /// row to the bottom row (OpenGL stores the bottom row first).
+    ///
+    /// The texture must have been created in this window (with `create_texture()`).
+    pub fn download_texture(&self, texture: &Texture) -> Result<Vec<u8>, DownloadError> {
+        use glium::GlObject;
+
+        let width = texture.inner.width();
+        let height = texture.inner.height();
+        if width == 0 || height == 0 {
+            return Err(DownloadError::EmptyTexture);
+        }
+
+        self.make_current();
+        let gl = self.get_gl();
+
+        // attach the texture to a temporary framebuffer, so that it can be read with glReadPixels
+        let framebuffers = gl.gen_framebuffers(1);
+        gl.bind_framebuffer(gl::FRAMEBUFFER, framebuffers[0]);
+        gl.framebuffer_texture_2d(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, texture.inner.get_id(), 0);
+
+        let status = gl.check_frame_buffer_status(gl::FRAMEBUFFER);
+        let pixels = if status != gl::FRAMEBUFFER_COMPLETE {
+            Err(DownloadError::IncompleteFramebuffer(status))
+        } else {
+            gl.pixel_store_i(gl::PACK_ALIGNMENT, 1);
+            let pixels = gl.read_pixels(0, 0, width as i32, height as i32, gl::RGBA, gl::UNSIGNED_BYTE);
+            match gl.get_error() {
+                gl::NO_ERROR => Ok(flip_rows(pixels, width as usize * 4)),
+                error => Err(DownloadError::GlError(error)),
+            }
+        };
+
+        gl.bind_framebuffer(gl::FRAMEBUFFER, 0);
+        gl.delete_framebuffers(&framebuffers);
+
+        pixels
+    }
+
     /// Unbind the current framebuffer manually. Is also executed on `Drop`.
     ///
     /// TODO: Is it necessary to expose this or is it enough to just
     /// unbind the framebuffer on drop?
     pub fn unbind_framebuffer(&self) {
-        let gl = match self.inner.gl_window().get_api() {
+        self.get_gl().bind_framebuffer(gl::FRAMEBUFFER, 0);
+    }
+
+    /// Loads the OpenGL functions of the window
+    fn get_gl(&self) -> Rc<Gl> {
+        match self.inner.gl_window().get_api() {
             glutin::Api::OpenGl => unsafe {
                 gl::GlFns::load_with(|symbol|
                     self.inner.gl_window().get_proc_address(symbol) as *const _)
@@ -141,12 +558,40 @@ impl ReadOnlyWindow {
                     self.inner.gl_window().get_proc_address(symbol) as *const _)
             },
             glutin::Api::WebGl => unreachable!(),
-        };
+        }
+    }
+}
 
-        gl.bind_framebuffer(gl::FRAMEBUFFER, 0);
+/// Error that can happen in `ReadOnlyWindow::download_texture()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DownloadError {
+    /// The texture has a width or height of 0
+    EmptyTexture,
+    /// The texture can't be attached to a framebuffer (the `glCheckFramebufferStatus` result),
+    /// i.e. because the format of the texture isn't color-renderable
+    IncompleteFramebuffer(u32),
+    /// `glReadPixels` failed with the given error code
+    GlError(u32),
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::DownloadError::*;
+        match self {
+            EmptyTexture => write!(f, "The texture has a width or height of 0"),
+            IncompleteFramebuffer(status) => write!(f, "The texture can't be attached to a framebuffer (status 0x{:x})", status),
+            GlError(error) => write!(f, "glReadPixels failed with the error 0x{:x}", error),
+        }
     }
 }
 
+impl ::std::error::Error for DownloadError { }
+
+/// Reverses the order of the rows of the `pixels` (each row is `row_len` bytes long)
+pub(crate) fn flip_rows(pixels: Vec<u8>, row_len: usize) -> Vec<u8> {
+    pixels.chunks(row_len).rev().flat_map(|row| row.iter().cloned()).collect()
+}
+
 impl Drop for ReadOnlyWindow {
     fn drop(&mut self) {
         self.unbind_framebuffer();
@@ -156,6 +601,42 @@ impl Drop for ReadOnlyWindow {
 pub struct WindowInfo {
     pub window_id: WindowId,
     pub window: ReadOnlyWindow,
+    /// Drawers that were opened via `FakeWindow::open_drawer`
+    pub(crate) open_drawers: FastHashSet<DrawerId>,
+    /// Messages from the channels registered via `App::register_background_channel()`
+    pub(crate) background_messages: Vec<Rc<Any + Send>>,
+    /// The monitor that (the larger part of) the window is currently on
+    pub monitor_id: Option<MonitorId>,
+    /// DPI factor of the monitor the window is on
+    pub monitor_dpi: f32,
+    /// Whether the DOM is laid out for printing, see `App::render_for_print()`
+    pub(crate) is_printing: bool,
+}
+
+impl WindowInfo {
+    /// Always true while printing, so that the content of all drawers is printed
+    pub fn is_drawer_open(&self, id: DrawerId) -> bool {
+        self.is_printing || self.open_drawers.contains(&id)
+    }
+
+    /// Whether the DOM is laid out for printing (see `App::render_for_print()`):
+    /// collapsed sections of the UI should be expanded, so that their content is printed
+    pub fn is_printing(&self) -> bool {
+        self.is_printing
+    }
+
+    /// Returns all messages that were received from the background channels since the
+    /// last time the layout function of this window was called. Every window gets
+    /// the same messages.
+    pub fn background_messages(&self) -> &[Rc<Any + Send>] {
+        &self.background_messages
+    }
+
+    /// Same as `background_messages()`, but only returns the messages of type `M`,
+    /// i.e. the messages of the channel registered with a `Receiver<M>`
+    pub fn get_background_messages<M: Any>(&self) -> Vec<&M> {
+        self.background_messages.iter().filter_map(|message| message.downcast_ref::<M>()).collect()
+    }
 }
 
 impl fmt::Debug for FakeWindow {
@@ -172,9 +653,13 @@ impl fmt::Debug for FakeWindow {
 /// Window event that is passed to the user when a callback is invoked
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct WindowEvent {
-    /// The ID of the window that the event was clicked on (for indexing into
+    /// The index of the window that the event was clicked on (for indexing into
     /// `app_state.windows`). `app_state.windows[event.window]` should never panic.
+    /// The index changes when other windows are closed, use the `window_id` to
+    /// refer to the window later on, i.e. in `AppState::send_event_to_window()`.
     pub window: usize,
+    /// The ID of the window that the event was clicked on, stays the same until the window is closed
+    pub window_id: WindowId,
     /// The nth child of the parent DOM node will generate a value of `Some(n)`
     /// when it is hit - i.e. if an element is hit, this number is set to
     ///
@@ -185,6 +670,38 @@ pub struct WindowEvent {
     pub cursor_relative_to_item: (f32, f32),
     /// The (x, y) position of the mouse cursor, **relative to top left of the window**.
     pub cursor_in_viewport: (f32, f32),
+    /// The drag-and-drop that is currently in progress (or that has ended, for `On::Drop`)
+    pub drag_and_drop: Option<DragAndDrop>,
+    /// The pinch gesture that has invoked an `On::Pinch` callback, `None` for all other events
+    pub pinch_gesture: Option<PinchGesture>,
+    /// The rotation gesture that has invoked an `On::Rotate` callback, `None` for all other events
+    pub rotate_gesture: Option<RotateGesture>,
+    /// The finger that has invoked an `On::TouchStart` / `TouchMove` / `TouchEnd` callback
+    /// (or a mouse callback, if the finger is simulated as the mouse), `None` for all other events
+    pub touch: Option<(TouchId, TouchPoint)>,
+    /// The node whose callback is invoked (the hit node, or the drop target for `On::Drop`),
+    /// i.e. for `FakeWindow::scroll_to_node()`. `None` if the event has no node.
+    pub hit_node: Option<NodeId>,
+}
+
+/// Window that should be opened at the end of the frame, see `FakeWindow::open_new_window()`
+#[derive(Debug, Clone)]
+pub(crate) struct WindowCreateRequest {
+    pub(crate) id: WindowId,
+    pub(crate) options: WindowCreateOptions,
+    pub(crate) css: Css,
+}
+
+/// Source and target of a drag-and-drop - the nodes can be in different windows
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DragAndDrop {
+    /// Window of the node where the drag started
+    pub source_window: WindowId,
+    /// Node with the `On::DragStart` callback where the drag started
+    pub source_node: NodeId,
+    /// Window and node with the `On::Drop` callback that the source was dropped on,
+    /// `None` while the drag is still in progress
+    pub target: Option<(WindowId, NodeId)>,
 }
 
 impl WindowEvent {
@@ -192,14 +709,23 @@ impl WindowEvent {
     pub fn mock() -> Self {
         Self {
             window: 0,
+            window_id: WindowId::new(0),
             number_of_previous_siblings: None,
             cursor_relative_to_item: (0.0, 0.0),
             cursor_in_viewport: (0.0, 0.0),
+            drag_and_drop: None,
+            pinch_gesture: None,
+            rotate_gesture: None,
+            touch: None,
+            hit_node: None,
         }
     }
 }
 
 /// Options on how to initially create the window
+///
+/// All fields (including the `MonitorId` of a `WindowMonitorTarget::Custom`) can be
+/// cloned, so one `WindowCreateOptions` can be used as a template for multiple windows.
 #[derive(Debug, Clone)]
 pub struct WindowCreateOptions {
     /// State of the window, set the initial title / width / height here.
@@ -222,6 +748,59 @@ pub struct WindowCreateOptions {
     pub update_behaviour: UpdateBehaviour,
     /// Renderer type: Hardware-with-software-fallback, pure software or pure hardware renderer?
     pub renderer_type: RendererType,
+    /// Allow webrender captures via `FakeWindow::trigger_wr_capture` (default: false)
+    pub enable_wr_capture: bool,
+    /// Level for azul-internal log messages, if the application hasn't installed
+    /// its own logger (via the `log` crate). Default: `LevelFilter::Warn`
+    pub log_level: LevelFilter,
+    /// File to store the compiled shaders in, so that they don't have to be compiled again on
+    /// the next startup (saves 200 - 1000ms). The file is written when the window is closed and
+    /// discarded if the GPU driver or azul version changes. Default: `None` (no shader cache)
+    pub shader_cache_path: Option<PathBuf>,
+    /// File to store the `DomTreeCache` (the hashed DOM of the last frame) in when the window
+    /// is closed. On the next startup, the cache is restored and compared with the first DOM -
+    /// only the nodes that have changed since then are rebuilt. Default: `None` (no DOM cache)
+    pub dom_cache_path: Option<PathBuf>,
+    /// Called before WebRender renders a frame, i.e. to set up additional OpenGL state
+    /// or to render into auxiliary framebuffers. Default: `None`
+    pub pre_render: Option<RenderCallback>,
+    /// Called after WebRender has rendered a frame (before the buffers are swapped), i.e. to
+    /// read back the framebuffer or to draw a debug UI directly with OpenGL. Default: `None`
+    pub post_render: Option<RenderCallback>,
+    /// Whether WebRender should support sub-pixel anti-aliasing of text. If `false`, the text
+    /// is always drawn with grayscale anti-aliasing. Default: `true`
+    pub subpixel_aa: bool,
+    /// Whether the touch events should be dispatched (`On::TouchStart`, etc.) and recognized
+    /// as gestures (`On::Pinch`, `On::Rotate`).
+    ///
+    /// **NOTE**: Only touch screens / touchpads that report the individual fingers are
+    /// supported - the native touchpad gestures of macOS aren't reported by winit yet.
+    /// Default: `false`
+    pub enable_touch: bool,
+    /// CSS ID (see `Dom::with_id()`) of the node that should have the keyboard focus when
+    /// the window is opened (i.e. the first input field of a dialog), see
+    /// `FakeWindow::get_focused_node()`. If `None` or if no node has this ID, the first
+    /// focusable node is focused (see `Dom::set_tab_index()`).
+    /// Default: `None`
+    pub initial_focus: Option<String>,
+}
+
+/// Callback that is invoked when a frame is rendered, see `WindowCreateOptions::pre_render`.
+///
+/// Stored in an `Rc`, so that the `WindowCreateOptions` can still be cloned.
+#[derive(Clone)]
+pub struct RenderCallback(pub Rc<Fn(&ReadOnlyWindow)>);
+
+impl RenderCallback {
+    pub fn new<F: Fn(&ReadOnlyWindow) + 'static>(callback: F) -> Self {
+        RenderCallback(Rc::new(callback))
+    }
+}
+
+impl fmt::Debug for RenderCallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RenderCallback(_)")
+    }
 }
 
 impl Default for WindowCreateOptions {
@@ -236,6 +815,15 @@ impl Default for WindowCreateOptions {
             mouse_mode: MouseMode::default(),
             update_behaviour: UpdateBehaviour::default(),
             renderer_type: RendererType::default(),
+            enable_wr_capture: false,
+            log_level: LevelFilter::Warn,
+            shader_cache_path: None,
+            dom_cache_path: None,
+            pre_render: None,
+            post_render: None,
+            subpixel_aa: true,
+            enable_touch: false,
+            initial_focus: None,
         }
     }
 }
@@ -319,6 +907,23 @@ impl Default for MouseMode {
     }
 }
 
+/// Error that can happen when triggering a webrender capture
+#[derive(Debug)]
+pub enum CaptureError {
+    /// azul wasn't compiled with the `wr-capture` feature
+    FeatureNotEnabled,
+    /// The window wasn't created with `WindowCreateOptions::enable_wr_capture`
+    NotEnabledForWindow,
+    /// Couldn't create the output directory
+    Io(IoError),
+}
+
+impl From<IoError> for CaptureError {
+    fn from(e: IoError) -> Self {
+        CaptureError::Io(e)
+    }
+}
+
 /// Error that could happen during window creation
 #[derive(Debug)]
 pub enum WindowCreateError {
@@ -376,6 +981,57 @@ impl From<ContextError> for WindowCreateError {
     }
 }
 
+impl fmt::Display for WindowCreateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::WindowCreateError::*;
+        match self {
+            WebGlNotSupported => write!(f, "WebGl is not supported by webrender"),
+            DisplayCreateError(e) => write!(f, "Could not create the display: {}", e),
+            Gl(e) => write!(f, "Incompatible OpenGL version: {}", e),
+            Context(e) => write!(f, "Could not create the OpenGL context: {}", e),
+            CreateError(e) => write!(f, "Could not create the window: {}", e),
+            SwapBuffers(e) => write!(f, "Could not swap the front and back buffers: {}", e),
+            Io(e) => write!(f, "IO error: {}", e),
+            Renderer => write!(f, "Could not create the webrender renderer"),
+        }
+    }
+}
+
+impl ::std::error::Error for WindowCreateError {
+    fn source(&self) -> Option<&(::std::error::Error + 'static)> {
+        use self::WindowCreateError::*;
+        match self {
+            DisplayCreateError(e) => Some(e),
+            Gl(e) => Some(e),
+            Context(e) => Some(e),
+            CreateError(e) => Some(e),
+            SwapBuffers(e) => Some(e),
+            Io(e) => Some(e),
+            WebGlNotSupported | Renderer => None,
+        }
+    }
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CaptureError::*;
+        match self {
+            FeatureNotEnabled => write!(f, "azul wasn't compiled with the `wr-capture` feature"),
+            NotEnabledForWindow => write!(f, "The window wasn't created with `enable_wr_capture`"),
+            Io(e) => write!(f, "Could not create the capture directory: {}", e),
+        }
+    }
+}
+
+impl ::std::error::Error for CaptureError {
+    fn source(&self) -> Option<&(::std::error::Error + 'static)> {
+        match self {
+            CaptureError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 struct Notifier {
     events_loop_proxy: EventsLoopProxy,
 }
@@ -408,7 +1064,7 @@ impl RenderNotifier for Notifier {
 
     fn wake_up(&self) {
         #[cfg(not(target_os = "android"))]
-        self.events_loop_proxy.wakeup().unwrap_or_else(|_| { eprintln!("couldn't wakeup event loop"); });
+        self.events_loop_proxy.wakeup().unwrap_or_else(|_| { error!("couldn't wakeup event loop"); });
     }
 
     fn new_frame_ready(&self, _id: DocumentId, _scrolled: bool, _composite_needed: bool, _render_time: Option<u64>) {
@@ -455,6 +1111,8 @@ impl Default for WindowMonitorTarget {
 
 /// Represents one graphical window to be rendered
 pub struct Window<T: Layout> {
+    /// Unique ID of the window, stays the same when other windows are closed
+    pub(crate) id: WindowId,
     // TODO: technically, having one EventsLoop for all windows is sufficient
     pub(crate) events_loop: EventsLoop,
     /// Current state of the window, stores the keyboard / mouse state,
@@ -478,6 +1136,224 @@ pub struct Window<T: Layout> {
     // pub(crate) background_thread: Option<JoinHandle<()>>,
     /// The css (how the current window is styled)
     pub css: Css,
+    /// The monitor that the window is currently on, updated when the window is moved
+    pub(crate) current_monitor: Option<MonitorId>,
+    /// The DPI factor of the `current_monitor`
+    pub(crate) current_monitor_dpi: f32,
+    /// Scroll offsets and running scroll animations of the scroll containers
+    pub(crate) scroll_states: ScrollStates,
+    /// Whether the user scrolled (with the mouse wheel) during the last frame,
+    /// for detecting the end of a scroll gesture (for `scroll-snap-type`)
+    pub(crate) is_scrolling: bool,
+    /// The `resize` container whose drag handle is currently dragged by the user
+    pub(crate) resize_drag: Option<ResizeDrag>,
+    /// Where to store the `DomTreeCache` when the window is closed, see `WindowCreateOptions::dom_cache_path`
+    pub(crate) dom_cache_path: Option<PathBuf>,
+    /// See `WindowCreateOptions::pre_render`
+    pub(crate) pre_render: Option<RenderCallback>,
+    /// See `WindowCreateOptions::post_render`
+    pub(crate) post_render: Option<RenderCallback>,
+    /// See `WindowCreateOptions::subpixel_aa`
+    pub(crate) subpixel_aa: bool,
+    /// See `WindowCreateOptions::initial_focus`
+    pub(crate) initial_focus: Option<String>,
+    /// Layout functions of the embedded pipelines, see `App::add_embedded_pipeline()`
+    pub(crate) embedded_layouts: FastHashMap<PipelineId, EmbeddedLayout<T>>,
+}
+
+/// Layout function of an embedded pipeline (see `App::add_embedded_pipeline()`), called
+/// whenever the window that contains the pipeline is laid out
+pub type EmbeddedLayout<T> = fn(&T, WindowInfo) -> Dom<T>;
+
+/// A drag of the handle in the bottom right corner of a `resize` container,
+/// started by pressing the left mouse button on the handle
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct ResizeDrag {
+    /// The container that is being resized
+    pub(crate) node_id: NodeId,
+    pub(crate) resize: LayoutResize,
+    /// Size of the container when the drag started
+    pub(crate) initial_size: (f32, f32),
+    /// Cursor position when the drag started
+    pub(crate) initial_cursor_pos: (f32, f32),
+}
+
+/// How long a `scroll-behavior: smooth` scroll animation takes
+const SMOOTH_SCROLL_DURATION: Duration = Duration::from_millis(300);
+
+/// A running scroll animation, started by `FakeWindow::scroll_by` / `scroll_to_node`
+/// (for `scroll-behavior: smooth`) or `FakeWindow::animate_scroll`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct ScrollAnimation {
+    /// The scroll container that is being scrolled
+    pub(crate) node_id: NodeId,
+    pub(crate) start_time: Instant,
+    pub(crate) duration: Duration,
+    /// Scroll offset (x, y) of the container when the animation started
+    pub(crate) start_offset: (f32, f32),
+    /// Scroll offset (x, y) of the container at the end of the animation
+    pub(crate) target_offset: (f32, f32),
+}
+
+/// Scroll offsets of the scroll containers (`overflow` other than `visible`) of a window.
+///
+/// The offsets are applied to the scroll frames of the containers (see
+/// `display_list::get_external_scroll_id()`) every time the display list is sent.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ScrollStates {
+    /// Scroll offset (x, y) of each scrolled container, in pixels. Containers
+    /// that haven't been scrolled yet aren't in the map (i.e. are at (0, 0)).
+    pub(crate) offsets: FastHashMap<NodeId, (f32, f32)>,
+    /// Currently running smooth scroll animations, at most one per container
+    pub(crate) animations: Vec<ScrollAnimation>,
+}
+
+impl ScrollStates {
+
+    /// Current scroll offset of the container (in the middle of an animation: the animated offset)
+    pub(crate) fn get_offset(&self, node_id: NodeId) -> (f32, f32) {
+        self.offsets.get(&node_id).cloned().unwrap_or((0.0, 0.0))
+    }
+
+    /// Sets the scroll offset of the container immediately, stops a running animation of the container.
+    /// The scroll offset can't be negative.
+    pub(crate) fn set_offset(&mut self, node_id: NodeId, offset: (f32, f32)) {
+        self.animations.retain(|a| a.node_id != node_id);
+        self.offsets.insert(node_id, clamp_scroll_offset(offset));
+    }
+
+    /// Animates the scroll offset of the container from its current offset to the `target_offset`.
+    /// A running animation of the container is replaced, the new one starts at the current
+    /// (animated) offset, so the scroll position doesn't jump.
+    pub(crate) fn animate_offset(&mut self, node_id: NodeId, target_offset: (f32, f32), duration: Duration, now: Instant) {
+        let start_offset = self.get_offset(node_id);
+        self.animations.retain(|a| a.node_id != node_id);
+        self.animations.push(ScrollAnimation {
+            node_id: node_id,
+            start_time: now,
+            duration: duration,
+            start_offset: start_offset,
+            target_offset: clamp_scroll_offset(target_offset),
+        });
+    }
+
+    /// Updates the offsets of the animated containers to the time `now`, removes finished
+    /// animations. Returns whether any offset was changed.
+    pub(crate) fn advance_animations(&mut self, now: Instant) -> bool {
+        if self.animations.is_empty() {
+            return false;
+        }
+
+        for animation in &self.animations {
+            let elapsed = now.duration_since(animation.start_time);
+            let progress = ease_in_out_quad(get_animation_time(elapsed, animation.duration));
+            let (start_x, start_y) = animation.start_offset;
+            let (target_x, target_y) = animation.target_offset;
+            self.offsets.insert(animation.node_id, (
+                start_x + (target_x - start_x) * progress,
+                start_y + (target_y - start_y) * progress));
+        }
+
+        self.animations.retain(|a| now.duration_since(a.start_time) < a.duration);
+        true
+    }
+}
+
+/// The content of a scroll container can't be scrolled before its start
+fn clamp_scroll_offset((x, y): (f32, f32)) -> (f32, f32) {
+    (x.max(0.0), y.max(0.0))
+}
+
+/// Returns how much of the `duration` has `elapsed`, from 0.0 to 1.0
+fn get_animation_time(elapsed: Duration, duration: Duration) -> f32 {
+    let to_secs = |d: Duration| d.as_secs() as f32 + d.subsec_nanos() as f32 / 1_000_000_000.0;
+    if duration == Duration::from_secs(0) {
+        return 1.0;
+    }
+    (to_secs(elapsed) / to_secs(duration)).min(1.0)
+}
+
+/// Quadratic ease-in-out: slow start, fast middle, slow end. `t` goes from 0.0 to 1.0
+fn ease_in_out_quad(t: f32) -> f32 {
+    if t < 0.5 {
+        2.0 * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+    }
+}
+
+/// With `scroll-snap-type: proximity`, only snap points that are closer than
+/// this fraction of the size of the scroll container are snapped to
+const SCROLL_SNAP_PROXIMITY: f32 = 0.3;
+
+/// Moves the edges of the `rect` inwards by `[top, right, bottom, left]` pixels (outwards if negative),
+/// for the `scroll-padding` of a scroll container and the `scroll-margin` of its children
+fn inset_rect(rect: &LayoutRect, insets: [f32; 4]) -> LayoutRect {
+    let [top, right, bottom, left] = insets;
+    LayoutRect::new(
+        LayoutPoint::new(rect.origin.x + left, rect.origin.y + top),
+        LayoutSize::new((rect.size.width - left - right).max(0.0), (rect.size.height - top - bottom).max(0.0)))
+}
+
+/// Returns how far the scroll container has to be scrolled (x, y) so that the nearest
+/// snap point (the aligned edge of a child) lines up with the same edge of the container.
+/// Returns `None` if there is nothing to snap to.
+///
+/// The `container` should already be shrunk by its `scroll-padding` and the children
+/// grown by their `scroll-margin` (see `inset_rect()`).
+fn get_scroll_snap_delta(
+    container: &LayoutRect,
+    children: &[(LayoutRect, StyleScrollSnapAlign)],
+    axis: ScrollSnapAxis,
+    strictness: ScrollSnapStrictness)
+-> Option<(f32, f32)>
+{
+    fn aligned_position(start: f32, size: f32, align: StyleScrollSnapAlign) -> f32 {
+        match align {
+            StyleScrollSnapAlign::Center => start + size / 2.0,
+            StyleScrollSnapAlign::End => start + size,
+            StyleScrollSnapAlign::Start | StyleScrollSnapAlign::None => start,
+        }
+    }
+
+    let nearest_delta = |get_start_and_size: &Fn(&LayoutRect) -> (f32, f32)| -> Option<f32> {
+        let (container_start, container_size) = get_start_and_size(container);
+        let nearest = children.iter()
+            .filter(|&&(_, align)| align != StyleScrollSnapAlign::None)
+            .map(|&(ref child, align)| {
+                let (child_start, child_size) = get_start_and_size(child);
+                aligned_position(child_start, child_size, align) - aligned_position(container_start, container_size, align)
+            })
+            .min_by(|a, b| a.abs().partial_cmp(&b.abs()).unwrap_or(::std::cmp::Ordering::Equal))?;
+        if strictness == ScrollSnapStrictness::Proximity && nearest.abs() > container_size * SCROLL_SNAP_PROXIMITY {
+            None
+        } else {
+            Some(nearest)
+        }
+    };
+
+    let delta_x = match axis {
+        ScrollSnapAxis::X | ScrollSnapAxis::Both => nearest_delta(&|r: &LayoutRect| (r.origin.x, r.size.width)),
+        ScrollSnapAxis::Y => None,
+    };
+    let delta_y = match axis {
+        ScrollSnapAxis::Y | ScrollSnapAxis::Both => nearest_delta(&|r: &LayoutRect| (r.origin.y, r.size.height)),
+        ScrollSnapAxis::X => None,
+    };
+
+    match (delta_x.unwrap_or(0.0), delta_y.unwrap_or(0.0)) {
+        (x, y) if x == 0.0 && y == 0.0 => None,
+        (x, y) => Some((x, y)),
+    }
+}
+
+/// Returns the scroll offset of the `container` at which the `node` is scrolled into view
+/// (see `FakeWindow::scroll_to_node()`), both in unscrolled layout coordinates.
+///
+/// The `container` should already be shrunk by its `scroll-padding` and the `node`
+/// grown by its `scroll-margin` (see `inset_rect()`).
+fn get_scroll_into_view_offset(container: &LayoutRect, node: &LayoutRect) -> (f32, f32) {
+    (node.origin.x - container.origin.x, node.origin.y - container.origin.y)
 }
 
 /// Used in the solver, for the root constraint
@@ -516,12 +1392,90 @@ pub(crate) struct UiSolver<T: Layout> {
     pub(crate) edit_variable_cache: EditVariableCache,
     /// The cache of the previous frames DOM tree
     pub(crate) dom_tree_cache: DomTreeCache,
+    /// All constraints that were added to the `solver`, see `debug_constraints()`
+    #[cfg(debug_assertions)]
+    pub(crate) added_constraints: Vec<::cassowary::Constraint>,
 }
 
 impl<T: Layout> UiSolver<T> {
-    pub(crate) fn query_bounds_of_rect(&self, rect_id: NodeId) {
-        // TODO: After solving the UI, use this function to get the actual coordinates of an item in the UI.
-        // This function should cache values accordingly
+    /// Solver without any constraints, for laying out a DOM from scratch
+    pub(crate) fn empty() -> Self {
+        Self {
+            solver: Solver::new(),
+            solved_layout: SolvedLayout::empty(),
+            edit_variable_cache: EditVariableCache::empty(),
+            dom_tree_cache: DomTreeCache::empty(),
+            #[cfg(debug_assertions)]
+            added_constraints: Vec::new(),
+        }
+    }
+
+    /// Returns the position and size of the rectangle, as of the last time the solver ran.
+    /// Returns `None` if the node doesn't exist (anymore) in the last layout.
+    pub(crate) fn query_bounds_of_rect(&self, rect_id: NodeId) -> Option<LayoutRect> {
+        // TODO: This function should cache the values after the solver ran
+        let previous_arena = &self.dom_tree_cache.previous_layout.arena;
+        if rect_id.index.get() >= previous_arena.nodes_len() {
+            return None;
+        }
+        let dom_hash = &previous_arena[rect_id].data;
+        let &(_, display_rect) = self.edit_variable_cache.map.get(dom_hash)?;
+        Some(LayoutRect::new(
+            LayoutPoint::new(self.solver.get_value(display_rect.left) as f32, self.solver.get_value(display_rect.top) as f32),
+            LayoutSize::new(self.solver.get_value(display_rect.width) as f32, self.solver.get_value(display_rect.height) as f32),
+        ))
+    }
+
+    /// Removes all constraints from the solver and forgets the last layout, so that
+    /// the constraints of all nodes are created again on the next layout
+    pub(crate) fn reset(&mut self) {
+        self.solver.reset();
+        self.solved_layout = SolvedLayout::empty();
+        self.edit_variable_cache = EditVariableCache::empty();
+        self.dom_tree_cache = DomTreeCache::empty();
+        #[cfg(debug_assertions)]
+        self.added_constraints.clear();
+    }
+
+    /// Suggests the new size of a `resize` container to the solver (via the edit
+    /// variables of the rectangle). Does nothing if the node isn't in the last layout.
+    pub(crate) fn apply_resize_interaction(&mut self, rect_id: NodeId, interaction: &ResizeInteraction) {
+        let previous_arena = &self.dom_tree_cache.previous_layout.arena;
+        if rect_id.index.get() >= previous_arena.nodes_len() {
+            return;
+        }
+        let dom_hash = &previous_arena[rect_id].data;
+        self.edit_variable_cache.apply_resize_interaction(&mut self.solver, dom_hash, interaction);
+    }
+
+    /// Describes the constraints of the solver and whether the last solved layout fulfills them.
+    /// The variables of the nodes are named after the node and the property, i.e. `Node(42).width`.
+    #[cfg(debug_assertions)]
+    pub(crate) fn debug_constraints(&self) -> Vec<::inspector::ConstraintDebugInfo> {
+        let mut variable_names = FastHashMap::default();
+
+        let previous_arena = &self.dom_tree_cache.previous_layout.arena;
+        for node_id in previous_arena.linear_iter() {
+            let display_rect = match self.edit_variable_cache.map.get(&previous_arena[node_id].data) {
+                Some(&(_, display_rect)) => display_rect,
+                None => continue,
+            };
+            let node_name = format!("Node({})", node_id.index.get());
+            for &(variable, property) in &[
+                (display_rect.left, "left"),
+                (display_rect.top, "top"),
+                (display_rect.right, "right"),
+                (display_rect.bottom, "bottom"),
+                (display_rect.width, "width"),
+                (display_rect.height, "height"),
+            ] {
+                variable_names.insert(variable, format!("{}.{}", node_name, property));
+            }
+        }
+
+        self.added_constraints.iter()
+            .map(|constraint| ::inspector::ConstraintDebugInfo::new(constraint, &variable_names, |variable| self.solver.get_value(variable)))
+            .collect()
     }
 }
 
@@ -531,18 +1485,31 @@ pub(crate) struct WindowInternal {
     pub(crate) epoch: Epoch,
     pub(crate) pipeline_id: PipelineId,
     pub(crate) document_id: DocumentId,
+    /// Additional pipelines that are embedded into the main pipeline via `Dom::iframe()`,
+    /// along with the last display list that was submitted for them
+    pub(crate) embedded_pipelines: FastHashMap<PipelineId, Option<BuiltDisplayList>>,
+    /// ID of the next embedded pipeline - IDs are not reused after a pipeline is removed,
+    /// so that an old `Dom::iframe()` can't show the content of a newer pipeline
+    pub(crate) next_embedded_pipeline_id: u32,
 }
 
 impl<T: Layout> Window<T> {
 
     /// Creates a new window
     pub fn new(options: WindowCreateOptions, css: Css) -> Result<Self, WindowCreateError>  {
+        Self::with_id(WindowId::next(), options, css)
+    }
+
+    /// Creates a new window with an ID that was allocated before, see `FakeWindow::open_new_window()`
+    pub(crate) fn with_id(id: WindowId, options: WindowCreateOptions, css: Css) -> Result<Self, WindowCreateError>  {
+
+        ::logging::set_up_logging(options.log_level);
 
         let events_loop = EventsLoop::new();
 
         let mut window = WindowBuilder::new()
             .with_dimensions(options.state.size.width, options.state.size.height)
-            .with_title(options.state.title.clone())
+            .with_title(sanitize_window_title(&options.state.title))
             .with_decorations(options.state.has_decorations)
             .with_visibility(options.state.is_visible)
             .with_transparency(options.state.is_transparent)
@@ -617,20 +1584,19 @@ impl<T: Layout> Window<T> {
         let device_pixel_ratio = display.gl_window().hidpi_factor();
 
         // this exists because RendererOptions isn't Clone-able
-        fn get_renderer_opts(native: bool, device_pixel_ratio: f32, clear_color: Option<ColorF>) -> RendererOptions {
-            use webrender::ProgramCache;
+        fn get_renderer_opts(native: bool, device_pixel_ratio: f32, clear_color: Option<ColorF>, cached_programs: Rc<ProgramCache>, subpixel_aa: bool) -> RendererOptions {
             RendererOptions {
                 resource_override_path: None,
                 // pre-caching shaders means to compile all shaders on startup
                 // this can take significant time and should be only used for testing the shaders
                 precache_shaders: false,
                 device_pixel_ratio: device_pixel_ratio,
-                enable_subpixel_aa: true,
+                enable_subpixel_aa: subpixel_aa,
                 enable_aa: true,
                 clear_color: clear_color,
                 enable_render_on_scroll: true,
                 enable_scrollbars: true,
-                cached_programs: Some(ProgramCache::new(None)),
+                cached_programs: Some(cached_programs),
                 renderer_kind: if native {
                     RendererKind::Native
                 } else {
@@ -650,8 +1616,14 @@ impl<T: Layout> Window<T> {
 
         let gl = get_gl_context(&display)?;
 
-        let opts_native = get_renderer_opts(true, device_pixel_ratio, Some(options.background));
-        let opts_osmesa = get_renderer_opts(false, device_pixel_ratio, Some(options.background));
+        // both renderers share the cache, only the one that is actually used adds programs to it
+        let cached_programs = match options.shader_cache_path {
+            Some(ref path) => create_disk_program_cache(path, &*gl),
+            None => ProgramCache::new(None),
+        };
+
+        let opts_native = get_renderer_opts(true, device_pixel_ratio, Some(options.background), cached_programs.clone(), options.subpixel_aa);
+        let opts_osmesa = get_renderer_opts(false, device_pixel_ratio, Some(options.background), cached_programs, options.subpixel_aa);
 
         use self::RendererType::*;
         let (mut renderer, sender) = match options.renderer_type {
@@ -688,11 +1660,22 @@ impl<T: Layout> Window<T> {
         solver.suggest_value(window_dim.width_var, window_dim.width() as f64).unwrap();
         solver.suggest_value(window_dim.height_var, window_dim.height() as f64).unwrap();
 
+        // the nodes of a restored DOM cache are added to the solver now, so that
+        // the first layout only has to add the nodes that changed since then
+        let dom_tree_cache = options.dom_cache_path.as_ref().map(|path| load_dom_tree_cache(path)).unwrap_or_else(DomTreeCache::empty);
+        let mut edit_variable_cache = EditVariableCache::empty();
+        edit_variable_cache.apply_diff(&mut solver, &dom_tree_cache.get_restored_nodes());
+
         renderer.set_external_image_handler(Box::new(Compositor::default()));
 
-        let window = Window {
+        let mut window = Window {
+            id: id,
             events_loop: events_loop,
-            state: options.state,
+            state: WindowState {
+                background_color: options.background,
+                touch_state: TouchState { enabled: options.enable_touch, .. Default::default() },
+                .. options.state
+            },
             renderer: Some(renderer),
             display: Rc::new(display),
             css: css,
@@ -702,18 +1685,63 @@ impl<T: Layout> Window<T> {
                 pipeline_id: pipeline_id,
                 document_id: document_id,
                 last_display_list_builder: BuiltDisplayList::default(),
+                embedded_pipelines: FastHashMap::default(),
+                // PipelineId(0, 0) is the main pipeline of the window
+                next_embedded_pipeline_id: 1,
             },
             solver: UiSolver {
                 solver: solver,
                 solved_layout: SolvedLayout::empty(),
-                edit_variable_cache: EditVariableCache::empty(),
-                dom_tree_cache: DomTreeCache::empty(),
-            }
+                edit_variable_cache: edit_variable_cache,
+                dom_tree_cache: dom_tree_cache,
+                #[cfg(debug_assertions)]
+                added_constraints: Vec::new(),
+            },
+            current_monitor: None,
+            current_monitor_dpi: device_pixel_ratio,
+            scroll_states: ScrollStates::default(),
+            is_scrolling: false,
+            resize_drag: None,
+            dom_cache_path: options.dom_cache_path,
+            pre_render: options.pre_render,
+            post_render: options.post_render,
+            subpixel_aa: options.subpixel_aa,
+            initial_focus: options.initial_focus,
+            embedded_layouts: FastHashMap::default(),
         };
 
+        window.update_current_monitor();
+
         Ok(window)
     }
 
+    /// Registers a new webrender pipeline, which can be embedded into the
+    /// DOM of this window via `Dom::iframe(pipeline_id, width, height)`.
+    /// The content of the pipeline is the DOM returned by `layout`, see
+    /// `App::add_embedded_pipeline()`.
+    pub(crate) fn add_embedded_pipeline(&mut self, layout: EmbeddedLayout<T>) -> PipelineId {
+        let pipeline_id = PipelineId(0, self.internal.next_embedded_pipeline_id);
+        self.internal.next_embedded_pipeline_id += 1;
+        self.internal.embedded_pipelines.insert(pipeline_id, None);
+        self.embedded_layouts.insert(pipeline_id, layout);
+        pipeline_id
+    }
+
+    /// Removes an embedded pipeline, `Dom::iframe()` nodes that still refer
+    /// to this pipeline will show up empty
+    pub(crate) fn remove_embedded_pipeline(&mut self, pipeline_id: &PipelineId) {
+        self.internal.embedded_pipelines.remove(pipeline_id);
+        self.embedded_layouts.remove(pipeline_id);
+    }
+
+    /// Sets the content of an embedded pipeline, which will be submitted
+    /// to webrender on the next frame
+    pub(crate) fn set_embedded_display_list(&mut self, pipeline_id: PipelineId, display_list: BuiltDisplayList) {
+        if let Some(entry) = self.internal.embedded_pipelines.get_mut(&pipeline_id) {
+            *entry = Some(display_list);
+        }
+    }
+
     pub fn get_available_monitors() -> MonitorIter {
         MonitorIter {
             inner: EventsLoop::new().get_available_monitors(),
@@ -736,7 +1764,7 @@ impl<T: Layout> Window<T> {
         // Compare the old and new state, field by field
 
         if old_state.title != new_state.title {
-            window.set_title(&new_state.title);
+            window.set_title(&sanitize_window_title(&new_state.title));
             old_state.title = new_state.title;
         }
 
@@ -782,6 +1810,10 @@ impl<T: Layout> Window<T> {
             window.set_max_dimensions(new_state.size.max_dimensions);
             old_state.size.max_dimensions = new_state.size.max_dimensions;
         }
+
+        // the new background color is sent to webrender with the next display list
+        old_state.background_color = new_state.background_color;
+        old_state.text_aa_mode = new_state.text_aa_mode;
     }
 
     pub(crate) fn update_from_external_window_state(&mut self, frame_event_info: &mut FrameEventInfo) {
@@ -797,6 +1829,49 @@ impl<T: Layout> Window<T> {
             self.state.size.hidpi_factor = dpi;
             frame_event_info.should_redraw_window = true;
         }
+
+        if frame_event_info.is_move_event || frame_event_info.new_dpi_factor.is_some() {
+            self.update_current_monitor();
+        }
+    }
+
+    /// Determines the monitor that the window is on. If the window spans multiple
+    /// monitors, the monitor that covers the largest part of the window is used.
+    fn update_current_monitor(&mut self) {
+
+        let gl_window = self.display.gl_window();
+
+        let window_rect = match (gl_window.get_position(), gl_window.get_outer_size()) {
+            (Some((x, y)), Some((width, height))) => Some((x, y, width as i32, height as i32)),
+            _ => None,
+        };
+
+        // area of the intersection between the window and the monitor
+        let get_covered_area = |monitor: &MonitorId| -> i64 {
+            let (window_x, window_y, window_width, window_height) = match window_rect {
+                Some(rect) => rect,
+                None => return 0,
+            };
+            let (monitor_x, monitor_y) = monitor.get_position();
+            let (monitor_width, monitor_height) = monitor.get_dimensions();
+            let overlap_x = (window_x + window_width).min(monitor_x + monitor_width as i32) - window_x.max(monitor_x);
+            let overlap_y = (window_y + window_height).min(monitor_y + monitor_height as i32) - window_y.max(monitor_y);
+            if overlap_x <= 0 || overlap_y <= 0 {
+                0
+            } else {
+                overlap_x as i64 * overlap_y as i64
+            }
+        };
+
+        let monitor = self.events_loop.get_available_monitors()
+            .map(|monitor| (get_covered_area(&monitor), monitor))
+            .filter(|&(area, _)| area > 0)
+            .max_by_key(|&(area, _)| area)
+            .map(|(_, monitor)| monitor)
+            .unwrap_or_else(|| gl_window.get_current_monitor());
+
+        self.current_monitor_dpi = monitor.get_hidpi_factor();
+        self.current_monitor = Some(monitor);
     }
 
     /// Resets the mouse states `scroll_x` and `scroll_y` to 0
@@ -804,6 +1879,285 @@ impl<T: Layout> Window<T> {
         self.state.mouse_state.scroll_x = 0.0;
         self.state.mouse_state.scroll_y = 0.0;
     }
+
+    /// Scrolls the container by the given amount, either instantly (`scroll-behavior: auto`)
+    /// or by starting an animation that is advanced in `advance_scroll_animations()`
+    pub(crate) fn start_scroll(&mut self, node_id: NodeId, scroll_x: f32, scroll_y: f32, behavior: StyleScrollBehavior) {
+        match behavior {
+            StyleScrollBehavior::Auto => {
+                let (offset_x, offset_y) = self.scroll_states.get_offset(node_id);
+                self.scroll_states.set_offset(node_id, (offset_x + scroll_x, offset_y + scroll_y));
+            },
+            StyleScrollBehavior::Smooth => {
+                // A new scroll on the same node replaces the old animation,
+                // the rest of the old scroll distance is added to the new one
+                let (offset_x, offset_y) = self.scroll_states.animations.iter()
+                    .find(|a| a.node_id == node_id)
+                    .map(|a| a.target_offset)
+                    .unwrap_or_else(|| self.scroll_states.get_offset(node_id));
+                self.animate_scroll(node_id, (offset_x + scroll_x, offset_y + scroll_y), SMOOTH_SCROLL_DURATION);
+            }
+        }
+    }
+
+    /// Animates the scroll offset of the container to the `target` offset, see `FakeWindow::animate_scroll()`
+    pub(crate) fn animate_scroll(&mut self, node_id: NodeId, target: (f32, f32), duration: Duration) {
+        self.scroll_states.animate_offset(node_id, target, duration, Instant::now());
+    }
+
+    /// Scrolls the nearest scroll container of the node so that the node is in view,
+    /// see `FakeWindow::scroll_to_node()`. Returns whether a container was scrolled.
+    pub(crate) fn scroll_node_into_view(&mut self, node_id: NodeId, ui_description: &UiDescription<T>) -> bool {
+        let container = {
+            let arena = ui_description.ui_descr_arena.borrow();
+            if node_id.index.get() >= arena.nodes_len() {
+                return false;
+            }
+            node_id.ancestors(&*arena).skip(1).find(|ancestor| !ui_description.get_overflow(*ancestor).is_visible())
+        };
+
+        let container = match container {
+            Some(container) => container,
+            None => return false,
+        };
+
+        let (container_bounds, node_bounds) = match (self.solver.query_bounds_of_rect(container), self.solver.query_bounds_of_rect(node_id)) {
+            (Some(container_bounds), Some(node_bounds)) => (container_bounds, node_bounds),
+            _ => return false,
+        };
+
+        let [top, right, bottom, left] = ui_description.get_scroll_margin(node_id).to_pixels();
+        let target_offset = get_scroll_into_view_offset(
+            &inset_rect(&container_bounds, ui_description.get_scroll_padding(container).to_pixels()),
+            &inset_rect(&node_bounds, [-top, -right, -bottom, -left]));
+
+        match ui_description.get_scroll_behavior(container) {
+            StyleScrollBehavior::Auto => self.scroll_states.set_offset(container, target_offset),
+            StyleScrollBehavior::Smooth => self.animate_scroll(container, target_offset, SMOOTH_SCROLL_DURATION),
+        }
+
+        true
+    }
+
+    /// Scrolls the frontmost scroll container under the mouse cursor by the distance that
+    /// the mouse wheel was scrolled in this frame - always instantly, regardless of the
+    /// `scroll-behavior`, to prevent input lag. Returns whether a container was scrolled.
+    pub(crate) fn scroll_hovered_container(&mut self, ui_description: &UiDescription<T>) -> bool {
+        let (scroll_x, scroll_y) = (self.state.mouse_state.scroll_x, self.state.mouse_state.scroll_y);
+        if scroll_x == 0.0 && scroll_y == 0.0 {
+            return false;
+        }
+
+        let cursor = match self.state.mouse_state.cursor_pos {
+            Some((x, y)) => LayoutPoint::new(x as f32, y as f32),
+            None => return false,
+        };
+
+        // Nodes later in the document are drawn on top
+        let hovered_container = ui_description.styled_nodes.keys().rev().find(|node_id| {
+            !ui_description.get_overflow(**node_id).is_visible() &&
+            self.solver.query_bounds_of_rect(**node_id).map(|bounds| bounds.contains(&cursor)).unwrap_or(false)
+        }).cloned();
+
+        match hovered_container {
+            Some(node_id) => {
+                // Scrolling the wheel up (positive delta) moves the content down
+                self.start_scroll(node_id, -scroll_x, -scroll_y, StyleScrollBehavior::Auto);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Starts loading the lazy images (see `Dom::image_lazy()`) of the `dom` that are within
+    /// their load distance of the visible area of the window. Has to run every frame, because
+    /// the display list isn't rebuilt when the window is scrolled: the bounds of each node are
+    /// moved by the scroll offsets of all of its ancestors.
+    pub(crate) fn start_loading_visible_lazy_images(&self, dom: &Dom<T>) {
+        use dom::NodeType;
+
+        let hidpi_factor = self.state.size.hidpi_factor;
+        let window_rect = LayoutRect::new(LayoutPoint::zero(), LayoutSize::new(
+            self.state.size.width as f32 / hidpi_factor,
+            self.state.size.height as f32 / hidpi_factor));
+
+        let arena = dom.arena.borrow();
+
+        for node_id in arena.linear_iter() {
+            let lazy_image = match arena[node_id].data.node_type {
+                NodeType::LazyImage(ref lazy_image) => lazy_image,
+                _ => continue,
+            };
+
+            if lazy_image.has_finished_loading() {
+                continue;
+            }
+
+            let bounds = match self.solver.query_bounds_of_rect(node_id) {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+
+            let (scroll_x, scroll_y) = node_id.ancestors(&*arena).skip(1)
+                .map(|ancestor| self.scroll_states.get_offset(ancestor))
+                .fold((0.0, 0.0), |(x, y), (offset_x, offset_y)| (x + offset_x, y + offset_y));
+            let scrolled_bounds = bounds.translate(&LayoutVector2D::new(-scroll_x, -scroll_y));
+
+            let load_distance = lazy_image.load_distance();
+            if window_rect.inflate(load_distance, load_distance).intersects(&scrolled_bounds) {
+                lazy_image.start_loading();
+            }
+        }
+    }
+
+    /// If the user has stopped scrolling (there was a scroll event in the last frame, but
+    /// not in this one), snaps all `scroll-snap-type` containers to their nearest snap point,
+    /// using the same animation as `scroll-behavior: smooth`. The snap points are the
+    /// positions of the children, moved by the current scroll offset of the container.
+    pub(crate) fn snap_scroll_containers(&mut self, is_scroll_event: bool, ui_description: &UiDescription<T>) {
+        let scroll_gesture_ended = self.is_scrolling && !is_scroll_event;
+        self.is_scrolling = is_scroll_event;
+        if !scroll_gesture_ended {
+            return;
+        }
+
+        let mut snaps = Vec::new();
+
+        {
+            let arena = ui_description.ui_descr_arena.borrow();
+
+            for node_id in ui_description.styled_nodes.keys() {
+                let (axis, strictness) = match ui_description.get_css_property(*node_id, |property| match *property {
+                    ParsedCssProperty::ScrollSnapType(snap_type) => Some(snap_type),
+                    _ => None,
+                }) {
+                    Some(StyleScrollSnapType::Snap(axis, strictness)) => (axis, strictness),
+                    _ => continue,
+                };
+
+                let container = match self.solver.query_bounds_of_rect(*node_id) {
+                    Some(r) => inset_rect(&r, ui_description.get_scroll_padding(*node_id).to_pixels()),
+                    None => continue,
+                };
+
+                let (offset_x, offset_y) = self.scroll_states.get_offset(*node_id);
+                let scroll_offset = LayoutVector2D::new(-offset_x, -offset_y);
+
+                let children = node_id.children(&*arena).filter_map(|child| {
+                    let align = ui_description.get_css_property(child, |property| match *property {
+                        ParsedCssProperty::ScrollSnapAlign(align) => Some(align),
+                        _ => None,
+                    })?;
+                    let [top, right, bottom, left] = ui_description.get_scroll_margin(child).to_pixels();
+                    let scrolled_bounds = self.solver.query_bounds_of_rect(child)?.translate(&scroll_offset);
+                    let bounds = inset_rect(&scrolled_bounds, [-top, -right, -bottom, -left]);
+                    Some((bounds, align))
+                }).collect::<Vec<_>>();
+
+                if let Some((delta_x, delta_y)) = get_scroll_snap_delta(&container, &children, axis, strictness) {
+                    snaps.push((*node_id, delta_x, delta_y));
+                }
+            }
+        }
+
+        for (node_id, delta_x, delta_y) in snaps {
+            self.start_scroll(node_id, delta_x, delta_y, StyleScrollBehavior::Smooth);
+        }
+    }
+
+    /// Replaces the CSS of the window, see `FakeWindow::switch_css()`. The old constraints
+    /// can't be reused, so the window has to be redrawn from scratch.
+    pub(crate) fn switch_css(&mut self, new_css: Css) {
+        self.css.replace_rules(new_css);
+        self.solver.reset();
+    }
+
+    /// The anti-aliasing mode that the text is drawn with, see `FakeWindow::set_text_aa_mode()`
+    pub(crate) fn get_text_aa_mode(&self) -> AaMode {
+        match self.state.text_aa_mode {
+            AaMode::SubPixel if !self.subpixel_aa => AaMode::Grayscale,
+            mode => mode,
+        }
+    }
+
+    /// Position of the mouse cursor relative to the top left corner of the screen
+    pub(crate) fn get_screen_cursor_position(&self) -> Option<(f64, f64)> {
+        let (cursor_x, cursor_y) = self.state.mouse_state.cursor_pos?;
+        let (window_x, window_y) = self.display.gl_window().get_inner_position()?;
+        Some((window_x as f64 + cursor_x, window_y as f64 + cursor_y))
+    }
+
+    /// Starts, updates or ends the drag of the resize handle of a `resize` container.
+    ///
+    /// Pressing the left mouse button on the handle of a container starts the drag (the
+    /// frontmost container wins), moving the mouse suggests the new width / height to the
+    /// solver, releasing the button ends the drag. Returns whether the window has to be redrawn.
+    pub(crate) fn update_resize_drag(&mut self, event: &glutin::Event, ui_description: &UiDescription<T>) -> bool {
+        use glium::glutin::{Event, WindowEvent, ElementState, MouseButton};
+        use display_list::RESIZE_HANDLE_SIZE;
+
+        let cursor_pos = match self.state.mouse_state.cursor_pos {
+            Some((x, y)) => (x as f32, y as f32),
+            None => return false,
+        };
+
+        let window_event = match event {
+            Event::WindowEvent { event, .. } => event,
+            _ => return false,
+        };
+
+        match window_event {
+            WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } => {
+                // Nodes later in the document are drawn on top
+                let resize_drag = ui_description.styled_nodes.keys().rev().filter_map(|node_id| {
+                    let resize = ui_description.get_resize(*node_id);
+                    if !resize.is_resizable() {
+                        return None;
+                    }
+                    let bounds = self.solver.query_bounds_of_rect(*node_id)?;
+                    let handle_origin = (
+                        bounds.origin.x + bounds.size.width - RESIZE_HANDLE_SIZE,
+                        bounds.origin.y + bounds.size.height - RESIZE_HANDLE_SIZE);
+                    let is_on_handle =
+                        cursor_pos.0 >= handle_origin.0 && cursor_pos.0 <= handle_origin.0 + RESIZE_HANDLE_SIZE &&
+                        cursor_pos.1 >= handle_origin.1 && cursor_pos.1 <= handle_origin.1 + RESIZE_HANDLE_SIZE;
+                    if !is_on_handle {
+                        return None;
+                    }
+                    Some(ResizeDrag {
+                        node_id: *node_id,
+                        resize: resize,
+                        initial_size: (bounds.size.width, bounds.size.height),
+                        initial_cursor_pos: cursor_pos,
+                    })
+                }).next();
+
+                self.resize_drag = resize_drag;
+                false
+            },
+            WindowEvent::MouseInput { state: ElementState::Released, button: MouseButton::Left, .. } => {
+                self.resize_drag = None;
+                false
+            },
+            WindowEvent::CursorMoved { .. } => {
+                let drag = match self.resize_drag {
+                    Some(drag) => drag,
+                    None => return false,
+                };
+                let drag_delta = (cursor_pos.0 - drag.initial_cursor_pos.0, cursor_pos.1 - drag.initial_cursor_pos.1);
+                let interaction = ResizeInteraction::from_drag(drag.resize, drag.initial_size, drag_delta);
+                self.solver.apply_resize_interaction(drag.node_id, &interaction);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Advances all running smooth scroll animations to the current time, removes
+    /// finished animations. Returns whether a scroll offset has changed.
+    pub(crate) fn advance_scroll_animations(&mut self) -> bool {
+        self.scroll_states.advance_animations(Instant::now())
+    }
 }
 
 pub(crate) fn get_gl_context(display: &Display) -> Result<Rc<Gl>, WindowCreateError> {
@@ -818,11 +2172,36 @@ pub(crate) fn get_gl_context(display: &Display) -> Result<Rc<Gl>, WindowCreateEr
     }
 }
 
+/// Restores the `DomTreeCache` from the file at `path`, or returns an empty cache
+/// if the file doesn't exist or is invalid
+fn load_dom_tree_cache(path: &Path) -> DomTreeCache {
+    use std::fs;
+    match fs::read(path) {
+        Ok(data) => DomTreeCache::deserialize(&data).unwrap_or_else(|e| {
+            info!("discarding the DOM cache at {:?}: {}", path, e);
+            DomTreeCache::empty()
+        }),
+        Err(e) => {
+            // The cache file doesn't exist on the first startup
+            if e.kind() != ::std::io::ErrorKind::NotFound {
+                info!("could not read the DOM cache at {:?}: {}", path, e);
+            }
+            DomTreeCache::empty()
+        }
+    }
+}
+
 impl<T: Layout> Drop for Window<T> {
     fn drop(&mut self) {
         // self.background_thread.take().unwrap().join();
         let renderer = self.renderer.take().unwrap();
         renderer.deinit();
+
+        if let Some(ref path) = self.dom_cache_path {
+            if let Err(e) = ::std::fs::write(path, self.solver.dom_tree_cache.serialize()) {
+                warn!("could not write the DOM cache to {:?}: {}", path, e);
+            }
+        }
     }
 }
 
@@ -832,4 +2211,131 @@ impl<T: Layout> Drop for Window<T> {
 #[test]
 fn __codecov_test_window_file() {
 
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_window_create_error_display() {
+    use std::error::Error;
+    let error = WindowCreateError::from(IoError::new(::std::io::ErrorKind::NotFound, "no display"));
+    assert_eq!(format!("{}", error), "IO error: no display");
+    assert!(error.source().is_some());
+    assert!(WindowCreateError::WebGlNotSupported.source().is_none());
+}
+
+#[test]
+fn test_ease_in_out_quad() {
+    assert_eq!(ease_in_out_quad(0.0), 0.0);
+    assert_eq!(ease_in_out_quad(0.5), 0.5);
+    assert_eq!(ease_in_out_quad(1.0), 1.0);
+    assert!(ease_in_out_quad(0.25) < 0.25);
+    assert!(ease_in_out_quad(0.75) > 0.75);
+}
+
+#[test]
+fn test_get_animation_time() {
+    assert_eq!(get_animation_time(Duration::from_millis(250), Duration::from_secs(1)), 0.25);
+    assert_eq!(get_animation_time(Duration::from_secs(2), Duration::from_secs(1)), 1.0);
+    assert_eq!(get_animation_time(Duration::from_millis(0), Duration::from_secs(0)), 1.0);
+}
+
+#[test]
+fn test_get_scroll_into_view_offset() {
+    let rect = |x: f32, y: f32| LayoutRect::new(LayoutPoint::new(x, y), LayoutSize::new(100.0, 100.0));
+    let container = rect(0.0, 50.0);
+
+    assert_eq!(get_scroll_into_view_offset(&container, &rect(0.0, 450.0)), (0.0, 400.0));
+
+    // 16px scroll-margin on the node, 8px scroll-padding on the container
+    let container = inset_rect(&container, [8.0; 4]);
+    let node = inset_rect(&rect(0.0, 450.0), [-16.0; 4]);
+    assert_eq!(get_scroll_into_view_offset(&container, &node), (-24.0, 376.0));
+}
+
+#[test]
+fn test_scroll_states() {
+    let container = NodeId::new(1);
+    let other_container = NodeId::new(2);
+    let mut scroll_states = ScrollStates::default();
+
+    // each container has its own offset, which can't be negative
+    scroll_states.set_offset(container, (0.0, 100.0));
+    scroll_states.set_offset(other_container, (-10.0, 50.0));
+    assert_eq!(scroll_states.get_offset(container), (0.0, 100.0));
+    assert_eq!(scroll_states.get_offset(other_container), (0.0, 50.0));
+    assert_eq!(scroll_states.get_offset(NodeId::new(3)), (0.0, 0.0));
+
+    // animations interpolate from the current offset to the target
+    let start = Instant::now();
+    scroll_states.animate_offset(container, (0.0, 300.0), Duration::from_secs(1), start);
+    assert!(scroll_states.advance_animations(start + Duration::from_millis(500)));
+    assert_eq!(scroll_states.get_offset(container), (0.0, 200.0));
+    assert_eq!(scroll_states.get_offset(other_container), (0.0, 50.0));
+
+    assert!(scroll_states.advance_animations(start + Duration::from_secs(2)));
+    assert_eq!(scroll_states.get_offset(container), (0.0, 300.0));
+    assert!(scroll_states.animations.is_empty());
+    assert!(!scroll_states.advance_animations(start + Duration::from_secs(3)));
+}
+
+/// Removes the null bytes from the window title, since winit panics on X11 (it converts
+/// the title to a `CString`) and the title is cut off at the first null byte on Windows.
+/// Other Unicode (emoji, right-to-left scripts, etc.) is passed through, winit converts
+/// the UTF-8 to UTF-16 on Windows and to an `NSString` on macOS.
+fn sanitize_window_title(title: &str) -> String {
+    title.chars().filter(|c| *c != '\0').collect()
+}
+
+#[test]
+fn test_window_create_options_clone() {
+    fn assert_clone<T: Clone>() { }
+    assert_clone::<WindowCreateOptions>();
+    assert_clone::<WindowMonitorTarget>();
+
+    let mut template = WindowCreateOptions::default();
+    template.state.title = String::from("Template");
+    template.enable_wr_capture = true;
+
+    let options = template.clone();
+    assert_eq!(options.state.title, "Template");
+    assert!(options.enable_wr_capture);
+}
+
+#[test]
+fn test_get_scroll_snap_delta() {
+    let container = LayoutRect::new(LayoutPoint::new(0.0, 0.0), LayoutSize::new(100.0, 100.0));
+    let children = [
+        (LayoutRect::new(LayoutPoint::new(-80.0, 0.0), LayoutSize::new(100.0, 100.0)), StyleScrollSnapAlign::Start),
+        (LayoutRect::new(LayoutPoint::new(20.0, 0.0), LayoutSize::new(100.0, 100.0)), StyleScrollSnapAlign::Start),
+        (LayoutRect::new(LayoutPoint::new(5.0, 0.0), LayoutSize::new(100.0, 100.0)), StyleScrollSnapAlign::None),
+    ];
+
+    assert_eq!(get_scroll_snap_delta(&container, &children, ScrollSnapAxis::X, ScrollSnapStrictness::Mandatory), Some((20.0, 0.0)));
+    assert_eq!(get_scroll_snap_delta(&container, &children, ScrollSnapAxis::Y, ScrollSnapStrictness::Mandatory), None);
+    assert_eq!(get_scroll_snap_delta(&container, &children[..1], ScrollSnapAxis::X, ScrollSnapStrictness::Proximity), None);
+}
+
+#[test]
+fn test_flip_rows() {
+    assert_eq!(flip_rows(vec![1, 2, 3, 4, 5, 6], 2), vec![5, 6, 3, 4, 1, 2]);
+    assert_eq!(flip_rows(Vec::new(), 4), Vec::<u8>::new());
+}
+
+#[test]
+fn test_inset_rect() {
+    let rect = LayoutRect::new(LayoutPoint::new(0.0, 0.0), LayoutSize::new(100.0, 100.0));
+    let sticky_header_padding = inset_rect(&rect, [20.0, 0.0, 0.0, 0.0]);
+    assert_eq!(sticky_header_padding, LayoutRect::new(LayoutPoint::new(0.0, 20.0), LayoutSize::new(100.0, 80.0)));
+    let margin = inset_rect(&rect, [-10.0, -10.0, -10.0, -10.0]);
+    assert_eq!(margin, LayoutRect::new(LayoutPoint::new(-10.0, -10.0), LayoutSize::new(120.0, 120.0)));
+}
+
+#[test]
+fn test_sanitize_window_title() {
+    for title in &["\u{1F980} Azul App", "\u{0645}\u{0631}\u{062D}\u{0628}\u{0627}", "\u{4F60}\u{597D}, \u{3053}\u{3093}\u{306B}\u{3061}\u{306F}"] {
+        assert_eq!(sanitize_window_title(title), *title);
+        // the title survives the conversion to UTF-16 (for Windows)
+        assert_eq!(String::from_utf16(&title.encode_utf16().collect::<Vec<u16>>()).unwrap(), *title);
+    }
+    assert_eq!("\u{1F980} Azul App".encode_utf16().count(), 11);
+    assert_eq!(sanitize_window_title("Azul\0 App\0"), "Azul App");
+}
@@ -3,11 +3,14 @@
 
 use glium::glutin::{
     Window, Event, WindowEvent, KeyboardInput, ElementState,
-    MouseCursor, VirtualKeyCode, MouseButton, MouseScrollDelta, TouchPhase,
+    MouseCursor, VirtualKeyCode, MouseButton, MouseScrollDelta, TouchPhase, Touch,
 };
+use std::collections::BTreeMap;
+use webrender::api::ColorF;
 use {
     dom::On,
     menu::{ApplicationMenu, ContextMenu},
+    id_tree::NodeId,
 };
 
 const DEFAULT_TITLE: &str = "Azul App";
@@ -24,6 +27,10 @@ pub struct KeyboardState
     pub hidden_keys: Vec<char>,
     /// Actual keys pressed during this frame (i.e. regular text input)
     pub keys: Vec<char>,
+    /// The key that has invoked an `On::VirtualKeyDown` / `On::VirtualKeyUp` callback
+    pub current_virtual_keycode: Option<VirtualKeyCode>,
+    /// The character that has invoked an `On::TextInput` callback
+    pub current_char: Option<char>,
 }
 
 /// Mouse position on the screen
@@ -44,6 +51,12 @@ pub struct MouseState
     pub scroll_x: f32,
     /// Scroll amount in pixels in the vertical direction. Gets reset to 0 after every frame
     pub scroll_y: f32,
+    /// The node that a mouse button (or the space bar) was pressed on, matched by the
+    /// `:active` pseudo-class. Set on mouse-down and cleared on mouse-up.
+    pub active_node: Option<NodeId>,
+    /// Where the left mouse button was last pressed (in window coordinates). Kept until
+    /// the next press, so that `On::LeftMouseUp` callbacks can tell clicks from drags.
+    pub left_down_position: Option<(f64, f64)>,
 }
 
 impl Default for MouseState {
@@ -57,6 +70,8 @@ impl Default for MouseState {
             middle_down: false,
             scroll_x: 0.0,
             scroll_y: 0.0,
+            active_node: None,
+            left_down_position: None,
         }
     }
 }
@@ -93,6 +108,189 @@ pub struct WindowState
     pub is_transparent: bool,
     /// Is the window always on top?
     pub is_always_on_top: bool,
+    /// Background color of the window (drawn behind all DOM nodes)
+    pub background_color: ColorF,
+    /// Anti-aliasing of the text, see `FakeWindow::set_text_aa_mode()`
+    pub text_aa_mode: AaMode,
+    /// Fingers on the touch screen / touchpad, for recognizing gestures
+    pub(crate) touch_state: TouchState,
+}
+
+/// Two-finger pinch (zoom) gesture, see `On::Pinch`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PinchGesture {
+    /// Ratio of the current to the previous distance between the two fingers
+    /// (`> 1.0` if the fingers move apart, i.e. zoom in)
+    pub scale: f32,
+    /// Point in the middle of the two fingers, relative to the top left of the window
+    pub center: (f32, f32),
+}
+
+/// Two-finger rotation gesture, see `On::Rotate`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RotateGesture {
+    /// Angle (in radians, clockwise) that the line between the two fingers was
+    /// rotated by since the last event
+    pub angle_delta_rad: f32,
+    /// Point in the middle of the two fingers, relative to the top left of the window
+    pub center: (f32, f32),
+}
+
+/// ID of a finger on the touch screen, assigned by the OS when the finger touches
+/// the screen. The same ID can be reused for a later finger.
+pub type TouchId = u64;
+
+/// Fingers that are currently touching the screen, ordered by their ID
+pub type MultiTouchState = BTreeMap<TouchId, TouchPoint>;
+
+/// Finger on the touch screen, see `WindowEvent::touch`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TouchPoint {
+    /// Position of the finger, relative to the top left of the window
+    pub position: (f32, f32),
+    /// Pressure of the finger - always `1.0`, winit doesn't report the pressure yet
+    pub pressure: f32,
+    /// Whether the finger has just touched the screen, has moved or has been lifted
+    pub phase: TouchPhase,
+}
+
+/// Positions of the fingers that are currently touching the screen / touchpad
+#[derive(Debug, Default, Clone)]
+pub(crate) struct TouchState {
+    /// Copied from `WindowCreateOptions::enable_touch`
+    pub(crate) enabled: bool,
+    /// Fingers that are currently down
+    pub(crate) touches: MultiTouchState,
+    /// The first finger that touched the screen (while no other finger was down),
+    /// which is simulated as the left mouse button until it is lifted
+    pub(crate) primary_touch: Option<TouchId>,
+    /// Finger that has produced the current event, if any
+    pub(crate) current_touch: Option<(TouchId, TouchPoint)>,
+    /// Pinch gesture of the current event, if any
+    pub(crate) pinch_gesture: Option<PinchGesture>,
+    /// Rotation gesture of the current event, if any
+    pub(crate) rotate_gesture: Option<RotateGesture>,
+}
+
+impl TouchState {
+
+    /// Updates the fingers and the pinch / rotation gestures (if two fingers are moved),
+    /// returns whether the finger is the primary touch
+    fn update(&mut self, touch: &Touch) -> bool {
+        let previous = self.get_two_fingers();
+        let is_primary = self.update_touch(touch.id, touch.phase, touch.location);
+        let (pinch_gesture, rotate_gesture) = match (touch.phase, previous, self.get_two_fingers()) {
+            (TouchPhase::Moved, Some(previous), Some(current)) => (get_pinch_gesture(previous, current), get_rotate_gesture(previous, current)),
+            _ => (None, None),
+        };
+        self.pinch_gesture = pinch_gesture;
+        self.rotate_gesture = rotate_gesture;
+        is_primary
+    }
+
+    /// Updates the position of the finger and the primary touch, returns whether
+    /// the finger is the primary touch
+    fn update_touch(&mut self, id: TouchId, phase: TouchPhase, location: (f64, f64)) -> bool {
+        let touch_point = TouchPoint {
+            position: (location.0 as f32, location.1 as f32),
+            pressure: 1.0,
+            phase: phase,
+        };
+
+        match phase {
+            TouchPhase::Started | TouchPhase::Moved => { self.touches.insert(id, touch_point); },
+            TouchPhase::Ended | TouchPhase::Cancelled => { self.touches.remove(&id); },
+        }
+
+        if phase == TouchPhase::Started && self.primary_touch.is_none() {
+            self.primary_touch = Some(id);
+        }
+        let is_primary = self.primary_touch == Some(id);
+        if is_primary && (phase == TouchPhase::Ended || phase == TouchPhase::Cancelled) {
+            // Lifting the primary finger doesn't make the other fingers primary
+            self.primary_touch = None;
+        }
+
+        self.current_touch = Some((id, touch_point));
+        is_primary
+    }
+
+    /// Resets the current touch and the gestures, which are only valid for the event
+    /// that produced them
+    fn clear_gestures(&mut self) {
+        self.current_touch = None;
+        self.pinch_gesture = None;
+        self.rotate_gesture = None;
+    }
+
+    /// Positions of the fingers (ordered by touch ID), if exactly two fingers are down
+    fn get_two_fingers(&self) -> Option<[(f64, f64); 2]> {
+        if self.touches.len() != 2 {
+            return None;
+        }
+        let mut positions = self.touches.values().map(|touch| (touch.position.0 as f64, touch.position.1 as f64));
+        Some([positions.next()?, positions.next()?])
+    }
+}
+
+/// Returns the pinch gesture between the previous and the current positions of two
+/// fingers, or `None` if the two fingers were previously at the same position
+fn get_pinch_gesture(previous: [(f64, f64); 2], current: [(f64, f64); 2]) -> Option<PinchGesture> {
+    let distance = |p: [(f64, f64); 2]| ((p[1].0 - p[0].0).powi(2) + (p[1].1 - p[0].1).powi(2)).sqrt();
+    let previous_distance = distance(previous);
+    if previous_distance == 0.0 {
+        return None;
+    }
+    Some(PinchGesture {
+        scale: (distance(current) / previous_distance) as f32,
+        center: get_center(current),
+    })
+}
+
+/// Returns the rotation gesture between the previous and the current positions of two
+/// fingers, or `None` if the fingers haven't rotated (or were at the same position)
+fn get_rotate_gesture(previous: [(f64, f64); 2], current: [(f64, f64); 2]) -> Option<RotateGesture> {
+    use std::f64::consts::PI;
+    let angle = |p: [(f64, f64); 2]| (p[1].1 - p[0].1).atan2(p[1].0 - p[0].0);
+    if previous[0] == previous[1] || current[0] == current[1] {
+        return None;
+    }
+    // normalize to -PI..PI, so that crossing the negative x axis isn't a full turn
+    let mut angle_delta = angle(current) - angle(previous);
+    if angle_delta > PI {
+        angle_delta -= 2.0 * PI;
+    } else if angle_delta < -PI {
+        angle_delta += 2.0 * PI;
+    }
+    if angle_delta == 0.0 {
+        return None;
+    }
+    Some(RotateGesture {
+        angle_delta_rad: angle_delta as f32,
+        center: get_center(current),
+    })
+}
+
+fn get_center(fingers: [(f64, f64); 2]) -> (f32, f32) {
+    (((fingers[0].0 + fingers[1].0) / 2.0) as f32, ((fingers[0].1 + fingers[1].1) / 2.0) as f32)
+}
+
+/// Anti-aliasing mode of the text in a window
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AaMode {
+    /// Sub-pixel anti-aliasing (sharpest, but assumes a light background -
+    /// causes colored fringes around light text on dark backgrounds)
+    SubPixel,
+    /// Grayscale anti-aliasing
+    Grayscale,
+    /// No anti-aliasing
+    None,
+}
+
+impl Default for AaMode {
+    fn default() -> Self {
+        AaMode::SubPixel
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -147,6 +345,9 @@ impl Default for WindowState {
             is_visible: true,
             is_transparent: false,
             is_always_on_top: false,
+            background_color: ColorF::new(1.0, 1.0, 1.0, 1.0),
+            text_aa_mode: AaMode::default(),
+            touch_state: TouchState::default(),
         }
     }
 }
@@ -172,6 +373,10 @@ impl WindowState
 
         let mut events_vec = Vec::<On>::new();
 
+        self.touch_state.clear_gestures();
+        self.keyboard_state.current_virtual_keycode = None;
+        self.keyboard_state.current_char = None;
+
         // TODO: right mouse down / middle mouse down?
         match event {
             MouseInput { state: ElementState::Pressed, button, .. } => {
@@ -180,6 +385,8 @@ impl WindowState
                         if !self.mouse_state.left_down {
                             events_vec.push(On::MouseDown);
                             events_vec.push(On::LeftMouseDown);
+                            events_vec.push(On::DragStart);
+                            self.mouse_state.left_down_position = self.mouse_state.cursor_pos;
                         }
                         self.mouse_state.left_down = true;
                     },
@@ -235,6 +442,66 @@ impl WindowState
                 self.mouse_state.scroll_y = scroll_y_px;
                 events_vec.push(On::Scroll);
             },
+            // Keyboard events go to the focused node, see `FakeWindow::set_focus()`
+            ::glium::glutin::WindowEvent::KeyboardInput { input: KeyboardInput { state, virtual_keycode: Some(key), .. }, .. } => {
+                self.keyboard_state.current_virtual_keycode = Some(*key);
+                let is_modifier = is_modifier_key(*key);
+                match state {
+                    ElementState::Pressed => {
+                        if is_modifier && !self.keyboard_state.modifiers.contains(key) {
+                            self.keyboard_state.modifiers.push(*key);
+                        }
+                        events_vec.push(On::VirtualKeyDown);
+                    },
+                    ElementState::Released => {
+                        self.keyboard_state.modifiers.retain(|modifier| modifier != key);
+                        events_vec.push(On::VirtualKeyUp);
+                    },
+                }
+            },
+            ReceivedCharacter(c) => {
+                self.keyboard_state.current_char = Some(*c);
+                events_vec.push(On::TextInput);
+            },
+            Touch(touch) if self.touch_state.enabled => {
+                let is_primary = self.touch_state.update(touch);
+                events_vec.push(match touch.phase {
+                    TouchPhase::Started => On::TouchStart,
+                    TouchPhase::Moved => On::TouchMove,
+                    TouchPhase::Ended | TouchPhase::Cancelled => On::TouchEnd,
+                });
+                // The primary touch is simulated as the left mouse button. Some platforms
+                // (Windows) already send mouse events for touches, so the mouse events
+                // are only sent if the button state changes.
+                // TODO: drag-and-drop via touch
+                if is_primary {
+                    self.mouse_state.cursor_pos = Some(touch.location);
+                    match touch.phase {
+                        TouchPhase::Started => {
+                            if !self.mouse_state.left_down {
+                                events_vec.push(On::MouseDown);
+                                events_vec.push(On::LeftMouseDown);
+                                self.mouse_state.left_down_position = self.mouse_state.cursor_pos;
+                            }
+                            self.mouse_state.left_down = true;
+                        },
+                        TouchPhase::Ended | TouchPhase::Cancelled => {
+                            if self.mouse_state.left_down {
+                                events_vec.push(On::MouseUp);
+                                events_vec.push(On::LeftMouseUp);
+                            }
+                            self.mouse_state.left_down = false;
+                        },
+                        TouchPhase::Moved => { },
+                    }
+                }
+                if self.touch_state.pinch_gesture.is_some() {
+                    events_vec.push(On::Pinch);
+                }
+                if self.touch_state.rotate_gesture.is_some() {
+                    events_vec.push(On::Rotate);
+                }
+            },
             _ => { }
         }
 
@@ -272,6 +539,22 @@ fn update_mouse_cursor(window: &Window, old: &MouseCursor, new: &MouseCursor) {
     }
 }
 
+/// Shift, Control, Alt and the Windows / Command key
+fn is_modifier_key(key: VirtualKeyCode) -> bool {
+    use glium::glutin::VirtualKeyCode::*;
+    match key {
+        LShift | RShift | LControl | RControl | LAlt | RAlt | LWin | RWin => true,
+        _ => false,
+    }
+}
+
+impl KeyboardState {
+    /// Whether one of the shift keys is currently pressed
+    pub fn is_shift_down(&self) -> bool {
+        self.modifiers.iter().any(|key| *key == VirtualKeyCode::LShift || *key == VirtualKeyCode::RShift)
+    }
+}
+
 fn virtual_key_code_to_char(code: VirtualKeyCode) -> Option<char> {
     use glium::glutin::VirtualKeyCode::*;
     match code {
@@ -335,4 +618,49 @@ fn virtual_key_code_to_char(code: VirtualKeyCode) -> Option<char> {
 #[test]
 fn __codecov_test_window_state_file() {
 
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_update_touch() {
+    let mut touch_state = TouchState::default();
+
+    assert!(touch_state.update_touch(3, TouchPhase::Started, (10.0, 10.0)));
+    assert!(!touch_state.update_touch(5, TouchPhase::Started, (50.0, 10.0)));
+    assert!(!touch_state.update_touch(5, TouchPhase::Moved, (60.0, 20.0)));
+    assert_eq!(touch_state.touches.len(), 2);
+    assert_eq!(touch_state.touches[&5].position, (60.0, 20.0));
+
+    // the second finger doesn't become primary when the first finger is lifted
+    assert!(touch_state.update_touch(3, TouchPhase::Ended, (10.0, 10.0)));
+    assert_eq!(touch_state.primary_touch, None);
+    assert!(!touch_state.update_touch(5, TouchPhase::Moved, (70.0, 20.0)));
+    assert_eq!(touch_state.touches.keys().collect::<Vec<_>>(), vec![&5]);
+}
+
+#[test]
+fn test_get_pinch_gesture() {
+    // fingers move apart from 100px to 200px distance
+    let gesture = get_pinch_gesture([(100.0, 100.0), (200.0, 100.0)], [(50.0, 100.0), (250.0, 100.0)]);
+    assert_eq!(gesture, Some(PinchGesture { scale: 2.0, center: (150.0, 100.0) }));
+
+    let gesture = get_pinch_gesture([(0.0, 0.0), (0.0, 100.0)], [(0.0, 25.0), (0.0, 75.0)]);
+    assert_eq!(gesture, Some(PinchGesture { scale: 0.5, center: (0.0, 50.0) }));
+
+    assert_eq!(get_pinch_gesture([(10.0, 10.0), (10.0, 10.0)], [(0.0, 0.0), (20.0, 20.0)]), None);
+}
+
+#[test]
+fn test_get_rotate_gesture() {
+    use std::f32::consts::PI;
+
+    // clockwise quarter turn around (100, 100)
+    let gesture = get_rotate_gesture([(50.0, 100.0), (150.0, 100.0)], [(100.0, 50.0), (100.0, 150.0)]).unwrap();
+    assert!((gesture.angle_delta_rad - PI / 2.0).abs() < 0.0001);
+    assert_eq!(gesture.center, (100.0, 100.0));
+
+    // crossing the negative x axis is a small rotation, not a full turn
+    let gesture = get_rotate_gesture([(1.0, 0.0), (0.0, 0.1)], [(1.0, 0.0), (0.0, -0.1)]).unwrap();
+    assert!(gesture.angle_delta_rad.abs() < 0.3);
+
+    assert_eq!(get_rotate_gesture([(0.0, 0.0), (100.0, 0.0)], [(0.0, 0.0), (200.0, 0.0)]), None);
+}